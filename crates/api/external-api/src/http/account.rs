@@ -3,13 +3,15 @@
 use alloy::primitives::Address;
 use circuit_types::schnorr::SchnorrPublicKey;
 use constants::Scalar;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use types_core::HmacKey;
 use uuid::Uuid;
 
 use crate::{
     serde_helpers,
-    types::{ApiAccount, ApiPoseidonCSPRNG},
+    types::{ApiAccount, ApiBalance, ApiOrder, ApiPoseidonCSPRNG, ApiTask},
 };
 
 // ---------------
@@ -20,10 +22,22 @@ use crate::{
 pub const CREATE_ACCOUNT_ROUTE: &str = "/v2/account";
 /// Route to get an account by ID
 pub const GET_ACCOUNT_BY_ID_ROUTE: &str = "/v2/account/:account_id";
+/// Route to get a summary of an account's activity
+pub const GET_ACCOUNT_SUMMARY_ROUTE: &str = "/v2/account/:account_id/summary";
 /// Route to get account seeds
 pub const GET_ACCOUNT_SEEDS_ROUTE: &str = "/v2/account/:account_id/seeds";
 /// Route to sync an account
 pub const SYNC_ACCOUNT_ROUTE: &str = "/v2/account/:account_id/sync";
+/// Route to get or set the peers an account has pinned
+pub const ACCOUNT_PINNED_PEERS_ROUTE: &str = "/v2/account/:account_id/pinned-peers";
+/// Route to list or create an account's API keys
+pub const ACCOUNT_API_KEYS_ROUTE: &str = "/v2/account/:account_id/api-keys";
+/// Route to rotate an account's API key
+pub const ROTATE_ACCOUNT_API_KEY_ROUTE: &str = "/v2/account/:account_id/api-keys/:key_id/rotate";
+/// Route to revoke an account's API key
+pub const REVOKE_ACCOUNT_API_KEY_ROUTE: &str = "/v2/account/:account_id/api-keys/:key_id/revoke";
+/// Route to list the accounts of the requesting tenant
+pub const GET_TENANT_ACCOUNTS_ROUTE: &str = "/v2/tenant/accounts";
 
 // --------------------
 // | Request/Response |
@@ -31,32 +45,85 @@ pub const SYNC_ACCOUNT_ROUTE: &str = "/v2/account/:account_id/sync";
 
 /// Response for getting an account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetAccountResponse {
     /// The account
     pub account: ApiAccount,
+    /// The account's version, to be sent on mutating requests in the
+    /// `x-renegade-account-version` header
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Response for getting a summary of an account's activity
+///
+/// Gathers the account's orders, balances and pending tasks into a single
+/// response, along with the health of the node serving it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetAccountSummaryResponse {
+    /// The account's open orders
+    pub orders: Vec<ApiOrder>,
+    /// The account's darkpool balances, with their estimated USD values
+    pub balances: Vec<ApiBalanceValue>,
+    /// The estimated USD value of the account's priced balances
+    pub total_usd_value: f64,
+    /// The account's queued and running tasks
+    pub pending_tasks: Vec<ApiTask>,
+    /// The time in milliseconds of the account's last match, if it has matched
+    pub last_match_timestamp: Option<u64>,
+    /// The replication health of the node serving the account
+    pub replication: ApiReplicationHealth,
+}
+
+/// A balance along with its estimated USD value
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiBalanceValue {
+    /// The balance
+    pub balance: ApiBalance,
+    /// The estimated USD value of the balance, if its token is priced
+    pub usd_value: Option<f64>,
+}
+
+/// The replication health of a relayer node
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiReplicationHealth {
+    /// Whether the node knows a leader and is a member of the cluster
+    pub ready: bool,
+    /// The number of log entries the node has not yet applied to its state
+    pub apply_lag: u64,
+    /// The number of nodes in the cluster
+    pub cluster_size: usize,
 }
 
 /// Request to create a new account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CreateAccountRequest {
     /// The account identifier
     pub account_id: Uuid,
     /// The Ethereum address associated with the account
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub address: Address,
     /// The master view seed for deriving keys
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub master_view_seed: Scalar,
     /// The HMAC key for authenticating requests
     #[serde(with = "serde_helpers::hmac_key_as_base64_string")]
     pub auth_hmac_key: HmacKey,
     /// The schnorr public key used for in-circuit verification
     #[serde(with = "serde_helpers::schnorr_public_key_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub schnorr_public_key: SchnorrPublicKey,
 }
 
 /// Response for get account seeds
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetAccountSeedsResponse {
     /// The recovery seed CSPRNG state
     pub recovery_seed_csprng: ApiPoseidonCSPRNG,
@@ -66,17 +133,20 @@ pub struct GetAccountSeedsResponse {
 
 /// Request to sync an account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct SyncAccountRequest {
     /// The account identifier
     pub account_id: Uuid,
     /// The master view seed for deriving keys
     #[serde(with = "serde_helpers::scalar_as_hex_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub master_view_seed: Scalar,
     /// The HMAC key for authenticating requests
     #[serde(with = "serde_helpers::hmac_key_as_base64_string")]
     pub auth_hmac_key: HmacKey,
     /// The schnorr public key used for in-circuit verification
     #[serde(with = "serde_helpers::schnorr_public_key_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub schnorr_public_key: SchnorrPublicKey,
     /// Tokens whose Ring 0 backing balances should be re-fetched from
     /// chain in addition to those that appear in the wallet's active
@@ -86,14 +156,63 @@ pub struct SyncAccountRequest {
     /// since `refresh_state` only walks tokens referenced by current
     /// intents). Defaults to empty for backward compatibility.
     #[serde(default)]
+    #[cfg_attr(feature = "openapi", schemars(with = "Vec<String>"))]
     pub additional_tokens: Vec<Address>,
 }
 
 /// Response from syncing an account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct SyncAccountResponse {
     /// The task identifier for the sync operation
     pub task_id: Uuid,
     /// Whether the sync has already completed
     pub completed: bool,
 }
+
+/// The peers an account has pinned for executing its tasks and matching its
+/// orders
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetAccountPinnedPeersResponse {
+    /// The pinned peer IDs, empty if the account has not pinned any peers
+    pub peers: Vec<String>,
+}
+
+/// Request to set the peers an account has pinned
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetAccountPinnedPeersRequest {
+    /// The peer IDs to pin, in order of preference; empty to clear the pin
+    pub peers: Vec<String>,
+}
+
+/// The IDs of the API keys an account has created
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetAccountApiKeysResponse {
+    /// The API key IDs
+    pub key_ids: Vec<Uuid>,
+}
+
+/// The IDs of the accounts that belong to a tenant
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetTenantAccountsResponse {
+    /// The account IDs
+    pub account_ids: Vec<Uuid>,
+}
+
+/// An API key created or rotated for an account
+///
+/// The secret is only ever returned in this response; the relayer does not
+/// expose it again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AccountApiKeyResponse {
+    /// The ID of the key, sent in the key ID header of requests signed with it
+    pub key_id: Uuid,
+    /// The HMAC secret for signing requests
+    #[serde(with = "serde_helpers::hmac_key_as_base64_string")]
+    pub secret: HmacKey,
+}
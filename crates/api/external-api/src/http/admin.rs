@@ -1,6 +1,15 @@
 //! Request/response types for the admin api
 
+use std::collections::HashMap;
+
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use types_core::{BlackoutWindow, Chain, Exchange, TokenListing};
+use types_gossip::HeartbeatIntervals;
+use uuid::Uuid;
+
+use crate::types::{ApiAccountRiskConfig, ApiAccountSweepPolicy};
 
 // ---------
 // | Paths |
@@ -10,21 +19,65 @@ use serde::{Deserialize, Serialize};
 pub const IS_LEADER_ROUTE: &str = "/v2/admin/is-leader";
 /// Trigger a raft snapshot
 pub const ADMIN_TRIGGER_SNAPSHOT_ROUTE: &str = "/v2/admin/trigger-snapshot";
+/// Cross-check the node's state indices, optionally repairing them
+pub const ADMIN_CHECK_STATE_CONSISTENCY_ROUTE: &str = "/v2/admin/check-state-consistency";
+/// Export tables of the node's state to analytics formats
+pub const ADMIN_EXPORT_STATE_ROUTE: &str = "/v2/admin/export-state";
 /// Route to refresh the token mapping
 pub const ADMIN_REFRESH_TOKEN_MAPPING_ROUTE: &str = "/v2/admin/refresh-token-mapping";
+/// Route to add a token to the token mapping
+pub const ADMIN_ADD_TOKEN_ROUTE: &str = "/v2/admin/tokens";
 /// Route to refresh the match fee constants from the contract
 pub const ADMIN_REFRESH_MATCH_FEES_ROUTE: &str = "/v2/admin/refresh-match-fees";
 /// Route to get disabled assets
 pub const ADMIN_GET_DISABLED_ASSETS_ROUTE: &str = "/v2/admin/disabled-assets";
+/// Route to get the values of all feature flags
+pub const ADMIN_GET_FEATURE_FLAGS_ROUTE: &str = "/v2/admin/feature-flags";
+/// Route to set the value of a feature flag
+pub const ADMIN_SET_FEATURE_FLAG_ROUTE: &str = "/v2/admin/feature-flags/:flag";
+/// Route to list the matching blackout windows set at runtime, or to add one
+pub const ADMIN_MATCHING_BLACKOUTS_ROUTE: &str = "/v2/admin/matching-blackouts";
+/// Route to remove a matching blackout window
+pub const ADMIN_REMOVE_MATCHING_BLACKOUT_ROUTE: &str =
+    "/v2/admin/matching-blackouts/:window_id/remove";
+/// Route to get the local node's chain events checkpoint
+pub const ADMIN_GET_CHAIN_EVENTS_CHECKPOINT_ROUTE: &str = "/v2/admin/chain-events/checkpoint";
+/// Route to reset the local node's chain events checkpoint
+pub const ADMIN_RESET_CHAIN_EVENTS_CHECKPOINT_ROUTE: &str =
+    "/v2/admin/chain-events/checkpoint/reset";
 /// Route to get all orders as an admin
 pub const ADMIN_GET_ORDERS_ROUTE: &str = "/v2/relayer-admin/orders";
 /// Route to get an order by ID as an admin
 pub const ADMIN_GET_ORDER_BY_ID_ROUTE: &str = "/v2/relayer-admin/orders/:order_id";
 /// Route to get orders for an account as an admin
 pub const ADMIN_GET_ACCOUNT_ORDERS_ROUTE: &str = "/v2/relayer-admin/account/:account_id/orders";
+/// Route to get the local node's most recent match attempts
+pub const ADMIN_GET_MATCH_ATTEMPTS_ROUTE: &str = "/v2/relayer-admin/match-attempts";
+/// Route to get the local node's match attempts involving an order
+pub const ADMIN_GET_ORDER_MATCH_ATTEMPTS_ROUTE: &str =
+    "/v2/relayer-admin/orders/:order_id/match-attempts";
 /// Route to check if task queue is paused for an account
 pub const ADMIN_GET_TASK_QUEUE_PAUSED_ROUTE: &str =
     "/v2/relayer-admin/account/:account_id/tasks/paused";
+/// Route to get the gas costs of on-chain submissions made for an account
+pub const ADMIN_GET_ACCOUNT_GAS_COSTS_ROUTE: &str =
+    "/v2/relayer-admin/account/:account_id/gas-costs";
+/// Route to get the gas costs of on-chain submissions by task type
+pub const ADMIN_GET_TASK_GAS_COSTS_ROUTE: &str = "/v2/relayer-admin/gas-costs";
+/// Route to pause an account's task queue
+pub const ADMIN_PAUSE_TASK_QUEUE_ROUTE: &str = "/v2/relayer-admin/account/:account_id/tasks/pause";
+/// Route to resume an account's task queue
+pub const ADMIN_RESUME_TASK_QUEUE_ROUTE: &str =
+    "/v2/relayer-admin/account/:account_id/tasks/resume";
+
+/// Route to list the peers known to the node
+pub const ADMIN_GET_PEERS_ROUTE: &str = "/v2/admin/peers";
+/// Route to forcibly expire a peer
+pub const ADMIN_EXPIRE_PEER_ROUTE: &str = "/v2/admin/peers/:peer_id/expire";
+/// Route to get or adjust the node's peer allowlist and blocklist
+pub const ADMIN_PEER_ACCESS_ROUTE: &str = "/v2/admin/peer-access";
+/// Route to get or adjust the node's heartbeat intervals
+pub const ADMIN_HEARTBEAT_INTERVALS_ROUTE: &str = "/v2/admin/heartbeat-intervals";
 
 /// Route to create a matching pool
 pub const ADMIN_MATCHING_POOL_CREATE_ROUTE: &str = "/v2/admin/matching-pools/:matching_pool";
@@ -39,6 +92,10 @@ pub const ADMIN_ASSIGN_ORDER_TO_POOL_ROUTE: &str = "/v2/relayer-admin/orders/:or
 /// Route to set the default matching pool for an account
 pub const ADMIN_SET_ACCOUNT_DEFAULT_POOL_ROUTE: &str =
     "/v2/admin/account/:account_id/default-matching-pool";
+/// Route to set the risk limits for an account
+pub const ADMIN_SET_ACCOUNT_RISK_CONFIG_ROUTE: &str = "/v2/admin/account/:account_id/risk-config";
+/// Route to set the balance sweep policy for an account
+pub const ADMIN_SET_ACCOUNT_SWEEP_POLICY_ROUTE: &str = "/v2/admin/account/:account_id/sweep-policy";
 
 // -------------------
 // | Request/Response |
@@ -46,6 +103,7 @@ pub const ADMIN_SET_ACCOUNT_DEFAULT_POOL_ROUTE: &str =
 
 /// The response to an "is leader" request
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct IsLeaderResponse {
     /// Whether the target node is a raft leader
     pub leader: bool,
@@ -53,13 +111,432 @@ pub struct IsLeaderResponse {
 
 /// The response to a "get disabled assets" request
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetDisabledAssetsResponse {
     /// The list of disabled asset tickers
     pub disabled_assets: Vec<String>,
 }
 
+/// The request to add a token to the token mapping
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AddTokenRequest {
+    /// The name of the token
+    pub name: String,
+    /// The token's ticker
+    pub ticker: String,
+    /// The address of the token on each chain it is listed on
+    #[cfg_attr(feature = "openapi", schemars(with = "HashMap<String, String>"))]
+    pub addresses: HashMap<Chain, String>,
+    /// The number of decimals the token uses in its ERC20 representation
+    pub decimals: u8,
+    /// The exchanges that list the token, along with the ticker used to fetch
+    /// the token's price from each
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schemars(with = "HashMap<String, String>"))]
+    pub supported_exchanges: HashMap<Exchange, String>,
+    /// The canonical exchange from which to source the token's price
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub canonical_exchange: Exchange,
+}
+
+impl From<AddTokenRequest> for TokenListing {
+    fn from(req: AddTokenRequest) -> Self {
+        TokenListing {
+            name: req.name,
+            ticker: req.ticker,
+            decimals: req.decimals,
+            addresses: req.addresses.into_iter().collect(),
+            supported_exchanges: req.supported_exchanges.into_iter().collect(),
+            canonical_exchange: req.canonical_exchange,
+        }
+    }
+}
+
+/// The value of a feature flag
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiFeatureFlag {
+    /// The name of the flag
+    pub flag: String,
+    /// Whether the flag is enabled
+    pub enabled: bool,
+    /// The value the flag takes when it has not been set
+    pub default: bool,
+}
+
+/// The response to a "get feature flags" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetFeatureFlagsResponse {
+    /// The values of all feature flags
+    pub flags: Vec<ApiFeatureFlag>,
+}
+
+/// The request to set the value of a feature flag
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetFeatureFlagRequest {
+    /// Whether the flag should be enabled
+    pub enabled: bool,
+}
+
+/// A window during which no new matches are made
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiBlackoutWindow {
+    /// The ID of the window
+    pub id: Uuid,
+    /// The start of the window, in milliseconds since the epoch
+    pub start: u64,
+    /// The end of the window (exclusive), in milliseconds since the epoch
+    pub end: u64,
+    /// The period at which the window recurs, in milliseconds, if it recurs
+    pub period: Option<u64>,
+    /// The reason for the blackout
+    pub reason: String,
+}
+
+impl From<BlackoutWindow> for ApiBlackoutWindow {
+    fn from(window: BlackoutWindow) -> Self {
+        Self {
+            id: window.id,
+            start: window.start,
+            end: window.end,
+            period: window.period,
+            reason: window.reason,
+        }
+    }
+}
+
+/// The response to a "get matching blackouts" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetBlackoutWindowsResponse {
+    /// The blackout windows set at runtime
+    pub windows: Vec<ApiBlackoutWindow>,
+    /// Whether one of the windows covers the current time
+    pub active: bool,
+}
+
+/// The request to add a matching blackout window
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AddBlackoutWindowRequest {
+    /// The start of the window, in milliseconds since the epoch
+    pub start: u64,
+    /// The end of the window (exclusive), in milliseconds since the epoch
+    pub end: u64,
+    /// The period at which the window recurs, in milliseconds, if it recurs
+    #[serde(default)]
+    pub period: Option<u64>,
+    /// The reason for the blackout
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// The response to an "add matching blackout" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AddBlackoutWindowResponse {
+    /// The window added
+    pub window: ApiBlackoutWindow,
+}
+
+/// The position of the last on-chain event a node fully processed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiChainEventCursor {
+    /// The block in which the event was emitted
+    pub block_number: u64,
+    /// The index of the event's log within its block
+    pub log_index: u64,
+}
+
+/// The response to a "get chain events checkpoint" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetChainEventsCheckpointResponse {
+    /// The checkpoint, if the node has recorded one
+    pub checkpoint: Option<ApiChainEventCursor>,
+}
+
+/// The request to reset the chain events checkpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ResetChainEventsCheckpointRequest {
+    /// The block from which to replay events; if omitted, the checkpoint is
+    /// cleared and only events emitted from now on are processed
+    #[serde(default)]
+    pub from_block: Option<u64>,
+}
+
+/// A peer known to the node, as seen by an admin
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiAdminPeer {
+    /// The ID of the peer
+    pub peer_id: String,
+    /// The cluster the peer belongs to
+    pub cluster_id: String,
+    /// The multiaddr at which the peer is dialed
+    pub addr: String,
+    /// The time of the last successful heartbeat with the peer, in
+    /// milliseconds since the epoch
+    pub last_heartbeat: u64,
+    /// Whether the peer is in the node's own cluster
+    pub is_cluster_peer: bool,
+    /// The smoothed round trip time to the peer in milliseconds, if measured
+    pub rtt_ms: Option<u64>,
+    /// The relayer version the peer runs, if it has advertised one
+    pub version: Option<String>,
+    /// The circuit versions the peer supports, empty if not advertised
+    pub circuit_versions: Vec<u32>,
+    /// The protocol feature bits the peer supports, if it has advertised them
+    pub feature_bits: Option<u64>,
+}
+
+/// The response to a "get peers" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetPeersAdminResponse {
+    /// The peers known to the node
+    pub peers: Vec<ApiAdminPeer>,
+}
+
+/// The response to a "get peer access" or "set peer access" request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct PeerAccessResponse {
+    /// The only peers the node talks to, all peers not blocked if empty
+    pub allowlist: Vec<String>,
+    /// The peers the node refuses to talk to
+    pub blocklist: Vec<String>,
+}
+
+/// The request to adjust the node's peer allowlist and blocklist
+///
+/// Indexed peers excluded by the adjusted lists are expired
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetPeerAccessRequest {
+    /// The peers to add to the blocklist
+    #[serde(default)]
+    pub block: Vec<String>,
+    /// The peers to remove from the blocklist
+    #[serde(default)]
+    pub unblock: Vec<String>,
+    /// The peers to add to the allowlist
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// The peers to remove from the allowlist
+    #[serde(default)]
+    pub disallow: Vec<String>,
+}
+
+/// The intervals and failure thresholds of the node's heartbeat protocol
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiHeartbeatIntervals {
+    /// The base interval at which non-cluster peers are heartbeated, in
+    /// milliseconds
+    pub interval_ms: u64,
+    /// The interval at which cluster peers are heartbeated, in milliseconds
+    pub cluster_interval_ms: u64,
+    /// The base time without a heartbeat after which a non-cluster peer is
+    /// assumed to have failed, in milliseconds
+    pub failure_ms: u64,
+    /// The time without a heartbeat after which a cluster peer is assumed to
+    /// have failed, in milliseconds
+    pub cluster_failure_ms: u64,
+    /// The maximum factor by which a stable non-cluster peer is backed off
+    pub max_backoff: u64,
+    /// The time a non-cluster peer must be stable for each doubling of its
+    /// interval, in milliseconds
+    pub backoff_after_ms: u64,
+}
+
+impl From<HeartbeatIntervals> for ApiHeartbeatIntervals {
+    fn from(intervals: HeartbeatIntervals) -> Self {
+        Self {
+            interval_ms: intervals.interval_ms,
+            cluster_interval_ms: intervals.cluster_interval_ms,
+            failure_ms: intervals.failure_ms,
+            cluster_failure_ms: intervals.cluster_failure_ms,
+            max_backoff: intervals.max_backoff,
+            backoff_after_ms: intervals.backoff_after_ms,
+        }
+    }
+}
+
+/// The response to a "get heartbeat intervals" or "set heartbeat intervals"
+/// request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct HeartbeatIntervalsResponse {
+    /// The heartbeat intervals in use
+    pub intervals: ApiHeartbeatIntervals,
+}
+
+/// The request to adjust the node's heartbeat intervals
+///
+/// Omitted fields keep their current values
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetHeartbeatIntervalsRequest {
+    /// The base interval at which to heartbeat non-cluster peers
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// The interval at which to heartbeat cluster peers
+    #[serde(default)]
+    pub cluster_interval_ms: Option<u64>,
+    /// The base time without a heartbeat after which a non-cluster peer is
+    /// assumed to have failed
+    #[serde(default)]
+    pub failure_ms: Option<u64>,
+    /// The time without a heartbeat after which a cluster peer is assumed to
+    /// have failed
+    #[serde(default)]
+    pub cluster_failure_ms: Option<u64>,
+    /// The maximum factor by which a stable non-cluster peer is backed off
+    #[serde(default)]
+    pub max_backoff: Option<u64>,
+    /// The time a non-cluster peer must be stable for each doubling of its
+    /// interval
+    #[serde(default)]
+    pub backoff_after_ms: Option<u64>,
+}
+
+impl SetHeartbeatIntervalsRequest {
+    /// Apply the request to the current intervals
+    pub fn apply(&self, current: HeartbeatIntervals) -> HeartbeatIntervals {
+        HeartbeatIntervals {
+            interval_ms: self.interval_ms.unwrap_or(current.interval_ms),
+            cluster_interval_ms: self.cluster_interval_ms.unwrap_or(current.cluster_interval_ms),
+            failure_ms: self.failure_ms.unwrap_or(current.failure_ms),
+            cluster_failure_ms: self.cluster_failure_ms.unwrap_or(current.cluster_failure_ms),
+            max_backoff: self.max_backoff.unwrap_or(current.max_backoff),
+            backoff_after_ms: self.backoff_after_ms.unwrap_or(current.backoff_after_ms),
+        }
+    }
+}
+
+/// The request to check the consistency of the node's state
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct CheckStateConsistencyRequest {
+    /// Whether to repair the inconsistencies found
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// The response to a state consistency check
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct CheckStateConsistencyResponse {
+    /// The inconsistencies found, empty if the state is consistent
+    pub issues: Vec<String>,
+    /// Whether the inconsistencies found were repaired
+    pub repaired: bool,
+}
+
+/// A table of the state that may be exported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportTable {
+    /// The orders of all accounts
+    Orders,
+    /// The match attempts in the node's audit trail
+    MatchAttempts,
+    /// The node's task history
+    TaskHistory,
+}
+
+impl ExportTable {
+    /// All exportable tables
+    pub const ALL: [ExportTable; 3] =
+        [ExportTable::Orders, ExportTable::MatchAttempts, ExportTable::TaskHistory];
+}
+
+impl std::fmt::Display for ExportTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportTable::Orders => write!(f, "orders"),
+            ExportTable::MatchAttempts => write!(f, "match-attempts"),
+            ExportTable::TaskHistory => write!(f, "task-history"),
+        }
+    }
+}
+
+/// The file format of a state export
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma separated values, with a header row
+    #[default]
+    Csv,
+    /// Apache Parquet
+    Parquet,
+}
+
+impl ExportFormat {
+    /// The file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// The request to export tables of the node's state
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ExportStateRequest {
+    /// The tables to export, all tables if empty
+    #[serde(default)]
+    pub tables: Vec<ExportTable>,
+    /// The format to write the tables in
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// The S3 bucket to upload the export to
+    ///
+    /// The export is only written locally if unset. Otherwise the local files
+    /// are removed once the upload finishes
+    #[serde(default)]
+    pub s3_bucket: Option<String>,
+    /// The key prefix under which to upload the export
+    #[serde(default)]
+    pub s3_prefix: Option<String>,
+}
+
+/// A table written by a state export
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ExportedTable {
+    /// The table
+    pub table: ExportTable,
+    /// The number of rows written
+    pub rows: u64,
+    /// The location of the file, a local path or an `s3://` URI
+    pub location: String,
+}
+
+/// The response to a state export
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ExportStateResponse {
+    /// The ID of the export
+    pub export_id: Uuid,
+    /// The tables written
+    pub tables: Vec<ExportedTable>,
+}
+
 /// The request to assign an order to a matching pool
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct AssignOrderToPoolRequest {
     /// The matching pool to assign the order to
     pub matching_pool: String,
@@ -67,7 +544,24 @@ pub struct AssignOrderToPoolRequest {
 
 /// Request to set the default matching pool for an account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct SetAccountDefaultMatchingPoolRequest {
     /// The matching pool name, or null to clear the binding
     pub matching_pool: Option<String>,
 }
+
+/// Request to set the risk limits for an account
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetAccountRiskConfigRequest {
+    /// The risk config, or null to clear the account's limits
+    pub risk_config: Option<ApiAccountRiskConfig>,
+}
+
+/// Request to set the balance sweep policy for an account
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct SetAccountSweepPolicyRequest {
+    /// The sweep policy, or null to stop sweeping the account's balances
+    pub sweep_policy: Option<ApiAccountSweepPolicy>,
+}
@@ -2,6 +2,8 @@
 
 use alloy::primitives::Address;
 use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -27,6 +29,7 @@ pub const WITHDRAW_BALANCE_ROUTE: &str = "/v2/account/:account_id/balances/:mint
 
 /// Response for get balances
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetBalancesResponse {
     /// The balances
     pub balances: Vec<ApiBalance>,
@@ -34,6 +37,7 @@ pub struct GetBalancesResponse {
 
 /// Response for get balance by mint
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetBalanceByMintResponse {
     /// The balance
     pub balance: ApiBalance,
@@ -41,12 +45,15 @@ pub struct GetBalanceByMintResponse {
 
 /// Request to deposit a balance
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct DepositBalanceRequest {
     /// The address to deposit from
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub from_address: Address,
     /// The amount to deposit
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Amount,
     /// The authority public key
     pub authority: ApiSchnorrPublicKey,
@@ -56,6 +63,7 @@ pub struct DepositBalanceRequest {
 
 /// Response for deposit balance
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct DepositBalanceResponse {
     /// The task ID for the deposit
     pub task_id: Uuid,
@@ -67,9 +75,11 @@ pub struct DepositBalanceResponse {
 
 /// Request to withdraw a balance
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct WithdrawBalanceRequest {
     /// The amount to withdraw
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Amount,
     /// The signature authorizing the withdrawal
     #[serde(with = "serde_helpers::bytes_as_base64_string")]
@@ -78,6 +88,7 @@ pub struct WithdrawBalanceRequest {
 
 /// Response for withdraw balance
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct WithdrawBalanceResponse {
     /// The task ID for the withdrawal
     pub task_id: Uuid,
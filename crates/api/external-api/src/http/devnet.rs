@@ -0,0 +1,73 @@
+//! HTTP route definitions and request/response types for devnet seeding
+//!
+//! These routes mint test balances and fabricate orders directly in the
+//! relayer's state, bypassing the chain. They are only served by relayers built
+//! for devnet
+
+use alloy::primitives::Address;
+use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::serde_helpers;
+use crate::types::{ApiBalance, ApiIntent, ApiOrder};
+
+// ---------------
+// | HTTP Routes |
+// ---------------
+
+/// Route to mint a test balance into an account
+pub const DEVNET_FAUCET_ROUTE: &str = "/v2/devnet/account/:account_id/balances/:mint/faucet";
+/// Route to fabricate a verified order on an account
+pub const DEVNET_FABRICATE_ORDER_ROUTE: &str = "/v2/devnet/account/:account_id/orders";
+
+// -------------------
+// | Request/Response |
+// -------------------
+
+/// Request to mint a test balance into an account
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct DevnetFaucetRequest {
+    /// The owner of the balance, used if the account holds no balance of the
+    /// mint yet
+    #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub owner: Address,
+    /// The amount to mint
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub amount: Amount,
+}
+
+/// Response for a faucet request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct DevnetFaucetResponse {
+    /// The balance after minting
+    pub balance: ApiBalance,
+}
+
+/// Request to fabricate a verified order on an account
+///
+/// The order is a natively settled private (ring 1) order, created with a mock
+/// signature and proven through the relayer's proof manager
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct DevnetFabricateOrderRequest {
+    /// The intent of the order
+    pub intent: ApiIntent,
+    /// The matching pool to place the order in, the account's default pool if
+    /// not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching_pool: Option<String>,
+}
+
+/// Response for a fabricated order
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct DevnetFabricateOrderResponse {
+    /// The fabricated order
+    pub order: ApiOrder,
+}
@@ -5,6 +5,8 @@ use crate::serde_helpers;
 use alloy::primitives::Address;
 #[cfg(feature = "full-api")]
 use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use types_account::MatchingPoolName;
 
@@ -25,6 +27,7 @@ pub const ASSEMBLE_MATCH_BUNDLE_ROUTE: &str = "/v2/external-matches/assemble-mat
 
 /// Request to get an external match quote
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExternalQuoteRequest {
     /// The external order
     pub external_order: ExternalOrder,
@@ -35,6 +38,7 @@ pub struct ExternalQuoteRequest {
 
 /// Response for external match quote
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExternalQuoteResponse {
     /// The signed quote
     pub signed_quote: ApiSignedQuote,
@@ -42,6 +46,7 @@ pub struct ExternalQuoteResponse {
 
 /// The assembly type for an external match
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(tag = "type", rename_all = "kebab-case")]
 #[allow(clippy::large_enum_variant)]
 pub enum ExternalMatchAssemblyType {
@@ -90,6 +95,7 @@ impl ExternalMatchAssemblyType {
 
 /// Request to assemble an external match bundle
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct AssembleExternalMatchRequest {
     /// Whether to do gas estimation
     #[serde(default)]
@@ -98,6 +104,7 @@ pub struct AssembleExternalMatchRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     #[serde(with = "serde_helpers::option_address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "Option<String>"))]
     pub receiver_address: Option<Address>,
     /// The assembly type
     pub order: ExternalMatchAssemblyType,
@@ -108,6 +115,7 @@ pub struct AssembleExternalMatchRequest {
 
 /// Response for external match
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExternalMatchResponse {
     /// The match bundle
     pub match_bundle: BoundedExternalMatchApiBundle,
@@ -115,6 +123,7 @@ pub struct ExternalMatchResponse {
 
 /// Options for the external matching engine
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExternalMatchingEngineOptions {
     /// The relayer fee rate to apply to the match
     ///
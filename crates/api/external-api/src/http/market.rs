@@ -1,8 +1,10 @@
 //! HTTP route definitions and request/response types for market operations
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{MarketDepth, MarketInfo};
+use crate::types::{ApiToken, FeeEstimate, MarketDepth, MarketInfo, PriceCandle};
 
 // ---------------
 // | HTTP Routes |
@@ -16,6 +18,10 @@ pub const GET_MARKETS_DEPTH_ROUTE: &str = "/v2/markets/depth";
 pub const GET_MARKET_DEPTH_BY_MINT_ROUTE: &str = "/v2/markets/:mint/depth";
 /// Route to get market price by mint
 pub const GET_MARKET_PRICE_ROUTE: &str = "/v2/markets/:mint/price";
+/// Route to estimate the cost of an order action in a market
+pub const GET_FEE_ESTIMATE_ROUTE: &str = "/v2/markets/:mint/fee-estimate";
+/// Route to get the price history of a market by mint
+pub const GET_PRICE_HISTORY_ROUTE: &str = "/v2/markets/:mint/price-history";
 
 // -------------------
 // | Request/Response |
@@ -23,6 +29,7 @@ pub const GET_MARKET_PRICE_ROUTE: &str = "/v2/markets/:mint/price";
 
 /// Response for get markets
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetMarketsResponse {
     /// The markets
     pub markets: Vec<MarketInfo>,
@@ -30,6 +37,7 @@ pub struct GetMarketsResponse {
 
 /// Response for get market depths
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetMarketDepthsResponse {
     /// The market depths
     pub market_depths: Vec<MarketDepth>,
@@ -37,7 +45,32 @@ pub struct GetMarketDepthsResponse {
 
 /// Response for get market depth by mint
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetMarketDepthByMintResponse {
     /// The market depth
     pub market_depth: MarketDepth,
 }
+
+/// Response for get fee estimate
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetFeeEstimateResponse {
+    /// The fee estimate
+    pub estimate: FeeEstimate,
+}
+
+/// Response for get price history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetPriceHistoryResponse {
+    /// The base token
+    pub base: ApiToken,
+    /// The quote token
+    pub quote: ApiToken,
+    /// The length of the interval summarized by each candle, in milliseconds
+    pub interval_ms: u64,
+    /// The candles summarizing the requested window, oldest first
+    ///
+    /// Intervals in which no prices were recorded are omitted
+    pub candles: Vec<PriceCandle>,
+}
@@ -1,11 +1,15 @@
 //! Groups API types for the HTTP API
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub mod account;
 #[cfg(feature = "admin-api")]
 pub mod admin;
 pub mod balance;
+#[cfg(feature = "devnet-api")]
+pub mod devnet;
 #[cfg(feature = "external-match-api")]
 pub mod external_match;
 pub mod market;
@@ -20,6 +24,7 @@ pub mod task;
 
 /// A ping response
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct PingResponse {
     /// The timestamp when the response is sent
     pub timestamp: u64,
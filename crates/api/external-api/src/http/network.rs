@@ -1,8 +1,10 @@
 //! Groups API type definitions for network API operations
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::types::Network;
+use crate::types::{Network, OrderBookGroup};
 
 // ---------------
 // | HTTP Routes |
@@ -10,6 +12,8 @@ use crate::types::Network;
 
 /// Returns the full network topology known to the local node
 pub const GET_NETWORK_TOPOLOGY_ROUTE: &str = "/v2/network";
+/// Returns a snapshot of the network order book, grouped by pair and state
+pub const GET_ORDER_BOOK_SNAPSHOT_ROUTE: &str = "/v2/network/order-book";
 
 // -------------
 // | API Types |
@@ -17,9 +21,18 @@ pub const GET_NETWORK_TOPOLOGY_ROUTE: &str = "/v2/network";
 
 /// The response type to fetch the entire known network topology
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetNetworkTopologyResponse {
     /// The local peer's cluster ID
     pub local_cluster_id: String,
     /// The network topology
     pub network: Network,
 }
+
+/// The response type to fetch a snapshot of the network order book
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetOrderBookSnapshotResponse {
+    /// The groups of orders in the book
+    pub groups: Vec<OrderBookGroup>,
+}
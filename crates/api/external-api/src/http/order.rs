@@ -4,6 +4,8 @@
 use alloy::primitives::Address;
 #[cfg(feature = "full-api")]
 use darkpool_types::intent::Intent;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "full-api")]
 use types_account::order_auth::OrderAuth as AccountOrderAuth;
@@ -25,6 +27,8 @@ const ERR_PERMIT_MISMATCH: &str = "client permit does not match order";
 pub const GET_ORDERS_ROUTE: &str = "/v2/account/:account_id/orders";
 /// Route to create a new order
 pub const CREATE_ORDER_ROUTE: &str = "/v2/account/:account_id/orders";
+/// Route to create and cancel a batch of orders
+pub const CREATE_ORDERS_BATCH_ROUTE: &str = "/v2/account/:account_id/orders/batch";
 /// Route to get an order by ID
 pub const GET_ORDER_BY_ID_ROUTE: &str = "/v2/account/:account_id/orders/:order_id";
 /// Route to update an order
@@ -38,6 +42,7 @@ pub const CANCEL_ORDER_ROUTE: &str = "/v2/account/:account_id/orders/:order_id/c
 
 /// Response for get orders
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetOrdersResponse {
     /// The orders
     pub orders: Vec<ApiOrder>,
@@ -48,6 +53,7 @@ pub struct GetOrdersResponse {
 
 /// Response for get order by ID
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetOrderByIdResponse {
     /// The order
     pub order: ApiOrder,
@@ -57,6 +63,7 @@ pub struct GetOrderByIdResponse {
 
 /// Request to create a new order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CreateOrderRequest {
     /// The order to create
     pub order: ApiOrderCore,
@@ -102,6 +109,7 @@ impl CreateOrderRequest {
 
 /// Response for create order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CreateOrderResponse {
     /// The task ID for the creation
     pub task_id: Uuid,
@@ -109,8 +117,48 @@ pub struct CreateOrderResponse {
     pub completed: bool,
 }
 
+/// Request to create and cancel a batch of orders
+///
+/// The batch is validated as a whole; if any creation or cancellation is
+/// rejected, the account is left untouched
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct CreateOrdersBatchRequest {
+    /// The orders to create
+    #[serde(default)]
+    pub orders: Vec<CreateOrderRequest>,
+    /// The orders to cancel
+    #[serde(default)]
+    pub cancellations: Vec<BatchOrderCancellation>,
+}
+
+/// A cancellation within an order batch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct BatchOrderCancellation {
+    /// The ID of the order to cancel
+    pub order_id: Uuid,
+    /// The signature authorizing the cancellation
+    pub cancel_signature: SignatureWithNonce,
+}
+
+/// Response for create orders batch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct CreateOrdersBatchResponse {
+    /// The task IDs for the creations, in the order of the request's orders
+    pub task_ids: Vec<Uuid>,
+    /// The task IDs for the cancellations, in the order of the request's
+    /// cancellations
+    #[serde(default)]
+    pub cancel_task_ids: Vec<Uuid>,
+    /// Whether the operations have completed
+    pub completed: bool,
+}
+
 /// Request to update an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct UpdateOrderRequest {
     /// The updated order
     pub order: ApiOrderCore,
@@ -118,6 +166,7 @@ pub struct UpdateOrderRequest {
 
 /// Response for update order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct UpdateOrderResponse {
     /// The updated order
     pub order: ApiOrder,
@@ -125,6 +174,7 @@ pub struct UpdateOrderResponse {
 
 /// Request to cancel an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CancelOrderRequest {
     /// The signature authorizing the cancellation
     pub cancel_signature: SignatureWithNonce,
@@ -132,6 +182,7 @@ pub struct CancelOrderRequest {
 
 /// Response for cancel order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CancelOrderResponse {
     /// The task ID for the cancellation
     pub task_id: Uuid,
@@ -141,6 +192,7 @@ pub struct CancelOrderResponse {
 
 /// Request to create a new order in a specific matching pool (admin only)
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct CreateOrderInPoolRequest {
     /// The order to create
     pub order: ApiOrderCore,
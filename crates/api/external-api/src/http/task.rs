@@ -1,6 +1,9 @@
 //! HTTP route definitions and request/response types for task operations
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::types::ApiTask;
 
@@ -12,6 +15,8 @@ use crate::types::ApiTask;
 pub const GET_TASKS_ROUTE: &str = "/v2/account/:account_id/tasks";
 /// Route to get a task by ID
 pub const GET_TASK_BY_ID_ROUTE: &str = "/v2/account/:account_id/tasks/:task_id";
+/// Route to page through an account's task history
+pub const GET_TASK_HISTORY_ROUTE: &str = "/v2/account/:account_id/task-history";
 
 // -------------------
 // | Request/Response |
@@ -19,6 +24,7 @@ pub const GET_TASK_BY_ID_ROUTE: &str = "/v2/account/:account_id/tasks/:task_id";
 
 /// Response for get tasks
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetTasksResponse {
     /// The tasks
     pub tasks: Vec<ApiTask>,
@@ -29,7 +35,19 @@ pub struct GetTasksResponse {
 
 /// Response for get task by ID
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetTaskByIdResponse {
     /// The task
     pub task: ApiTask,
 }
+
+/// Response for get task history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetTaskHistoryResponse {
+    /// The page of tasks, running tasks first then most recent first
+    pub tasks: Vec<ApiTask>,
+    /// The cursor to pass to fetch the next page, absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Uuid>,
+}
@@ -17,6 +17,20 @@ pub mod websocket;
 pub const RENEGADE_AUTH_HEADER_NAME: &str = "x-renegade-auth";
 /// Header name for the expiration timestamp of a signature; lower cased
 pub const RENEGADE_SIG_EXPIRATION_HEADER_NAME: &str = "x-renegade-auth-expiration";
+/// Header name for the ID of the API key a request is signed with; lower cased
+///
+/// Requests without this header are signed with the account's own HMAC key
+pub const RENEGADE_API_KEY_ID_HEADER_NAME: &str = "x-renegade-api-key-id";
+/// Header name for the tenant a request is made on behalf of; lower cased
+///
+/// Requests with this header are signed with the tenant's HMAC key, and may
+/// only access the tenant's own accounts
+pub const RENEGADE_TENANT_ID_HEADER_NAME: &str = "x-renegade-tenant-id";
+/// Header name for the account version a mutating request was built against;
+/// lower cased
+///
+/// A request whose version is stale is rejected with a conflict
+pub const RENEGADE_ACCOUNT_VERSION_HEADER_NAME: &str = "x-renegade-account-version";
 
 // -------------------------
 // | Serialization Helpers |
@@ -63,6 +77,18 @@ impl serde::de::Visitor<'_> for EmptyRequestResponseVisitor {
     }
 }
 
+/// The schema of an empty request/response, which is always `null`
+#[cfg(feature = "openapi")]
+impl schemars::JsonSchema for EmptyRequestResponse {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "EmptyRequestResponse".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "null" })
+    }
+}
+
 // ---------
 // | Tests |
 // ---------
@@ -8,6 +8,7 @@ pub mod f64_as_string;
 pub mod fixed_point_as_string;
 pub mod hmac_key_as_base64_string;
 pub mod option_address_as_string;
+pub mod option_amount_as_string;
 pub mod scalar_as_hex_string;
 pub mod scalar_as_string;
 pub mod schnorr_public_key_as_string;
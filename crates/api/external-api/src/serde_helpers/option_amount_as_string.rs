@@ -0,0 +1,51 @@
+//! Serialize optional amounts as strings
+
+use circuit_types::Amount;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Serialize an `Option<Amount>` as a string
+pub fn serialize<S>(val: &Option<Amount>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match val {
+        Some(amount) => serializer.serialize_str(&amount.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize an `Option<Amount>` from a string
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Amount>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let opt: Option<String> = Option::deserialize(deserializer)?;
+    opt.map(|amount_str| amount_str.parse::<Amount>().map_err(serde::de::Error::custom)).transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TestStruct {
+        #[serde(with = "crate::serde_helpers::option_amount_as_string")]
+        #[serde(default)]
+        amount: Option<Amount>,
+    }
+
+    #[test]
+    fn test_round_trip_some() {
+        let original = TestStruct { amount: Some(100) };
+        let serialized = serde_json::to_string(&original).unwrap();
+        let deserialized: TestStruct = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let deserialized: TestStruct = serde_json::from_str("{}").unwrap();
+        assert_eq!(deserialized.amount, None);
+    }
+}
@@ -1,9 +1,19 @@
 //! API types for account management
 
+use alloy::primitives::Address;
+use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use types_account::Account;
+use types_account::{
+    Account,
+    risk::AccountRiskConfig,
+    sweep::{AccountSweepPolicy, SweepThreshold},
+};
 use uuid::Uuid;
 
+use crate::serde_helpers;
+
 use super::balance::ApiBalance;
 use super::order::ApiOrder;
 
@@ -13,6 +23,7 @@ use super::order::ApiOrder;
 
 /// An account managed by the relayer
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiAccount {
     /// The identifier used to index the wallet
     pub id: Uuid,
@@ -30,3 +41,105 @@ impl From<Account> for ApiAccount {
         Self { id: acct.id, orders, balances }
     }
 }
+
+/// The risk limits configured for an account
+///
+/// Notional values are denominated in the quote token (USDC) in its smallest
+/// unit. Unset limits are not enforced
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiAccountRiskConfig {
+    /// The maximum notional value of a single order
+    #[serde(with = "serde_helpers::option_amount_as_string", default)]
+    #[cfg_attr(feature = "openapi", schemars(with = "Option<String>"))]
+    pub max_order_notional: Option<Amount>,
+    /// The maximum notional volume the account may match in a single day
+    #[serde(with = "serde_helpers::option_amount_as_string", default)]
+    #[cfg_attr(feature = "openapi", schemars(with = "Option<String>"))]
+    pub max_daily_volume: Option<Amount>,
+    /// The base tokens the account may trade, or null to allow all pairs
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schemars(with = "Option<Vec<String>>"))]
+    pub allowed_base_tokens: Option<Vec<Address>>,
+}
+
+impl From<AccountRiskConfig> for ApiAccountRiskConfig {
+    fn from(config: AccountRiskConfig) -> Self {
+        Self {
+            max_order_notional: config.max_order_notional,
+            max_daily_volume: config.max_daily_volume,
+            allowed_base_tokens: config.allowed_base_tokens,
+        }
+    }
+}
+
+impl From<ApiAccountRiskConfig> for AccountRiskConfig {
+    fn from(config: ApiAccountRiskConfig) -> Self {
+        Self {
+            max_order_notional: config.max_order_notional,
+            max_daily_volume: config.max_daily_volume,
+            allowed_base_tokens: config.allowed_base_tokens,
+        }
+    }
+}
+
+/// The balance sweep policy configured for an account
+///
+/// A balance above its trigger amount is swept down to its target amount; no
+/// further sweep is requested until the balance falls to the target amount
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiAccountSweepPolicy {
+    /// The address to which swept balances are sent
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub cold_address: Address,
+    /// The sweep thresholds of the account's balances, at most one per mint
+    pub thresholds: Vec<ApiSweepThreshold>,
+}
+
+/// The amounts at which a balance is swept
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiSweepThreshold {
+    /// The mint of the balance
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub mint: Address,
+    /// The amount above which a sweep is requested
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub trigger_amount: Amount,
+    /// The amount left in the balance after a sweep
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub target_amount: Amount,
+}
+
+impl From<AccountSweepPolicy> for ApiAccountSweepPolicy {
+    fn from(policy: AccountSweepPolicy) -> Self {
+        let thresholds = policy
+            .thresholds
+            .into_iter()
+            .map(|t| ApiSweepThreshold {
+                mint: t.mint,
+                trigger_amount: t.trigger_amount,
+                target_amount: t.target_amount,
+            })
+            .collect();
+        Self { cold_address: policy.cold_address, thresholds }
+    }
+}
+
+impl From<ApiAccountSweepPolicy> for AccountSweepPolicy {
+    fn from(policy: ApiAccountSweepPolicy) -> Self {
+        let thresholds = policy
+            .thresholds
+            .into_iter()
+            .map(|t| SweepThreshold {
+                mint: t.mint,
+                trigger_amount: t.trigger_amount,
+                target_amount: t.target_amount,
+            })
+            .collect();
+        Self { cold_address: policy.cold_address, thresholds }
+    }
+}
@@ -1,6 +1,8 @@
 //! API types for admin requests
 
 use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -14,6 +16,7 @@ use super::order::{ApiOrder, OrderAuth};
 
 /// An admin order with additional metadata
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiAdminOrder {
     /// The order details
     pub order: ApiOrder,
@@ -25,11 +28,13 @@ pub struct ApiAdminOrder {
     /// This represents how much of the order can actually be filled given
     /// the account's current balance state.
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub matchable_amount: Amount,
 }
 
 /// Response for admin get orders request
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetOrdersAdminResponse {
     /// The orders
     pub orders: Vec<ApiAdminOrder>,
@@ -40,6 +45,7 @@ pub struct GetOrdersAdminResponse {
 
 /// Response for admin get order by ID request
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct GetOrderAdminResponse {
     /// The order
     pub order: ApiAdminOrder,
@@ -49,7 +55,100 @@ pub struct GetOrderAdminResponse {
 
 /// Response for checking if an account's task queue is paused
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct TaskQueuePausedResponse {
     /// Whether the task queue is paused
     pub paused: bool,
+    /// Whether the task queue is paused by an operator
+    #[serde(default)]
+    pub paused_by_operator: bool,
+}
+
+/// The phase an internal matching attempt reached
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ApiMatchPhase {
+    /// The matching engine began processing the order
+    Started,
+    /// A crossing counterparty order was found
+    MatchFound,
+    /// The match passed both accounts' risk checks
+    RiskChecked,
+    /// The settlement task was enqueued
+    SettlementQueued,
+    /// The match was recorded as an indicative fill without settling
+    IndicativeFill,
+}
+
+/// An entry in a node's match attempt audit trail
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiMatchAttempt {
+    /// The ID of the attempt
+    pub id: Uuid,
+    /// The peer that ran the attempt
+    pub peer_id: String,
+    /// The order the matching engine was run on
+    pub order_id: Uuid,
+    /// The counterparty order, if a match was found
+    pub other_order_id: Option<Uuid>,
+    /// The furthest phase the attempt reached
+    pub phase: ApiMatchPhase,
+    /// The reason the attempt did not settle, if it failed or was skipped
+    pub failure_reason: Option<String>,
+    /// The time (in milliseconds) at which the attempt started
+    pub started_at: u64,
+    /// The duration of the attempt in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Response for an admin match attempt audit request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetMatchAttemptsResponse {
+    /// The match attempts, most recent first
+    pub attempts: Vec<ApiMatchAttempt>,
+}
+
+/// Aggregate gas costs over a set of on-chain submissions
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiGasCostTotals {
+    /// The number of submissions
+    pub num_submissions: u64,
+    /// The total gas used
+    pub gas_used: u64,
+    /// The total fee paid, in wei
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub fee_paid: Amount,
+}
+
+/// Response for an admin request for an account's gas costs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetAccountGasCostsResponse {
+    /// The account ID
+    pub account_id: Uuid,
+    /// The gas costs of submissions made on behalf of the account
+    pub totals: ApiGasCostTotals,
+}
+
+/// The gas costs of submissions made by tasks of a given type
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct ApiTaskTypeGasCosts {
+    /// The task type
+    pub task_type: String,
+    /// The gas costs of submissions made by tasks of this type
+    pub totals: ApiGasCostTotals,
+}
+
+/// Response for an admin request for gas costs by task type
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct GetTaskTypeGasCostsResponse {
+    /// The gas costs for each task type with recorded submissions
+    pub task_types: Vec<ApiTaskTypeGasCosts>,
 }
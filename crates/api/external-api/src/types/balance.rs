@@ -10,6 +10,8 @@ use constants::Scalar;
 use darkpool_types::balance::{DarkpoolBalance, DarkpoolBalanceShare, DarkpoolStateBalance};
 #[cfg(feature = "full-api")]
 use renegade_solidity_abi::v2::IDarkpoolV2::DepositAuth;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use types_account::balance::Balance;
 
@@ -23,26 +25,33 @@ use crate::serde_helpers;
 
 /// A balance in an account
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiBalance {
     /// The token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub mint: Address,
     /// The owner address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub owner: Address,
     /// The relayer fee recipient address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_recipient: Address,
     /// The authority public key
     pub authority: ApiSchnorrPublicKey,
     /// The relayer fee balance
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_balance: Amount,
     /// The protocol fee balance
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub protocol_fee_balance: Amount,
     /// The available amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Amount,
     /// The recovery stream CSPRNG state
     pub recovery_stream: ApiPoseidonCSPRNG,
@@ -96,26 +105,33 @@ impl TryFrom<ApiBalance> for DarkpoolStateBalance {
 
 /// Public shares of a balance
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiBalanceShare {
     /// The token mint address share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub mint: Scalar,
     /// The owner address share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub owner: Scalar,
     /// The relayer fee recipient address share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_recipient: Scalar,
     /// The authority public key share
     pub authority: ApiSchnorrPublicKeyShare,
     /// The relayer fee balance share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_balance: Scalar,
     /// The protocol fee balance share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub protocol_fee_balance: Scalar,
     /// The amount share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Scalar,
 }
 
@@ -151,6 +167,7 @@ impl From<ApiBalanceShare> for DarkpoolBalanceShare {
 
 /// A deposit permit for Permit2
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiDepositPermit {
     /// The permit nonce
     #[serde(with = "serde_helpers::u256_as_string")]
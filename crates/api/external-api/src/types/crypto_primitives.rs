@@ -10,6 +10,8 @@ use circuit_types::{
 };
 use constants::{EmbeddedScalarField, Scalar};
 use darkpool_types::csprng::PoseidonCSPRNG;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::serde_helpers;
@@ -20,9 +22,11 @@ use crate::serde_helpers;
 
 /// A Poseidon-based CSPRNG state
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiPoseidonCSPRNG {
     /// The seed of the CSPRNG
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub seed: Scalar,
     /// The current index of the CSPRNG
     pub index: u64,
@@ -42,12 +46,15 @@ impl From<ApiPoseidonCSPRNG> for PoseidonCSPRNG {
 
 /// A Baby JubJub curve point
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiBabyJubJubPoint {
     /// The x-coordinate
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub x: Scalar,
     /// The y-coordinate
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub y: Scalar,
 }
 
@@ -65,9 +72,11 @@ impl From<ApiBabyJubJubPoint> for BabyJubJubPoint {
 
 /// A Schnorr signature over a Baby JubJub curve
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiSchnorrSignature {
     /// The embedded scalar component of the signature
     #[serde(with = "serde_helpers::embedded_scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub s: EmbeddedScalarField,
     /// The point component of the signature
     pub r: ApiBabyJubJubPoint,
@@ -87,6 +96,7 @@ impl From<SchnorrSignature> for ApiSchnorrSignature {
 
 /// A Schnorr public key
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiSchnorrPublicKey {
     /// The curve point
     pub point: ApiBabyJubJubPoint,
@@ -106,12 +116,15 @@ impl From<ApiSchnorrPublicKey> for SchnorrPublicKey {
 
 /// A share of a Schnorr public key
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiSchnorrPublicKeyShare {
     /// The x-coordinate share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub x: Scalar,
     /// The y-coordinate share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub y: Scalar,
 }
 
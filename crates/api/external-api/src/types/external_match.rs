@@ -4,6 +4,8 @@ use alloy::primitives::Address;
 use alloy::rpc::types::TransactionRequest;
 use circuit_types::{Amount, fixed_point::FixedPoint};
 use constants::Scalar;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::serde_helpers;
@@ -12,8 +14,8 @@ use crate::serde_helpers;
 use {
     crypto::fields::scalar_to_u128, darkpool_types::bounded_match_result::BoundedMatchResult,
     darkpool_types::fee::FeeRates, darkpool_types::fee::FeeTake, darkpool_types::intent::Intent,
-    types_account::order::Order, types_account::order::OrderMetadata, types_core::TimestampedPrice,
-    types_core::TimestampedPriceFp, util::get_current_time_millis,
+    types_account::order::Order, types_account::order::OrderMetadata, types_core::Price,
+    types_core::TimestampedPrice, types_core::TimestampedPriceFp, util::get_current_time_millis,
 };
 
 // ------------------
@@ -22,23 +24,29 @@ use {
 
 /// An external order for matching
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExternalOrder {
     /// The input token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub input_mint: Address,
     /// The output token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub output_mint: Address,
     /// The input amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub input_amount: Amount,
     /// The output amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub output_amount: Amount,
     /// Whether to use exact output amount
     pub use_exact_output_amount: bool,
     /// The minimum fill size
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub min_fill_size: Amount,
 }
 
@@ -80,11 +88,7 @@ impl ExternalOrder {
 
     /// Parse the metadata for the order
     pub fn order_metadata(&self) -> OrderMetadata {
-        OrderMetadata {
-            min_fill_size: self.min_fill_size,
-            allow_external_matches: true,
-            has_been_filled: false,
-        }
+        OrderMetadata::new(self.min_fill_size, true /* allow_external_matches */)
     }
 }
 
@@ -113,11 +117,13 @@ impl From<ExternalOrder> for Order {
 
 /// A signed quote for an external order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiSignedQuote {
     /// The quote details
     pub quote: ApiExternalQuote,
     /// The signature over the quote
     #[serde(with = "serde_helpers::bytes_as_hex_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub signature: Vec<u8>,
     /// The deadline for the quote
     pub deadline: u64,
@@ -125,6 +131,7 @@ pub struct ApiSignedQuote {
 
 /// A quote for an external order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiExternalQuote {
     /// The external order
     pub order: ExternalOrder,
@@ -144,6 +151,7 @@ pub struct ApiExternalQuote {
 
 /// A timestamped price
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiTimestampedPrice {
     /// The price as a string
     #[serde(with = "serde_helpers::f64_as_string")]
@@ -162,24 +170,37 @@ impl ApiTimestampedPrice {
 }
 
 #[cfg(feature = "full-api")]
-impl From<ApiTimestampedPrice> for TimestampedPrice {
-    fn from(price: ApiTimestampedPrice) -> Self {
-        Self { price: price.price, timestamp: price.timestamp }
+impl TryFrom<ApiTimestampedPrice> for TimestampedPrice {
+    type Error = String;
+
+    fn try_from(price: ApiTimestampedPrice) -> Result<Self, Self::Error> {
+        let fixed_price = Price::from_f64_round_down(price.price)
+            .ok_or(format!("invalid price: {}", price.price))?;
+        Ok(Self { price: fixed_price, timestamp: price.timestamp })
     }
 }
 
 #[cfg(feature = "full-api")]
 impl From<TimestampedPrice> for ApiTimestampedPrice {
     fn from(price: TimestampedPrice) -> Self {
-        Self { price: price.price, timestamp: price.timestamp }
+        Self { price: price.price.to_f64(), timestamp: price.timestamp }
+    }
+}
+
+#[cfg(feature = "full-api")]
+impl From<TimestampedPriceFp> for ApiTimestampedPrice {
+    fn from(price: TimestampedPriceFp) -> Self {
+        Self { price: price.price.to_f64(), timestamp: price.timestamp }
     }
 }
 
 /// A timestamped price with full fixed-point precision
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiTimestampedPriceFp {
     /// The price as a fixed-point value
     #[serde(with = "serde_helpers::fixed_point_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub price: FixedPoint,
     /// The timestamp in milliseconds
     pub timestamp: u64,
@@ -201,12 +222,15 @@ impl From<TimestampedPriceFp> for ApiTimestampedPriceFp {
 
 /// Fees taken from a match
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiFeeTake {
     /// The relayer fee amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee: Amount,
     /// The protocol fee amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub protocol_fee: Amount,
 }
 
@@ -219,12 +243,15 @@ impl From<FeeTake> for ApiFeeTake {
 
 /// An asset transfer in an external match
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiExternalAssetTransfer {
     /// The token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub mint: Address,
     /// The amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Amount,
 }
 
@@ -242,18 +269,23 @@ impl ApiExternalAssetTransfer {
 
 /// An API server external match result
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiExternalMatchResult {
     /// The mint of the input token in the matched asset pair
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub input_mint: Address,
     /// The mint of the output token in the matched asset pair
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub output_mint: Address,
     /// The amount of the input token exchanged by the match
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub input_amount: Amount,
     /// The amount of the output token exchanged by the match
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub output_amount: Amount,
     /// The execution price with full fixed-point precision
     pub price_fp: ApiTimestampedPriceFp,
@@ -261,23 +293,29 @@ pub struct ApiExternalMatchResult {
 
 /// A bounded match result for malleable matches
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiBoundedMatchResult {
     /// The input token mint
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub input_mint: Address,
     /// The output token mint
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub output_mint: Address,
     /// The fixed-point price
     ///
     /// In units of the external party's output per input token
     #[serde(with = "serde_helpers::fixed_point_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub price_fp: FixedPoint,
     /// The minimum input amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub min_input_amount: Amount,
     /// The maximum input amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub max_input_amount: Amount,
 }
 
@@ -331,12 +369,15 @@ impl From<ApiBoundedMatchResult> for BoundedMatchResult {
 
 /// Fee rates for a match
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct FeeTakeRate {
     /// The relayer fee rate
     #[serde(with = "serde_helpers::fixed_point_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_rate: FixedPoint,
     /// The protocol fee rate
     #[serde(with = "serde_helpers::fixed_point_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub protocol_fee_rate: FixedPoint,
 }
 
@@ -352,6 +393,7 @@ impl From<FeeRates> for FeeTakeRate {
 
 /// A malleable atomic match bundle
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct BoundedExternalMatchApiBundle {
     /// The bounded match result
     pub match_result: ApiBoundedMatchResult,
@@ -366,6 +408,7 @@ pub struct BoundedExternalMatchApiBundle {
     /// The minimum send amount
     pub min_send: ApiExternalAssetTransfer,
     /// The settlement transaction
+    #[cfg_attr(feature = "openapi", schemars(with = "serde_json::Map<String, serde_json::Value>"))]
     pub settlement_tx: TransactionRequest,
     /// The deadline for the match
     pub deadline: u64,
@@ -1,7 +1,11 @@
 //! API types for markets
 
+use std::str::FromStr;
+
 use alloy::primitives::Address;
 use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "full-api")]
 use types_core::Token;
@@ -15,9 +19,11 @@ use crate::serde_helpers::{self, address_as_string};
 
 /// A token in the supported token list
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiToken {
     /// The token address
     #[serde(with = "address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub address: Address,
     /// The token symbol
     pub symbol: String,
@@ -45,6 +51,7 @@ impl From<Token> for ApiToken {
 
 /// Information about a market
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct MarketInfo {
     /// The base token
     pub base: ApiToken,
@@ -52,6 +59,10 @@ pub struct MarketInfo {
     pub quote: ApiToken,
     /// The current price
     pub price: ApiTimestampedPrice,
+    /// The confidence in the current price, between 0 and 1, if the price
+    /// reporter has scored it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_confidence: Option<f64>,
     /// The fee rates for internal matches
     pub internal_match_fee_rates: FeeTakeRate,
     /// The fee rates for external matches
@@ -60,6 +71,7 @@ pub struct MarketInfo {
 
 /// The depth of a market
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct MarketDepth {
     /// The market information
     pub market: MarketInfo,
@@ -71,11 +83,123 @@ pub struct MarketDepth {
 
 /// One side of the depth book
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct DepthSide {
     /// The total quantity in base token units
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub total_quantity: Amount,
     /// The total quantity in USD
     #[serde(with = "serde_helpers::f64_as_string")]
     pub total_quantity_usd: f64,
 }
+
+// ----------------------
+// | Fee Estimate Types |
+// ----------------------
+
+/// An order action whose cost may be estimated
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ApiOrderAction {
+    /// Placing an order
+    Place,
+    /// Cancelling an order
+    Cancel,
+    /// Settling a match on an order
+    Match,
+}
+
+impl FromStr for ApiOrderAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "place" => Ok(Self::Place),
+            "cancel" => Ok(Self::Cancel),
+            "match" => Ok(Self::Match),
+            _ => Err(format!("Unknown order action: {s}")),
+        }
+    }
+}
+
+/// The source of a fee estimate's expected gas usage
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GasEstimateSource {
+    /// The average gas used by the relayer's past submissions for the action
+    Historical,
+    /// A static default, used when the relayer has no history for the action
+    Default,
+}
+
+/// An estimate of the total cost of an order action
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct FeeEstimate {
+    /// The estimated action
+    pub action: ApiOrderAction,
+    /// The base token
+    pub base: ApiToken,
+    /// The quote token
+    pub quote: ApiToken,
+    /// The order amount the estimate was computed for, in base token units
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub amount: Amount,
+    /// The expected gas used by the action's on-chain submissions
+    pub gas_units: u64,
+    /// The source of the expected gas usage
+    pub gas_source: GasEstimateSource,
+    /// The current gas price, in wei
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub gas_price: u128,
+    /// The expected gas cost of the action, in wei
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub gas_cost: u128,
+    /// The fee rates applied to a match
+    pub fee_rates: FeeTakeRate,
+    /// The expected relayer fee, in base token units
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub relayer_fee: Amount,
+    /// The expected protocol fee, in base token units
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub protocol_fee: Amount,
+}
+
+// -----------------------
+// | Price History Types |
+// -----------------------
+
+/// A summary of the prices reported for a pair over an interval
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct PriceCandle {
+    /// The start of the interval, in milliseconds since the epoch
+    pub start: u64,
+    /// The end of the interval (exclusive), in milliseconds since the epoch
+    pub end: u64,
+    /// The first price reported in the interval
+    #[serde(with = "serde_helpers::f64_as_string")]
+    pub open: f64,
+    /// The highest price reported in the interval
+    #[serde(with = "serde_helpers::f64_as_string")]
+    pub high: f64,
+    /// The lowest price reported in the interval
+    #[serde(with = "serde_helpers::f64_as_string")]
+    pub low: f64,
+    /// The last price reported in the interval
+    #[serde(with = "serde_helpers::f64_as_string")]
+    pub close: f64,
+    /// The median price reported in the interval
+    #[serde(with = "serde_helpers::f64_as_string")]
+    pub median: f64,
+    /// The number of prices reported in the interval
+    pub num_samples: usize,
+}
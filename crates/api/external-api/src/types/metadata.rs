@@ -1,6 +1,8 @@
 //! API types for exchange metadata
 
 use alloy::primitives::Address;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::market::ApiToken;
@@ -12,17 +14,21 @@ use crate::serde_helpers::address_as_string;
 
 /// Response containing exchange metadata
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ExchangeMetadataResponse {
     /// The chain ID
     pub chain_id: u64,
     /// The settlement contract address
     #[serde(with = "address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub settlement_contract_address: Address,
     /// The executor address
     #[serde(with = "address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub executor_address: Address,
     /// The relayer fee recipient address
     #[serde(with = "address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee_recipient: Address,
     /// The list of supported tokens
     pub supported_tokens: Vec<ApiToken>,
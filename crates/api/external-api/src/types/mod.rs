@@ -27,6 +27,8 @@ pub use market::*;
 pub use metadata::*;
 pub use network::*;
 pub use order::*;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 pub use task::*;
 #[cfg(feature = "websocket")]
@@ -36,6 +38,7 @@ use crate::serde_helpers;
 
 /// A signature with an associated nonce
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct SignatureWithNonce {
     /// The nonce
     #[serde(with = "serde_helpers::u256_as_string")]
@@ -2,11 +2,16 @@
 
 use std::collections::HashMap;
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use types_core::Chain;
 
+use super::ApiToken;
+
 /// The network topology
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct Network {
     /// Identifier, e.g. "arbitrum-one"
     pub id: String,
@@ -30,6 +35,7 @@ impl Network {
 /// A cluster of peers, in the security model a cluster is assumed to be
 /// controlled by a single actor
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct Cluster {
     /// Identifier
     pub id: String,
@@ -37,8 +43,25 @@ pub struct Cluster {
     pub peers: Vec<Peer>,
 }
 
+/// A group of orders in the network order book sharing a pair and a state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct OrderBookGroup {
+    /// The base token of the orders' pair, if known to the local node
+    ///
+    /// Orders managed by remote clusters do not disclose their pair
+    pub base: Option<ApiToken>,
+    /// The quote token of the orders' pair, if known to the local node
+    pub quote: Option<ApiToken>,
+    /// The orders' state, e.g. "Received", "Verified", or "Cancelled"
+    pub state: String,
+    /// The number of orders in the group
+    pub num_orders: usize,
+}
+
 /// A peer in the network known to the local node
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct Peer {
     /// Identifier
     pub id: String,
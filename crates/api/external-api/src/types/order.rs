@@ -6,6 +6,8 @@ use constants::Scalar;
 #[cfg(feature = "full-api")]
 use darkpool_types::intent::DarkpoolStateIntent;
 use darkpool_types::intent::{Intent, IntentShare};
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use types_account::{
     OrderId,
@@ -24,21 +26,27 @@ use crate::serde_helpers;
 
 /// The intent of an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiIntent {
     /// The input token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub in_token: Address,
     /// The output token mint address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub out_token: Address,
     /// The owner's address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub owner: Address,
     /// The minimum price for the order
     #[serde(with = "serde_helpers::fixed_point_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub min_price: FixedPoint,
     /// The input amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount_in: Amount,
 }
 
@@ -69,6 +77,7 @@ impl From<Intent> for ApiIntent {
 
 /// The core order data
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiOrderCore {
     /// The order identifier
     pub id: Uuid,
@@ -76,21 +85,25 @@ pub struct ApiOrderCore {
     pub intent: ApiIntent,
     /// The minimum fill size
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub min_fill_size: Amount,
     /// The type of order
     pub order_type: OrderType,
     /// Whether to allow external matches
     pub allow_external_matches: bool,
+    /// The identifier the client assigned to the order, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    /// Free-form tags the client attached to the order
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl ApiOrderCore {
     /// Return the order metadata from the core order
     pub fn get_order_metadata(&self) -> OrderMetadata {
-        OrderMetadata {
-            min_fill_size: self.min_fill_size,
-            allow_external_matches: self.allow_external_matches,
-            has_been_filled: false,
-        }
+        OrderMetadata::new(self.min_fill_size, self.allow_external_matches)
+            .with_client_fields(self.client_order_id.clone(), self.tags.clone())
     }
 
     /// Get the intent from the core order
@@ -118,27 +131,35 @@ impl From<Order> for ApiOrderCore {
             min_fill_size: order.metadata.min_fill_size,
             order_type: order.ring.into(),
             allow_external_matches: order.metadata.allow_external_matches,
+            client_order_id: order.metadata.client_order_id,
+            tags: order.metadata.tags,
         }
     }
 }
 
 /// The public shares of an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiOrderShare {
     /// The input token share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub in_token: Scalar,
     /// The output token share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub out_token: Scalar,
     /// The owner share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub owner: Scalar,
     /// The minimum price share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub min_price: Scalar,
     /// The amount in share
     #[serde(with = "serde_helpers::scalar_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount_in: Scalar,
 }
 
@@ -168,6 +189,7 @@ impl From<ApiOrderShare> for IntentShare {
 
 /// The full order with metadata
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiOrder {
     /// The order identifier
     pub id: OrderId,
@@ -237,6 +259,7 @@ impl From<ApiOrder> for Order {
 
 /// The type of order
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OrderType {
     /// A public order visible to all
@@ -273,6 +296,7 @@ impl From<PrivacyRing> for OrderType {
 
 /// The state of an order
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OrderState {
     /// Order has been created
@@ -289,11 +313,13 @@ pub enum OrderState {
 
 /// A public intent permit for a public order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiPublicIntentPermit {
     /// The intent this permit authorizes
     pub intent: ApiIntent,
     /// The executor address
     #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub executor: Address,
 }
 
@@ -316,6 +342,7 @@ impl From<ApiPublicIntentPermit> for renegade_solidity_abi::v2::IDarkpoolV2::Pub
 
 /// Authentication for an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OrderAuth {
     /// Authentication for a public order
@@ -363,9 +390,11 @@ impl From<AccountOrderAuth> for OrderAuth {
 
 /// A partial fill of an order
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiPartialOrderFill {
     /// The amount filled
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub amount: Amount,
     /// The price at which the fill occurred
     pub price: ApiTimestampedPriceFloat,
@@ -377,6 +406,7 @@ pub struct ApiPartialOrderFill {
 
 /// A timestamped price with float representation
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiTimestampedPriceFloat {
     /// The price as a string to avoid fixed point precision issues
     pub price: String,
@@ -386,17 +416,21 @@ pub struct ApiTimestampedPriceFloat {
 
 /// Fees taken from a match
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct FeeTake {
     /// The relayer fee amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub relayer_fee: Amount,
     /// The protocol fee amount
     #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
     pub protocol_fee: Amount,
 }
 
 /// The type of order update
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ApiOrderUpdateType {
     /// Order was created
@@ -1,5 +1,7 @@
 //! API types for tasks
 
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -9,6 +11,7 @@ use uuid::Uuid;
 
 /// A task in the system
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ApiTask {
     /// The task identifier
     pub id: Uuid,
@@ -22,6 +25,7 @@ pub struct ApiTask {
 
 /// The type/description of a task
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ApiTaskDescription {
     /// Create a new account
@@ -30,6 +34,8 @@ pub enum ApiTaskDescription {
     SyncAccount,
     /// Deposit funds
     Deposit,
+    /// Create a balance
+    CreateBalance,
     /// Pay a fee
     PayFee,
     /// Withdraw funds
@@ -2,7 +2,12 @@
 
 use std::collections::HashMap;
 
+use alloy::primitives::Address;
+use circuit_types::Amount;
+#[cfg(feature = "openapi")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use types_core::Exchange;
 use uuid::Uuid;
 
 use super::{
@@ -11,6 +16,7 @@ use super::{
     order::{ApiOrder, ApiOrderCore, ApiOrderUpdateType, ApiPartialOrderFill},
     task::ApiTask,
 };
+use crate::serde_helpers;
 
 // ---------------------------
 // | Client Message Types    |
@@ -18,6 +24,7 @@ use super::{
 
 /// A message from the client over WebSocket
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ClientWebsocketMessage {
     /// Headers for the message
     pub headers: HashMap<String, String>,
@@ -27,6 +34,7 @@ pub struct ClientWebsocketMessage {
 
 /// The body of a client WebSocket message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(tag = "method", rename_all = "snake_case")]
 pub enum ClientWebsocketMessageBody {
     /// Subscribe to a topic
@@ -47,6 +55,7 @@ pub enum ClientWebsocketMessageBody {
 
 /// A message from the server over WebSocket
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct ServerWebsocketMessage {
     /// The topic of the message
     pub topic: String,
@@ -58,6 +67,7 @@ pub struct ServerWebsocketMessage {
 
 /// A subscriptions list message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct SubscriptionsMessage {
     /// The list of subscribed topics
     pub subscriptions: Vec<String>,
@@ -65,13 +75,37 @@ pub struct SubscriptionsMessage {
 
 /// A balance update message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct BalanceUpdateMessage {
     /// The updated balance
     pub balance: ApiBalance,
 }
 
+/// A balance sweep request message
+///
+/// Sent when a balance exceeds its trigger amount under the account's sweep
+/// policy; the owner should withdraw the amount and forward it to the cold
+/// address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct BalanceSweepMessage {
+    /// The mint of the balance
+    #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub mint: Address,
+    /// The amount to sweep
+    #[serde(with = "serde_helpers::amount_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub amount: Amount,
+    /// The address to which the balance is swept
+    #[serde(with = "serde_helpers::address_as_string")]
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub cold_address: Address,
+}
+
 /// An order update message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct OrderUpdateMessage {
     /// The updated order
     pub order: ApiOrder,
@@ -81,6 +115,7 @@ pub struct OrderUpdateMessage {
 
 /// A fill message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct FillMessage {
     /// The fill details
     pub fill: ApiPartialOrderFill,
@@ -92,6 +127,7 @@ pub struct FillMessage {
 
 /// A task update message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct TaskUpdateMessage {
     /// The updated task
     pub task: ApiTask,
@@ -99,6 +135,7 @@ pub struct TaskUpdateMessage {
 
 /// An admin balance update message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct AdminBalanceUpdateMessage {
     /// The account ID
     pub account_id: Uuid,
@@ -108,6 +145,7 @@ pub struct AdminBalanceUpdateMessage {
 
 /// An admin order update message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 pub struct AdminOrderUpdateMessage {
     /// The account ID
     pub account_id: Uuid,
@@ -117,14 +155,93 @@ pub struct AdminOrderUpdateMessage {
     pub update_type: ApiOrderUpdateType,
 }
 
+/// An admin alert that an exchange's price for a pair was excluded by the
+/// price deviation circuit breaker, or was included again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AdminPriceDeviationMessage {
+    /// The exchange whose price deviated
+    #[cfg_attr(feature = "openapi", schemars(with = "String"))]
+    pub exchange: Exchange,
+    /// The address of the pair's base token
+    pub base: String,
+    /// The address of the pair's quote token
+    pub quote: String,
+    /// The price's deviation from the median of the pair as a fraction,
+    /// `None` once the price is included again
+    pub deviation: Option<f64>,
+}
+
+/// An admin alert that the state of a price reporter connection changed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AdminPriceReporterConnectionMessage {
+    /// The name of the connection
+    pub connection: String,
+    /// The new state of the connection
+    pub state: ApiPriceReporterConnectionState,
+}
+
+/// The state of a price reporter connection
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ApiPriceReporterConnectionState {
+    /// The connection is established and delivering messages
+    Connected,
+    /// The connection is down, and will be retried after a backoff
+    Reconnecting {
+        /// The number of consecutive reconnect attempts, including this one
+        attempt: u32,
+        /// The backoff before the attempt, in milliseconds
+        backoff_ms: u64,
+    },
+    /// The reconnect attempt is held back, as too many reconnects were
+    /// attempted recently across all connections
+    Throttled {
+        /// The time until the attempt may proceed, in milliseconds
+        wait_ms: u64,
+    },
+}
+
+/// An event on an account's journaled event stream
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiAccountEvent {
+    /// An update to one of the account's orders
+    OrderUpdate(OrderUpdateMessage),
+    /// An update to one of the account's balances
+    BalanceUpdate(BalanceUpdateMessage),
+    /// A transition of one of the account's tasks
+    TaskUpdate(TaskUpdateMessage),
+}
+
+/// A journaled account event message
+///
+/// Cursors increase across all events served by a relayer, so a client that
+/// records the cursor of each event it processes may resubscribe with the last
+/// recorded cursor and receive each later event exactly once
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
+pub struct AccountEventMessage {
+    /// The cursor of the event
+    pub cursor: u64,
+    /// The event
+    pub event: ApiAccountEvent,
+}
+
 /// The body of a server WebSocket message
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(JsonSchema))]
 #[serde(tag = "event", rename_all = "snake_case")]
 pub enum ServerWebsocketMessageBody {
     /// List of current subscriptions
     Subscriptions(SubscriptionsMessage),
     /// A balance update event
     BalanceUpdate(BalanceUpdateMessage),
+    /// A balance sweep request event
+    BalanceSweep(BalanceSweepMessage),
     /// An order update event
     OrderUpdate(OrderUpdateMessage),
     /// A fill event
@@ -135,4 +252,10 @@ pub enum ServerWebsocketMessageBody {
     AdminBalanceUpdate(AdminBalanceUpdateMessage),
     /// An admin order update event
     AdminOrderUpdate(AdminOrderUpdateMessage),
+    /// An admin price deviation alert
+    AdminPriceDeviation(AdminPriceDeviationMessage),
+    /// An admin price reporter connection state change
+    AdminPriceReporterConnection(AdminPriceReporterConnectionMessage),
+    /// A journaled account event
+    AccountEvent(AccountEventMessage),
 }
@@ -22,6 +22,13 @@ pub enum WebsocketMessage {
     Subscribe {
         /// The topic being subscribed to
         topic: String,
+        /// The cursor of the last event the client processed, for topics that
+        /// journal their events
+        ///
+        /// If set, journaled events after the cursor are replayed before the
+        /// subscription streams new events
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cursor: Option<u64>,
     },
     /// Indicates that the client would like to unsubscribe to the given topic
     Unsubscribe {
@@ -37,4 +44,19 @@ pub enum WebsocketMessage {
 pub struct SubscriptionResponse {
     /// The subscriptions that remain after applying the requested update
     pub subscriptions: Vec<String>,
+    /// The ID the relayer assigned to the request, for correlation with its
+    /// logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// A message that is sent in response to a subscribe/unsubscribe message that
+/// could not be served
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebsocketErrorResponse {
+    /// The reason the request could not be served
+    pub error: String,
+    /// The ID the relayer assigned to the request, for correlation with its
+    /// logs
+    pub request_id: String,
 }
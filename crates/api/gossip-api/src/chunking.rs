@@ -0,0 +1,355 @@
+//! Chunked transfer of oversized gossip payloads
+//!
+//! libp2p bounds the size of a single request or pubsub message. Payloads
+//! larger than `MAX_CHUNK_SIZE` once serialized, e.g. raft replication batches
+//! or validity proof bundles, are split into chunks which are sent as
+//! individual messages and reassembled by the recipient before the original
+//! message is handled.
+//!
+//! A chunk of a message that requires cluster authentication is itself
+//! authenticated, so that a recipient only buffers such chunks from its own
+//! cluster. Peers that predate chunking cannot decode chunks, so messages are
+//! only chunked for peers that negotiated `CHUNKING_PROTOCOL_VERSION`
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use types_gossip::WrappedPeerId;
+use uuid::Uuid;
+
+/// The maximum number of payload bytes sent in a single message; larger
+/// payloads are chunked
+pub const MAX_CHUNK_SIZE: usize = 1 << 20; // 1 MiB
+/// The maximum size of a reassembled payload
+pub const MAX_PAYLOAD_SIZE: usize = 1 << 29; // 512 MiB
+/// The maximum number of bytes buffered across the partially received payloads
+/// of a single peer
+///
+/// The limit is per peer so that one peer cannot exhaust the buffer for others
+const MAX_BUFFERED_BYTES_PER_PEER: usize = MAX_PAYLOAD_SIZE;
+/// The time after which a partially received payload is dropped
+const PARTIAL_PAYLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Error emitted when a chunk's index is out of range
+const ERR_INVALID_CHUNK_INDEX: &str = "chunk index out of range";
+/// Error emitted when a chunk disagrees with earlier chunks of its payload
+const ERR_INCONSISTENT_CHUNK: &str = "chunk count differs from earlier chunks";
+/// Error emitted when a payload exceeds the maximum size
+const ERR_PAYLOAD_TOO_LARGE: &str = "chunked payload exceeds the maximum size";
+/// Error emitted when a peer's reassembly buffer is full
+const ERR_BUFFER_FULL: &str = "chunk reassembly buffer is full for peer";
+
+/// A chunk of a payload too large to send in a single message
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GossipChunk {
+    /// The identifier of the payload the chunk belongs to
+    pub payload_id: Uuid,
+    /// The index of the chunk within the payload
+    pub index: u32,
+    /// The number of chunks the payload was split into
+    pub total: u32,
+    /// Whether the chunked message requires cluster authentication
+    ///
+    /// If so, the chunk is authenticated with the cluster key as well
+    pub cluster_auth: bool,
+    /// The chunk's bytes
+    pub data: Vec<u8>,
+}
+
+impl GossipChunk {
+    /// Whether this is the last chunk of its payload
+    pub fn is_last(&self) -> bool {
+        self.index + 1 == self.total
+    }
+}
+
+/// Whether a serialized payload must be chunked to be sent
+pub fn requires_chunking(payload: &[u8]) -> bool {
+    payload.len() > MAX_CHUNK_SIZE
+}
+
+/// Split a serialized payload into chunks of at most `MAX_CHUNK_SIZE` bytes
+///
+/// `cluster_auth` is whether the chunked message requires cluster
+/// authentication
+pub fn split_payload(payload: &[u8], cluster_auth: bool) -> Vec<GossipChunk> {
+    let payload_id = Uuid::new_v4();
+    let total = payload.len().div_ceil(MAX_CHUNK_SIZE) as u32;
+    payload
+        .chunks(MAX_CHUNK_SIZE)
+        .enumerate()
+        .map(|(index, data)| GossipChunk {
+            payload_id,
+            index: index as u32,
+            total,
+            cluster_auth,
+            data: data.to_vec(),
+        })
+        .collect()
+}
+
+// ---------------
+// | Reassembler |
+// ---------------
+
+/// A payload of which some chunks have been received
+#[derive(Debug)]
+struct PartialPayload<T> {
+    /// The chunks received so far, indexed by their position in the payload
+    chunks: Vec<Option<Vec<u8>>>,
+    /// The number of chunks received so far
+    received: usize,
+    /// The number of bytes received so far
+    bytes: usize,
+    /// The time at which the first chunk was received
+    started: Instant,
+    /// The value attached to the last chunk, e.g. the channel on which to
+    /// reply once the payload is complete
+    reply: Option<T>,
+}
+
+/// Reassembles chunked payloads received from peers
+///
+/// A value may be attached to each chunk, the value attached to a payload's
+/// last chunk is returned alongside the payload once it is complete
+#[derive(Debug)]
+pub struct ChunkReassembler<T> {
+    /// The partially received payloads, keyed by sender and payload ID
+    partial: HashMap<(WrappedPeerId, Uuid), PartialPayload<T>>,
+    /// The number of bytes buffered across each peer's partial payloads
+    buffered_bytes: HashMap<WrappedPeerId, usize>,
+    /// The maximum number of bytes buffered for a single peer
+    max_buffered_bytes: usize,
+}
+
+impl<T> Default for ChunkReassembler<T> {
+    fn default() -> Self {
+        Self {
+            partial: HashMap::new(),
+            buffered_bytes: HashMap::new(),
+            max_buffered_bytes: MAX_BUFFERED_BYTES_PER_PEER,
+        }
+    }
+}
+
+impl<T> ChunkReassembler<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of bytes buffered for the given peer
+    fn peer_buffered_bytes(&self, peer: &WrappedPeerId) -> usize {
+        self.buffered_bytes.get(peer).copied().unwrap_or_default()
+    }
+
+    /// Add a chunk received from a peer
+    ///
+    /// Returns the reassembled payload and the value attached to its last
+    /// chunk once every chunk of the payload has been received
+    pub fn insert(
+        &mut self,
+        sender: WrappedPeerId,
+        chunk: GossipChunk,
+        reply: Option<T>,
+    ) -> Result<Option<(Vec<u8>, Option<T>)>, String> {
+        self.evict_expired();
+        if chunk.index >= chunk.total {
+            return Err(ERR_INVALID_CHUNK_INDEX.to_string());
+        }
+        if chunk.total as usize > MAX_PAYLOAD_SIZE.div_ceil(MAX_CHUNK_SIZE) {
+            return Err(ERR_PAYLOAD_TOO_LARGE.to_string());
+        }
+
+        // A retransmitted chunk replaces rather than adds to the buffered bytes
+        let key = (sender, chunk.payload_id);
+        let prev_len = self
+            .partial
+            .get(&key)
+            .and_then(|partial| partial.chunks.get(chunk.index as usize))
+            .and_then(|slot| slot.as_ref().map(Vec::len))
+            .unwrap_or_default();
+        let peer_bytes = self.peer_buffered_bytes(&sender) - prev_len + chunk.data.len();
+        if peer_bytes > self.max_buffered_bytes {
+            return Err(format!("{ERR_BUFFER_FULL}: {sender}"));
+        }
+
+        let partial = self.partial.entry(key).or_insert_with(|| PartialPayload {
+            chunks: vec![None; chunk.total as usize],
+            received: 0,
+            bytes: 0,
+            started: Instant::now(),
+            reply: None,
+        });
+        if partial.chunks.len() != chunk.total as usize {
+            return Err(ERR_INCONSISTENT_CHUNK.to_string());
+        }
+
+        let is_last = chunk.is_last();
+        let slot = &mut partial.chunks[chunk.index as usize];
+        if slot.take().is_some() {
+            partial.received -= 1;
+            partial.bytes -= prev_len;
+        }
+        partial.received += 1;
+        partial.bytes += chunk.data.len();
+        *slot = Some(chunk.data);
+        if is_last {
+            partial.reply = reply;
+        }
+
+        let complete = partial.received == partial.chunks.len();
+        self.buffered_bytes.insert(sender, peer_bytes);
+        if !complete {
+            return Ok(None);
+        }
+
+        let partial = self.partial.remove(&key).expect("partial payload was just inserted");
+        release_bytes(&mut self.buffered_bytes, &sender, partial.bytes);
+        let payload = partial.chunks.into_iter().flatten().flatten().collect();
+        Ok(Some((payload, partial.reply)))
+    }
+
+    /// Drop the partial payloads whose chunks have not all arrived in time
+    fn evict_expired(&mut self) {
+        let buffered_bytes = &mut self.buffered_bytes;
+        self.partial.retain(|(sender, _), partial| {
+            let expired = partial.started.elapsed() > PARTIAL_PAYLOAD_TIMEOUT;
+            if expired {
+                release_bytes(buffered_bytes, sender, partial.bytes);
+            }
+
+            !expired
+        });
+    }
+}
+
+/// Release bytes buffered for a peer, dropping the peer's entry once it has
+/// nothing buffered
+fn release_bytes(
+    buffered_bytes: &mut HashMap<WrappedPeerId, usize>,
+    peer: &WrappedPeerId,
+    n: usize,
+) {
+    if let Some(bytes) = buffered_bytes.get_mut(peer) {
+        *bytes -= n;
+        if *bytes == 0 {
+            buffered_bytes.remove(peer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a payload of the given length
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    /// Tests that a small payload is sent whole
+    #[test]
+    fn test_small_payload() {
+        let payload = payload(MAX_CHUNK_SIZE);
+        assert!(!requires_chunking(&payload));
+        assert_eq!(split_payload(&payload, false /* cluster_auth */).len(), 1);
+    }
+
+    /// Tests that a chunked payload is reassembled regardless of the order in
+    /// which its chunks arrive
+    #[test]
+    fn test_reassemble_out_of_order() {
+        let payload = payload(3 * MAX_CHUNK_SIZE + 1);
+        assert!(requires_chunking(&payload));
+
+        let mut chunks = split_payload(&payload, false /* cluster_auth */);
+        assert_eq!(chunks.len(), 4);
+        chunks.swap(0, 2);
+        chunks.reverse();
+
+        let sender = WrappedPeerId::random();
+        let mut reassembler = ChunkReassembler::new();
+        let mut result = None;
+        for chunk in chunks {
+            let reply = chunk.is_last().then_some(chunk.index);
+            if let Some(res) = reassembler.insert(sender, chunk, reply).unwrap() {
+                result = Some(res);
+            }
+        }
+
+        let (reassembled, reply) = result.unwrap();
+        assert_eq!(reassembled, payload);
+        assert_eq!(reply, Some(3));
+        assert!(reassembler.buffered_bytes.is_empty());
+    }
+
+    /// Tests that chunks from different senders are not mixed
+    #[test]
+    fn test_reassemble_per_sender() {
+        let payload = payload(2 * MAX_CHUNK_SIZE);
+        let chunks = split_payload(&payload, false /* cluster_auth */);
+
+        let mut reassembler = ChunkReassembler::<()>::new();
+        let first = reassembler.insert(WrappedPeerId::random(), chunks[0].clone(), None).unwrap();
+        let second = reassembler.insert(WrappedPeerId::random(), chunks[1].clone(), None).unwrap();
+        assert!(first.is_none());
+        assert!(second.is_none());
+    }
+
+    /// Tests that malformed chunks are rejected
+    #[test]
+    fn test_invalid_chunks() {
+        let sender = WrappedPeerId::random();
+        let mut reassembler = ChunkReassembler::<()>::new();
+        let payload_id = Uuid::new_v4();
+
+        let out_of_range =
+            GossipChunk { payload_id, index: 2, total: 2, cluster_auth: false, data: vec![] };
+        assert!(reassembler.insert(sender, out_of_range, None).is_err());
+
+        let too_large = GossipChunk {
+            payload_id,
+            index: 0,
+            total: u32::MAX,
+            cluster_auth: false,
+            data: vec![],
+        };
+        assert!(reassembler.insert(sender, too_large, None).is_err());
+
+        let first =
+            GossipChunk { payload_id, index: 0, total: 2, cluster_auth: false, data: vec![1] };
+        let inconsistent =
+            GossipChunk { payload_id, index: 1, total: 3, cluster_auth: false, data: vec![2] };
+        assert!(reassembler.insert(sender, first, None).unwrap().is_none());
+        assert!(reassembler.insert(sender, inconsistent, None).is_err());
+    }
+
+    /// Tests that the buffer limit applies to each peer separately
+    #[test]
+    fn test_buffer_limit_per_peer() {
+        let payload = payload(3 * MAX_CHUNK_SIZE);
+        let chunks = split_payload(&payload, false /* cluster_auth */);
+
+        let mut reassembler = ChunkReassembler::<()>::new();
+        reassembler.max_buffered_bytes = 2 * MAX_CHUNK_SIZE;
+
+        // The first peer fills its buffer
+        let peer1 = WrappedPeerId::random();
+        assert!(reassembler.insert(peer1, chunks[0].clone(), None).unwrap().is_none());
+        assert!(reassembler.insert(peer1, chunks[1].clone(), None).unwrap().is_none());
+        assert!(reassembler.insert(peer1, chunks[2].clone(), None).is_err());
+
+        // A retransmitted chunk replaces the buffered one
+        assert!(reassembler.insert(peer1, chunks[1].clone(), None).unwrap().is_none());
+        assert_eq!(reassembler.peer_buffered_bytes(&peer1), 2 * MAX_CHUNK_SIZE);
+
+        // Another peer may still buffer chunks
+        let peer2 = WrappedPeerId::random();
+        assert!(reassembler.insert(peer2, chunks[0].clone(), None).unwrap().is_none());
+        assert_eq!(reassembler.peer_buffered_bytes(&peer2), MAX_CHUNK_SIZE);
+    }
+}
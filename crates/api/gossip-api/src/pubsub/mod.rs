@@ -1,9 +1,16 @@
 //! Pubsub API definitions for the gossip protocol
 
+use ed25519_dalek::{Digest, Keypair, Sha512, Signature};
 use serde::{Deserialize, Serialize};
 use types_core::HmacKey;
+use types_gossip::ClusterId;
 
-use crate::{GossipDestination, check_hmac, create_hmac};
+use crate::{
+    GossipDestination, check_hmac,
+    chunking::GossipChunk,
+    create_hmac,
+    request_response::version::{GossipVersion, Versioned, decode_versioned},
+};
 
 use self::{
     cluster::{ClusterManagementMessage, ClusterManagementMessageType},
@@ -17,8 +24,15 @@ pub mod orderbook;
 /// message
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AuthenticatedPubsubMessage {
+    /// The gossip protocol versions spoken by the publisher
+    #[serde(default)]
+    pub version: GossipVersion,
     /// The signature attached to the message
     pub sig: Vec<u8>,
+    /// The signature of the message's origin cluster over the body, attached
+    /// to messages published on behalf of a cluster
+    #[serde(default)]
+    pub origin_sig: Vec<u8>,
     /// The body of the message
     pub body: PubsubMessage,
 }
@@ -31,7 +45,22 @@ impl AuthenticatedPubsubMessage {
         let sig =
             if body.requires_cluster_auth() { create_hmac(&body, cluster_key) } else { Vec::new() };
 
-        Self { sig, body }
+        Self { version: GossipVersion::local(), sig, origin_sig: Vec::new(), body }
+    }
+
+    /// Sign the body with the local cluster's keypair if the message type
+    /// requires origin authentication
+    ///
+    /// The caller is expected to only publish messages on behalf of its own
+    /// cluster; a message claiming another cluster will fail verification
+    pub fn with_origin_signature(mut self, cluster_keypair: &Keypair) -> Self {
+        if self.body.origin_cluster().is_some() {
+            let digest = origin_digest(&self.body);
+            let sig = cluster_keypair.sign_prehashed(digest, None /* context */).unwrap();
+            self.origin_sig = sig.to_bytes().to_vec();
+        }
+
+        self
     }
 
     /// Verify the signature on an authenticated request
@@ -42,6 +71,37 @@ impl AuthenticatedPubsubMessage {
 
         check_hmac(&self.body, &self.sig, cluster_key)
     }
+
+    /// Verify the origin signature against the public key of the cluster the
+    /// message claims to originate from
+    pub fn verify_origin_auth(&self) -> bool {
+        let Some(cluster) = self.body.origin_cluster() else {
+            return true;
+        };
+
+        let Ok(pubkey) = cluster.get_public_key() else {
+            return false;
+        };
+        let Ok(sig) = Signature::from_bytes(&self.origin_sig) else {
+            return false;
+        };
+
+        let digest = origin_digest(&self.body);
+        pubkey.verify_prehashed(digest, None /* context */, &sig).is_ok()
+    }
+
+    /// Whether the message omits the origin signature its type requires, as
+    /// messages from peers that predate origin signing do
+    pub fn is_origin_unsigned(&self) -> bool {
+        self.body.origin_cluster().is_some() && self.origin_sig.is_empty()
+    }
+}
+
+/// Hash a pubsub message body for an origin signature
+fn origin_digest(body: &PubsubMessage) -> Sha512 {
+    let mut digest = Sha512::new();
+    digest.update(bincode::serialize(body).unwrap());
+    digest
 }
 
 /// Explicit byte serialization and deserialization
@@ -57,8 +117,13 @@ impl From<AuthenticatedPubsubMessage> for Vec<u8> {
 impl TryFrom<Vec<u8>> for AuthenticatedPubsubMessage {
     type Error = String;
 
+    /// Messages from publishers on an incompatible protocol version are
+    /// refused without decoding their bodies
     fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
-        serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+        match decode_versioned(&bytes)? {
+            Versioned::Compatible(msg) => Ok(msg),
+            Versioned::Incompatible(version) => Err(version.incompatibility()),
+        }
     }
 }
 
@@ -74,6 +139,11 @@ pub enum PubsubMessage {
     /// A message broadcast to the network to indicate that OrderBook state has
     /// changed
     Orderbook(OrderBookManagementMessage),
+    /// A chunk of a serialized `AuthenticatedPubsubMessage` too large to be
+    /// published in a single message
+    ///
+    /// Chunks are published on the topic of the message they belong to
+    Chunk(GossipChunk),
 }
 
 impl PubsubMessage {
@@ -85,6 +155,24 @@ impl PubsubMessage {
         match self {
             PubsubMessage::Cluster(..) => true,
             PubsubMessage::Orderbook(..) => false,
+            // A chunk is authenticated if the chunked message is, the
+            // reassembled message is then authenticated on its own
+            PubsubMessage::Chunk(GossipChunk { cluster_auth, .. }) => *cluster_auth,
+        }
+    }
+
+    /// The cluster a message claims to originate from, if the message must be
+    /// signed by that cluster
+    ///
+    /// Order book messages name the cluster managing the order; peers place
+    /// the order in their book under that cluster, so the claim must be
+    /// authenticated
+    pub fn origin_cluster(&self) -> Option<&ClusterId> {
+        match self {
+            PubsubMessage::Cluster(..) => None,
+            PubsubMessage::Orderbook(msg) => Some(msg.cluster()),
+            // The reassembled message is authenticated on its own
+            PubsubMessage::Chunk(..) => None,
         }
     }
 
@@ -100,6 +188,88 @@ impl PubsubMessage {
                 }
             },
             PubsubMessage::Orderbook(..) => GossipDestination::GossipServer,
+            // Chunks are reassembled in the network manager
+            PubsubMessage::Chunk(..) => GossipDestination::NetworkManager,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use circuit_types::Nullifier;
+    use types_account::account::OrderId;
+    use types_gossip::ClusterAsymmetricKeypair;
+
+    use super::*;
+
+    /// Build an order book message claiming the given cluster
+    fn order_received(cluster: ClusterId) -> PubsubMessage {
+        let msg = OrderBookManagementMessage::OrderReceived {
+            order_id: OrderId::new_v4(),
+            nullifier: Nullifier::zero(),
+            cluster,
+        };
+        PubsubMessage::Orderbook(msg)
+    }
+
+    /// Tests that an order book message signed by its origin cluster verifies
+    #[test]
+    fn test_origin_auth() {
+        let keypair = ClusterAsymmetricKeypair::random();
+        let body = order_received(ClusterId::new(&keypair.public));
+        let msg = AuthenticatedPubsubMessage::new_with_body(body, &HmacKey([0u8; 32]))
+            .with_origin_signature(&keypair);
+
+        assert!(msg.verify_origin_auth());
+    }
+
+    /// Tests that an order book message claiming another cluster is rejected
+    #[test]
+    fn test_spoofed_origin() {
+        let keypair = ClusterAsymmetricKeypair::random();
+        let victim = ClusterAsymmetricKeypair::random();
+        let body = order_received(ClusterId::new(&victim.public));
+        let msg = AuthenticatedPubsubMessage::new_with_body(body, &HmacKey([0u8; 32]))
+            .with_origin_signature(&keypair);
+        assert!(!msg.verify_origin_auth());
+
+        // An unsigned message is rejected as well
+        let body = order_received(ClusterId::new(&victim.public));
+        let msg = AuthenticatedPubsubMessage::new_with_body(body, &HmacKey([0u8; 32]));
+        assert!(!msg.verify_origin_auth());
+        assert!(msg.is_origin_unsigned());
+    }
+
+    /// Tests that only messages missing a required origin signature are
+    /// reported as unsigned
+    #[test]
+    fn test_origin_unsigned() {
+        let keypair = ClusterAsymmetricKeypair::random();
+        let body = order_received(ClusterId::new(&keypair.public));
+        let signed = AuthenticatedPubsubMessage::new_with_body(body, &HmacKey([0u8; 32]))
+            .with_origin_signature(&keypair);
+        assert!(!signed.is_origin_unsigned());
+
+        // A forged signature is not mistaken for a missing one
+        let forged = AuthenticatedPubsubMessage { origin_sig: vec![1u8; 64], ..signed };
+        assert!(!forged.verify_origin_auth());
+        assert!(!forged.is_origin_unsigned());
+    }
+
+    /// Tests that messages are stamped with the local versions and that those
+    /// from incompatible publishers are refused
+    #[test]
+    fn test_versioned_message() {
+        let keypair = ClusterAsymmetricKeypair::random();
+        let body = order_received(ClusterId::new(&keypair.public));
+        let msg = AuthenticatedPubsubMessage::new_with_body(body, &HmacKey([0u8; 32]));
+        assert_eq!(msg.version, GossipVersion::local());
+
+        let bytes: Vec<u8> = msg.clone().into();
+        assert!(AuthenticatedPubsubMessage::try_from(bytes).is_ok());
+
+        let legacy = AuthenticatedPubsubMessage { version: GossipVersion::default(), ..msg };
+        let bytes: Vec<u8> = legacy.into();
+        assert!(AuthenticatedPubsubMessage::try_from(bytes).is_err());
+    }
+}
@@ -35,3 +35,14 @@ pub enum OrderBookManagementMessage {
         proof_bundle: OrderValidityProofBundle,
     },
 }
+
+impl OrderBookManagementMessage {
+    /// The cluster that manages the order the message describes
+    pub fn cluster(&self) -> &ClusterId {
+        match self {
+            Self::OrderReceived { cluster, .. } | Self::OrderProofUpdated { cluster, .. } => {
+                cluster
+            },
+        }
+    }
+}
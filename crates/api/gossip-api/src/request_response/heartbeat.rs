@@ -1,10 +1,24 @@
 //! Groups API definitions for heartbeat requests and responses
+//!
+//! A heartbeat may carry the sender's known peers and orders in full, or as a
+//! delta against the last set the recipient acknowledged. Both sides identify
+//! a set by its digest, so a recipient that cannot reconstruct the sender's
+//! set from a delta acknowledges nothing and receives a full heartbeat next
 
+use std::collections::BTreeSet;
+
+use sha2::{Digest, Sha256};
 use types_account::account::OrderId;
-use types_gossip::{PeerInfo, WrappedPeerId};
+use types_gossip::{PeerInfo, PeerMetadata, WrappedPeerId};
 
 use serde::{Deserialize, Serialize};
 
+/// The version of the heartbeat delta encoding
+///
+/// A recipient discards deltas of a version it does not understand, which
+/// causes the sender to fall back to full heartbeats
+pub const HEARTBEAT_DELTA_VERSION: u16 = 1;
+
 /// Defines the heartbeat message, both request and response take
 /// on this message format
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +27,156 @@ pub struct HeartbeatMessage {
     pub known_peers: Vec<WrappedPeerId>,
     /// The list of orders known to the sending node
     pub known_orders: Vec<OrderId>,
+    /// The sender's wall clock time at which the heartbeat was built, in
+    /// milliseconds since the epoch
+    ///
+    /// Used by the recipient to estimate clock skew; zero if the sender
+    /// predates this field
+    #[serde(default)]
+    pub timestamp: u64,
+    /// The sender's software version and capabilities
+    ///
+    /// `None` if the sender predates this field
+    #[serde(default)]
+    pub metadata: Option<PeerMetadata>,
+    /// The digest of the sender's full known peers and orders
+    ///
+    /// `None` if the sender predates delta heartbeats, in which case the
+    /// recipient does not acknowledge the heartbeat
+    #[serde(default)]
+    pub digest: Option<HeartbeatDigest>,
+    /// The changes to the sender's known peers and orders since the digest
+    /// last acknowledged by the recipient
+    ///
+    /// If set, `known_peers` and `known_orders` are empty and the recipient
+    /// applies the delta to the sender's previous heartbeat instead
+    #[serde(default)]
+    pub delta: Option<HeartbeatDelta>,
+    /// The interval at which the sender heartbeats the recipient, in
+    /// milliseconds
+    ///
+    /// Senders back off their heartbeats to stable peers, so the recipient
+    /// expires the sender against this interval. `None` if the sender predates
+    /// this field
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+}
+
+/// A digest of the peers and orders known to a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HeartbeatDigest(pub u64);
+
+impl HeartbeatDigest {
+    /// Compute the digest of a set of peers and orders
+    pub fn compute(peers: &BTreeSet<WrappedPeerId>, orders: &BTreeSet<OrderId>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update((peers.len() as u64).to_le_bytes());
+        for peer in peers {
+            hasher.update(peer.0.to_bytes());
+        }
+        hasher.update((orders.len() as u64).to_le_bytes());
+        for order in orders {
+            hasher.update(order.as_bytes());
+        }
+
+        let hash = hasher.finalize();
+        let mut digest = [0u8; 8];
+        digest.copy_from_slice(&hash[..8]);
+        Self(u64::from_le_bytes(digest))
+    }
+}
+
+/// The known peers and orders advertised in a heartbeat
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeartbeatSnapshot {
+    /// The known peers
+    pub peers: BTreeSet<WrappedPeerId>,
+    /// The known orders
+    pub orders: BTreeSet<OrderId>,
+}
+
+impl HeartbeatSnapshot {
+    /// Build a snapshot from the full lists in a heartbeat
+    pub fn from_message(message: &HeartbeatMessage) -> Self {
+        let peers = message.known_peers.iter().copied().collect();
+        let orders = message.known_orders.iter().copied().collect();
+        Self { peers, orders }
+    }
+
+    /// The digest of the snapshot
+    pub fn digest(&self) -> HeartbeatDigest {
+        HeartbeatDigest::compute(&self.peers, &self.orders)
+    }
+
+    /// The delta from the given base snapshot to this one
+    pub fn delta_from(&self, base: &HeartbeatSnapshot) -> HeartbeatDelta {
+        HeartbeatDelta {
+            version: HEARTBEAT_DELTA_VERSION,
+            base_digest: base.digest(),
+            added_peers: self.peers.difference(&base.peers).copied().collect(),
+            removed_peers: base.peers.difference(&self.peers).copied().collect(),
+            added_orders: self.orders.difference(&base.orders).copied().collect(),
+            removed_orders: base.orders.difference(&self.orders).copied().collect(),
+        }
+    }
+
+    /// Apply a delta to the snapshot, returning the resulting snapshot
+    ///
+    /// Returns `None` if the delta is of an unknown version or was not taken
+    /// against this snapshot
+    pub fn apply(&self, delta: &HeartbeatDelta) -> Option<HeartbeatSnapshot> {
+        if delta.version != HEARTBEAT_DELTA_VERSION || delta.base_digest != self.digest() {
+            return None;
+        }
+
+        let mut res = self.clone();
+        for peer in &delta.removed_peers {
+            res.peers.remove(peer);
+        }
+        for order in &delta.removed_orders {
+            res.orders.remove(order);
+        }
+        res.peers.extend(delta.added_peers.iter().copied());
+        res.orders.extend(delta.added_orders.iter().copied());
+        Some(res)
+    }
+}
+
+/// The changes to a node's known peers and orders since a previous heartbeat
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatDelta {
+    /// The version of the delta encoding
+    pub version: u16,
+    /// The digest of the heartbeat the delta was taken against
+    pub base_digest: HeartbeatDigest,
+    /// The peers learned since the base heartbeat
+    pub added_peers: Vec<WrappedPeerId>,
+    /// The peers dropped since the base heartbeat
+    pub removed_peers: Vec<WrappedPeerId>,
+    /// The orders learned since the base heartbeat
+    pub added_orders: Vec<OrderId>,
+    /// The orders dropped since the base heartbeat
+    pub removed_orders: Vec<OrderId>,
+}
+
+impl HeartbeatDelta {
+    /// Whether the delta carries no changes
+    pub fn is_empty(&self) -> bool {
+        self.added_peers.is_empty()
+            && self.removed_peers.is_empty()
+            && self.added_orders.is_empty()
+            && self.removed_orders.is_empty()
+    }
+}
+
+/// Acknowledges a heartbeat that carried a digest
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeartbeatAck {
+    /// The digest of the sender's peers and orders now held by the recipient
+    ///
+    /// `None` if the recipient could not apply the heartbeat's delta, in which
+    /// case the sender should send a full heartbeat next
+    pub digest: Option<HeartbeatDigest>,
 }
 
 /// Defines a request to bootstrap the cluster state from the recipient
@@ -20,6 +184,13 @@ pub struct HeartbeatMessage {
 pub struct BootstrapRequest {
     /// The requester's peer ID
     pub peer_info: PeerInfo,
+    /// The requester's wall clock time at which the request was built, in
+    /// milliseconds since the epoch
+    ///
+    /// Used by the recipient to check that a joining cluster peer's clock is
+    /// close to its own; zero if the requester predates this field
+    #[serde(default)]
+    pub timestamp: u64,
 }
 
 /// Defines a request for peer info from the recipient
@@ -35,3 +206,54 @@ pub struct PeerInfoResponse {
     /// The peer info for the requested peers
     pub peer_info: Vec<PeerInfo>,
 }
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+    use types_gossip::WrappedPeerId;
+    use uuid::Uuid;
+
+    use super::{HEARTBEAT_DELTA_VERSION, HeartbeatSnapshot};
+
+    /// Build a snapshot with the given number of random peers and orders
+    fn random_snapshot(n: usize) -> HeartbeatSnapshot {
+        let peers = (0..n).map(|_| WrappedPeerId(PeerId::random())).collect();
+        let orders = (0..n).map(|_| Uuid::new_v4()).collect();
+        HeartbeatSnapshot { peers, orders }
+    }
+
+    /// Tests that applying a delta to its base reproduces the new snapshot
+    #[test]
+    fn test_delta_round_trip() {
+        let base = random_snapshot(5);
+        let mut next = base.clone();
+        let removed_peer = *next.peers.iter().next().unwrap();
+        next.peers.remove(&removed_peer);
+        next.peers.insert(WrappedPeerId(PeerId::random()));
+        next.orders.insert(Uuid::new_v4());
+
+        let delta = next.delta_from(&base);
+        assert_eq!(delta.added_peers.len(), 1);
+        assert_eq!(delta.removed_peers, vec![removed_peer]);
+        assert_eq!(delta.added_orders.len(), 1);
+        assert!(delta.removed_orders.is_empty());
+
+        let applied = base.apply(&delta).unwrap();
+        assert_eq!(applied, next);
+        assert_eq!(applied.digest(), next.digest());
+        assert!(next.delta_from(&next).is_empty());
+    }
+
+    /// Tests that a delta is rejected against the wrong base or version
+    #[test]
+    fn test_delta_mismatch() {
+        let base = random_snapshot(3);
+        let next = random_snapshot(3);
+        let delta = next.delta_from(&base);
+        assert!(next.apply(&delta).is_none());
+
+        let mut versioned = delta.clone();
+        versioned.version = HEARTBEAT_DELTA_VERSION + 1;
+        assert!(base.apply(&versioned).is_none());
+    }
+}
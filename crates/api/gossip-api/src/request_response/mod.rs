@@ -6,17 +6,25 @@ use types_core::HmacKey;
 use util::telemetry::propagation::{TraceContext, trace_context};
 
 use crate::{
-    GossipDestination, check_hmac, create_hmac,
-    request_response::orderbook::{OrderInfoRequest, OrderInfoResponse},
+    GossipDestination, check_hmac,
+    chunking::GossipChunk,
+    create_hmac,
+    request_response::orderbook::{
+        OrderBookSyncRequest, OrderBookSyncResponse, OrderInfoRequest, OrderInfoResponse,
+    },
 };
 
 use self::{
-    heartbeat::{BootstrapRequest, HeartbeatMessage, PeerInfoRequest, PeerInfoResponse},
+    heartbeat::{
+        BootstrapRequest, HeartbeatAck, HeartbeatMessage, PeerInfoRequest, PeerInfoResponse,
+    },
     // orderbook::{OrderInfoRequest, OrderInfoResponse},
+    version::GossipVersion,
 };
 
 pub mod heartbeat;
 pub mod orderbook;
+pub mod version;
 
 // -----------------
 // | Request Types |
@@ -26,6 +34,9 @@ pub mod orderbook;
 /// signatures to each request
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AuthenticatedGossipRequest {
+    /// The gossip protocol versions spoken by the sender
+    #[serde(default)]
+    pub version: GossipVersion,
     /// A signature of the request body with the sender's cluster private key
     pub sig: Vec<u8>,
     /// The body of the request
@@ -35,7 +46,8 @@ pub struct AuthenticatedGossipRequest {
 impl AuthenticatedGossipRequest {
     /// Constructs a new authenticated gossip request given the request body
     ///
-    /// Attaches a signature of the body using the given cluster private key
+    /// Stamps the request with the local protocol versions and attaches a
+    /// signature of the body using the given cluster private key
     /// if one is necessary
     pub fn new_with_body(req: GossipRequest, cluster_key: &HmacKey) -> Self {
         // Create a signature fo the body
@@ -45,7 +57,16 @@ impl AuthenticatedGossipRequest {
             Vec::new()
         };
 
-        Self { sig, inner: req }
+        Self { version: GossipVersion::local(), sig, inner: req }
+    }
+
+    /// Create a placeholder for a request that was refused unread because its
+    /// sender speaks an incompatible protocol version
+    ///
+    /// The placeholder is answered with a version rejection and never
+    /// dispatched
+    pub fn incompatible(version: GossipVersion) -> Self {
+        Self { version, sig: Vec::new(), inner: GossipRequestType::Ack.into() }
     }
 
     /// Verify the signature on an authenticated request
@@ -108,6 +129,13 @@ pub enum GossipRequestType {
     // // --- Order Book --- //
     /// A request for order information from a peer
     OrderInfo(OrderInfoRequest),
+    /// A request to reconcile the order book with a peer
+    OrderBookSync(OrderBookSyncRequest),
+
+    // --- Chunked Transfer --- //
+    /// A chunk of a serialized `AuthenticatedGossipRequest` too large to be
+    /// sent in a single message
+    Chunk(GossipChunk),
 }
 
 impl GossipRequest {
@@ -124,6 +152,10 @@ impl GossipRequest {
             GossipRequestType::Heartbeat(..) => false,
             GossipRequestType::PeerInfo(..) => false,
             GossipRequestType::OrderInfo(..) => false,
+            GossipRequestType::OrderBookSync(..) => false,
+            // A chunk is authenticated if the chunked request is, the
+            // reassembled request is then authenticated on its own
+            GossipRequestType::Chunk(GossipChunk { cluster_auth, .. }) => cluster_auth,
         }
     }
 
@@ -137,6 +169,9 @@ impl GossipRequest {
             GossipRequestType::Heartbeat(..) => GossipDestination::GossipServer,
             GossipRequestType::PeerInfo(..) => GossipDestination::GossipServer,
             GossipRequestType::OrderInfo(..) => GossipDestination::GossipServer,
+            GossipRequestType::OrderBookSync(..) => GossipDestination::GossipServer,
+            // Chunks are reassembled in the network manager
+            GossipRequestType::Chunk(..) => GossipDestination::NetworkManager,
         }
     }
 }
@@ -155,6 +190,9 @@ impl From<GossipRequestType> for GossipRequest {
 /// signatures
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AuthenticatedGossipResponse {
+    /// The gossip protocol versions spoken by the sender
+    #[serde(default)]
+    pub version: GossipVersion,
     /// A signature of the request body with the sender's cluster private key
     pub sig: Vec<u8>,
     /// The body of the request
@@ -165,7 +203,22 @@ impl AuthenticatedGossipResponse {
     /// A helper function to create a simple ack without needing to explicitly
     /// construct the nested enumerative types
     pub fn new_ack() -> Self {
-        Self { sig: Vec::new(), inner: GossipResponseType::Ack.into() }
+        let inner = GossipResponseType::Ack.into();
+        Self { version: GossipVersion::local(), sig: Vec::new(), inner }
+    }
+
+    /// Create a refusal of a request whose sender speaks an incompatible
+    /// protocol version
+    pub fn version_rejected() -> Self {
+        let inner = GossipResponseType::VersionRejected(GossipVersion::local()).into();
+        Self { version: GossipVersion::local(), sig: Vec::new(), inner }
+    }
+
+    /// Create a placeholder for a response that was refused unread because its
+    /// sender speaks an incompatible protocol version
+    pub fn incompatible(version: GossipVersion) -> Self {
+        let inner = GossipResponseType::Ack.into();
+        Self { version, sig: Vec::new(), inner }
     }
 
     /// Constructs a new authenticated gossip request given the request body.
@@ -179,7 +232,7 @@ impl AuthenticatedGossipResponse {
             Vec::new()
         };
 
-        Self { sig, inner: req }
+        Self { version: GossipVersion::local(), sig, inner: req }
     }
 
     /// Verify the signature on an authenticated request
@@ -225,16 +278,23 @@ pub enum GossipResponseType {
     Ack,
     /// A response from a peer to a sender's heartbeat request
     Heartbeat(HeartbeatMessage),
+    /// An acknowledgement of the digest carried on a sender's heartbeat
+    HeartbeatAck(HeartbeatAck),
     /// A response from a peer to a sender's request for peer info
     PeerInfo(PeerInfoResponse),
     /// A response to a request for order information
     OrderInfo(OrderInfoResponse),
+    /// A response to an order book sync request
+    OrderBookSync(OrderBookSyncResponse),
     /// A response to a raft message
     ///
     /// We (de)serialize at the raft networking layer and pass an opaque byte
     /// buffer here to avoid pulling in `state` dependencies to the `gossip-api`
     /// package
     Raft(Vec<u8>),
+    /// A refusal of a request whose sender speaks an incompatible protocol
+    /// version, carrying the versions spoken by the local node
+    VersionRejected(GossipVersion),
 }
 
 impl GossipResponse {
@@ -246,9 +306,12 @@ impl GossipResponse {
         match self.body {
             GossipResponseType::Ack => false,
             GossipResponseType::Heartbeat(..) => false,
+            GossipResponseType::HeartbeatAck(..) => false,
             GossipResponseType::OrderInfo(..) => false,
+            GossipResponseType::OrderBookSync(..) => false,
             GossipResponseType::PeerInfo(..) => false,
             GossipResponseType::Raft(..) => true,
+            GossipResponseType::VersionRejected(..) => false,
         }
     }
 
@@ -257,9 +320,12 @@ impl GossipResponse {
         match self.body {
             GossipResponseType::Ack => GossipDestination::NetworkManager,
             GossipResponseType::Heartbeat(..) => GossipDestination::GossipServer,
+            GossipResponseType::HeartbeatAck(..) => GossipDestination::GossipServer,
             GossipResponseType::PeerInfo(..) => GossipDestination::GossipServer,
             GossipResponseType::OrderInfo(..) => GossipDestination::GossipServer,
+            GossipResponseType::OrderBookSync(..) => GossipDestination::GossipServer,
             GossipResponseType::Raft(..) => GossipDestination::NetworkManager,
+            GossipResponseType::VersionRejected(..) => GossipDestination::NetworkManager,
         }
     }
 }
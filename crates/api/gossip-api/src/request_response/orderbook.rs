@@ -1,10 +1,15 @@
 //! Types for request response about order book info
 
+use circuit_types::Nullifier;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use types_account::account::OrderId;
 use types_gossip::network_order::NetworkOrder;
 use types_proofs::OrderValidityProofBundle;
 
+/// The number of buckets an order book digest partitions orders into
+pub const ORDER_BOOK_DIGEST_BUCKETS: usize = 64;
+
 /// The message type used to request order information from a peer
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OrderInfoRequest {
@@ -33,3 +38,203 @@ impl From<NetworkOrder> for NetworkOrderInfo {
         Self { order, validity_proofs: None }
     }
 }
+
+// ----------------
+// | Anti-Entropy |
+// ----------------
+
+/// The domain separator of a removed order's fingerprint, so that a removed
+/// version of an order never sums to the same digest as the live version
+const TOMBSTONE_DOMAIN: &[u8] = b"order-tombstone";
+
+/// A fingerprint of the network-visible version of an order
+///
+/// An order's nullifier changes whenever its intent is updated, so two nodes
+/// holding the same fingerprint for an order hold the same version of it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderFingerprint(pub u64);
+
+impl OrderFingerprint {
+    /// Compute the fingerprint of an order
+    pub fn compute(order: &NetworkOrder) -> Self {
+        Self::of_version(&order.id, order.nullifier)
+    }
+
+    /// Compute the fingerprint of the version of an order with the given
+    /// nullifier
+    pub fn of_version(order_id: &OrderId, nullifier: Nullifier) -> Self {
+        Self::hash(&[order_id.as_bytes(), &nullifier.to_bytes_be()])
+    }
+
+    /// Compute the fingerprint of the removal of the version of an order with
+    /// the given nullifier
+    pub fn of_tombstone(order_id: &OrderId, nullifier: Nullifier) -> Self {
+        Self::hash(&[TOMBSTONE_DOMAIN, order_id.as_bytes(), &nullifier.to_bytes_be()])
+    }
+
+    /// Truncate the hash of the given byte strings to a fingerprint
+    fn hash(parts: &[&[u8]]) -> Self {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+
+        let hash = hasher.finalize();
+        let mut fingerprint = [0u8; 8];
+        fingerprint.copy_from_slice(&hash[..8]);
+        Self(u64::from_le_bytes(fingerprint))
+    }
+}
+
+/// A version of an order held by a node, either live or removed
+///
+/// Removed versions are kept as tombstones so that a peer which has not yet
+/// seen an order's removal cannot hand it back
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderSyncEntry {
+    /// The ID of the order
+    pub id: OrderId,
+    /// The fingerprint of the version
+    pub fingerprint: OrderFingerprint,
+    /// Whether the version has been removed
+    pub removed: bool,
+}
+
+impl OrderSyncEntry {
+    /// The entry for a live order
+    pub fn live(order: &NetworkOrder) -> Self {
+        Self { id: order.id, fingerprint: OrderFingerprint::compute(order), removed: false }
+    }
+
+    /// The entry for a removed order
+    pub fn tombstone(order: &NetworkOrder) -> Self {
+        let fingerprint = OrderFingerprint::of_tombstone(&order.id, order.nullifier);
+        Self { id: order.id, fingerprint, removed: true }
+    }
+}
+
+/// A summary of a node's order book, used to find the orders two nodes
+/// disagree on without exchanging the full book
+///
+/// Orders are partitioned into buckets by ID, and each bucket is summarized by
+/// the wrapping sum of the fingerprints of its live and removed orders
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBookDigest {
+    /// The summary of each bucket
+    pub buckets: Vec<u64>,
+}
+
+impl OrderBookDigest {
+    /// Compute the digest of a set of order versions
+    pub fn compute<'a>(entries: impl IntoIterator<Item = &'a OrderSyncEntry>) -> Self {
+        let mut buckets = vec![0u64; ORDER_BOOK_DIGEST_BUCKETS];
+        for entry in entries {
+            let bucket = &mut buckets[Self::bucket_of(&entry.id)];
+            *bucket = bucket.wrapping_add(entry.fingerprint.0);
+        }
+
+        Self { buckets }
+    }
+
+    /// The bucket an order falls into
+    pub fn bucket_of(order_id: &OrderId) -> usize {
+        order_id.as_bytes()[0] as usize % ORDER_BOOK_DIGEST_BUCKETS
+    }
+
+    /// The buckets in which this digest differs from another
+    ///
+    /// Every bucket differs if the digests partition orders differently
+    pub fn divergent_buckets(&self, other: &OrderBookDigest) -> Vec<usize> {
+        if self.buckets.len() != other.buckets.len() {
+            return (0..self.buckets.len()).collect();
+        }
+
+        (0..self.buckets.len()).filter(|&i| self.buckets[i] != other.buckets[i]).collect()
+    }
+}
+
+/// A request to reconcile the order book with the recipient
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderBookSyncRequest {
+    /// The digest of the requester's order book
+    pub digest: OrderBookDigest,
+}
+
+/// A response to an order book sync request, listing the recipient's live and
+/// removed orders in the buckets where its digest differs from the requester's
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderBookSyncResponse {
+    /// The versions of the orders in the divergent buckets
+    pub orders: Vec<OrderSyncEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use circuit_types::Nullifier;
+    use types_gossip::{ClusterId, network_order::NetworkOrder};
+    use uuid::Uuid;
+
+    use super::{ORDER_BOOK_DIGEST_BUCKETS, OrderBookDigest, OrderSyncEntry};
+
+    /// Build a network order with a random ID and the given nullifier
+    fn order_with_nullifier(nullifier: u64) -> NetworkOrder {
+        let cluster = ClusterId::from_str("cluster").unwrap();
+        NetworkOrder::new(Uuid::new_v4(), Nullifier::from(nullifier), cluster, false)
+    }
+
+    /// The live entries of a set of orders
+    fn live_entries<'a>(orders: impl IntoIterator<Item = &'a NetworkOrder>) -> Vec<OrderSyncEntry> {
+        orders.into_iter().map(OrderSyncEntry::live).collect()
+    }
+
+    /// Tests that digests differ only in the buckets of divergent orders
+    #[test]
+    fn test_divergent_buckets() {
+        let orders = (0..100).map(order_with_nullifier).collect::<Vec<_>>();
+        let digest = OrderBookDigest::compute(&live_entries(&orders));
+        assert_eq!(digest.buckets.len(), ORDER_BOOK_DIGEST_BUCKETS);
+        assert!(
+            digest
+                .divergent_buckets(&OrderBookDigest::compute(&live_entries(orders.iter().rev())))
+                .is_empty()
+        );
+
+        // A missing order
+        let missing = OrderBookDigest::compute(&live_entries(&orders[1..]));
+        assert_eq!(
+            digest.divergent_buckets(&missing),
+            vec![OrderBookDigest::bucket_of(&orders[0].id)]
+        );
+
+        // A divergent order
+        let mut updated = orders.clone();
+        updated[0].nullifier = Nullifier::from(100u64);
+        assert_eq!(
+            digest.divergent_buckets(&OrderBookDigest::compute(&live_entries(&updated))),
+            vec![OrderBookDigest::bucket_of(&orders[0].id)]
+        );
+    }
+
+    /// Tests that a removed order diverges from its live version and converges
+    /// with another removal of it
+    #[test]
+    fn test_tombstone_digest() {
+        let order = order_with_nullifier(1);
+        let live = OrderSyncEntry::live(&order);
+        let tombstone = OrderSyncEntry::tombstone(&order);
+        assert_eq!(live.id, tombstone.id);
+        assert_ne!(live.fingerprint, tombstone.fingerprint);
+
+        let live_digest = OrderBookDigest::compute(&[live]);
+        let removed_digest = OrderBookDigest::compute(&[tombstone]);
+        assert_eq!(
+            live_digest.divergent_buckets(&removed_digest),
+            vec![OrderBookDigest::bucket_of(&order.id)]
+        );
+        assert!(
+            removed_digest.divergent_buckets(&OrderBookDigest::compute(&[tombstone])).is_empty()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Versioning of the gossip request/response protocol
+//!
+//! Each request, response and pubsub message carries the range of protocol
+//! versions its sender speaks. A receiver accepts a message only if that range
+//! overlaps its own, so that a cluster may be upgraded one node at a time:
+//! adjacent versions interoperate, and a peer too far ahead or behind is
+//! refused before its message body is decoded. Two compatible nodes speak the
+//! highest version both decode, which gates the features used between them
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+/// The version of the gossip protocol spoken by the local node
+///
+/// Bump this when a change to the request or response types cannot be decoded
+/// by nodes on the previous version
+pub const GOSSIP_PROTOCOL_VERSION: u16 = 2;
+/// The oldest gossip protocol version the local node can decode
+///
+/// Nodes that predate versioning send no version and are read as version zero,
+/// so they are refused
+pub const MIN_GOSSIP_PROTOCOL_VERSION: u16 = 1;
+/// The first gossip protocol version able to decode chunked messages
+pub const CHUNKING_PROTOCOL_VERSION: u16 = 2;
+
+/// The range of gossip protocol versions a node speaks
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipVersion {
+    /// The version the node sends messages in
+    pub version: u16,
+    /// The oldest version the node can decode
+    pub min_version: u16,
+}
+
+impl GossipVersion {
+    /// The versions spoken by the local node
+    pub const fn local() -> Self {
+        Self { version: GOSSIP_PROTOCOL_VERSION, min_version: MIN_GOSSIP_PROTOCOL_VERSION }
+    }
+
+    /// Whether a peer speaking these versions can exchange messages with the
+    /// local node
+    pub fn is_compatible(&self) -> bool {
+        self.is_compatible_with(&Self::local())
+    }
+
+    /// Whether two nodes speaking the given versions can exchange messages,
+    /// i.e. whether each decodes the version the other sends
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        self.version >= other.min_version && other.version >= self.min_version
+    }
+
+    /// The version spoken between two nodes speaking the given versions, i.e.
+    /// the highest version both decode
+    ///
+    /// Returns `None` if the nodes are incompatible
+    pub fn negotiate(&self, other: &Self) -> Option<u16> {
+        self.is_compatible_with(other).then(|| self.version.min(other.version))
+    }
+
+    /// The version spoken between the local node and a peer speaking these
+    /// versions
+    pub fn negotiated(&self) -> Option<u16> {
+        self.negotiate(&Self::local())
+    }
+
+    /// Whether the local node may send chunked messages to a peer speaking
+    /// these versions
+    pub fn supports_chunking(&self) -> bool {
+        self.negotiated().is_some_and(|v| v >= CHUNKING_PROTOCOL_VERSION)
+    }
+
+    /// The error describing a peer speaking these versions as incompatible
+    pub fn incompatibility(&self) -> String {
+        let local = Self::local();
+        format!(
+            "incompatible gossip protocol version: peer speaks {}-{}, local node speaks {}-{}",
+            self.min_version, self.version, local.min_version, local.version,
+        )
+    }
+}
+
+/// A decoded message, or the versions of a sender whose message was refused
+#[derive(Debug)]
+pub enum Versioned<T> {
+    /// The message, from a sender on a compatible version
+    Compatible(T),
+    /// The versions spoken by a sender on an incompatible version
+    Incompatible(GossipVersion),
+}
+
+/// Decode a serialized request, response or pubsub message, refusing it
+/// without decoding its body if its sender's protocol version is incompatible
+///
+/// The message is parsed once; its body is only decoded into `T` once the
+/// version header has been checked
+pub fn decode_versioned<T: DeserializeOwned>(buf: &[u8]) -> Result<Versioned<T>, String> {
+    let value: Value = serde_json::from_slice(buf).map_err(|e| e.to_string())?;
+    let version = match value.get("version") {
+        Some(version) => GossipVersion::deserialize(version).map_err(|e| e.to_string())?,
+        None => GossipVersion::default(),
+    };
+
+    if !version.is_compatible() {
+        return Ok(Versioned::Incompatible(version));
+    }
+
+    T::deserialize(value).map(Versioned::Compatible).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests compatibility between adjacent and distant versions
+    #[test]
+    fn test_compatibility() {
+        let v1 = GossipVersion { version: 1, min_version: 0 };
+        let v2 = GossipVersion { version: 2, min_version: 1 };
+        let v3 = GossipVersion { version: 3, min_version: 2 };
+
+        assert!(v1.is_compatible_with(&v2));
+        assert!(v2.is_compatible_with(&v3));
+        assert!(!v1.is_compatible_with(&v3));
+        assert!(!v3.is_compatible_with(&v1));
+    }
+
+    /// Tests that two nodes speak the highest version both decode
+    #[test]
+    fn test_negotiate() {
+        let v1 = GossipVersion { version: 1, min_version: 0 };
+        let v2 = GossipVersion { version: 2, min_version: 1 };
+        let v3 = GossipVersion { version: 3, min_version: 2 };
+
+        assert_eq!(v1.negotiate(&v2), Some(1));
+        assert_eq!(v3.negotiate(&v2), Some(2));
+        assert_eq!(v1.negotiate(&v3), None);
+    }
+
+    /// Tests that chunking is only used with peers that negotiate the chunking
+    /// version
+    #[test]
+    fn test_supports_chunking() {
+        let legacy = GossipVersion::default();
+        let v1 = GossipVersion { version: 1, min_version: 0 };
+        let future = GossipVersion { version: GOSSIP_PROTOCOL_VERSION + 1, min_version: 1 };
+
+        assert!(!legacy.supports_chunking());
+        assert!(!v1.supports_chunking());
+        assert!(GossipVersion::local().supports_chunking());
+        assert!(future.supports_chunking());
+    }
+
+    /// Tests that a message without a version header is read as a legacy
+    /// message, which is refused
+    #[test]
+    fn test_legacy_header() {
+        let decoded = decode_versioned::<Value>(br#"{"sig":[]}"#).unwrap();
+        assert!(matches!(decoded, Versioned::Incompatible(v) if v == GossipVersion::default()));
+    }
+
+    /// Tests that only messages from compatible senders are decoded
+    #[test]
+    fn test_decode_versioned() {
+        let compatible = br#"{"version":{"version":1,"min_version":0},"body":1}"#;
+        let decoded = decode_versioned::<Value>(compatible).unwrap();
+        assert!(matches!(decoded, Versioned::Compatible(v) if v["body"] == 1));
+
+        // The body of an incompatible message is not decoded
+        let future = GOSSIP_PROTOCOL_VERSION + 1;
+        let incompatible = format!(
+            r#"{{"version":{{"version":{},"min_version":{future}}},"body":1}}"#,
+            future + 1
+        );
+        let decoded = decode_versioned::<u64>(incompatible.as_bytes()).unwrap();
+        assert!(matches!(decoded, Versioned::Incompatible(v) if v.min_version == future));
+    }
+}
@@ -0,0 +1,73 @@
+//! Prints a breakdown of each circuit's gates by the gadgets that generate
+//! them
+//!
+//! Run with `cargo run --release --example constraint_profile --features
+//! profiling`
+
+use circuit_types::traits::SingleProverCircuit;
+use circuits_core::{
+    profiling::profile_circuit,
+    zk_circuits::{
+        fees::{
+            valid_note_redemption::SizedValidNoteRedemption,
+            valid_private_protocol_fee_payment::SizedValidPrivateProtocolFeePayment,
+            valid_private_relayer_fee_payment::SizedValidPrivateRelayerFeePayment,
+            valid_public_protocol_fee_payment::SizedValidPublicProtocolFeePayment,
+            valid_public_relayer_fee_payment::SizedValidPublicRelayerFeePayment,
+        },
+        settlement::{
+            intent_and_balance_bounded_settlement::IntentAndBalanceBoundedSettlementCircuit,
+            intent_and_balance_private_settlement::IntentAndBalancePrivateSettlementCircuit,
+            intent_and_balance_public_settlement::IntentAndBalancePublicSettlementCircuit,
+            intent_only_bounded_settlement::IntentOnlyBoundedSettlementCircuit,
+            intent_only_public_settlement::IntentOnlyPublicSettlementCircuit,
+        },
+        valid_balance_create::ValidBalanceCreate,
+        valid_deposit::SizedValidDeposit,
+        valid_order_cancellation::SizedValidOrderCancellationCircuit,
+        valid_withdrawal::SizedValidWithdrawal,
+        validity_proofs::{
+            intent_and_balance::SizedIntentAndBalanceValidityCircuit,
+            intent_and_balance_first_fill::SizedIntentAndBalanceFirstFillValidityCircuit,
+            intent_only::SizedIntentOnlyValidityCircuit,
+            intent_only_first_fill::IntentOnlyFirstFillValidityCircuit,
+            new_output_balance::SizedNewOutputBalanceValidityCircuit,
+            output_balance::SizedOutputBalanceValidityCircuit,
+        },
+    },
+};
+
+/// Profile a circuit and print its breakdown
+fn print_profile<C: SingleProverCircuit>() {
+    match profile_circuit::<C>() {
+        Ok(profile) => println!("{profile}"),
+        Err(e) => eprintln!("failed to profile {}: {e}", C::name()),
+    }
+}
+
+fn main() {
+    // Update proofs
+    print_profile::<ValidBalanceCreate>();
+    print_profile::<SizedValidDeposit>();
+    print_profile::<SizedValidOrderCancellationCircuit>();
+    print_profile::<SizedValidWithdrawal>();
+    // Validity proofs
+    print_profile::<SizedIntentAndBalanceValidityCircuit>();
+    print_profile::<SizedIntentAndBalanceFirstFillValidityCircuit>();
+    print_profile::<SizedIntentOnlyValidityCircuit>();
+    print_profile::<IntentOnlyFirstFillValidityCircuit>();
+    print_profile::<SizedNewOutputBalanceValidityCircuit>();
+    print_profile::<SizedOutputBalanceValidityCircuit>();
+    // Settlement proofs
+    print_profile::<IntentAndBalanceBoundedSettlementCircuit>();
+    print_profile::<IntentAndBalancePrivateSettlementCircuit>();
+    print_profile::<IntentAndBalancePublicSettlementCircuit>();
+    print_profile::<IntentOnlyBoundedSettlementCircuit>();
+    print_profile::<IntentOnlyPublicSettlementCircuit>();
+    // Fee proofs
+    print_profile::<SizedValidNoteRedemption>();
+    print_profile::<SizedValidPrivateProtocolFeePayment>();
+    print_profile::<SizedValidPrivateRelayerFeePayment>();
+    print_profile::<SizedValidPublicProtocolFeePayment>();
+    print_profile::<SizedValidPublicRelayerFeePayment>();
+}
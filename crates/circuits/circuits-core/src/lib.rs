@@ -17,6 +17,8 @@ use constants::Scalar;
 
 pub mod mpc_circuits;
 pub mod mpc_gadgets;
+#[cfg(feature = "profiling")]
+pub mod profiling;
 #[cfg(any(test, feature = "test_helpers"))]
 pub mod test_helpers;
 pub mod zk_circuits;
@@ -88,6 +90,36 @@ macro_rules! print_multiprover_wire {
     }};
 }
 
+/// Attribute the gates added by a gadget's body to the gadget when profiling
+/// constraints
+///
+/// The body must evaluate to a `Result<_, CircuitError>`; `?` within the body
+/// returns from the body rather than the enclosing function
+#[cfg(feature = "profiling")]
+macro_rules! profile_gadget {
+    ($kind:ident, $cs:ident, $body:block) => {{
+        use mpc_relation::traits::Circuit;
+
+        $crate::profiling::enter_gadget(
+            $crate::profiling::GadgetKind::$kind,
+            Circuit::num_gates(&*$cs),
+        );
+        #[allow(clippy::redundant_closure_call)]
+        let res = (|| -> Result<_, mpc_relation::errors::CircuitError> { $body })();
+        $crate::profiling::exit_gadget(Circuit::num_gates(&*$cs));
+        res
+    }};
+}
+
+/// Attribute the gates added by a gadget's body to the gadget when profiling
+/// constraints
+#[cfg(not(feature = "profiling"))]
+macro_rules! profile_gadget {
+    ($kind:ident, $cs:ident, $body:block) => {
+        $body
+    };
+}
+
 #[allow(unused)]
 pub(crate) use print_mpc_wire;
 #[allow(unused)]
@@ -96,6 +128,7 @@ pub(crate) use print_multiprover_wire;
 pub(crate) use print_wire;
 #[allow(unused)]
 pub(crate) use print_wire_debug;
+pub(crate) use profile_gadget;
 
 // -----------
 // | Helpers |
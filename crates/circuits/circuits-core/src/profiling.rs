@@ -0,0 +1,218 @@
+//! Constraint profiling, attributing a circuit's gates to the gadgets that
+//! generate them
+//!
+//! Gadgets wrap their bodies in `profile_gadget!`, which records the gates
+//! added while the gadget runs. [`profile_circuit`] applies a circuit's
+//! constraints with profiling active and returns the breakdown.
+//!
+//! Nested gadgets are attributed exclusively: the gates of the Poseidon hashes
+//! within a Merkle opening count towards Poseidon, not Merkle. The inclusive
+//! count of a gadget also includes the gadgets it calls
+
+use std::{cell::RefCell, collections::BTreeMap, fmt, iter};
+
+use circuit_types::{
+    PlonkCircuit,
+    traits::{CircuitBaseType, SingleProverCircuit},
+};
+use constants::Scalar;
+use mpc_plonk::errors::PlonkError;
+use mpc_relation::traits::Circuit;
+
+/// A kind of gadget whose gates are profiled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GadgetKind {
+    /// Merkle opening verification
+    Merkle,
+    /// The Poseidon permutation
+    Poseidon,
+    /// Bit decomposition, as used by range checks and comparisons
+    RangeCheck,
+}
+
+impl fmt::Display for GadgetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GadgetKind::Merkle => "merkle",
+            GadgetKind::Poseidon => "poseidon",
+            GadgetKind::RangeCheck => "range-check",
+        };
+        f.pad(name)
+    }
+}
+
+/// The gates attributed to a kind of gadget
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GadgetProfile {
+    /// The number of times the gadget was invoked, excluding invocations
+    /// nested within the same gadget
+    pub calls: usize,
+    /// The gates added by the gadget itself, excluding the gadgets it calls
+    pub exclusive_gates: usize,
+    /// The gates added by the gadget and the gadgets it calls
+    pub inclusive_gates: usize,
+}
+
+/// The breakdown of a circuit's gates by gadget
+#[derive(Clone, Debug)]
+pub struct ConstraintProfile {
+    /// The name of the circuit
+    pub circuit: String,
+    /// The total number of gates in the circuit
+    pub total_gates: usize,
+    /// The gates attributed to each kind of gadget
+    pub gadgets: BTreeMap<GadgetKind, GadgetProfile>,
+}
+
+impl ConstraintProfile {
+    /// The gates not attributed to any profiled gadget
+    pub fn unattributed_gates(&self) -> usize {
+        let attributed: usize = self.gadgets.values().map(|g| g.exclusive_gates).sum();
+        self.total_gates.saturating_sub(attributed)
+    }
+}
+
+impl fmt::Display for ConstraintProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pct = |gates: usize| 100. * gates as f64 / self.total_gates.max(1) as f64;
+
+        writeln!(f, "{}: {} gates", self.circuit, self.total_gates)?;
+        writeln!(
+            f,
+            "  {:<12} {:>8} {:>10} {:>7} {:>10}",
+            "gadget", "calls", "self", "%", "inclusive"
+        )?;
+        for (kind, profile) in self.gadgets.iter() {
+            writeln!(
+                f,
+                "  {:<12} {:>8} {:>10} {:>6.1}% {:>10}",
+                kind,
+                profile.calls,
+                profile.exclusive_gates,
+                pct(profile.exclusive_gates),
+                profile.inclusive_gates
+            )?;
+        }
+
+        let other = self.unattributed_gates();
+        writeln!(f, "  {:<12} {:>8} {:>10} {:>6.1}% {:>10}", "other", "-", other, pct(other), "-")
+    }
+}
+
+/// Apply a circuit's constraints to a dummy witness and statement, profiling
+/// the gates added by each gadget
+pub fn profile_circuit<C: SingleProverCircuit>() -> Result<ConstraintProfile, PlonkError> {
+    // Allocate the circuit as its layout is computed
+    let mut cs = PlonkCircuit::new_turbo_plonk();
+    for (id, layout) in C::proof_linking_groups()?.into_iter() {
+        cs.create_link_group(id, layout);
+    }
+
+    let mut values_iter = iter::repeat(Scalar::zero());
+    let witness = C::Witness::from_scalars(&mut values_iter);
+    let statement = C::Statement::from_scalars(&mut values_iter);
+    let witness_var = witness.create_witness(&mut cs);
+    let statement_var = statement.create_public_var(&mut cs);
+
+    PROFILER.with(|p| *p.borrow_mut() = Some(Profiler::default()));
+    let res = C::apply_constraints(witness_var, statement_var, &mut cs);
+    let profiler = PROFILER.with(|p| p.borrow_mut().take()).unwrap_or_default();
+    res?;
+
+    Ok(ConstraintProfile {
+        circuit: C::name(),
+        total_gates: cs.num_gates(),
+        gadgets: profiler.gadgets,
+    })
+}
+
+// -------------
+// | Recording |
+// -------------
+
+thread_local! {
+    /// The profiler of the circuit being profiled on this thread, if any
+    static PROFILER: RefCell<Option<Profiler>> = const { RefCell::new(None) };
+}
+
+/// A gadget invocation in progress
+#[derive(Debug)]
+struct Frame {
+    /// The kind of gadget
+    kind: GadgetKind,
+    /// The number of gates in the circuit when the gadget was entered
+    start_gates: usize,
+    /// The gates added by the gadgets called from this one
+    child_gates: usize,
+}
+
+/// Accumulates the gates attributed to each gadget
+#[derive(Debug, Default)]
+struct Profiler {
+    /// The gadget invocations in progress, innermost last
+    stack: Vec<Frame>,
+    /// The gates attributed to each kind of gadget
+    gadgets: BTreeMap<GadgetKind, GadgetProfile>,
+}
+
+/// Record entering a gadget, given the circuit's current number of gates
+///
+/// A no-op unless a circuit is being profiled on this thread
+pub(crate) fn enter_gadget(kind: GadgetKind, n_gates: usize) {
+    PROFILER.with(|p| {
+        if let Some(profiler) = p.borrow_mut().as_mut() {
+            profiler.stack.push(Frame { kind, start_gates: n_gates, child_gates: 0 });
+        }
+    });
+}
+
+/// Record exiting the innermost gadget, given the circuit's current number of
+/// gates
+pub(crate) fn exit_gadget(n_gates: usize) {
+    PROFILER.with(|p| {
+        let mut p = p.borrow_mut();
+        let Some(profiler) = p.as_mut() else { return };
+        let Some(frame) = profiler.stack.pop() else { return };
+
+        let total = n_gates.saturating_sub(frame.start_gates);
+        let nested = profiler.stack.iter().any(|f| f.kind == frame.kind);
+        if let Some(parent) = profiler.stack.last_mut() {
+            parent.child_gates += total;
+        }
+
+        let entry = profiler.gadgets.entry(frame.kind).or_default();
+        entry.exclusive_gates += total.saturating_sub(frame.child_gates);
+        if !nested {
+            entry.calls += 1;
+            entry.inclusive_gates += total;
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GadgetKind, PROFILER, Profiler, enter_gadget, exit_gadget};
+
+    /// Tests that nested gadgets are attributed exclusively
+    #[test]
+    fn test_nested_attribution() {
+        PROFILER.with(|p| *p.borrow_mut() = Some(Profiler::default()));
+
+        // A Merkle opening of 100 gates which calls a 60 gate hash, and
+        // itself nests a 10 gate opening
+        enter_gadget(GadgetKind::Merkle, 0);
+        enter_gadget(GadgetKind::Poseidon, 10);
+        exit_gadget(70);
+        enter_gadget(GadgetKind::Merkle, 80);
+        exit_gadget(90);
+        exit_gadget(100);
+
+        let profiler = PROFILER.with(|p| p.borrow_mut().take()).unwrap();
+        let merkle = profiler.gadgets[&GadgetKind::Merkle];
+        let poseidon = profiler.gadgets[&GadgetKind::Poseidon];
+        assert_eq!(merkle.calls, 1);
+        assert_eq!(merkle.exclusive_gates, 40);
+        assert_eq!(merkle.inclusive_gates, 100);
+        assert_eq!(poseidon.exclusive_gates, 60);
+    }
+}
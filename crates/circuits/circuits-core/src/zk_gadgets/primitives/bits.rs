@@ -41,8 +41,10 @@ impl ToBitsGadget {
         num_bits: usize,
         cs: &mut PlonkCircuit,
     ) -> Result<Variable, CircuitError> {
-        let bits = Self::to_bits_unconstrained(a, num_bits, cs)?;
-        Self::bit_reconstruct(&bits, cs)
+        crate::profile_gadget!(RangeCheck, cs, {
+            let bits = Self::to_bits_unconstrained(a, num_bits, cs)?;
+            Self::bit_reconstruct(&bits, cs)
+        })
     }
 
     /// Converts a value to its bitwise representation in a single-prover
@@ -52,11 +54,13 @@ impl ToBitsGadget {
         num_bits: usize,
         cs: &mut PlonkCircuit,
     ) -> Result<Vec<BoolVar>, CircuitError> {
-        let bits = Self::to_bits_unconstrained(a, num_bits, cs)?;
-        let reconstructed = Self::bit_reconstruct(&bits, cs)?;
-        cs.enforce_equal(reconstructed, a)?;
+        crate::profile_gadget!(RangeCheck, cs, {
+            let bits = Self::to_bits_unconstrained(a, num_bits, cs)?;
+            let reconstructed = Self::bit_reconstruct(&bits, cs)?;
+            cs.enforce_equal(reconstructed, a)?;
 
-        Ok(bits)
+            Ok(bits)
+        })
     }
 
     /// Converts a value to its bitwise representation without constraining the
@@ -19,9 +19,11 @@ impl<const HEIGHT: usize> PoseidonMerkleHashGadget<HEIGHT> {
         opening: &MerkleOpeningVar<HEIGHT>,
         cs: &mut C,
     ) -> Result<Variable, CircuitError> {
-        // Hash the leaf_node into a field element
-        let leaf_hash = Self::leaf_hash(leaf_node, cs)?;
-        Self::compute_root_prehashed(leaf_hash, opening, cs)
+        crate::profile_gadget!(Merkle, cs, {
+            // Hash the leaf_node into a field element
+            let leaf_hash = Self::leaf_hash(leaf_node, cs)?;
+            Self::compute_root_prehashed(leaf_hash, opening, cs)
+        })
     }
 
     /// Compute the root given an already hashed leaf, i.e. do not hash a leaf
@@ -31,16 +33,19 @@ impl<const HEIGHT: usize> PoseidonMerkleHashGadget<HEIGHT> {
         opening: &MerkleOpeningVar<HEIGHT>,
         cs: &mut C,
     ) -> Result<Variable, CircuitError> {
-        // Hash the leaf_node into a field element
-        let mut current_hash = leaf_node;
-        for (path_elem, lr_select) in opening.elems.into_iter().zip(opening.indices.into_iter()) {
-            // Select the left and right hand sides based on whether this node in the
-            // opening represents the left or right hand child of its parent
-            let (lhs, rhs) = Self::select_left_right(current_hash, path_elem, lr_select, cs)?;
-            current_hash = Self::hash_internal_nodes(lhs, rhs, cs)?;
-        }
-
-        Ok(current_hash)
+        crate::profile_gadget!(Merkle, cs, {
+            // Hash the leaf_node into a field element
+            let mut current_hash = leaf_node;
+            for (path_elem, lr_select) in opening.elems.into_iter().zip(opening.indices.into_iter())
+            {
+                // Select the left and right hand sides based on whether this node in the
+                // opening represents the left or right hand child of its parent
+                let (lhs, rhs) = Self::select_left_right(current_hash, path_elem, lr_select, cs)?;
+                current_hash = Self::hash_internal_nodes(lhs, rhs, cs)?;
+            }
+
+            Ok(current_hash)
+        })
     }
 
     /// Compute the root and constrain it to an expected value
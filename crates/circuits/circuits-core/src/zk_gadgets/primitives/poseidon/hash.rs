@@ -197,12 +197,17 @@ impl PoseidonHashGadget {
     // -----------------------
 
     /// Permute the state using the Poseidon 2 permutation
+    fn permute<C: Circuit<ScalarField>>(&mut self, cs: &mut C) -> Result<(), CircuitError> {
+        crate::profile_gadget!(Poseidon, cs, { self.permute_rounds(cs) })
+    }
+
+    /// Apply the rounds of the Poseidon 2 permutation
     ///
     /// Throughout the permutation, the arithmetization fuses the gates in
     /// between rounds, adding the round constants for the next round after the
     /// MDS multiplication in the current round.
     #[allow(clippy::missing_docs_in_private_items)]
-    fn permute<C: Circuit<ScalarField>>(&mut self, cs: &mut C) -> Result<(), CircuitError> {
+    fn permute_rounds<C: Circuit<ScalarField>>(&mut self, cs: &mut C) -> Result<(), CircuitError> {
         // Begin by multiplying by the external round matrix
         // Fuse with the first round's constants
         self.external_mds_with_rc(&FULL_ROUND_CONSTANTS[0], cs)?;
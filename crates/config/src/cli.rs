@@ -11,10 +11,15 @@ use std::{
     net::{IpAddr, SocketAddr},
     path::Path,
 };
-use types_core::{Chain, Exchange, HmacKey, Token};
-use types_gossip::{ClusterAsymmetricKeypair, ClusterId, WrappedPeerId};
+use types_core::{
+    BlackoutWindow, Chain, ConfirmationDepth, Exchange, HmacKey, PriceAggregationStrategy, Token,
+    VwapWindow,
+};
+use types_gossip::{
+    BootstrapDnsSeed, ClusterAsymmetricKeypair, ClusterId, HeartbeatIntervals, WrappedPeerId,
+};
 use url::Url;
-use util::telemetry::configure_telemetry;
+use util::telemetry::{LogFormat, configure_telemetry, set_log_context};
 
 use crate::parsing::{parse_config_from_args, utils::parse_cli_map};
 
@@ -50,7 +55,7 @@ pub struct Cli {
     pub event_export_url: Option<String>,
     /// The price reporter from which to stream prices.
     /// If unset, the relayer will connect to exchanges directly.
-    #[clap(long, value_parser, conflicts_with_all = &["coinbase_key_name", "coinbase_key_secret"])]
+    #[clap(long, value_parser)]
     pub price_reporter_url: Option<String>,
     /// The URL (host:port) of the external prover service to use for Plonk and link proofs
     /// 
@@ -74,6 +79,12 @@ pub struct Cli {
     /// The minimum amount of the quote asset that the relayer should settle matches on
     #[clap(long, value_parser, default_value = "0")]
     pub min_fill_size: Amount,
+    /// Whether to run the matching engine in quote-only mode
+    ///
+    /// In quote-only mode internal matches are found and risk checked, then recorded as
+    /// indicative fills in the match audit trail rather than settled
+    #[clap(long, value_parser)]
+    pub quote_only_matching: bool,
     /// The maximum amount that a relayer will charge as a fee for a match
     /// 
     /// Defaults to 10 basis points
@@ -95,6 +106,32 @@ pub struct Cli {
     /// The block deadline is set to current_block + this value
     #[clap(long, value_parser, default_value = "50")]
     pub external_match_validity_window: u64,
+    /// The minimum confidence, between 0 and 1, in a price at which the relayer will match
+    ///
+    /// Confidence is scored from the number of exchanges reporting a price, the spread between
+    /// them, and the price's age. Defaults to 0, which matches at any nominal price
+    #[clap(long, value_parser, default_value = "0")]
+    pub min_price_confidence: f64,
+    /// The amount of the quote asset at or above which a match is priced off the VWAP over
+    /// `vwap-pricing-window` rather than the instantaneous price
+    ///
+    /// Falls back to the instantaneous price while no VWAP is available. Disabled if unset
+    #[clap(long, value_parser)]
+    pub vwap_pricing_threshold: Option<Amount>,
+    /// The trailing window over which large matches are priced off the VWAP: one of `1m`, `5m`
+    /// or `30m`
+    ///
+    /// Defaults to `5m`
+    #[clap(long, value_parser, default_value = "5m")]
+    pub vwap_pricing_window: VwapWindow,
+    /// Windows during which the relayer neither initiates nor accepts new matches, e.g. around
+    /// known oracle updates or maintenance
+    ///
+    /// Each window is given as `<start>-<end>`, or `<start>-<end>/<period>` for a window that
+    /// recurs every period, with all values in milliseconds. More windows may be added at
+    /// runtime through the admin API
+    #[clap(long, value_parser, num_args=1.., value_delimiter=' ')]
+    pub matching_blackout_windows: Vec<BlackoutWindow>,
     /// The address at which to collect relayer fees
     /// 
     /// This is the address at which the relayer collects match fees.
@@ -108,13 +145,20 @@ pub struct Cli {
     /// The chain that the relayer settles to
     #[clap(long, value_parser, default_value = "arbitrum-sepolia", env = "CHAIN")]
     pub chain_id: Chain,
+    /// The depth at which a submitted transaction is considered confirmed, either a
+    /// number of blocks (counting the transaction's own block), `safe`, or `finalized`
+    /// 
+    /// Defaults to the chain's default: one block on rollups, `safe` on Ethereum Sepolia,
+    /// and `finalized` on Ethereum Mainnet
+    #[clap(long, value_parser)]
+    pub tx_confirmation_depth: Option<ConfirmationDepth>,
     /// The address of the darkpool contract, defaults to the internal testnet deployment
     #[clap(long, value_parser, env = "DARKPOOL_ADDRESS")]
     pub contract_address: String,
     /// The address of the permit2 contract
     #[clap(long, value_parser, env = "PERMIT2_ADDRESS")]
     pub permit2_address: String,
-    /// The path to the file containing token remaps for the given chain
+    /// The path to the file containing token remaps for the given chain, in JSON or, with a `.toml` extension, TOML
     /// 
     /// See https://github.com/renegade-fi/token-mappings for more information on the format of this file
     #[clap(long, value_parser)]
@@ -138,6 +182,34 @@ pub struct Cli {
     /// Defaults to 30s
     #[clap(long, value_parser, default_value = "30000")]
     pub gossip_warmup: u64,
+    /// The base interval at which to heartbeat peers outside the local cluster,
+    /// in milliseconds
+    #[clap(long, value_parser, default_value = "10000")]
+    pub heartbeat_interval_ms: u64,
+    /// The interval at which to heartbeat peers in the local cluster, in
+    /// milliseconds
+    #[clap(long, value_parser, default_value = "3000")]
+    pub cluster_heartbeat_interval_ms: u64,
+    /// The base time without a heartbeat after which a peer outside the local
+    /// cluster is assumed to have failed, in milliseconds
+    #[clap(long, value_parser, default_value = "30000")]
+    pub heartbeat_failure_ms: u64,
+    /// The time without a heartbeat after which a peer in the local cluster is
+    /// assumed to have failed, in milliseconds
+    #[clap(long, value_parser, default_value = "15000")]
+    pub cluster_heartbeat_failure_ms: u64,
+    /// The maximum factor by which the heartbeat interval and failure threshold
+    /// of a stable peer outside the local cluster are backed off
+    ///
+    /// Set to 1 to disable backoff
+    #[clap(long, value_parser, default_value = "4")]
+    pub heartbeat_max_backoff: u64,
+    /// The time, in milliseconds, a peer outside the local cluster must
+    /// heartbeat consistently for each doubling of its heartbeat interval
+    ///
+    /// Set to 0 to disable backoff
+    #[clap(long, value_parser, default_value = "300000")]
+    pub heartbeat_backoff_after_ms: u64,
     
     // -------------------------
     // | Cluster Configuration |
@@ -160,6 +232,75 @@ pub struct Cli {
     /// The bootstrap servers that the peer should dial initially
     #[clap(short, long, value_parser, env = "BOOTSTRAP_SERVERS", use_value_delimiter = true)]
     pub bootstrap_servers: Option<Vec<String>>,
+    /// The DNS names to resolve bootstrap peers from, re-resolved periodically
+    ///
+    /// Each is `txt:<name>`, whose TXT records hold `dnsaddr=<multiaddr>`
+    /// entries, or `srv:_<service>._<udp|tcp>.<domain>`, whose SRV records
+    /// point to bootstrap peers with a `peer_id=<peer id>` TXT record on each
+    /// target host. A bare name is a TXT name
+    #[clap(long, value_parser, env = "BOOTSTRAP_DNS", use_value_delimiter = true)]
+    pub bootstrap_dns: Vec<String>,
+    /// The interval at which to re-resolve the bootstrap DNS names, in
+    /// milliseconds
+    #[clap(long, value_parser, default_value = "300000", env = "BOOTSTRAP_DNS_REFRESH_MS")]
+    pub bootstrap_dns_refresh_ms: u64,
+    /// The peer IDs to exclude from the peer index and refuse to talk to
+    #[clap(long, value_parser, env = "PEER_BLOCKLIST", use_value_delimiter = true)]
+    pub peer_blocklist: Vec<String>,
+    /// The only peer IDs to index and talk to, e.g. the nodes of a private
+    /// cluster
+    ///
+    /// If empty, all peers not blocked are allowed
+    #[clap(long, value_parser, env = "PEER_ALLOWLIST", use_value_delimiter = true)]
+    pub peer_allowlist: Vec<String>,
+    /// The largest clock difference, in milliseconds, tolerated between the
+    /// local node and a cluster peer bootstrapping into the network through it
+    ///
+    /// Heartbeat expiry, task timestamps, and invisibility windows all assume
+    /// roughly synchronized clocks across the cluster. Peers beyond this are
+    /// warned on, or refused if `--reject-clock-skewed-peers` is set
+    #[clap(long, value_parser, default_value = "7500")]
+    pub max_cluster_clock_skew_ms: u64,
+    /// Whether to refuse cluster peers whose clocks differ from the local
+    /// clock by more than `--max-cluster-clock-skew-ms` when they join
+    #[clap(long, value_parser)]
+    pub reject_clock_skewed_peers: bool,
+    /// Whether to accept order book messages that carry no origin signature
+    ///
+    /// Set while a cluster is upgraded to nodes that sign the origin of their
+    /// order book messages; messages with an invalid signature are rejected
+    /// regardless
+    #[clap(long, value_parser)]
+    pub allow_unsigned_order_book_messages: bool,
+    /// The interval at which to reconcile the order book with a peer, in
+    /// milliseconds
+    ///
+    /// Each interval, the relayer exchanges an order book digest with the next
+    /// known peer and fetches the orders it missed. Zero disables the sync
+    #[clap(long, value_parser, default_value = "60000", env = "ORDER_BOOK_SYNC_INTERVAL_MS")]
+    pub order_book_sync_interval_ms: u64,
+    /// The number of heartbeats per minute the relayer handles from each peer
+    ///
+    /// Heartbeats beyond this are dropped. A value of zero disables the limit
+    #[clap(long, value_parser, default_value = "120")]
+    pub gossip_heartbeat_rate_limit: u32,
+    /// The number of order book messages per minute the relayer handles from
+    /// each peer
+    ///
+    /// A value of zero disables the limit
+    #[clap(long, value_parser, default_value = "600")]
+    pub gossip_order_rate_limit: u32,
+    /// The number of messages carrying validity proofs per minute the relayer
+    /// handles from each peer
+    ///
+    /// Each such message is verified, so this bounds the verification work a
+    /// single peer may impose. A value of zero disables the limit
+    #[clap(long, value_parser, default_value = "60")]
+    pub gossip_proof_rate_limit: u32,
+    /// How long to drop all rate limited messages from a peer that repeatedly
+    /// exceeds its gossip rate limits, in milliseconds
+    #[clap(long, value_parser, default_value = "60000")]
+    pub gossip_rate_limit_penalty_ms: u64,
     /// The cluster private key to use
     #[clap(long = "cluster-private-key", value_parser, env = "CLUSTER_PRIVATE_KEY")]
     pub cluster_private_key: Option<String>,
@@ -175,6 +316,13 @@ pub struct Cli {
     /// If not set, the admin API is disabled
     #[clap(long, value_parser, env = "ADMIN_API_KEY")]
     pub admin_api_key: Option<String>,
+    /// The keys of the tenants served by the relayer's API, each a symmetric
+    /// key encoded as a base64 string
+    ///
+    /// A tenant signs its requests with its key, and may only access the
+    /// accounts it created. Mapping from tenant ID to key
+    #[clap(long, value_parser, value_parser = parse_cli_map::<String>, default_value = "")]
+    pub api_tenant_keys: HashMap<String, String>,
 
     // ----------------------------
     // | Local Node Configuration |
@@ -209,14 +357,93 @@ pub struct Cli {
     /// The path at which to save raft snapshots
     #[clap(long, value_parser, env = "RAFT_SNAPSHOT_PATH", default_value = "/raft_snapshots")]
     pub raft_snapshot_path: String,
+    /// The directory in which to write state exports triggered via the admin API
+    #[clap(long, value_parser, env = "STATE_EXPORT_PATH", default_value = "/state_exports")]
+    pub state_export_path: String,
     /// Whether to record historical state locally
     #[clap(long, value_parser)]
     pub record_historical_state: bool,
+    /// Whether to cross-check the state's indices against the data they index at startup,
+    /// logging any inconsistencies found
+    #[clap(long, value_parser)]
+    pub fsck: bool,
+    /// Whether to repair the inconsistencies found by `--fsck`
+    /// 
+    /// The repair is proposed through raft once the node has started
+    #[clap(long, value_parser, requires = "fsck")]
+    pub fsck_repair: bool,
+    /// Whether to dry-apply state transitions against the local state before proposing them,
+    /// rejecting those that could not apply (e.g. duplicate account IDs) without a raft round
+    #[clap(long, value_parser)]
+    pub validate_proposals: bool,
     /// The maximum number of wallet operations a user is allowed to perform per hour
     /// 
     /// Defaults to 500
     #[clap(long, value_parser, default_value = "500")]
     pub wallet_task_rate_limit: u32,
+    /// The number of read requests per minute each API client may make to the HTTP API
+    ///
+    /// Each source IP is limited before its requests are authenticated, and authenticated
+    /// requests are further limited by the tenant or API key they are signed with. A value of
+    /// zero disables the limit. Defaults to 600
+    #[clap(long, value_parser, default_value = "600")]
+    pub api_read_rate_limit: u32,
+    /// The number of mutating (POST) requests per minute each API client may make to the HTTP
+    /// API
+    ///
+    /// A value of zero disables the limit. Defaults to 60
+    #[clap(long, value_parser, default_value = "60")]
+    pub api_write_rate_limit: u32,
+    /// Whether to identify API clients by the last `X-Forwarded-For` entry rather than the
+    /// connection's source IP when rate limiting
+    ///
+    /// Only enable this when the relayer sits behind a proxy that appends the header
+    #[clap(long, value_parser)]
+    pub api_trust_forwarded_for: bool,
+    /// The number of messages that may be queued for a websocket connection before its client
+    /// is considered slow
+    ///
+    /// Defaults to 1024
+    #[clap(long, value_parser, default_value = "1024")]
+    pub websocket_send_queue_size: usize,
+    /// Whether to close slow websocket clients rather than drop the messages that do not fit in
+    /// their send queue
+    #[clap(long, value_parser)]
+    pub websocket_close_slow_clients: bool,
+    /// The maximum number of open websocket connections, zero if unlimited
+    ///
+    /// Connections beyond the limit are closed before the websocket handshake. Defaults to 4096
+    #[clap(long, value_parser, default_value = "4096")]
+    pub websocket_max_connections: usize,
+    /// The maximum number of open websocket connections from a single IP, zero if unlimited
+    ///
+    /// Defaults to 32
+    #[clap(long, value_parser, default_value = "32")]
+    pub websocket_max_connections_per_ip: usize,
+    /// The minimum size in bytes of an HTTP response body for it to be compressed
+    ///
+    /// Responses are only compressed when the client accepts gzip or deflate. Defaults to 1024
+    #[clap(long, value_parser, default_value = "1024")]
+    pub api_compression_min_size: usize,
+    /// The content types of HTTP responses that may be compressed
+    ///
+    /// Defaults to `application/json`
+    #[clap(long, value_parser, num_args=1.., value_delimiter=' ', default_value = "application/json")]
+    pub api_compression_content_types: Vec<String>,
+    /// The origins allowed to make cross-origin requests to the HTTP API, e.g.
+    /// `https://trade.example.com`
+    ///
+    /// Defaults to `*`, allowing any origin
+    #[clap(long, value_parser, num_args=1.., value_delimiter=' ', default_value = "*")]
+    pub api_cors_allowed_origins: Vec<String>,
+    /// Whether cross-origin requests to the HTTP API may carry credentials
+    #[clap(long, value_parser)]
+    pub api_cors_allow_credentials: bool,
+    /// The number of hours of price history to record for the price history API
+    ///
+    /// A value of zero disables recording. Defaults to 24
+    #[clap(long, value_parser, default_value = "24")]
+    pub price_history_retention_hours: u64,
     /// The minimum usdc denominated value for a deposit or withdrawal
     /// 
     /// Defaults to 1 USDC (ignoring decimals)
@@ -232,21 +459,82 @@ pub struct Cli {
     /// Disables exchanges for price reporting
     #[clap(long, value_parser, num_args=1.., value_delimiter=' ')]
     pub disabled_exchanges: Vec<Exchange>,
+    /// The Uniswap V3 pools from which to read TWAP prices, in place of the
+    /// external price reporter's spot prices
+    ///
+    /// Mapping from base ticker to the address of the ticker's USDC pool
+    #[clap(long, value_parser, value_parser = parse_cli_map::<Address>, default_value = "")]
+    pub uniswap_twap_pools: HashMap<String, Address>,
+    /// The TWAP window, in seconds, for each Uniswap V3 pool
+    ///
+    /// Mapping from base ticker to window length. Pools without a window use 30 minutes
+    #[clap(long, value_parser, value_parser = parse_cli_map::<u32>, default_value = "")]
+    pub uniswap_twap_windows: HashMap<String, u32>,
+    /// The interval, in milliseconds, at which exchange REST ticker endpoints are
+    /// polled while the connection to the external price reporter keeps failing
+    ///
+    /// A value of zero disables the fallback. Defaults to 5000
+    #[clap(long, value_parser, default_value = "5000")]
+    pub price_rest_fallback_interval_ms: u64,
+    /// The age, in milliseconds, past which a price stream's last report is stale.
+    /// Matches are not priced on a stale stream
+    ///
+    /// Defaults to 10000
+    #[clap(long, value_parser, default_value = "10000")]
+    pub price_staleness_threshold_ms: u64,
+    /// The strategy by which exchange prices are aggregated into a reference price:
+    /// one of `median`, `weighted-median`, `trimmed-mean` or `midpoint`
+    ///
+    /// Defaults to `median`
+    #[clap(long, value_parser, default_value = "median")]
+    pub price_aggregation_strategy: PriceAggregationStrategy,
+    /// The aggregation strategy for individual pairs, in place of the default
+    ///
+    /// Mapping from base ticker to strategy
+    #[clap(long, value_parser, value_parser = parse_cli_map::<PriceAggregationStrategy>, default_value = "")]
+    pub price_aggregation_strategies: HashMap<String, PriceAggregationStrategy>,
+    /// The percentage by which an exchange's price may deviate from the median of
+    /// all exchange prices for the pair before the exchange is excluded from the
+    /// pair's reference price
+    ///
+    /// A value of zero disables the check. Defaults to 5
+    #[clap(long, value_parser, default_value = "5")]
+    pub price_max_source_deviation_pct: f64,
+    /// The routes through which to price tokens with no direct market, e.g.
+    /// TOKEN=WETH prices TOKEN/USDC as TOKEN/WETH × WETH/USDC
+    ///
+    /// Each exchange prices the intermediate in its own quote, e.g. WETH/USDT on an exchange
+    /// quoting in USDT
+    ///
+    /// Mapping from base ticker to the ticker of the intermediate token
+    #[clap(long, value_parser, value_parser = parse_cli_map::<String>, default_value = "")]
+    pub synthetic_price_routes: HashMap<String, String>,
     /// Assets for which to disable matching (by ticker)
     #[clap(long, value_parser, num_args=1.., value_delimiter=' ')]
     pub disabled_assets: Vec<String>,
+    /// The only assets for which to allow matching (by ticker)
+    ///
+    /// If empty, all assets not disabled are allowed
+    #[clap(long, value_parser, num_args=1.., value_delimiter=' ')]
+    pub allowed_assets: Vec<String>,
 
     // -----------
     // | Secrets |
     // -----------
     /// The Coinbase API key name to use for price streaming
-    #[clap(long = "coinbase-key-name", value_parser)]
+    #[clap(long = "coinbase-key-name", value_parser, requires = "coinbase_key_secret")]
     pub coinbase_key_name: Option<String>,
     /// The Coinbase API secret to use for price streaming
     /// 
     /// Expected as a PKCS#8 encoded ES256 private key 
     #[clap(long = "coinbase-key-secret", value_parser)]
     pub coinbase_key_secret: Option<String>,
+    /// The Binance API key with which exchange requests are authenticated
+    ///
+    /// Binance market data requests are authenticated by the key alone, so no
+    /// secret is taken
+    #[clap(long = "binance-api-key", value_parser, env = "BINANCE_API_KEY")]
+    pub binance_api_key: Option<String>,
     /// The Ethereum RPC node websocket address to dial for on-chain data
     #[clap(long = "eth-websocket-url", value_parser, env = "ETH_WEBSOCKET_URL")]
     pub eth_websocket_addr: Option<String>,
@@ -279,6 +567,13 @@ pub struct Cli {
     /// Whether or not to enable Datadog-formatted logs
     #[clap(long = "enable-datadog", value_parser)]
     pub datadog_enabled: bool,
+    /// The format in which logs are written: `pretty`, or `json` for one JSON
+    /// object per record carrying the worker, peer, cluster, request, task, and
+    /// span fields
+    ///
+    /// Ignored if Datadog-formatted logs are enabled
+    #[clap(long, value_parser, default_value = "pretty")]
+    pub log_format: LogFormat,
     /// Whether or not to enable metrics collection
     #[clap(long = "enable-metrics", value_parser)]
     pub metrics_enabled: bool,
@@ -304,6 +599,9 @@ pub struct RelayerConfig {
     /// The minimum amount of the quote asset that the relayer should settle
     /// matches on
     pub min_fill_size: Amount,
+    /// Whether the matching engine records internal matches as indicative
+    /// fills rather than settling them
+    pub quote_only_matching: bool,
     /// The maximum amount that a relayer will charge as a fee for a match
     pub max_match_fee: FixedPoint,
     /// The default match fee that the relayer will charge
@@ -315,6 +613,16 @@ pub struct RelayerConfig {
     pub per_asset_fees: HashMap<String, FixedPoint>,
     /// The number of blocks an external match bundle remains valid
     pub external_match_validity_window: u64,
+    /// The minimum confidence in a price at which the relayer will match
+    pub min_price_confidence: f64,
+    /// The amount of the quote asset at or above which a match is priced off
+    /// the VWAP rather than the instantaneous price, if enabled
+    pub vwap_pricing_threshold: Option<Amount>,
+    /// The trailing window over which large matches are priced off the VWAP
+    pub vwap_pricing_window: VwapWindow,
+    /// The windows during which no new matches are made, in addition to those
+    /// set at runtime
+    pub matching_blackout_windows: Vec<BlackoutWindow>,
     /// The address at which the relayer collects match fees
     ///
     /// This is the address at which the relayer collects match fees.
@@ -352,6 +660,8 @@ pub struct RelayerConfig {
     // -----------------------
     /// The chain that the relayer settles to
     pub chain_id: Chain,
+    /// The depth at which a submitted transaction is considered confirmed
+    pub tx_confirmation_depth: ConfirmationDepth,
     /// The address of the contract in the target network
     pub contract_address: Address,
     /// The address of the permit2 contract
@@ -368,6 +678,11 @@ pub struct RelayerConfig {
     pub public_ip: Option<SocketAddr>,
     /// The amount of time to allow for gossip warmup, in milliseconds
     pub gossip_warmup: u64,
+    /// The intervals and failure thresholds of the heartbeat protocol
+    ///
+    /// These seed the runtime heartbeat settings, which may be adjusted through
+    /// the admin API
+    pub heartbeat_intervals: HeartbeatIntervals,
 
     // -------------------------
     // | Cluster Configuration |
@@ -384,6 +699,42 @@ pub struct RelayerConfig {
     pub raft_seed: bool,
     /// Bootstrap servers that the peer should connect to
     pub bootstrap_servers: Vec<(WrappedPeerId, Multiaddr)>,
+    /// The DNS names to resolve bootstrap peers from
+    pub bootstrap_dns: Vec<BootstrapDnsSeed>,
+    /// The interval at which to re-resolve the bootstrap DNS names, in
+    /// milliseconds
+    pub bootstrap_dns_refresh_ms: u64,
+    /// The peers to exclude from the peer index and refuse to talk to
+    pub peer_blocklist: Vec<WrappedPeerId>,
+    /// The only peers to index and talk to, all peers not blocked if empty
+    ///
+    /// These seed the runtime access lists, which may be adjusted through the
+    /// admin API
+    pub peer_allowlist: Vec<WrappedPeerId>,
+    /// The largest clock difference, in milliseconds, tolerated between the
+    /// local node and a joining cluster peer
+    pub max_cluster_clock_skew_ms: u64,
+    /// Whether to refuse joining cluster peers whose clocks differ by more
+    /// than `max_cluster_clock_skew_ms`, rather than warn on them
+    pub reject_clock_skewed_peers: bool,
+    /// Whether to accept order book messages that carry no origin signature,
+    /// from peers that predate origin signing
+    pub allow_unsigned_order_book_messages: bool,
+    /// The interval at which to reconcile the order book with a peer, in
+    /// milliseconds; zero disables order book sync
+    pub order_book_sync_interval_ms: u64,
+    /// The number of heartbeats per minute handled from each peer, zero for
+    /// no limit
+    pub gossip_heartbeat_rate_limit: u32,
+    /// The number of order book messages per minute handled from each peer,
+    /// zero for no limit
+    pub gossip_order_rate_limit: u32,
+    /// The number of proof carrying messages per minute handled from each
+    /// peer, zero for no limit
+    pub gossip_proof_rate_limit: u32,
+    /// How long to drop all rate limited messages from a peer that repeatedly
+    /// exceeds its gossip rate limits, in milliseconds
+    pub gossip_rate_limit_penalty_ms: u64,
     /// The cluster keypair
     pub cluster_keypair: ClusterAsymmetricKeypair,
     /// The cluster symmetric keypair
@@ -394,6 +745,8 @@ pub struct RelayerConfig {
     ///
     /// If not set, the admin API is disabled
     pub admin_api_key: Option<HmacKey>,
+    /// The keys of the tenants served by the relayer's API, keyed by tenant ID
+    pub api_tenant_keys: HashMap<String, HmacKey>,
 
     // ----------------------------
     // | Local Node Configuration |
@@ -410,11 +763,50 @@ pub struct RelayerConfig {
     pub db_path: String,
     /// The path at which to save raft snapshots
     pub raft_snapshot_path: String,
+    /// The directory in which to write state exports
+    pub state_export_path: String,
     /// Whether to record historical state locally
     pub record_historical_state: bool,
+    /// Whether to check the consistency of the state's indices at startup
+    pub fsck: bool,
+    /// Whether to repair inconsistencies found by the startup check
+    pub fsck_repair: bool,
+    /// Whether to dry-apply state transitions before proposing them
+    pub validate_proposals: bool,
     /// The maximum number of wallet operations a user is allowed to perform per
     /// hour
     pub wallet_task_rate_limit: u32,
+    /// The number of read requests per minute each API client may make, zero
+    /// if unlimited
+    pub api_read_rate_limit: u32,
+    /// The number of mutating requests per minute each API client may make,
+    /// zero if unlimited
+    pub api_write_rate_limit: u32,
+    /// Whether to identify API clients by the `X-Forwarded-For` header when
+    /// rate limiting
+    pub api_trust_forwarded_for: bool,
+    /// The number of messages that may be queued for a websocket connection
+    /// before its client is considered slow
+    pub websocket_send_queue_size: usize,
+    /// Whether to close slow websocket clients rather than drop the messages
+    /// that do not fit in their send queue
+    pub websocket_close_slow_clients: bool,
+    /// The maximum number of open websocket connections, zero if unlimited
+    pub websocket_max_connections: usize,
+    /// The maximum number of open websocket connections from a single IP,
+    /// zero if unlimited
+    pub websocket_max_connections_per_ip: usize,
+    /// The minimum size in bytes of an HTTP response body for it to be
+    /// compressed
+    pub api_compression_min_size: usize,
+    /// The content types of HTTP responses that may be compressed
+    pub api_compression_content_types: Vec<String>,
+    /// The origins allowed to make cross-origin requests, `*` for any origin
+    pub api_cors_allowed_origins: Vec<String>,
+    /// Whether cross-origin requests may carry credentials
+    pub api_cors_allow_credentials: bool,
+    /// The number of hours of price history to record, zero if disabled
+    pub price_history_retention_hours: u64,
     /// The minimum usdc denominated value for a deposit or withdrawal
     pub min_transfer_amount: f64,
     /// The maximum staleness (number of newer roots observed) to allow on
@@ -426,8 +818,34 @@ pub struct RelayerConfig {
     pub disable_price_reporter: bool,
     /// The exchanges explicitly disabled for price reports
     pub disabled_exchanges: Vec<Exchange>,
+    /// The Uniswap V3 pools from which TWAP prices are read, keyed by base
+    /// ticker
+    pub uniswap_twap_pools: HashMap<String, Address>,
+    /// The TWAP window in seconds for each Uniswap V3 pool, keyed by base
+    /// ticker
+    pub uniswap_twap_windows: HashMap<String, u32>,
+    /// The interval in milliseconds at which exchange REST tickers are polled
+    /// while the external price reporter connection fails, zero if disabled
+    pub price_rest_fallback_interval_ms: u64,
+    /// The age in milliseconds past which a price stream's last report is
+    /// stale
+    pub price_staleness_threshold_ms: u64,
+    /// The strategy by which exchange prices are aggregated for pairs without
+    /// a strategy of their own
+    pub price_aggregation_strategy: PriceAggregationStrategy,
+    /// The aggregation strategy for individual pairs, keyed by base ticker
+    pub price_aggregation_strategies: HashMap<String, PriceAggregationStrategy>,
+    /// The percentage by which an exchange price may deviate from the median
+    /// of its pair's exchange prices before it is excluded, zero if unlimited
+    pub price_max_source_deviation_pct: f64,
+    /// The intermediate token through which each token with no direct market
+    /// is priced, keyed by base ticker
+    pub synthetic_price_routes: HashMap<String, String>,
     /// Assets for which matching is disabled (by ticker)
     pub disabled_assets: Vec<String>,
+    /// The only assets for which matching is allowed (by ticker), all assets
+    /// not disabled are allowed if empty
+    pub allowed_assets: Vec<String>,
 
     // -----------
     // | Secrets |
@@ -438,6 +856,8 @@ pub struct RelayerConfig {
     pub coinbase_key_name: Option<String>,
     /// The Coinbase API secret to use for price streaming
     pub coinbase_key_secret: Option<String>,
+    /// The Binance API key with which exchange requests are authenticated
+    pub binance_api_key: Option<String>,
     /// The HTTP addressable Ethereum JSON-RPC node
     pub rpc_url: Option<String>,
     /// The private key used to send transactions
@@ -456,6 +876,8 @@ pub struct RelayerConfig {
     pub otlp_collector_url: String,
     /// Whether or not to enable Datadog-formatted logs
     pub datadog_enabled: bool,
+    /// The format in which logs are written
+    pub log_format: LogFormat,
     /// Whether or not to enable metrics collection
     pub metrics_enabled: bool,
     /// The StatsD recorder host to send metrics to
@@ -503,8 +925,10 @@ impl RelayerConfig {
 
     /// Configure the telemetry layers from the relayer config
     pub fn configure_telemetry(&self) -> Result<(), String> {
+        set_log_context(self.peer_id(), &self.cluster_id);
         configure_telemetry(
             self.datadog_enabled,
+            self.log_format,
             self.otlp_enabled,
             self.metrics_enabled,
             self.otlp_collector_url.clone(),
@@ -588,4 +1012,42 @@ mod test {
         config.raft_seed = true;
         assert!(config.is_raft_seed());
     }
+
+    /// Test that the confirmation depth defaults to the chain's default
+    #[test]
+    fn test_default_confirmation_depth() {
+        let config = RelayerConfig::default();
+        let expected = config.chain_id.default_confirmation_depth();
+        assert_eq!(config.tx_confirmation_depth, expected);
+    }
+
+    /// Test that quote-only matching is off unless the flag is given
+    #[test]
+    fn test_quote_only_matching_flag() {
+        use clap::Parser;
+
+        use crate::Cli;
+
+        assert!(!RelayerConfig::default().quote_only_matching);
+
+        let required_args = [
+            "relayer",
+            "--contract-address",
+            "0x0",
+            "--relayer-fee-addr",
+            "0x0",
+            "--permit2-address",
+            "0x0",
+            "--indexer-url",
+            "http://localhost:8080",
+            "--indexer-hmac-key",
+            "key",
+            "--executor-private-key",
+            "0x0",
+        ];
+        let cli = Cli::parse_from(required_args);
+        assert!(!cli.quote_only_matching);
+        let cli = Cli::parse_from(required_args.into_iter().chain(["--quote-only-matching"]));
+        assert!(cli.quote_only_matching);
+    }
 }
@@ -7,4 +7,7 @@ mod token_remaps;
 mod validation;
 
 pub use cli::*;
-pub use token_remaps::{fetch_remap_from_repo, parse_remap_from_file, setup_token_remaps};
+pub use token_remaps::{
+    TokenInfo, add_token, check_token_listing, fetch_remap_from_repo, parse_remap_from_file,
+    setup_token_remaps,
+};
@@ -7,7 +7,7 @@ use circuit_types::fixed_point::FixedPoint;
 use clap::Parser;
 use constants::set_bootstrap_mode;
 use libp2p::{Multiaddr, PeerId, identity::Keypair};
-use types_gossip::{ClusterId, WrappedPeerId};
+use types_gossip::{BootstrapDnsSeed, ClusterId, HeartbeatIntervals, WrappedPeerId};
 use url::Url;
 use util::hex::address_from_hex_string;
 
@@ -100,6 +100,11 @@ pub fn parse_command_line_args() -> Result<RelayerConfig, String> {
 pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, String> {
     let (cluster_symmetric_key, cluster_keypair) = parse_cluster_keys(&cli_args)?;
     let admin_api_key = cli_args.admin_api_key.map(parse_symmetric_key).transpose()?;
+    let api_tenant_keys = cli_args
+        .api_tenant_keys
+        .into_iter()
+        .map(|(tenant, key)| parse_symmetric_key(key).map(|key| (tenant, key)))
+        .collect::<Result<_, _>>()?;
 
     // Parse the local relayer's keys and fee configuration from the CLI
     let private_key =
@@ -135,6 +140,31 @@ pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, Str
             .expect("Invalid address passed as --bootstrap-server");
         parsed_bootstrap_addrs.push((WrappedPeerId(peer_id), parsed_addr));
     }
+    let bootstrap_dns = cli_args
+        .bootstrap_dns
+        .iter()
+        .map(|seed| BootstrapDnsSeed::from_str(seed))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Parse the peer access lists
+    let parse_peer_ids = |ids: &[String]| -> Result<Vec<WrappedPeerId>, String> {
+        ids.iter()
+            .map(|id| WrappedPeerId::from_str(id).map_err(|e| format!("invalid peer ID {id}: {e}")))
+            .collect()
+    };
+    let peer_blocklist = parse_peer_ids(&cli_args.peer_blocklist)?;
+    let peer_allowlist = parse_peer_ids(&cli_args.peer_allowlist)?;
+
+    // Parse the heartbeat intervals
+    let heartbeat_intervals = HeartbeatIntervals {
+        interval_ms: cli_args.heartbeat_interval_ms,
+        cluster_interval_ms: cli_args.cluster_heartbeat_interval_ms,
+        failure_ms: cli_args.heartbeat_failure_ms,
+        cluster_failure_ms: cli_args.cluster_heartbeat_failure_ms,
+        max_backoff: cli_args.heartbeat_max_backoff,
+        backoff_after_ms: cli_args.heartbeat_backoff_after_ms,
+    };
+    heartbeat_intervals.validate()?;
 
     // --- Parse Service URLs --- //
     let compliance_service_url = cli_args
@@ -164,13 +194,21 @@ pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, Str
 
     let config = RelayerConfig {
         min_fill_size: cli_args.min_fill_size,
+        quote_only_matching: cli_args.quote_only_matching,
         max_match_fee,
         default_match_fee,
         per_asset_fees,
         external_match_validity_window: cli_args.external_match_validity_window,
+        min_price_confidence: cli_args.min_price_confidence,
+        vwap_pricing_threshold: cli_args.vwap_pricing_threshold,
+        vwap_pricing_window: cli_args.vwap_pricing_window,
+        matching_blackout_windows: cli_args.matching_blackout_windows,
         relayer_fee_addr,
         price_reporter_url,
         chain_id: cli_args.chain_id,
+        tx_confirmation_depth: cli_args
+            .tx_confirmation_depth
+            .unwrap_or_else(|| cli_args.chain_id.default_confirmation_depth()),
         contract_address,
         permit2_address,
         compliance_service_url,
@@ -181,6 +219,18 @@ pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, Str
         bootstrap_mode: cli_args.bootstrap_mode,
         raft_seed: cli_args.raft_seed,
         bootstrap_servers: parsed_bootstrap_addrs,
+        bootstrap_dns,
+        bootstrap_dns_refresh_ms: cli_args.bootstrap_dns_refresh_ms,
+        peer_blocklist,
+        peer_allowlist,
+        max_cluster_clock_skew_ms: cli_args.max_cluster_clock_skew_ms,
+        reject_clock_skewed_peers: cli_args.reject_clock_skewed_peers,
+        allow_unsigned_order_book_messages: cli_args.allow_unsigned_order_book_messages,
+        order_book_sync_interval_ms: cli_args.order_book_sync_interval_ms,
+        gossip_heartbeat_rate_limit: cli_args.gossip_heartbeat_rate_limit,
+        gossip_order_rate_limit: cli_args.gossip_order_rate_limit,
+        gossip_proof_rate_limit: cli_args.gossip_proof_rate_limit,
+        gossip_rate_limit_penalty_ms: cli_args.gossip_rate_limit_penalty_ms,
         p2p_port: cli_args.p2p_port,
         http_port: cli_args.http_port,
         websocket_port: cli_args.websocket_port,
@@ -189,22 +239,50 @@ pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, Str
         p2p_key,
         db_path: cli_args.db_path,
         raft_snapshot_path: cli_args.raft_snapshot_path,
+        state_export_path: cli_args.state_export_path,
         record_historical_state: cli_args.record_historical_state,
+        fsck: cli_args.fsck,
+        fsck_repair: cli_args.fsck_repair,
+        validate_proposals: cli_args.validate_proposals,
         event_export_url,
         wallet_task_rate_limit: cli_args.wallet_task_rate_limit,
+        api_read_rate_limit: cli_args.api_read_rate_limit,
+        api_write_rate_limit: cli_args.api_write_rate_limit,
+        api_trust_forwarded_for: cli_args.api_trust_forwarded_for,
+        websocket_send_queue_size: cli_args.websocket_send_queue_size,
+        websocket_close_slow_clients: cli_args.websocket_close_slow_clients,
+        websocket_max_connections: cli_args.websocket_max_connections,
+        websocket_max_connections_per_ip: cli_args.websocket_max_connections_per_ip,
+        api_compression_min_size: cli_args.api_compression_min_size,
+        api_compression_content_types: cli_args.api_compression_content_types,
+        api_cors_allowed_origins: cli_args.api_cors_allowed_origins,
+        api_cors_allow_credentials: cli_args.api_cors_allow_credentials,
+        price_history_retention_hours: cli_args.price_history_retention_hours,
         min_transfer_amount: cli_args.min_transfer_amount,
         bind_addr: cli_args.bind_addr,
         public_ip: cli_args.public_ip,
         gossip_warmup: cli_args.gossip_warmup,
+        heartbeat_intervals,
         disable_price_reporter: cli_args.disable_price_reporter,
         disabled_exchanges: cli_args.disabled_exchanges,
+        uniswap_twap_pools: cli_args.uniswap_twap_pools,
+        uniswap_twap_windows: cli_args.uniswap_twap_windows,
+        price_rest_fallback_interval_ms: cli_args.price_rest_fallback_interval_ms,
+        price_staleness_threshold_ms: cli_args.price_staleness_threshold_ms,
+        price_aggregation_strategy: cli_args.price_aggregation_strategy,
+        price_aggregation_strategies: cli_args.price_aggregation_strategies,
+        price_max_source_deviation_pct: cli_args.price_max_source_deviation_pct,
+        synthetic_price_routes: cli_args.synthetic_price_routes,
         disabled_assets: cli_args.disabled_assets,
+        allowed_assets: cli_args.allowed_assets,
         cluster_keypair,
         cluster_symmetric_key,
         admin_api_key,
+        api_tenant_keys,
         cluster_id,
         coinbase_key_name: cli_args.coinbase_key_name,
         coinbase_key_secret: cli_args.coinbase_key_secret,
+        binance_api_key: cli_args.binance_api_key,
         rpc_url: cli_args.rpc_url,
         private_key,
         executor_private_key,
@@ -212,6 +290,7 @@ pub(crate) fn parse_config_from_args(cli_args: Cli) -> Result<RelayerConfig, Str
         otlp_enabled: cli_args.otlp_enabled,
         otlp_collector_url: cli_args.otlp_collector_url,
         datadog_enabled: cli_args.datadog_enabled,
+        log_format: cli_args.log_format,
         metrics_enabled: cli_args.metrics_enabled,
         statsd_host: cli_args.statsd_host,
         statsd_port: cli_args.statsd_port,
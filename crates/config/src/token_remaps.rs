@@ -2,12 +2,17 @@
 //!
 //! See https://github.com/renegade-fi/token-mappings/tree/main for more information
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+};
 
+use alloy::primitives::Address;
 use serde::{Deserialize, Serialize};
 use types_core::{
-    Chain, Exchange, USD_TICKER, set_default_chain, write_exchange_support,
-    write_token_decimals_map, write_token_remaps,
+    Chain, Exchange, TokenListing, USD_TICKER, read_token_decimals_map, read_token_remaps,
+    set_default_chain, write_exchange_support, write_token_decimals_map, write_token_remaps,
 };
 use util::log_task;
 use util::logging::Outcome;
@@ -24,6 +29,10 @@ const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 /// Default set of exchanges that support USD-quoted prices
 const DEFAULT_USD_EXCHANGES: &[Exchange] = &[Exchange::Coinbase, Exchange::Kraken, Exchange::Okx];
 
+/// The extension of a token remap file in TOML format; other files are parsed
+/// as JSON
+const TOML_EXTENSION: &str = "toml";
+
 // --------------------
 // | Serialized Types |
 // --------------------
@@ -38,25 +47,25 @@ pub struct TokenRemap {
 }
 
 /// The token info type
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct TokenInfo {
     /// The name of the token
-    name: String,
+    pub name: String,
     /// The token's ticker
-    ticker: String,
+    pub ticker: String,
     /// The address of the token in the chain
-    address: String,
+    pub address: String,
     /// The number of decimals the token uses in the ERC20 representation
-    decimals: u8,
+    pub decimals: u8,
     /// Whether the token has been delisted or is otherwise not tradable on
     /// Renegade. Optional in the JSON; absent or false means enabled.
     #[serde(default)]
-    disabled: bool,
+    pub disabled: bool,
     /// The exchanges that list the token, along with the ticker that we should
     /// use to fetch the token's price from the exchange
-    supported_exchanges: HashMap<Exchange, String>,
+    pub supported_exchanges: HashMap<Exchange, String>,
     /// The canonical exchange from which to source the token's price
-    canonical_exchange: Exchange,
+    pub canonical_exchange: Exchange,
 }
 
 impl TokenRemap {
@@ -152,6 +161,94 @@ pub fn setup_token_remaps(remap_file: Option<String>, chain: Chain) -> Result<Ve
     Ok(map.disabled_tickers())
 }
 
+/// Add a listed token to the global token remaps
+///
+/// The token is mapped on every chain it has an address on. Re-adding a token
+/// updates its exchange support. A listing is rejected if it maps a ticker or
+/// address to a different token, or changes the decimals of an address already
+/// mapped, as either would silently reprice the existing token
+pub fn add_token(listing: &TokenListing) -> Result<(), String> {
+    let listing = normalize_listing(listing);
+    check_listing(&listing)?;
+
+    let mut all_maps = write_token_remaps();
+    for (chain, addr) in &listing.addresses {
+        all_maps.entry(*chain).or_default().insert(addr.clone(), listing.ticker.clone());
+    }
+    drop(all_maps);
+
+    let mut all_decimals = write_token_decimals_map();
+    for (chain, addr) in &listing.addresses {
+        all_decimals.entry(*chain).or_default().insert(addr.clone(), listing.decimals);
+    }
+    drop(all_decimals);
+
+    write_exchange_support().insert(listing.ticker.clone(), listing.exchange_support());
+    Ok(())
+}
+
+/// Check that a listing is well formed and may be added to the global token
+/// remaps, without adding it
+pub fn check_token_listing(listing: &TokenListing) -> Result<(), String> {
+    check_listing(&normalize_listing(listing))
+}
+
+/// Lowercase the addresses of a listing, as in the token remaps
+fn normalize_listing(listing: &TokenListing) -> TokenListing {
+    let mut listing = listing.clone();
+    for (_, addr) in listing.addresses.iter_mut() {
+        *addr = addr.to_lowercase();
+    }
+
+    listing
+}
+
+/// Check a normalized listing against the global token remaps
+fn check_listing(listing: &TokenListing) -> Result<(), String> {
+    if listing.ticker.is_empty() {
+        return Err("token ticker must not be empty".to_string());
+    }
+    if listing.addresses.is_empty() {
+        return Err(format!("token {} has no addresses", listing.ticker));
+    }
+
+    let mut chains = HashSet::new();
+    for (chain, addr) in &listing.addresses {
+        if !chains.insert(*chain) {
+            return Err(format!("token {} has multiple addresses on {chain}", listing.ticker));
+        }
+        Address::from_str(addr).map_err(|_| format!("invalid token address {addr} on {chain}"))?;
+        check_token_conflicts(listing, *chain, addr)?;
+    }
+
+    Ok(())
+}
+
+/// Check that neither the token's ticker nor its address is mapped to a
+/// different token on the given chain, and that the address is not mapped
+/// with different decimals
+fn check_token_conflicts(listing: &TokenListing, chain: Chain, addr: &str) -> Result<(), String> {
+    let ticker = &listing.ticker;
+    if let Some(remap) = read_token_remaps().get(&chain) {
+        if let Some(other) = remap.get_by_left(addr).filter(|t| *t != ticker) {
+            return Err(format!("address {addr} is already mapped to {other} on {chain}"));
+        }
+        if let Some(other) = remap.get_by_right(ticker).filter(|a| *a != addr) {
+            return Err(format!("ticker {ticker} is already mapped to {other} on {chain}"));
+        }
+    }
+
+    let decimals = read_token_decimals_map().get(&chain).and_then(|m| m.get(addr).copied());
+    if let Some(decimals) = decimals.filter(|d| *d != listing.decimals) {
+        return Err(format!(
+            "address {addr} is already mapped with {decimals} decimals on {chain}, not {}",
+            listing.decimals
+        ));
+    }
+
+    Ok(())
+}
+
 /// Emit a warning if the JSON `disabled` flags don't match the CLI
 /// `disabled_assets` list. The two are independent inputs today: the JSON is
 /// informational for clients, the CLI list drives the relayer's `AssetFilter`.
@@ -190,12 +287,24 @@ fn lowercase_addresses(remap: &mut TokenRemap) {
     }
 }
 
-/// Parse a token remap from a JSON file
+/// Parse a token remap from a JSON or TOML file, by the file's extension
 pub fn parse_remap_from_file(file_path: String) -> Result<TokenRemap, String> {
+    let is_toml = Path::new(&file_path).extension().is_some_and(|ext| ext == TOML_EXTENSION);
+
     // Read the file into a string
     let file = std::fs::read_to_string(file_path)
         .map_err(raw_err_str!("Failed to read remap file: {}"))?;
-    serde_json::from_str(&file).map_err(raw_err_str!("Failed to parse remap from file: {}"))
+    if !is_toml {
+        return serde_json::from_str(&file)
+            .map_err(raw_err_str!("Failed to parse remap from file: {}"));
+    }
+
+    // TOML table keys do not deserialize into enums, so the exchange keyed
+    // tables are parsed by way of a JSON value
+    let value: toml::Value =
+        toml::from_str(&file).map_err(raw_err_str!("Failed to parse remap from file: {}"))?;
+    let json = serde_json::to_value(value).map_err(raw_err_str!("Failed to convert remap: {}"))?;
+    serde_json::from_value(json).map_err(raw_err_str!("Failed to parse remap from file: {}"))
 }
 
 /// Pull the token remap from the repo
@@ -212,11 +321,18 @@ mod test {
     use std::{collections::HashMap, fs::File};
 
     use tempfile::{TempDir, tempdir};
-    use types_core::{Chain, Exchange, read_token_remaps};
+    use types_core::{Chain, Exchange, TokenListing, read_exchange_support, read_token_remaps};
 
     use crate::token_remaps::parse_remap_from_file;
 
-    use super::{TokenInfo, TokenRemap, setup_token_remaps};
+    use super::{TokenInfo, TokenRemap, add_token, check_token_listing, setup_token_remaps};
+
+    /// Token addresses used in tests
+    const ADDR1: &str = "0xAbCdEf0000000000000000000000000000000001";
+    /// Token addresses used in tests
+    const ADDR2: &str = "0xabcdef0000000000000000000000000000000002";
+    /// Token addresses used in tests
+    const ADDR3: &str = "0xabcdef0000000000000000000000000000000003";
 
     /// Get a temporary dir and remap file for testing
     ///
@@ -265,6 +381,85 @@ mod test {
         assert_eq!(remap, parsed);
     }
 
+    /// Tests parsing the token remap from a TOML file
+    #[test]
+    fn test_parse_toml_token_remap() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("remap.toml").to_str().unwrap().to_string();
+        let toml = r#"
+            [[tokens]]
+            name = "Renegade"
+            ticker = "RNG"
+            address = "0x1234"
+            decimals = 18
+            canonical_exchange = "Binance"
+
+            [tokens.supported_exchanges]
+            Binance = "RNG"
+        "#;
+        std::fs::write(&path, toml).unwrap();
+
+        let parsed = parse_remap_from_file(path).unwrap();
+        let token = &parsed.tokens[0];
+        assert_eq!(token.ticker, "RNG");
+        assert!(!token.disabled);
+        assert_eq!(token.canonical_exchange, Exchange::Binance);
+        assert_eq!(token.supported_exchanges.get(&Exchange::Binance).unwrap(), "RNG");
+    }
+
+    /// Build a listing of a token on the given chains
+    fn listing(ticker: &str, decimals: u8, addresses: &[(Chain, &str)]) -> TokenListing {
+        TokenListing {
+            name: ticker.to_string(),
+            ticker: ticker.to_string(),
+            decimals,
+            addresses: addresses.iter().map(|(c, a)| (*c, a.to_string())).collect(),
+            supported_exchanges: vec![(Exchange::Binance, ticker.to_string())],
+            canonical_exchange: Exchange::Binance,
+        }
+    }
+
+    /// Tests adding tokens to the remap at runtime
+    #[test]
+    fn test_add_token() {
+        // Use chains no other test sets up, as setup overwrites the chain's remap
+        let (chain, other_chain) = (Chain::EthereumSepolia, Chain::BaseSepolia);
+        let info = listing("ADD", 6, &[(chain, ADDR1), (other_chain, ADDR2)]);
+        add_token(&info).unwrap();
+
+        let token_remaps = read_token_remaps();
+        let addr1 = ADDR1.to_lowercase();
+        assert_eq!(token_remaps.get(&chain).unwrap().get_by_left(&addr1), Some(&info.ticker));
+        assert_eq!(token_remaps.get(&other_chain).unwrap().get_by_left(ADDR2), Some(&info.ticker));
+        drop(token_remaps);
+        assert_eq!(
+            read_exchange_support().get("ADD").unwrap().get(&Exchange::Binance).unwrap(),
+            "ADD"
+        );
+
+        // Re-adding the same token is allowed, remapping its ticker or address is not
+        add_token(&info).unwrap();
+        assert!(add_token(&listing("ADD", 6, &[(chain, ADDR3)])).is_err());
+        assert!(add_token(&listing("OTHER", 6, &[(chain, ADDR1)])).is_err());
+
+        // Nor is changing its decimals
+        assert!(add_token(&listing("ADD", 18, &[(chain, ADDR1)])).is_err());
+    }
+
+    /// Tests that malformed listings are rejected
+    #[test]
+    fn test_check_token_listing() {
+        let chain = Chain::EthereumSepolia;
+        assert!(check_token_listing(&listing("CHK", 18, &[(chain, ADDR3)])).is_ok());
+
+        assert!(check_token_listing(&listing("CHK", 18, &[])).is_err());
+        assert!(check_token_listing(&listing("", 18, &[(chain, ADDR3)])).is_err());
+        assert!(check_token_listing(&listing("CHK", 18, &[(chain, "0x1234")])).is_err());
+        assert!(check_token_listing(&listing("CHK", 18, &[(chain, "not an address")])).is_err());
+        let duplicate = listing("CHK", 18, &[(chain, ADDR3), (chain, ADDR3)]);
+        assert!(check_token_listing(&duplicate).is_err());
+    }
+
     /// Tests that the token remap is correctly shared across threads
     #[test]
     fn test_token_remap_sharing() {
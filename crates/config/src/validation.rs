@@ -20,6 +20,45 @@ pub fn validate_config(config: &RelayerConfig) -> Result<(), String> {
         return Err("`cluster-keypair` is not a valid keypair".to_string());
     }
 
+    // The price confidence is a score between 0 and 1
+    if !(0. ..=1.).contains(&config.min_price_confidence) {
+        return Err("`min-price-confidence` must be between 0 and 1".to_string());
+    }
+
+    if config.price_staleness_threshold_ms == 0 {
+        return Err("`price-staleness-threshold-ms` must be non-zero".to_string());
+    }
+
+    if !(0. ..100.).contains(&config.price_max_source_deviation_pct) {
+        return Err("`price-max-source-deviation-pct` must be at least 0 and below 100".to_string());
+    }
+
+    if !config.bootstrap_dns.is_empty() && config.bootstrap_dns_refresh_ms == 0 {
+        return Err("`bootstrap-dns-refresh-ms` must be non-zero".to_string());
+    }
+
+    // A synthetic route must go through a token with a direct market
+    for (base, intermediate) in config.synthetic_price_routes.iter() {
+        if config.synthetic_price_routes.contains_key(intermediate) {
+            return Err(format!(
+                "`synthetic-price-routes` routes {base} through {intermediate}, itself synthetic"
+            ));
+        }
+    }
+
+    // Every TWAP window must belong to a configured pool and be non-empty
+    for (ticker, window) in config.uniswap_twap_windows.iter() {
+        if !config.uniswap_twap_pools.contains_key(ticker) {
+            return Err(format!(
+                "`uniswap-twap-windows` sets a window for {ticker}, which has no pool"
+            ));
+        }
+
+        if *window == 0 {
+            return Err(format!("`uniswap-twap-windows` sets an empty window for {ticker}"));
+        }
+    }
+
     Ok(())
 }
 
@@ -16,6 +16,8 @@ pub(crate) enum Task {
     ServiceLifecycle,
     /// Recovery of a faulted worker in the coordinator's recovery loop.
     WorkerRecovery,
+    /// The ordered drain of workers on a termination signal.
+    Shutdown,
 }
 
 impl LogTask for Task {
@@ -23,6 +25,7 @@ impl LogTask for Task {
         match self {
             Task::ServiceLifecycle => "service-lifecycle",
             Task::WorkerRecovery => "worker-recovery",
+            Task::Shutdown => "shutdown",
         }
     }
 }
@@ -10,8 +10,9 @@
 mod error;
 mod logging;
 mod setup;
+mod shutdown;
 
-use std::{thread, time::Duration};
+use std::{path::PathBuf, thread, time::Duration};
 
 use api_server::worker::{ApiServer, ApiServerConfig};
 use chain_events::{OnChainEventListener, OnChainEventListenerConfig};
@@ -20,7 +21,9 @@ use darkpool_client::client::DarkpoolClientConfig;
 use darkpool_client::constants::{BLOCK_POLLING_INTERVAL, EVENT_FILTER_POLLING_INTERVAL};
 use darkpool_client::DarkpoolClient;
 use event_manager::{manager::EventManager, worker::EventManagerConfig};
-use gossip_server::{server::GossipServer, worker::GossipServerConfig};
+use gossip_server::{
+    rate_limit::GossipRateLimits, server::GossipServer, worker::GossipServerConfig,
+};
 use job_types::matching_engine::new_matching_engine_worker_queue;
 use job_types::network_manager::new_network_manager_queue;
 use job_types::proof_manager::new_proof_manager_queue;
@@ -29,13 +32,20 @@ use job_types::{event_manager::new_event_manager_queue, gossip_server::new_gossi
 use matching_engine_core::MatchingEngine;
 use matching_engine_worker::worker::{MatchingEngineConfig, MatchingEngineManager};
 use network_manager::{worker::NetworkManager, worker::NetworkManagerConfig};
-use price_reporter::worker::PriceReporterConfig;
-use price_reporter::worker::{ExchangeConnectionsConfig, PriceReporter};
+use price_reporter::worker::{
+    exchange_credentials_from_keys, synthetic_routes_from_tickers, AggregationStrategies,
+    PriceReporterConfig,
+};
+use price_reporter::worker::{ExchangeConnectionsConfig, PriceReporter, UniswapTwapConfig};
 use proof_manager::worker::{ProofManager, ProofManagerConfig};
 use state::create_global_state;
 use system_bus::SystemBus;
+use types_core::Exchange;
+use types_gossip::{HeartbeatSettings, PeerAccessList, PeerLatencies};
 use types_runtime::new_cancel_channel;
-use types_runtime::{new_worker_failure_channel, watch_worker, Worker};
+use types_runtime::{
+    new_worker_failure_channel, watch_worker, ShutdownProgress, Worker, WorkerLiveness,
+};
 use util::default_option;
 
 use error::CoordinatorError;
@@ -112,6 +122,14 @@ async fn main() -> Result<(), CoordinatorError> {
     // First, the global shared mpmc bus that all workers have access to
     let system_bus = SystemBus::new();
     let system_clock = SystemClock::new().await;
+    // The table of round trip times to peers, written by the network manager
+    let peer_latencies = PeerLatencies::new();
+    // The heartbeat intervals, read by the gossip server and adjusted through the
+    // admin API
+    let heartbeat_settings = HeartbeatSettings::new(args.heartbeat_intervals);
+    // The peers the relayer may talk to, enforced by the network manager and gossip
+    // server and adjusted through the admin API
+    let peer_access = PeerAccessList::new(args.peer_allowlist.clone(), args.peer_blocklist.clone());
     let (network_sender, network_receiver) = new_network_manager_queue();
     let (gossip_worker_sender, gossip_worker_receiver) = new_gossip_server_queue();
     let (matching_engine_worker_sender, matching_engine_worker_receiver) =
@@ -137,6 +155,19 @@ async fn main() -> Result<(), CoordinatorError> {
     )
     .await?;
 
+    // Refuse to start with access lists that exclude a known peer of the local
+    // cluster; raft could not replicate to it
+    let local_peer_id = global_state.get_peer_id()?;
+    let mut cluster_peers = global_state.get_cluster_peers(&args.cluster_id).await?;
+    cluster_peers.retain(|peer_id| *peer_id != local_peer_id);
+    let excluded = peer_access.excluded(&cluster_peers);
+    if !excluded.is_empty() {
+        let excluded = excluded.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        return Err(CoordinatorError::setup(format!(
+            "peer access lists exclude cluster peers: {excluded}"
+        )));
+    }
+
     // Set the chain ID in the global static from static config. Bootstrap-mode
     // nodes (seeds) skip fetch_contract_constants(), so they would otherwise
     // never set CHAIN_ID and panic in get_chain_id() (e.g. ring0 settle
@@ -151,6 +182,7 @@ async fn main() -> Result<(), CoordinatorError> {
         rpc_url: args.rpc_url.clone().expect("rpc url not set"),
         private_key: args.private_key.clone(),
         block_polling_interval: BLOCK_POLLING_INTERVAL,
+        confirmation_depth: args.tx_confirmation_depth,
     };
     let darkpool_client =
         DarkpoolClient::new(darkpool_client_config).map_err(CoordinatorError::darkpool_client)?;
@@ -163,6 +195,7 @@ async fn main() -> Result<(), CoordinatorError> {
         rpc_url: args.rpc_url.unwrap(),
         private_key: args.private_key.clone(),
         block_polling_interval: EVENT_FILTER_POLLING_INTERVAL,
+        confirmation_depth: args.tx_confirmation_depth,
     };
     let chain_listener_darkpool_client = DarkpoolClient::new(chain_listener_darkpool_client_config)
         .map_err(CoordinatorError::darkpool_client)?;
@@ -173,6 +206,13 @@ async fn main() -> Result<(), CoordinatorError> {
 
     // --- Node Setup Phase --- //
 
+    // The liveness of each watched worker, reported by the API server's health
+    // probes
+    let worker_liveness = WorkerLiveness::new();
+    // The progress of the shutdown sequence, read by workers that refuse new
+    // work while the node drains
+    let shutdown = ShutdownProgress::new();
+
     // Start the price reporter manager
     let (price_reporter_cancel_sender, price_reporter_cancel_receiver) = new_cancel_channel();
     let (mut price_reporter_manager, price_streams) =
@@ -180,22 +220,42 @@ async fn main() -> Result<(), CoordinatorError> {
             system_bus: system_bus.clone(),
             cancel_channel: price_reporter_cancel_receiver,
             exchange_conn_config: ExchangeConnectionsConfig {
-                coinbase_key_name: args.coinbase_key_name,
-                coinbase_key_secret: args.coinbase_key_secret,
+                credentials: exchange_credentials_from_keys(vec![
+                    (Exchange::Coinbase, args.coinbase_key_name, args.coinbase_key_secret),
+                    (Exchange::Binance, args.binance_api_key, None),
+                ]),
                 eth_websocket_addr: args.eth_websocket_addr.clone(),
+                uniswap_twap_pools: UniswapTwapConfig::from_tickers(
+                    &args.uniswap_twap_pools,
+                    &args.uniswap_twap_windows,
+                ),
+                rest_fallback_interval_ms: args.price_rest_fallback_interval_ms,
             },
             price_reporter_url: args.price_reporter_url,
+            staleness_threshold_ms: args.price_staleness_threshold_ms,
+            aggregation_strategies: AggregationStrategies::from_tickers(
+                args.price_aggregation_strategy,
+                &args.price_aggregation_strategies,
+            )
+            .map_err(CoordinatorError::setup)?,
+            max_source_deviation: args.price_max_source_deviation_pct / 100.,
+            synthetic_routes: synthetic_routes_from_tickers(&args.synthetic_price_routes),
+            darkpool_client: Some(darkpool_client.clone()),
             disabled: args.disable_price_reporter,
             disabled_exchanges: args.disabled_exchanges,
         });
     price_reporter_manager.start().expect("failed to start price reporter manager");
     let (price_reporter_failure_sender, mut price_reporter_failure_receiver) =
         new_worker_failure_channel();
-    watch_worker::<PriceReporter>(&mut price_reporter_manager, &price_reporter_failure_sender);
+    watch_worker::<PriceReporter>(
+        &mut price_reporter_manager,
+        &price_reporter_failure_sender,
+        &worker_liveness,
+    );
 
     // Build a task driver that may be used to spawn long-lived asynchronous tasks
     // that are common among workers
-    let task_driver_config = TaskDriverConfig::new(
+    let mut task_driver_config = TaskDriverConfig::new(
         task_receiver,
         task_sender.clone(),
         darkpool_client.clone(),
@@ -208,13 +268,14 @@ async fn main() -> Result<(), CoordinatorError> {
         args.indexer_url.clone(),
         args.indexer_hmac_key,
     );
+    task_driver_config.shutdown = shutdown.clone();
     let mut task_driver =
         TaskDriver::new(task_driver_config).await.expect("failed to build task driver");
     task_driver.start().expect("failed to start task driver");
 
     let (task_driver_failure_sender, mut task_driver_failure_receiver) =
         new_worker_failure_channel();
-    watch_worker::<TaskDriver>(&mut task_driver, &task_driver_failure_sender);
+    watch_worker::<TaskDriver>(&mut task_driver, &task_driver_failure_sender, &worker_liveness);
 
     // Start the proof generation module
     let (proof_manager_cancel_sender, proof_manager_cancel_receiver) = new_cancel_channel();
@@ -229,7 +290,11 @@ async fn main() -> Result<(), CoordinatorError> {
     proof_manager.start().expect("failed to start proof generation module");
     let (proof_manager_failure_sender, mut proof_manager_failure_receiver) =
         new_worker_failure_channel();
-    watch_worker::<ProofManager>(&mut proof_manager, &proof_manager_failure_sender);
+    watch_worker::<ProofManager>(
+        &mut proof_manager,
+        &proof_manager_failure_sender,
+        &worker_liveness,
+    );
 
     // Start the network manager
     let (network_cancel_sender, network_cancel_receiver) = new_cancel_channel();
@@ -244,6 +309,9 @@ async fn main() -> Result<(), CoordinatorError> {
         send_channel: default_option(network_receiver),
         gossip_work_queue: gossip_worker_sender.clone(),
         global_state: global_state.clone(),
+        peer_latencies: peer_latencies.clone(),
+        peer_access: peer_access.clone(),
+        allow_unsigned_order_book_messages: args.allow_unsigned_order_book_messages,
         system_bus: system_bus.clone(),
         cancel_channel: network_cancel_receiver,
     };
@@ -252,7 +320,7 @@ async fn main() -> Result<(), CoordinatorError> {
     network_manager.start().expect("failed to start network manager");
 
     let (network_failure_sender, mut network_failure_receiver) = new_worker_failure_channel();
-    watch_worker::<NetworkManager>(&mut network_manager, &network_failure_sender);
+    watch_worker::<NetworkManager>(&mut network_manager, &network_failure_sender, &worker_liveness);
 
     // Start the gossip server
     let (gossip_cancel_sender, gossip_cancel_receiver) = new_cancel_channel();
@@ -261,6 +329,19 @@ async fn main() -> Result<(), CoordinatorError> {
         local_addr: network_manager.local_addr.clone(),
         cluster_id: args.cluster_id,
         bootstrap_servers: args.bootstrap_servers,
+        bootstrap_dns: args.bootstrap_dns,
+        bootstrap_dns_refresh_ms: args.bootstrap_dns_refresh_ms,
+        heartbeat_settings: heartbeat_settings.clone(),
+        peer_access: peer_access.clone(),
+        max_cluster_clock_skew_ms: args.max_cluster_clock_skew_ms,
+        reject_clock_skewed_peers: args.reject_clock_skewed_peers,
+        order_book_sync_interval_ms: args.order_book_sync_interval_ms,
+        rate_limits: GossipRateLimits {
+            heartbeats_per_minute: args.gossip_heartbeat_rate_limit,
+            orders_per_minute: args.gossip_order_rate_limit,
+            proofs_per_minute: args.gossip_proof_rate_limit,
+            penalty: Duration::from_millis(args.gossip_rate_limit_penalty_ms),
+        },
         darkpool_client: darkpool_client.clone(),
         global_state: global_state.clone(),
         job_sender: gossip_worker_sender.clone(),
@@ -272,7 +353,7 @@ async fn main() -> Result<(), CoordinatorError> {
     .expect("failed to build gossip server");
     gossip_server.start().expect("failed to start gossip server");
     let (gossip_failure_sender, mut gossip_failure_receiver) = new_worker_failure_channel();
-    watch_worker::<GossipServer>(&mut gossip_server, &gossip_failure_sender);
+    watch_worker::<GossipServer>(&mut gossip_server, &gossip_failure_sender, &worker_liveness);
 
     // Once the minimal set of workers are running, run the setup task
     //
@@ -293,7 +374,11 @@ async fn main() -> Result<(), CoordinatorError> {
     event_manager.start().expect("failed to start event manager");
     let (event_manager_failure_sender, mut event_manager_failure_receiver) =
         new_worker_failure_channel();
-    watch_worker::<EventManager>(&mut event_manager, &event_manager_failure_sender);
+    watch_worker::<EventManager>(
+        &mut event_manager,
+        &event_manager_failure_sender,
+        &worker_liveness,
+    );
 
     // --- Workers Setup Phase --- //
 
@@ -301,8 +386,14 @@ async fn main() -> Result<(), CoordinatorError> {
     let (handshake_cancel_sender, handshake_cancel_receiver) = new_cancel_channel();
     let mut handshake_manager = MatchingEngineManager::new(MatchingEngineConfig {
         min_fill_size: args.min_fill_size,
+        quote_only: args.quote_only_matching,
         external_match_validity_window: args.external_match_validity_window,
+        min_price_confidence: args.min_price_confidence,
+        vwap_pricing_threshold: args.vwap_pricing_threshold,
+        vwap_pricing_window: args.vwap_pricing_window,
+        blackout_windows: args.matching_blackout_windows.clone(),
         disabled_assets: args.disabled_assets.clone(),
+        allowed_assets: args.allowed_assets.clone(),
         state: global_state.clone(),
         matching_engine: matching_engine.clone(),
         price_streams: price_streams.clone(),
@@ -315,7 +406,11 @@ async fn main() -> Result<(), CoordinatorError> {
     .expect("failed to build handshake manager");
     handshake_manager.start().expect("failed to start handshake manager");
     let (handshake_failure_sender, mut handshake_failure_receiver) = new_worker_failure_channel();
-    watch_worker::<MatchingEngineManager>(&mut handshake_manager, &handshake_failure_sender);
+    watch_worker::<MatchingEngineManager>(
+        &mut handshake_manager,
+        &handshake_failure_sender,
+        &worker_liveness,
+    );
 
     // Start the on-chain event listener
     let (chain_listener_cancel_sender, chain_listener_cancel_receiver) = new_cancel_channel();
@@ -333,7 +428,11 @@ async fn main() -> Result<(), CoordinatorError> {
     chain_listener.start().expect("failed to start on-chain event listener");
     let (chain_listener_failure_sender, mut chain_listener_failure_receiver) =
         new_worker_failure_channel();
-    watch_worker::<OnChainEventListener>(&mut chain_listener, &chain_listener_failure_sender);
+    watch_worker::<OnChainEventListener>(
+        &mut chain_listener,
+        &chain_listener_failure_sender,
+        &worker_liveness,
+    );
 
     // Start the API server
     let (api_cancel_sender, api_cancel_receiver) = new_cancel_channel();
@@ -346,17 +445,37 @@ async fn main() -> Result<(), CoordinatorError> {
         // this port.
         health_port: args.http_port + 1,
         admin_api_key: args.admin_api_key,
+        tenant_keys: args.api_tenant_keys.clone(),
         min_transfer_amount: args.min_transfer_amount,
         min_order_size,
         chain: args.chain_id,
         compliance_service_url: args.compliance_service_url.clone(),
         wallet_task_rate_limit: args.wallet_task_rate_limit,
+        read_rate_limit: args.api_read_rate_limit,
+        write_rate_limit: args.api_write_rate_limit,
+        trust_forwarded_for: args.api_trust_forwarded_for,
+        websocket_send_queue_size: args.websocket_send_queue_size,
+        close_slow_websocket_clients: args.websocket_close_slow_clients,
+        websocket_max_connections: args.websocket_max_connections,
+        websocket_max_connections_per_ip: args.websocket_max_connections_per_ip,
+        compression_min_size: args.api_compression_min_size,
+        compression_content_types: args.api_compression_content_types.clone(),
+        cors_allowed_origins: args.api_cors_allowed_origins.clone(),
+        cors_allow_credentials: args.api_cors_allow_credentials,
+        price_history_retention_hours: args.price_history_retention_hours,
+        state_export_path: PathBuf::from(&args.state_export_path),
         disabled_assets: args.disabled_assets.clone(),
+        allowed_assets: args.allowed_assets.clone(),
         darkpool_client: darkpool_client.clone(),
         network_sender: network_sender.clone(),
         state: global_state.clone(),
         system_bus,
         price_streams: price_streams.clone(),
+        peer_latencies,
+        heartbeat_settings,
+        peer_access,
+        worker_liveness: worker_liveness.clone(),
+        shutdown: shutdown.clone(),
         proof_generation_work_queue: proof_generation_worker_sender,
         matching_engine_worker_queue: matching_engine_worker_sender.clone(),
         task_queue: task_sender.clone(),
@@ -366,13 +485,18 @@ async fn main() -> Result<(), CoordinatorError> {
     .expect("failed to build api server");
     api_server.start().expect("failed to start api server");
     let (api_failure_sender, mut api_failure_receiver) = new_worker_failure_channel();
-    watch_worker::<ApiServer>(&mut api_server, &api_failure_sender);
+    watch_worker::<ApiServer>(&mut api_server, &api_failure_sender, &worker_liveness);
 
     // Await module termination, and send a cancel signal for any modules that
-    // have been detected to fault
+    // have been detected to fault. A termination signal ends the loop and
+    // begins the shutdown sequence
     let recovery_loop = || async {
+        let mut termination = Box::pin(shutdown::termination_signal());
         loop {
             select! {
+                _ = &mut termination => {
+                    return Ok(());
+                },
                 _ = state_failure_recv.recv() => {
                     return Err(CoordinatorError::State("state submodule failed".to_string()));
                 },
@@ -423,17 +547,33 @@ async fn main() -> Result<(), CoordinatorError> {
         }
     };
 
-    // Wait for an error, log the error, and teardown the relayer
+    // Wait for a termination signal or an error, then teardown the relayer
     let loop_res: Result<(), CoordinatorError> = recovery_loop().await;
-    let err = loop_res.err().unwrap();
-    log_task!(
-        Task::ServiceLifecycle,
-        Outcome::Failed,
-        error = ?err,
-        "coordinator thread exiting on error"
-    );
+    match &loop_res {
+        Ok(()) => {
+            log_task!(
+                Task::ServiceLifecycle,
+                Outcome::Started,
+                "received termination signal, draining workers"
+            );
+            shutdown::drain(
+                &shutdown,
+                &global_state,
+                &handshake_cancel_sender,
+                &[&gossip_cancel_sender, &network_cancel_sender],
+            )
+            .await;
+        },
+        Err(err) => log_task!(
+            Task::ServiceLifecycle,
+            Outcome::Failed,
+            error = ?err,
+            "coordinator thread exiting on error"
+        ),
+    }
 
-    // Send cancel signals to all workers
+    // Send cancel signals to all workers; a worker that has already exited has
+    // dropped its receiver, so send failures are ignored
     for cancel_channel in [
         network_cancel_sender,
         gossip_cancel_sender,
@@ -445,7 +585,7 @@ async fn main() -> Result<(), CoordinatorError> {
     ]
     .iter()
     {
-        cancel_channel.send(()).unwrap();
+        let _ = cancel_channel.send(());
     }
 
     // Give workers time to teardown execution then terminate
@@ -456,7 +596,7 @@ async fn main() -> Result<(), CoordinatorError> {
     if args.otlp_enabled {
         opentelemetry::global::shutdown_tracer_provider();
     }
-    Err(err)
+    loop_res
 }
 
 /// Attempt to recover a failed module by cleaning up its resources and
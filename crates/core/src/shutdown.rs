@@ -0,0 +1,115 @@
+//! The coordinator's shutdown sequence
+//!
+//! On SIGTERM or SIGINT the coordinator drains the relayer rather than
+//! cancelling every worker at once, so that no task is interrupted between
+//! its on-chain submission and its state update:
+//!     1. The API server refuses writes, so no new work enters the relayer
+//!     2. The matching engine is stopped; matches it has already found settle
+//!        as tasks
+//!     3. In-flight tasks run to completion; tasks not yet started are held in
+//!        their queues and run after the node restarts
+//!     4. The raft is snapshotted and shut down
+//!     5. The gossip server and network manager are stopped
+//!
+//! The health probes report each phase as it is entered
+
+use std::time::{Duration, Instant};
+
+use state::State;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch::Sender as WatchSender;
+use types_runtime::{ShutdownPhase, ShutdownProgress};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::logging::Task;
+
+/// The amount of time given to the matching engine to stop after it is
+/// cancelled
+const MATCHING_DRAIN_MS: u64 = 2_000; // 2 seconds
+/// The maximum amount of time to wait for in-flight tasks to complete
+const TASK_DRAIN_TIMEOUT_MS: u64 = 60_000; // 1 minute
+/// The interval at which the number of in-flight tasks is polled
+const TASK_DRAIN_POLL_MS: u64 = 500; // 500 milliseconds
+
+/// A sender on a worker's cancel channel
+type CancelSender = WatchSender<()>;
+
+/// Await a termination signal, either SIGTERM from an orchestrator or SIGINT
+pub(crate) async fn termination_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    tokio::select! {
+        _ = sigterm.recv() => {},
+        _ = tokio::signal::ctrl_c() => {},
+    }
+}
+
+/// Drain the relayer's workers in order
+///
+/// The matching engine is cancelled through `matching_cancel`, and the network
+/// workers through `network_cancels` in the order given. Failures are logged
+/// rather than propagated so that the sequence always reaches its end
+pub(crate) async fn drain(
+    shutdown: &ShutdownProgress,
+    state: &State,
+    matching_cancel: &CancelSender,
+    network_cancels: &[&CancelSender],
+) {
+    // Refuse API writes
+    enter_phase(shutdown, ShutdownPhase::StoppingWrites);
+
+    // Stop the matching engine
+    enter_phase(shutdown, ShutdownPhase::DrainingMatching);
+    let _ = matching_cancel.send(());
+    tokio::time::sleep(Duration::from_millis(MATCHING_DRAIN_MS)).await;
+
+    // Let in-flight tasks reach completion
+    enter_phase(shutdown, ShutdownPhase::DrainingTasks);
+    await_in_flight_tasks(shutdown).await;
+
+    // Flush the raft
+    enter_phase(shutdown, ShutdownPhase::FlushingRaft);
+    if let Err(e) = state.shutdown_raft().await {
+        log_task!(Task::Shutdown, Outcome::Failed, error = %e, "error flushing raft");
+    }
+
+    // Stop the network
+    enter_phase(shutdown, ShutdownPhase::StoppingNetwork);
+    for cancel in network_cancels {
+        let _ = cancel.send(());
+    }
+
+    enter_phase(shutdown, ShutdownPhase::Stopped);
+}
+
+/// Enter a phase of the shutdown sequence
+fn enter_phase(shutdown: &ShutdownProgress, phase: ShutdownPhase) {
+    log_task!(Task::Shutdown, Outcome::Started, phase = %phase, "entering shutdown phase");
+    shutdown.advance(phase);
+}
+
+/// Wait for the tasks running on the local node to complete, up to a timeout
+async fn await_in_flight_tasks(shutdown: &ShutdownProgress) {
+    let start = Instant::now();
+    let timeout = Duration::from_millis(TASK_DRAIN_TIMEOUT_MS);
+    let poll_interval = Duration::from_millis(TASK_DRAIN_POLL_MS);
+
+    loop {
+        let in_flight = shutdown.in_flight_tasks();
+        if in_flight == 0 {
+            return;
+        }
+
+        if start.elapsed() >= timeout {
+            log_task!(
+                Task::Shutdown,
+                Outcome::Failed,
+                in_flight_tasks = in_flight,
+                "timed out waiting for in-flight tasks"
+            );
+            return;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
@@ -28,7 +28,7 @@ use test_helpers::{
 };
 use util::{
     on_chain::{DARKPOOL_PROXY_CONTRACT_KEY, parse_addr_from_deployments_file},
-    telemetry::LevelFilter,
+    telemetry::{LevelFilter, LogFormat},
 };
 
 /// The arguments used to run the integration tests
@@ -114,6 +114,7 @@ impl From<CliArgs> for IntegrationTestArgs {
             private_key,
             rpc_url: test_args.rpc_url,
             block_polling_interval: Duration::from_millis(100),
+            confirmation_depth: Chain::Devnet.default_confirmation_depth(),
         })
         .unwrap();
 
@@ -125,7 +126,7 @@ impl From<CliArgs> for IntegrationTestArgs {
 fn setup_integration_tests(test_args: &CliArgs) {
     // Configure logging
     if matches!(test_args.verbosity, TestVerbosity::Full) {
-        util::telemetry::setup_system_logger(LevelFilter::INFO);
+        util::telemetry::setup_system_logger(LevelFilter::INFO, LogFormat::Pretty);
     }
 }
 
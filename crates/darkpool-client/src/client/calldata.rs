@@ -0,0 +1,134 @@
+//! Decoding helpers for darkpool transaction calldata
+//!
+//! Consumers that inspect darkpool transactions, e.g. account recovery or
+//! external analytics, decode the calls here rather than matching selectors
+//! themselves
+
+use alloy::consensus::constants::SELECTOR_LEN;
+use alloy_primitives::TxHash;
+use alloy_sol_types::SolCall;
+use renegade_solidity_abi::v2::IDarkpoolV2::{
+    cancelPublicOrderCall, depositCall, depositNewBalanceCall, settleExternalMatchCall,
+    settleMatchCall, withdrawCall,
+};
+
+use crate::errors::DarkpoolClientError;
+
+use super::DarkpoolClient;
+
+/// The selectors of the darkpool calls decoded by `decode_darkpool_call`
+pub(crate) const DECODED_SELECTORS: [[u8; SELECTOR_LEN]; 6] = [
+    depositNewBalanceCall::SELECTOR,
+    depositCall::SELECTOR,
+    withdrawCall::SELECTOR,
+    settleMatchCall::SELECTOR,
+    settleExternalMatchCall::SELECTOR,
+    cancelPublicOrderCall::SELECTOR,
+];
+
+/// A darkpool call decoded from calldata
+///
+/// Each variant holds the decoded call arguments, including the statements
+/// and settlement bundles submitted with the call
+#[derive(Clone)]
+pub enum DarkpoolCall {
+    /// A `depositNewBalance` call, creating a balance
+    CreateBalance(depositNewBalanceCall),
+    /// A `deposit` call into an existing balance
+    Deposit(depositCall),
+    /// A `withdraw` call from an existing balance
+    Withdraw(withdrawCall),
+    /// A `settleMatch` call, settling an internal match
+    SettleMatch(settleMatchCall),
+    /// A `settleExternalMatch` call, settling a match with an external party
+    SettleExternalMatch(settleExternalMatchCall),
+    /// A `cancelPublicOrder` call
+    CancelPublicOrder(cancelPublicOrderCall),
+}
+
+impl DarkpoolCall {
+    /// The name of the darkpool method called
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            Self::CreateBalance(_) => depositNewBalanceCall::SIGNATURE,
+            Self::Deposit(_) => depositCall::SIGNATURE,
+            Self::Withdraw(_) => withdrawCall::SIGNATURE,
+            Self::SettleMatch(_) => settleMatchCall::SIGNATURE,
+            Self::SettleExternalMatch(_) => settleExternalMatchCall::SIGNATURE,
+            Self::CancelPublicOrder(_) => cancelPublicOrderCall::SIGNATURE,
+        }
+    }
+}
+
+/// Decode a darkpool call from its calldata
+///
+/// Returns `None` if the calldata does not begin with the selector of a
+/// decoded darkpool call, and an error if it does but fails to decode
+pub fn decode_darkpool_call(calldata: &[u8]) -> Result<Option<DarkpoolCall>, DarkpoolClientError> {
+    if calldata.len() < SELECTOR_LEN {
+        return Ok(None);
+    }
+
+    let selector: [u8; SELECTOR_LEN] = calldata[..SELECTOR_LEN].try_into().unwrap();
+    let call = match selector {
+        depositNewBalanceCall::SELECTOR => {
+            DarkpoolCall::CreateBalance(depositNewBalanceCall::abi_decode(calldata)?)
+        },
+        depositCall::SELECTOR => DarkpoolCall::Deposit(depositCall::abi_decode(calldata)?),
+        withdrawCall::SELECTOR => DarkpoolCall::Withdraw(withdrawCall::abi_decode(calldata)?),
+        settleMatchCall::SELECTOR => {
+            DarkpoolCall::SettleMatch(settleMatchCall::abi_decode(calldata)?)
+        },
+        settleExternalMatchCall::SELECTOR => {
+            DarkpoolCall::SettleExternalMatch(settleExternalMatchCall::abi_decode(calldata)?)
+        },
+        cancelPublicOrderCall::SELECTOR => {
+            DarkpoolCall::CancelPublicOrder(cancelPublicOrderCall::abi_decode(calldata)?)
+        },
+        _ => return Ok(None),
+    };
+
+    Ok(Some(call))
+}
+
+impl DarkpoolClient {
+    /// Decode the darkpool calls made in a transaction
+    ///
+    /// The transaction is traced so that darkpool calls made through another
+    /// contract, e.g. a router or a multicall, are decoded as well. Darkpool
+    /// calls to methods that are not decoded are skipped
+    pub async fn decode_darkpool_calls_in_tx(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<Vec<DarkpoolCall>, DarkpoolClientError> {
+        let mut calls = Vec::new();
+        for frame in self.fetch_tx_darkpool_calls(tx_hash).await? {
+            if let Some(call) = decode_darkpool_call(&frame.input)? {
+                calls.push(call);
+            }
+        }
+
+        Ok(calls)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DECODED_SELECTORS, decode_darkpool_call};
+
+    /// Tests that calldata without a decoded selector is skipped
+    #[test]
+    fn test_decode_unknown_calldata() {
+        assert!(decode_darkpool_call(&[]).unwrap().is_none());
+        assert!(decode_darkpool_call(&[0xde, 0xad, 0xbe]).unwrap().is_none());
+        assert!(decode_darkpool_call(&[0xde, 0xad, 0xbe, 0xef, 0x00]).unwrap().is_none());
+    }
+
+    /// Tests that truncated calldata with a decoded selector is an error
+    #[test]
+    fn test_decode_truncated_calldata() {
+        for selector in DECODED_SELECTORS {
+            assert!(decode_darkpool_call(&selector).is_err());
+        }
+    }
+}
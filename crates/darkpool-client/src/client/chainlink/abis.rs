@@ -0,0 +1,14 @@
+//! ABI definitions for Chainlink price feeds
+//!
+//! Note that these definitions only contain the read-only subset of the
+//! `AggregatorV3Interface` used to fetch prices
+use alloy::sol;
+
+sol! {
+    #[sol(rpc)]
+    contract IAggregatorV3 {
+        function decimals() external view returns (uint8);
+        function description() external view returns (string memory);
+        function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound);
+    }
+}
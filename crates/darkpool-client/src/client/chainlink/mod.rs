@@ -0,0 +1,84 @@
+//! Chainlink price feed reads on the Darkpool client
+
+pub mod abis;
+
+use alloy_primitives::Address;
+use types_core::Price;
+
+use crate::{
+    client::{DarkpoolClient, RPC_READ_TIMEOUT, RenegadeProvider, record_rpc_error},
+    errors::DarkpoolClientError,
+};
+use abis::IAggregatorV3::IAggregatorV3Instance;
+
+/// The latest round reported by a Chainlink aggregator
+#[derive(Clone, Copy, Debug)]
+pub struct ChainlinkRound {
+    /// The reported price, corrected for the feed's decimals
+    pub price: Price,
+    /// The unix timestamp, in seconds, at which the round was last updated
+    pub updated_at: u64,
+}
+
+impl DarkpoolClient {
+    /// Get the latest round reported by a Chainlink aggregator
+    ///
+    /// Rounds with a non-positive answer are rejected, as is any round which
+    /// was never updated
+    pub async fn get_chainlink_round(
+        &self,
+        feed: Address,
+    ) -> Result<ChainlinkRound, DarkpoolClientError> {
+        let aggregator = self.chainlink_client(feed);
+        let read = async {
+            let decimals = aggregator.decimals().call().await?;
+            let round = aggregator.latestRoundData().call().await?;
+            Ok::<_, alloy::contract::Error>((decimals, round))
+        };
+
+        let (decimals, round) = tokio::time::timeout(RPC_READ_TIMEOUT, read)
+            .await
+            .map_err(|_| {
+                DarkpoolClientError::chainlink(format!(
+                    "feed {feed} timed out after {}s",
+                    RPC_READ_TIMEOUT.as_secs()
+                ))
+            })
+            .and_then(|res| res.map_err(DarkpoolClientError::chainlink))
+            .inspect_err(|_| record_rpc_error("chainlink_round"))?;
+
+        if round.answer.is_negative() || round.answer.is_zero() {
+            let msg = format!("feed {feed} reported non-positive answer {}", round.answer);
+            return Err(DarkpoolClientError::chainlink(msg));
+        }
+
+        let updated_at = u64::try_from(round.updatedAt).map_err(DarkpoolClientError::chainlink)?;
+        if updated_at == 0 {
+            return Err(DarkpoolClientError::chainlink(format!(
+                "feed {feed} has no complete round"
+            )));
+        }
+
+        // Answers are fixed-point with the feed's decimals, so the price is
+        // their exact ratio
+        let answer =
+            u128::try_from(round.answer.into_raw()).map_err(DarkpoolClientError::chainlink)?;
+        let price = 10u128
+            .checked_pow(u32::from(decimals))
+            .and_then(|scale| Price::from_integer_ratio(answer, scale))
+            .ok_or_else(|| {
+                let msg = format!("feed {feed} answer {answer} with {decimals} decimals overflows");
+                DarkpoolClientError::chainlink(msg)
+            })?;
+        Ok(ChainlinkRound { price, updated_at })
+    }
+
+    /// Get an instance of a Chainlink aggregator client
+    pub(crate) fn chainlink_client(
+        &self,
+        feed: Address,
+    ) -> IAggregatorV3Instance<&RenegadeProvider> {
+        let provider = self.provider();
+        IAggregatorV3Instance::new(feed, provider)
+    }
+}
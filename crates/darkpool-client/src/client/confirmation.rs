@@ -0,0 +1,170 @@
+//! Waiting for submitted transactions to reach the confirmation depth
+//!
+//! A receipt only shows that a transaction was included in a block. On chains
+//! where that block may still be reorged out, the client waits for the block
+//! to reach the configured confirmation depth, then checks that the
+//! transaction is still included in the same block before returning.
+
+use std::time::Duration;
+
+use alloy::{eips::BlockNumberOrTag, providers::Provider, rpc::types::TransactionReceipt};
+use alloy_primitives::BlockNumber;
+use types_core::ConfirmationDepth;
+use util::{log_task, logging::Outcome};
+
+use crate::{errors::DarkpoolClientError, logging::Task};
+
+use super::DarkpoolClient;
+
+/// The timeout for an included transaction to reach the confirmation depth
+///
+/// Ethereum finalizes a block after two epochs, roughly 13 minutes
+const TX_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+impl DarkpoolClient {
+    /// Wait for an included transaction to reach the confirmation depth,
+    /// returning its receipt once confirmed
+    pub(crate) async fn await_confirmation(
+        &self,
+        receipt: TransactionReceipt,
+    ) -> Result<TransactionReceipt, DarkpoolClientError> {
+        // A single block confirms a transaction once it is included
+        if matches!(self.confirmation_depth, ConfirmationDepth::Blocks(blocks) if blocks <= 1) {
+            return Ok(receipt);
+        }
+
+        let tx_hash = format!("{:#x}", receipt.transaction_hash);
+        let block_number = receipt.block_number.ok_or_else(|| {
+            DarkpoolClientError::TxQuerying(format!("receipt for {tx_hash} has no block number"))
+        })?;
+        log_task!(
+            Task::ConfirmTx,
+            Outcome::Started,
+            subject = %tx_hash,
+            block = block_number,
+            depth = %self.confirmation_depth,
+            "awaiting tx confirmation"
+        );
+
+        let await_depth =
+            tokio::time::timeout(TX_CONFIRMATION_TIMEOUT, self.await_depth(block_number));
+        let reached_depth = match await_depth.await {
+            Ok(res) => {
+                res?;
+                true
+            },
+            Err(_) => false,
+        };
+
+        // Check that the transaction was not reorged out while awaiting the depth
+        let included = self
+            .provider()
+            .get_transaction_receipt(receipt.transaction_hash)
+            .await
+            .map_err(DarkpoolClientError::rpc)?
+            .filter(|r| r.block_hash == receipt.block_hash);
+
+        let res = confirmation_outcome(included, reached_depth, || {
+            format!(
+                "tx ({tx_hash}) not confirmed at depth {} after {}s",
+                self.confirmation_depth,
+                TX_CONFIRMATION_TIMEOUT.as_secs()
+            )
+        });
+        match &res {
+            Ok(_) => log_task!(Task::ConfirmTx, Outcome::Ok, subject = %tx_hash, "tx confirmed"),
+            Err(e) => log_task!(
+                Task::ConfirmTx,
+                Outcome::Failed,
+                subject = %tx_hash,
+                error = %e,
+                "tx not confirmed"
+            ),
+        }
+        res
+    }
+
+    /// Wait until the given block reaches the confirmation depth
+    async fn await_depth(&self, block_number: BlockNumber) -> Result<(), DarkpoolClientError> {
+        let poll_interval = self.provider().client().poll_interval();
+        while self.confirmed_through().await? < block_number {
+            tokio::time::sleep(poll_interval).await;
+        }
+
+        Ok(())
+    }
+
+    /// Get the newest block at the confirmation depth
+    async fn confirmed_through(&self) -> Result<BlockNumber, DarkpoolClientError> {
+        let tag = match self.confirmation_depth {
+            ConfirmationDepth::Blocks(blocks) => {
+                let head = self.block_number().await?;
+                return Ok((head + 1).saturating_sub(blocks));
+            },
+            ConfirmationDepth::Safe => BlockNumberOrTag::Safe,
+            ConfirmationDepth::Finalized => BlockNumberOrTag::Finalized,
+        };
+
+        let block = self
+            .provider()
+            .get_block_by_number(tag)
+            .await
+            .map_err(DarkpoolClientError::rpc)?
+            .ok_or_else(|| DarkpoolClientError::rpc(format!("no {tag} block")))?;
+        Ok(block.header.number)
+    }
+}
+
+/// Decide the outcome of awaiting a transaction's confirmation, given its
+/// receipt if it is still included in the block it was mined in, and whether
+/// that block reached the confirmation depth in time
+///
+/// A transaction that is still included when the timeout elapses may yet be
+/// confirmed, so it is reported as unconfirmed rather than failed; only a
+/// transaction that left its block is reported as dropped
+fn confirmation_outcome<R>(
+    included: Option<R>,
+    reached_depth: bool,
+    timeout_msg: impl FnOnce() -> String,
+) -> Result<R, DarkpoolClientError> {
+    match included {
+        None => Err(DarkpoolClientError::TxDropped),
+        Some(_) if !reached_depth => Err(DarkpoolClientError::TxUnconfirmed(timeout_msg())),
+        Some(receipt) => Ok(receipt),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that a transaction which reached the depth and is still included
+    /// is confirmed
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_confirmation_outcome__confirmed() {
+        let res = confirmation_outcome(Some(1), true /* reached_depth */, String::new);
+        assert!(matches!(res, Ok(1)));
+    }
+
+    /// Tests that a transaction still included after the timeout is reported
+    /// as unconfirmed rather than failed
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_confirmation_outcome__timed_out() {
+        let res =
+            confirmation_outcome(Some(1), false /* reached_depth */, || "timeout".into());
+        assert!(matches!(res, Err(DarkpoolClientError::TxUnconfirmed(msg)) if msg == "timeout"));
+    }
+
+    /// Tests that a transaction no longer included in its block is reported as
+    /// dropped, whether or not the depth was reached
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_confirmation_outcome__dropped() {
+        for reached_depth in [true, false] {
+            let res = confirmation_outcome(None::<u64>, reached_depth, String::new);
+            assert!(matches!(res, Err(DarkpoolClientError::TxDropped)));
+        }
+    }
+}
@@ -1,7 +1,6 @@
 //! Defines `DarkpoolClient` helpers that allow for interacting with the
 //! darkpool contract
 
-use alloy::consensus::constants::SELECTOR_LEN;
 use alloy::rpc::types::TransactionReceipt;
 use alloy_primitives::{Address, Selector};
 use circuit_types::Nullifier;
@@ -23,11 +22,7 @@ use crate::logging::Task;
 
 use crate::errors::DarkpoolClientError;
 
-use super::DarkpoolClient;
-
-/// The set of known selectors for the Solidity darkpool
-/// TODO: Add known selectors
-const KNOWN_SELECTORS: [[u8; SELECTOR_LEN]; 0] = [];
+use super::{DarkpoolClient, calldata::DECODED_SELECTORS};
 
 impl DarkpoolClient {
     // -----------
@@ -128,7 +123,7 @@ impl DarkpoolClient {
     /// trace calls the darkpool directly. If not, the relayer should trace the
     /// transaction's calls and parse shares from darkpool subcalls.
     pub fn is_known_selector(selector: Selector) -> bool {
-        KNOWN_SELECTORS.contains(&selector.0)
+        DECODED_SELECTORS.contains(&selector.0)
     }
 
     // -----------
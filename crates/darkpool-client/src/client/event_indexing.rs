@@ -4,7 +4,6 @@
 use std::cmp::Reverse;
 use std::collections::VecDeque;
 
-use alloy::consensus::constants::SELECTOR_LEN;
 use alloy::providers::{Provider, ext::DebugApi};
 use alloy::rpc::types::Log as RpcLog;
 use alloy::rpc::types::TransactionReceipt;
@@ -13,14 +12,14 @@ use alloy::rpc::types::trace::geth::{
 };
 use alloy_contract::Event;
 use alloy_primitives::{B256, Log, TxHash, keccak256};
-use alloy_sol_types::{SolCall, SolEvent, SolValue};
+use alloy_sol_types::{SolEvent, SolValue};
 use circuit_types::Amount;
 use constants::{MERKLE_HEIGHT, Scalar};
 use crypto::fields::{scalar_to_u256, u256_to_scalar};
 use darkpool_types::bounded_match_result::BoundedMatchResult;
 use itertools::Itertools;
 use renegade_solidity_abi::v2::IDarkpoolV2::{
-    self, MerkleInsertion as AbiMerkleInsertion, MerkleOpeningNode as AbiMerkleOpeningNode,
+    MerkleInsertion as AbiMerkleInsertion, MerkleOpeningNode as AbiMerkleOpeningNode,
     PublicIntentPublicBalanceBundle, SettlementBundle,
 };
 use renegade_solidity_abi::v2::calldata_bundles::NATIVE_SETTLED_PUBLIC_INTENT_BUNDLE_TYPE;
@@ -30,7 +29,10 @@ use types_account::MerkleAuthenticationPath;
 
 use crate::errors::DarkpoolClientError;
 
-use super::{DarkpoolClient, RenegadeProvider};
+use super::{
+    DarkpoolClient, RenegadeProvider,
+    calldata::{DarkpoolCall, decode_darkpool_call},
+};
 
 /// The starting range of blocks to query for events
 const STARTING_BLOCK_RANGE: u64 = 10;
@@ -232,17 +234,9 @@ impl DarkpoolClient {
         &self,
         calldata: &[u8],
     ) -> Result<Option<ExternalMatchCalldata>, DarkpoolClientError> {
-        if calldata.len() < SELECTOR_LEN {
-            return Ok(None);
-        }
-
         // Parse calldata
-        let selector: [u8; SELECTOR_LEN] = calldata[..SELECTOR_LEN].try_into().unwrap();
-        let call = match selector {
-            IDarkpoolV2::settleExternalMatchCall::SELECTOR => {
-                IDarkpoolV2::settleExternalMatchCall::abi_decode(calldata)?
-            },
-            _ => return Ok(None),
+        let Some(DarkpoolCall::SettleExternalMatch(call)) = decode_darkpool_call(calldata)? else {
+            return Ok(None);
         };
 
         let Some(intent_hash) = Self::extract_intent_hash(&call.internalPartySettlementBundle)?
@@ -0,0 +1,56 @@
+//! Tracking of the gas spent by transactions submitted through a client
+//!
+//! A tracker is attached to a clone of the client, so that a caller (e.g. a
+//! task) can account for the gas spent by the transactions it submits without
+//! observing those of other callers sharing the underlying client
+
+use std::sync::{Arc, Mutex};
+
+use alloy::rpc::types::TransactionReceipt;
+use alloy_primitives::TxHash;
+
+/// The gas cost of a submitted transaction, as read from its receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxGasCost {
+    /// The hash of the transaction
+    pub tx_hash: TxHash,
+    /// The gas used by the transaction
+    pub gas_used: u64,
+    /// The price paid per unit of gas, in wei
+    pub effective_gas_price: u128,
+}
+
+impl TxGasCost {
+    /// Read the gas cost from a transaction receipt
+    pub fn from_receipt(receipt: &TransactionReceipt) -> Self {
+        Self {
+            tx_hash: receipt.transaction_hash,
+            gas_used: receipt.gas_used,
+            effective_gas_price: receipt.effective_gas_price,
+        }
+    }
+
+    /// The total fee paid for the transaction, in wei
+    pub fn fee_paid(&self) -> u128 {
+        (self.gas_used as u128).saturating_mul(self.effective_gas_price)
+    }
+}
+
+/// A shared log of the gas costs of transactions submitted through a client
+#[derive(Clone, Debug, Default)]
+pub struct GasTracker {
+    /// The costs recorded so far
+    costs: Arc<Mutex<Vec<TxGasCost>>>,
+}
+
+impl GasTracker {
+    /// Record the cost of a transaction
+    pub(crate) fn record(&self, cost: TxGasCost) {
+        self.costs.lock().expect("gas tracker lock poisoned").push(cost);
+    }
+
+    /// Take all costs recorded so far, leaving the tracker empty
+    pub fn take(&self) -> Vec<TxGasCost> {
+        std::mem::take(&mut *self.costs.lock().expect("gas tracker lock poisoned"))
+    }
+}
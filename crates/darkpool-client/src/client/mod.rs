@@ -21,7 +21,7 @@ use constants::{
     ETHEREUM_SEPOLIA_DEPLOY_BLOCK, MERKLE_HEIGHT,
 };
 use renegade_solidity_abi::v2::IDarkpoolV2::{self, IDarkpoolV2Instance};
-use types_core::Chain;
+use types_core::{Chain, ConfirmationDepth};
 use util::err_str;
 use util::log_task;
 use util::logging::Outcome;
@@ -29,11 +29,17 @@ use util::logging::Outcome;
 use crate::errors::{DarkpoolClientConfigError, DarkpoolClientError};
 use crate::logging::Task;
 
+pub mod calldata;
+pub mod chainlink;
+mod confirmation;
 mod contract_interaction;
 pub mod erc20;
 mod event_indexing;
+pub mod gas;
 mod nonce;
+pub mod uniswap_v3;
 
+use gas::{GasTracker, TxGasCost};
 use nonce::ResyncNonceManager;
 
 // -------------
@@ -81,6 +87,11 @@ const GAS_PRICE_MULTIPLIER: u128 = 2;
 /// estimates.
 const MIN_PRIORITY_FEE_WEI: u128 = 1_000_000;
 
+/// The metric counting failed RPC calls to the chain
+const RPC_ERRORS_METRIC: &str = "darkpool_rpc_errors";
+/// The metric tag naming the RPC method that failed
+const RPC_METHOD_METRIC_TAG: &str = "method";
+
 /// A type alias for the RPC client, which is an alloy middleware stack that
 /// includes a signer derived from a raw private key, and a provider that
 /// connects to the RPC endpoint over HTTP.
@@ -107,6 +118,8 @@ pub struct DarkpoolClientConfig {
     pub private_key: PrivateKeySigner,
     /// The interval at which to poll for event filters and pending transactions
     pub block_polling_interval: Duration,
+    /// The depth at which a submitted transaction is considered confirmed
+    pub confirmation_depth: ConfirmationDepth,
 }
 
 impl DarkpoolClientConfig {
@@ -169,9 +182,14 @@ pub struct DarkpoolClient {
     permit2_addr: Address,
     /// The address of the gas wallet used for signing transactions
     client_addr: Address,
+    /// The depth at which a submitted transaction is considered confirmed
+    confirmation_depth: ConfirmationDepth,
     /// Handle to the provider's nonce cache, used to force a resync from the
     /// chain after a failed submission (see `ResyncNonceManager`)
     nonce_manager: ResyncNonceManager,
+    /// The tracker recording the gas spent by transactions submitted through
+    /// this handle, if one is attached
+    gas_tracker: Option<GasTracker>,
 }
 
 impl DarkpoolClient {
@@ -188,10 +206,22 @@ impl DarkpoolClient {
             deploy_block,
             permit2_addr: config.permit2_addr,
             client_addr,
+            confirmation_depth: config.confirmation_depth,
             nonce_manager,
+            gas_tracker: None,
         })
     }
 
+    /// Clone the client with a fresh gas tracker attached
+    ///
+    /// Transactions submitted through the returned client record their gas
+    /// costs in the returned tracker
+    pub fn with_gas_tracker(&self) -> (Self, GasTracker) {
+        let tracker = GasTracker::default();
+        let client = Self { gas_tracker: Some(tracker.clone()), ..self.clone() };
+        (client, tracker)
+    }
+
     /// Mark the signer's cached nonce stale after a failed submission, so the
     /// next submit refetches the chain's pending count. One lost head tx
     /// otherwise nonce-gaps every subsequent tx from this signer until process
@@ -234,8 +264,9 @@ impl DarkpoolClient {
                     "chain_id RPC timed out after {}s",
                     RPC_READ_TIMEOUT.as_secs()
                 ))
-            })?
-            .map_err(err_str!(DarkpoolClientError::Rpc))
+            })
+            .and_then(|res| res.map_err(err_str!(DarkpoolClientError::Rpc)))
+            .inspect_err(|_| record_rpc_error("chain_id"))
     }
 
     /// Get the current block number
@@ -247,8 +278,26 @@ impl DarkpoolClient {
                     "block_number RPC timed out after {}s",
                     RPC_READ_TIMEOUT.as_secs()
                 ))
-            })?
-            .map_err(err_str!(DarkpoolClientError::Rpc))
+            })
+            .and_then(|res| res.map_err(err_str!(DarkpoolClientError::Rpc)))
+            .inspect_err(|_| record_rpc_error("block_number"))
+    }
+
+    /// Get the node's gas price oracle estimate, in wei
+    ///
+    /// This is the price a transaction submitted now is expected to pay per
+    /// unit of gas, as opposed to the max-fee cap set on submitted transactions
+    pub async fn estimate_gas_price(&self) -> Result<u128, DarkpoolClientError> {
+        tokio::time::timeout(RPC_READ_TIMEOUT, self.provider().get_gas_price())
+            .await
+            .map_err(|_| {
+                DarkpoolClientError::Rpc(format!(
+                    "gas_price RPC timed out after {}s",
+                    RPC_READ_TIMEOUT.as_secs()
+                ))
+            })
+            .and_then(|res| res.map_err(err_str!(DarkpoolClientError::Rpc)))
+            .inspect_err(|_| record_rpc_error("gas_price"))
     }
 
     /// Create an event filter
@@ -280,6 +329,7 @@ impl DarkpoolClient {
             tokio::time::timeout(TX_SUBMIT_TIMEOUT, self.get_adjusted_eip1559_fees())
                 .await
                 .map_err(|_| {
+                    record_rpc_error("estimate_fees");
                     DarkpoolClientError::contract_interaction(format!(
                         "gas fee estimation timed out after {}s (client_addr = {:#x})",
                         TX_SUBMIT_TIMEOUT.as_secs(),
@@ -318,6 +368,7 @@ impl DarkpoolClient {
                 // The nonce filler may or may not have consumed a nonce before
                 // the timeout dropped the send future; resync either way
                 self.resync_nonce_on_failure();
+                record_rpc_error("send_tx");
                 log_task!(
                     Task::SubmitTx,
                     Outcome::Failed,
@@ -341,6 +392,7 @@ impl DarkpoolClient {
                 // Broadcast rejected after the filler consumed a nonce: the
                 // cache is now one ahead of the chain
                 self.resync_nonce_on_failure();
+                record_rpc_error("send_tx");
                 // Decode the error payload if possible using the ABI
                 let decoded =
                     err_payload.as_decoded_interface_error::<IDarkpoolV2::IDarkpoolV2Errors>();
@@ -358,6 +410,7 @@ impl DarkpoolClient {
                 // Same as above: a nonce may have been consumed for a tx that
                 // never made it to the pool
                 self.resync_nonce_on_failure();
+                record_rpc_error("send_tx");
                 return Err(DarkpoolClientError::contract_interaction(format!(
                     "{e} (client_addr = {:#x})",
                     self.client_addr
@@ -386,6 +439,7 @@ impl DarkpoolClient {
                 // later tx from this signer; resync so the next submit refetches
                 // pending and refills the gap.
                 self.resync_nonce_on_failure();
+                record_rpc_error("get_receipt");
                 log_task!(
                     Task::SubmitTx,
                     Outcome::Failed,
@@ -399,6 +453,11 @@ impl DarkpoolClient {
             },
         };
 
+        // Reverted transactions still pay for gas, so record the cost first
+        if let Some(tracker) = &self.gas_tracker {
+            tracker.record(TxGasCost::from_receipt(&receipt));
+        }
+
         // Check for failure
         if !receipt.status() {
             let error_msg = format!(
@@ -408,7 +467,7 @@ impl DarkpoolClient {
             return Err(DarkpoolClientError::contract_interaction(error_msg));
         }
 
-        Ok(receipt)
+        self.await_confirmation(receipt).await
     }
 
     /// Get EIP-1559 fees for submitting a transaction.
@@ -420,8 +479,12 @@ impl DarkpoolClient {
     /// between estimation and inclusion (the cap is a ceiling, not the amount
     /// paid). Returns `(max_fee_per_gas, max_priority_fee_per_gas)`.
     async fn get_adjusted_eip1559_fees(&self) -> Result<(u128, u128), DarkpoolClientError> {
-        let est =
-            self.provider().estimate_eip1559_fees().await.map_err(DarkpoolClientError::rpc)?;
+        let est = self
+            .provider()
+            .estimate_eip1559_fees()
+            .await
+            .map_err(DarkpoolClientError::rpc)
+            .inspect_err(|_| record_rpc_error("estimate_fees"))?;
         // Floor the tip: alloy's estimator returns 1 WEI when recent blocks
         // show zero reward percentiles (quiet testnet), which can leave a tx
         // accepted but never included
@@ -442,3 +505,8 @@ impl DarkpoolClient {
         Ok(())
     }
 }
+
+/// Record a failed RPC call to the chain
+fn record_rpc_error(method: &'static str) {
+    metrics::counter!(RPC_ERRORS_METRIC, RPC_METHOD_METRIC_TAG => method).increment(1);
+}
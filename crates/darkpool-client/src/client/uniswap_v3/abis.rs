@@ -0,0 +1,14 @@
+//! ABI definitions for Uniswap V3 pools
+//!
+//! Note that these definitions only contain the read-only subset of the
+//! `IUniswapV3Pool` interface used to compute TWAPs
+use alloy::sol;
+
+sol! {
+    #[sol(rpc)]
+    contract IUniswapV3Pool {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function observe(uint32[] calldata secondsAgos) external view returns (int56[] memory tickCumulatives, uint160[] memory secondsPerLiquidityCumulativeX128s);
+    }
+}
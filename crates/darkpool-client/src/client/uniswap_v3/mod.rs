@@ -0,0 +1,130 @@
+//! Uniswap V3 pool reads on the Darkpool client
+
+pub mod abis;
+
+use alloy_primitives::Address;
+
+use crate::{
+    client::{DarkpoolClient, RPC_READ_TIMEOUT, RenegadeProvider, record_rpc_error},
+    errors::DarkpoolClientError,
+};
+use abis::IUniswapV3Pool::IUniswapV3PoolInstance;
+
+/// The base of the Uniswap V3 tick price, each tick is a 1bp move
+const TICK_BASE: f64 = 1.0001;
+
+/// A time-weighted average price read from a Uniswap V3 pool
+#[derive(Clone, Copy, Debug)]
+pub struct UniswapV3Twap {
+    /// The pool's first token
+    pub token0: Address,
+    /// The pool's second token
+    pub token1: Address,
+    /// The arithmetic mean of the pool's tick over the window
+    pub mean_tick: f64,
+}
+
+impl UniswapV3Twap {
+    /// The TWAP in base units of the other token per base unit of `base`
+    ///
+    /// Returns `None` if `base` is not one of the pool's tokens
+    pub fn price_of(&self, base: Address) -> Option<f64> {
+        // A tick prices token0 in units of token1
+        let token0_price = TICK_BASE.powf(self.mean_tick);
+        if base == self.token0 {
+            Some(token0_price)
+        } else if base == self.token1 {
+            Some(1. / token0_price)
+        } else {
+            None
+        }
+    }
+}
+
+impl DarkpoolClient {
+    /// Get the time-weighted average price of a Uniswap V3 pool over the last
+    /// `window_secs` seconds
+    ///
+    /// The pool must have enough observation slots to cover the window, else
+    /// `observe` reverts
+    pub async fn get_uniswap_v3_twap(
+        &self,
+        pool: Address,
+        window_secs: u32,
+    ) -> Result<UniswapV3Twap, DarkpoolClientError> {
+        if window_secs == 0 {
+            return Err(DarkpoolClientError::uniswap_v3("TWAP window must be non-zero"));
+        }
+
+        let pool_client = self.uniswap_v3_client(pool);
+        let read = async {
+            let token0 = pool_client.token0().call().await?;
+            let token1 = pool_client.token1().call().await?;
+            let observations = pool_client.observe(vec![window_secs, 0]).call().await?;
+            Ok::<_, alloy::contract::Error>((token0, token1, observations.tickCumulatives))
+        };
+
+        let (token0, token1, tick_cumulatives) = tokio::time::timeout(RPC_READ_TIMEOUT, read)
+            .await
+            .map_err(|_| {
+                DarkpoolClientError::uniswap_v3(format!(
+                    "pool {pool} timed out after {}s",
+                    RPC_READ_TIMEOUT.as_secs()
+                ))
+            })
+            .and_then(|res| res.map_err(DarkpoolClientError::uniswap_v3))
+            .inspect_err(|_| record_rpc_error("uniswap_v3_twap"))?;
+
+        let [start, end] = tick_cumulatives[..] else {
+            let msg = format!("pool {pool} returned {} observations", tick_cumulatives.len());
+            return Err(DarkpoolClientError::uniswap_v3(msg));
+        };
+        let start = i64::try_from(start).map_err(DarkpoolClientError::uniswap_v3)?;
+        let end = i64::try_from(end).map_err(DarkpoolClientError::uniswap_v3)?;
+        let mean_tick = mean_tick(start, end, window_secs);
+
+        Ok(UniswapV3Twap { token0, token1, mean_tick })
+    }
+
+    /// Get an instance of a Uniswap V3 pool client
+    pub(crate) fn uniswap_v3_client(
+        &self,
+        pool: Address,
+    ) -> IUniswapV3PoolInstance<&RenegadeProvider> {
+        let provider = self.provider();
+        IUniswapV3PoolInstance::new(pool, provider)
+    }
+}
+
+/// The arithmetic mean of a pool's tick over a window, from the pool's tick
+/// accumulator at the window's start and end
+fn mean_tick(start_cumulative: i64, end_cumulative: i64, window_secs: u32) -> f64 {
+    (end_cumulative - start_cumulative) as f64 / f64::from(window_secs)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::Address;
+
+    use super::{UniswapV3Twap, mean_tick};
+
+    /// Tests that the mean tick is the accumulator's growth per second,
+    /// including for pools priced below one
+    #[test]
+    fn test_mean_tick() {
+        assert_eq!(mean_tick(1_000, 7_000, 60), 100.);
+        assert_eq!(mean_tick(-1_000, -7_000, 60), -100.);
+    }
+
+    /// Tests that a TWAP prices either of the pool's tokens in the other
+    #[test]
+    fn test_price_of() {
+        let (token0, token1) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let mean_tick = 2f64.ln() / 1.0001f64.ln();
+        let twap = UniswapV3Twap { token0, token1, mean_tick };
+
+        assert!((twap.price_of(token0).unwrap() - 2.).abs() < 1e-9);
+        assert!((twap.price_of(token1).unwrap() - 0.5).abs() < 1e-9);
+        assert!(twap.price_of(Address::repeat_byte(3)).is_none());
+    }
+}
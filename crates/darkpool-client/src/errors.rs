@@ -5,6 +5,9 @@ use alloy_sol_types::Error as SolError;
 /// The error type returned by the darkpool client interface
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum DarkpoolClientError {
+    /// An error reading a Chainlink price feed
+    #[error("Chainlink error: {0}")]
+    Chainlink(String),
     /// Error thrown when a commitment can't be found in the Merkle tree
     #[error("commitment not found")]
     CommitmentNotFound,
@@ -46,15 +49,31 @@ pub enum DarkpoolClientError {
     /// Error thrown when a transaction is dropped from the mempool
     #[error("transaction dropped from mempool")]
     TxDropped,
+    /// Error thrown when a mined transaction does not reach the confirmation
+    /// depth in time
+    ///
+    /// The transaction is still included and may yet be confirmed, so callers
+    /// must not resubmit it
+    #[error("transaction mined but not confirmed: {0}")]
+    TxUnconfirmed(String),
     /// Error thrown when a transaction can't be found
     #[error("transaction not found: {0}")]
     TxNotFound(String),
     /// Error thrown when getting a transaction fails
     #[error("transaction querying error: {0}")]
     TxQuerying(String),
+    /// An error reading a Uniswap V3 pool
+    #[error("Uniswap V3 error: {0}")]
+    UniswapV3(String),
 }
 
 impl DarkpoolClientError {
+    /// Create a new Chainlink error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn chainlink<T: ToString>(msg: T) -> Self {
+        Self::Chainlink(msg.to_string())
+    }
+
     /// Create a new contract interaction error
     #[allow(clippy::needless_pass_by_value)]
     pub fn contract_interaction<T: ToString>(msg: T) -> Self {
@@ -96,6 +115,12 @@ impl DarkpoolClientError {
     pub fn tx_querying<T: ToString>(msg: T) -> Self {
         Self::TxQuerying(msg.to_string())
     }
+
+    /// Create a new Uniswap V3 error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn uniswap_v3<T: ToString>(msg: T) -> Self {
+        Self::UniswapV3(msg.to_string())
+    }
 }
 
 /// The error type returned by the darkpool client configuration interface
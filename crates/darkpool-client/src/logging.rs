@@ -11,6 +11,8 @@ use util::logging::LogTask;
 pub enum Task {
     /// Generic transaction submission to the chain (pre-confirmation).
     SubmitTx,
+    /// Awaiting a submitted transaction's confirmation depth.
+    ConfirmTx,
     /// Create a new balance in the darkpool contract.
     CreateBalance,
     /// Deposit funds into an existing balance.
@@ -27,6 +29,7 @@ impl LogTask for Task {
     fn as_str(&self) -> &'static str {
         match self {
             Task::SubmitTx => "submit-tx",
+            Task::ConfirmTx => "confirm-tx",
             Task::CreateBalance => "create-balance",
             Task::Deposit => "deposit",
             Task::Withdraw => "withdraw",
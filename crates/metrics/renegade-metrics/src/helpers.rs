@@ -1,14 +1,19 @@
 //! Helpers for calculating and recording metrics
 
+use std::time::Duration;
+
 use alloy_primitives::Address;
 use darkpool_types::settlement_obligation::SettlementObligation;
-use types_core::{AccountId, MatchResult, Token};
+use types_core::{MatchResult, Token};
 use util::hex::address_to_hex_string;
 
 use crate::labels::{
-    ASSET_METRIC_TAG, BASE_ASSET_METRIC_TAG, EXTERNAL_MATCH_METRIC_TAG, FEES_COLLECTED_METRIC,
-    INTERNAL_MATCH_SETTLE_METRIC, MATCH_BASE_VOLUME_METRIC, MATCH_QUOTE_VOLUME_METRIC,
-    MATCHING_POOL_METRIC_TAG, SETTLE_OUTCOME_METRIC_TAG, wallet_id_tag,
+    ASSET_METRIC_TAG, BASE_ASSET_METRIC_TAG, CIRCUIT_CONSTRAINTS_METRIC,
+    CIRCUIT_DOMAIN_SIZE_METRIC, CIRCUIT_METRIC_TAG, CIRCUIT_PROOF_SIZE_METRIC,
+    CIRCUIT_WITNESS_SIZE_METRIC, EXTERNAL_MATCH_METRIC_TAG, FEES_COLLECTED_METRIC,
+    INTERNAL_MATCH_SETTLE_METRIC, MATCH_BASE_VOLUME_METRIC, MATCH_FILLS_METRIC,
+    MATCH_QUOTE_VOLUME_METRIC, MATCHING_POOL_METRIC_TAG, PROOF_GENERATION_LATENCY_METRIC,
+    SETTLE_OUTCOME_METRIC_TAG,
 };
 
 /// Get the human-readable asset and volume of
@@ -48,10 +53,10 @@ fn record_volume_with_tags(
 }
 
 /// Record the volume of base/quote assets moved in a match
-pub fn record_match_volume(res: &MatchResult, is_external_match: bool, account_ids: &[AccountId]) {
+pub fn record_match_volume(res: &MatchResult, is_external_match: bool) {
     // A match result contains one obligation per party; either obligation has
     // enough information to derive base/quote volumes for USDC-quoted pairs.
-    record_match_volume_from_obligation(&res.party0_obligation, is_external_match, account_ids);
+    record_match_volume_from_obligation(&res.party0_obligation, is_external_match);
 }
 
 /// Record the volume of base/quote assets moved by a settlement obligation
@@ -61,8 +66,8 @@ pub fn record_match_volume(res: &MatchResult, is_external_match: bool, account_i
 pub fn record_match_volume_from_obligation(
     obligation: &SettlementObligation,
     is_external_match: bool,
-    account_ids: &[AccountId],
 ) {
+    record_match_fill(is_external_match);
     let usdc = Token::usdc().get_alloy_address();
     let Some((base_mint, base_amount, quote_mint, quote_amount)) =
         derive_match_volumes(obligation, usdc)
@@ -70,7 +75,7 @@ pub fn record_match_volume_from_obligation(
         return;
     };
 
-    let mut labels = build_match_labels(account_ids, is_external_match);
+    let mut labels = build_match_labels(is_external_match);
 
     record_volume_with_tags(&base_mint, base_amount, MATCH_BASE_VOLUME_METRIC, &labels);
 
@@ -79,6 +84,16 @@ pub fn record_match_volume_from_obligation(
     record_volume_with_tags(&quote_mint, quote_amount, MATCH_QUOTE_VOLUME_METRIC, &labels);
 }
 
+/// Record a settled match fill, tagged by whether it was against an external
+/// party
+///
+/// Unlike the volume metrics, fills are counted for every pair, so the
+/// internal-vs-external fill ratio covers all of the relayer's order flow
+pub fn record_match_fill(is_external_match: bool) {
+    let labels = [(EXTERNAL_MATCH_METRIC_TAG.to_string(), is_external_match.to_string())];
+    metrics::counter!(MATCH_FILLS_METRIC, &labels).increment(1);
+}
+
 /// Record the volume of a fee settlement into the relayer's wallet
 pub fn record_relayer_fee_settlement(mint: &Address, amount: u128) {
     record_volume(mint, amount, FEES_COLLECTED_METRIC);
@@ -98,6 +113,29 @@ pub fn record_internal_match_settle(matching_pool: &str, settled: bool) {
     metrics::counter!(INTERNAL_MATCH_SETTLE_METRIC, &labels).increment(1);
 }
 
+/// Record the time taken to generate a proof
+pub fn record_proof_generation_latency(latency: Duration) {
+    metrics::histogram!(PROOF_GENERATION_LATENCY_METRIC).record(latency.as_secs_f64());
+}
+
+/// Record the size of a circuit, tagged by circuit name
+///
+/// These are recorded once at startup so that a deployment proving larger
+/// circuits than expected after an upgrade is visible before proofs slow down
+pub fn record_circuit_size(
+    circuit: &str,
+    n_constraints: usize,
+    domain_size: usize,
+    witness_size: usize,
+    proof_size: usize,
+) {
+    let labels = [(CIRCUIT_METRIC_TAG.to_string(), circuit.to_string())];
+    metrics::gauge!(CIRCUIT_CONSTRAINTS_METRIC, &labels).set(n_constraints as f64);
+    metrics::gauge!(CIRCUIT_DOMAIN_SIZE_METRIC, &labels).set(domain_size as f64);
+    metrics::gauge!(CIRCUIT_WITNESS_SIZE_METRIC, &labels).set(witness_size as f64);
+    metrics::gauge!(CIRCUIT_PROOF_SIZE_METRIC, &labels).set(proof_size as f64);
+}
+
 /// Derive (base_mint, base_amount, quote_mint, quote_amount) from an
 /// obligation.
 fn derive_match_volumes(
@@ -126,13 +164,11 @@ fn derive_match_volumes(
 }
 
 /// Build labels shared by match metrics.
-fn build_match_labels(account_ids: &[AccountId], is_external_match: bool) -> Vec<(String, String)> {
-    let mut labels: Vec<_> = account_ids
-        .iter()
-        .enumerate()
-        .map(|(i, account_id)| (wallet_id_tag(i + 1), account_id.to_string()))
-        .collect();
-
+///
+/// Accounts are not labelled, as a label per account would give the metrics
+/// unbounded cardinality
+fn build_match_labels(is_external_match: bool) -> Vec<(String, String)> {
+    let mut labels = Vec::new();
     if is_external_match {
         labels.push((EXTERNAL_MATCH_METRIC_TAG.to_string(), "true".to_string()));
     }
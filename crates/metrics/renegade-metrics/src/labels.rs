@@ -20,12 +20,17 @@ pub const WITHDRAWAL_VOLUME_METRIC: &str = "withdrawal_volume";
 pub const MATCH_BASE_VOLUME_METRIC: &str = "match_base_volume";
 /// Metric describing the volume of the quote asset in a match
 pub const MATCH_QUOTE_VOLUME_METRIC: &str = "match_quote_volume";
+/// Metric counting settled match fills, tagged by whether the fill was
+/// against an external party. The external share of fills is the fraction of
+/// local order flow not crossed internally
+pub const MATCH_FILLS_METRIC: &str = "num_match_fills";
 /// Metric describing the total fees collected by asset
 pub const FEES_COLLECTED_METRIC: &str = "fees_collected";
 /// Metric counting internal-match settlement outcomes, tagged by matching pool
 /// and outcome. The rate per pool is the per-base settlement demand (λ); the
 /// failed/(settled+failed) ratio is the per-pool preemption-conflict rate. Used
-/// to size quoter sharding (see ticket 2026-05-30-batched-internal-match-settlement).
+/// to size quoter sharding (see ticket
+/// 2026-05-30-batched-internal-match-settlement).
 pub const INTERNAL_MATCH_SETTLE_METRIC: &str = "internal_match_settle";
 
 // P2P metrics
@@ -36,6 +41,23 @@ pub const NUM_LOCAL_PEERS_METRIC: &str = "num_local_peers";
 /// Metric describing the number of remote peers the relayer
 /// is connected to
 pub const NUM_REMOTE_PEERS_METRIC: &str = "num_remote_peers";
+/// Metric describing the estimated clock skew of a peer relative to the local
+/// node, in milliseconds
+pub const PEER_CLOCK_SKEW_METRIC: &str = "peer_clock_skew_ms";
+/// Metric describing the number of peers whose heartbeats timed out
+pub const NUM_HEARTBEAT_FAILURES_METRIC: &str = "num_heartbeat_failures";
+/// Metric describing the number of inbound gossip messages dropped for
+/// exceeding their sender's rate limit
+pub const NUM_GOSSIP_RATE_LIMITED_METRIC: &str = "num_gossip_rate_limited";
+/// Metric describing the number of times a peer was penalized for repeatedly
+/// exceeding its gossip rate limits
+pub const NUM_GOSSIP_PEERS_PENALIZED_METRIC: &str = "num_gossip_peers_penalized";
+/// Metric describing the number of inbound pubsub messages rejected for an
+/// origin signature that does not match their claimed cluster
+pub const NUM_PUBSUB_ORIGIN_REJECTED_METRIC: &str = "num_pubsub_origin_rejected";
+/// Metric describing the number of inbound pubsub messages accepted without
+/// the origin signature their type requires
+pub const NUM_PUBSUB_UNSIGNED_ACCEPTED_METRIC: &str = "num_pubsub_unsigned_accepted";
 
 // Task metrics
 
@@ -43,6 +65,48 @@ pub const NUM_REMOTE_PEERS_METRIC: &str = "num_remote_peers";
 pub const NUM_INFLIGHT_TASKS_METRIC: &str = "num_inflight_tasks";
 /// Metric describing the number of tasks completed
 pub const NUM_COMPLETED_TASKS_METRIC: &str = "num_completed_tasks";
+/// Metric describing the number of tasks queued across all task queues
+pub const TASK_QUEUE_DEPTH_METRIC: &str = "task_queue_depth";
+
+// Proof metrics
+
+/// Metric describing the time taken to generate a proof, in seconds
+pub const PROOF_GENERATION_LATENCY_METRIC: &str = "proof_generation_latency";
+/// Metric describing the number of constraints (gates) in a circuit
+pub const CIRCUIT_CONSTRAINTS_METRIC: &str = "circuit_constraints";
+/// Metric describing the size of a circuit's evaluation domain, i.e. its
+/// constraint count padded to the next power of two
+pub const CIRCUIT_DOMAIN_SIZE_METRIC: &str = "circuit_domain_size";
+/// Metric describing the number of scalars in a circuit's witness
+pub const CIRCUIT_WITNESS_SIZE_METRIC: &str = "circuit_witness_size";
+/// Metric describing the expected size of a circuit's proof, in bytes
+pub const CIRCUIT_PROOF_SIZE_METRIC: &str = "circuit_proof_size_bytes";
+
+// Price feed metrics
+
+/// Metric counting the prices received on each price feed; its rate is the
+/// feed's message rate
+pub const PRICE_FEED_MESSAGES_METRIC: &str = "price_feed_messages";
+/// Metric counting the messages on each price feed that could not be parsed
+/// into a price
+pub const PRICE_FEED_PARSE_FAILURES_METRIC: &str = "price_feed_parse_failures";
+/// Metric describing the delay between a price feed timestamping a price and
+/// the relayer receiving it, in milliseconds
+pub const PRICE_FEED_LATENCY_METRIC: &str = "price_feed_latency_ms";
+/// Metric describing the age of the price an on-chain feed reports, i.e. the
+/// time since the feed last updated it, in milliseconds
+pub const PRICE_FEED_AGE_METRIC: &str = "price_feed_age_ms";
+/// Metric counting the reconnect attempts of each price reporter connection
+pub const PRICE_FEED_RECONNECTS_METRIC: &str = "price_feed_reconnects";
+
+// Websocket metrics
+
+/// Metric describing the number of open websocket connections
+pub const WEBSOCKET_ACTIVE_CONNECTIONS_METRIC: &str = "websocket_active_connections";
+/// Metric describing the number of active websocket subscriptions on a route
+pub const WEBSOCKET_ACTIVE_SUBSCRIPTIONS_METRIC: &str = "websocket_active_subscriptions";
+/// Metric counting websocket connections rejected by the session limits
+pub const WEBSOCKET_REJECTED_CONNECTIONS_METRIC: &str = "websocket_rejected_connections";
 
 // Event metrics
 
@@ -53,6 +117,13 @@ pub const NUM_EVENT_SEND_FAILURES_METRIC: &str = "num_event_send_failures";
 /// manager
 pub const NUM_EVENT_EXPORT_FAILURES_METRIC: &str = "num_event_export_failures";
 
+// Job queue metrics
+
+/// Metric describing the number of jobs waiting in a worker's job queue
+pub const JOB_QUEUE_LENGTH_METRIC: &str = "job_queue_length";
+/// Metric describing the number of jobs rejected by a full job queue
+pub const NUM_JOB_QUEUE_REJECTIONS_METRIC: &str = "num_job_queue_rejections";
+
 // ---------------
 // | METRIC TAGS |
 // ---------------
@@ -65,9 +136,23 @@ pub const BASE_ASSET_METRIC_TAG: &str = "base_asset";
 pub const EXTERNAL_MATCH_METRIC_TAG: &str = "is_external_match";
 /// Metric tag for the matching pool an internal-match settlement targets
 pub const MATCHING_POOL_METRIC_TAG: &str = "matching_pool";
+/// Metric tag for the circuit a circuit size metric describes
+pub const CIRCUIT_METRIC_TAG: &str = "circuit";
+/// Metric tag for the peer a P2P metric describes
+pub const PEER_ID_METRIC_TAG: &str = "peer_id";
 /// Metric tag for an internal-match settlement outcome (`settled` | `failed`)
 pub const SETTLE_OUTCOME_METRIC_TAG: &str = "outcome";
-/// Helper to generate wallet ID tag names
-pub fn wallet_id_tag(n: usize) -> String {
-    format!("wallet_id{}", n)
-}
+/// Metric tag for the exchange a price feed metric describes
+pub const EXCHANGE_METRIC_TAG: &str = "exchange";
+/// Metric tag for the source of a price feed (`stream` | `rest` | `onchain`)
+pub const PRICE_SOURCE_METRIC_TAG: &str = "source";
+/// Metric tag for the price reporter connection a reconnect metric describes
+pub const CONNECTION_METRIC_TAG: &str = "connection";
+/// Metric tag for the name of a worker's job queue
+pub const JOB_QUEUE_METRIC_TAG: &str = "queue";
+/// Metric tag for the class of a rate limited gossip message
+pub const GOSSIP_MESSAGE_CLASS_METRIC_TAG: &str = "message_class";
+/// Metric tag for the route pattern of a websocket subscription
+pub const WEBSOCKET_ROUTE_METRIC_TAG: &str = "route";
+/// Metric tag for the session limit that rejected a websocket connection
+pub const SESSION_LIMIT_METRIC_TAG: &str = "limit";
@@ -19,7 +19,7 @@ use snapshot::{ENV_SNAP_BUCKET, download_snapshot};
 use tokio::process::Command;
 use util::log_task;
 use util::logging::Outcome;
-use util::telemetry::{LevelFilter, setup_system_logger};
+use util::telemetry::{LevelFilter, LogFormat, setup_system_logger};
 
 use crate::logging::Task;
 
@@ -42,7 +42,7 @@ const RELAYER_BIN: &str = "/bin/renegade-relayer";
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
-    setup_system_logger(LevelFilter::INFO);
+    setup_system_logger(LevelFilter::INFO, LogFormat::Pretty);
 
     // Build an s3 client
     let s3_client = build_s3_client().await;
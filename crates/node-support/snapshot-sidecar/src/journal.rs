@@ -0,0 +1,300 @@
+//! An on-disk journal of the sidecar's snapshot uploads
+//!
+//! The journal records the raft index of the last snapshot uploaded and the
+//! progress of any in-flight multipart upload. This lets the sidecar resume an
+//! upload interrupted by a crash or a failed request from its last completed
+//! part, and avoid re-uploading a snapshot it has already stored.
+//!
+//! The journal is a small line-oriented text file, rewritten atomically (write
+//! then rename) on every change so that a crash never leaves it half-written
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use util::raw_err_str;
+
+/// The name of the journal file within the journal directory
+const JOURNAL_FILE_NAME: &str = "upload.journal";
+/// The name of the temporary file the journal is written to before renaming
+const JOURNAL_TMP_FILE_NAME: &str = "upload.journal.tmp";
+
+/// The journal line recording the last uploaded index
+const LAST_UPLOADED_TAG: &str = "last_uploaded";
+/// The journal line recording an in-flight upload
+const PENDING_TAG: &str = "pending";
+/// The journal line recording a completed part of the in-flight upload
+const PART_TAG: &str = "part";
+
+/// An in-flight multipart upload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingUpload {
+    /// The raft log index of the snapshot being uploaded
+    pub index: u64,
+    /// The object key the snapshot is uploaded to
+    pub key: String,
+    /// The S3 multipart upload ID
+    pub upload_id: String,
+    /// The local copy of the snapshot being uploaded
+    ///
+    /// The relayer overwrites its snapshot in place, so the sidecar uploads
+    /// from a copy that is stable across restarts
+    pub staging_path: PathBuf,
+    /// The part numbers and ETags of the parts uploaded so far, in order
+    pub parts: Vec<(i32, String)>,
+}
+
+/// The on-disk upload journal
+pub struct UploadJournal {
+    /// The directory holding the journal and staged snapshots
+    dir: PathBuf,
+    /// The raft log index of the last snapshot uploaded
+    last_uploaded: Option<u64>,
+    /// The in-flight upload, if any
+    pending: Option<PendingUpload>,
+}
+
+impl UploadJournal {
+    /// Open the journal in the given directory, creating it if it does not
+    /// exist
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(raw_err_str!("Failed to create journal dir: {}"))?;
+        let mut journal = Self { dir: dir.to_path_buf(), last_uploaded: None, pending: None };
+
+        let path = journal.journal_path();
+        if path.exists() {
+            let contents =
+                fs::read_to_string(&path).map_err(raw_err_str!("Failed to read journal: {}"))?;
+            journal.parse(&contents)?;
+        }
+
+        Ok(journal)
+    }
+
+    /// The raft log index of the last snapshot uploaded
+    pub fn last_uploaded(&self) -> Option<u64> {
+        self.last_uploaded
+    }
+
+    /// The in-flight upload, if any
+    pub fn pending(&self) -> Option<&PendingUpload> {
+        self.pending.as_ref()
+    }
+
+    /// The path at which to stage the snapshot with the given index
+    pub fn staging_path(&self, index: u64) -> PathBuf {
+        self.dir.join(format!("snapshot-{index}.gz"))
+    }
+
+    /// Record the start of a multipart upload
+    pub fn begin(&mut self, upload: PendingUpload) -> Result<(), String> {
+        self.pending = Some(upload);
+        self.persist()
+    }
+
+    /// Record a completed part of the in-flight upload
+    pub fn record_part(&mut self, part_number: i32, etag: String) -> Result<(), String> {
+        let pending = self.pending.as_mut().ok_or("no upload in flight")?;
+        pending.parts.push((part_number, etag));
+        self.persist()
+    }
+
+    /// Record the completion of the in-flight upload
+    pub fn complete(&mut self) -> Result<(), String> {
+        let pending = self.pending.take().ok_or("no upload in flight")?;
+        self.last_uploaded = Some(pending.index);
+        self.persist()?;
+        remove_staged_file(&pending.staging_path);
+        Ok(())
+    }
+
+    /// Drop the in-flight upload without marking it as uploaded
+    pub fn abandon(&mut self) -> Result<Option<PendingUpload>, String> {
+        let pending = self.pending.take();
+        self.persist()?;
+        if let Some(p) = pending.as_ref() {
+            remove_staged_file(&p.staging_path);
+        }
+
+        Ok(pending)
+    }
+
+    // -----------
+    // | Helpers |
+    // -----------
+
+    /// The path of the journal file
+    fn journal_path(&self) -> PathBuf {
+        self.dir.join(JOURNAL_FILE_NAME)
+    }
+
+    /// Write the journal to disk atomically
+    fn persist(&self) -> Result<(), String> {
+        let tmp_path = self.dir.join(JOURNAL_TMP_FILE_NAME);
+        let mut file =
+            fs::File::create(&tmp_path).map_err(raw_err_str!("Failed to write journal: {}"))?;
+        file.write_all(self.serialize().as_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(raw_err_str!("Failed to write journal: {}"))?;
+
+        fs::rename(&tmp_path, self.journal_path())
+            .map_err(raw_err_str!("Failed to replace journal: {}"))
+    }
+
+    /// Serialize the journal to its on-disk format
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        if let Some(index) = self.last_uploaded {
+            out.push_str(&format!("{LAST_UPLOADED_TAG} {index}\n"));
+        }
+
+        if let Some(p) = self.pending.as_ref() {
+            let staging = p.staging_path.display();
+            out.push_str(&format!(
+                "{PENDING_TAG} {} {} {} {staging}\n",
+                p.index, p.key, p.upload_id
+            ));
+            for (part_number, etag) in p.parts.iter() {
+                out.push_str(&format!("{PART_TAG} {part_number} {etag}\n"));
+            }
+        }
+
+        out
+    }
+
+    /// Parse the journal from its on-disk format
+    fn parse(&mut self, contents: &str) -> Result<(), String> {
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.splitn(5, ' ').collect();
+            match fields.as_slice() {
+                [LAST_UPLOADED_TAG, index] => {
+                    self.last_uploaded = Some(parse_field(index)?);
+                },
+                [PENDING_TAG, index, key, upload_id, staging] => {
+                    self.pending = Some(PendingUpload {
+                        index: parse_field(index)?,
+                        key: key.to_string(),
+                        upload_id: upload_id.to_string(),
+                        staging_path: PathBuf::from(staging),
+                        parts: Vec::new(),
+                    });
+                },
+                [PART_TAG, part_number, etag] => {
+                    let pending = self.pending.as_mut().ok_or("part recorded without upload")?;
+                    pending.parts.push((parse_field(part_number)?, etag.to_string()));
+                },
+                _ => return Err(format!("Malformed journal line: {line}")),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a numeric journal field
+fn parse_field<N: std::str::FromStr>(field: &str) -> Result<N, String> {
+    field.parse().map_err(|_| format!("Malformed journal field: {field}"))
+}
+
+/// Remove a staged snapshot, ignoring errors as the file may already be gone
+fn remove_staged_file(path: &Path) {
+    let _ = fs::remove_file(path);
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{JOURNAL_FILE_NAME, PendingUpload, UploadJournal};
+
+    /// Build an in-flight upload of the given snapshot, staged in the journal
+    /// directory
+    fn pending_upload(journal: &UploadJournal, index: u64) -> PendingUpload {
+        PendingUpload {
+            index,
+            key: format!("snapshots/{index}.gz"),
+            upload_id: "upload-id".to_string(),
+            staging_path: journal.staging_path(index),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Tests that an in-flight upload and its completed parts survive a
+    /// restart
+    #[test]
+    fn test_resume_pending_upload() {
+        let dir = tempdir().unwrap();
+        let mut journal = UploadJournal::open(dir.path()).unwrap();
+        assert_eq!(journal.last_uploaded(), None);
+        assert!(journal.pending().is_none());
+
+        let upload = pending_upload(&journal, 42);
+        journal.begin(upload.clone()).unwrap();
+        journal.record_part(1, "etag-1".to_string()).unwrap();
+        journal.record_part(2, "etag-2".to_string()).unwrap();
+
+        let reopened = UploadJournal::open(dir.path()).unwrap();
+        let expected = PendingUpload {
+            parts: vec![(1, "etag-1".to_string()), (2, "etag-2".to_string())],
+            ..upload
+        };
+        assert_eq!(reopened.pending(), Some(&expected));
+        assert_eq!(reopened.last_uploaded(), None);
+    }
+
+    /// Tests that completing an upload records its index and removes the
+    /// staged snapshot
+    #[test]
+    fn test_complete_upload() {
+        let dir = tempdir().unwrap();
+        let mut journal = UploadJournal::open(dir.path()).unwrap();
+        let upload = pending_upload(&journal, 7);
+        fs::write(&upload.staging_path, b"snapshot").unwrap();
+
+        journal.begin(upload.clone()).unwrap();
+        journal.complete().unwrap();
+        assert!(!upload.staging_path.exists());
+
+        let reopened = UploadJournal::open(dir.path()).unwrap();
+        assert_eq!(reopened.last_uploaded(), Some(7));
+        assert!(reopened.pending().is_none());
+    }
+
+    /// Tests that abandoning an upload keeps the last uploaded index and
+    /// removes the staged snapshot
+    #[test]
+    fn test_abandon_upload() {
+        let dir = tempdir().unwrap();
+        let mut journal = UploadJournal::open(dir.path()).unwrap();
+        journal.begin(pending_upload(&journal, 1)).unwrap();
+        journal.complete().unwrap();
+
+        let upload = pending_upload(&journal, 2);
+        fs::write(&upload.staging_path, b"snapshot").unwrap();
+        journal.begin(upload.clone()).unwrap();
+        assert_eq!(journal.abandon().unwrap(), Some(upload.clone()));
+        assert!(!upload.staging_path.exists());
+
+        let reopened = UploadJournal::open(dir.path()).unwrap();
+        assert_eq!(reopened.last_uploaded(), Some(1));
+        assert!(reopened.pending().is_none());
+        assert!(journal.record_part(1, "etag".to_string()).is_err());
+    }
+
+    /// Tests that a corrupt journal is refused rather than misread
+    #[test]
+    fn test_malformed_journal() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(JOURNAL_FILE_NAME);
+
+        fs::write(&path, "part 1 etag-1\n").unwrap();
+        assert!(UploadJournal::open(dir.path()).is_err());
+
+        fs::write(&path, "last_uploaded not-a-number\n").unwrap();
+        assert!(UploadJournal::open(dir.path()).is_err());
+    }
+}
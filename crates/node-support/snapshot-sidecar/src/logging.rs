@@ -12,6 +12,8 @@ pub enum Task {
     HandleSnapshot,
     /// Uploading a snapshot file to the configured S3 bucket.
     UploadSnapshot,
+    /// Resuming a snapshot upload recorded as in-flight in the journal.
+    ResumeUpload,
 }
 
 impl LogTask for Task {
@@ -20,6 +22,7 @@ impl LogTask for Task {
             Task::LeaderCheck => "leader-check",
             Task::HandleSnapshot => "handle-snapshot",
             Task::UploadSnapshot => "upload-snapshot",
+            Task::ResumeUpload => "resume-upload",
         }
     }
 }
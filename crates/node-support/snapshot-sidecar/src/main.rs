@@ -15,7 +15,7 @@ use std::{
 };
 
 use aws_config::Region;
-use aws_sdk_s3::{Client, primitives::ByteStream};
+use aws_sdk_s3::Client;
 use clap::Parser;
 use config::{RelayerConfig, parsing::config_file::parse_config_from_file};
 use external_api::http::admin::{IS_LEADER_ROUTE, IsLeaderResponse};
@@ -24,14 +24,22 @@ use reqwest::Client as HttpClient;
 use util::logging::Outcome;
 use util::{get_current_time_millis, log_task, raw_err_str};
 
+use crate::journal::UploadJournal;
 use crate::logging::Task;
+use crate::upload::{resume_pending_upload, upload_snapshot};
 
+mod journal;
 mod logging;
+mod upload;
 
 /// The postfix of the snapshot path
 const SNAPSHOT_FILE_NAME: &str = "snapshot.gz";
 /// The postfix of the snapshot lock file path
 const SNAPSHOT_LOCK_FILE_NAME: &str = "snapshot.lock";
+/// The postfix of the file holding the snapshot's raft log index
+const SNAPSHOT_INDEX_FILE_NAME: &str = "snapshot.index";
+/// The default name of the journal directory within the snapshot directory
+const DEFAULT_JOURNAL_DIR_NAME: &str = "sidecar";
 
 /// The sidecar CLI
 #[derive(Debug, Parser)]
@@ -56,6 +64,12 @@ struct Cli {
     /// Defaults to one minute
     #[clap(short, long, default_value = "60")]
     interval: u64,
+    /// The directory in which to keep the upload journal and staged snapshots
+    ///
+    /// Defaults to a `sidecar` directory within the relayer's snapshot
+    /// directory
+    #[clap(long)]
+    journal_dir: Option<String>,
 }
 
 /// Main
@@ -77,6 +91,17 @@ async fn main() {
         fs::create_dir_all(path).expect("Failed to create snapshot directory");
     }
 
+    // Open the journal and finish any upload interrupted by a crash
+    let journal_dir = cli
+        .journal_dir
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| path.join(DEFAULT_JOURNAL_DIR_NAME));
+    let mut journal = UploadJournal::open(&journal_dir).expect("Failed to open upload journal");
+    if let Err(e) = resume_pending_upload(&cli.bucket, &s3_client, &mut journal).await {
+        log_task!(Task::ResumeUpload, Outcome::Failed, error = %e, "failed to resume upload");
+    }
+
     // Build a notification channel
     let (tx, rx) = channel();
     let mut watcher =
@@ -90,7 +115,7 @@ async fn main() {
         if let EventKind::Remove(RemoveKind::File) = event.kind {
             let lock_path = snapshot_lock_path(&relayer_config);
             if event.paths.contains(&lock_path) && Instant::now() - last_event > debounce_interval {
-                maybe_record_snapshot(&cli, &relayer_config, &s3_client).await;
+                maybe_record_snapshot(&cli, &relayer_config, &s3_client, &mut journal).await;
                 last_event = Instant::now();
             }
         }
@@ -98,7 +123,12 @@ async fn main() {
 }
 
 /// Check if the local node is the leader and record the snapshot if so
-async fn maybe_record_snapshot(args: &Cli, conf: &RelayerConfig, s3_client: &Client) {
+async fn maybe_record_snapshot(
+    args: &Cli,
+    conf: &RelayerConfig,
+    s3_client: &Client,
+    journal: &mut UploadJournal,
+) {
     // Check if the local relayer is the leader first
     match check_leader(&args.http_addr).await {
         Ok(false) => {
@@ -113,17 +143,41 @@ async fn maybe_record_snapshot(args: &Cli, conf: &RelayerConfig, s3_client: &Cli
     };
 
     // Record the snapshot
-    if let Err(e) = handle_new_snapshot(&args.bucket, conf, s3_client).await {
+    if let Err(e) = handle_new_snapshot(&args.bucket, conf, s3_client, journal).await {
         log_task!(Task::HandleSnapshot, Outcome::Failed, error = %e, "failed to handle new snapshot");
     }
 }
 
 /// Copy a snapshot to s3
+///
+/// Any upload left incomplete by a previous failure is finished first, and
+/// snapshots already uploaded are skipped
 async fn handle_new_snapshot(
     bucket: &str,
     conf: &RelayerConfig,
     s3_client: &Client,
+    journal: &mut UploadJournal,
 ) -> Result<(), String> {
+    if let Err(e) = resume_pending_upload(bucket, s3_client, journal).await {
+        log_task!(
+            Task::HandleSnapshot,
+            Outcome::Partial,
+            error = %e,
+            "previous upload abandoned, uploading new snapshot"
+        );
+    }
+
+    let index = read_snapshot_index(conf).await?;
+    if journal.last_uploaded().is_some_and(|last| index <= last) {
+        log_task!(
+            Task::UploadSnapshot,
+            Outcome::Skipped,
+            index = index,
+            "snapshot already uploaded, skipping"
+        );
+        return Ok(());
+    }
+
     // Build the file path
     let ts = get_current_time_millis();
     let path = snapshot_path(conf);
@@ -134,18 +188,20 @@ async fn handle_new_snapshot(
         Outcome::Started,
         subject = %file_name,
         bucket = %bucket,
+        index = index,
         "uploading snapshot"
     );
 
-    // Send the file at `path` to the s3 bucket
-    let body = ByteStream::read_from().path(path).build().await.map_err(raw_err_str!("{}"))?;
-    let put_object_request = s3_client.put_object().bucket(bucket).key(file_name).body(body);
+    upload_snapshot(bucket, file_name, index, &path, s3_client, journal).await
+}
 
-    put_object_request
-        .send()
+/// Read the raft log index of the current snapshot
+async fn read_snapshot_index(conf: &RelayerConfig) -> Result<u64, String> {
+    let path = conf.raft_snapshot_path().join(SNAPSHOT_INDEX_FILE_NAME);
+    let contents = tokio::fs::read_to_string(&path)
         .await
-        .map_err(raw_err_str!("Failed to upload file to S3: {}"))
-        .map(|_| ())
+        .map_err(raw_err_str!("Failed to read snapshot index: {}"))?;
+    contents.trim().parse().map_err(raw_err_str!("Malformed snapshot index: {}"))
 }
 
 /// Check whether the local relayer is a leader
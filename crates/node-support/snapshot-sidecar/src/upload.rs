@@ -0,0 +1,191 @@
+//! Journaled multipart uploads of snapshots to S3
+//!
+//! Each snapshot is copied to a staging file, then uploaded in fixed-size
+//! parts. The journal is updated after every part, so an interrupted upload
+//! continues from its last completed part rather than starting over
+
+use std::path::Path;
+
+use aws_sdk_s3::{
+    Client,
+    primitives::{ByteStream, Length},
+    types::{CompletedMultipartUpload, CompletedPart},
+};
+use util::log_task;
+use util::logging::Outcome;
+use util::raw_err_str;
+
+use crate::journal::{PendingUpload, UploadJournal};
+use crate::logging::Task;
+
+/// The size of each part of a multipart upload
+///
+/// S3 requires every part but the last to be at least 5 MiB
+const UPLOAD_PART_SIZE: u64 = 64 * 1024 * 1024; // 64 MiB
+
+/// Stage the snapshot at `source` and upload it under `key`, journaling the
+/// upload's progress
+pub async fn upload_snapshot(
+    bucket: &str,
+    key: String,
+    index: u64,
+    source: &Path,
+    s3_client: &Client,
+    journal: &mut UploadJournal,
+) -> Result<(), String> {
+    // Copy the snapshot so that the relayer may overwrite it mid-upload
+    let staging_path = journal.staging_path(index);
+    tokio::fs::copy(source, &staging_path)
+        .await
+        .map_err(raw_err_str!("Failed to stage snapshot: {}"))?;
+
+    let res = s3_client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(&key)
+        .send()
+        .await
+        .map_err(raw_err_str!("Failed to create multipart upload: {}"))?;
+    let upload_id = res.upload_id().ok_or("Multipart upload missing ID")?.to_string();
+
+    journal.begin(PendingUpload { index, key, upload_id, staging_path, parts: Vec::new() })?;
+    continue_upload(bucket, s3_client, journal).await
+}
+
+/// Resume the journal's in-flight upload, if there is one
+///
+/// If the upload cannot be resumed, it is aborted and dropped from the journal
+/// so that the next snapshot is not blocked behind it
+pub async fn resume_pending_upload(
+    bucket: &str,
+    s3_client: &Client,
+    journal: &mut UploadJournal,
+) -> Result<(), String> {
+    let Some(pending) = journal.pending() else {
+        return Ok(());
+    };
+
+    log_task!(
+        Task::ResumeUpload,
+        Outcome::Started,
+        subject = %pending.key,
+        index = pending.index,
+        parts_done = pending.parts.len(),
+        "resuming interrupted snapshot upload"
+    );
+
+    if let Err(e) = continue_upload(bucket, s3_client, journal).await {
+        log_task!(
+            Task::ResumeUpload,
+            Outcome::Failed,
+            error = %e,
+            "failed to resume snapshot upload, abandoning"
+        );
+        abandon_upload(bucket, s3_client, journal).await?;
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Upload the remaining parts of the journal's in-flight upload and complete
+/// it
+async fn continue_upload(
+    bucket: &str,
+    s3_client: &Client,
+    journal: &mut UploadJournal,
+) -> Result<(), String> {
+    let pending = journal.pending().cloned().ok_or("no upload in flight")?;
+    let PendingUpload { key, upload_id, staging_path, parts, .. } = pending;
+
+    let size = tokio::fs::metadata(&staging_path)
+        .await
+        .map_err(raw_err_str!("Failed to read staged snapshot: {}"))?
+        .len();
+    let num_parts = size.div_ceil(UPLOAD_PART_SIZE).max(1);
+
+    // Upload each part not already recorded in the journal
+    let mut completed = parts;
+    for part_idx in completed.len() as u64..num_parts {
+        let offset = part_idx * UPLOAD_PART_SIZE;
+        let length = UPLOAD_PART_SIZE.min(size - offset);
+        let body = ByteStream::read_from()
+            .path(&staging_path)
+            .offset(offset)
+            .length(Length::Exact(length))
+            .build()
+            .await
+            .map_err(raw_err_str!("{}"))?;
+
+        let part_number = part_idx as i32 + 1;
+        let res = s3_client
+            .upload_part()
+            .bucket(bucket)
+            .key(&key)
+            .upload_id(&upload_id)
+            .part_number(part_number)
+            .body(body)
+            .send()
+            .await
+            .map_err(raw_err_str!("Failed to upload part: {}"))?;
+
+        let etag = res.e_tag().ok_or("Uploaded part missing ETag")?.to_string();
+        journal.record_part(part_number, etag.clone())?;
+        completed.push((part_number, etag));
+    }
+
+    // Complete the upload
+    let parts = completed
+        .into_iter()
+        .map(|(part_number, etag)| {
+            CompletedPart::builder().part_number(part_number).e_tag(etag).build()
+        })
+        .collect();
+    let upload = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+    s3_client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(&key)
+        .upload_id(&upload_id)
+        .multipart_upload(upload)
+        .send()
+        .await
+        .map_err(raw_err_str!("Failed to complete multipart upload: {}"))?;
+
+    journal.complete()?;
+    log_task!(Task::UploadSnapshot, Outcome::Ok, subject = %key, "snapshot uploaded");
+    Ok(())
+}
+
+/// Abort the journal's in-flight upload and drop it from the journal
+///
+/// Aborting on S3 is best-effort; a leftover upload is reclaimed by the
+/// bucket's lifecycle policy
+async fn abandon_upload(
+    bucket: &str,
+    s3_client: &Client,
+    journal: &mut UploadJournal,
+) -> Result<(), String> {
+    let Some(pending) = journal.abandon()? else {
+        return Ok(());
+    };
+
+    let res = s3_client
+        .abort_multipart_upload()
+        .bucket(bucket)
+        .key(&pending.key)
+        .upload_id(&pending.upload_id)
+        .send()
+        .await;
+    if let Err(e) = res {
+        log_task!(
+            Task::ResumeUpload,
+            Outcome::Failed,
+            subject = %pending.key,
+            error = %e,
+            "failed to abort multipart upload"
+        );
+    }
+
+    Ok(())
+}
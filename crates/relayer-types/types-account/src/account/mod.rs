@@ -12,6 +12,8 @@ pub mod mocks;
 pub mod order;
 pub mod order_auth;
 pub mod pair;
+pub mod risk;
+pub mod sweep;
 
 use std::collections::HashMap;
 
@@ -44,6 +46,9 @@ pub use order_auth::OrderAuth;
 pub type OrderId = Uuid;
 /// The name of a matching pool
 pub type MatchingPoolName = String;
+/// The identifier of a tenant, an API client whose accounts are isolated from
+/// those of other tenants
+pub type TenantId = String;
 
 /// The Merkle opening from the wallet shares' commitment to the global root
 pub type WalletAuthenticationPath = MerkleAuthenticationPath;
@@ -19,6 +19,15 @@ use crate::{
     MatchingPoolName, OrderId, balance::BalanceLocation, order_auth::OrderAuth, pair::Pair,
 };
 
+/// The maximum length of a client order id, in bytes
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 64;
+/// The maximum number of tags on an order
+pub const MAX_ORDER_TAGS: usize = 16;
+/// The maximum length of an order tag, in bytes
+pub const MAX_ORDER_TAG_LEN: usize = 64;
+/// The separator between tags in a tag filter, which tags may not contain
+pub const ORDER_TAG_SEPARATOR: char = ',';
+
 /// The order type for an account
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "rkyv", derive(Archive, RkyvDeserialize, RkyvSerialize))]
@@ -45,6 +54,12 @@ pub struct OrderMetadata {
     pub allow_external_matches: bool,
     /// Whether the order has received at least one fill
     pub has_been_filled: bool,
+    /// The identifier the client assigned to the order, if any
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    /// Free-form tags the client attached to the order
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Order {
@@ -172,7 +187,47 @@ impl OrderMetadata {
     /// Create a new order metadata from the given min fill size and allow
     /// external matches
     pub fn new(min_fill_size: Amount, allow_external_matches: bool) -> Self {
-        Self { min_fill_size, allow_external_matches, has_been_filled: false }
+        Self {
+            min_fill_size,
+            allow_external_matches,
+            has_been_filled: false,
+            client_order_id: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Attach a client order id and tags to the metadata
+    pub fn with_client_fields(
+        mut self,
+        client_order_id: Option<String>,
+        tags: Vec<String>,
+    ) -> Self {
+        self.client_order_id = client_order_id;
+        self.tags = tags;
+        self
+    }
+
+    /// Validate the client order id and tags against their limits
+    pub fn validate_client_fields(&self) -> Result<(), String> {
+        if let Some(id) = &self.client_order_id {
+            if id.is_empty() || id.len() > MAX_CLIENT_ORDER_ID_LEN {
+                return Err(format!(
+                    "client order id must be between 1 and {MAX_CLIENT_ORDER_ID_LEN} bytes"
+                ));
+            }
+        }
+
+        if self.tags.len() > MAX_ORDER_TAGS {
+            return Err(format!("an order may have at most {MAX_ORDER_TAGS} tags"));
+        }
+        if self.tags.iter().any(|tag| tag.is_empty() || tag.len() > MAX_ORDER_TAG_LEN) {
+            return Err(format!("order tags must be between 1 and {MAX_ORDER_TAG_LEN} bytes"));
+        }
+        if self.tags.iter().any(|tag| tag.contains(ORDER_TAG_SEPARATOR)) {
+            return Err(format!("order tags may not contain '{ORDER_TAG_SEPARATOR}'"));
+        }
+
+        Ok(())
     }
 
     /// Mark the order as having received its first fill
@@ -195,7 +250,31 @@ impl From<StateWrapper<Intent>> for Order {
 
 impl Default for OrderMetadata {
     fn default() -> Self {
-        Self { min_fill_size: 0, allow_external_matches: true, has_been_filled: false }
+        Self::new(0 /* min_fill_size */, true /* allow_external_matches */)
+    }
+}
+
+/// A filter on orders by the identifiers their client attached to them
+///
+/// An order matches if it has the given client order id, if one is set, and
+/// carries every one of the given tags
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrderFilter {
+    /// The client order id to match
+    pub client_order_id: Option<String>,
+    /// The tags an order must carry
+    pub tags: Vec<String>,
+}
+
+impl OrderFilter {
+    /// Whether the given order matches the filter
+    pub fn matches(&self, order: &Order) -> bool {
+        let metadata = order.metadata();
+        let id_matches = self
+            .client_order_id
+            .as_ref()
+            .is_none_or(|id| metadata.client_order_id.as_ref() == Some(id));
+        id_matches && self.tags.iter().all(|tag| metadata.tags.contains(tag))
     }
 }
 
@@ -346,3 +425,56 @@ pub mod mocks {
         Order::new(state_wrapper, OrderMetadata::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests the limits on client order ids and tags
+    #[test]
+    fn test_validate_client_fields() {
+        let valid = OrderMetadata::default()
+            .with_client_fields(Some("oms-1".to_string()), vec!["desk-a".to_string()]);
+        assert!(valid.validate_client_fields().is_ok());
+
+        let empty_id = OrderMetadata::default().with_client_fields(Some(String::new()), vec![]);
+        assert!(empty_id.validate_client_fields().is_err());
+
+        let long_id = "a".repeat(MAX_CLIENT_ORDER_ID_LEN + 1);
+        let long_id = OrderMetadata::default().with_client_fields(Some(long_id), vec![]);
+        assert!(long_id.validate_client_fields().is_err());
+
+        let tags = vec!["tag".to_string(); MAX_ORDER_TAGS + 1];
+        let too_many_tags = OrderMetadata::default().with_client_fields(None, tags);
+        assert!(too_many_tags.validate_client_fields().is_err());
+
+        let comma_tag = vec!["desk-a,algo".to_string()];
+        let comma_tag = OrderMetadata::default().with_client_fields(None, comma_tag);
+        assert!(comma_tag.validate_client_fields().is_err());
+    }
+
+    /// Tests matching orders against a filter
+    #[test]
+    #[cfg(feature = "mocks")]
+    fn test_order_filter() {
+        let mut order = mocks::mock_order();
+        order.metadata = OrderMetadata::default().with_client_fields(
+            Some("oms-1".to_string()),
+            vec!["desk-a".to_string(), "algo".to_string()],
+        );
+
+        assert!(OrderFilter::default().matches(&order));
+        let by_id = OrderFilter { client_order_id: Some("oms-1".to_string()), tags: vec![] };
+        assert!(by_id.matches(&order));
+        let other_id = OrderFilter { client_order_id: Some("oms-2".to_string()), tags: vec![] };
+        assert!(!other_id.matches(&order));
+
+        let by_tags = OrderFilter { client_order_id: None, tags: vec!["algo".to_string()] };
+        assert!(by_tags.matches(&order));
+        let missing_tag = OrderFilter {
+            client_order_id: None,
+            tags: vec!["algo".to_string(), "desk-b".to_string()],
+        };
+        assert!(!missing_tag.matches(&order));
+    }
+}
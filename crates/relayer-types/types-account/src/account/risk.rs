@@ -0,0 +1,100 @@
+//! Per-account risk limits, bounding the relayer's autonomy over an account
+//!
+//! All limits are optional; an account without a risk config is unrestricted.
+//! Notional values are denominated in the quote token (USDC) in its smallest
+//! unit, as all pairs are USDC quoted.
+
+use alloy::primitives::Address;
+use circuit_types::Amount;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rkyv")]
+use darkpool_types::rkyv_remotes::AddressDef;
+#[cfg(feature = "rkyv")]
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize, with::Map};
+
+use crate::pair::Pair;
+
+/// The number of milliseconds in a day, used to bucket matched volume
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Get the index of the (UTC) day containing the given timestamp
+///
+/// Daily volume limits reset when this value changes
+pub fn volume_day(timestamp_ms: u64) -> u64 {
+    timestamp_ms / MS_PER_DAY
+}
+
+/// The risk limits configured for an account
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvDeserialize, RkyvSerialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct AccountRiskConfig {
+    /// The maximum notional value of a single order
+    pub max_order_notional: Option<Amount>,
+    /// The maximum notional volume the account may match in a single day
+    pub max_daily_volume: Option<Amount>,
+    /// The base tokens the account may trade, if restricted
+    ///
+    /// All pairs are USDC quoted, so a base token identifies a pair
+    #[cfg_attr(feature = "rkyv", rkyv(with = Map<Map<AddressDef>>))]
+    pub allowed_base_tokens: Option<Vec<Address>>,
+}
+
+impl AccountRiskConfig {
+    /// Whether the account may trade the given pair
+    pub fn allows_pair(&self, pair: &Pair) -> bool {
+        let base = pair.base_token().get_alloy_address();
+        match &self.allowed_base_tokens {
+            Some(allowed) => allowed.contains(&base),
+            None => true,
+        }
+    }
+
+    /// Whether the account may place an order of the given notional value
+    pub fn allows_order_notional(&self, notional: Amount) -> bool {
+        self.max_order_notional.is_none_or(|max| notional <= max)
+    }
+
+    /// Whether the account may match the given volume, having already matched
+    /// `matched_today` in the current day
+    pub fn allows_daily_volume(&self, matched_today: Amount, volume: Amount) -> bool {
+        self.max_daily_volume.is_none_or(|max| matched_today.saturating_add(volume) <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that an empty config does not bound order or volume size
+    #[test]
+    fn test_unrestricted() {
+        let config = AccountRiskConfig::default();
+        assert!(config.allows_order_notional(Amount::MAX));
+        assert!(config.allows_daily_volume(Amount::MAX, Amount::MAX));
+    }
+
+    /// Tests the notional and daily volume limits
+    #[test]
+    fn test_amount_limits() {
+        let config = AccountRiskConfig {
+            max_order_notional: Some(100),
+            max_daily_volume: Some(1_000),
+            allowed_base_tokens: None,
+        };
+
+        assert!(config.allows_order_notional(100));
+        assert!(!config.allows_order_notional(101));
+
+        assert!(config.allows_daily_volume(900, 100));
+        assert!(!config.allows_daily_volume(901, 100));
+    }
+
+    /// Tests that volume days roll over at UTC midnight
+    #[test]
+    fn test_volume_day() {
+        assert_eq!(volume_day(0), volume_day(MS_PER_DAY - 1));
+        assert_eq!(volume_day(MS_PER_DAY), 1);
+    }
+}
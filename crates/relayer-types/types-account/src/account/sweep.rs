@@ -0,0 +1,152 @@
+//! Per-account balance sweep policies, bounding an account's hot exposure
+//!
+//! A withdrawal must be authorized by the balance owner and is paid to the
+//! owner, so the relayer cannot sweep a balance itself. Instead, when a balance
+//! exceeds its trigger amount the relayer requests a sweep down to the target
+//! amount, which the owner's tooling signs and forwards to the cold address.
+//!
+//! The gap between the trigger and the target is a hysteresis band: once a
+//! sweep is requested for a balance, no further sweep is requested until the
+//! balance has fallen to the target amount
+
+use alloy::primitives::Address;
+use circuit_types::Amount;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rkyv")]
+use darkpool_types::rkyv_remotes::AddressDef;
+#[cfg(feature = "rkyv")]
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+
+/// The sweep policy configured for an account
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvDeserialize, RkyvSerialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct AccountSweepPolicy {
+    /// The address to which swept balances are sent
+    #[cfg_attr(feature = "rkyv", rkyv(with = AddressDef))]
+    pub cold_address: Address,
+    /// The sweep thresholds of the account's balances, at most one per mint
+    pub thresholds: Vec<SweepThreshold>,
+}
+
+/// The amounts at which a balance is swept
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvDeserialize, RkyvSerialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct SweepThreshold {
+    /// The mint of the balance
+    #[cfg_attr(feature = "rkyv", rkyv(with = AddressDef))]
+    pub mint: Address,
+    /// The amount above which a sweep is requested
+    pub trigger_amount: Amount,
+    /// The amount left in the balance after a sweep
+    pub target_amount: Amount,
+}
+
+/// The action to take on a balance update under a sweep policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SweepAction {
+    /// Request a sweep of the given amount
+    Request(Amount),
+    /// Clear the balance's outstanding sweep request
+    Rearm,
+    /// Leave the balance's sweep state unchanged
+    None,
+}
+
+impl AccountSweepPolicy {
+    /// Validate the policy, returning an error message if it is malformed
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, threshold) in self.thresholds.iter().enumerate() {
+            if threshold.target_amount >= threshold.trigger_amount {
+                return Err(format!(
+                    "target amount must be less than trigger amount for mint {}",
+                    threshold.mint
+                ));
+            }
+            if self.thresholds[..i].iter().any(|t| t.mint == threshold.mint) {
+                return Err(format!("duplicate sweep threshold for mint {}", threshold.mint));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the sweep threshold for the given mint, if one is configured
+    pub fn threshold_for(&self, mint: &Address) -> Option<&SweepThreshold> {
+        self.thresholds.iter().find(|t| t.mint == *mint)
+    }
+
+    /// Decide the action to take when the given balance is updated, given
+    /// whether a sweep has already been requested for it
+    pub fn evaluate(&self, mint: &Address, amount: Amount, pending: bool) -> SweepAction {
+        let Some(threshold) = self.threshold_for(mint) else {
+            // The balance's threshold was removed since the sweep was requested
+            return if pending { SweepAction::Rearm } else { SweepAction::None };
+        };
+
+        if pending {
+            if amount <= threshold.target_amount {
+                return SweepAction::Rearm;
+            }
+            return SweepAction::None;
+        }
+
+        if amount > threshold.trigger_amount {
+            return SweepAction::Request(amount - threshold.target_amount);
+        }
+        SweepAction::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a policy sweeping the given mint above 100, down to 40
+    fn policy(mint: Address) -> AccountSweepPolicy {
+        let threshold = SweepThreshold { mint, trigger_amount: 100, target_amount: 40 };
+        AccountSweepPolicy { cold_address: Address::ZERO, thresholds: vec![threshold] }
+    }
+
+    /// Tests that a sweep is requested once per excursion above the trigger
+    #[test]
+    fn test_hysteresis() {
+        let mint = Address::repeat_byte(1);
+        let policy = policy(mint);
+
+        assert_eq!(policy.evaluate(&mint, 100, false /* pending */), SweepAction::None);
+        assert_eq!(policy.evaluate(&mint, 150, false /* pending */), SweepAction::Request(110));
+
+        // No further request until the balance falls to the target
+        assert_eq!(policy.evaluate(&mint, 200, true /* pending */), SweepAction::None);
+        assert_eq!(policy.evaluate(&mint, 41, true /* pending */), SweepAction::None);
+        assert_eq!(policy.evaluate(&mint, 40, true /* pending */), SweepAction::Rearm);
+    }
+
+    /// Tests that balances without a threshold are not swept
+    #[test]
+    fn test_unconfigured_mint() {
+        let policy = policy(Address::repeat_byte(1));
+        let other = Address::repeat_byte(2);
+
+        assert_eq!(policy.evaluate(&other, Amount::MAX, false /* pending */), SweepAction::None);
+        assert_eq!(policy.evaluate(&other, Amount::MAX, true /* pending */), SweepAction::Rearm);
+    }
+
+    /// Tests policy validation
+    #[test]
+    fn test_validate() {
+        let mint = Address::repeat_byte(1);
+        let mut policy = policy(mint);
+        assert!(policy.validate().is_ok());
+
+        policy.thresholds.push(policy.thresholds[0].clone());
+        assert!(policy.validate().is_err());
+
+        policy.thresholds.truncate(1);
+        policy.thresholds[0].target_amount = policy.thresholds[0].trigger_amount;
+        assert!(policy.validate().is_err());
+    }
+}
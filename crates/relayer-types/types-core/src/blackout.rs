@@ -0,0 +1,152 @@
+//! Defines blackout windows, during which the relayer does not match orders
+//!
+//! Windows are placed around events that make prices unreliable, e.g. known
+//! oracle updates, or around maintenance. A window may recur at a fixed
+//! period, so that a window around a daily event need only be set once
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A window of time during which no new matches are made
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct BlackoutWindow {
+    /// The ID of the window
+    pub id: Uuid,
+    /// The start of the window, in milliseconds since the epoch
+    pub start: u64,
+    /// The end of the window (exclusive), in milliseconds since the epoch
+    pub end: u64,
+    /// The period at which the window recurs, in milliseconds, if it recurs
+    pub period: Option<u64>,
+    /// The reason for the blackout
+    pub reason: String,
+}
+
+impl BlackoutWindow {
+    /// Create a new blackout window, validating its bounds
+    pub fn new(start: u64, end: u64, period: Option<u64>, reason: String) -> Result<Self, String> {
+        if end <= start {
+            return Err(format!("blackout window ends ({end}) before it starts ({start})"));
+        }
+        if let Some(period) = period
+            && period <= end - start
+        {
+            return Err(format!("blackout window recurs ({period}ms) before it ends"));
+        }
+
+        Ok(Self { id: Uuid::new_v4(), start, end, period, reason })
+    }
+
+    /// Whether the window covers the given time, in milliseconds since the
+    /// epoch
+    pub fn contains(&self, timestamp: u64) -> bool {
+        if timestamp < self.start {
+            return false;
+        }
+
+        let offset = match self.period {
+            Some(period) => (timestamp - self.start) % period,
+            None => timestamp - self.start,
+        };
+        offset < self.end - self.start
+    }
+
+    /// Whether the window has passed and will not recur after the given time
+    pub fn is_expired(&self, timestamp: u64) -> bool {
+        self.period.is_none() && timestamp >= self.end
+    }
+}
+
+impl Display for BlackoutWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)?;
+        if let Some(period) = self.period {
+            write!(f, "/{period}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a window of the form `<start>-<end>` or `<start>-<end>/<period>`,
+/// with all values in milliseconds
+impl FromStr for BlackoutWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_ms = |v: &str| {
+            v.trim().parse::<u64>().map_err(|e| format!("invalid blackout window {s}: {e}"))
+        };
+
+        let (bounds, period) = match s.split_once('/') {
+            Some((bounds, period)) => (bounds, Some(parse_ms(period)?)),
+            None => (s, None),
+        };
+        let (start, end) =
+            bounds.split_once('-').ok_or_else(|| format!("invalid blackout window: {s}"))?;
+
+        BlackoutWindow::new(parse_ms(start)?, parse_ms(end)?, period, String::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests the times covered by a one-off window
+    #[test]
+    fn test_contains_one_off() {
+        let window = BlackoutWindow::new(100, 200, None, String::new()).unwrap();
+        assert!(!window.contains(99));
+        assert!(window.contains(100));
+        assert!(window.contains(199));
+        assert!(!window.contains(200));
+        assert!(!window.contains(1_100));
+
+        assert!(!window.is_expired(199));
+        assert!(window.is_expired(200));
+    }
+
+    /// Tests the times covered by a recurring window
+    #[test]
+    fn test_contains_recurring() {
+        let window = BlackoutWindow::new(100, 200, Some(1_000), String::new()).unwrap();
+        assert!(!window.contains(99));
+        assert!(window.contains(150));
+        assert!(!window.contains(200));
+        assert!(!window.contains(1_099));
+        assert!(window.contains(1_100));
+        assert!(window.contains(5_199));
+        assert!(!window.contains(5_200));
+
+        // A recurring window never expires
+        assert!(!window.is_expired(u64::MAX));
+    }
+
+    /// Tests parsing windows from their string form
+    #[test]
+    fn test_from_str() {
+        let window = BlackoutWindow::from_str("100-200").unwrap();
+        assert_eq!((window.start, window.end, window.period), (100, 200, None));
+
+        let window = BlackoutWindow::from_str(" 100 - 200 / 1000 ").unwrap();
+        assert_eq!((window.start, window.end, window.period), (100, 200, Some(1_000)));
+        assert_eq!(window.to_string(), "100-200/1000");
+
+        // Malformed windows
+        assert!(BlackoutWindow::from_str("100").is_err());
+        assert!(BlackoutWindow::from_str("a-200").is_err());
+        assert!(BlackoutWindow::from_str("100-200/").is_err());
+
+        // Windows with invalid bounds
+        assert!(BlackoutWindow::from_str("200-100").is_err());
+        assert!(BlackoutWindow::from_str("100-100").is_err());
+        assert!(BlackoutWindow::from_str("100-200/100").is_err());
+    }
+}
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 
 /// The chain environment
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
 #[serde(rename_all = "kebab-case")]
 pub enum Chain {
     /// The Arbitrum Sepolia chain
@@ -68,4 +70,60 @@ impl Chain {
             Chain::Devnet => 31337,
         }
     }
+
+    /// Get the default depth at which a transaction on this chain is
+    /// considered confirmed
+    ///
+    /// Rollups settle to L1 themselves, so a transaction is confirmed once
+    /// included. On Ethereum, a transaction waits for its block to be safe or
+    /// finalized so that it cannot be reorged out
+    pub fn default_confirmation_depth(&self) -> ConfirmationDepth {
+        match self {
+            Chain::ArbitrumSepolia
+            | Chain::ArbitrumOne
+            | Chain::BaseSepolia
+            | Chain::BaseMainnet
+            | Chain::Devnet => ConfirmationDepth::Blocks(1),
+            Chain::EthereumSepolia => ConfirmationDepth::Safe,
+            Chain::EthereumMainnet => ConfirmationDepth::Finalized,
+        }
+    }
+}
+
+/// The depth at which a submitted transaction is considered confirmed
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfirmationDepth {
+    /// The transaction's block and the given number of blocks in total have
+    /// been built, i.e. one block confirms a transaction once it is included
+    Blocks(u64),
+    /// The transaction's block is at or below the chain's `safe` block
+    Safe,
+    /// The transaction's block is at or below the chain's `finalized` block
+    Finalized,
+}
+
+impl Display for ConfirmationDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfirmationDepth::Blocks(blocks) => write!(f, "{blocks}"),
+            ConfirmationDepth::Safe => write!(f, "safe"),
+            ConfirmationDepth::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+impl FromStr for ConfirmationDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "safe" => Ok(ConfirmationDepth::Safe),
+            "finalized" => Ok(ConfirmationDepth::Finalized),
+            blocks => match blocks.parse::<u64>() {
+                Ok(0) | Err(_) => Err(format!("Invalid confirmation depth: {s}")),
+                Ok(blocks) => Ok(ConfirmationDepth::Blocks(blocks)),
+            },
+        }
+    }
 }
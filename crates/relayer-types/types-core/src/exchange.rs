@@ -13,11 +13,16 @@ use crate::token::Token;
 /// The identifier of an exchange
 #[allow(clippy::missing_docs_in_private_items, missing_docs)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
 pub enum Exchange {
     Binance,
+    Bybit,
     Coinbase,
     Kraken,
+    Kucoin,
     Okx,
+    Chainlink,
     UniswapV3,
     Renegade,
 }
@@ -27,9 +32,12 @@ impl Exchange {
     pub fn all() -> Vec<Exchange> {
         vec![
             Exchange::Binance,
+            Exchange::Bybit,
             Exchange::Coinbase,
             Exchange::Kraken,
+            Exchange::Kucoin,
             Exchange::Okx,
+            Exchange::Chainlink,
             Exchange::UniswapV3,
             Exchange::Renegade,
         ]
@@ -40,9 +48,12 @@ impl Display for Exchange {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let fmt_str = match self {
             Exchange::Binance => String::from("binance"),
+            Exchange::Bybit => String::from("bybit"),
             Exchange::Coinbase => String::from("coinbase"),
             Exchange::Kraken => String::from("kraken"),
+            Exchange::Kucoin => String::from("kucoin"),
             Exchange::Okx => String::from("okx"),
+            Exchange::Chainlink => String::from("chainlink"),
             Exchange::UniswapV3 => String::from("uniswapv3"),
             Exchange::Renegade => String::from("renegade"),
         };
@@ -56,9 +67,12 @@ impl FromStr for Exchange {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "binance" => Ok(Exchange::Binance),
+            "bybit" => Ok(Exchange::Bybit),
             "coinbase" => Ok(Exchange::Coinbase),
             "kraken" => Ok(Exchange::Kraken),
+            "kucoin" => Ok(Exchange::Kucoin),
             "okx" => Ok(Exchange::Okx),
+            "chainlink" => Ok(Exchange::Chainlink),
             "uniswapv3" | "uniswap" => Ok(Exchange::UniswapV3),
             "renegade" => Ok(Exchange::Renegade),
             _ => Err(format!("Unknown exchange: {s}")),
@@ -66,6 +80,103 @@ impl FromStr for Exchange {
     }
 }
 
+/// The strategy by which the prices reported for a pair across exchanges are
+/// aggregated into a single reference price
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PriceAggregationStrategy {
+    /// The median of the exchange prices
+    #[default]
+    Median,
+    /// The median of the exchange prices, weighted by each exchange's traded
+    /// volume for the pair
+    WeightedMedian,
+    /// The mean of the exchange prices after discarding the outliers on
+    /// either side
+    TrimmedMean,
+    /// The midpoint of the best bid and best ask across exchanges
+    Midpoint,
+}
+
+impl Display for PriceAggregationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceAggregationStrategy::Median => write!(f, "median"),
+            PriceAggregationStrategy::WeightedMedian => write!(f, "weighted-median"),
+            PriceAggregationStrategy::TrimmedMean => write!(f, "trimmed-mean"),
+            PriceAggregationStrategy::Midpoint => write!(f, "midpoint"),
+        }
+    }
+}
+
+impl FromStr for PriceAggregationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median" => Ok(PriceAggregationStrategy::Median),
+            "weighted-median" => Ok(PriceAggregationStrategy::WeightedMedian),
+            "trimmed-mean" => Ok(PriceAggregationStrategy::TrimmedMean),
+            "midpoint" => Ok(PriceAggregationStrategy::Midpoint),
+            _ => Err(format!("Unknown price aggregation strategy: {s}")),
+        }
+    }
+}
+
+/// The trailing window over which a rolling volume weighted average price is
+/// computed
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum VwapWindow {
+    /// The trailing minute
+    #[serde(rename = "1m")]
+    OneMinute,
+    /// The trailing five minutes
+    #[default]
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    /// The trailing thirty minutes
+    #[serde(rename = "30m")]
+    ThirtyMinutes,
+}
+
+impl VwapWindow {
+    /// All windows, in increasing order of length
+    pub const ALL: [VwapWindow; 3] =
+        [VwapWindow::OneMinute, VwapWindow::FiveMinutes, VwapWindow::ThirtyMinutes];
+
+    /// The length of the window in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        match self {
+            VwapWindow::OneMinute => 60_000,
+            VwapWindow::FiveMinutes => 5 * 60_000,
+            VwapWindow::ThirtyMinutes => 30 * 60_000,
+        }
+    }
+}
+
+impl Display for VwapWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VwapWindow::OneMinute => write!(f, "1m"),
+            VwapWindow::FiveMinutes => write!(f, "5m"),
+            VwapWindow::ThirtyMinutes => write!(f, "30m"),
+        }
+    }
+}
+
+impl FromStr for VwapWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(VwapWindow::OneMinute),
+            "5m" => Ok(VwapWindow::FiveMinutes),
+            "30m" => Ok(VwapWindow::ThirtyMinutes),
+            _ => Err(format!("Unknown VWAP window: {s}")),
+        }
+    }
+}
+
 /// The PriceReport is the universal format for price feeds from all external
 /// exchanges.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -80,6 +191,12 @@ pub struct PriceReport {
     /// The time that this update was received by the relayer node,
     /// expected to be in milliseconds since the UNIX epoch
     pub local_timestamp: u64,
+    /// The confidence in the price, between 0 and 1
+    ///
+    /// Scored from the number of exchanges reporting, the spread between
+    /// them, and the age of the price
+    #[serde(default)]
+    pub confidence: f64,
 }
 
 /// The state of the PriceReporter. The Nominal state means that enough
@@ -113,6 +230,16 @@ impl PriceReporterState {
         }
     }
 
+    /// Get the confidence in the reported price, if a report exists
+    pub fn confidence(&self) -> Option<f64> {
+        match self {
+            PriceReporterState::Nominal(report)
+            | PriceReporterState::DataTooStale(report, _)
+            | PriceReporterState::TooMuchDeviation(report, _) => Some(report.confidence),
+            _ => None,
+        }
+    }
+
     /// Get the nominal price report, if it exists
     pub fn into_nominal(self) -> Option<PriceReport> {
         match self {
@@ -131,6 +258,12 @@ impl PriceReporterState {
 pub enum ExchangeConnectionState {
     /// The ExchangeConnection is reporting as normal.
     Nominal(PriceReport),
+    /// The ExchangeConnection's stream is down, and its prices are polled from
+    /// the exchange's REST ticker instead.
+    Degraded(PriceReport),
+    /// The ExchangeConnection has not reported a price within the staleness
+    /// threshold; includes the last report.
+    Stale(PriceReport),
     /// No data has yet to be reported from the ExchangeConnection.
     NoDataReported,
     /// This Exchange is unsupported for the given Token pair
@@ -143,6 +276,12 @@ impl Display for ExchangeConnectionState {
             ExchangeConnectionState::Nominal(price_report) => {
                 format!("{:.4}", price_report.price)
             },
+            ExchangeConnectionState::Degraded(price_report) => {
+                format!("Degraded({:.4})", price_report.price)
+            },
+            ExchangeConnectionState::Stale(price_report) => {
+                format!("Stale({:.4})", price_report.price)
+            },
             ExchangeConnectionState::NoDataReported => String::from("NoDataReported"),
             ExchangeConnectionState::Unsupported => String::from("Unsupported"),
         };
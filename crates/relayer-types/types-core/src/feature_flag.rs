@@ -0,0 +1,54 @@
+//! Defines feature flags toggled at runtime across the cluster
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A feature flag that may be toggled at runtime without a config redeploy
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum FeatureFlag {
+    /// Whether external matches may be quoted and assembled
+    ExternalMatches,
+    /// Whether new account creation is paused
+    PauseAccountCreation,
+}
+
+impl FeatureFlag {
+    /// Get all feature flags
+    pub fn all() -> Vec<FeatureFlag> {
+        vec![FeatureFlag::ExternalMatches, FeatureFlag::PauseAccountCreation]
+    }
+
+    /// The value of the flag when it has not been set
+    pub fn default_value(&self) -> bool {
+        match self {
+            FeatureFlag::ExternalMatches => true,
+            FeatureFlag::PauseAccountCreation => false,
+        }
+    }
+}
+
+impl Display for FeatureFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fmt_str = match self {
+            FeatureFlag::ExternalMatches => "enable-external-matches",
+            FeatureFlag::PauseAccountCreation => "pause-new-account-creation",
+        };
+        write!(f, "{fmt_str}")
+    }
+}
+
+impl FromStr for FeatureFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "enable-external-matches" => Ok(FeatureFlag::ExternalMatches),
+            "pause-new-account-creation" => Ok(FeatureFlag::PauseAccountCreation),
+            _ => Err(format!("Unknown feature flag: {s}")),
+        }
+    }
+}
@@ -9,21 +9,27 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![deny(clippy::missing_docs_in_private_items)]
 
+mod blackout;
 mod chain;
 mod exchange;
+mod feature_flag;
 #[cfg(feature = "hmac")]
 mod hmac;
 mod match_result;
 mod price;
 mod token;
+mod token_listing;
 
+pub use blackout::*;
 pub use chain::*;
 pub use exchange::*;
+pub use feature_flag::*;
 #[cfg(feature = "hmac")]
 pub use hmac::*;
 pub use match_result::*;
 pub use price::*;
 pub use token::*;
+pub use token_listing::*;
 
 /// A type alias for the account identifier type, currently a UUID
 pub type AccountId = uuid::Uuid;
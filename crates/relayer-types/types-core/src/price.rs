@@ -1,18 +1,268 @@
 //! Types for prices and price timestamps
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use circuit_types::fixed_point::FixedPoint;
+use alloy::primitives::U256;
+use circuit_types::fixed_point::{DEFAULT_FP_PRECISION, FixedPoint};
+use constants::Scalar;
 #[cfg(feature = "rkyv")]
 use darkpool_types::rkyv_remotes::FixedPointDef;
-use serde::{Deserialize, Serialize};
+use num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::exchange::PriceReport;
 use crate::token::Token;
 
-/// An alias for the price of an asset pair that abstracts away its
-/// representation
-pub type Price = f64;
+/// The price of an asset pair
+///
+/// A price is held in the fixed-point representation used by the circuits,
+/// i.e. as the price scaled by `2^DEFAULT_FP_PRECISION` and rounded down, so
+/// that the price the relayer reports is exactly the price it proves against.
+/// Floats are converted once, where a price is parsed from an exchange
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(u128);
+
+impl Price {
+    /// A price of zero
+    pub const ZERO: Price = Price(0);
+    /// A price of one
+    pub const ONE: Price = Price(1 << DEFAULT_FP_PRECISION);
+
+    /// Construct a price from its fixed-point representation
+    pub const fn from_repr(repr: u128) -> Self {
+        Self(repr)
+    }
+
+    /// Get the fixed-point representation of the price
+    pub const fn repr(&self) -> u128 {
+        self.0
+    }
+
+    /// Whether the price is zero
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    // --- Conversion --- //
+
+    /// Convert a float to a price, rounding down to the nearest representable
+    /// price
+    ///
+    /// Returns `None` for negative or non-finite floats, and for floats too
+    /// large to represent
+    pub fn from_f64_round_down(val: f64) -> Option<Self> {
+        if !val.is_finite() || val.is_sign_negative() {
+            return None;
+        }
+
+        // Decompose the float into `mantissa * 2^exp`
+        let bits = val.to_bits();
+        let biased_exp = ((bits >> 52) & 0x7ff) as i32;
+        let mut mantissa = (bits & ((1 << 52) - 1)) as u128;
+        let exp = if biased_exp == 0 {
+            -1074
+        } else {
+            mantissa |= 1 << 52;
+            biased_exp - 1075
+        };
+
+        // The representation is `mantissa * 2^(exp + DEFAULT_FP_PRECISION)`
+        let shift = exp + DEFAULT_FP_PRECISION as i32;
+        if shift < 0 {
+            return Some(Self(mantissa.checked_shr(shift.unsigned_abs()).unwrap_or(0)));
+        }
+        if mantissa != 0 && shift as u32 > mantissa.leading_zeros() {
+            return None;
+        }
+        Some(Self(mantissa << shift))
+    }
+
+    /// Convert a ratio of integers to a price, rounding down
+    ///
+    /// Returns `None` if the denominator is zero or the ratio is too large to
+    /// represent
+    pub fn from_integer_ratio(numerator: u128, denominator: u128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+
+        let repr = (U256::from(numerator) << DEFAULT_FP_PRECISION) / U256::from(denominator);
+        u128::try_from(repr).ok().map(Self)
+    }
+
+    /// Convert the price to a float
+    ///
+    /// Floats are lossy, so the result should only be used for display and
+    /// for statistics over prices, never to derive another price
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / (1u128 << DEFAULT_FP_PRECISION) as f64
+    }
+
+    /// Convert the price to the fixed-point type used by the circuits
+    ///
+    /// The conversion is exact
+    pub fn to_fixed_point(&self) -> FixedPoint {
+        FixedPoint::from_repr(Scalar::from(self.0))
+    }
+
+    /// Convert a fixed point from the circuits to a price
+    ///
+    /// Returns `None` for negative fixed points and for those too large to
+    /// represent
+    pub fn from_fixed_point(fp: &FixedPoint) -> Option<Self> {
+        if fp.is_negative() {
+            return None;
+        }
+
+        u128::try_from(fp.repr.to_biguint()).ok().map(Self)
+    }
+
+    // --- Arithmetic --- //
+
+    /// Multiply two prices, rounding down
+    ///
+    /// Returns `None` if the product is too large to represent
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let product = (U256::from(self.0) * U256::from(rhs.0)) >> DEFAULT_FP_PRECISION;
+        u128::try_from(product).ok().map(Self)
+    }
+
+    /// Divide one price by another, rounding down
+    ///
+    /// Returns `None` if the divisor is zero or the quotient is too large to
+    /// represent
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+
+        let quotient = (U256::from(self.0) << DEFAULT_FP_PRECISION) / U256::from(rhs.0);
+        u128::try_from(quotient).ok().map(Self)
+    }
+
+    /// Invert the price, rounding down as `FixedPoint::inverse` does
+    ///
+    /// Returns `None` if the price is zero or its inverse too large to
+    /// represent
+    pub fn inverse(self) -> Option<Self> {
+        Self::ONE.checked_div(self)
+    }
+
+    /// Scale the price by `10^exp`, rounding down
+    ///
+    /// Returns `None` if the scaled price is too large to represent
+    pub fn scale_pow10(self, exp: i32) -> Option<Self> {
+        let factor = 10u128.checked_pow(exp.unsigned_abs());
+        if exp >= 0 {
+            factor.and_then(|f| self.0.checked_mul(f)).map(Self)
+        } else {
+            Some(Self(factor.map_or(0, |f| self.0 / f)))
+        }
+    }
+
+    /// The midpoint of two prices, rounding down
+    pub fn midpoint(self, other: Self) -> Self {
+        Self(self.0 / 2 + other.0 / 2 + (self.0 & other.0 & 1))
+    }
+
+    /// The deviation of the price from a reference price, as a fraction of
+    /// the reference price
+    pub fn relative_deviation(self, reference: Self) -> f64 {
+        self.0.abs_diff(reference.0) as f64 / reference.0 as f64
+    }
+}
+
+impl Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.to_f64(), f)
+    }
+}
+
+impl FromStr for Price {
+    type Err = String;
+
+    /// Parse a decimal string to a price, exactly up to rounding down to the
+    /// nearest representable price
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid price: {s}");
+        let (int_digits, frac_digits) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+        let all_digits = |d: &str| d.bytes().all(|b| b.is_ascii_digit());
+        if int_digits.len() + frac_digits.len() == 0
+            || !all_digits(int_digits)
+            || !all_digits(frac_digits)
+        {
+            return Err(err());
+        }
+
+        // The price is the digits as an integer, scaled down by the number of
+        // fractional digits
+        let digits = format!("{int_digits}{frac_digits}");
+        let numerator = BigUint::parse_bytes(digits.as_bytes(), 10 /* radix */).ok_or_else(err)?;
+        let denominator = BigUint::from(10u8).pow(frac_digits.len() as u32);
+        let repr = (numerator << DEFAULT_FP_PRECISION) / denominator;
+        u128::try_from(repr).map(Self).map_err(|_| err())
+    }
+}
+
+/// Serialize and deserialize a price as its fixed-point representation, in
+/// the same format as `FixedPoint`
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = String::deserialize(deserializer)?;
+        repr.parse().map(Self).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A running sum of weighted prices, from which their weighted mean is taken
+#[derive(Copy, Clone, Debug, Default)]
+pub struct WeightedPriceSum {
+    /// The sum of the price representations, each scaled by its weight
+    weighted_repr: U256,
+    /// The sum of the weights
+    weight: u128,
+}
+
+impl WeightedPriceSum {
+    /// Add a price with the given weight to the sum
+    pub fn add(&mut self, price: Price, weight: u128) {
+        self.weighted_repr += U256::from(price.0) * U256::from(weight);
+        self.weight += weight;
+    }
+
+    /// Add the prices of another sum to this one
+    pub fn merge(&mut self, other: &Self) {
+        self.weighted_repr += other.weighted_repr;
+        self.weight += other.weight;
+    }
+
+    /// The sum of the weights
+    pub fn weight(&self) -> u128 {
+        self.weight
+    }
+
+    /// The weighted mean of the prices, rounding down
+    ///
+    /// Returns `None` if the sum has no weight
+    pub fn mean(&self) -> Option<Price> {
+        if self.weight == 0 {
+            return None;
+        }
+
+        // The mean of representable prices is itself representable
+        let mean = self.weighted_repr / U256::from(self.weight);
+        Some(Price(mean.to::<u128>()))
+    }
+}
 
 /// Returns the current unix timestamp in milliseconds
 fn get_current_time_millis() -> u64 {
@@ -50,14 +300,19 @@ impl TimestampedPrice {
 
         let TimestampedPrice { price: original_price, timestamp } = self;
         let decimal_diff = quote_decimals as i32 - base_decimals as i32;
-        let corrected_price = original_price * 10f64.powi(decimal_diff);
+        let corrected_price = original_price.scale_pow10(decimal_diff).ok_or(format!(
+            "Price overflow correcting decimals for {} / {}",
+            base_token.get_addr(),
+            quote_token.get_addr()
+        ))?;
 
         Ok(TimestampedPrice { price: corrected_price, timestamp })
     }
 
     /// Invert the price
-    pub fn invert(self) -> Self {
-        Self { price: 1.0 / self.price, timestamp: self.timestamp }
+    pub fn invert(self) -> Result<Self, String> {
+        let price = self.price.inverse().ok_or(format!("Cannot invert price {}", self.price))?;
+        Ok(Self { price, timestamp: self.timestamp })
     }
 }
 
@@ -67,6 +322,46 @@ impl From<&PriceReport> for TimestampedPrice {
     }
 }
 
+/// Serialize and deserialize a timestamped price with its price as a float
+///
+/// This is the format timestamped prices had before prices were held in fixed
+/// point. Exported events keep it, so that their consumers need not change
+pub mod timestamped_price_as_float {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    use super::{Price, TimestampedPrice};
+
+    /// A timestamped price with its price as a float
+    #[derive(Serialize, Deserialize)]
+    struct FloatTimestampedPrice {
+        /// The price
+        price: f64,
+        /// The time the price was sampled, in milliseconds since the epoch
+        timestamp: u64,
+    }
+
+    /// Serialize a timestamped price with its price as a float
+    pub fn serialize<S: Serializer>(
+        price: &TimestampedPrice,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        FloatTimestampedPrice { price: price.price.to_f64(), timestamp: price.timestamp }
+            .serialize(serializer)
+    }
+
+    /// Deserialize a timestamped price with its price as a float, rounding
+    /// the price down
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TimestampedPrice, D::Error> {
+        let FloatTimestampedPrice { price, timestamp } =
+            FloatTimestampedPrice::deserialize(deserializer)?;
+        let price = Price::from_f64_round_down(price)
+            .ok_or_else(|| D::Error::custom(format!("invalid price: {price}")))?;
+        Ok(TimestampedPrice { price, timestamp })
+    }
+}
+
 /// A price along with the time it was sampled represented as a fixed point
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
@@ -81,14 +376,17 @@ pub struct TimestampedPriceFp {
 
 impl From<TimestampedPrice> for TimestampedPriceFp {
     fn from(value: TimestampedPrice) -> Self {
-        let price = FixedPoint::from_f64_round_down(value.price);
-        Self { price, timestamp: value.timestamp }
+        Self { price: value.price.to_fixed_point(), timestamp: value.timestamp }
     }
 }
 
-impl From<TimestampedPriceFp> for TimestampedPrice {
-    fn from(value: TimestampedPriceFp) -> Self {
-        Self { price: value.price.to_f64(), timestamp: value.timestamp }
+impl TryFrom<TimestampedPriceFp> for TimestampedPrice {
+    type Error = String;
+
+    fn try_from(value: TimestampedPriceFp) -> Result<Self, Self::Error> {
+        let price = Price::from_fixed_point(&value.price)
+            .ok_or(format!("Fixed point {:?} is not a valid price", value.price))?;
+        Ok(Self { price, timestamp: value.timestamp })
     }
 }
 
@@ -98,3 +396,137 @@ impl From<FixedPoint> for TimestampedPriceFp {
         Self { price, timestamp }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests converting floats to prices
+    #[test]
+    fn test_from_f64_round_down() {
+        assert_eq!(Price::from_f64_round_down(0.), Some(Price::ZERO));
+        assert_eq!(Price::from_f64_round_down(1.), Some(Price::ONE));
+        assert_eq!(Price::from_f64_round_down(0.5), Some(Price::from_repr(1 << 62)));
+        assert_eq!(Price::from_f64_round_down(1234.5).unwrap().to_f64(), 1234.5);
+
+        // Floats below the precision round down to zero
+        assert_eq!(Price::from_f64_round_down(f64::MIN_POSITIVE), Some(Price::ZERO));
+
+        // Negative, non-finite and unrepresentable floats are rejected
+        assert_eq!(Price::from_f64_round_down(-1.), None);
+        assert_eq!(Price::from_f64_round_down(f64::NAN), None);
+        assert_eq!(Price::from_f64_round_down(f64::INFINITY), None);
+        assert_eq!(Price::from_f64_round_down(2f64.powi(65)), None);
+    }
+
+    /// Tests parsing decimal strings to prices
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1".parse::<Price>(), Ok(Price::ONE));
+        assert_eq!("1.".parse::<Price>(), Ok(Price::ONE));
+        assert_eq!(".5".parse::<Price>(), Ok(Price::from_repr(1 << 62)));
+        assert_eq!(" 0.25 ".parse::<Price>(), Ok(Price::from_repr(1 << 61)));
+
+        // Decimals that are not dyadic round down
+        let tenth = "0.1".parse::<Price>().unwrap();
+        assert_eq!(tenth, Price::from_integer_ratio(1, 10).unwrap());
+
+        for invalid in ["", ".", "-1", "1e5", "1.2.3", "abc"] {
+            assert!(invalid.parse::<Price>().is_err(), "parsed {invalid:?}");
+        }
+
+        // Prices too large to represent are rejected
+        assert!("1000000000000000000000".parse::<Price>().is_err());
+    }
+
+    /// Tests the midpoint of two prices
+    #[test]
+    fn test_midpoint() {
+        let one = Price::from_repr(1);
+        let three = Price::from_repr(3);
+        assert_eq!(one.midpoint(three), Price::from_repr(2));
+        assert_eq!(one.midpoint(one), one);
+        assert_eq!(Price::ZERO.midpoint(one), Price::ZERO);
+
+        // The midpoint does not overflow
+        let max = Price::from_repr(u128::MAX);
+        assert_eq!(max.midpoint(max), max);
+    }
+
+    /// Tests scaling prices by powers of ten
+    #[test]
+    fn test_scale_pow10() {
+        let price = Price::from_repr(12345);
+        assert_eq!(price.scale_pow10(0), Some(price));
+        assert_eq!(price.scale_pow10(2), Some(Price::from_repr(1234500)));
+        assert_eq!(price.scale_pow10(-2), Some(Price::from_repr(123)));
+
+        // Scaling down past the precision rounds to zero, scaling up overflows
+        assert_eq!(price.scale_pow10(-40), Some(Price::ZERO));
+        assert_eq!(price.scale_pow10(40), None);
+    }
+
+    /// Tests multiplying and dividing prices
+    #[test]
+    fn test_checked_mul_div() {
+        let two = Price::from_integer_ratio(2, 1).unwrap();
+        let half = Price::from_integer_ratio(1, 2).unwrap();
+        assert_eq!(two.checked_mul(half), Some(Price::ONE));
+        assert_eq!(Price::ONE.checked_div(two), Some(half));
+        assert_eq!(two.inverse(), Some(half));
+
+        // Division rounds down
+        let three = Price::from_integer_ratio(3, 1).unwrap();
+        assert_eq!(Price::ONE.checked_div(three), Price::from_integer_ratio(1, 3));
+
+        // Overflow and division by zero are rejected
+        let max = Price::from_repr(u128::MAX);
+        assert_eq!(max.checked_mul(two), None);
+        assert_eq!(max.checked_div(half), None);
+        assert_eq!(Price::ONE.checked_div(Price::ZERO), None);
+        assert_eq!(Price::ZERO.inverse(), None);
+    }
+
+    /// Tests the weighted mean of prices
+    #[test]
+    fn test_weighted_price_sum() {
+        let mut sum = WeightedPriceSum::default();
+        assert_eq!(sum.mean(), None);
+
+        sum.add(Price::from_repr(100), 1);
+        sum.add(Price::from_repr(200), 3);
+        assert_eq!(sum.weight(), 4);
+        assert_eq!(sum.mean(), Some(Price::from_repr(175)));
+
+        let mut other = WeightedPriceSum::default();
+        other.add(Price::from_repr(400), 4);
+        sum.merge(&other);
+        assert_eq!(sum.weight(), 8);
+        assert_eq!(sum.mean(), Some(Price::from_repr(287)));
+
+        // Large weights do not overflow
+        let mut large = WeightedPriceSum::default();
+        large.add(Price::from_repr(u128::MAX), u128::MAX / 2);
+        assert_eq!(large.mean(), Some(Price::from_repr(u128::MAX)));
+    }
+
+    /// Tests that the float format round trips a timestamped price
+    #[test]
+    fn test_timestamped_price_as_float() {
+        /// A wrapper serializing its price as a float
+        #[derive(Serialize, Deserialize)]
+        #[serde(transparent)]
+        struct Wrapper {
+            /// The wrapped price
+            #[serde(with = "timestamped_price_as_float")]
+            price: TimestampedPrice,
+        }
+
+        let price = TimestampedPrice { price: "1234.5".parse().unwrap(), timestamp: 10 };
+        let json = serde_json::to_string(&Wrapper { price }).unwrap();
+        assert_eq!(json, r#"{"price":1234.5,"timestamp":10}"#);
+
+        let parsed: Wrapper = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.price, price);
+    }
+}
@@ -362,9 +362,15 @@ pub fn is_pair_named(base: &Token, quote: &Token) -> bool {
 pub fn default_exchange_stable(exchange: &Exchange) -> Token {
     match exchange {
         Exchange::Binance => Token::from_ticker(USDT_TICKER),
+        Exchange::Bybit => Token::from_ticker(USDT_TICKER),
         Exchange::Coinbase => Token::from_ticker(USD_TICKER),
         Exchange::Kraken => Token::from_ticker(USD_TICKER),
+        Exchange::Kucoin => Token::from_ticker(USDT_TICKER),
         Exchange::Okx => Token::from_ticker(USDT_TICKER),
+        // Chainlink feeds are USD denominated
+        Exchange::Chainlink => Token::from_ticker(USD_TICKER),
+        // Uniswap V3 TWAPs are read from USDC pools
+        Exchange::UniswapV3 => Token::from_ticker(USDC_TICKER),
         Exchange::Renegade => Token::from_ticker(USDC_TICKER),
         _ => panic!("No default stable quote asset for exchange: {exchange:?}"),
     }
@@ -0,0 +1,42 @@
+//! Tokens listed at runtime, in addition to those in the token mapping
+//!
+//! A listing is replicated across the cluster and applied on top of the token
+//! mapping on every node
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Chain, Exchange};
+
+/// A token listed at runtime
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct TokenListing {
+    /// The name of the token
+    pub name: String,
+    /// The token's ticker
+    pub ticker: String,
+    /// The number of decimals the token uses in its ERC20 representation
+    pub decimals: u8,
+    /// The token's address on each chain it is listed on
+    pub addresses: Vec<(Chain, String)>,
+    /// The exchanges that list the token, along with the ticker used to fetch
+    /// the token's price from each
+    pub supported_exchanges: Vec<(Exchange, String)>,
+    /// The canonical exchange from which to source the token's price
+    pub canonical_exchange: Exchange,
+}
+
+impl TokenListing {
+    /// The token's address on the given chain, if it is listed there
+    pub fn address(&self, chain: Chain) -> Option<&str> {
+        self.addresses.iter().find(|(c, _)| *c == chain).map(|(_, addr)| addr.as_str())
+    }
+
+    /// The exchanges that list the token, keyed by exchange
+    pub fn exchange_support(&self) -> HashMap<Exchange, String> {
+        self.supported_exchanges.iter().cloned().collect()
+    }
+}
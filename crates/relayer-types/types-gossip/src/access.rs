@@ -0,0 +1,201 @@
+//! Operator-managed lists of peers the relayer will or will not talk to
+//!
+//! Blocked peers are never indexed, dialed, or answered. If the allowlist is
+//! non-empty, only the peers on it are; this restricts a private cluster to a
+//! known set of nodes. The lists are seeded from the relayer config and shared
+//! with the workers that enforce or adjust them
+//!
+//! Raft replicates between the peers of a cluster over the same network, so
+//! the lists must never exclude a cluster peer
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, RwLock},
+};
+
+use crate::WrappedPeerId;
+
+/// The peers on each list
+#[derive(Clone, Debug, Default)]
+struct AccessLists {
+    /// The peers that are allowed, all peers not blocked if empty
+    allowlist: BTreeSet<WrappedPeerId>,
+    /// The peers that are blocked
+    blocklist: BTreeSet<WrappedPeerId>,
+}
+
+impl AccessLists {
+    /// Whether the lists allow the given peer
+    fn is_allowed(&self, peer_id: &WrappedPeerId) -> bool {
+        !self.blocklist.contains(peer_id)
+            && (self.allowlist.is_empty() || self.allowlist.contains(peer_id))
+    }
+}
+
+/// An operator adjustment to the access lists
+#[derive(Clone, Debug, Default)]
+pub struct PeerAccessAdjustment {
+    /// The peers to add to the blocklist
+    pub block: Vec<WrappedPeerId>,
+    /// The peers to remove from the blocklist
+    pub unblock: Vec<WrappedPeerId>,
+    /// The peers to add to the allowlist
+    pub allow: Vec<WrappedPeerId>,
+    /// The peers to remove from the allowlist
+    pub disallow: Vec<WrappedPeerId>,
+}
+
+/// The peer allowlist and blocklist
+#[derive(Clone, Debug, Default)]
+pub struct PeerAccessList {
+    /// The lists, shared between workers
+    lists: Arc<RwLock<AccessLists>>,
+}
+
+impl PeerAccessList {
+    /// Constructor
+    pub fn new(allowlist: Vec<WrappedPeerId>, blocklist: Vec<WrappedPeerId>) -> Self {
+        let lists = AccessLists {
+            allowlist: allowlist.into_iter().collect(),
+            blocklist: blocklist.into_iter().collect(),
+        };
+
+        Self { lists: Arc::new(RwLock::new(lists)) }
+    }
+
+    /// Whether the relayer may talk to the given peer
+    pub fn is_allowed(&self, peer_id: &WrappedPeerId) -> bool {
+        self.lists.read().expect("peer access lock poisoned").is_allowed(peer_id)
+    }
+
+    /// Get the given peers the lists exclude
+    pub fn excluded(&self, peers: &[WrappedPeerId]) -> Vec<WrappedPeerId> {
+        let lists = self.lists.read().expect("peer access lock poisoned");
+        peers.iter().filter(|peer_id| !lists.is_allowed(peer_id)).copied().collect()
+    }
+
+    /// Apply an adjustment to the lists
+    ///
+    /// The adjustment is refused, leaving the lists unchanged, if the adjusted
+    /// lists would exclude any of the `required` peers; these are returned
+    pub fn adjust(
+        &self,
+        adjustment: &PeerAccessAdjustment,
+        required: &[WrappedPeerId],
+    ) -> Result<(), Vec<WrappedPeerId>> {
+        let mut lists = self.lists.write().expect("peer access lock poisoned");
+        let mut adjusted = lists.clone();
+        adjusted.blocklist.extend(adjustment.block.iter().copied());
+        adjusted.blocklist.retain(|peer_id| !adjustment.unblock.contains(peer_id));
+        adjusted.allowlist.extend(adjustment.allow.iter().copied());
+        adjusted.allowlist.retain(|peer_id| !adjustment.disallow.contains(peer_id));
+
+        let excluded: Vec<_> =
+            required.iter().filter(|peer_id| !adjusted.is_allowed(peer_id)).copied().collect();
+        if !excluded.is_empty() {
+            return Err(excluded);
+        }
+
+        *lists = adjusted;
+        Ok(())
+    }
+
+    /// Get the allowed peers, empty if all peers not blocked are allowed
+    pub fn allowlist(&self) -> Vec<WrappedPeerId> {
+        self.lists.read().expect("peer access lock poisoned").allowlist.iter().copied().collect()
+    }
+
+    /// Get the blocked peers
+    pub fn blocklist(&self) -> Vec<WrappedPeerId> {
+        self.lists.read().expect("peer access lock poisoned").blocklist.iter().copied().collect()
+    }
+
+    /// Block a peer
+    pub fn block(&self, peer_id: WrappedPeerId) {
+        self.lists.write().expect("peer access lock poisoned").blocklist.insert(peer_id);
+    }
+
+    /// Unblock a peer
+    pub fn unblock(&self, peer_id: &WrappedPeerId) {
+        self.lists.write().expect("peer access lock poisoned").blocklist.remove(peer_id);
+    }
+
+    /// Add a peer to the allowlist
+    pub fn allow(&self, peer_id: WrappedPeerId) {
+        self.lists.write().expect("peer access lock poisoned").allowlist.insert(peer_id);
+    }
+
+    /// Remove a peer from the allowlist
+    ///
+    /// Removing the last peer opens the relayer to all peers not blocked
+    pub fn disallow(&self, peer_id: &WrappedPeerId) {
+        self.lists.write().expect("peer access lock poisoned").allowlist.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PeerAccessAdjustment, PeerAccessList};
+    use crate::WrappedPeerId;
+
+    /// Tests that blocked peers are refused, and that a non-empty allowlist
+    /// refuses all other peers
+    #[test]
+    fn test_access() {
+        let (peer1, peer2) = (WrappedPeerId::random(), WrappedPeerId::random());
+        let access = PeerAccessList::default();
+        assert!(access.is_allowed(&peer1));
+
+        access.block(peer1);
+        assert!(!access.is_allowed(&peer1));
+        assert!(access.is_allowed(&peer2));
+
+        // A blocked peer stays blocked when allowlisted
+        access.allow(peer1);
+        assert!(!access.is_allowed(&peer1));
+        assert!(!access.is_allowed(&peer2));
+
+        access.unblock(&peer1);
+        assert!(access.is_allowed(&peer1));
+
+        access.disallow(&peer1);
+        assert!(access.is_allowed(&peer2));
+    }
+
+    /// Tests that an adjustment excluding a required peer is refused and
+    /// leaves the lists unchanged
+    #[test]
+    fn test_adjust() {
+        let (peer1, peer2, cluster_peer) =
+            (WrappedPeerId::random(), WrappedPeerId::random(), WrappedPeerId::random());
+        let access = PeerAccessList::default();
+        let required = [cluster_peer];
+
+        // An allowlist leaving out the cluster peer is refused
+        let adjustment = PeerAccessAdjustment { allow: vec![peer1], ..Default::default() };
+        assert_eq!(access.adjust(&adjustment, &required), Err(vec![cluster_peer]));
+        assert!(access.allowlist().is_empty());
+
+        // Blocking the cluster peer is refused
+        let adjustment = PeerAccessAdjustment { block: vec![cluster_peer], ..Default::default() };
+        assert_eq!(access.adjust(&adjustment, &required), Err(vec![cluster_peer]));
+        assert!(access.blocklist().is_empty());
+
+        // An allowlist including the cluster peer is applied
+        let adjustment = PeerAccessAdjustment {
+            allow: vec![peer1, cluster_peer],
+            block: vec![peer2],
+            ..Default::default()
+        };
+        assert_eq!(access.adjust(&adjustment, &required), Ok(()));
+        assert!(access.is_allowed(&peer1));
+        assert!(!access.is_allowed(&peer2));
+        assert_eq!(access.excluded(&[peer1, peer2, cluster_peer]), vec![peer2]);
+
+        // Disallowing the cluster peer from a non-empty allowlist is refused
+        let adjustment =
+            PeerAccessAdjustment { disallow: vec![cluster_peer], ..Default::default() };
+        assert_eq!(access.adjust(&adjustment, &required), Err(vec![cluster_peer]));
+        assert!(access.is_allowed(&cluster_peer));
+    }
+}
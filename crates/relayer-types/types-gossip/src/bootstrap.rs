@@ -0,0 +1,145 @@
+//! DNS records from which the relayer resolves its bootstrap peers
+//!
+//! Publishing bootstrap peers in DNS lets new nodes join the network without a
+//! hardcoded peer multiaddr; the records may change as bootstrap nodes are
+//! replaced, and the relayer periodically re-resolves them
+
+use std::{fmt, str::FromStr};
+
+/// The prefix marking a TXT bootstrap seed
+const TXT_SEED_PREFIX: &str = "txt:";
+/// The prefix marking an SRV bootstrap seed
+const SRV_SEED_PREFIX: &str = "srv:";
+
+/// The transport a bootstrap peer named by an SRV record listens on, given by
+/// the `_<proto>` label of the SRV name
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SrvTransport {
+    /// The `_udp` label, a QUIC listener
+    Udp,
+    /// The `_tcp` label, a TCP listener
+    Tcp,
+}
+
+impl SrvTransport {
+    /// Parse the transport from an SRV name of the form
+    /// `_<service>._<proto>.<domain>`
+    fn from_name(name: &str) -> Option<Self> {
+        let mut labels = name.split('.');
+        let service = labels.next()?;
+        if !service.starts_with('_') || service.len() == 1 {
+            return None;
+        }
+
+        match labels.next()? {
+            "_udp" => Some(Self::Udp),
+            "_tcp" => Some(Self::Tcp),
+            _ => None,
+        }
+    }
+}
+
+/// A DNS name from which bootstrap peers are resolved
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BootstrapDnsSeed {
+    /// A name whose TXT records each hold a bootstrap peer's multiaddr, in the
+    /// libp2p `dnsaddr=<multiaddr>` form
+    ///
+    /// The multiaddr must end in the peer's `/p2p/<peer id>`
+    Txt(String),
+    /// A name whose SRV records each point to a bootstrap peer's host and
+    /// port
+    ///
+    /// The name takes the `_<service>._<proto>.<domain>` form, the protocol
+    /// label giving the transport the peers listen on. The peer's ID is read
+    /// from a `peer_id=<peer id>` TXT record on the SRV record's target host
+    Srv(String),
+}
+
+impl BootstrapDnsSeed {
+    /// The DNS name to query
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Txt(name) | Self::Srv(name) => name,
+        }
+    }
+
+    /// The transport the peers named by an SRV seed listen on
+    pub fn srv_transport(&self) -> Option<SrvTransport> {
+        match self {
+            Self::Txt(_) => None,
+            Self::Srv(name) => SrvTransport::from_name(name),
+        }
+    }
+}
+
+impl FromStr for BootstrapDnsSeed {
+    type Err = String;
+
+    /// Parse a seed of the form `txt:<name>` or `srv:<name>`; a bare name is a
+    /// TXT seed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seed = if let Some(name) = s.strip_prefix(SRV_SEED_PREFIX) {
+            Self::Srv(name.to_string())
+        } else {
+            Self::Txt(s.strip_prefix(TXT_SEED_PREFIX).unwrap_or(s).to_string())
+        };
+
+        if seed.name().is_empty() {
+            return Err(format!("empty bootstrap DNS name: {s}"));
+        }
+        if matches!(seed, Self::Srv(_)) && seed.srv_transport().is_none() {
+            return Err(format!(
+                "SRV bootstrap DNS name must take the form _<service>._<udp|tcp>.<domain>: {s}"
+            ));
+        }
+        Ok(seed)
+    }
+}
+
+impl fmt::Display for BootstrapDnsSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Txt(name) => write!(f, "{TXT_SEED_PREFIX}{name}"),
+            Self::Srv(name) => write!(f, "{SRV_SEED_PREFIX}{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{BootstrapDnsSeed, SrvTransport};
+
+    /// Tests parsing TXT seeds, with and without their prefix
+    #[test]
+    fn test_parse_txt_seed() {
+        let seed = BootstrapDnsSeed::from_str("bootstrap.example.com").unwrap();
+        assert_eq!(seed, BootstrapDnsSeed::Txt("bootstrap.example.com".to_string()));
+        assert_eq!(BootstrapDnsSeed::from_str("txt:bootstrap.example.com").unwrap(), seed);
+        assert_eq!(seed.to_string(), "txt:bootstrap.example.com");
+        assert!(seed.srv_transport().is_none());
+
+        assert!(BootstrapDnsSeed::from_str("").is_err());
+        assert!(BootstrapDnsSeed::from_str("txt:").is_err());
+    }
+
+    /// Tests parsing SRV seeds and the transport named by their protocol label
+    #[test]
+    fn test_parse_srv_seed() {
+        let seed = BootstrapDnsSeed::from_str("srv:_renegade._udp.example.com").unwrap();
+        assert_eq!(seed, BootstrapDnsSeed::Srv("_renegade._udp.example.com".to_string()));
+        assert_eq!(seed.srv_transport(), Some(SrvTransport::Udp));
+        assert_eq!(BootstrapDnsSeed::from_str(&seed.to_string()).unwrap(), seed);
+
+        let seed = BootstrapDnsSeed::from_str("srv:_renegade._tcp.example.com").unwrap();
+        assert_eq!(seed.srv_transport(), Some(SrvTransport::Tcp));
+
+        // The name must carry a service and a known protocol label
+        assert!(BootstrapDnsSeed::from_str("srv:").is_err());
+        assert!(BootstrapDnsSeed::from_str("srv:example.com").is_err());
+        assert!(BootstrapDnsSeed::from_str("srv:_renegade._sctp.example.com").is_err());
+        assert!(BootstrapDnsSeed::from_str("srv:_._udp.example.com").is_err());
+    }
+}
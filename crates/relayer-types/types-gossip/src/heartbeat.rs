@@ -0,0 +1,186 @@
+//! Heartbeat intervals and failure thresholds, adjustable at runtime
+//!
+//! Cluster peers are heartbeated at a fixed, tight interval. Peers in other
+//! clusters are backed off once they have heartbeated consistently: their
+//! interval and failure threshold double for every `backoff_after_ms` they
+//! have been stable, up to `max_backoff` times the base values
+
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+/// The default interval at which to send heartbeats to non-cluster peers
+pub const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = 10_000; // 10 seconds
+/// The default interval at which to send heartbeats to cluster peers
+pub const DEFAULT_CLUSTER_HEARTBEAT_INTERVAL_MS: u64 = 3_000; // 3 seconds
+/// The default time without a successful heartbeat after which a non-cluster
+/// peer is assumed to have failed
+pub const DEFAULT_HEARTBEAT_FAILURE_MS: u64 = 30_000; // 30 seconds
+/// The default time without a successful heartbeat after which a cluster peer
+/// is assumed to have failed
+pub const DEFAULT_CLUSTER_HEARTBEAT_FAILURE_MS: u64 = 15_000; // 15 seconds
+/// The default maximum factor by which a stable non-cluster peer is backed off
+pub const DEFAULT_HEARTBEAT_MAX_BACKOFF: u64 = 4;
+/// The default time a non-cluster peer must be stable for each doubling of its
+/// interval
+pub const DEFAULT_HEARTBEAT_BACKOFF_AFTER_MS: u64 = 300_000; // 5 minutes
+
+/// The intervals and failure thresholds of the heartbeat protocol
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatIntervals {
+    /// The base interval at which to heartbeat non-cluster peers
+    pub interval_ms: u64,
+    /// The interval at which to heartbeat cluster peers
+    pub cluster_interval_ms: u64,
+    /// The base time without a heartbeat after which a non-cluster peer is
+    /// assumed to have failed
+    pub failure_ms: u64,
+    /// The time without a heartbeat after which a cluster peer is assumed to
+    /// have failed
+    pub cluster_failure_ms: u64,
+    /// The maximum factor by which a stable non-cluster peer's interval and
+    /// failure threshold are scaled, one to disable backoff
+    pub max_backoff: u64,
+    /// The time a non-cluster peer must be stable for each doubling of its
+    /// interval, zero to disable backoff
+    pub backoff_after_ms: u64,
+}
+
+impl Default for HeartbeatIntervals {
+    fn default() -> Self {
+        Self {
+            interval_ms: DEFAULT_HEARTBEAT_INTERVAL_MS,
+            cluster_interval_ms: DEFAULT_CLUSTER_HEARTBEAT_INTERVAL_MS,
+            failure_ms: DEFAULT_HEARTBEAT_FAILURE_MS,
+            cluster_failure_ms: DEFAULT_CLUSTER_HEARTBEAT_FAILURE_MS,
+            max_backoff: DEFAULT_HEARTBEAT_MAX_BACKOFF,
+            backoff_after_ms: DEFAULT_HEARTBEAT_BACKOFF_AFTER_MS,
+        }
+    }
+}
+
+impl HeartbeatIntervals {
+    /// Check that the intervals are usable
+    ///
+    /// A peer must be given more than one interval to heartbeat before it is
+    /// considered failed
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_ms == 0 || self.cluster_interval_ms == 0 {
+            return Err("heartbeat intervals must be non-zero".to_string());
+        }
+        if self.failure_ms <= self.interval_ms {
+            return Err(
+                "heartbeat failure threshold must exceed the heartbeat interval".to_string()
+            );
+        }
+        if self.cluster_failure_ms <= self.cluster_interval_ms {
+            return Err(
+                "cluster heartbeat failure threshold must exceed the cluster heartbeat interval"
+                    .to_string(),
+            );
+        }
+        if self.max_backoff == 0 {
+            return Err("heartbeat max backoff must be at least one".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// The factor by which to scale a non-cluster peer's interval and failure
+    /// threshold, given how long the peer has been stable
+    pub fn backoff_factor(&self, stable_for_ms: u64) -> u64 {
+        if self.backoff_after_ms == 0 {
+            return 1;
+        }
+
+        let doublings = (stable_for_ms / self.backoff_after_ms).min(u64::BITS as u64 - 1);
+        (1u64 << doublings).min(self.max_backoff.max(1))
+    }
+
+    /// The interval at which to heartbeat a peer
+    pub fn peer_interval_ms(&self, same_cluster: bool, stable_for_ms: u64) -> u64 {
+        if same_cluster {
+            return self.cluster_interval_ms;
+        }
+
+        self.interval_ms.saturating_mul(self.backoff_factor(stable_for_ms))
+    }
+
+    /// The time without a heartbeat after which a peer is assumed to have
+    /// failed
+    pub fn peer_failure_ms(&self, same_cluster: bool, stable_for_ms: u64) -> u64 {
+        if same_cluster {
+            return self.cluster_failure_ms;
+        }
+
+        self.failure_ms.saturating_mul(self.backoff_factor(stable_for_ms))
+    }
+}
+
+/// The heartbeat intervals in use, shared between the workers that read and
+/// adjust them
+#[derive(Clone, Debug, Default)]
+pub struct HeartbeatSettings {
+    /// The current intervals
+    intervals: Arc<RwLock<HeartbeatIntervals>>,
+}
+
+impl HeartbeatSettings {
+    /// Constructor
+    pub fn new(intervals: HeartbeatIntervals) -> Self {
+        Self { intervals: Arc::new(RwLock::new(intervals)) }
+    }
+
+    /// Get the current intervals
+    pub fn get(&self) -> HeartbeatIntervals {
+        *self.intervals.read().expect("heartbeat settings lock poisoned")
+    }
+
+    /// Replace the current intervals, if they are valid
+    pub fn set(&self, intervals: HeartbeatIntervals) -> Result<(), String> {
+        intervals.validate()?;
+        *self.intervals.write().expect("heartbeat settings lock poisoned") = intervals;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HeartbeatIntervals, HeartbeatSettings};
+
+    /// Tests that stable non-cluster peers are backed off up to the maximum,
+    /// while cluster peers keep the base interval
+    #[test]
+    fn test_backoff() {
+        let intervals = HeartbeatIntervals {
+            interval_ms: 10,
+            cluster_interval_ms: 3,
+            failure_ms: 30,
+            cluster_failure_ms: 15,
+            max_backoff: 4,
+            backoff_after_ms: 100,
+        };
+
+        assert_eq!(intervals.peer_interval_ms(false /* same_cluster */, 0), 10);
+        assert_eq!(intervals.peer_interval_ms(false /* same_cluster */, 150), 20);
+        assert_eq!(intervals.peer_interval_ms(false /* same_cluster */, 250), 40);
+        assert_eq!(intervals.peer_interval_ms(false /* same_cluster */, u64::MAX), 40);
+        assert_eq!(intervals.peer_failure_ms(false /* same_cluster */, 150), 60);
+
+        assert_eq!(intervals.peer_interval_ms(true /* same_cluster */, u64::MAX), 3);
+        assert_eq!(intervals.peer_failure_ms(true /* same_cluster */, u64::MAX), 15);
+
+        let no_backoff = HeartbeatIntervals { backoff_after_ms: 0, ..intervals };
+        assert_eq!(no_backoff.peer_interval_ms(false /* same_cluster */, u64::MAX), 10);
+    }
+
+    /// Tests that invalid intervals are rejected at runtime
+    #[test]
+    fn test_set_invalid() {
+        let settings = HeartbeatSettings::default();
+        let invalid = HeartbeatIntervals { failure_ms: 1, ..settings.get() };
+
+        assert!(settings.set(invalid).is_err());
+        assert_eq!(settings.get(), HeartbeatIntervals::default());
+    }
+}
@@ -0,0 +1,140 @@
+//! A table of round trip times to peers
+//!
+//! The network manager times each request it sends against the response it
+//! receives and folds the sample into a smoothed per-peer estimate. The table
+//! is shared with other workers that read the estimates, e.g. to prefer low
+//! latency counterparties when choosing whom to handshake with
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::WrappedPeerId;
+
+/// The weight given to each new sample when smoothing, as a divisor
+///
+/// I.e. each sample moves the estimate an eighth of the way towards it, as in
+/// TCP's smoothed RTT
+const RTT_SMOOTHING_DIVISOR: u32 = 8;
+/// The RTT at or below which a counterparty keeps its full selection weight
+const REFERENCE_RTT: Duration = Duration::from_millis(50);
+
+/// The smoothed round trip time to each peer
+#[derive(Clone, Debug, Default)]
+pub struct PeerLatencies {
+    /// The RTT estimates, keyed by peer
+    rtts: Arc<RwLock<HashMap<WrappedPeerId, Duration>>>,
+}
+
+impl PeerLatencies {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold an RTT sample into a peer's estimate
+    pub fn record_sample(&self, peer_id: WrappedPeerId, sample: Duration) {
+        let mut rtts = self.rtts.write().expect("peer latencies lock poisoned");
+        let rtt = match rtts.get(&peer_id) {
+            Some(prev) if sample >= *prev => *prev + (sample - *prev) / RTT_SMOOTHING_DIVISOR,
+            Some(prev) => *prev - (*prev - sample) / RTT_SMOOTHING_DIVISOR,
+            None => sample,
+        };
+
+        rtts.insert(peer_id, rtt);
+    }
+
+    /// Get the estimated RTT to a peer, if one has been measured
+    pub fn get(&self, peer_id: &WrappedPeerId) -> Option<Duration> {
+        self.rtts.read().expect("peer latencies lock poisoned").get(peer_id).copied()
+    }
+
+    /// Get the lowest estimated RTT to any of the given peers
+    pub fn min_rtt(&self, peers: &[WrappedPeerId]) -> Option<Duration> {
+        let rtts = self.rtts.read().expect("peer latencies lock poisoned");
+        peers.iter().filter_map(|peer_id| rtts.get(peer_id)).min().copied()
+    }
+
+    /// Choose the peer with the lowest estimated RTT
+    ///
+    /// Returns `None` if none of the given peers has been measured
+    pub fn fastest(&self, peers: &[WrappedPeerId]) -> Option<WrappedPeerId> {
+        let rtts = self.rtts.read().expect("peer latencies lock poisoned");
+        peers
+            .iter()
+            .filter_map(|peer_id| rtts.get(peer_id).map(|rtt| (rtt, peer_id)))
+            .min()
+            .map(|(_, peer_id)| *peer_id)
+    }
+
+    /// Scale a counterparty's selection weight by the RTT to the nearest of
+    /// its peers
+    ///
+    /// The weight is kept in full up to the reference RTT and shrinks in
+    /// proportion to the RTT beyond it; a counterparty none of whose peers has
+    /// been measured keeps its weight
+    pub fn scale_weight(&self, weight: f64, peers: &[WrappedPeerId]) -> f64 {
+        match self.min_rtt(peers) {
+            Some(rtt) if rtt > REFERENCE_RTT => {
+                weight * REFERENCE_RTT.as_secs_f64() / rtt.as_secs_f64()
+            },
+            _ => weight,
+        }
+    }
+
+    /// Remove a peer's estimate, e.g. when the peer is expired
+    pub fn remove(&self, peer_id: &WrappedPeerId) {
+        self.rtts.write().expect("peer latencies lock poisoned").remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use libp2p::{PeerId, identity::Keypair};
+
+    use crate::WrappedPeerId;
+
+    use super::PeerLatencies;
+
+    /// Tests that samples are smoothed into a peer's estimate
+    #[test]
+    fn test_record_sample() {
+        let latencies = PeerLatencies::new();
+        let peer = WrappedPeerId(PeerId::from_public_key(&Keypair::generate_ed25519().public()));
+        assert_eq!(latencies.get(&peer), None);
+
+        latencies.record_sample(peer, Duration::from_millis(80));
+        assert_eq!(latencies.get(&peer), Some(Duration::from_millis(80)));
+
+        latencies.record_sample(peer, Duration::from_millis(160));
+        assert_eq!(latencies.get(&peer), Some(Duration::from_millis(90)));
+
+        latencies.record_sample(peer, Duration::from_millis(10));
+        assert_eq!(latencies.get(&peer), Some(Duration::from_millis(80)));
+
+        latencies.remove(&peer);
+        assert_eq!(latencies.get(&peer), None);
+    }
+
+    /// Tests choosing and weighting counterparties by their RTT
+    #[test]
+    fn test_counterparty_preference() {
+        let latencies = PeerLatencies::new();
+        let (near, far, unmeasured) =
+            (WrappedPeerId::random(), WrappedPeerId::random(), WrappedPeerId::random());
+        latencies.record_sample(near, Duration::from_millis(20));
+        latencies.record_sample(far, Duration::from_millis(200));
+
+        assert_eq!(latencies.fastest(&[far, unmeasured, near]), Some(near));
+        assert_eq!(latencies.fastest(&[unmeasured]), None);
+
+        assert_eq!(latencies.scale_weight(10., &[near]), 10.);
+        assert_eq!(latencies.scale_weight(10., &[far]), 2.5);
+        assert_eq!(latencies.scale_weight(10., &[far, near]), 10.);
+        assert_eq!(latencies.scale_weight(10., &[unmeasured]), 10.);
+    }
+}
@@ -8,19 +8,34 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![deny(clippy::missing_docs_in_private_items)]
 
+mod access;
+mod bootstrap;
 mod cluster;
 mod handshake;
+mod heartbeat;
+mod latency;
 #[cfg(feature = "mocks")]
 pub mod mocks;
 pub mod network_order;
 mod peer_id;
 mod peer_info;
+mod peer_metadata;
 
 // Re-exports
+pub use access::{PeerAccessAdjustment, PeerAccessList};
+pub use bootstrap::{BootstrapDnsSeed, SrvTransport};
 pub use cluster::{CLUSTER_MANAGEMENT_TOPIC_PREFIX, ClusterAsymmetricKeypair, ClusterId};
 pub use handshake::ConnectionRole;
+pub use heartbeat::{
+    DEFAULT_CLUSTER_HEARTBEAT_FAILURE_MS, DEFAULT_CLUSTER_HEARTBEAT_INTERVAL_MS,
+    DEFAULT_HEARTBEAT_BACKOFF_AFTER_MS, DEFAULT_HEARTBEAT_FAILURE_MS,
+    DEFAULT_HEARTBEAT_INTERVAL_MS, DEFAULT_HEARTBEAT_MAX_BACKOFF, HeartbeatIntervals,
+    HeartbeatSettings,
+};
+pub use latency::PeerLatencies;
 pub use peer_id::WrappedPeerId;
 pub use peer_info::PeerInfo;
+pub use peer_metadata::{FEATURE_HEARTBEAT_CLOCK, FEATURE_MATCH_OWNERSHIP, PeerMetadata};
 
 #[cfg(feature = "rkyv")]
 pub use peer_info::MultiaddrDef;
@@ -49,6 +64,7 @@ mod tests {
             cluster_auth_signature: Vec::new(),
             last_heartbeat: 0,
             addr: Multiaddr::empty(),
+            metadata: None,
         };
 
         let serialized = serde_json::to_string(&peer_info).unwrap();
@@ -9,7 +9,7 @@ use util::{get_current_time_millis, networking::is_dialable_multiaddr};
 #[cfg(feature = "rkyv")]
 use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 
-use crate::{ClusterId, WrappedPeerId};
+use crate::{ClusterId, PeerMetadata, WrappedPeerId};
 
 /// Contains information about connected peers
 #[derive(Clone, Debug, Serialize, Deserialize, Derivative)]
@@ -30,6 +30,10 @@ pub struct PeerInfo {
     /// prove that the peer is a valid cluster member
     #[derivative(PartialEq = "ignore")]
     pub cluster_auth_signature: Vec<u8>,
+    /// The software version and capabilities most recently advertised by the
+    /// peer, if it has advertised any
+    #[serde(default)]
+    pub metadata: Option<PeerMetadata>,
 }
 
 impl Default for PeerInfo {
@@ -40,6 +44,7 @@ impl Default for PeerInfo {
             last_heartbeat: 0,
             cluster_id: ClusterId::from_str_infallible("0"),
             cluster_auth_signature: vec![],
+            metadata: None,
         }
     }
 }
@@ -58,6 +63,7 @@ impl PeerInfo {
             cluster_id,
             cluster_auth_signature,
             last_heartbeat: get_current_time_millis(),
+            metadata: None,
         }
     }
 
@@ -117,6 +123,11 @@ impl PeerInfo {
     pub fn get_last_heartbeat(&self) -> u64 {
         self.last_heartbeat
     }
+
+    /// Records the metadata advertised in a peer's heartbeat
+    pub fn set_metadata(&mut self, metadata: PeerMetadata) {
+        self.metadata = Some(metadata);
+    }
 }
 
 // -----------------------
@@ -0,0 +1,68 @@
+//! Software and capability metadata advertised by peers in their heartbeats
+//!
+//! Peers attach their metadata to every heartbeat so that cluster operators
+//! can see which software each peer runs, e.g. during a rolling upgrade
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rkyv")]
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+
+/// The version of the relayer software, taken from the crate version
+const RELAYER_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The circuit versions the local relayer can prove and verify
+const SUPPORTED_CIRCUIT_VERSIONS: &[u32] = &[2];
+
+/// Feature bit set by peers that send their wall clock time in heartbeats
+pub const FEATURE_HEARTBEAT_CLOCK: u64 = 1 << 0;
+/// Feature bit set by peers that assign matching ownership of orders by
+/// rendezvous hashing over live cluster peers
+pub const FEATURE_MATCH_OWNERSHIP: u64 = 1 << 1;
+/// The feature bits supported by the local relayer
+const LOCAL_FEATURE_BITS: u64 = FEATURE_HEARTBEAT_CLOCK | FEATURE_MATCH_OWNERSHIP;
+
+/// The software version and capabilities of a peer
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvDeserialize, RkyvSerialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct PeerMetadata {
+    /// The semantic version of the relayer software the peer runs
+    pub version: String,
+    /// The circuit versions the peer can prove and verify
+    pub circuit_versions: Vec<u32>,
+    /// A bitmask of the optional protocol features the peer supports
+    pub feature_bits: u64,
+}
+
+impl PeerMetadata {
+    /// The metadata of the local relayer
+    pub fn local() -> Self {
+        Self {
+            version: RELAYER_VERSION.to_string(),
+            circuit_versions: SUPPORTED_CIRCUIT_VERSIONS.to_vec(),
+            feature_bits: LOCAL_FEATURE_BITS,
+        }
+    }
+
+    /// Whether the peer supports the given feature bit
+    pub fn supports(&self, feature: u64) -> bool {
+        self.feature_bits & feature == feature
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FEATURE_HEARTBEAT_CLOCK, FEATURE_MATCH_OWNERSHIP, PeerMetadata};
+
+    /// Tests checking a peer's feature bits
+    #[test]
+    fn test_supports() {
+        let local = PeerMetadata::local();
+        assert!(local.supports(FEATURE_HEARTBEAT_CLOCK));
+        assert!(local.supports(FEATURE_HEARTBEAT_CLOCK | FEATURE_MATCH_OWNERSHIP));
+
+        let legacy = PeerMetadata { feature_bits: FEATURE_MATCH_OWNERSHIP, ..local };
+        assert!(!legacy.supports(FEATURE_HEARTBEAT_CLOCK));
+        assert!(!legacy.supports(FEATURE_HEARTBEAT_CLOCK | FEATURE_MATCH_OWNERSHIP));
+    }
+}
@@ -8,9 +8,13 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![deny(clippy::missing_docs_in_private_items)]
 
+mod liveness;
 mod logging;
+mod shutdown;
 mod worker;
 
+pub use liveness::WorkerLiveness;
+pub use shutdown::{InFlightTask, ShutdownPhase, ShutdownProgress};
 pub use worker::*;
 
 use tokio::sync::watch::{
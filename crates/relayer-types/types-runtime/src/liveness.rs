@@ -0,0 +1,39 @@
+//! Tracks which workers have exited, for reporting by health probes
+//!
+//! The coordinator's worker watchers mark a worker failed when any of its
+//! threads exits; other workers, e.g. the API server's health probes, read the
+//! set of failed workers
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, RwLock},
+};
+
+/// The liveness of the relayer's watched workers
+#[derive(Clone, Debug, Default)]
+pub struct WorkerLiveness {
+    /// The names of the workers that have exited
+    failed: Arc<RwLock<BTreeSet<String>>>,
+}
+
+impl WorkerLiveness {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a worker as failed
+    pub fn mark_failed(&self, worker_name: String) {
+        self.failed.write().expect("worker liveness lock poisoned").insert(worker_name);
+    }
+
+    /// Whether every watched worker is live
+    pub fn all_live(&self) -> bool {
+        self.failed.read().expect("worker liveness lock poisoned").is_empty()
+    }
+
+    /// The names of the workers that have exited, in sorted order
+    pub fn failed_workers(&self) -> Vec<String> {
+        self.failed.read().expect("worker liveness lock poisoned").iter().cloned().collect()
+    }
+}
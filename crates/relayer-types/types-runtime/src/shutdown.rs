@@ -0,0 +1,123 @@
+//! Tracks the relayer's progress through its shutdown sequence
+//!
+//! On a termination signal the coordinator drains the relayer in phases: API
+//! writes are refused, the matching engine is stopped, in-flight tasks run to
+//! completion, the raft is flushed, and finally the network is stopped. Workers
+//! read the current phase to refuse new work, and the health probes report it
+//! so that orchestrators wait for the drain before killing the process
+
+use std::{
+    fmt::{self, Display},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// A phase of the shutdown sequence, in the order the phases are entered
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownPhase {
+    /// The relayer is running normally
+    #[default]
+    Running,
+    /// The API server refuses writes
+    StoppingWrites,
+    /// The matching engine is being stopped
+    DrainingMatching,
+    /// In-flight tasks are running to completion, no new tasks are started
+    DrainingTasks,
+    /// The raft is being snapshotted and shut down
+    FlushingRaft,
+    /// The gossip server and network manager are being stopped
+    StoppingNetwork,
+    /// The drain is complete, the process may exit
+    Stopped,
+}
+
+impl ShutdownPhase {
+    /// The name of the phase, as reported by the health probes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShutdownPhase::Running => "running",
+            ShutdownPhase::StoppingWrites => "stopping-writes",
+            ShutdownPhase::DrainingMatching => "draining-matching",
+            ShutdownPhase::DrainingTasks => "draining-tasks",
+            ShutdownPhase::FlushingRaft => "flushing-raft",
+            ShutdownPhase::StoppingNetwork => "stopping-network",
+            ShutdownPhase::Stopped => "stopped",
+        }
+    }
+}
+
+impl Display for ShutdownPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The relayer's progress through its shutdown sequence
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownProgress {
+    /// The current phase of the shutdown sequence
+    phase: Arc<RwLock<ShutdownPhase>>,
+    /// The number of tasks currently running on the local node
+    in_flight_tasks: Arc<AtomicUsize>,
+}
+
+impl ShutdownProgress {
+    /// Constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current phase of the shutdown sequence
+    pub fn phase(&self) -> ShutdownPhase {
+        *self.phase.read().expect("shutdown phase lock poisoned")
+    }
+
+    /// Enter the given phase
+    ///
+    /// The sequence only moves forwards, entering an earlier phase is a no-op
+    pub fn advance(&self, phase: ShutdownPhase) {
+        let mut current = self.phase.write().expect("shutdown phase lock poisoned");
+        *current = (*current).max(phase);
+    }
+
+    /// Whether the shutdown sequence has begun
+    pub fn is_draining(&self) -> bool {
+        self.phase() != ShutdownPhase::Running
+    }
+
+    /// Whether new tasks may be started on the local node
+    pub fn accepts_tasks(&self) -> bool {
+        self.phase() < ShutdownPhase::DrainingTasks
+    }
+
+    /// The number of tasks currently running on the local node
+    pub fn in_flight_tasks(&self) -> usize {
+        self.in_flight_tasks.load(Ordering::SeqCst)
+    }
+
+    /// Count a task as in flight until the returned guard is dropped
+    ///
+    /// Callers should take the guard before checking `accepts_tasks`, so that
+    /// the coordinator never observes zero in-flight tasks while a task is
+    /// starting
+    pub fn track_task(&self) -> InFlightTask {
+        self.in_flight_tasks.fetch_add(1, Ordering::SeqCst);
+        InFlightTask { in_flight_tasks: self.in_flight_tasks.clone() }
+    }
+}
+
+/// A guard counting a task as in flight while it is held
+#[derive(Debug)]
+pub struct InFlightTask {
+    /// The in-flight task count to decrement on drop
+    in_flight_tasks: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightTask {
+    fn drop(&mut self) {
+        self.in_flight_tasks.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -11,7 +11,7 @@ use tokio::sync::mpsc::{Receiver, Sender, channel};
 use util::log_task;
 use util::logging::Outcome;
 
-use crate::logging::Task;
+use crate::{liveness::WorkerLiveness, logging::Task};
 
 /// A channel for sending worker failures
 pub type WorkerFailureSender = Sender<()>;
@@ -72,12 +72,18 @@ pub trait Worker {
 /// Spawn a watcher thread for each join handle in the worker being watched
 ///
 /// A worker may have more than one join handle in the case that it spawns
-/// multiple sub-worker threads. Each will be individually watched
-pub fn watch_worker<W: Worker>(worker: &mut W, failure_channel: &WorkerFailureSender) {
+/// multiple sub-worker threads. Each will be individually watched, and the
+/// worker is marked failed in the liveness table when any of them exits
+pub fn watch_worker<W: Worker>(
+    worker: &mut W,
+    failure_channel: &WorkerFailureSender,
+    liveness: &WorkerLiveness,
+) {
     let watcher_name = format!("{}-watcher", worker.name());
     for join_handle in worker.join() {
         let worker_name = worker.name();
         let channel_clone = failure_channel.clone();
+        let liveness = liveness.clone();
 
         Builder::new()
             .name(watcher_name.clone())
@@ -102,6 +108,8 @@ pub fn watch_worker<W: Worker>(worker: &mut W, failure_channel: &WorkerFailureSe
                         );
                     },
                 }
+                liveness.mark_failed(worker_name.clone());
+
                 // Notify the coordinator of the worker failure. If the
                 // coordinator has already dropped the receiver -- because the
                 // node is tearing down, or another worker already reported a
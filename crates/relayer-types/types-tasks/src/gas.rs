@@ -0,0 +1,44 @@
+//! Types for accounting the gas spent by tasks on on-chain submissions
+#![cfg_attr(feature = "rkyv", allow(missing_docs))]
+
+#[cfg(feature = "rkyv")]
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::{Deserialize, Serialize};
+
+/// The gas cost of a single on-chain submission made by a task, as read from
+/// its receipt
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct ChainSubmission {
+    /// The hash of the submitted transaction
+    pub tx_hash: String,
+    /// The gas used by the transaction
+    pub gas_used: u64,
+    /// The price paid per unit of gas, in wei
+    pub effective_gas_price: u128,
+    /// The total fee paid for the transaction, in wei
+    pub fee_paid: u128,
+}
+
+/// Aggregate gas costs over a set of on-chain submissions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(derive(Debug)))]
+pub struct GasCostTotals {
+    /// The number of submissions
+    pub num_submissions: u64,
+    /// The total gas used
+    pub gas_used: u64,
+    /// The total fee paid, in wei
+    pub fee_paid: u128,
+}
+
+impl GasCostTotals {
+    /// Add a submission to the totals
+    pub fn add(&mut self, submission: &ChainSubmission) {
+        self.num_submissions += 1;
+        self.gas_used = self.gas_used.saturating_add(submission.gas_used);
+        self.fee_paid = self.fee_paid.saturating_add(submission.fee_paid);
+    }
+}
@@ -1,6 +1,8 @@
 //! Types for task history storage
 #![cfg_attr(feature = "rkyv", allow(missing_docs))]
 
+use std::str::FromStr;
+
 use alloy::primitives::Address;
 use circuit_types::Amount;
 #[cfg(feature = "rkyv")]
@@ -119,6 +121,113 @@ impl HistoricalTaskDescription {
             TaskDescriptor::Withdraw(_) => None,
         }
     }
+
+    /// Get the kind of the task described
+    pub fn kind(&self) -> HistoricalTaskKind {
+        match self {
+            Self::NewAccount => HistoricalTaskKind::NewAccount,
+            Self::Deposit { .. } => HistoricalTaskKind::Deposit,
+            Self::CreateBalance { .. } => HistoricalTaskKind::CreateBalance,
+            Self::CreateOrder { .. } => HistoricalTaskKind::CreateOrder,
+            Self::RefreshAccount { .. } => HistoricalTaskKind::RefreshAccount,
+        }
+    }
+}
+
+// --- Filtering --- //
+
+/// The kind of a historical task, without its auxiliary information
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoricalTaskKind {
+    /// A new account was created
+    NewAccount,
+    /// A deposit was made
+    Deposit,
+    /// A balance was created
+    CreateBalance,
+    /// An order was created
+    CreateOrder,
+    /// An account was refreshed
+    RefreshAccount,
+}
+
+impl FromStr for HistoricalTaskKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new_account" => Ok(Self::NewAccount),
+            "deposit" => Ok(Self::Deposit),
+            "create_balance" => Ok(Self::CreateBalance),
+            "create_order" => Ok(Self::CreateOrder),
+            "refresh_account" => Ok(Self::RefreshAccount),
+            _ => Err(format!("Unknown task type: {s}")),
+        }
+    }
+}
+
+/// The coarse status of a task, collapsing the running states
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    /// The task is waiting in the queue
+    Queued,
+    /// The task is running
+    Running,
+    /// The task completed
+    Completed,
+    /// The task failed
+    Failed,
+}
+
+impl TaskStatus {
+    /// Get the status of a task in the given state
+    pub fn from_state(state: &QueuedTaskState) -> Self {
+        match state {
+            QueuedTaskState::Queued => Self::Queued,
+            QueuedTaskState::Preemptive | QueuedTaskState::Running { .. } => Self::Running,
+            QueuedTaskState::Completed => Self::Completed,
+            QueuedTaskState::Failed => Self::Failed,
+        }
+    }
+}
+
+impl FromStr for TaskStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(Self::Queued),
+            "running" => Ok(Self::Running),
+            "completed" => Ok(Self::Completed),
+            "failed" => Ok(Self::Failed),
+            _ => Err(format!("Unknown task status: {s}")),
+        }
+    }
+}
+
+/// A filter over an account's task history
+///
+/// Unset fields match every task
+#[derive(Clone, Debug, Default)]
+pub struct TaskHistoryFilter {
+    /// Only match tasks of this kind
+    pub kind: Option<HistoricalTaskKind>,
+    /// Only match tasks with this status
+    pub status: Option<TaskStatus>,
+    /// Only match tasks created at or after this time
+    pub created_after: Option<u64>,
+    /// Only match tasks created strictly before this time
+    pub created_before: Option<u64>,
+}
+
+impl TaskHistoryFilter {
+    /// Whether the filter matches the given task
+    pub fn matches(&self, task: &HistoricalTask) -> bool {
+        self.kind.is_none_or(|k| task.task_info.kind() == k)
+            && self.status.is_none_or(|s| TaskStatus::from_state(&task.state) == s)
+            && self.created_after.is_none_or(|t| task.created_at >= t)
+            && self.created_before.is_none_or(|t| task.created_at < t)
+    }
 }
 
 // --- Mocks --- //
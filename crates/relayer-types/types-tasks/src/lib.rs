@@ -10,10 +10,12 @@
 
 mod descriptors;
 mod error;
+mod gas;
 mod history;
 #[cfg(feature = "mocks")]
 pub mod mocks;
 
 pub use descriptors::*;
 pub use error::*;
+pub use gas::*;
 pub use history::*;
@@ -9,20 +9,24 @@ use matching_engine_core::MatchingEngine;
 use renegade_solidity_abi::v2::IDarkpoolV2::PublicIntentPermit;
 use system_bus::{
     ADMIN_BALANCE_UPDATES_TOPIC, ADMIN_ORDER_UPDATES_TOPIC, AdminOrderUpdateType,
-    OWNER_INDEX_CHANGED_TOPIC, SystemBusMessage, account_fills_topic,
+    OWNER_INDEX_CHANGED_TOPIC, SystemBusMessage, account_balances_topic, account_fills_topic,
 };
 use types_account::{
-    MatchingPoolName, OrderRefreshData,
+    MatchingPoolName, OrderRefreshData, TenantId,
     account::{Account, OrderId},
     balance::Balance,
     keychain::KeyChain,
     order::{Order, PrivacyRing},
     order_auth::OrderAuth,
+    risk::{AccountRiskConfig, volume_day},
+    sweep::{AccountSweepPolicy, SweepAction},
 };
-use types_core::AccountId;
+use types_core::{AccountId, HmacKey};
+use types_gossip::WrappedPeerId;
 use types_proofs::ValidityProofLocator;
 use util::log_task;
 use util::logging::Outcome;
+use uuid::Uuid;
 
 use crate::{
     applicator::error::StateApplicatorError,
@@ -35,6 +39,18 @@ use crate::{
 
 use super::{Result, StateApplicator, return_type::ApplicatorReturnType};
 
+/// Error message emitted when an account is claimed by another tenant, or
+/// outside of any tenant
+const ERR_ACCOUNT_CLAIMED: &str = "account is claimed by another tenant";
+/// Error message emitted when an account version is advanced from a version
+/// other than its current one
+const ERR_STALE_ACCOUNT_VERSION: &str = "account was modified since the given version";
+/// Error message emitted when a credit would overflow a balance
+const ERR_BALANCE_OVERFLOW: &str = "credit overflows the balance";
+/// Error message emitted when a client order id is already used by another of
+/// the account's orders
+const ERR_DUPLICATE_CLIENT_ORDER_ID: &str = "client order id already in use";
+
 /// Update the matching engine cache for orders affected by a balance change
 pub fn update_matchable_amounts<T: libmdbx::TransactionKind>(
     account_id: AccountId,
@@ -69,6 +85,42 @@ pub fn update_matchable_amounts<T: libmdbx::TransactionKind>(
     Ok(())
 }
 
+/// A sweep requested for one of an account's balances
+struct SweepRequest {
+    /// The mint of the balance
+    mint: Address,
+    /// The amount to sweep
+    amount: Amount,
+    /// The address to which the balance is swept
+    cold_address: Address,
+}
+
+/// Apply the account's sweep policy to an updated balance, returning the sweep
+/// to request, if any
+fn apply_sweep_policy(
+    account_id: AccountId,
+    balance: &Balance,
+    tx: &StateTxn<'_, libmdbx::RW>,
+) -> Result<Option<SweepRequest>> {
+    let Some(policy) = tx.get_account_sweep_policy(&account_id)? else {
+        return Ok(None);
+    };
+
+    let mint = balance.mint();
+    let pending = tx.is_sweep_pending(&account_id, &mint)?;
+    match policy.evaluate(&mint, balance.amount(), pending) {
+        SweepAction::Request(amount) => {
+            tx.set_sweep_pending(&account_id, &mint, true)?;
+            Ok(Some(SweepRequest { mint, amount, cold_address: policy.cold_address }))
+        },
+        SweepAction::Rearm => {
+            tx.set_sweep_pending(&account_id, &mint, false)?;
+            Ok(None)
+        },
+        SweepAction::None => Ok(None),
+    }
+}
+
 impl StateApplicator {
     // -------------
     // | Interface |
@@ -113,6 +165,19 @@ impl StateApplicator {
             return Err(StateApplicatorError::reject(MATCHING_POOL_DOES_NOT_EXIST_ERR));
         }
 
+        // Client order ids are unique within an account. The API server checks
+        // this before proposing, but two concurrent creates may both pass that
+        // check, so the applicator is the source of truth
+        if let Some(client_order_id) = &order.metadata.client_order_id {
+            let duplicate = tx.get_account_orders(&account_id)?.into_iter().any(|existing| {
+                existing.id != order.id
+                    && existing.metadata.client_order_id.as_ref() == Some(client_order_id)
+            });
+            if duplicate {
+                return Err(StateApplicatorError::reject(ERR_DUPLICATE_CLIENT_ORDER_ID));
+            }
+        }
+
         // Write the order fields
         tx.add_order(&account_id, order)?;
         tx.write_order_auth(&order.id, auth)?;
@@ -313,6 +378,7 @@ impl StateApplicator {
             return Err(StateApplicatorError::reject("account not found"));
         }
         tx.update_balance(&account_id, balance)?;
+        let sweep = apply_sweep_policy(account_id, balance, &tx)?;
         tx.commit()?;
 
         // Open a read transaction to get order info for matching engine updates
@@ -321,8 +387,51 @@ impl StateApplicator {
         let tx = self.db().new_read_tx()?;
         update_matchable_amounts(account_id, balance, &engine, &tx)?;
 
-        // Publish admin balance update event
-        self.publish_admin_balance_update(account_id, balance);
+        // Publish balance update events
+        self.publish_balance_update(account_id, balance);
+        if let Some(sweep) = sweep {
+            self.publish_sweep_request(account_id, sweep);
+        }
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Credit an amount to an account's balance
+    ///
+    /// The existing balance is read in the same transaction it is written in,
+    /// so concurrent credits to the same balance are all applied
+    pub fn credit_account_balance(
+        &self,
+        account_id: AccountId,
+        credit: &Balance,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::credit_account_balance")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+
+        let balance = match tx.get_balance(&account_id, &credit.mint(), credit.location)? {
+            Some(existing) => {
+                let mut balance = existing.deserialize()?;
+                let amount = balance.amount_mut();
+                *amount = amount
+                    .checked_add(credit.amount())
+                    .ok_or_else(|| StateApplicatorError::reject(ERR_BALANCE_OVERFLOW))?;
+                balance
+            },
+            None => credit.clone(),
+        };
+        tx.update_balance(&account_id, &balance)?;
+        let sweep = apply_sweep_policy(account_id, &balance, &tx)?;
+        tx.commit()?;
+
+        let engine = self.matching_engine();
+        let tx = self.db().new_read_tx()?;
+        update_matchable_amounts(account_id, &balance, &engine, &tx)?;
+
+        self.publish_balance_update(account_id, &balance);
+        if let Some(sweep) = sweep {
+            self.publish_sweep_request(account_id, sweep);
+        }
         Ok(ApplicatorReturnType::None)
     }
 
@@ -341,6 +450,145 @@ impl StateApplicator {
         Ok(ApplicatorReturnType::None)
     }
 
+    /// Set or clear the risk config for an account
+    pub fn set_account_risk_config(
+        &self,
+        account_id: AccountId,
+        config: Option<&AccountRiskConfig>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::set_account_risk_config")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+        tx.set_account_risk_config(&account_id, config)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Set or clear the sweep policy for an account
+    pub fn set_account_sweep_policy(
+        &self,
+        account_id: AccountId,
+        policy: Option<&AccountSweepPolicy>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::set_account_sweep_policy")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+        tx.set_account_sweep_policy(&account_id, policy)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Set or clear the pinned peers for an account
+    ///
+    /// An empty set of peers clears the pin
+    pub fn set_account_pinned_peers(
+        &self,
+        account_id: AccountId,
+        peers: Option<&[WrappedPeerId]>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::set_account_pinned_peers")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+
+        let peers = peers.filter(|peers| !peers.is_empty());
+        tx.set_account_pinned_peers(&account_id, peers)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Set or revoke an API key for an account
+    pub fn set_account_api_key(
+        &self,
+        account_id: AccountId,
+        key_id: Uuid,
+        secret: Option<HmacKey>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::set_account_api_key")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+
+        tx.set_account_api_key(&account_id, key_id, secret)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Claim an account for a tenant, or outside of any tenant if `tenant` is
+    /// `None`
+    ///
+    /// An account may not move between tenants once claimed, and an account
+    /// that already exists outside of any tenant may not be claimed by one
+    pub fn set_account_tenant(
+        &self,
+        account_id: AccountId,
+        tenant: Option<&TenantId>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::set_account_tenant")?;
+        match tx.get_account_tenant_claim(&account_id)? {
+            Some(claim) if claim.as_ref() != tenant => {
+                return Err(StateApplicatorError::reject(ERR_ACCOUNT_CLAIMED));
+            },
+            Some(_) => return Ok(ApplicatorReturnType::None),
+            // Accounts created before claims were recorded belong to no tenant
+            None if tx.contains_account(&account_id)? => {
+                if tenant.is_some() {
+                    return Err(StateApplicatorError::reject(ERR_ACCOUNT_CLAIMED));
+                }
+
+                return Ok(ApplicatorReturnType::None);
+            },
+            None => {},
+        }
+
+        tx.set_account_tenant(&account_id, tenant.map(String::as_str))?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Record matched volume against an account's daily total, and the time
+    /// of the account's last match
+    ///
+    /// The day is derived from the proposal's timestamp so that every replica
+    /// buckets the volume identically
+    pub fn record_account_match_volume(
+        &self,
+        account_id: AccountId,
+        volume: Amount,
+        timestamp: u64,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::record_account_match_volume")?;
+        if !tx.contains_account(&account_id)? {
+            return Err(StateApplicatorError::reject("account not found"));
+        }
+        tx.add_account_daily_volume(&account_id, volume_day(timestamp), volume)?;
+        tx.set_account_last_match_time(&account_id, timestamp)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Advance an account's version
+    ///
+    /// Raft orders concurrent proposals, so of two proposals expecting the same
+    /// version only the first is applied
+    pub fn advance_account_version(
+        &self,
+        account_id: AccountId,
+        expected: Option<u64>,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("account_index::advance_account_version")?;
+        let version = tx.get_account_version(&account_id)?;
+        if expected.is_some_and(|expected| expected != version) {
+            return Err(StateApplicatorError::reject(ERR_STALE_ACCOUNT_VERSION));
+        }
+
+        tx.set_account_version(&account_id, version + 1)?;
+        tx.commit()?;
+        Ok(ApplicatorReturnType::None)
+    }
+
     /// Refresh an account's state from the indexer
     pub fn refresh_account(
         &self,
@@ -358,8 +606,10 @@ impl StateApplicator {
         // bus publish can block on a stalled subscriber, and blocking while
         // holding the write tx starves every writer in the process (raft log
         // appends included).
+        let mut sweeps = Vec::new();
         for balance in balances {
             tx.update_balance(&account_id, balance)?;
+            sweeps.extend(apply_sweep_policy(account_id, balance, &tx)?);
         }
 
         // Collect order IDs from the refresh set
@@ -446,7 +696,10 @@ impl StateApplicator {
 
         // Publish the deferred events now that the write tx is released
         for balance in balances {
-            self.publish_admin_balance_update(account_id, balance);
+            self.publish_balance_update(account_id, balance);
+        }
+        for sweep in sweeps {
+            self.publish_sweep_request(account_id, sweep);
         }
         for (order, pool, update_type, matchable_amount) in deferred_order_updates {
             self.publish_admin_order_update(
@@ -512,11 +765,33 @@ impl StateApplicator {
         self.system_bus().publish(ADMIN_ORDER_UPDATES_TOPIC.to_string(), msg);
     }
 
-    /// Publish an admin balance update event to the system bus
-    fn publish_admin_balance_update(&self, account_id: AccountId, balance: &Balance) {
-        let msg =
-            SystemBusMessage::AdminBalanceUpdate { account_id, balance: Box::new(balance.clone()) };
+    /// Publish a balance update event to the admin topic and the account's
+    /// balances topic
+    fn publish_balance_update(&self, account_id: AccountId, balance: &Balance) {
+        let balance = Box::new(balance.clone());
+        let msg = SystemBusMessage::AdminBalanceUpdate { account_id, balance: balance.clone() };
         self.system_bus().publish(ADMIN_BALANCE_UPDATES_TOPIC.to_string(), msg);
+
+        let msg = SystemBusMessage::BalanceUpdate { account_id, balance };
+        self.system_bus().publish(account_balances_topic(&account_id), msg);
+    }
+
+    /// Publish a sweep request to the account's balances topic, for the
+    /// account owner's tooling to sign and submit the withdrawal
+    fn publish_sweep_request(&self, account_id: AccountId, sweep: SweepRequest) {
+        let SweepRequest { mint, amount, cold_address } = sweep;
+        log_task!(
+            Task::AccountIndexUpdate,
+            Outcome::Ok,
+            subject = %account_id,
+            mint = %mint,
+            amount = %amount,
+            "balance sweep requested"
+        );
+
+        let msg =
+            SystemBusMessage::BalanceSweepRequested { account_id, mint, amount, cold_address };
+        self.system_bus().publish(account_balances_topic(&account_id), msg);
     }
 
     /// Publish a per-account fill event to the system bus
@@ -565,7 +840,9 @@ pub(crate) mod test {
         IntentOnlyValidityBundle, ValidityProofLocator, mocks::mock_intent_only_validity_bundle,
     };
 
-    use crate::applicator::test_helpers::mock_applicator;
+    use crate::applicator::{
+        StateApplicator, error::StateApplicatorError, test_helpers::mock_applicator,
+    };
 
     /// Tests adding a new account to the index
     #[test]
@@ -659,6 +936,38 @@ pub(crate) mod test {
         assert!(contains_order);
     }
 
+    /// Tests that an order reusing a client order id of another of the
+    /// account's orders is rejected
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_add_order_to_account__duplicate_client_order_id() {
+        let applicator = mock_applicator();
+        let account = mock_empty_account();
+        applicator.create_account(&account).unwrap();
+
+        let client_order_id = Some("oms-1".to_string());
+        let mut order = mock_order();
+        order.metadata.client_order_id = client_order_id.clone();
+        let mut duplicate = mock_order();
+        duplicate.metadata.client_order_id = client_order_id;
+        let mut other = mock_order();
+        other.metadata.client_order_id = Some("oms-2".to_string());
+
+        let auth = mock_order_auth();
+        let pool = GLOBAL_MATCHING_POOL.to_string();
+        applicator.add_order_to_account(account.id, &order, &auth, pool.clone()).unwrap();
+        let err = applicator
+            .add_order_to_account(account.id, &duplicate, &auth, pool.clone())
+            .unwrap_err();
+        assert!(matches!(err, StateApplicatorError::Rejected(_)));
+        applicator.add_order_to_account(account.id, &other, &auth, pool).unwrap();
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        let orders = tx.get_account_orders(&account.id).unwrap();
+        assert_eq!(orders.len(), 2);
+        assert!(!orders.iter().any(|o| o.id == duplicate.id));
+    }
+
     /// Test removing an order from an account
     #[test]
     fn test_remove_order_from_account() {
@@ -722,9 +1031,10 @@ pub(crate) mod test {
     }
 
     /// Fix A: a settlement update that FULLY consumes an order (amount_in -> 0)
-    /// must REMOVE the order, not leave an amount-0 zombie. A retained zombie is
-    /// un-cancellable (its on-chain nonce is spent) and the quoter rebalance
-    /// re-detects it as a duplicate forever, flooding CancelOrder tasks.
+    /// must REMOVE the order, not leave an amount-0 zombie. A retained zombie
+    /// is un-cancellable (its on-chain nonce is spent) and the quoter
+    /// rebalance re-detects it as a duplicate forever, flooding CancelOrder
+    /// tasks.
     #[test]
     #[allow(non_snake_case)]
     fn test_update_order__full_consume_removes() {
@@ -893,6 +1203,72 @@ pub(crate) mod test {
         assert!(matchable > 1, "unfilled order must refresh up from the stored remaining");
     }
 
+    /// Test that balance updates request sweeps under the account's sweep
+    /// policy, with hysteresis
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_update_account_balance__sweep_policy() {
+        use types_account::sweep::{AccountSweepPolicy, SweepThreshold};
+
+        let applicator = mock_applicator();
+        let account = mock_empty_account();
+        applicator.create_account(&account).unwrap();
+
+        let mut balance = mock_balance();
+        let mint = balance.mint();
+        let threshold = SweepThreshold { mint, trigger_amount: 100, target_amount: 40 };
+        let policy =
+            AccountSweepPolicy { cold_address: Address::ZERO, thresholds: vec![threshold] };
+        applicator.set_account_sweep_policy(account.id, Some(&policy)).unwrap();
+
+        let mut update_and_check = |amount: Amount, expect_pending: bool| {
+            *balance.amount_mut() = amount;
+            applicator.update_account_balance(account.id, &balance).unwrap();
+            let tx = applicator.db().new_read_tx().unwrap();
+            assert_eq!(tx.is_sweep_pending(&account.id, &mint).unwrap(), expect_pending);
+        };
+
+        update_and_check(100, false /* expect_pending */);
+        update_and_check(150, true /* expect_pending */);
+        update_and_check(50, true /* expect_pending */);
+        update_and_check(40, false /* expect_pending */);
+
+        // Replacing the policy clears pending sweeps
+        update_and_check(150, true /* expect_pending */);
+        applicator.set_account_sweep_policy(account.id, Some(&policy)).unwrap();
+        let tx = applicator.db().new_read_tx().unwrap();
+        assert!(!tx.is_sweep_pending(&account.id, &mint).unwrap());
+    }
+
+    /// Test crediting an account balance, with and without an existing balance
+    #[test]
+    fn test_credit_account_balance() {
+        let applicator = mock_applicator();
+        let account = mock_empty_account();
+        applicator.create_account(&account).unwrap();
+
+        let mut credit = mock_balance();
+        *credit.amount_mut() = 100;
+        let (mint, location) = (credit.mint(), credit.location);
+        let read_amount = || {
+            let tx = applicator.db().new_read_tx().unwrap();
+            let balance = tx.get_balance(&account.id, &mint, location).unwrap().unwrap();
+            balance.deserialize().unwrap().amount()
+        };
+
+        // The first credit creates the balance, later credits add to it
+        applicator.credit_account_balance(account.id, &credit).unwrap();
+        assert_eq!(read_amount(), 100);
+        applicator.credit_account_balance(account.id, &credit).unwrap();
+        assert_eq!(read_amount(), 200);
+
+        // A credit that overflows the balance is rejected
+        *credit.amount_mut() = Amount::MAX;
+        let res = applicator.credit_account_balance(account.id, &credit);
+        assert!(res.is_err());
+        assert_eq!(read_amount(), 200);
+    }
+
     /// Test updating an account balance
     #[test]
     fn test_update_account_balance() {
@@ -1058,4 +1434,69 @@ pub(crate) mod test {
         let tx = applicator.db().new_read_tx().unwrap();
         assert_eq!(tx.get_account_by_owner(&owner).unwrap(), None);
     }
+
+    /// Tests that an account claimed by one tenant cannot be claimed by
+    /// another, nor outside of any tenant
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_set_account_tenant__claimed() {
+        let applicator = mock_applicator();
+        let account_id = mock_empty_account().id;
+        let tenant_a = Some("tenant-a".to_string());
+        let tenant_b = Some("tenant-b".to_string());
+
+        applicator.set_account_tenant(account_id, tenant_a.as_ref()).unwrap();
+        applicator.set_account_tenant(account_id, tenant_a.as_ref()).unwrap();
+        assert!(applicator.set_account_tenant(account_id, tenant_b.as_ref()).is_err());
+        assert!(applicator.set_account_tenant(account_id, None).is_err());
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        assert_eq!(tx.get_account_tenant(&account_id).unwrap(), tenant_a);
+    }
+
+    /// Tests that an account claimed outside of any tenant, or created before
+    /// claims were recorded, cannot be claimed by a tenant
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_set_account_tenant__untenanted() {
+        let applicator = mock_applicator();
+        let tenant = Some("tenant-a".to_string());
+
+        let claimed_id = mock_empty_account().id;
+        applicator.set_account_tenant(claimed_id, None).unwrap();
+        assert!(applicator.set_account_tenant(claimed_id, tenant.as_ref()).is_err());
+
+        let account = mock_empty_account();
+        applicator.create_account(&account).unwrap();
+        applicator.set_account_tenant(account.id, None).unwrap();
+        assert!(applicator.set_account_tenant(account.id, tenant.as_ref()).is_err());
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        assert_eq!(tx.get_account_tenant(&account.id).unwrap(), None);
+    }
+
+    /// Tests that an account's version only advances from its current version
+    #[test]
+    fn test_advance_account_version() {
+        let applicator = mock_applicator();
+        let account = mock_empty_account();
+        applicator.create_account(&account).unwrap();
+
+        let version = |applicator: &StateApplicator| {
+            let tx = applicator.db().new_read_tx().unwrap();
+            tx.get_account_version(&account.id).unwrap()
+        };
+        assert_eq!(version(&applicator), 0);
+
+        // Two mutations built against the same version, the second is rejected
+        applicator.advance_account_version(account.id, Some(0)).unwrap();
+        let err = applicator.advance_account_version(account.id, Some(0)).unwrap_err();
+        assert!(matches!(err, StateApplicatorError::Rejected(_)));
+        assert_eq!(version(&applicator), 1);
+
+        // Unversioned mutations advance the version without a check
+        applicator.advance_account_version(account.id, Some(1)).unwrap();
+        applicator.advance_account_version(account.id, None).unwrap();
+        assert_eq!(version(&applicator), 3);
+    }
 }
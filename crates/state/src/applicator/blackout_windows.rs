@@ -0,0 +1,30 @@
+//! Applicator methods for matching blackout windows
+
+use types_core::BlackoutWindow;
+use uuid::Uuid;
+
+use super::{
+    Result, StateApplicator, error::StateApplicatorError, return_type::ApplicatorReturnType,
+};
+
+impl StateApplicator {
+    /// Add a blackout window
+    pub fn add_blackout_window(&self, window: BlackoutWindow) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("blackout_windows::add_blackout_window")?;
+        tx.add_blackout_window(window)?;
+        tx.commit()?;
+
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Remove a blackout window, rejecting the transition if it does not exist
+    pub fn remove_blackout_window(&self, id: Uuid) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("blackout_windows::remove_blackout_window")?;
+        if !tx.remove_blackout_window(id)? {
+            return Err(StateApplicatorError::reject(format!("blackout window {id} not found")));
+        }
+        tx.commit()?;
+
+        Ok(ApplicatorReturnType::None)
+    }
+}
@@ -0,0 +1,49 @@
+//! Applicator methods for repairing the state's indices
+
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::logging::Task;
+
+use super::{Result, StateApplicator, return_type::ApplicatorReturnType};
+
+impl StateApplicator {
+    /// Repair the inconsistencies between the state's tables and indices
+    pub fn repair_state_consistency(&self) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("consistency::repair_state_consistency")?;
+        let issues = tx.repair_consistency()?;
+        tx.commit()?;
+
+        for issue in issues.iter() {
+            log_task!(Task::ConsistencyCheck, Outcome::Ok, issue = %issue, "repaired state inconsistency");
+        }
+        Ok(ApplicatorReturnType::None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_account::{account::mocks::mock_empty_account, order::mocks::mock_order};
+
+    use crate::applicator::test_helpers::mock_applicator;
+
+    /// Tests that applying the repair transition rebuilds a dropped index entry
+    #[test]
+    fn test_repair_state_consistency() {
+        let applicator = mock_applicator();
+        let account = mock_empty_account();
+        let order = mock_order();
+
+        let tx = applicator.db().new_write_tx().unwrap();
+        tx.new_account(&account).unwrap();
+        tx.add_order(&account.id, &order).unwrap();
+        tx.delete_order_index(&order.id).unwrap();
+        tx.commit().unwrap();
+
+        applicator.repair_state_consistency().unwrap();
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        assert!(tx.check_consistency().unwrap().is_empty());
+        assert_eq!(tx.get_account_id_for_order(&order.id).unwrap(), Some(account.id));
+    }
+}
@@ -0,0 +1,29 @@
+//! Applicator methods for runtime feature flags
+
+use std::str::FromStr;
+
+use system_bus::{FEATURE_FLAGS_TOPIC, SystemBusMessage};
+use types_core::FeatureFlag;
+
+use super::{
+    Result, StateApplicator, error::StateApplicatorError, return_type::ApplicatorReturnType,
+};
+
+impl StateApplicator {
+    /// Set the value of a feature flag and notify workers of the change
+    ///
+    /// Flags unknown to this node version are rejected rather than stored
+    pub fn set_feature_flag(&self, flag: &str, enabled: bool) -> Result<ApplicatorReturnType> {
+        let flag = FeatureFlag::from_str(flag).map_err(StateApplicatorError::reject)?;
+
+        let tx = self.db().new_write_tx_with_retry("feature_flags::set_feature_flag")?;
+        tx.set_feature_flag(flag, enabled)?;
+        tx.commit()?;
+
+        self.system_bus().publish(
+            FEATURE_FLAGS_TOPIC.to_string(),
+            SystemBusMessage::FeatureFlagUpdated { flag, enabled },
+        );
+        Ok(ApplicatorReturnType::None)
+    }
+}
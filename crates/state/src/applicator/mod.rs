@@ -18,12 +18,17 @@ use matching_engine_core::MatchingEngine;
 use self::{error::StateApplicatorError, return_type::ApplicatorReturnType};
 
 pub mod account_index;
+pub mod blackout_windows;
+pub mod consistency;
 pub mod error;
+pub mod feature_flags;
 pub mod matching_pools;
 pub mod merkle_proofs;
 pub mod order_book;
 pub mod return_type;
+pub mod settlement_locks;
 pub mod task_queue;
+pub mod token_listings;
 
 // -------------
 // | Constants |
@@ -102,9 +107,33 @@ impl StateApplicator {
             StateTransition::UpdateAccountBalance { account_id, balance } => {
                 self.update_account_balance(account_id, &balance)
             },
+            StateTransition::CreditAccountBalance { account_id, credit } => {
+                self.credit_account_balance(account_id, &credit)
+            },
             StateTransition::UpdateAccountKeychain { account_id, keychain } => {
                 self.update_account_keychain(account_id, &keychain)
             },
+            StateTransition::SetAccountRiskConfig { account_id, config } => {
+                self.set_account_risk_config(account_id, config.as_ref())
+            },
+            StateTransition::SetAccountSweepPolicy { account_id, policy } => {
+                self.set_account_sweep_policy(account_id, policy.as_ref())
+            },
+            StateTransition::SetAccountPinnedPeers { account_id, peers } => {
+                self.set_account_pinned_peers(account_id, peers.as_deref())
+            },
+            StateTransition::SetAccountApiKey { account_id, key_id, secret } => {
+                self.set_account_api_key(account_id, key_id, secret)
+            },
+            StateTransition::SetAccountTenant { account_id, tenant } => {
+                self.set_account_tenant(account_id, tenant.as_ref())
+            },
+            StateTransition::AdvanceAccountVersion { account_id, expected } => {
+                self.advance_account_version(account_id, expected)
+            },
+            StateTransition::RecordAccountMatchVolume { account_id, volume, timestamp } => {
+                self.record_account_match_volume(account_id, volume, timestamp)
+            },
             StateTransition::RefreshAccount { account_id, orders, balances } => {
                 self.refresh_account(account_id, orders, &balances)
             },
@@ -123,19 +152,43 @@ impl StateApplicator {
             StateTransition::SetAccountDefaultMatchingPool { account_id, pool } => {
                 self.set_account_default_matching_pool(account_id, pool.as_deref())
             },
+            StateTransition::AcquireSettlementLock { order_ids, holder, acquired_at } => {
+                self.acquire_settlement_lock(&order_ids, &holder, acquired_at)
+            },
+            StateTransition::ReleaseSettlementLock { order_ids, holder } => {
+                self.release_settlement_lock(&order_ids, &holder)
+            },
             StateTransition::AppendTask { task, executor } => self.append_task(&task, &executor),
+            StateTransition::AppendTasks { tasks, executor } => {
+                self.append_tasks(&tasks, &executor)
+            },
             StateTransition::PopTask { task_id, success } => self.pop_task(task_id, success),
             StateTransition::TransitionTask { task_id, state } => {
                 self.transition_task_state(task_id, state)
             },
             StateTransition::ClearTaskQueue { queue } => self.clear_queue(queue),
+            StateTransition::PauseTaskQueue { queue } => self.pause_queue(queue),
+            StateTransition::ResumeTaskQueue { queue } => self.resume_queue(queue),
             StateTransition::EnqueuePreemptiveTask { keys, task, executor, serial } => {
                 self.enqueue_preemptive_task(&keys, &task, &executor, serial)
             },
-            StateTransition::ReassignTasks { from, to } => self.reassign_tasks(&from, &to),
+            StateTransition::ReassignTasks { from, to } => self.reassign_tasks(&from, &to, &[]),
+            StateTransition::ReassignTasksPinned { from, to, pinned } => {
+                self.reassign_tasks(&from, &to, &pinned)
+            },
+            StateTransition::RecordTaskGasCosts { task_id, task_type, account_ids, costs } => {
+                self.record_task_gas_costs(task_id, &task_type, &account_ids, &costs)
+            },
             StateTransition::AddMerkleProof { proof_type, proof } => {
                 self.add_merkle_proof(proof_type, proof)
             },
+            StateTransition::SetFeatureFlag { flag, enabled } => {
+                self.set_feature_flag(&flag, enabled)
+            },
+            StateTransition::AddBlackoutWindow { window } => self.add_blackout_window(window),
+            StateTransition::RemoveBlackoutWindow { id } => self.remove_blackout_window(id),
+            StateTransition::AddTokenListing { listing } => self.add_token_listing(listing),
+            StateTransition::RepairStateConsistency => self.repair_state_consistency(),
             _ => unimplemented!("Unsupported state transition forwarded to applicator"),
         }
     }
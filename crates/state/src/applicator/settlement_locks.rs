@@ -0,0 +1,126 @@
+//! Applicator methods for cluster-wide settlement locks
+
+use types_account::OrderId;
+use types_gossip::WrappedPeerId;
+
+use crate::storage::tx::settlement_locks::SettlementLock;
+
+use super::{StateApplicator, error::StateApplicatorError, return_type::ApplicatorReturnType};
+
+/// The duration for which a settlement lock is held before it may be taken
+/// over by another peer
+///
+/// Bounds the time an order is blocked if the holder crashes mid-settlement
+pub const SETTLEMENT_LOCK_TTL_MS: u64 = 60_000; // 1 minute
+
+/// The error message emitted when an order is locked by another peer
+pub const ERR_SETTLEMENT_LOCK_HELD: &str = "order is locked for settlement by another peer";
+
+impl StateApplicator {
+    /// Acquire a settlement lock on a set of orders
+    ///
+    /// The lock is all-or-nothing: if any order is held by another peer, no
+    /// locks are written. Expiry is evaluated against the proposal's timestamp
+    /// so that every replica reaches the same decision
+    pub fn acquire_settlement_lock(
+        &self,
+        order_ids: &[OrderId],
+        holder: &WrappedPeerId,
+        acquired_at: u64,
+    ) -> Result<ApplicatorReturnType, StateApplicatorError> {
+        let tx = self.db().new_write_tx_with_retry("settlement_locks::acquire_settlement_lock")?;
+        for order_id in order_ids.iter() {
+            if let Some(lock) = tx.get_settlement_lock(order_id)?
+                && lock.holder != *holder
+                && !lock.is_expired(acquired_at)
+            {
+                return Err(StateApplicatorError::reject(format!(
+                    "{ERR_SETTLEMENT_LOCK_HELD}: {order_id}"
+                )));
+            }
+        }
+
+        let lock =
+            SettlementLock { holder: *holder, expires_at: acquired_at + SETTLEMENT_LOCK_TTL_MS };
+        for order_id in order_ids.iter() {
+            tx.write_settlement_lock(order_id, &lock)?;
+        }
+        tx.commit()?;
+
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Release the settlement locks held by a peer on a set of orders
+    ///
+    /// Locks held by other peers are left in place
+    pub fn release_settlement_lock(
+        &self,
+        order_ids: &[OrderId],
+        holder: &WrappedPeerId,
+    ) -> Result<ApplicatorReturnType, StateApplicatorError> {
+        let tx = self.db().new_write_tx_with_retry("settlement_locks::release_settlement_lock")?;
+        for order_id in order_ids.iter() {
+            if let Some(lock) = tx.get_settlement_lock(order_id)?
+                && lock.holder == *holder
+            {
+                tx.delete_settlement_lock(order_id)?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(ApplicatorReturnType::None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::WrappedPeerId;
+    use uuid::Uuid;
+
+    use crate::applicator::{error::StateApplicatorError, test_helpers::mock_applicator};
+
+    use super::SETTLEMENT_LOCK_TTL_MS;
+
+    /// Tests that a second peer cannot lock an order held by another peer
+    /// until the lock is released
+    #[test]
+    fn test_settlement_lock_contention() {
+        let applicator = mock_applicator();
+        let (peer1, peer2) = (WrappedPeerId::random(), WrappedPeerId::random());
+        let order1 = Uuid::new_v4();
+        let order2 = Uuid::new_v4();
+
+        // The first peer locks the pair, the second peer overlaps on one order
+        applicator.acquire_settlement_lock(&[order1, order2], &peer1, 0).unwrap();
+        let res = applicator.acquire_settlement_lock(&[order2, Uuid::new_v4()], &peer2, 1);
+        assert!(matches!(res, Err(StateApplicatorError::Rejected(_))));
+
+        // Re-acquiring by the holder is allowed
+        applicator.acquire_settlement_lock(&[order1], &peer1, 2).unwrap();
+
+        // A release by a non-holder is a no-op
+        applicator.release_settlement_lock(&[order2], &peer2).unwrap();
+        let res = applicator.acquire_settlement_lock(&[order2], &peer2, 3);
+        assert!(res.is_err());
+
+        // After the holder releases, the second peer may lock the order
+        applicator.release_settlement_lock(&[order1, order2], &peer1).unwrap();
+        applicator.acquire_settlement_lock(&[order2], &peer2, 4).unwrap();
+    }
+
+    /// Tests that an expired lock may be taken over by another peer
+    #[test]
+    fn test_settlement_lock_expiry() {
+        let applicator = mock_applicator();
+        let (peer1, peer2) = (WrappedPeerId::random(), WrappedPeerId::random());
+        let order_id = Uuid::new_v4();
+
+        applicator.acquire_settlement_lock(&[order_id], &peer1, 0).unwrap();
+        let res = applicator.acquire_settlement_lock(&[order_id], &peer2, SETTLEMENT_LOCK_TTL_MS);
+        assert!(res.is_ok());
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        let lock = tx.get_settlement_lock(&order_id).unwrap().unwrap();
+        assert_eq!(lock.holder, peer2);
+    }
+}
@@ -5,11 +5,13 @@ use job_types::{
     task_driver::TaskDriverJob,
 };
 use libmdbx::{RW, TransactionKind};
-use system_bus::{SystemBusMessage, TaskStatus, task_topic};
+use system_bus::{ALL_TASK_UPDATES_TOPIC, SystemBusMessage, TaskStatus, task_topic};
 use tracing::instrument;
+use types_core::AccountId;
 use types_gossip::WrappedPeerId;
 use types_tasks::{
-    ArchivedQueuedTask, HistoricalTask, QueuedTask, QueuedTaskState, TaskIdentifier, TaskQueueKey,
+    ArchivedQueuedTask, ChainSubmission, HistoricalTask, QueuedTask, QueuedTaskState,
+    TaskIdentifier, TaskQueueKey,
 };
 use util::log_task;
 use util::logging::Outcome;
@@ -57,7 +59,14 @@ fn task_to_status(task: &QueuedTask) -> TaskStatus {
         QueuedTaskState::Completed => ("completed".to_string(), None),
         QueuedTaskState::Failed => ("failed".to_string(), None),
     };
-    TaskStatus { id: task.id, status, description }
+    TaskStatus {
+        id: task.id,
+        status,
+        description,
+        state: task.state.clone(),
+        created_at: task.created_at,
+        descriptor: Box::new(task.descriptor.clone()),
+    }
 }
 
 impl StateApplicator {
@@ -88,6 +97,37 @@ impl StateApplicator {
         Ok(ApplicatorReturnType::None)
     }
 
+    /// Apply an `AppendTasks` state transition
+    ///
+    /// The tasks are indexed in a single transaction, so either all of them are
+    /// enqueued or none are
+    #[instrument(skip_all, err, fields(num_tasks = tasks.len()))]
+    pub fn append_tasks(
+        &self,
+        tasks: &[QueuedTask],
+        executor: &WrappedPeerId,
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("task_queue::append_tasks")?;
+
+        // Index the tasks
+        for task in tasks.iter() {
+            tx.enqueue_serial_task(&task.descriptor.queue_key(), task)?;
+            tx.add_assigned_task(executor, &task.id)?;
+        }
+
+        // Run the tasks if possible, only those at the front of their queues start
+        for task in tasks.iter() {
+            let archived_task = tx.get_task(&task.id)?.unwrap();
+            self.maybe_run_task(&archived_task, &tx)?;
+        }
+        tx.commit()?;
+
+        for task in tasks.iter() {
+            self.publish_task_updates(task.descriptor.queue_key(), task);
+        }
+        Ok(ApplicatorReturnType::None)
+    }
+
     /// Apply a `PopTask` state transition
     #[instrument(skip_all, err, fields(task_id = %task_id))]
     pub fn pop_task(&self, task_id: TaskIdentifier, success: bool) -> Result<ApplicatorReturnType> {
@@ -172,6 +212,36 @@ impl StateApplicator {
         Ok(ApplicatorReturnType::None)
     }
 
+    /// Pause a task queue on behalf of an operator
+    #[instrument(skip_all, err, fields(queue_key = %key))]
+    pub fn pause_queue(&self, key: TaskQueueKey) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("task_queue::pause_queue")?;
+        tx.set_queue_paused_by_operator(&key, true)?;
+        tx.commit()?;
+
+        log_task!(Task::TaskQueue, Outcome::Ok, queue_key = %key, "task queue paused by operator");
+        Ok(ApplicatorReturnType::None)
+    }
+
+    /// Resume a task queue paused by an operator, starting its next task if
+    /// one is waiting
+    #[instrument(skip_all, err, fields(queue_key = %key))]
+    pub fn resume_queue(&self, key: TaskQueueKey) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("task_queue::resume_queue")?;
+        tx.set_queue_paused_by_operator(&key, false)?;
+
+        // A task that was already running when the queue was paused is left as is
+        if let Some(task) = tx.next_runnable_task(&key)?
+            && !task.state.is_running()
+        {
+            self.maybe_run_task(&task, &tx)?;
+        }
+        tx.commit()?;
+
+        log_task!(Task::TaskQueue, Outcome::Ok, queue_key = %key, "task queue resumed by operator");
+        Ok(ApplicatorReturnType::None)
+    }
+
     /// Enqueue a preemptive task onto the given task queues
     pub fn enqueue_preemptive_task(
         &self,
@@ -203,14 +273,45 @@ impl StateApplicator {
         Ok(ApplicatorReturnType::None)
     }
 
+    /// Record the gas costs of a task's on-chain submissions
+    ///
+    /// The costs are added to the aggregate totals, and recorded against the
+    /// task so that they outlive its place in the queue
+    #[instrument(skip_all, err, fields(task_id = %task_id, task = %task_type))]
+    pub fn record_task_gas_costs(
+        &self,
+        task_id: TaskIdentifier,
+        task_type: &str,
+        account_ids: &[AccountId],
+        costs: &[ChainSubmission],
+    ) -> Result<ApplicatorReturnType> {
+        let tx = self.db().new_write_tx_with_retry("task_queue::record_task_gas_costs")?;
+        tx.add_gas_costs(task_type, account_ids, costs)?;
+        tx.append_task_gas_costs(&task_id, costs)?;
+        tx.commit()?;
+
+        Ok(ApplicatorReturnType::None)
+    }
+
     /// Reassign all tasks from one peer to another
+    ///
+    /// Tasks in `pinned` are assigned to the paired peer rather than `to`
     pub fn reassign_tasks(
         &self,
         from: &WrappedPeerId,
         to: &WrappedPeerId,
+        pinned: &[(TaskIdentifier, WrappedPeerId)],
     ) -> Result<ApplicatorReturnType> {
         let tx = self.db().new_write_tx_with_retry("task_queue::reassign_tasks")?;
         let reassigned_tasks = tx.reassign_tasks(from, to)?;
+        for (task_id, peer_id) in pinned.iter() {
+            if !reassigned_tasks.contains(task_id) {
+                continue;
+            }
+
+            tx.remove_assigned_task(to, task_id)?;
+            tx.add_assigned_task(peer_id, task_id)?;
+        }
         if !reassigned_tasks.is_empty() {
             log_task!(
                 Task::TaskQueue,
@@ -255,7 +356,7 @@ impl StateApplicator {
     }
 
     /// Publish system bus messages indicating a task has been updated
-    fn publish_task_updates(&self, _key: TaskQueueKey, task: &QueuedTask) {
+    fn publish_task_updates(&self, key: TaskQueueKey, task: &QueuedTask) {
         let task_id = task.id;
 
         // Publish a message for the individual task
@@ -264,6 +365,14 @@ impl StateApplicator {
             let status = task_to_status(task);
             self.system_bus().publish(task_topic, SystemBusMessage::TaskStatusUpdate { status });
         }
+
+        // Publish a message on the all-tasks topic, tagged with the queue
+        let all_tasks_topic = ALL_TASK_UPDATES_TOPIC.to_string();
+        if self.system_bus().has_listeners(&all_tasks_topic) {
+            let status = task_to_status(task);
+            let msg = SystemBusMessage::QueuedTaskStatusUpdate { queue_key: key, status };
+            self.system_bus().publish(all_tasks_topic, msg);
+        }
     }
 
     /// Transition a task into the running state
@@ -1330,6 +1439,37 @@ mod test {
         Ok(())
     }
 
+    /// Tests that a task appended to an operator-paused queue is not started
+    /// until the queue is resumed
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_pause_queue__defers_until_resume() -> Result<()> {
+        let (applicator, task_recv) = setup_mock_applicator_with_driver_queue();
+        let peer_id = get_local_peer_id(&applicator);
+        let task_queue_key = TaskQueueKey::new_v4();
+
+        // Pause the queue, then append a task
+        applicator.pause_queue(task_queue_key)?;
+        let task = mock_queued_task(task_queue_key);
+        applicator.append_task(&task, &peer_id)?;
+
+        let tx = applicator.db().new_read_tx()?;
+        assert!(tx.is_queue_paused_by_operator(&task_queue_key)?);
+        let task_retrieved = tx.get_task(&task.id)?.unwrap();
+        assert!(matches!(task_retrieved.state, ArchivedQueuedTaskState::Queued));
+        assert!(task_recv.is_empty());
+        drop(task_retrieved);
+        tx.commit()?;
+
+        // Resume the queue, the task should now be started
+        applicator.resume_queue(task_queue_key)?;
+        let tx = applicator.db().new_read_tx()?;
+        assert!(!tx.is_queue_paused_by_operator(&task_queue_key)?);
+        let job = task_recv.recv()?;
+        assert_run_task(job, task.id);
+        Ok(())
+    }
+
     /// Tests clearing an empty queue
     #[test]
     #[allow(non_snake_case)]
@@ -1716,7 +1856,7 @@ mod test {
         applicator.append_task(&task, &failed_peer)?;
 
         // Reassign the task
-        applicator.reassign_tasks(&failed_peer, &reassigned_peer)?;
+        applicator.reassign_tasks(&failed_peer, &reassigned_peer, &[])?;
 
         // Ensure the task was reassigned
         let tx = applicator.db().new_read_tx()?;
@@ -1745,7 +1885,7 @@ mod test {
         applicator.append_task(&task, &failed_peer)?;
 
         // Reassign the task
-        applicator.reassign_tasks(&failed_peer, &peer_id)?;
+        applicator.reassign_tasks(&failed_peer, &peer_id, &[])?;
 
         // Ensure the task was reassigned
         let tx = applicator.db().new_read_tx()?;
@@ -1781,7 +1921,7 @@ mod test {
         applicator.append_task(&task2, &failed_peer)?;
 
         // Reassign the task
-        applicator.reassign_tasks(&failed_peer, &local_peer_id)?;
+        applicator.reassign_tasks(&failed_peer, &local_peer_id, &[])?;
 
         // Ensure the first task was not reassigned and the second task was
         let tx = applicator.db().new_read_tx()?;
@@ -1799,4 +1939,45 @@ mod test {
         assert_run_task(task_recv.recv()?, task2.id);
         Ok(())
     }
+
+    /// Tests that pinned tasks are reassigned to their pinned peer rather than
+    /// the reassignment target
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_reassign__pinned_peer() -> Result<()> {
+        let (task_queue, task_recv) = new_task_driver_queue();
+        let applicator = mock_applicator_with_task_queue(task_queue);
+
+        // Set the local peer ID
+        let local_peer_id = WrappedPeerId::random();
+        set_local_peer_id(&local_peer_id, applicator.db());
+
+        // Add two tasks on a failed peer
+        let failed_peer = WrappedPeerId::random();
+        let pinned_peer = WrappedPeerId::random();
+        let pinned_task = mock_queued_task(TaskQueueKey::new_v4());
+        let unpinned_task = mock_queued_task(TaskQueueKey::new_v4());
+        applicator.append_task(&pinned_task, &failed_peer)?;
+        applicator.append_task(&unpinned_task, &failed_peer)?;
+
+        // Reassign the tasks, pinning the first
+        let pinned = [(pinned_task.id, pinned_peer)];
+        applicator.reassign_tasks(&failed_peer, &local_peer_id, &pinned)?;
+
+        let tx = applicator.db().new_read_tx()?;
+        let executor1 = tx.get_task_assignment(&pinned_task.id)?.unwrap().deserialize()?;
+        let executor2 = tx.get_task_assignment(&unpinned_task.id)?.unwrap().deserialize()?;
+        let pinned_assigned = tx.get_assigned_tasks(&pinned_peer)?;
+        let local_assigned = tx.get_assigned_tasks(&local_peer_id)?;
+        tx.commit()?;
+        assert_eq!(executor1, pinned_peer);
+        assert_eq!(executor2, local_peer_id);
+        assert_eq!(pinned_assigned, vec![pinned_task.id]);
+        assert_eq!(local_assigned, vec![unpinned_task.id]);
+
+        // Only the unpinned task should be started locally
+        assert_run_task(task_recv.recv()?, unpinned_task.id);
+        assert!(task_recv.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,72 @@
+//! Applicator methods for tokens listed at runtime
+
+use types_core::TokenListing;
+use util::{log_task, logging::Outcome};
+
+use crate::logging::Task;
+
+use super::{
+    Result, StateApplicator, error::StateApplicatorError, return_type::ApplicatorReturnType,
+};
+
+impl StateApplicator {
+    /// Add a token listing and apply it to the local token mapping
+    ///
+    /// The transition is rejected if the listing conflicts with the token
+    /// mapping, e.g. it remaps an address to another ticker or changes the
+    /// decimals of a mapped address
+    pub fn add_token_listing(&self, listing: TokenListing) -> Result<ApplicatorReturnType> {
+        config::check_token_listing(&listing).map_err(StateApplicatorError::reject)?;
+
+        let tx = self.db().new_write_tx_with_retry("token_listings::add_token_listing")?;
+        tx.add_token_listing(listing.clone())?;
+        tx.commit()?;
+
+        if let Err(e) = config::add_token(&listing) {
+            log_task!(Task::TokenListing, Outcome::Failed, ticker = %listing.ticker, error = %e, "failed to apply token listing");
+        }
+        Ok(ApplicatorReturnType::None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_core::{Chain, Exchange, TokenListing, read_token_remaps};
+
+    use crate::applicator::{error::StateApplicatorError, test_helpers::mock_applicator};
+
+    /// The address of the listed token
+    const ADDR: &str = "0x00000000000000000000000000000000000a11ed";
+
+    /// Build a listing of the test token with the given decimals
+    fn listing(decimals: u8) -> TokenListing {
+        TokenListing {
+            name: "Listed".to_string(),
+            ticker: "LISTED".to_string(),
+            decimals,
+            addresses: vec![(Chain::BaseMainnet, ADDR.to_string())],
+            supported_exchanges: vec![(Exchange::Binance, "LISTED".to_string())],
+            canonical_exchange: Exchange::Binance,
+        }
+    }
+
+    /// Tests that a listing is stored and applied, and that a listing changing
+    /// the token's decimals is rejected
+    #[test]
+    fn test_add_token_listing() {
+        let applicator = mock_applicator();
+        applicator.add_token_listing(listing(18)).unwrap();
+
+        let remaps = read_token_remaps();
+        let ticker = remaps.get(&Chain::BaseMainnet).and_then(|m| m.get_by_left(ADDR));
+        assert_eq!(ticker.map(String::as_str), Some("LISTED"));
+        drop(remaps);
+
+        let res = applicator.add_token_listing(listing(6));
+        assert!(matches!(res, Err(StateApplicatorError::Rejected(_))));
+
+        let tx = applicator.db().new_read_tx().unwrap();
+        assert_eq!(tx.get_token_listings().unwrap(), vec![listing(18)]);
+        tx.commit().unwrap();
+    }
+}
@@ -25,6 +25,9 @@ pub enum StateError {
     /// An error sending a proposal to the replication layer
     #[error("error sending proposal to replication layer: {0}")]
     Proposal(String),
+    /// The raft quorum is unreachable and the node is in read-only safe mode
+    #[error("raft quorum lost, state is read-only")]
+    QuorumLost,
     /// An error in the replication substrate
     #[error("replication error: {0}")]
     Replication(ReplicationError),
@@ -45,6 +48,16 @@ impl StateError {
     pub fn serde<T: ToString>(msg: T) -> Self {
         Self::Serde(msg.to_string())
     }
+
+    /// Whether the error is a rejection of a state transition, either by the
+    /// proposer's dry-apply or by the applicator
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            StateError::TransitionRejected(_)
+                | StateError::Applicator(StateApplicatorError::Rejected(_))
+        )
+    }
 }
 
 impl From<StorageError> for StateError {
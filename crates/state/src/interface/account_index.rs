@@ -6,15 +6,19 @@
 use alloy_primitives::{Address, B256};
 use circuit_types::Amount;
 use types_account::{
-    MatchingPoolName, OrderRefreshData,
+    MatchingPoolName, OrderRefreshData, TenantId,
     account::{Account, OrderId},
     balance::{Balance, BalanceLocation},
     keychain::KeyChain,
-    order::{Order, PrivacyRing},
+    order::{Order, OrderFilter, PrivacyRing},
     order_auth::OrderAuth,
+    risk::{AccountRiskConfig, volume_day},
+    sweep::AccountSweepPolicy,
 };
 use types_core::{AccountId, HmacKey};
-use util::res_some;
+use types_gossip::WrappedPeerId;
+use util::{get_current_time_millis, res_some};
+use uuid::Uuid;
 
 use crate::{
     StateInner, applicator::account_index::update_matchable_amounts, error::StateError,
@@ -36,6 +40,160 @@ impl StateInner {
         .await
     }
 
+    // --- Risk --- //
+
+    /// Get the risk config for an account, if one is set
+    pub async fn get_account_risk_config(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<AccountRiskConfig>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let config = tx.get_account_risk_config(&account_id)?;
+            Ok(config)
+        })
+        .await
+    }
+
+    // --- Sweep --- //
+
+    /// Get the balance sweep policy for an account, if one is set
+    pub async fn get_account_sweep_policy(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<AccountSweepPolicy>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let policy = tx.get_account_sweep_policy(&account_id)?;
+            Ok(policy)
+        })
+        .await
+    }
+
+    // --- Pinned Peers --- //
+
+    /// Get the peers an account has pinned, if any
+    pub async fn get_account_pinned_peers(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<Vec<WrappedPeerId>>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let peers = tx.get_account_pinned_peers(&account_id)?;
+            Ok(peers)
+        })
+        .await
+    }
+
+    // --- API Keys --- //
+
+    /// Get the IDs of the API keys issued for an account
+    pub async fn get_account_api_key_ids(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<Uuid>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let keys = tx.get_account_api_keys(&account_id)?;
+            Ok(keys.into_iter().map(|(id, _)| id).collect())
+        })
+        .await
+    }
+
+    /// Get the secret of an account's API key, if the key exists
+    pub async fn get_account_api_key(
+        &self,
+        account_id: &AccountId,
+        key_id: &Uuid,
+    ) -> Result<Option<HmacKey>, StateError> {
+        let account_id = *account_id;
+        let key_id = *key_id;
+        self.with_read_tx(move |tx| {
+            let keys = tx.get_account_api_keys(&account_id)?;
+            Ok(keys.into_iter().find(|(id, _)| *id == key_id).map(|(_, secret)| secret))
+        })
+        .await
+    }
+
+    // --- Tenants --- //
+
+    /// Get the tenant an account belongs to, if any
+    pub async fn get_account_tenant(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<TenantId>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let tenant = tx.get_account_tenant(&account_id)?;
+            Ok(tenant)
+        })
+        .await
+    }
+
+    /// Get the tenant claim on an account, if the account has been claimed
+    ///
+    /// An account claimed outside of any tenant has a claim of `Some(None)`
+    pub async fn get_account_tenant_claim(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<Option<TenantId>>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let claim = tx.get_account_tenant_claim(&account_id)?;
+            Ok(claim)
+        })
+        .await
+    }
+
+    /// Get the IDs of the accounts that belong to a tenant
+    pub async fn get_tenant_account_ids(&self, tenant: &str) -> Result<Vec<AccountId>, StateError> {
+        let tenant = tenant.to_string();
+        self.with_read_tx(move |tx| {
+            let ids = tx.get_tenant_account_ids(&tenant)?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    /// Get the notional volume an account has matched in the current day
+    pub async fn get_account_daily_volume(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Amount, StateError> {
+        let account_id = *account_id;
+        let day = volume_day(get_current_time_millis());
+        self.with_read_tx(move |tx| {
+            let volume = tx.get_account_daily_volume(&account_id, day)?;
+            Ok(volume)
+        })
+        .await
+    }
+
+    /// Get an account's version, advanced on each mutation enqueued through
+    /// the API
+    pub async fn get_account_version(&self, account_id: &AccountId) -> Result<u64, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let version = tx.get_account_version(&account_id)?;
+            Ok(version)
+        })
+        .await
+    }
+
+    /// Get the time in milliseconds of an account's last match, if it has
+    /// matched
+    pub async fn get_account_last_match_time(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<u64>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let timestamp = tx.get_account_last_match_time(&account_id)?;
+            Ok(timestamp)
+        })
+        .await
+    }
+
     // --- Keychain --- //
 
     /// Get the symmetric key for an account
@@ -211,6 +369,20 @@ impl StateInner {
         .await
     }
 
+    /// Get the orders for an account which match the given filter
+    pub async fn get_account_orders_filtered(
+        &self,
+        account_id: &AccountId,
+        filter: OrderFilter,
+    ) -> Result<Vec<Order>, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| {
+            let orders = tx.get_account_orders(&account_id)?;
+            Ok(orders.into_iter().filter(|order| filter.matches(order)).collect())
+        })
+        .await
+    }
+
     /// Get all orders for an account with their matching pool and matchable
     /// amount
     pub async fn get_account_orders_with_matching_pool(
@@ -440,6 +612,16 @@ impl StateInner {
         self.send_proposal(StateTransition::UpdateAccountBalance { account_id, balance }).await
     }
 
+    /// Credit an amount to an account's balance, creating the balance from the
+    /// given one if the account holds none of its mint and location
+    pub async fn credit_account_balance(
+        &self,
+        account_id: AccountId,
+        credit: types_account::balance::Balance,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::CreditAccountBalance { account_id, credit }).await
+    }
+
     /// Update an account's keychain
     pub async fn update_account_keychain(
         &self,
@@ -449,6 +631,80 @@ impl StateInner {
         self.send_proposal(StateTransition::UpdateAccountKeychain { account_id, keychain }).await
     }
 
+    /// Set or clear the risk config for an account
+    pub async fn set_account_risk_config(
+        &self,
+        account_id: AccountId,
+        config: Option<AccountRiskConfig>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetAccountRiskConfig { account_id, config }).await
+    }
+
+    /// Set or clear the balance sweep policy for an account
+    pub async fn set_account_sweep_policy(
+        &self,
+        account_id: AccountId,
+        policy: Option<AccountSweepPolicy>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetAccountSweepPolicy { account_id, policy }).await
+    }
+
+    /// Set or clear the peers an account has pinned
+    pub async fn set_account_pinned_peers(
+        &self,
+        account_id: AccountId,
+        peers: Option<Vec<WrappedPeerId>>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetAccountPinnedPeers { account_id, peers }).await
+    }
+
+    /// Set or revoke an API key for an account
+    pub async fn set_account_api_key(
+        &self,
+        account_id: AccountId,
+        key_id: Uuid,
+        secret: Option<HmacKey>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetAccountApiKey { account_id, key_id, secret }).await
+    }
+
+    /// Claim an account for a tenant, or outside of any tenant if `tenant` is
+    /// `None`
+    pub async fn set_account_tenant(
+        &self,
+        account_id: AccountId,
+        tenant: Option<TenantId>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetAccountTenant { account_id, tenant }).await
+    }
+
+    /// Advance an account's version
+    ///
+    /// The proposal is rejected if `expected` is given and the account has
+    /// since advanced past it
+    pub async fn advance_account_version(
+        &self,
+        account_id: AccountId,
+        expected: Option<u64>,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::AdvanceAccountVersion { account_id, expected }).await
+    }
+
+    /// Record matched volume against an account's daily volume total
+    pub async fn record_account_match_volume(
+        &self,
+        account_id: AccountId,
+        volume: Amount,
+    ) -> Result<ProposalWaiter, StateError> {
+        let timestamp = get_current_time_millis();
+        self.send_proposal(StateTransition::RecordAccountMatchVolume {
+            account_id,
+            volume,
+            timestamp,
+        })
+        .await
+    }
+
     /// Refresh an account's state with updated orders and balances
     pub async fn refresh_account(
         &self,
@@ -486,8 +742,11 @@ mod test {
     use constants::GLOBAL_MATCHING_POOL;
     use types_account::{
         account::mocks::mock_empty_account, order::mocks::mock_order,
-        order_auth::mocks::mock_order_auth,
+        order_auth::mocks::mock_order_auth, risk::AccountRiskConfig,
     };
+    use types_core::HmacKey;
+    use types_gossip::mocks::mock_peer;
+    use uuid::Uuid;
 
     use crate::test_helpers::mock_state;
 
@@ -557,4 +816,110 @@ mod test {
         let retrieved_account = state.get_account(&account.id).await.unwrap().unwrap();
         assert!(!retrieved_account.orders.contains_key(&order.id));
     }
+
+    /// Test setting an account's risk config and recording matched volume
+    #[tokio::test]
+    async fn test_account_risk_config() {
+        let state = mock_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+        assert!(state.get_account_risk_config(&account.id).await.unwrap().is_none());
+
+        // Set a config
+        let config = AccountRiskConfig { max_daily_volume: Some(100), ..Default::default() };
+        let waiter = state.set_account_risk_config(account.id, Some(config.clone())).await.unwrap();
+        waiter.await.unwrap();
+        let stored = state.get_account_risk_config(&account.id).await.unwrap();
+        assert_eq!(stored, Some(config));
+
+        // Record volume
+        assert!(state.get_account_last_match_time(&account.id).await.unwrap().is_none());
+        state.record_account_match_volume(account.id, 40).await.unwrap().await.unwrap();
+        state.record_account_match_volume(account.id, 2).await.unwrap().await.unwrap();
+        assert_eq!(state.get_account_daily_volume(&account.id).await.unwrap(), 42);
+        assert!(state.get_account_last_match_time(&account.id).await.unwrap().is_some());
+
+        // Clear the config
+        state.set_account_risk_config(account.id, None).await.unwrap().await.unwrap();
+        assert!(state.get_account_risk_config(&account.id).await.unwrap().is_none());
+    }
+
+    /// Test that pinned peers restrict matching ownership of an account's
+    /// orders to the live pinned peers
+    #[tokio::test]
+    async fn test_account_pinned_peers() {
+        let state = mock_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+        assert!(state.get_account_pinned_peers(&account.id).await.unwrap().is_none());
+
+        // Add a live peer to the local cluster and an order to the account
+        let mut peer = mock_peer();
+        peer.cluster_id = state.get_cluster_id().unwrap();
+        state.add_peer(peer.clone()).await.unwrap();
+
+        let order = mock_order();
+        let auth = mock_order_auth();
+        let pool_name = GLOBAL_MATCHING_POOL.to_string();
+        let waiter =
+            state.add_order_to_account(account.id, order.clone(), auth, pool_name).await.unwrap();
+        waiter.await.unwrap();
+
+        // Pinning either peer makes it the owner
+        let local_peer = state.get_peer_id().unwrap();
+        for pinned in [local_peer, peer.peer_id] {
+            let waiter =
+                state.set_account_pinned_peers(account.id, Some(vec![pinned])).await.unwrap();
+            waiter.await.unwrap();
+            assert_eq!(state.get_matching_owner(&order.id).await.unwrap(), pinned);
+        }
+
+        // An empty set clears the pin
+        state.set_account_pinned_peers(account.id, Some(vec![])).await.unwrap().await.unwrap();
+        assert!(state.get_account_pinned_peers(&account.id).await.unwrap().is_none());
+    }
+
+    /// Test issuing, rotating, and revoking an account's API keys
+    #[tokio::test]
+    async fn test_account_api_keys() {
+        let state = mock_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+
+        // Issue a key
+        let key_id = Uuid::new_v4();
+        let secret = HmacKey::random();
+        state.set_account_api_key(account.id, key_id, Some(secret)).await.unwrap().await.unwrap();
+        assert_eq!(state.get_account_api_key_ids(&account.id).await.unwrap(), vec![key_id]);
+        assert_eq!(state.get_account_api_key(&account.id, &key_id).await.unwrap(), Some(secret));
+
+        // Rotate the key
+        let rotated = HmacKey::random();
+        state.set_account_api_key(account.id, key_id, Some(rotated)).await.unwrap().await.unwrap();
+        assert_eq!(state.get_account_api_key_ids(&account.id).await.unwrap(), vec![key_id]);
+        assert_eq!(state.get_account_api_key(&account.id, &key_id).await.unwrap(), Some(rotated));
+
+        // Revoke the key
+        state.set_account_api_key(account.id, key_id, None).await.unwrap().await.unwrap();
+        assert!(state.get_account_api_key_ids(&account.id).await.unwrap().is_empty());
+        assert!(state.get_account_api_key(&account.id, &key_id).await.unwrap().is_none());
+    }
+
+    /// Tests that of two concurrent mutations built against the same account
+    /// version, only one advances the version
+    #[tokio::test]
+    async fn test_advance_account_version() {
+        let state = mock_state().await;
+        let account_id = Uuid::new_v4();
+        assert_eq!(state.get_account_version(&account_id).await.unwrap(), 0);
+
+        let first = state.advance_account_version(account_id, Some(0)).await.unwrap();
+        let second = state.advance_account_version(account_id, Some(0)).await.unwrap();
+        let (first, second) = tokio::join!(first, second);
+        let rejected = [first, second].into_iter().filter_map(Result::err).collect::<Vec<_>>();
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].is_rejection());
+
+        assert_eq!(state.get_account_version(&account_id).await.unwrap(), 1);
+    }
 }
@@ -0,0 +1,50 @@
+//! Interface methods for matching blackout windows
+
+use types_core::BlackoutWindow;
+use uuid::Uuid;
+
+use crate::{
+    StateInner, error::StateError, notifications::ProposalWaiter, state_transition::StateTransition,
+};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Get all blackout windows
+    pub async fn get_blackout_windows(&self) -> Result<Vec<BlackoutWindow>, StateError> {
+        self.with_read_tx(move |tx| {
+            let windows = tx.get_blackout_windows()?;
+            Ok(windows)
+        })
+        .await
+    }
+
+    /// Get a blackout window covering the given time, in milliseconds since
+    /// the epoch, if one does
+    pub async fn get_active_blackout_window(
+        &self,
+        timestamp: u64,
+    ) -> Result<Option<BlackoutWindow>, StateError> {
+        let windows = self.get_blackout_windows().await?;
+        Ok(windows.into_iter().find(|w| w.contains(timestamp)))
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Add a blackout window across the cluster
+    pub async fn add_blackout_window(
+        &self,
+        window: BlackoutWindow,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::AddBlackoutWindow { window }).await
+    }
+
+    /// Remove a blackout window across the cluster
+    pub async fn remove_blackout_window(&self, id: Uuid) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::RemoveBlackoutWindow { id }).await
+    }
+}
@@ -0,0 +1,86 @@
+//! State interface for the chain events listener's checkpoint
+//!
+//! The checkpoint is node-local, so writes bypass raft and go directly to the
+//! local database
+
+use system_bus::{CHAIN_EVENTS_CHECKPOINT_TOPIC, SystemBusMessage};
+
+use crate::{StateInner, error::StateError, storage::tx::chain_events::ChainEventCursor};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Get the cursor of the last chain event the local node fully processed
+    pub async fn get_chain_events_checkpoint(
+        &self,
+    ) -> Result<Option<ChainEventCursor>, StateError> {
+        self.with_read_tx(|tx| Ok(tx.get_chain_events_checkpoint()?)).await
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Advance the chain events checkpoint as the listener processes events
+    pub async fn set_chain_events_checkpoint(
+        &self,
+        cursor: ChainEventCursor,
+    ) -> Result<(), StateError> {
+        self.with_write_tx(move |tx| {
+            tx.set_chain_events_checkpoint(&cursor)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Reset the chain events checkpoint and notify the listener, which
+    /// resumes from the new checkpoint
+    ///
+    /// With a block given, the listener replays events from the start of that
+    /// block; otherwise it only processes events emitted from now on
+    pub async fn reset_chain_events_checkpoint(
+        &self,
+        from_block: Option<u64>,
+    ) -> Result<(), StateError> {
+        self.with_write_tx(move |tx| {
+            match from_block {
+                Some(block) => {
+                    let cursor = ChainEventCursor::end_of_block(block.saturating_sub(1));
+                    tx.set_chain_events_checkpoint(&cursor)?
+                },
+                None => tx.clear_chain_events_checkpoint()?,
+            };
+            Ok(())
+        })
+        .await?;
+
+        // Notify the listener once the new checkpoint is committed
+        self.bus.publish(
+            CHAIN_EVENTS_CHECKPOINT_TOPIC.to_string(),
+            SystemBusMessage::ChainEventsCheckpointReset,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{storage::tx::chain_events::ChainEventCursor, test_helpers::mock_state};
+
+    /// Tests resetting the checkpoint to a block and clearing it
+    #[tokio::test]
+    async fn test_reset_chain_events_checkpoint() {
+        let state = mock_state().await;
+        state.set_chain_events_checkpoint(ChainEventCursor::new(200, 3)).await.unwrap();
+
+        state.reset_chain_events_checkpoint(Some(150)).await.unwrap();
+        let checkpoint = state.get_chain_events_checkpoint().await.unwrap().unwrap();
+        assert!(checkpoint < ChainEventCursor::new(150, 0));
+        assert!(checkpoint > ChainEventCursor::new(149, 1_000));
+
+        state.reset_chain_events_checkpoint(None).await.unwrap();
+        assert_eq!(state.get_chain_events_checkpoint().await.unwrap(), None);
+    }
+}
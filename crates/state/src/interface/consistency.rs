@@ -0,0 +1,80 @@
+//! State interface for checking the consistency of the state's indices
+//!
+//! Repairs are proposed through raft, so that every replica repairs its
+//! indices at the same position in the log
+
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    StateInner,
+    error::StateError,
+    logging::Task,
+    state_transition::StateTransition,
+    storage::{db::DB, tx::consistency::ConsistencyIssue},
+};
+
+impl StateInner {
+    /// Check the invariants between the state's tables and indices,
+    /// optionally repairing the inconsistencies found
+    ///
+    /// Returns the inconsistencies found by the local check, before any repair
+    pub async fn check_consistency(
+        &self,
+        repair: bool,
+    ) -> Result<Vec<ConsistencyIssue>, StateError> {
+        let issues = self.with_read_tx(|tx| Ok(tx.check_consistency()?)).await?;
+        if repair && !issues.is_empty() {
+            self.send_proposal(StateTransition::RepairStateConsistency).await?.await?;
+        }
+
+        Ok(issues)
+    }
+
+    /// Propose a repair of the inconsistencies found by the startup check
+    ///
+    /// The repair cannot be proposed until the raft has started, so it is
+    /// proposed in the background rather than blocking startup on a leader
+    pub(crate) fn spawn_startup_repair(&self) {
+        let state = self.clone();
+        tokio::spawn(async move {
+            match state.check_consistency(true /* repair */).await {
+                Ok(issues) => log_task!(
+                    Task::ConsistencyCheck,
+                    Outcome::Ok,
+                    issues = issues.len(),
+                    "state consistency repair applied"
+                ),
+                Err(e) => log_task!(
+                    Task::ConsistencyCheck,
+                    Outcome::Failed,
+                    error = %e,
+                    "failed to repair state consistency, retry via the admin api"
+                ),
+            }
+        });
+    }
+}
+
+/// Check the consistency of the database at startup, logging each
+/// inconsistency found
+///
+/// Runs before the raft core starts so that no transitions are applied
+/// concurrently with the check. Returns the number of inconsistencies found
+pub(crate) fn check_consistency_at_startup(db: &DB) -> Result<usize, StateError> {
+    log_task!(Task::ConsistencyCheck, Outcome::Started, "checking state consistency");
+    let tx = db.new_read_tx()?;
+    let issues = tx.check_consistency()?;
+    for issue in issues.iter() {
+        log_task!(Task::ConsistencyCheck, Outcome::Partial, issue = %issue, "state inconsistency");
+    }
+
+    let outcome = if issues.is_empty() { Outcome::Ok } else { Outcome::Partial };
+    log_task!(
+        Task::ConsistencyCheck,
+        outcome,
+        issues = issues.len(),
+        "state consistency check complete"
+    );
+    Ok(issues.len())
+}
@@ -0,0 +1,363 @@
+//! State interface for exporting tables to analytics formats
+//!
+//! Each table is read in pages, each page within its own short read
+//! transaction, so that an export does not hold back the reclamation of
+//! database pages for the length of the export. As a result the files written
+//! are not a point-in-time snapshot: entries written while an export runs may
+//! or may not appear in it. Exports are written from the local replica and are
+//! not coordinated through raft
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use arrow_array::{
+    ArrayRef, RecordBatch,
+    builder::{BooleanBuilder, StringBuilder, UInt64Builder},
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use external_api::http::admin::{ExportFormat, ExportTable};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    StateInner,
+    error::StateError,
+    logging::Task,
+    storage::{
+        error::StorageError,
+        tx::export::{ExportColumn, ExportColumnType, ExportSink, ExportValue, export_columns},
+    },
+};
+
+/// The number of rows buffered into each parquet record batch
+const PARQUET_BATCH_SIZE: usize = 8192;
+/// The maximum number of table entries scanned within a single read
+/// transaction
+const EXPORT_PAGE_SIZE: usize = 10_000;
+
+/// A table written to a file by an export
+#[derive(Clone, Debug)]
+pub struct TableExport {
+    /// The table exported
+    pub table: ExportTable,
+    /// The path of the file written
+    pub path: PathBuf,
+    /// The number of rows written
+    pub rows: u64,
+}
+
+impl StateInner {
+    /// Export the given tables into files in `dir`, one file per table
+    pub async fn export_tables(
+        &self,
+        tables: Vec<ExportTable>,
+        format: ExportFormat,
+        dir: PathBuf,
+    ) -> Result<Vec<TableExport>, StateError> {
+        fs::create_dir_all(&dir).map_err(StorageError::other)?;
+
+        let mut exports = Vec::with_capacity(tables.len());
+        for table in tables {
+            let path = dir.join(format!("{table}.{}", format.extension()));
+            let writer = ExportWriter::new(format, &path, export_columns(table))?;
+            let rows = self.export_table(table, writer).await?;
+
+            log_task!(Task::StateExport, Outcome::Ok, subject = %table, rows = rows, path = %path.display(), "exported table");
+            exports.push(TableExport { table, path, rows });
+        }
+
+        Ok(exports)
+    }
+
+    /// Export a table through the given writer, one page per read transaction
+    ///
+    /// Returns the number of rows written
+    async fn export_table(
+        &self,
+        table: ExportTable,
+        mut writer: ExportWriter,
+    ) -> Result<u64, StateError> {
+        let mut rows = 0;
+        let mut after = None;
+        loop {
+            // The writer moves into each transaction's blocking task and back
+            let (returned, page) = self
+                .with_read_tx(move |tx| {
+                    let page = tx.export_table_page(
+                        table,
+                        after.as_deref(),
+                        EXPORT_PAGE_SIZE,
+                        &mut writer,
+                    )?;
+                    Ok((writer, page))
+                })
+                .await?;
+
+            writer = returned;
+            rows += page.rows;
+            after = page.resume_after;
+            if after.is_none() {
+                break;
+            }
+        }
+
+        writer.finish()?;
+        Ok(rows)
+    }
+}
+
+// -----------
+// | Writers |
+// -----------
+
+/// Writes exported rows to a file in the requested format
+enum ExportWriter {
+    /// A CSV writer
+    Csv(csv::Writer<File>),
+    /// A parquet writer, buffering rows into record batches
+    Parquet(ParquetSink),
+}
+
+impl ExportWriter {
+    /// Create the file at `path` and write the table's header
+    fn new(
+        format: ExportFormat,
+        path: &Path,
+        columns: &'static [ExportColumn],
+    ) -> Result<Self, StorageError> {
+        let file = File::create(path).map_err(StorageError::other)?;
+        match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer
+                    .write_record(columns.iter().map(|(name, _)| *name))
+                    .map_err(StorageError::serialization)?;
+                Ok(ExportWriter::Csv(writer))
+            },
+            ExportFormat::Parquet => Ok(ExportWriter::Parquet(ParquetSink::new(file, columns)?)),
+        }
+    }
+
+    /// Flush the remaining rows and close the file
+    fn finish(self) -> Result<(), StorageError> {
+        match self {
+            ExportWriter::Csv(mut writer) => writer.flush().map_err(StorageError::other),
+            ExportWriter::Parquet(sink) => sink.finish(),
+        }
+    }
+}
+
+impl ExportSink for ExportWriter {
+    fn write_row(&mut self, row: Vec<ExportValue>) -> Result<(), StorageError> {
+        match self {
+            ExportWriter::Csv(writer) => writer
+                .write_record(row.into_iter().map(csv_field))
+                .map_err(StorageError::serialization),
+            ExportWriter::Parquet(sink) => sink.write_row(row),
+        }
+    }
+}
+
+/// Format a value as a CSV field, leaving missing values empty
+fn csv_field(value: ExportValue) -> String {
+    match value {
+        ExportValue::String(s) => s,
+        ExportValue::UInt64(n) => n.to_string(),
+        ExportValue::Bool(b) => b.to_string(),
+        ExportValue::Null => String::new(),
+    }
+}
+
+/// Buffers rows into record batches written to a parquet file
+struct ParquetSink {
+    /// The columns of the table
+    columns: &'static [ExportColumn],
+    /// The schema of the table
+    schema: SchemaRef,
+    /// The rows buffered for the next batch
+    buffer: Vec<Vec<ExportValue>>,
+    /// The underlying writer
+    writer: ArrowWriter<File>,
+}
+
+impl ParquetSink {
+    /// Create a sink writing the given columns to the file
+    fn new(file: File, columns: &'static [ExportColumn]) -> Result<Self, StorageError> {
+        let fields: Vec<_> = columns
+            .iter()
+            .map(|(name, ty)| {
+                let data_type = match ty {
+                    ExportColumnType::String => DataType::Utf8,
+                    ExportColumnType::UInt64 => DataType::UInt64,
+                    ExportColumnType::Bool => DataType::Boolean,
+                };
+                Field::new(*name, data_type, true /* nullable */)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
+            .map_err(StorageError::serialization)?;
+        Ok(Self { columns, schema, buffer: Vec::with_capacity(PARQUET_BATCH_SIZE), writer })
+    }
+
+    /// Buffer a row, writing a batch once the buffer is full
+    fn write_row(&mut self, row: Vec<ExportValue>) -> Result<(), StorageError> {
+        self.buffer.push(row);
+        if self.buffer.len() >= PARQUET_BATCH_SIZE {
+            self.flush_batch()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the buffered rows and close the file
+    fn finish(mut self) -> Result<(), StorageError> {
+        self.flush_batch()?;
+        self.writer.close().map_err(StorageError::serialization)?;
+        Ok(())
+    }
+
+    /// Write the buffered rows as a record batch
+    fn flush_batch(&mut self) -> Result<(), StorageError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(&mut self.buffer);
+        let arrays: Vec<ArrayRef> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, ty))| build_column(*ty, rows.iter().map(|row| &row[idx])))
+            .collect();
+
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .map_err(StorageError::serialization)?;
+        self.writer.write(&batch).map_err(StorageError::serialization)
+    }
+}
+
+/// Build an arrow array from a column's values
+///
+/// Values that do not match the column's type are written as nulls
+fn build_column<'a>(
+    ty: ExportColumnType,
+    values: impl Iterator<Item = &'a ExportValue>,
+) -> ArrayRef {
+    match ty {
+        ExportColumnType::String => {
+            let mut builder = StringBuilder::new();
+            for value in values {
+                match value {
+                    ExportValue::String(s) => builder.append_value(s),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        },
+        ExportColumnType::UInt64 => {
+            let mut builder = UInt64Builder::new();
+            for value in values {
+                match value {
+                    ExportValue::UInt64(n) => builder.append_value(*n),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        },
+        ExportColumnType::Bool => {
+            let mut builder = BooleanBuilder::new();
+            for value in values {
+                match value {
+                    ExportValue::Bool(b) => builder.append_value(*b),
+                    _ => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+
+    use arrow_array::{Array, BooleanArray, StringArray, UInt64Array};
+    use external_api::http::admin::ExportFormat;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::{ExportWriter, PARQUET_BATCH_SIZE};
+    use crate::storage::tx::export::{ExportColumn, ExportColumnType, ExportSink, ExportValue};
+
+    /// The columns of the table written in tests
+    const COLUMNS: &[ExportColumn] = &[
+        ("name", ExportColumnType::String),
+        ("count", ExportColumnType::UInt64),
+        ("flag", ExportColumnType::Bool),
+    ];
+
+    /// A row of the test table
+    fn row(name: &str, count: Option<u64>, flag: bool) -> Vec<ExportValue> {
+        vec![name.to_string().into(), count.into(), flag.into()]
+    }
+
+    /// Tests that the CSV writer writes a header, and leaves missing values
+    /// empty
+    #[test]
+    fn test_csv_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("table.csv");
+
+        let mut writer = ExportWriter::new(ExportFormat::Csv, &path, COLUMNS).unwrap();
+        writer.write_row(row("a", Some(1), true)).unwrap();
+        writer.write_row(row("b", None, false)).unwrap();
+        writer.finish().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, "name,count,flag\na,1,true\nb,,false\n");
+    }
+
+    /// Tests that the parquet writer writes every row across several record
+    /// batches, writing missing values as nulls
+    #[test]
+    fn test_parquet_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("table.parquet");
+
+        let n_rows = PARQUET_BATCH_SIZE + 1;
+        let mut writer = ExportWriter::new(ExportFormat::Parquet, &path, COLUMNS).unwrap();
+        for i in 0..n_rows {
+            let count = (i % 2 == 0).then_some(i as u64);
+            writer.write_row(row(&i.to_string(), count, i % 3 == 0)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build();
+        let mut i = 0;
+        for batch in reader {
+            let batch = batch.unwrap();
+            let names = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+            let counts = batch.column(1).as_any().downcast_ref::<UInt64Array>().unwrap();
+            let flags = batch.column(2).as_any().downcast_ref::<BooleanArray>().unwrap();
+
+            for j in 0..batch.num_rows() {
+                assert_eq!(names.value(j), i.to_string());
+                assert_eq!(counts.is_null(j), i % 2 != 0);
+                if !counts.is_null(j) {
+                    assert_eq!(counts.value(j), i as u64);
+                }
+                assert_eq!(flags.value(j), i % 3 == 0);
+                i += 1;
+            }
+        }
+
+        assert_eq!(i, n_rows);
+    }
+}
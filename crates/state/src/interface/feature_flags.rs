@@ -0,0 +1,124 @@
+//! Interface methods for runtime feature flags
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use system_bus::{FEATURE_FLAGS_TOPIC, SystemBusMessage};
+use types_core::FeatureFlag;
+
+use crate::{
+    StateInner, error::StateError, notifications::ProposalWaiter, state_transition::StateTransition,
+};
+
+/// A local view of the feature flags, kept current by the updates published on
+/// `FEATURE_FLAGS_TOPIC`
+///
+/// Lets workers check a flag on their request paths without a state read
+#[derive(Clone, Debug)]
+pub struct FeatureFlagView {
+    /// The value of each flag
+    flags: Arc<RwLock<HashMap<FeatureFlag, bool>>>,
+}
+
+impl FeatureFlagView {
+    /// Whether a feature flag is enabled
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        let flags = self.flags.read().expect("feature flags lock poisoned");
+        flags.get(&flag).copied().unwrap_or_else(|| flag.default_value())
+    }
+}
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Whether a feature flag is enabled
+    pub async fn is_feature_enabled(&self, flag: FeatureFlag) -> Result<bool, StateError> {
+        self.with_read_tx(move |tx| {
+            let enabled = tx.get_feature_flag(flag)?;
+            Ok(enabled)
+        })
+        .await
+    }
+
+    /// Get the values of all feature flags
+    pub async fn get_feature_flags(&self) -> Result<Vec<(FeatureFlag, bool)>, StateError> {
+        self.with_read_tx(move |tx| {
+            let flags = tx.get_all_feature_flags()?;
+            Ok(flags)
+        })
+        .await
+    }
+
+    /// Watch the feature flags, returning a view that follows their updates
+    ///
+    /// The view subscribes to updates before reading the current values, so an
+    /// update applied in between is not missed. The update task exits once all
+    /// clones of the view are dropped
+    pub async fn watch_feature_flags(&self) -> Result<FeatureFlagView, StateError> {
+        let mut updates = self.bus.subscribe(FEATURE_FLAGS_TOPIC.to_string());
+        let flags = self.get_feature_flags().await?.into_iter().collect();
+        let view = FeatureFlagView { flags: Arc::new(RwLock::new(flags)) };
+
+        let watched = Arc::downgrade(&view.flags);
+        tokio::spawn(async move {
+            loop {
+                let msg = updates.next_message().await;
+                let Some(flags) = watched.upgrade() else { break };
+                if let SystemBusMessage::FeatureFlagUpdated { flag, enabled } = msg {
+                    flags.write().expect("feature flags lock poisoned").insert(flag, enabled);
+                }
+            }
+        });
+
+        Ok(view)
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Set the value of a feature flag across the cluster
+    pub async fn set_feature_flag(
+        &self,
+        flag: FeatureFlag,
+        enabled: bool,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::SetFeatureFlag { flag: flag.to_string(), enabled })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use types_core::FeatureFlag;
+
+    use crate::test_helpers::mock_state;
+
+    /// Tests that a feature flag view follows flag updates
+    #[tokio::test]
+    async fn test_watch_feature_flags() {
+        let state = mock_state().await;
+        let flag = FeatureFlag::ExternalMatches;
+        let view = state.watch_feature_flags().await.unwrap();
+        assert_eq!(view.is_enabled(flag), flag.default_value());
+
+        state.set_feature_flag(flag, false).await.unwrap().await.unwrap();
+        for _ in 0..100 {
+            if !view.is_enabled(flag) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!view.is_enabled(flag));
+
+        // A view created after the update starts from the stored value
+        let view = state.watch_feature_flags().await.unwrap();
+        assert!(!view.is_enabled(flag));
+    }
+}
@@ -0,0 +1,78 @@
+//! State interface for the local node's match attempt audit trail
+//!
+//! The audit trail is node-local, so writes bypass raft and go directly to the
+//! local database
+
+use types_account::OrderId;
+
+use crate::{StateInner, error::StateError, storage::tx::match_audit::MatchAttempt};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Get up to `limit` of the most recent match attempts, optionally
+    /// filtered to those involving the given order
+    pub async fn get_match_attempts(
+        &self,
+        order_id: Option<OrderId>,
+        limit: usize,
+    ) -> Result<Vec<MatchAttempt>, StateError> {
+        self.with_read_tx(move |tx| {
+            let attempts = tx.get_match_attempts()?;
+            let attempts = attempts
+                .into_iter()
+                .filter(|attempt| order_id.as_ref().is_none_or(|id| attempt.involves(id)))
+                .take(limit)
+                .collect();
+            Ok(attempts)
+        })
+        .await
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Record a match attempt in the local audit trail
+    pub async fn record_match_attempt(&self, attempt: MatchAttempt) -> Result<(), StateError> {
+        self.with_write_tx(move |tx| {
+            tx.append_match_attempt(&attempt)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{storage::tx::match_audit::MatchAttempt, test_helpers::mock_state};
+
+    /// Tests filtering the audit trail by order
+    #[tokio::test]
+    async fn test_get_match_attempts_by_order() {
+        let state = mock_state().await;
+        let peer_id = state.get_peer_id().unwrap();
+        let (order1, order2) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut attempt1 = MatchAttempt::new(peer_id, order1, 0);
+        attempt1.other_order_id = Some(order2);
+        let attempt2 = MatchAttempt::new(peer_id, order2, 1);
+        let attempt3 = MatchAttempt::new(peer_id, Uuid::new_v4(), 2);
+        for attempt in [attempt1.clone(), attempt2.clone(), attempt3] {
+            state.record_match_attempt(attempt).await.unwrap();
+        }
+
+        let all = state.get_match_attempts(None, 10).await.unwrap();
+        assert_eq!(all.len(), 3);
+
+        let for_order2 = state.get_match_attempts(Some(order2), 10).await.unwrap();
+        assert_eq!(for_order2, vec![attempt2, attempt1]);
+
+        let limited = state.get_match_attempts(None, 1).await.unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+}
@@ -2,16 +2,29 @@
 //! proposing state transitions and reading from state
 
 pub mod account_index;
+pub mod blackout_windows;
+pub mod chain_events;
+mod consistency;
+pub mod export;
+pub mod feature_flags;
+pub mod match_audit;
 pub mod matching_pools;
 pub mod merkle_proofs;
 pub mod node_metadata;
 pub mod order_book;
 pub mod peer_index;
 mod peer_metrics;
+pub mod price_history;
 pub mod proofs;
+pub mod proposal_batcher;
+mod proposal_validation;
 pub mod raft;
 mod raft_metrics;
+mod safe_mode;
+pub mod settlement_locks;
 pub mod task_queue;
+mod task_queue_metrics;
+pub mod token_listings;
 
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
@@ -51,6 +64,8 @@ use crate::{
     },
 };
 use matching_engine_core::MatchingEngine;
+use proposal_batcher::ProposalBatcher;
+pub use safe_mode::SafeMode;
 
 use crate::error::StateError;
 
@@ -65,15 +80,15 @@ const DEFAULT_MAX_ELECTION_MS: u64 = 15_000; // 15 seconds
 const PANIC_CHECK_MS: u64 = 10_000; // 10 seconds
 /// The frequency with which to check for missed expiry
 const MEMBERSHIP_SYNC_INTERVAL_MS: u64 = 10_000; // 10 seconds
-/// How often to emit the periodic raft-health gauge log. Throttled so it does not
-/// dominate the logs; the membership-sync tick itself still runs every
+/// How often to emit the periodic raft-health gauge log. Throttled so it does
+/// not dominate the logs; the membership-sync tick itself still runs every
 /// `MEMBERSHIP_SYNC_INTERVAL_MS`.
 const RAFT_HEALTH_LOG_INTERVAL_MS: u64 = 60_000; // 60 seconds
 /// The maximum time a single membership-sync tick may run before it is
 /// abandoned. The clock schedules these callbacks sequentially, so a tick that
-/// hangs (e.g. on a wedged membership change) stops ALL future ticks and freezes
-/// membership reconciliation. Bounding each tick guarantees the timer keeps
-/// running and surfaces the hang instead of silently wedging.
+/// hangs (e.g. on a wedged membership change) stops ALL future ticks and
+/// freezes membership reconciliation. Bounding each tick guarantees the timer
+/// keeps running and surfaces the hang instead of silently wedging.
 const MEMBERSHIP_SYNC_TIMEOUT_MS: u64 = 20_000; // 20 seconds
 /// The frequency with which to self-heal orphaned serial-preemption queues
 const ORPHAN_SELFHEAL_INTERVAL_MS: u64 = 15_000; // 15 seconds
@@ -123,6 +138,9 @@ pub async fn create_global_state(
         failure_send,
     )
     .await?;
+
+    // Re-apply the tokens listed at runtime on top of the token mapping
+    state.apply_token_listings().await?;
     Ok(Arc::new(state))
 }
 
@@ -143,6 +161,9 @@ pub struct StateConfig {
     pub(crate) default_relayer_fee: FixedPoint,
     /// Per-asset relayer fee overrides (static boot config), keyed by ticker.
     pub(crate) per_asset_fees: HashMap<String, FixedPoint>,
+    /// Whether to dry-apply transitions against the local state before
+    /// proposing them
+    pub(crate) validate_proposals: bool,
 }
 
 impl StateConfig {
@@ -161,6 +182,7 @@ impl StateConfig {
             relayer_fee_addr: relayer_config.relayer_fee_addr,
             default_relayer_fee: relayer_config.default_match_fee,
             per_asset_fees,
+            validate_proposals: relayer_config.validate_proposals,
         }
     }
 }
@@ -181,6 +203,10 @@ pub struct StateInner {
     pub(crate) notifications: OpenNotifications,
     /// The raft client
     pub(crate) raft: RaftClient,
+    /// The batcher coalescing batchable proposals into shared raft entries
+    pub(crate) proposal_batcher: ProposalBatcher,
+    /// Whether the node is in read-only safe mode after losing the raft quorum
+    pub(crate) safe_mode: SafeMode,
 }
 
 /// The inner state struct, wrapped in an `Arc` to allow for efficient clones
@@ -202,6 +228,10 @@ pub struct StateInner {
     pub notifications: OpenNotifications,
     /// The raft client
     pub raft: RaftClient,
+    /// The batcher coalescing batchable proposals into shared raft entries
+    pub proposal_batcher: ProposalBatcher,
+    /// Whether the node is in read-only safe mode after losing the raft quorum
+    pub safe_mode: SafeMode,
 }
 
 impl StateInner {
@@ -297,6 +327,47 @@ impl StateInner {
         let sm = StateMachine::new(sm_config, notifications.clone(), applicator).await?;
         let recovered_from_snapshot = sm.recovered_from_snapshot;
 
+        // Rewrite orders and peers stored in an earlier layout, before anything
+        // reads them
+        let tx = db.new_write_tx()?;
+        let n_migrated = tx.migrate_legacy_orders()?;
+        let n_peers_migrated = tx.migrate_legacy_peers()?;
+        tx.commit()?;
+        if n_migrated > 0 {
+            log_task!(
+                Task::NodeSetup,
+                Outcome::Ok,
+                n_orders = n_migrated,
+                "migrated orders to the current layout"
+            );
+        }
+        if n_peers_migrated > 0 {
+            log_task!(
+                Task::NodeSetup,
+                Outcome::Ok,
+                n_peers = n_peers_migrated,
+                "migrated peers to the current layout"
+            );
+        }
+
+        // Index orders written before the order state index was introduced
+        let tx = db.new_write_tx()?;
+        let n_indexed = tx.backfill_order_state_index()?;
+        tx.commit()?;
+        if n_indexed > 0 {
+            log_task!(
+                Task::NodeSetup,
+                Outcome::Ok,
+                n_orders = n_indexed,
+                "backfilled order state index"
+            );
+        }
+
+        // Cross-check the restored state's indices before raft begins applying
+        // transitions on top of it
+        let n_issues =
+            if relayer_config.fsck { consistency::check_consistency_at_startup(&db)? } else { 0 };
+
         // Start a raft
         let raft = RaftClient::new(raft_config, db.clone(), network, sm)
             .await
@@ -305,13 +376,30 @@ impl StateInner {
         // Setup the node metadata from the config
         let mut config = StateConfig::new(relayer_config);
         config.recovered_from_snapshot = recovered_from_snapshot;
-        let this = Self { config, matching_engine, db, bus: system_bus, notifications, raft };
+        let proposal_batcher = ProposalBatcher::new(raft.clone(), notifications.clone());
+        let this = Self {
+            config,
+            matching_engine,
+            db,
+            bus: system_bus,
+            notifications,
+            raft,
+            proposal_batcher,
+            safe_mode: SafeMode::default(),
+        };
         this.setup_node_metadata(relayer_config).await?;
         this.setup_core_panic_timer(system_clock, failure_send).await?;
         this.setup_membership_sync_timer(system_clock).await?;
         this.setup_orphaned_queue_selfheal_timer(system_clock).await?;
         this.setup_raft_metrics_timer(system_clock).await?;
+        this.setup_quorum_watch_timer(system_clock).await?;
         this.setup_peer_metrics_timer(system_clock).await?;
+        this.setup_task_queue_metrics_timer(system_clock).await?;
+
+        // Repairs are replicated, so they wait for the raft to be running
+        if relayer_config.fsck_repair && n_issues > 0 {
+            this.spawn_startup_repair();
+        }
 
         Ok(this)
     }
@@ -457,12 +545,12 @@ impl StateInner {
     /// then the node assigned as its executor stops driving it (worker churn,
     /// scale-down, or the seed regenerating its p2p identity on restart). The
     /// head's executor no longer matches any live peer, so no `Run` job is ever
-    /// re-dispatched; the queue stays `SerialPreemptionQueued` forever and every
-    /// further settle is rejected (`deferred-queue full`, 0 fills). The startup
-    /// self-heal (`clear_orphaned_preempted_queues`) only runs once, so orphans
-    /// formed during steady-state operation persist until the next reboot. This
-    /// timer runs the same idempotent, leader-gated clear on a cadence so the
-    /// wedge is cleared continuously.
+    /// re-dispatched; the queue stays `SerialPreemptionQueued` forever and
+    /// every further settle is rejected (`deferred-queue full`, 0 fills).
+    /// The startup self-heal (`clear_orphaned_preempted_queues`) only runs
+    /// once, so orphans formed during steady-state operation persist until
+    /// the next reboot. This timer runs the same idempotent, leader-gated
+    /// clear on a cadence so the wedge is cleared continuously.
     async fn setup_orphaned_queue_selfheal_timer(
         &self,
         clock: &SystemClock,
@@ -621,14 +709,31 @@ impl StateInner {
     }
 
     /// Send a proposal to the raft node
+    ///
+    /// Batchable transitions are handed to the proposal batcher to be
+    /// coalesced with others into a single raft entry; the pending batch is
+    /// flushed before any other transition is proposed, so that transitions
+    /// enter the log in the order they were sent. Proposals are refused
+    /// while the node is in safe mode, as they could not be committed, and
+    /// optionally rejected up front if a dry-apply shows they could not apply
     pub(crate) async fn send_proposal(
         &self,
         transition: StateTransition,
     ) -> Result<ProposalWaiter, StateError> {
+        if self.is_safe_mode() {
+            return Err(StateError::QuorumLost);
+        }
+        self.validate_proposal(&transition).await?;
+
         let proposal = Proposal::from(transition);
         let recv = self.notifications.register_notification(proposal.id).await;
 
-        self.raft.propose_transition(proposal).await.map_err(StateError::Replication)?;
+        if proposal.transition.is_batchable() {
+            self.proposal_batcher.enqueue(proposal)?;
+        } else {
+            self.proposal_batcher.flush().await?;
+            self.raft.propose_transition(proposal).await.map_err(StateError::Replication)?;
+        }
         Ok(ProposalWaiter::new(recv))
     }
 }
@@ -5,7 +5,7 @@ use alloy_primitives::Address;
 use circuit_types::fixed_point::FixedPoint;
 use config::RelayerConfig;
 use libp2p::{core::Multiaddr, identity::Keypair};
-use types_gossip::{ClusterId, PeerInfo, WrappedPeerId};
+use types_gossip::{ClusterId, PeerInfo, PeerMetadata, WrappedPeerId};
 use util::log_task;
 use util::logging::Outcome;
 
@@ -91,6 +91,7 @@ impl StateInner {
     pub async fn set_local_peer_info(&self, mut info: PeerInfo) -> Result<(), StateError> {
         self.with_write_tx(move |tx| {
             info.successful_heartbeat();
+            info.set_metadata(PeerMetadata::local());
             tx.write_peer(&info)?;
             tx.add_to_cluster(&info.peer_id, &info.cluster_id)?;
             Ok(())
@@ -5,6 +5,8 @@
 //! of unconditional writes only and inconsistent state is okay between cluster
 //! peers
 
+use std::collections::HashMap;
+
 use circuit_types::{Amount, Nullifier};
 use libmdbx::TransactionKind;
 use rand::{
@@ -13,7 +15,7 @@ use rand::{
     thread_rng,
 };
 use types_account::{OrderId, order::Order, pair::Pair};
-use types_gossip::{ClusterId, WrappedPeerId, network_order::NetworkOrder};
+use types_gossip::{ClusterId, PeerLatencies, WrappedPeerId, network_order::NetworkOrder};
 use util::res_some;
 
 use crate::{
@@ -30,6 +32,20 @@ use crate::{
 /// directly
 const ERR_LOCAL_ORDER: &str = "local order should be updated through a wallet update";
 
+/// A group of network orders sharing a pair and a state
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderBookGroup {
+    /// The pair of the orders, with the base token as the input token
+    ///
+    /// `None` for orders whose pair is not known to the local node, i.e. those
+    /// managed by remote clusters
+    pub pair: Option<Pair>,
+    /// The name of the orders' state
+    pub state: String,
+    /// The number of orders in the group
+    pub num_orders: usize,
+}
+
 impl StateInner {
     // -----------
     // | Getters |
@@ -124,6 +140,80 @@ impl StateInner {
         .await
     }
 
+    /// Get all known orders in the book along with the tombstones of removed
+    /// orders, read in a single transaction
+    ///
+    /// Warning: like `get_all_orders`, this can be very slow when the state
+    /// has a medium to large number of orders
+    pub async fn get_order_book_versions(
+        &self,
+    ) -> Result<(Vec<NetworkOrder>, Vec<NetworkOrder>), StateError> {
+        self.with_read_tx(move |tx| {
+            let orders = tx
+                .get_all_orders()?
+                .into_iter()
+                .map(|o| o.deserialize())
+                .collect::<Result<Vec<_>, _>>()?;
+            let tombstones = tx
+                .get_order_tombstones()?
+                .into_iter()
+                .map(|o| o.deserialize())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok((orders, tombstones))
+        })
+        .await
+    }
+
+    /// Whether the order version with the given nullifier has been removed
+    /// from the book
+    pub async fn is_order_tombstoned(&self, nullifier: Nullifier) -> Result<bool, StateError> {
+        self.with_read_tx(move |tx| Ok(tx.is_order_tombstoned(nullifier)?)).await
+    }
+
+    /// Get a snapshot of the order book, grouped by pair and order state
+    ///
+    /// Only locally managed orders are read to resolve their pair; remote
+    /// orders do not disclose it, so they are counted from the state index
+    /// alone
+    pub async fn get_order_book_snapshot(&self) -> Result<Vec<OrderBookGroup>, StateError> {
+        self.with_read_tx(move |tx| {
+            let local_orders = match tx.get_local_orders()? {
+                Some(set) => set.deserialize()?,
+                None => Default::default(),
+            };
+
+            let mut groups: HashMap<(Option<Pair>, String), usize> = HashMap::new();
+            for (state, order_ids) in tx.get_order_ids_by_state()? {
+                for order_id in order_ids.iter() {
+                    if !local_orders.contains(order_id) {
+                        *groups.entry((None, state.clone())).or_default() += 1;
+                        continue;
+                    }
+
+                    // Group both sides of a market under its base/quote pair
+                    let pair = match tx.get_order(order_id)? {
+                        Some(order) => {
+                            let pair = Order::from_archived(&order)?.pair();
+                            let base = pair.base_token().get_alloy_address();
+                            let quote = pair.quote_token().get_alloy_address();
+                            Some(Pair::new(base, quote))
+                        },
+                        None => None,
+                    };
+                    *groups.entry((pair, state.clone())).or_default() += 1;
+                }
+            }
+
+            let snapshot = groups
+                .into_iter()
+                .map(|((pair, state), num_orders)| OrderBookGroup { pair, state, num_orders })
+                .collect();
+            Ok(snapshot)
+        })
+        .await
+    }
+
     /// Get the matchable amount for both sides of a pair
     ///
     /// Returns (buy_amount, sell_amount) where buy amount is denominated in the
@@ -154,12 +244,17 @@ impl StateInner {
 
     // --- Match --- //
 
-    /// Sample a peer in the cluster managing an order
+    /// Choose a peer in the cluster managing an order
+    ///
+    /// The peer with the lowest measured round trip time is preferred; a random
+    /// peer is sampled if none of the cluster's peers has been measured
     pub async fn get_peer_managing_order(
         &self,
         order_id: &OrderId,
+        latencies: &PeerLatencies,
     ) -> Result<Option<WrappedPeerId>, StateError> {
         let oid = *order_id;
+        let latencies = latencies.clone();
         self.with_read_tx(move |tx| {
             // Get the cluster ID managing the order
             let order = res_some!(tx.get_order_info(&oid)?);
@@ -167,24 +262,36 @@ impl StateInner {
 
             // Get the peers in the cluster
             let peers = res_some!(tx.get_cluster_peers(&cluster)?);
-            let n_peers = peers.len();
-            if n_peers == 0 {
+            let peers =
+                peers.iter().map(WrappedPeerId::from_archived).collect::<Result<Vec<_>, _>>()?;
+            if peers.is_empty() {
                 return Ok(None);
             }
 
-            // Choose a random peer from the cluster
-            let peer_idx = thread_rng().gen_range(0..n_peers);
-            let peer = peers.iter().nth(peer_idx).unwrap(); // safe because bounds are sampled
-            Ok(Some(WrappedPeerId::from_archived(peer)?))
+            // Prefer the nearest peer, otherwise choose a random peer from the cluster
+            if let Some(peer) = latencies.fastest(&peers) {
+                return Ok(Some(peer));
+            }
+
+            let peer_idx = thread_rng().gen_range(0..peers.len());
+            Ok(Some(peers[peer_idx]))
         })
         .await
     }
 
     /// Choose an order to handshake with according to their priorities
     ///
+    /// Each order's priority is scaled down by the round trip time to the
+    /// nearest peer of its cluster, so that low latency counterparties are
+    /// preferred among orders of otherwise equal priority
+    ///
     /// TODO(@joeykraut): Optimize this method when implementing multi-cluster
-    pub async fn choose_handshake_order(&self) -> Result<Option<OrderId>, StateError> {
-        self.with_read_tx(|tx| {
+    pub async fn choose_handshake_order(
+        &self,
+        latencies: &PeerLatencies,
+    ) -> Result<Option<OrderId>, StateError> {
+        let latencies = latencies.clone();
+        self.with_read_tx(move |tx| {
             // Get all orders and filter by those that are not managed internally and ready
             // for match
             let mut all_orders = tx.get_all_orders()?;
@@ -192,11 +299,19 @@ impl StateInner {
             let my_cluster = tx.get_cluster_id()?;
             all_orders.retain(|o| o.cluster != my_cluster && o.ready_for_match());
 
-            // Get the priorities of each order
+            // Get the priorities of each order, weighted by the latency to its cluster
             let mut priorities = Vec::with_capacity(all_orders.len());
-            for order in all_orders.iter().map(|o| o.id) {
-                let priority = tx.get_order_priority(&order)?;
-                priorities.push(priority.get_effective_priority());
+            for order in all_orders.iter() {
+                let priority = tx.get_order_priority(&order.id)?.get_effective_priority();
+                let cluster = ClusterId::from_archived(&order.cluster)?;
+                let cluster_peers = match tx.get_cluster_peers(&cluster)? {
+                    Some(peers) => peers
+                        .iter()
+                        .map(WrappedPeerId::from_archived)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => Vec::new(),
+                };
+                priorities.push(latencies.scale_weight(f64::from(priority), &cluster_peers));
             }
 
             // Sample a random priority-weighted order from the result
@@ -297,11 +412,16 @@ impl StateInner {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
 
-    use types_gossip::network_order::test_helpers::dummy_network_order;
+    use types_gossip::{
+        PeerLatencies, mocks::mock_peer, network_order::test_helpers::dummy_network_order,
+    };
 
     use crate::test_helpers::mock_state;
 
+    use super::OrderBookGroup;
+
     /// Test adding an order to the state
     #[tokio::test]
     async fn test_add_order() {
@@ -360,6 +480,21 @@ mod test {
         assert_eq!(missing, expected);
     }
 
+    /// Tests grouping the order book by pair and state
+    #[tokio::test]
+    async fn test_order_book_snapshot() {
+        let state = mock_state().await;
+
+        // Remote orders do not disclose their pair
+        for _ in 0..3 {
+            state.add_order(dummy_network_order()).await.unwrap();
+        }
+
+        let snapshot = state.get_order_book_snapshot().await.unwrap();
+        let expected = OrderBookGroup { pair: None, state: "Received".to_string(), num_orders: 3 };
+        assert_eq!(snapshot, vec![expected]);
+    }
+
     /// Tests nullifying an order
     #[tokio::test]
     async fn test_nullify_order() {
@@ -375,7 +510,39 @@ mod test {
         // Nullify the order
         state.nullify_orders(order.nullifier).await.unwrap();
 
-        // Check for the order in the state
+        // Check for the order in the state, and for its tombstone
         assert!(state.get_network_order(&order.id).await.unwrap().is_none());
+        assert!(state.is_order_tombstoned(order.nullifier).await.unwrap());
+
+        let (orders, tombstones) = state.get_order_book_versions().await.unwrap();
+        assert!(orders.is_empty());
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].id, order.id);
+    }
+
+    /// Tests that the peer chosen to handshake on an order is the nearest
+    /// measured peer of its cluster
+    #[tokio::test]
+    async fn test_get_peer_managing_order() {
+        let state = mock_state().await;
+        let latencies = PeerLatencies::new();
+
+        let order = dummy_network_order();
+        state.add_order(order.clone()).await.unwrap();
+
+        let (mut near, mut far) = (mock_peer(), mock_peer());
+        near.cluster_id = order.cluster.clone();
+        far.cluster_id = order.cluster.clone();
+        state.add_peer(near.clone()).await.unwrap();
+        state.add_peer(far.clone()).await.unwrap();
+
+        // With no measurements, either peer may be chosen
+        let peer = state.get_peer_managing_order(&order.id, &latencies).await.unwrap().unwrap();
+        assert!(peer == near.peer_id || peer == far.peer_id);
+
+        latencies.record_sample(near.peer_id, Duration::from_millis(10));
+        latencies.record_sample(far.peer_id, Duration::from_millis(100));
+        let peer = state.get_peer_managing_order(&order.id, &latencies).await.unwrap();
+        assert_eq!(peer, Some(near.peer_id));
     }
 }
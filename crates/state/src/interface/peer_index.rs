@@ -7,10 +7,14 @@
 
 use std::collections::HashMap;
 
+use alloy_primitives::{B256, keccak256};
 use gossip_api::request_response::heartbeat::HeartbeatMessage;
 use itertools::Itertools;
+use libmdbx::TransactionKind;
 use system_bus::{NETWORK_TOPOLOGY_TOPIC, SystemBusMessage};
-use types_gossip::{ClusterId, PeerInfo, WrappedPeerId};
+use types_account::OrderId;
+use types_gossip::{ClusterId, PeerInfo, PeerMetadata, WrappedPeerId};
+use util::get_current_time_millis;
 use util::log_task;
 use util::logging::Outcome;
 use util::res_some;
@@ -20,9 +24,15 @@ use crate::{
     error::StateError,
     logging::Task,
     replication::{RaftNode, get_raft_id},
-    storage::traits::RkyvValue,
+    storage::{error::StorageError, traits::RkyvValue, tx::StateTxn},
 };
 
+/// The amount of time after which a cluster peer without a successful
+/// heartbeat is no longer considered a candidate for matching ownership
+///
+/// Mirrors the cluster heartbeat failure window used by the gossip server
+const MATCHING_OWNER_LIVENESS_MS: u64 = 15_000; // 15 seconds
+
 impl StateInner {
     // -----------
     // | Getters |
@@ -135,11 +145,63 @@ impl StateInner {
             let known_peers =
                 peers.into_keys().filter(|peer| !excluded_peers.contains(peer)).collect_vec();
 
-            Ok(HeartbeatMessage { known_peers, known_orders })
+            let timestamp = get_current_time_millis();
+            let metadata = Some(PeerMetadata::local());
+            Ok(HeartbeatMessage {
+                known_peers,
+                known_orders,
+                timestamp,
+                metadata,
+                digest: None,
+                delta: None,
+                interval_ms: None,
+            })
+        })
+        .await
+    }
+
+    // --- Matching Ownership --- //
+
+    /// Get the cluster peer responsible for initiating matches on an order
+    ///
+    /// Ownership is assigned by rendezvous hashing the order ID against each
+    /// live peer in the local cluster. Every cluster peer computes the same
+    /// owner from its own view of the peer index, so only one node initiates
+    /// matching on a given order. If the owner stops heartbeating, it drops
+    /// out of the candidate set and the peer with the next highest score
+    /// takes over
+    ///
+    /// If the order's account has pinned peers and any of them is live, the
+    /// candidate set is restricted to the live pinned peers
+    pub async fn get_matching_owner(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<WrappedPeerId, StateError> {
+        let order_id = *order_id;
+        self.with_read_tx(move |tx| {
+            let my_id = tx.get_peer_id()?;
+            let mut candidates = live_cluster_peers(tx)?;
+            if let Some(account_id) = tx.get_account_id_for_order(&order_id)?
+                && let Some(pinned) = tx.get_account_pinned_peers(&account_id)?
+            {
+                let pinned_candidates =
+                    candidates.iter().copied().filter(|peer| pinned.contains(peer)).collect_vec();
+                if !pinned_candidates.is_empty() {
+                    candidates = pinned_candidates;
+                }
+            }
+
+            Ok(rendezvous_owner(&order_id, &candidates).unwrap_or(my_id))
         })
         .await
     }
 
+    /// Whether the local peer is responsible for initiating matches on an order
+    pub async fn is_matching_owner(&self, order_id: &OrderId) -> Result<bool, StateError> {
+        let owner = self.get_matching_owner(order_id).await?;
+        Ok(owner == self.get_peer_id()?)
+    }
+
     // -----------
     // | Setters |
     // -----------
@@ -249,13 +311,21 @@ impl StateInner {
         Ok(())
     }
 
-    /// Record a successful heartbeat on a peer
-    pub async fn record_heartbeat(&self, peer_id: &WrappedPeerId) -> Result<(), StateError> {
+    /// Record a successful heartbeat on a peer, along with the metadata the
+    /// peer advertised in it
+    pub async fn record_heartbeat(
+        &self,
+        peer_id: &WrappedPeerId,
+        metadata: Option<PeerMetadata>,
+    ) -> Result<(), StateError> {
         let peer_id = *peer_id;
         self.with_write_tx(move |tx| {
             if let Some(peer) = tx.get_peer_info(&peer_id)? {
                 let mut info = peer.deserialize()?;
                 info.successful_heartbeat();
+                if let Some(metadata) = metadata {
+                    info.set_metadata(metadata);
+                }
                 tx.write_peer(&info)?;
             }
             Ok(())
@@ -264,14 +334,60 @@ impl StateInner {
     }
 }
 
+// -----------
+// | Helpers |
+// -----------
+
+/// Get the local peer along with the cluster peers that have heartbeated
+/// within the liveness window
+///
+/// The local peer is always first
+pub(crate) fn live_cluster_peers<T: TransactionKind>(
+    tx: &StateTxn<'_, T>,
+) -> Result<Vec<WrappedPeerId>, StorageError> {
+    let my_id = tx.get_peer_id()?;
+    let cluster_id = tx.get_cluster_id()?;
+    let now = get_current_time_millis();
+
+    // The local peer is always live, other peers only if recently heard from
+    let mut peers = vec![my_id];
+    for (peer_id, info) in tx.get_info_map()?.into_iter() {
+        let elapsed = now.saturating_sub(info.get_last_heartbeat());
+        let is_live = elapsed < MATCHING_OWNER_LIVENESS_MS;
+        if peer_id != my_id && info.get_cluster_id() == cluster_id && is_live {
+            peers.push(peer_id);
+        }
+    }
+
+    Ok(peers)
+}
+
+/// Select the candidate with the highest rendezvous score for an order
+fn rendezvous_owner(order_id: &OrderId, candidates: &[WrappedPeerId]) -> Option<WrappedPeerId> {
+    candidates.iter().copied().max_by_key(|peer_id| rendezvous_score(order_id, peer_id))
+}
+
+/// Compute the rendezvous score of a peer for an order
+///
+/// We use keccak rather than the std hasher so that scores are stable across
+/// toolchains and builds
+fn rendezvous_score(order_id: &OrderId, peer_id: &WrappedPeerId) -> B256 {
+    let mut buf = order_id.as_bytes().to_vec();
+    buf.extend_from_slice(&peer_id.to_bytes());
+    keccak256(buf)
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
 
-    use types_gossip::{ClusterId, mocks::mock_peer};
+    use types_gossip::{ClusterId, PeerMetadata, WrappedPeerId, mocks::mock_peer};
+    use uuid::Uuid;
 
     use crate::test_helpers::mock_state;
 
+    use super::rendezvous_owner;
+
     /// Tests adding a peer to the peer index
     #[tokio::test]
     async fn test_add_peer() {
@@ -368,6 +484,30 @@ mod test {
         assert_eq!(peer3, *info_peer3);
     }
 
+    /// Tests that a heartbeat's metadata is merged into the peer's info
+    #[tokio::test]
+    async fn test_record_heartbeat_metadata() {
+        let state = mock_state().await;
+        let peer = mock_peer();
+        state.add_peer(peer.clone()).await.unwrap();
+
+        // A heartbeat without metadata leaves the peer's metadata unset
+        state.record_heartbeat(&peer.peer_id, None).await.unwrap();
+        let info = state.get_peer_info(&peer.peer_id).await.unwrap().unwrap();
+        assert_eq!(info.metadata, None);
+
+        // A heartbeat with metadata records it
+        let metadata = PeerMetadata::local();
+        state.record_heartbeat(&peer.peer_id, Some(metadata.clone())).await.unwrap();
+        let info = state.get_peer_info(&peer.peer_id).await.unwrap().unwrap();
+        assert_eq!(info.metadata, Some(metadata.clone()));
+
+        // A later heartbeat without metadata keeps the last advertised metadata
+        state.record_heartbeat(&peer.peer_id, None).await.unwrap();
+        let info = state.get_peer_info(&peer.peer_id).await.unwrap().unwrap();
+        assert_eq!(info.metadata, Some(metadata));
+    }
+
     /// Tests the `get_missing_peers` method
     #[tokio::test]
     async fn test_get_missing_peers() {
@@ -388,4 +528,40 @@ mod test {
 
         assert_eq!(missing_peers, expected);
     }
+
+    /// Tests that rendezvous ownership is order independent and fails over
+    /// when the owner leaves the candidate set
+    #[test]
+    fn test_rendezvous_owner_failover() {
+        let order_id = Uuid::new_v4();
+        let mut peers = (0..5).map(|_| WrappedPeerId::random()).collect::<Vec<_>>();
+        let owner = rendezvous_owner(&order_id, &peers).unwrap();
+
+        // The owner does not depend on the order of the candidates
+        peers.reverse();
+        assert_eq!(rendezvous_owner(&order_id, &peers), Some(owner));
+
+        // Removing a non-owner does not change the assignment
+        let non_owner_idx = peers.iter().position(|p| *p != owner).unwrap();
+        let removed = peers.remove(non_owner_idx);
+        assert_eq!(rendezvous_owner(&order_id, &peers), Some(owner));
+        peers.push(removed);
+
+        // Removing the owner fails over to another candidate
+        peers.retain(|p| *p != owner);
+        let new_owner = rendezvous_owner(&order_id, &peers).unwrap();
+        assert_ne!(new_owner, owner);
+        assert!(rendezvous_owner(&order_id, &[]).is_none());
+    }
+
+    /// Tests that a node with no live cluster peers owns every order
+    #[tokio::test]
+    async fn test_single_node_matching_owner() {
+        let state = mock_state().await;
+        let order_id = Uuid::new_v4();
+
+        let owner = state.get_matching_owner(&order_id).await.unwrap();
+        assert_eq!(owner, state.get_peer_id().unwrap());
+        assert!(state.is_matching_owner(&order_id).await.unwrap());
+    }
 }
@@ -0,0 +1,51 @@
+//! State interface for the local node's price history
+//!
+//! The price history is node-local, so writes bypass raft and go directly to
+//! the local database
+
+use alloy_primitives::Address;
+
+use crate::{StateInner, error::StateError, storage::tx::price_history::PriceSample};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Get the samples of a pair's price reported within `[start, end]`,
+    /// oldest first
+    pub async fn get_price_history(
+        &self,
+        base: Address,
+        quote: Address,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<PriceSample>, StateError> {
+        self.with_read_tx(move |tx| {
+            let samples = tx.get_price_samples(&base, &quote, start, end)?;
+            Ok(samples)
+        })
+        .await
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Record a batch of price samples in the local price history, evicting
+    /// samples older than the retention window
+    pub async fn record_price_samples(
+        &self,
+        samples: Vec<(Address, Address, PriceSample)>,
+        retention_ms: u64,
+    ) -> Result<(), StateError> {
+        self.with_write_tx(move |tx| {
+            for (base, quote, sample) in samples {
+                tx.append_price_sample(&base, &quote, sample, retention_ms)?;
+            }
+
+            Ok(())
+        })
+        .await
+    }
+}
@@ -2,7 +2,10 @@
 
 use alloy_primitives::Address;
 use tracing::instrument;
-use types_account::OrderId;
+use types_account::{
+    OrderId,
+    order::{Order, PrivacyRing},
+};
 use types_core::AccountId;
 use types_proofs::{
     IntentAndBalanceFirstFillValidityBundle, IntentAndBalanceValidityBundle,
@@ -37,6 +40,51 @@ impl StateInner {
         .await
     }
 
+    /// Check whether the intent validity proof that settling the given order
+    /// reads is stored, along with its witness where settlement reads one
+    ///
+    /// Only the presence of the proof is checked, it is neither deserialized
+    /// nor verified
+    ///
+    /// Ring 0 orders have no validity proof. Ring 1 settlement reads the
+    /// intent-only proof, while Ring 2 and 3 settlement read the
+    /// intent-and-balance proof and its witness. The first-fill variant is read
+    /// until the order has been filled
+    pub async fn is_settlement_validity_proof_stored(
+        &self,
+        order: &Order,
+    ) -> Result<bool, StateError> {
+        let locator = ValidityProofLocator::Intent { order_id: order.id };
+        let ring = order.ring;
+        let first_fill = !order.metadata.has_been_filled;
+        self.with_read_tx(move |tx| {
+            let ready = match (ring, first_fill) {
+                (PrivacyRing::Ring0, _) => true,
+                (PrivacyRing::Ring1, true) => {
+                    tx.has_validity_proof::<IntentOnlyFirstFillValidityBundle>(&locator)?
+                },
+                (PrivacyRing::Ring1, false) => {
+                    tx.has_validity_proof::<IntentOnlyValidityBundle>(&locator)?
+                },
+                (PrivacyRing::Ring2 | PrivacyRing::Ring3, true) => {
+                    tx.has_validity_proof::<IntentAndBalanceFirstFillValidityBundle>(&locator)?
+                        && tx
+                            .has_validity_witness::<SizedIntentAndBalanceFirstFillValidityWitness>(
+                                &locator,
+                            )?
+                },
+                (PrivacyRing::Ring2 | PrivacyRing::Ring3, false) => {
+                    tx.has_validity_proof::<IntentAndBalanceValidityBundle>(&locator)?
+                        && tx.has_validity_witness::<SizedIntentAndBalanceValidityWitness>(
+                            &locator,
+                        )?
+                },
+            };
+            Ok(ready)
+        })
+        .await
+    }
+
     /// Get the intent-only validity proof for a given order (subsequent fill)
     pub async fn get_intent_only_validity_proof(
         &self,
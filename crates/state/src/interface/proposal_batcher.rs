@@ -0,0 +1,223 @@
+//! Coalesces batchable proposals into a single raft entry
+//!
+//! Some transitions arrive in bursts (e.g. validity proofs during book sync)
+//! and are independent of the proposals around them. Rather than paying a
+//! consensus round-trip for each, the batcher collects those that arrive within
+//! a short window and proposes them as one raft entry. The state machine
+//! applies and notifies each coalesced proposal independently.
+//!
+//! Non-batchable proposals are proposed directly, so the pending batch is
+//! flushed before each of them to keep proposals in the order they were made
+
+use std::time::Duration;
+
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    oneshot,
+};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    error::StateError,
+    logging::Task,
+    notifications::OpenNotifications,
+    replication::raft::RaftClient,
+    state_transition::{Proposal, ProposalId},
+};
+
+/// The maximum time a batchable proposal waits for others to coalesce with
+const PROPOSAL_BATCH_WINDOW_MS: u64 = 5;
+/// The maximum number of proposals coalesced into a single raft entry
+const MAX_PROPOSAL_BATCH_SIZE: usize = 64;
+
+/// Error message emitted when the batcher's task has stopped
+const ERR_BATCHER_CLOSED: &str = "proposal batcher closed";
+
+/// A message to the batcher's task
+enum BatcherMessage {
+    /// A proposal to coalesce into the next batch
+    Propose(Proposal),
+    /// Propose the pending batch immediately, acking once it is proposed
+    Flush(oneshot::Sender<()>),
+}
+
+/// A batch of proposals collected by the batcher's task
+#[derive(Default)]
+struct Batch {
+    /// The proposals to coalesce into a single raft entry
+    proposals: Vec<Proposal>,
+    /// The flush that ended the batch, acked once the batch is proposed
+    flush: Option<oneshot::Sender<()>>,
+}
+
+/// A handle to the proposal batcher
+#[derive(Clone)]
+pub struct ProposalBatcher {
+    /// The queue of proposals awaiting coalescing
+    sender: UnboundedSender<BatcherMessage>,
+}
+
+impl ProposalBatcher {
+    /// Create a new batcher, spawning the task that proposes batches
+    pub fn new(raft: RaftClient, notifications: OpenNotifications) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        tokio::spawn(Self::run(receiver, raft, notifications));
+        Self { sender }
+    }
+
+    /// Enqueue a proposal to be coalesced into the next batch
+    pub fn enqueue(&self, proposal: Proposal) -> Result<(), StateError> {
+        self.send(BatcherMessage::Propose(proposal))
+    }
+
+    /// Propose the pending batch without waiting out the batching window
+    ///
+    /// Returns once every proposal enqueued before the flush has been proposed
+    pub async fn flush(&self) -> Result<(), StateError> {
+        let (ack, recv) = oneshot::channel();
+        self.send(BatcherMessage::Flush(ack))?;
+        recv.await.map_err(|_| StateError::Proposal(ERR_BATCHER_CLOSED.to_string()))
+    }
+
+    /// Send a message to the batcher's task
+    fn send(&self, msg: BatcherMessage) -> Result<(), StateError> {
+        self.sender.send(msg).map_err(|_| StateError::Proposal(ERR_BATCHER_CLOSED.to_string()))
+    }
+
+    /// Collect proposals into batches and propose each as a single raft entry
+    async fn run(
+        mut receiver: UnboundedReceiver<BatcherMessage>,
+        raft: RaftClient,
+        notifications: OpenNotifications,
+    ) {
+        while let Some(Batch { proposals, flush }) = Self::next_batch(&mut receiver).await {
+            let ids = proposals.iter().flat_map(|p| p.ids()).collect::<Vec<_>>();
+            if let Some(proposal) = Proposal::coalesce(proposals) {
+                Self::propose_batch(&raft, &notifications, proposal, ids).await;
+            }
+
+            // The flushing proposer may have gone away, in which case there is
+            // no one to ack
+            if let Some(ack) = flush {
+                let _ = ack.send(());
+            }
+        }
+    }
+
+    /// Propose a coalesced batch, failing each of its waiters if the entry
+    /// could not be proposed
+    async fn propose_batch(
+        raft: &RaftClient,
+        notifications: &OpenNotifications,
+        proposal: Proposal,
+        ids: Vec<ProposalId>,
+    ) {
+        if let Err(e) = raft.propose_transition(proposal).await {
+            let err_str = e.to_string();
+            log_task!(
+                Task::Proposal,
+                Outcome::Failed,
+                error = %err_str,
+                batch_size = ids.len(),
+                "failed to propose coalesced batch"
+            );
+
+            for id in ids {
+                notifications.notify(id, Err(StateError::Proposal(err_str.clone()))).await;
+            }
+        }
+    }
+
+    /// Wait for the next proposal, then collect any that follow within the
+    /// batching window
+    ///
+    /// A flush ends the batch early. Returns `None` once the queue is closed
+    /// and drained
+    async fn next_batch(receiver: &mut UnboundedReceiver<BatcherMessage>) -> Option<Batch> {
+        let mut batch = Batch::default();
+        match receiver.recv().await? {
+            BatcherMessage::Propose(proposal) => batch.proposals.push(proposal),
+            BatcherMessage::Flush(ack) => {
+                batch.flush = Some(ack);
+                return Some(batch);
+            },
+        }
+
+        let deadline = tokio::time::sleep(Duration::from_millis(PROPOSAL_BATCH_WINDOW_MS));
+        tokio::pin!(deadline);
+        while batch.proposals.len() < MAX_PROPOSAL_BATCH_SIZE {
+            tokio::select! {
+                _ = &mut deadline => break,
+                next = receiver.recv() => match next {
+                    Some(BatcherMessage::Propose(proposal)) => batch.proposals.push(proposal),
+                    Some(BatcherMessage::Flush(ack)) => {
+                        batch.flush = Some(ack);
+                        break;
+                    },
+                    None => break,
+                },
+            }
+        }
+
+        Some(batch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::{mpsc::unbounded_channel, oneshot};
+
+    use crate::state_transition::{Proposal, StateTransition};
+
+    use super::{BatcherMessage, MAX_PROPOSAL_BATCH_SIZE, ProposalBatcher};
+
+    /// Build a proposal for testing
+    fn mock_proposal() -> Proposal {
+        Proposal::from(StateTransition::CreateMatchingPool { pool_name: "pool".to_string() })
+    }
+
+    /// Tests that queued proposals are coalesced up to the max batch size
+    #[tokio::test]
+    async fn test_next_batch_bounded() {
+        let (sender, mut receiver) = unbounded_channel();
+        for _ in 0..MAX_PROPOSAL_BATCH_SIZE + 1 {
+            sender.send(BatcherMessage::Propose(mock_proposal())).unwrap();
+        }
+
+        let batch = ProposalBatcher::next_batch(&mut receiver).await.unwrap();
+        assert_eq!(batch.proposals.len(), MAX_PROPOSAL_BATCH_SIZE);
+        let batch = ProposalBatcher::next_batch(&mut receiver).await.unwrap();
+        assert_eq!(batch.proposals.len(), 1);
+
+        drop(sender);
+        assert!(ProposalBatcher::next_batch(&mut receiver).await.is_none());
+    }
+
+    /// Tests that a flush ends the pending batch
+    #[tokio::test]
+    async fn test_next_batch_flush() {
+        let (sender, mut receiver) = unbounded_channel();
+        let (ack, _recv) = oneshot::channel();
+        sender.send(BatcherMessage::Propose(mock_proposal())).unwrap();
+        sender.send(BatcherMessage::Propose(mock_proposal())).unwrap();
+        sender.send(BatcherMessage::Flush(ack)).unwrap();
+        sender.send(BatcherMessage::Propose(mock_proposal())).unwrap();
+
+        // The proposals before the flush are batched without the one after it
+        let batch = ProposalBatcher::next_batch(&mut receiver).await.unwrap();
+        assert_eq!(batch.proposals.len(), 2);
+        assert!(batch.flush.is_some());
+
+        let batch = ProposalBatcher::next_batch(&mut receiver).await.unwrap();
+        assert_eq!(batch.proposals.len(), 1);
+        assert!(batch.flush.is_none());
+
+        // A flush with nothing pending is acked without a batch
+        let (ack, _recv) = oneshot::channel();
+        sender.send(BatcherMessage::Flush(ack)).unwrap();
+        let batch = ProposalBatcher::next_batch(&mut receiver).await.unwrap();
+        assert!(batch.proposals.is_empty());
+        assert!(batch.flush.is_some());
+    }
+}
@@ -0,0 +1,173 @@
+//! A dry-apply validation pass run on state transitions before they are
+//! proposed
+//!
+//! Raft replicates a proposal before the applicator sees it, so an invalid
+//! transition costs a log entry on every node before it is rejected. When
+//! enabled, the proposer checks a transition against a read snapshot of the
+//! local state and rejects transitions that could not apply, e.g. duplicate
+//! account IDs or tasks referencing missing queues. The check is best effort:
+//! the applicator remains the authority on whether a transition applies
+
+use libmdbx::RO;
+use util::{log_task, logging::Outcome};
+
+use crate::{
+    StateInner, error::StateError, logging::Task, state_transition::StateTransition,
+    storage::tx::StateTxn,
+};
+
+/// The error message emitted when an account already exists
+const ERR_DUPLICATE_ACCOUNT: &str = "account already exists";
+/// The error message emitted when a task already exists
+const ERR_DUPLICATE_TASK: &str = "task already exists";
+/// The error message emitted when an account does not exist
+const ERR_MISSING_ACCOUNT: &str = "account not found";
+/// The error message emitted when a task is not in any queue
+const ERR_MISSING_TASK_QUEUE: &str = "task not found in any queue";
+
+impl StateInner {
+    /// Dry-apply a transition against a read snapshot of the state, if
+    /// proposal validation is enabled
+    ///
+    /// Returns `StateError::TransitionRejected` if the transition could not
+    /// apply
+    pub(crate) async fn validate_proposal(
+        &self,
+        transition: &StateTransition,
+    ) -> Result<(), StateError> {
+        if !self.config.validate_proposals || !needs_validation(transition) {
+            return Ok(());
+        }
+
+        let t = transition.clone();
+        let res = self.with_read_tx(move |tx| dry_apply(&t, tx)).await;
+        if let Err(StateError::TransitionRejected(msg)) = &res {
+            log_task!(
+                Task::Proposal,
+                Outcome::Skipped,
+                error = %msg,
+                "state transition rejected by dry-apply"
+            );
+        }
+
+        res
+    }
+}
+
+/// Whether a transition is covered by the dry-apply checks
+///
+/// Avoids opening a read tx for transitions that are never rejected here
+fn needs_validation(transition: &StateTransition) -> bool {
+    matches!(
+        transition,
+        StateTransition::CreateAccount { .. }
+            | StateTransition::AddOrderToAccount { .. }
+            | StateTransition::AppendTask { .. }
+            | StateTransition::AppendTasks { .. }
+            | StateTransition::PopTask { .. }
+            | StateTransition::TransitionTask { .. }
+    )
+}
+
+/// Check a transition against a read snapshot of the state
+fn dry_apply(transition: &StateTransition, tx: &StateTxn<'_, RO>) -> Result<(), StateError> {
+    match transition {
+        StateTransition::CreateAccount { account } => {
+            if tx.contains_account(&account.id)? {
+                return Err(reject(ERR_DUPLICATE_ACCOUNT, account.id));
+            }
+        },
+        StateTransition::AddOrderToAccount { account_id, .. } => {
+            if !tx.contains_account(account_id)? {
+                return Err(reject(ERR_MISSING_ACCOUNT, account_id));
+            }
+        },
+        StateTransition::AppendTask { task, .. } => {
+            if tx.get_task(&task.id)?.is_some() {
+                return Err(reject(ERR_DUPLICATE_TASK, task.id));
+            }
+        },
+        StateTransition::AppendTasks { tasks, .. } => {
+            for task in tasks {
+                if tx.get_task(&task.id)?.is_some() {
+                    return Err(reject(ERR_DUPLICATE_TASK, task.id));
+                }
+            }
+        },
+        StateTransition::PopTask { task_id, .. }
+        | StateTransition::TransitionTask { task_id, .. } => {
+            if tx.get_queue_keys_for_task(task_id)?.is_empty() {
+                return Err(reject(ERR_MISSING_TASK_QUEUE, task_id));
+            }
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+/// Build a rejection for the given entity
+fn reject<T: std::fmt::Display>(msg: &str, id: T) -> StateError {
+    StateError::TransitionRejected(format!("{msg}: {id}"))
+}
+
+#[cfg(test)]
+mod test {
+    use config::RelayerConfig;
+    use types_account::account::mocks::mock_empty_account;
+    use types_tasks::{TaskQueueKey, mocks::mock_queued_task};
+
+    use crate::{
+        State,
+        error::StateError,
+        state_transition::StateTransition,
+        test_helpers::{mock_relayer_config, mock_state_with_config},
+    };
+
+    /// Create a mock state with proposal validation enabled
+    async fn validating_state() -> State {
+        let config = RelayerConfig { validate_proposals: true, ..mock_relayer_config() };
+        mock_state_with_config(&config).await
+    }
+
+    /// Tests that creating an account twice is rejected before it is proposed
+    #[tokio::test]
+    async fn test_reject_duplicate_account() {
+        let state = validating_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+
+        let transition = StateTransition::CreateAccount { account };
+        let res = state.validate_proposal(&transition).await;
+        assert!(matches!(res, Err(StateError::TransitionRejected(_))));
+    }
+
+    /// Tests that popping a task that is in no queue is rejected before it is
+    /// proposed
+    #[tokio::test]
+    async fn test_reject_missing_task_queue() {
+        let state = validating_state().await;
+        let task = mock_queued_task(TaskQueueKey::new_v4());
+
+        let res = state.pop_task(task.id, true /* success */).await;
+        assert!(matches!(res, Err(StateError::TransitionRejected(_))));
+    }
+
+    /// Tests that appending a set of tasks is rejected if any of them already
+    /// exists
+    #[tokio::test]
+    async fn test_reject_duplicate_task_in_set() {
+        let state = validating_state().await;
+        let key = TaskQueueKey::new_v4();
+        let existing = mock_queued_task(key);
+        let (task_id, waiter) = state.append_task(existing.descriptor).await.unwrap();
+        waiter.await.unwrap();
+
+        let existing = state.get_task(&task_id).await.unwrap().unwrap();
+        let executor = state.get_peer_id().unwrap();
+        let tasks = vec![mock_queued_task(key), existing];
+        let transition = StateTransition::AppendTasks { tasks, executor };
+        let res = state.validate_proposal(&transition).await;
+        assert!(matches!(res, Err(StateError::TransitionRejected(_))));
+    }
+}
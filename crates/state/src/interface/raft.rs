@@ -61,6 +61,18 @@ impl StateInner {
             && metrics.membership_config.membership().get_node(&id).is_some()
     }
 
+    /// The number of log entries the local node holds that it has not yet
+    /// applied to its state machine
+    ///
+    /// A growing lag indicates that the state machine is falling behind the
+    /// log, so reads served from the local DB are increasingly stale
+    pub fn raft_apply_lag(&self) -> u64 {
+        let metrics = self.raft.metrics();
+        let last_log = metrics.last_log_index.unwrap_or(0);
+        let last_applied = metrics.last_applied.map(|log| log.index).unwrap_or(0);
+        last_log.saturating_sub(last_applied)
+    }
+
     /// Whether the local node is the leader
     pub fn is_leader(&self) -> bool {
         self.raft.is_leader()
@@ -106,6 +118,15 @@ impl StateInner {
         self.raft.trigger_snapshot().await.map_err(StateError::Replication)
     }
 
+    /// Flush the raft and shut it down
+    ///
+    /// Snapshots the applied state so that a restart recovers from the
+    /// snapshot, then stops the local node's participation in the raft
+    pub async fn shutdown_raft(&self) -> Result<(), StateError> {
+        self.raft.flush_snapshot().await.map_err(StateError::Replication)?;
+        self.raft.shutdown().await.map_err(StateError::Replication)
+    }
+
     // --- Networking --- //
 
     /// Handle a raft request from a peer
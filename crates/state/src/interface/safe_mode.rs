@@ -0,0 +1,174 @@
+//! A read-only safe mode entered when the raft quorum is unreachable
+//!
+//! Without a quorum, proposals are never committed and their waiters hang
+//! indefinitely. A timer samples the raft metrics and, once the quorum has
+//! been unreachable for longer than a threshold, places the node in safe mode:
+//! reads are still served from the local DB, but new proposals are refused
+//! with `StateError::QuorumLost` until the quorum is reachable again. The
+//! waiters of proposals in flight when safe mode is entered fail with the same
+//! error rather than waiting out their timeout
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use system_clock::SystemClock;
+use util::{get_current_time_millis, log_task, logging::Outcome};
+
+use crate::{StateInner, error::StateError, logging::Task};
+
+use super::DEFAULT_MAX_ELECTION_MS;
+
+/// The frequency with which to check for a reachable quorum
+const QUORUM_CHECK_INTERVAL_MS: u64 = 1_000; // 1 second
+/// The length of time the quorum must be unreachable before entering safe mode
+const QUORUM_LOSS_THRESHOLD_MS: u64 = 30_000; // 30 seconds
+/// The length of time since a leader's last quorum acknowledgement after which
+/// it considers the quorum unreachable
+///
+/// Matches the election timeout, after which a follower that has not heard
+/// from the leader would begin an election
+const LEADER_QUORUM_ACK_TIMEOUT_MS: u64 = DEFAULT_MAX_ELECTION_MS;
+/// Metric describing whether the local node is in safe mode
+const RAFT_SAFE_MODE_METRIC: &str = "raft_safe_mode";
+
+/// Tracks quorum reachability and whether the node is in safe mode
+#[derive(Clone, Debug, Default)]
+pub struct SafeMode {
+    /// Whether the node is in safe mode
+    active: Arc<AtomicBool>,
+    /// The time at which the quorum became unreachable, in milliseconds since
+    /// the epoch, or zero if it is reachable
+    unreachable_since: Arc<AtomicU64>,
+}
+
+impl SafeMode {
+    /// Whether the node is in safe mode
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Record an observation of the quorum's reachability
+    ///
+    /// Returns the new safe mode status if the observation changed it
+    fn observe(&self, quorum_reachable: bool, now_ms: u64) -> Option<bool> {
+        let active = if quorum_reachable {
+            self.unreachable_since.store(0, Ordering::Relaxed);
+            false
+        } else {
+            let since = match self.unreachable_since.load(Ordering::Relaxed) {
+                0 => {
+                    self.unreachable_since.store(now_ms, Ordering::Relaxed);
+                    now_ms
+                },
+                since => since,
+            };
+            now_ms.saturating_sub(since) >= QUORUM_LOSS_THRESHOLD_MS
+        };
+
+        let was_active = self.active.swap(active, Ordering::Relaxed);
+        (was_active != active).then_some(active)
+    }
+}
+
+impl StateInner {
+    /// Whether the node is in read-only safe mode, having lost the raft quorum
+    pub fn is_safe_mode(&self) -> bool {
+        self.safe_mode.is_active()
+    }
+
+    /// Whether the local node can currently reach a raft quorum
+    ///
+    /// A leader can reach the quorum if a quorum has recently acknowledged it,
+    /// a follower if it knows of a leader. An uninitialized raft, e.g. a node
+    /// awaiting adoption into the cluster, is not considered to have lost its
+    /// quorum
+    fn quorum_reachable(&self) -> bool {
+        let metrics = self.raft.metrics();
+        if metrics.membership_config.membership().voter_ids().next().is_none() {
+            return true;
+        }
+
+        match metrics.current_leader {
+            Some(leader) if leader == self.raft.node_id() => {
+                metrics.millis_since_quorum_ack.is_some_and(|ms| ms < LEADER_QUORUM_ACK_TIMEOUT_MS)
+            },
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Periodically checks for a reachable quorum, entering and exiting safe
+    /// mode as it is lost and regained
+    pub(super) async fn setup_quorum_watch_timer(
+        &self,
+        clock: &SystemClock,
+    ) -> Result<(), StateError> {
+        let duration = Duration::from_millis(QUORUM_CHECK_INTERVAL_MS);
+        let name = "raft-quorum-watch-loop".to_string();
+        let this = self.clone();
+
+        clock
+            .add_async_timer(name, duration, move || {
+                let this = this.clone();
+                async move {
+                    let reachable = this.quorum_reachable();
+                    match this.safe_mode.observe(reachable, get_current_time_millis()) {
+                        Some(true) => {
+                            log_task!(
+                                Task::RaftLifecycle,
+                                Outcome::Failed,
+                                threshold_ms = QUORUM_LOSS_THRESHOLD_MS,
+                                "raft quorum unreachable, entering read-only safe mode"
+                            );
+                            this.notifications.fail_all(|| StateError::QuorumLost).await;
+                        },
+                        Some(false) => log_task!(
+                            Task::RaftLifecycle,
+                            Outcome::Ok,
+                            "raft quorum reachable, exiting safe mode"
+                        ),
+                        None => {},
+                    }
+
+                    let active = if this.is_safe_mode() { 1.0 } else { 0.0 };
+                    metrics::gauge!(RAFT_SAFE_MODE_METRIC).set(active);
+                    Ok(())
+                }
+            })
+            .await
+            .map_err(StateError::Clock)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{QUORUM_LOSS_THRESHOLD_MS, SafeMode};
+
+    /// Tests entering and exiting safe mode as the quorum is lost and regained
+    #[test]
+    fn test_safe_mode_transitions() {
+        let safe_mode = SafeMode::default();
+        assert_eq!(safe_mode.observe(true /* quorum_reachable */, 1_000), None);
+
+        // Safe mode is only entered once the quorum has been unreachable for the
+        // threshold
+        assert_eq!(safe_mode.observe(false, 2_000), None);
+        assert_eq!(safe_mode.observe(false, 2_000 + QUORUM_LOSS_THRESHOLD_MS - 1), None);
+        assert!(!safe_mode.is_active());
+        assert_eq!(safe_mode.observe(false, 2_000 + QUORUM_LOSS_THRESHOLD_MS), Some(true));
+        assert!(safe_mode.is_active());
+
+        // Safe mode is exited as soon as the quorum is reachable
+        assert_eq!(safe_mode.observe(true, 3_000 + QUORUM_LOSS_THRESHOLD_MS), Some(false));
+        assert!(!safe_mode.is_active());
+
+        // A brief loss restarts the threshold
+        assert_eq!(safe_mode.observe(false, 4_000 + QUORUM_LOSS_THRESHOLD_MS), None);
+        assert_eq!(safe_mode.observe(false, 5_000 + QUORUM_LOSS_THRESHOLD_MS), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! State interface for cluster-wide settlement locks
+
+use types_account::OrderId;
+use util::get_current_time_millis;
+
+use crate::{
+    StateInner, error::StateError, notifications::ProposalWaiter, state_transition::StateTransition,
+};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Whether any of the given orders is locked for settlement by another
+    /// peer
+    pub async fn is_locked_for_settlement(
+        &self,
+        order_ids: Vec<OrderId>,
+    ) -> Result<bool, StateError> {
+        let my_id = self.get_peer_id()?;
+        let now = get_current_time_millis();
+        self.with_read_tx(move |tx| {
+            for order_id in order_ids.iter() {
+                if let Some(lock) = tx.get_settlement_lock(order_id)?
+                    && lock.holder != my_id
+                    && !lock.is_expired(now)
+                {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        })
+        .await
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// Propose acquiring a settlement lock on the given orders for the local
+    /// peer
+    ///
+    /// The proposal is rejected if another peer holds an unexpired lock on any
+    /// of the orders
+    pub async fn acquire_settlement_lock(
+        &self,
+        order_ids: Vec<OrderId>,
+    ) -> Result<ProposalWaiter, StateError> {
+        let holder = self.get_peer_id()?;
+        let acquired_at = get_current_time_millis();
+        self.send_proposal(StateTransition::AcquireSettlementLock {
+            order_ids,
+            holder,
+            acquired_at,
+        })
+        .await
+    }
+
+    /// Propose releasing the local peer's settlement locks on the given orders
+    pub async fn release_settlement_lock(
+        &self,
+        order_ids: Vec<OrderId>,
+    ) -> Result<ProposalWaiter, StateError> {
+        let holder = self.get_peer_id()?;
+        self.send_proposal(StateTransition::ReleaseSettlementLock { order_ids, holder }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::WrappedPeerId;
+    use util::get_current_time_millis;
+    use uuid::Uuid;
+
+    use crate::{state_transition::StateTransition, test_helpers::mock_state};
+
+    /// Tests acquiring and releasing a settlement lock through the state
+    /// interface
+    #[tokio::test]
+    async fn test_acquire_release_settlement_lock() {
+        let state = mock_state().await;
+        let order_ids = vec![Uuid::new_v4(), Uuid::new_v4()];
+
+        state.acquire_settlement_lock(order_ids.clone()).await.unwrap().await.unwrap();
+        assert!(!state.is_locked_for_settlement(order_ids.clone()).await.unwrap());
+
+        // Re-acquiring a held lock succeeds for the holder
+        state.acquire_settlement_lock(order_ids.clone()).await.unwrap().await.unwrap();
+        state.release_settlement_lock(order_ids.clone()).await.unwrap().await.unwrap();
+        assert!(!state.is_locked_for_settlement(order_ids).await.unwrap());
+    }
+
+    /// Tests that a lock held by another peer is reported, and that the local
+    /// peer cannot acquire it
+    #[tokio::test]
+    async fn test_locked_by_other_peer() {
+        let state = mock_state().await;
+        let (locked, free) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let transition = StateTransition::AcquireSettlementLock {
+            order_ids: vec![locked],
+            holder: WrappedPeerId::random(),
+            acquired_at: get_current_time_millis(),
+        };
+        state.send_proposal(transition).await.unwrap().await.unwrap();
+
+        assert!(state.is_locked_for_settlement(vec![free, locked]).await.unwrap());
+        assert!(!state.is_locked_for_settlement(vec![free]).await.unwrap());
+        assert!(state.acquire_settlement_lock(vec![locked]).await.unwrap().await.is_err());
+    }
+}
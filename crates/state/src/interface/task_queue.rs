@@ -1,11 +1,12 @@
 //! The interface for interacting with the task queue
 
+use libmdbx::TransactionKind;
 use tracing::instrument;
 use types_core::AccountId;
 use types_gossip::WrappedPeerId;
 use types_tasks::{
-    HistoricalTask, QueuedTask, QueuedTaskState, RefreshAccountTaskDescriptor, TaskDescriptor,
-    TaskIdentifier, TaskQueueKey,
+    ChainSubmission, GasCostTotals, HistoricalTask, QueuedTask, QueuedTaskState,
+    RefreshAccountTaskDescriptor, TaskDescriptor, TaskHistoryFilter, TaskIdentifier, TaskQueueKey,
 };
 use util::{get_current_time_millis, res_some, telemetry::helpers::backfill_trace_field};
 
@@ -15,11 +16,14 @@ use crate::{
     notifications::ProposalWaiter,
     state_transition::StateTransition,
     storage::{
-        error::StorageError, traits::RkyvValue,
-        tx::task_queue::queue_type::ArchivedTaskQueuePreemptionState,
+        error::StorageError,
+        traits::RkyvValue,
+        tx::{StateTxn, task_queue::queue_type::ArchivedTaskQueuePreemptionState},
     },
 };
 
+use super::peer_index::live_cluster_peers;
+
 impl StateInner {
     // -----------
     // | Getters |
@@ -31,6 +35,11 @@ impl StateInner {
         self.with_read_tx(move |tx| Ok(tx.get_task(&tid)?.is_some())).await
     }
 
+    /// Get the total number of tasks queued across all task queues
+    pub async fn get_task_queue_depth(&self) -> Result<usize, StateError> {
+        self.with_read_tx(move |tx| Ok(tx.get_total_queued_tasks()?)).await
+    }
+
     /// Whether the queue is paused by a serial task
     pub async fn is_queue_paused_serial(&self, key: &TaskQueueKey) -> Result<bool, StateError> {
         let key = *key;
@@ -46,6 +55,15 @@ impl StateInner {
         .await
     }
 
+    /// Whether an operator has paused the queue
+    pub async fn is_queue_paused_by_operator(
+        &self,
+        key: &TaskQueueKey,
+    ) -> Result<bool, StateError> {
+        let key = *key;
+        self.with_read_tx(move |tx| Ok(tx.is_queue_paused_by_operator(&key)?)).await
+    }
+
     /// Self-heal task queues wedged in `SerialPreemptionQueued` by an orphaned
     /// committed preemptive (settle) task -- the node that was running the
     /// settle stopped driving it (worker churn, scale-down, or the seed
@@ -190,6 +208,85 @@ impl StateInner {
         .await
     }
 
+    /// Get a page of up to `limit` tasks (running and historical) matching the
+    /// filter
+    ///
+    /// Tasks still in the queue come first, followed by the history from most
+    /// recent. The `cursor` is the ID of the last task on the previous page,
+    /// and the returned cursor is `None` once no matching tasks remain
+    pub async fn get_task_history_page(
+        &self,
+        key: &TaskQueueKey,
+        filter: TaskHistoryFilter,
+        cursor: Option<TaskIdentifier>,
+        limit: usize,
+    ) -> Result<(Vec<HistoricalTask>, Option<TaskIdentifier>), StateError> {
+        let key = *key;
+        self.with_read_tx(move |tx| {
+            let queued: Vec<HistoricalTask> = tx
+                .get_queued_tasks(&key)?
+                .into_iter()
+                .filter_map(|t| {
+                    let task = t.deserialize().ok()?;
+                    HistoricalTask::from_queued_task(key, task)
+                })
+                .collect();
+
+            // A cursor that is not in the queue refers to the history, which
+            // includes tasks that have finished since the previous page
+            let (queued_start, history_cursor) = match cursor {
+                Some(cursor) => match queued.iter().position(|t| t.id == cursor) {
+                    Some(idx) => (idx + 1, None),
+                    None => (queued.len(), Some(cursor)),
+                },
+                None => (0, None),
+            };
+
+            let mut tasks: Vec<HistoricalTask> =
+                queued.into_iter().skip(queued_start).filter(|t| filter.matches(t)).collect();
+            if tasks.len() > limit {
+                tasks.truncate(limit);
+                let next_cursor = tasks.last().map(|t| t.id);
+                return Ok((tasks, next_cursor));
+            }
+
+            let remaining = limit - tasks.len();
+            let (historical, has_more) =
+                tx.get_task_history_page(&key, &filter, history_cursor, remaining)?;
+            tasks.extend(historical);
+
+            let next_cursor = if has_more { tasks.last().map(|t| t.id) } else { None };
+            Ok((tasks, next_cursor))
+        })
+        .await
+    }
+
+    /// Get the aggregate gas costs of the on-chain submissions made on behalf
+    /// of an account
+    pub async fn get_account_gas_costs(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<GasCostTotals, StateError> {
+        let account_id = *account_id;
+        self.with_read_tx(move |tx| Ok(tx.get_account_gas_costs(&account_id)?)).await
+    }
+
+    /// Get the on-chain submissions made by a task
+    pub async fn get_task_gas_costs(
+        &self,
+        task_id: &TaskIdentifier,
+    ) -> Result<Vec<ChainSubmission>, StateError> {
+        let task_id = *task_id;
+        self.with_read_tx(move |tx| Ok(tx.get_task_gas_costs(&task_id)?)).await
+    }
+
+    /// Get the aggregate gas costs of on-chain submissions for each task type
+    pub async fn get_task_type_gas_costs(
+        &self,
+    ) -> Result<Vec<(String, GasCostTotals)>, StateError> {
+        self.with_read_tx(|tx| Ok(tx.get_task_type_gas_costs()?)).await
+    }
+
     /// Get a task by ID
     pub async fn get_task(
         &self,
@@ -262,6 +359,25 @@ impl StateInner {
         Ok((tid, waiter))
     }
 
+    /// Append a set of tasks to the queue in a single proposal
+    ///
+    /// Either all of the tasks are enqueued or none are. Tasks sharing a queue
+    /// run in the given order
+    #[instrument(name = "propose_append_tasks", skip_all, err, fields(num_tasks = tasks.len()))]
+    pub async fn append_tasks(
+        &self,
+        tasks: Vec<TaskDescriptor>,
+    ) -> Result<(Vec<TaskIdentifier>, ProposalWaiter), StateError> {
+        let tasks: Vec<_> = tasks.into_iter().map(QueuedTask::new).collect();
+        let tids = tasks.iter().map(|task| task.id).collect();
+
+        // Propose the tasks to the task queue
+        let executor = self.get_peer_id()?;
+        let proposal = StateTransition::AppendTasks { tasks, executor };
+        let waiter = self.send_proposal(proposal).await?;
+        Ok((tids, waiter))
+    }
+
     /// Pop a task from the queue
     #[instrument(name = "propose_pop_task", skip_all, err, fields(task_id = %task_id, success = %success))]
     pub async fn pop_task(
@@ -288,6 +404,19 @@ impl StateInner {
         self.send_proposal(StateTransition::ClearTaskQueue { queue: *key }).await
     }
 
+    /// Pause a task queue on behalf of an operator
+    pub async fn pause_task_queue(&self, key: &TaskQueueKey) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::PauseTaskQueue { queue: *key }).await
+    }
+
+    /// Resume a task queue paused by an operator
+    pub async fn resume_task_queue(
+        &self,
+        key: &TaskQueueKey,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::ResumeTaskQueue { queue: *key }).await
+    }
+
     /// Enqueue a preemptive task
     ///
     /// A task marked `serial` (i.e. `serial = true`) requires exclusive access
@@ -324,9 +453,73 @@ impl StateInner {
         failed_peer: &WrappedPeerId,
     ) -> Result<ProposalWaiter, StateError> {
         let local_peer = self.get_peer_id()?;
-        let proposal = StateTransition::ReassignTasks { from: *failed_peer, to: local_peer };
+        let from = *failed_peer;
+
+        // Tasks of accounts that pin peers go to a live pinned peer where possible
+        let pinned = self
+            .with_read_tx(move |tx| {
+                let live_peers = live_cluster_peers(tx)?;
+                let mut pinned = Vec::new();
+                for task_id in tx.get_assigned_tasks(&from)? {
+                    if let Some(peer_id) = select_pinned_executor(tx, &task_id, &live_peers)?
+                        && peer_id != local_peer
+                    {
+                        pinned.push((task_id, peer_id));
+                    }
+                }
+
+                Ok(pinned)
+            })
+            .await?;
+
+        let proposal = StateTransition::ReassignTasksPinned { from, to: local_peer, pinned };
         self.send_proposal(proposal).await
     }
+
+    /// Record the gas costs of a task's on-chain submissions
+    pub async fn record_task_gas_costs(
+        &self,
+        task_id: TaskIdentifier,
+        task_type: String,
+        account_ids: Vec<AccountId>,
+        costs: Vec<ChainSubmission>,
+    ) -> Result<ProposalWaiter, StateError> {
+        let transition =
+            StateTransition::RecordTaskGasCosts { task_id, task_type, account_ids, costs };
+        self.send_proposal(transition).await
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Select a live pinned peer to execute a task, if any of the task's accounts
+/// has pinned peers
+///
+/// The local peer is preferred if pinned, otherwise the first live peer in the
+/// account's pinned order is chosen
+fn select_pinned_executor<T: TransactionKind>(
+    tx: &StateTxn<'_, T>,
+    task_id: &TaskIdentifier,
+    live_peers: &[WrappedPeerId],
+) -> Result<Option<WrappedPeerId>, StorageError> {
+    for key in tx.get_queue_keys_for_task(task_id)? {
+        let Some(pinned) = tx.get_account_pinned_peers(&key)? else {
+            continue;
+        };
+
+        // The local peer is first in the live set
+        let executor = live_peers
+            .first()
+            .filter(|local| pinned.contains(*local))
+            .or_else(|| pinned.iter().find(|peer| live_peers.contains(*peer)));
+        if let Some(peer_id) = executor {
+            return Ok(Some(*peer_id));
+        }
+    }
+
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -334,7 +527,8 @@ mod test {
     use types_account::account::mocks::mock_empty_account;
     use types_core::AccountId;
     use types_tasks::{
-        QueuedTaskState, TaskIdentifier, TaskQueueKey,
+        ChainSubmission, GasCostTotals, QueuedTaskState, TaskHistoryFilter, TaskIdentifier,
+        TaskQueueKey, TaskStatus,
         mocks::{mock_queued_task, mock_task_descriptor},
     };
 
@@ -373,6 +567,23 @@ mod test {
         assert!(state.get_task(&task_id).await.unwrap().is_some());
     }
 
+    /// Tests appending a set of tasks in a single proposal
+    #[tokio::test]
+    async fn test_append_tasks() {
+        let state = mock_state().await;
+
+        let key = TaskQueueKey::new_v4();
+        let tasks = vec![mock_task_descriptor(key), mock_task_descriptor(key)];
+        let (task_ids, waiter) = state.append_tasks(tasks).await.unwrap();
+        waiter.await.unwrap();
+
+        // The tasks are queued in order, and only the first is started
+        let tasks = state.get_queued_tasks(&key).await.unwrap();
+        assert_eq!(tasks.iter().map(|task| task.id).collect::<Vec<_>>(), task_ids);
+        assert!(matches!(tasks[0].state, QueuedTaskState::Running { .. }));
+        assert!(matches!(tasks[1].state, QueuedTaskState::Queued));
+    }
+
     /// Tests popping from a queue
     #[tokio::test]
     async fn test_pop() {
@@ -895,4 +1106,90 @@ mod test {
             assert!(matches!(task.state, QueuedTaskState::Completed));
         }
     }
+
+    /// Tests paging through the task history with a status filter
+    #[tokio::test]
+    async fn test_task_history_page() {
+        const N: usize = 10;
+        const PAGE_SIZE: usize = 4;
+        let state = mock_state().await;
+        let account_id = AccountId::new_v4();
+
+        // Add historical tasks
+        for _ in 0..N {
+            let task = mock_task_descriptor(account_id);
+            let (task_id, waiter) = state.append_task(task).await.unwrap();
+            waiter.await.unwrap();
+
+            let waiter = state.pop_task(task_id, true /* success */).await.unwrap();
+            waiter.await.unwrap();
+        }
+
+        // Add a running task, which the filter should exclude
+        let task = mock_task_descriptor(account_id);
+        let (running_id, waiter) = state.append_task(task).await.unwrap();
+        waiter.await.unwrap();
+
+        // Page through the completed tasks
+        let filter =
+            TaskHistoryFilter { status: Some(TaskStatus::Completed), ..Default::default() };
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = state
+                .get_task_history_page(&account_id, filter.clone(), cursor, PAGE_SIZE)
+                .await
+                .unwrap();
+            assert!(page.len() <= PAGE_SIZE);
+            seen.extend(page);
+
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(seen.len(), N);
+        assert!(seen.iter().all(|t| t.state == QueuedTaskState::Completed));
+        assert!(seen.iter().all(|t| t.id != running_id));
+    }
+
+    /// Tests that recorded gas costs outlive the task's place in the queue and
+    /// are aggregated
+    #[tokio::test]
+    async fn test_record_task_gas_costs() {
+        let state = mock_state().await;
+        let account_id = AccountId::new_v4();
+        let cost = ChainSubmission {
+            tx_hash: "0x01".to_string(),
+            gas_used: 100,
+            effective_gas_price: 2,
+            fee_paid: 200,
+        };
+
+        // Record the costs of a running task, then complete it
+        let task = mock_task_descriptor(account_id);
+        let task_type = task.display_description();
+        let (task_id, waiter) = state.append_task(task).await.unwrap();
+        waiter.await.unwrap();
+
+        let costs = vec![cost.clone()];
+        let waiter = state
+            .record_task_gas_costs(task_id, task_type.clone(), vec![account_id], costs)
+            .await
+            .unwrap();
+        waiter.await.unwrap();
+
+        let waiter = state.pop_task(task_id, true /* success */).await.unwrap();
+        waiter.await.unwrap();
+
+        // Check the task's costs and the aggregates
+        let task_costs = state.get_task_gas_costs(&task_id).await.unwrap();
+        assert_eq!(task_costs, vec![cost]);
+
+        let totals = state.get_account_gas_costs(&account_id).await.unwrap();
+        assert_eq!(totals, GasCostTotals { num_submissions: 1, gas_used: 100, fee_paid: 200 });
+        let by_type = state.get_task_type_gas_costs().await.unwrap();
+        assert_eq!(by_type, vec![(task_type, totals)]);
+    }
 }
@@ -0,0 +1,40 @@
+//! Periodic task queue metrics sampling for state.
+
+use std::time::Duration;
+
+use renegade_metrics::labels::TASK_QUEUE_DEPTH_METRIC;
+use system_clock::SystemClock;
+
+use crate::{StateInner, error::StateError};
+
+/// The frequency with which to sample task queue metrics.
+const TASK_QUEUE_METRICS_SAMPLE_INTERVAL_MS: u64 = 10_000; // 10 seconds
+
+impl StateInner {
+    /// Periodically samples the task queues and emits a gauge metric for the
+    /// total number of queued tasks.
+    pub(super) async fn setup_task_queue_metrics_timer(
+        &self,
+        clock: &SystemClock,
+    ) -> Result<(), StateError> {
+        let duration = Duration::from_millis(TASK_QUEUE_METRICS_SAMPLE_INTERVAL_MS);
+        let name = "task-queue-metrics-sampler-loop".to_string();
+        let this = self.clone();
+
+        clock
+            .add_async_timer(name, duration, move || {
+                let this = this.clone();
+                async move {
+                    let depth = this
+                        .get_task_queue_depth()
+                        .await
+                        .map_err(|e| format!("get_task_queue_depth: {e}"))?;
+
+                    metrics::gauge!(TASK_QUEUE_DEPTH_METRIC).set(depth as f64);
+                    Ok(())
+                }
+            })
+            .await
+            .map_err(StateError::Clock)
+    }
+}
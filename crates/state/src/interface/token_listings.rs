@@ -0,0 +1,52 @@
+//! Interface methods for tokens listed at runtime
+
+use types_core::TokenListing;
+use util::{log_task, logging::Outcome};
+
+use crate::{
+    StateInner, error::StateError, logging::Task, notifications::ProposalWaiter,
+    state_transition::StateTransition,
+};
+
+impl StateInner {
+    // -----------
+    // | Getters |
+    // -----------
+
+    /// Get all tokens listed at runtime
+    pub async fn get_token_listings(&self) -> Result<Vec<TokenListing>, StateError> {
+        self.with_read_tx(move |tx| {
+            let listings = tx.get_token_listings()?;
+            Ok(listings)
+        })
+        .await
+    }
+
+    /// Apply the tokens listed at runtime to the local token mapping
+    ///
+    /// Called once the token mapping is set up, at startup or after it is
+    /// refreshed, as setting up the mapping overwrites the listed tokens. A
+    /// listing that no longer applies, e.g. because the mapping has since
+    /// assigned its address to another token, is skipped
+    pub async fn apply_token_listings(&self) -> Result<(), StateError> {
+        for listing in self.get_token_listings().await? {
+            if let Err(e) = config::add_token(&listing) {
+                log_task!(Task::TokenListing, Outcome::Skipped, ticker = %listing.ticker, error = %e, "token listing conflicts with token mapping");
+            }
+        }
+
+        Ok(())
+    }
+
+    // -----------
+    // | Setters |
+    // -----------
+
+    /// List a token across the cluster
+    pub async fn add_token_listing(
+        &self,
+        listing: TokenListing,
+    ) -> Result<ProposalWaiter, StateError> {
+        self.send_proposal(StateTransition::AddTokenListing { listing }).await
+    }
+}
@@ -50,7 +50,7 @@ pub fn ciborium_deserialize<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Res
 // -------------
 
 /// The number of tables to open in the database
-const NUM_TABLES: usize = 20;
+const NUM_TABLES: usize = 27;
 
 /// The name of the db table that stores node metadata
 pub(crate) const NODE_METADATA_TABLE: &str = "node-metadata";
@@ -91,6 +91,24 @@ pub(crate) const TASK_TO_KEY_TABLE: &str = "task-to-key";
 pub(crate) const TASK_ASSIGNMENT_TABLE: &str = "task-assignments";
 /// The name of the db table that stores historical task information
 pub(crate) const TASK_HISTORY_TABLE: &str = "task-history";
+/// The name of the db table that stores aggregate gas costs of on-chain
+/// submissions
+pub(crate) const GAS_COSTS_TABLE: &str = "gas-costs";
+
+/// The name of the db table that stores cluster-wide settlement locks on orders
+pub(crate) const SETTLEMENT_LOCKS_TABLE: &str = "settlement-locks";
+/// The name of the db table that stores the local node's match attempt audit
+/// trail
+pub(crate) const MATCH_AUDIT_TABLE: &str = "match-audit";
+/// The name of the db table that stores the local node's price history
+pub(crate) const PRICE_HISTORY_TABLE: &str = "price-history";
+
+/// The name of the db table that stores runtime feature flags
+pub(crate) const FEATURE_FLAGS_TABLE: &str = "feature-flags";
+/// The name of the db table that stores matching blackout windows
+pub(crate) const BLACKOUT_WINDOWS_TABLE: &str = "blackout-windows";
+/// The name of the db table that stores tokens listed at runtime
+pub(crate) const TOKEN_LISTINGS_TABLE: &str = "token-listings";
 
 /// The name of the db table that stores the offline phase values
 pub(crate) const MPC_PREPROCESSING_TABLE: &str = "mpc-preprocessing";
@@ -105,7 +123,11 @@ pub const RAFT_LOGS_TABLE: &str = "raft-logs";
 /// All tables in the database
 pub const ALL_TABLES: [&str; NUM_TABLES] = [
     ACCOUNTS_TABLE,
+    BLACKOUT_WINDOWS_TABLE,
     CLUSTER_MEMBERSHIP_TABLE,
+    FEATURE_FLAGS_TABLE,
+    GAS_COSTS_TABLE,
+    MATCH_AUDIT_TABLE,
     MERKLE_PROOFS_TABLE,
     MPC_PREPROCESSING_TABLE,
     NODE_METADATA_TABLE,
@@ -115,15 +137,18 @@ pub const ALL_TABLES: [&str; NUM_TABLES] = [
     ORDERS_TABLE,
     PEER_INFO_TABLE,
     POOL_TABLE,
+    PRICE_HISTORY_TABLE,
     PRIORITIES_TABLE,
     PROOFS_TABLE,
     RAFT_LOGS_TABLE,
     RAFT_METADATA_TABLE,
     RELAYER_FEES_TABLE,
+    SETTLEMENT_LOCKS_TABLE,
     TASK_ASSIGNMENT_TABLE,
     TASK_HISTORY_TABLE,
     TASK_QUEUE_TABLE,
     TASK_TO_KEY_TABLE,
+    TOKEN_LISTINGS_TABLE,
 ];
 
 // ---------
@@ -151,8 +176,9 @@ pub mod test_helpers {
     use matching_engine_core::MatchingEngine;
 
     use crate::{
-        State, StateConfig, StateInner,
+        SafeMode, State, StateConfig, StateInner,
         notifications::OpenNotifications,
+        proposal_batcher::ProposalBatcher,
         replication::{
             RaftNode, get_raft_id,
             mock_raft::{MockRaft, MockRaftNode, mock_raft_config},
@@ -304,13 +330,17 @@ pub mod test_helpers {
         let db = node.clone_db();
         let config = StateConfig::new(&RelayerConfig { allow_local: true, ..Default::default() });
         let matching_engine = MatchingEngine::new();
+        let notifications = OpenNotifications::new();
+        let proposal_batcher = ProposalBatcher::new(client.clone(), notifications.clone());
         let state = StateInner {
             config,
             matching_engine,
             db,
             raft: client,
             bus: SystemBus::new(),
-            notifications: OpenNotifications::new(),
+            notifications,
+            proposal_batcher,
+            safe_mode: SafeMode::default(),
         };
 
         // Configure the node
@@ -30,6 +30,12 @@ pub(crate) enum Task {
     OrderBookUpdate,
     /// Applying account index state transitions.
     AccountIndexUpdate,
+    /// Cross-checking and repairing the state's indices.
+    ConsistencyCheck,
+    /// Exporting tables of the state to analytics formats.
+    StateExport,
+    /// Applying tokens listed at runtime to the token mapping.
+    TokenListing,
 }
 
 impl LogTask for Task {
@@ -45,6 +51,9 @@ impl LogTask for Task {
             Task::TaskQueue => "task-queue",
             Task::OrderBookUpdate => "order-book-update",
             Task::AccountIndexUpdate => "account-index-update",
+            Task::ConsistencyCheck => "consistency-check",
+            Task::StateExport => "state-export",
+            Task::TokenListing => "token-listing",
         }
     }
 }
@@ -68,6 +68,18 @@ impl OpenNotifications {
         }
     }
 
+    /// Fail every open proposal with an error built by the given function
+    ///
+    /// Used when the proposals can no longer be expected to apply, e.g. once
+    /// the raft quorum is lost. The proposals may still commit if the quorum
+    /// is regained, so callers should treat the error as an unknown outcome
+    pub async fn fail_all(&self, err: impl Fn() -> StateError) {
+        let open = std::mem::take(&mut *self.map.write().await);
+        for sender in open.into_values() {
+            let _ = sender.send(Err(err()));
+        }
+    }
+
     /// Add a waiter for the given proposal, returns the channel to wait on
     pub async fn register_notification(&self, id: ProposalId) -> ProposalResultReceiver {
         let (sender, receiver) = new_proposal_result_channel();
@@ -112,3 +124,27 @@ impl Future for ProposalWaiter {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::error::StateError;
+
+    use super::{OpenNotifications, ProposalWaiter};
+
+    /// Tests that failing the open proposals resolves their waiters
+    #[tokio::test]
+    async fn test_fail_all() {
+        let notifications = OpenNotifications::new();
+        let waiters = [
+            ProposalWaiter::new(notifications.register_notification(Uuid::new_v4()).await),
+            ProposalWaiter::new(notifications.register_notification(Uuid::new_v4()).await),
+        ];
+
+        notifications.fail_all(|| StateError::QuorumLost).await;
+        for waiter in waiters {
+            assert!(matches!(waiter.await, Err(StateError::QuorumLost)));
+        }
+    }
+}
@@ -66,6 +66,8 @@ const CHANGE_MEMBERSHIP_TIMEOUT_MS: u64 = 5_000;
 const DEFAULT_PROMOTION_TIMEOUT_MS: u64 = 5 * 60 * 1000;
 /// The amount of time to await a leader election before timing out
 const DEFAULT_LEADER_ELECTION_TIMEOUT_MS: u64 = 30_000; // 30 seconds
+/// The amount of time to wait for a snapshot to build when flushing the raft
+const DEFAULT_SNAPSHOT_FLUSH_TIMEOUT_MS: u64 = 30_000; // 30 seconds
 /// The default max chunk size for snapshots
 const DEFAULT_SNAPSHOT_MAX_CHUNK_SIZE: u64 = 10 * 1024 * 1024; // 10MiB
 /// The default max number of logs to keep in a snapshot
@@ -430,6 +432,22 @@ impl RaftClient {
         self.raft().trigger().snapshot().await.map_err(err_str!(ReplicationError::Raft))
     }
 
+    /// Snapshot the applied state machine, waiting for the snapshot to build
+    ///
+    /// Used at shutdown so that a restart recovers from the snapshot rather than
+    /// replaying the log
+    pub async fn flush_snapshot(&self) -> Result<(), ReplicationError> {
+        self.trigger_snapshot().await?;
+
+        let timeout = Duration::from_millis(DEFAULT_SNAPSHOT_FLUSH_TIMEOUT_MS);
+        self.raft
+            .wait(Some(timeout))
+            .metrics(|m| m.snapshot >= m.last_applied, "snapshot-flush")
+            .await
+            .map_err(err_str!(ReplicationError::Raft))
+            .map(|_| ())
+    }
+
     // -------------
     // | Proposals |
     // -------------
@@ -26,7 +26,6 @@ use crate::{
     logging::Task,
     notifications::OpenNotifications,
     replication::error::new_apply_error,
-    state_transition::Proposal,
     storage::db::DB,
 };
 
@@ -41,6 +40,10 @@ pub(crate) const SNAPSHOT_FILE: &str = "snapshot.dat";
 pub(crate) const SNAPSHOT_ZIP: &str = "snapshot.gz";
 /// The snapshot lock file name
 pub(crate) const SNAPSHOT_LOCK: &str = "snapshot.lock";
+/// The name of the file holding the raft log index of the snapshot zip
+///
+/// Read by the snapshot sidecar to identify which snapshot it is uploading
+pub(crate) const SNAPSHOT_INDEX: &str = "snapshot.index";
 
 /// Get the path to the snapshot data file
 pub(crate) fn snapshot_data_path(snapshot_dir: &str) -> PathBuf {
@@ -145,6 +148,12 @@ impl StateMachine {
         dir.join(SNAPSHOT_LOCK)
     }
 
+    /// Get the path to the snapshot index file
+    pub fn snapshot_index_path(&self) -> PathBuf {
+        let dir = Path::new(&self.config.snapshot_out);
+        dir.join(SNAPSHOT_INDEX)
+    }
+
     /// Create the snapshot directory if it doesn't exist
     pub async fn create_snapshot_dir(&self) -> Result<(), ReplicationError> {
         let snap_dir = self.snapshot_dir();
@@ -182,6 +191,17 @@ impl StateMachine {
         tokio::fs::remove_file(&snapshot_lock).await.map_err(err_str!(ReplicationError::Snapshot))
     }
 
+    /// Write the raft log index of the snapshot zip to the snapshot index file
+    ///
+    /// Must be written before the lock is removed, so that the index always
+    /// describes the zip once the lock is released
+    pub async fn write_snapshot_index(&self, index: u64) -> Result<(), ReplicationError> {
+        let path = self.snapshot_index_path();
+        tokio::fs::write(&path, index.to_string())
+            .await
+            .map_err(err_str!(ReplicationError::Snapshot))
+    }
+
     /// Open the file containing the snapshot
     pub async fn open_snapshot_file(&self) -> Result<Option<File>, ReplicationError> {
         let snapshot_path = self.snapshot_archive_path();
@@ -222,46 +242,51 @@ impl RaftStateMachine<TypeConfig> for StateMachine {
                     self.last_membership = StoredMembership::new(Some(log_id), membership);
                 },
                 EntryPayload::Normal(proposal) => {
-                    let Proposal { id, transition } = proposal;
-
-                    // DB methods will naturally block the applicator without throwing an error, so
-                    // we must spawn a blocking thread for each update
-                    let applicator = self.applicator.clone();
-                    let res = tokio::task::spawn_blocking(move || {
-                        applicator.handle_state_transition(transition)
-                    })
-                    .await
-                    .map_err(|e| new_apply_error(log_id, e))?;
-
-                    match res {
-                        Err(StateApplicatorError::Rejected(msg)) => {
-                            // Surface the rejection in the logs. It is otherwise only sent to the
-                            // proposal waiter (often disconnected), so app-level issues -- e.g. an
-                            // order placed into a nonexistent matching pool, or a stale /
-                            // over-committed match -- stay invisible until they escalate to a
-                            // fatal error. Logging here makes them detectable directly.
-                            log_task!(
-                                Task::Proposal,
-                                Outcome::Failed,
-                                error = %msg,
-                                "state transition rejected at apply"
-                            );
-                            self.notifications
-                                .notify(id, Err(StateError::TransitionRejected(msg)))
-                                .await;
-                        },
-                        Err(err) => {
-                            // If the state machine failed to apply the state transition, notify the
-                            // client & propagate the error
-                            let err_str = err.to_string();
-                            self.notifications.notify(id, Err(StateError::Applicator(err))).await;
-                            return Err(new_apply_error(log_id, err_str));
-                        },
-                        res => {
-                            self.notifications
-                                .notify(id, res.map_err(StateError::Applicator))
-                                .await;
-                        },
+                    // A single entry may carry several coalesced proposals, each of which is
+                    // applied and notified independently
+                    for (id, transition) in proposal.into_parts() {
+                        // DB methods will naturally block the applicator without throwing an
+                        // error, so we must spawn a blocking thread for each update
+                        let applicator = self.applicator.clone();
+                        let res = tokio::task::spawn_blocking(move || {
+                            applicator.handle_state_transition(transition)
+                        })
+                        .await
+                        .map_err(|e| new_apply_error(log_id, e))?;
+
+                        match res {
+                            Err(StateApplicatorError::Rejected(msg)) => {
+                                // Surface the rejection in the logs. It is otherwise only sent to
+                                // the proposal waiter (often disconnected), so app-level issues --
+                                // e.g. an order placed into a nonexistent matching pool, or a
+                                // stale / over-committed match -- stay invisible until they
+                                // escalate to a fatal error. Logging here makes them detectable
+                                // directly.
+                                log_task!(
+                                    Task::Proposal,
+                                    Outcome::Failed,
+                                    error = %msg,
+                                    "state transition rejected at apply"
+                                );
+                                self.notifications
+                                    .notify(id, Err(StateError::TransitionRejected(msg)))
+                                    .await;
+                            },
+                            Err(err) => {
+                                // If the state machine failed to apply the state transition,
+                                // notify the client & propagate the error
+                                let err_str = err.to_string();
+                                self.notifications
+                                    .notify(id, Err(StateError::Applicator(err)))
+                                    .await;
+                                return Err(new_apply_error(log_id, err_str));
+                            },
+                            res => {
+                                self.notifications
+                                    .notify(id, res.map_err(StateError::Applicator))
+                                    .await;
+                            },
+                        }
                     }
                 },
             }
@@ -413,4 +438,42 @@ mod test {
         let res = rx.await.unwrap();
         assert!(res.is_ok());
     }
+
+    /// Tests that every proposal coalesced into a single entry is applied and
+    /// notified
+    #[tokio::test]
+    async fn test_apply_coalesced_proposals() {
+        let mut sm = mock_state_machine().await;
+        let notifs = sm.notifications.clone();
+
+        // Coalesce three proposals, the second of which is rejected
+        let pool_name = "test-pool".to_string();
+        let missing_account = StateTransition::RecordAccountMatchVolume {
+            account_id: uuid::Uuid::new_v4(),
+            volume: 1,
+            timestamp: 0,
+        };
+        let proposals = vec![
+            Proposal::from(StateTransition::CreateMatchingPool { pool_name: pool_name.clone() }),
+            Proposal::from(missing_account),
+            Proposal::from(StateTransition::DestroyMatchingPool { pool_name }),
+        ];
+
+        let mut receivers = Vec::new();
+        for prop in proposals.iter() {
+            receivers.push(notifs.register_notification(prop.id).await);
+        }
+        let prop = Proposal::coalesce(proposals).unwrap();
+        assert_eq!(prop.ids().len(), 3);
+
+        let leader_id = LeaderId::new(1 /* term */, 1 /* node */);
+        let log_id = LogId::new(leader_id, 1 /* index */);
+        let entry = Entry { log_id, payload: EntryPayload::Normal(prop) };
+        sm.apply(vec![entry]).await.unwrap();
+
+        let results: Vec<_> = futures::future::join_all(receivers).await;
+        assert!(results[0].as_ref().unwrap().is_ok());
+        assert!(results[1].as_ref().unwrap().is_err());
+        assert!(results[2].as_ref().unwrap().is_ok());
+    }
 }
@@ -20,8 +20,8 @@ use crate::logging::Task;
 use crate::replication::error::{ReplicationError, new_snapshot_error};
 use crate::storage::db::{DB, DbConfig};
 use crate::{
-    ALL_TABLES, CLUSTER_MEMBERSHIP_TABLE, NODE_METADATA_TABLE, PEER_INFO_TABLE, RAFT_LOGS_TABLE,
-    RAFT_METADATA_TABLE, RELAYER_FEES_TABLE,
+    ALL_TABLES, CLUSTER_MEMBERSHIP_TABLE, MATCH_AUDIT_TABLE, NODE_METADATA_TABLE, PEER_INFO_TABLE,
+    PRICE_HISTORY_TABLE, RAFT_LOGS_TABLE, RAFT_METADATA_TABLE, RELAYER_FEES_TABLE,
 };
 
 use super::{Node, NodeId, StateMachine, TypeConfig};
@@ -39,6 +39,8 @@ pub(crate) const EXCLUDED_TABLES: &[&str] = &[
     CLUSTER_MEMBERSHIP_TABLE,
     NODE_METADATA_TABLE,
     RELAYER_FEES_TABLE,
+    MATCH_AUDIT_TABLE,
+    PRICE_HISTORY_TABLE,
 ];
 
 /// An error awaiting a blocking zip task
@@ -89,7 +91,10 @@ impl RaftSnapshotBuilder<TypeConfig> for StateMachine {
             RaftStorageError::from_io_error(ErrorSubject::Snapshot(None), ErrorVerb::Read, err)
         })?;
 
-        // Remove the lock file, store the metadata and return
+        // Record the snapshot's index, remove the lock file, store the metadata and
+        // return
+        let index = info.last_log.map(|l| l.index).unwrap_or_default();
+        self.write_snapshot_index(index).await.map_err(new_snapshot_error)?;
         self.delete_snapshot_lock().await.map_err(new_snapshot_error)?;
         self.write_snapshot_metadata(&meta).map_err(new_snapshot_error)?;
         Ok(Snapshot { meta, snapshot: Box::new(snapshot_file) })
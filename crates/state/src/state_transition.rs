@@ -4,15 +4,17 @@
 //! generates archived types with undocumented fields.
 #![allow(missing_docs)]
 
+use circuit_types::Amount;
 use serde::{Deserialize, Serialize};
 use types_account::{
-    Account, MatchingPoolName, MerkleAuthenticationPath, OrderRefreshData, account::OrderId,
-    balance::Balance, keychain::KeyChain, order::Order, order_auth::OrderAuth,
+    Account, MatchingPoolName, MerkleAuthenticationPath, OrderRefreshData, TenantId,
+    account::OrderId, balance::Balance, keychain::KeyChain, order::Order, order_auth::OrderAuth,
+    risk::AccountRiskConfig, sweep::AccountSweepPolicy,
 };
-use types_core::AccountId;
+use types_core::{AccountId, BlackoutWindow, HmacKey, TokenListing};
 use types_gossip::WrappedPeerId;
 use types_proofs::{ValidityProofBundle, ValidityProofLocator};
-use types_tasks::{QueuedTask, QueuedTaskState, TaskIdentifier, TaskQueueKey};
+use types_tasks::{ChainSubmission, QueuedTask, QueuedTaskState, TaskIdentifier, TaskQueueKey};
 use uuid::Uuid;
 
 use crate::{
@@ -38,9 +40,54 @@ pub struct Proposal {
     pub transition: Box<StateTransition>,
 }
 
+impl Proposal {
+    /// Coalesce a set of proposals into a single proposal, applied in the
+    /// given order
+    ///
+    /// A single proposal is returned as is. Returns `None` if the set is empty
+    pub fn coalesce(mut proposals: Vec<Proposal>) -> Option<Proposal> {
+        match proposals.len() {
+            0 => None,
+            1 => proposals.pop(),
+            _ => Some(Proposal::from(StateTransition::Batch(proposals))),
+        }
+    }
+
+    /// The IDs of all proposals applied by this proposal's raft entry
+    pub fn ids(&self) -> Vec<ProposalId> {
+        match self.transition.as_ref() {
+            StateTransition::Batch(proposals) => proposals.iter().flat_map(Self::ids).collect(),
+            _ => vec![self.id],
+        }
+    }
+
+    /// Split the proposal into the transitions it applies, in order, along
+    /// with the IDs of the proposals they belong to
+    pub fn into_parts(self) -> Vec<(ProposalId, Box<StateTransition>)> {
+        match *self.transition {
+            StateTransition::Batch(proposals) => {
+                proposals.into_iter().flat_map(Self::into_parts).collect()
+            },
+            _ => vec![(self.id, self.transition)],
+        }
+    }
+}
+
 /// The `StateTransitionType` encapsulates all possible state transitions,
 /// allowing transitions to be handled generically before they are applied
+///
+/// The raft log stores transitions by their variant index, so new variants
+/// must be appended after the existing ones
 #[derive(Clone, Debug, Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
 #[rustfmt::skip]
 pub enum StateTransition {
     // --- Accounts --- //
@@ -126,6 +173,119 @@ pub enum StateTransition {
     AddRaftVoters { peer_ids: Vec<NodeId> },
     /// Remove a raft peer from the local consensus cluster
     RemoveRaftPeers { peer_ids: Vec<NodeId> },
+
+    // --- Settlement Locks --- //
+    /// Acquire a cluster-wide settlement lock on a set of orders
+    ///
+    /// Rejected if any of the orders is locked by another peer and the lock has
+    /// not expired as of `acquired_at`
+    AcquireSettlementLock { order_ids: Vec<OrderId>, holder: WrappedPeerId, acquired_at: u64 },
+    /// Release the settlement locks held by the given peer on a set of orders
+    ReleaseSettlementLock { order_ids: Vec<OrderId>, holder: WrappedPeerId },
+
+    // --- Account Risk --- //
+    /// Set or clear an account's risk config
+    SetAccountRiskConfig { account_id: AccountId, config: Option<AccountRiskConfig> },
+    /// Record matched volume against an account's daily volume total
+    RecordAccountMatchVolume { account_id: AccountId, volume: Amount, timestamp: u64 },
+
+    // --- Batches --- //
+    /// A batch of proposals coalesced into a single raft entry, each of which
+    /// is applied and notified in order as if proposed alone
+    Batch(#[rkyv(omit_bounds)] Vec<Proposal>),
+
+    // --- Gas Costs --- //
+    /// Record the gas costs of a task's on-chain submissions against the task and
+    /// the aggregate totals of its type and affected accounts
+    RecordTaskGasCosts {
+        task_id: TaskIdentifier,
+        task_type: String,
+        account_ids: Vec<AccountId>,
+        costs: Vec<ChainSubmission>,
+    },
+
+    // --- Feature Flags --- //
+    /// Set the value of a feature flag, given by name
+    SetFeatureFlag { flag: String, enabled: bool },
+
+    // --- Task Queue Pausing --- //
+    /// Pause a task queue on behalf of an operator; running tasks are left to
+    /// finish but no further tasks are started
+    PauseTaskQueue { queue: TaskQueueKey },
+    /// Resume a task queue paused by an operator, starting its next task
+    ResumeTaskQueue { queue: TaskQueueKey },
+
+    // --- Peer Pinning --- //
+    /// Set or clear the peers an account has pinned for executing its tasks and
+    /// matching its orders
+    SetAccountPinnedPeers { account_id: AccountId, peers: Option<Vec<WrappedPeerId>> },
+    /// Reassign all tasks from one peer to another peer
+    ///
+    /// Tasks listed in `pinned` are instead assigned to the given peer, which
+    /// the proposer selected from the task's account's pinned peers
+    ReassignTasksPinned { from: WrappedPeerId, to: WrappedPeerId, pinned: Vec<(TaskIdentifier, WrappedPeerId)> },
+
+    // --- API Keys --- //
+    /// Set or revoke an API key for an account
+    ///
+    /// The secret is generated by the proposer so that every replica stores the
+    /// same key
+    SetAccountApiKey { account_id: AccountId, key_id: Uuid, secret: Option<HmacKey> },
+
+    // --- Consistency --- //
+    /// Repair the inconsistencies between the state's tables and indices
+    ///
+    /// Each replica checks its own state when applying the transition, so the
+    /// repair is made against the same log position everywhere
+    RepairStateConsistency,
+
+    // --- Account Versions --- //
+    /// Advance an account's version, rejected if `expected` is given and is not
+    /// the account's current version
+    AdvanceAccountVersion { account_id: AccountId, expected: Option<u64> },
+
+    // --- Task Batches --- //
+    /// Add a set of tasks to the task queue, all or none of them
+    AppendTasks { tasks: Vec<QueuedTask>, executor: WrappedPeerId },
+
+    // --- Tenants --- //
+    /// Claim an account for a tenant, or outside of any tenant if `tenant` is
+    /// `None`
+    ///
+    /// The claim precedes the account's creation, so that an account created
+    /// by a tenant is never visible outside of it, and an account created
+    /// outside of a tenant cannot be taken over by one
+    SetAccountTenant { account_id: AccountId, tenant: Option<TenantId> },
+
+    // --- Token Listings --- //
+    /// List a token in addition to those in the token mapping
+    AddTokenListing { listing: TokenListing },
+
+    // --- Balance Sweeps --- //
+    /// Set or clear an account's balance sweep policy
+    SetAccountSweepPolicy { account_id: AccountId, policy: Option<AccountSweepPolicy> },
+
+    // --- Blackout Windows --- //
+    /// Add a window during which no new matches are made
+    AddBlackoutWindow { window: BlackoutWindow },
+    /// Remove a blackout window
+    RemoveBlackoutWindow { id: Uuid },
+
+    // --- Balance Credits --- //
+    /// Add the amount of the given balance to the account's balance of the
+    /// same mint and location, storing the given balance if there is none
+    CreditAccountBalance { account_id: AccountId, credit: Balance },
+}
+
+impl StateTransition {
+    /// Whether the transition may be coalesced with others into a single raft
+    /// entry
+    ///
+    /// Only writes that are independent of the proposals around them are
+    /// batched, as coalescing delays a proposal by up to the batching window
+    pub fn is_batchable(&self) -> bool {
+        matches!(self, Self::AddValidityProof { .. } | Self::AddMerkleProof { .. })
+    }
 }
 
 impl From<StateTransition> for Proposal {
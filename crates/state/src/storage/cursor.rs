@@ -141,7 +141,15 @@ impl<'txn, Tx: TransactionKind, K: Key, V: Value> DbCursor<'txn, Tx, K, V> {
     /// otherwise.
     pub fn seek(&mut self, k: &K) -> Result<bool, StorageError> {
         let k_bytes = k.rkyv_serialize()?;
-        match self.inner.set_range::<Self::TxBytes, Self::TxBytes>(&k_bytes) {
+        self.seek_raw(&k_bytes)
+    }
+
+    /// Position the cursor at the first key whose serialized bytes are >= the
+    /// given bytes
+    ///
+    /// Returns true if a matching key was found, false otherwise
+    pub fn seek_raw(&mut self, k_bytes: &[u8]) -> Result<bool, StorageError> {
+        match self.inner.set_range::<Self::TxBytes, Self::TxBytes>(k_bytes) {
             Ok(Some(_)) => Ok(true),
             Ok(None) => Ok(false),
             Err(MdbxError::NoData | MdbxError::NotFound) => Ok(false),
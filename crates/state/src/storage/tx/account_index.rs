@@ -8,17 +8,22 @@
 
 use alloy_primitives::{Address, B256};
 use circuit_types::Amount;
+use darkpool_types::{intent::Intent, state_wrapper::StateWrapper};
 use libmdbx::{RW, TransactionKind};
 use serde::{Deserialize, Serialize};
 use types_account::{
-    MatchingPoolName,
+    MatchingPoolName, TenantId,
     account::{Account, OrderId},
     balance::{Balance, BalanceLocation},
     keychain::KeyChain,
-    order::Order,
+    order::{Order, OrderMetadata, PrivacyRing},
+    risk::AccountRiskConfig,
+    sweep::AccountSweepPolicy,
 };
-use types_core::AccountId;
+use types_core::{AccountId, HmacKey};
+use types_gossip::WrappedPeerId;
 use util::res_some;
+use uuid::Uuid;
 
 use crate::{
     ACCOUNTS_TABLE,
@@ -57,6 +62,75 @@ pub struct AccountHeader {
     pub keychain: KeyChain,
 }
 
+/// The notional volume an account has matched in a single day
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub struct DailyMatchVolume {
+    /// The day index the volume was matched in, see `risk::volume_day`
+    pub day: u64,
+    /// The notional volume matched in the day
+    pub volume: Amount,
+}
+
+/// The name of the stored order layout, see `migrate_legacy_orders`
+pub(crate) const ORDER_LAYOUT: &str = "order";
+/// The current version of the stored order layout
+///
+/// Version 1 added the client order id and tags to the order metadata
+pub(crate) const ORDER_LAYOUT_VERSION: u32 = 1;
+
+/// The stored layout of an order before its metadata carried a client order
+/// id and tags
+///
+/// Only read when migrating orders written by earlier versions
+#[derive(Clone, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+struct LegacyOrder {
+    /// The id of the order
+    id: OrderId,
+    /// The intent
+    intent: StateWrapper<Intent>,
+    /// The privacy ring in which the intent is allocated
+    ring: PrivacyRing,
+    /// The metadata for the order
+    metadata: LegacyOrderMetadata,
+}
+
+/// The stored layout of the order metadata before it carried a client order id
+/// and tags
+#[derive(Clone, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+struct LegacyOrderMetadata {
+    /// The minimum fill size for the order
+    min_fill_size: Amount,
+    /// Whether or not to allow external matches
+    allow_external_matches: bool,
+    /// Whether the order has received at least one fill
+    has_been_filled: bool,
+}
+
+impl From<LegacyOrder> for Order {
+    fn from(legacy: LegacyOrder) -> Self {
+        let LegacyOrderMetadata { min_fill_size, allow_external_matches, has_been_filled } =
+            legacy.metadata;
+        let mut metadata = OrderMetadata::new(min_fill_size, allow_external_matches);
+        metadata.has_been_filled = has_been_filled;
+        Order::new_with_ring(legacy.id, legacy.intent, metadata, legacy.ring)
+    }
+}
+
 /// Type alias for an archived account header value with transaction lifetime
 pub type AccountHeaderValue<'a> = ArchivedValue<'a, AccountHeader>;
 /// Type alias for an archived order value with transaction lifetime
@@ -74,7 +148,7 @@ fn account_header_key(account_id: &AccountId) -> String {
 }
 
 /// Build the key for an order
-fn order_key(account_id: &AccountId, order_id: &OrderId) -> String {
+pub(crate) fn order_key(account_id: &AccountId, order_id: &OrderId) -> String {
     format!("{account_id}:orders:{order_id}")
 }
 
@@ -93,9 +167,12 @@ fn balances_prefix(account_id: &AccountId) -> String {
     format!("{account_id}:balances:")
 }
 
+/// The prefix of the order -> account index keys
+pub(crate) const ORDER_INDEX_PREFIX: &str = "order_index:";
+
 /// Build the key for the order -> account index
 fn order_index_key(order_id: &OrderId) -> String {
-    format!("order_index:{order_id}")
+    format!("{ORDER_INDEX_PREFIX}{order_id}")
 }
 
 /// Build the key for the owner -> account index
@@ -117,6 +194,61 @@ fn default_pool_key(account_id: &AccountId) -> String {
     format!("{account_id}:default_pool")
 }
 
+/// Build the key for an account's risk config
+fn risk_config_key(account_id: &AccountId) -> String {
+    format!("{account_id}:risk_config")
+}
+
+/// Build the key for an account's sweep policy
+fn sweep_policy_key(account_id: &AccountId) -> String {
+    format!("{account_id}:sweep_policy")
+}
+
+/// Build the key marking that a sweep has been requested for one of an
+/// account's balances
+fn sweep_pending_key(account_id: &AccountId, mint: &Address) -> String {
+    format!("{account_id}:sweep_pending:{mint}")
+}
+
+/// Build the key for an account's pinned peers
+fn pinned_peers_key(account_id: &AccountId) -> String {
+    format!("{account_id}:pinned_peers")
+}
+
+/// Build the key for an account's API keys
+fn api_keys_key(account_id: &AccountId) -> String {
+    format!("{account_id}:api_keys")
+}
+
+/// Build the key for an account's daily matched volume
+fn daily_volume_key(account_id: &AccountId) -> String {
+    format!("{account_id}:daily_volume")
+}
+
+/// Build the key for the time of an account's last match
+fn last_match_key(account_id: &AccountId) -> String {
+    format!("{account_id}:last_match")
+}
+
+/// Build the key for an account's version, advanced on each mutation
+/// enqueued through the API
+fn version_key(account_id: &AccountId) -> String {
+    format!("{account_id}:version")
+}
+
+/// Build the key for the tenant claim on an account
+fn tenant_key(account_id: &AccountId) -> String {
+    format!("{account_id}:tenant")
+}
+
+/// Build the key for the tenant -> accounts index
+///
+/// Maps a tenant to the accounts that belong to it, for listing a tenant's
+/// accounts
+fn tenant_index_key(tenant: &str) -> String {
+    format!("tenant_index:{tenant}")
+}
+
 // -----------
 // | Getters |
 // -----------
@@ -138,6 +270,134 @@ impl<T: TransactionKind> StateTxn<'_, T> {
             .map(|opt| opt.map(|archived| archived.deserialize()).transpose())?
     }
 
+    /// Get the risk config for an account, if set
+    pub fn get_account_risk_config(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<AccountRiskConfig>, StorageError> {
+        let key = risk_config_key(account_id);
+        self.inner()
+            .read::<_, AccountRiskConfig>(ACCOUNTS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())?
+    }
+
+    /// Get the sweep policy for an account, if set
+    pub fn get_account_sweep_policy(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<AccountSweepPolicy>, StorageError> {
+        let key = sweep_policy_key(account_id);
+        self.inner()
+            .read::<_, AccountSweepPolicy>(ACCOUNTS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())?
+    }
+
+    /// Whether a sweep has been requested for the account's balance of the
+    /// given mint, and not yet cleared
+    pub fn is_sweep_pending(
+        &self,
+        account_id: &AccountId,
+        mint: &Address,
+    ) -> Result<bool, StorageError> {
+        let key = sweep_pending_key(account_id, mint);
+        Ok(self.inner().read::<_, bool>(ACCOUNTS_TABLE, &key)?.is_some())
+    }
+
+    /// Get the peers an account has pinned for executing its tasks and
+    /// matching its orders, if set
+    pub fn get_account_pinned_peers(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<Vec<WrappedPeerId>>, StorageError> {
+        let key = pinned_peers_key(account_id);
+        self.inner()
+            .read::<_, Vec<WrappedPeerId>>(ACCOUNTS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())?
+    }
+
+    /// Get the API keys issued for an account, as pairs of key ID and secret
+    pub fn get_account_api_keys(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<(Uuid, HmacKey)>, StorageError> {
+        let key = api_keys_key(account_id);
+        let keys = self
+            .inner()
+            .read::<_, Vec<(Uuid, HmacKey)>>(ACCOUNTS_TABLE, &key)?
+            .map(|archived| archived.deserialize())
+            .transpose()?;
+
+        Ok(keys.unwrap_or_default())
+    }
+
+    /// Get the tenant an account belongs to, if any
+    pub fn get_account_tenant(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<TenantId>, StorageError> {
+        Ok(self.get_account_tenant_claim(account_id)?.flatten())
+    }
+
+    /// Get the tenant claim on an account, if the account has been claimed
+    ///
+    /// An account claimed outside of any tenant has a claim of `Some(None)`
+    pub fn get_account_tenant_claim(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<Option<TenantId>>, StorageError> {
+        let key = tenant_key(account_id);
+        self.inner()
+            .read::<_, Option<TenantId>>(ACCOUNTS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())?
+    }
+
+    /// Get the IDs of the accounts that belong to a tenant
+    pub fn get_tenant_account_ids(&self, tenant: &str) -> Result<Vec<AccountId>, StorageError> {
+        let key = tenant_index_key(tenant);
+        let ids = self
+            .inner()
+            .read::<_, Vec<AccountId>>(ACCOUNTS_TABLE, &key)?
+            .map(|archived| archived.deserialize())
+            .transpose()?;
+
+        Ok(ids.unwrap_or_default())
+    }
+
+    /// Get the notional volume an account has matched in the given day
+    pub fn get_account_daily_volume(
+        &self,
+        account_id: &AccountId,
+        day: u64,
+    ) -> Result<Amount, StorageError> {
+        let key = daily_volume_key(account_id);
+        let stored = self.inner().read::<_, DailyMatchVolume>(ACCOUNTS_TABLE, &key)?;
+        let volume = match stored.map(|v| v.deserialize()).transpose()? {
+            Some(v) if v.day == day => v.volume,
+            _ => 0,
+        };
+
+        Ok(volume)
+    }
+
+    /// Get the time in milliseconds of an account's last match, if it has
+    /// matched
+    pub fn get_account_last_match_time(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Option<u64>, StorageError> {
+        let key = last_match_key(account_id);
+        let stored = self.inner().read::<_, u64>(ACCOUNTS_TABLE, &key)?;
+        stored.map(|ts| ts.deserialize()).transpose()
+    }
+
+    /// Get an account's version, accounts never mutated through the API are
+    /// at version zero
+    pub fn get_account_version(&self, account_id: &AccountId) -> Result<u64, StorageError> {
+        let key = version_key(account_id);
+        let stored = self.inner().read::<_, u64>(ACCOUNTS_TABLE, &key)?;
+        Ok(stored.map(|v| v.deserialize()).transpose()?.unwrap_or_default())
+    }
+
     /// Get the account header for the given ID
     pub fn get_account_header(
         &self,
@@ -447,10 +707,56 @@ impl StateTxn<'_, RW> {
         self.inner().write(ACCOUNTS_TABLE, &key, order)?;
 
         // Write the order -> account index
-        let index_key = order_index_key(&order.id);
+        self.write_order_index(&order.id, account_id)
+    }
+
+    /// Index an order to the account holding it
+    pub(crate) fn write_order_index(
+        &self,
+        order_id: &OrderId,
+        account_id: &AccountId,
+    ) -> Result<(), StorageError> {
+        let index_key = order_index_key(order_id);
         self.inner().write(ACCOUNTS_TABLE, &index_key, account_id)
     }
 
+    /// Remove an order from the order -> account index
+    pub(crate) fn delete_order_index(&self, order_id: &OrderId) -> Result<(), StorageError> {
+        let index_key = order_index_key(order_id);
+        self.inner().delete(ACCOUNTS_TABLE, &index_key).map(|_| ())
+    }
+
+    /// Rewrite the orders stored in a layout older than `ORDER_LAYOUT_VERSION`
+    ///
+    /// Orders are stored as rkyv archives, which cannot be read once a field is
+    /// added to the type. Runs once per database, recording the layout version
+    /// in the node metadata when done. Returns the number of orders migrated
+    pub fn migrate_legacy_orders(&self) -> Result<usize, StorageError> {
+        if self.get_layout_version(ORDER_LAYOUT)? >= ORDER_LAYOUT_VERSION {
+            return Ok(0);
+        }
+
+        let mut n_migrated = 0;
+        for account_id in self.get_all_account_ids()? {
+            let prefix = orders_prefix(&account_id);
+            let legacy_orders = self
+                .inner()
+                .cursor::<String, LegacyOrder>(ACCOUNTS_TABLE)?
+                .with_key_prefix(&prefix)
+                .into_iter()
+                .map(|res| res.and_then(|(_key, val)| val.deserialize()))
+                .collect::<Result<Vec<_>, StorageError>>()?;
+
+            for legacy in legacy_orders {
+                self.update_order(&account_id, &Order::from(legacy))?;
+                n_migrated += 1;
+            }
+        }
+
+        self.set_layout_version(ORDER_LAYOUT, ORDER_LAYOUT_VERSION)?;
+        Ok(n_migrated)
+    }
+
     /// Update an existing order in an account
     ///
     /// This only updates the order data, not the order->account index
@@ -497,6 +803,152 @@ impl StateTxn<'_, RW> {
         }
     }
 
+    /// Set or clear the risk config for an account
+    pub fn set_account_risk_config(
+        &self,
+        account_id: &AccountId,
+        config: Option<&AccountRiskConfig>,
+    ) -> Result<(), StorageError> {
+        let key = risk_config_key(account_id);
+        match config {
+            Some(config) => self.inner().write(ACCOUNTS_TABLE, &key, config),
+            None => self.inner().delete(ACCOUNTS_TABLE, &key).map(|_| ()),
+        }
+    }
+
+    /// Set or clear the sweep policy for an account
+    ///
+    /// Clears the sweeps pending under the previous policy, so that balances
+    /// already above their new trigger amount are swept
+    pub fn set_account_sweep_policy(
+        &self,
+        account_id: &AccountId,
+        policy: Option<&AccountSweepPolicy>,
+    ) -> Result<(), StorageError> {
+        if let Some(prev) = self.get_account_sweep_policy(account_id)? {
+            for threshold in prev.thresholds.iter() {
+                self.set_sweep_pending(account_id, &threshold.mint, false)?;
+            }
+        }
+
+        let key = sweep_policy_key(account_id);
+        match policy {
+            Some(policy) => self.inner().write(ACCOUNTS_TABLE, &key, policy),
+            None => self.inner().delete(ACCOUNTS_TABLE, &key).map(|_| ()),
+        }
+    }
+
+    /// Mark or clear a sweep request for the account's balance of the given
+    /// mint
+    pub fn set_sweep_pending(
+        &self,
+        account_id: &AccountId,
+        mint: &Address,
+        pending: bool,
+    ) -> Result<(), StorageError> {
+        let key = sweep_pending_key(account_id, mint);
+        if pending {
+            self.inner().write(ACCOUNTS_TABLE, &key, &true)
+        } else {
+            self.inner().delete(ACCOUNTS_TABLE, &key).map(|_| ())
+        }
+    }
+
+    /// Set or clear the pinned peers for an account
+    pub fn set_account_pinned_peers(
+        &self,
+        account_id: &AccountId,
+        peers: Option<&[WrappedPeerId]>,
+    ) -> Result<(), StorageError> {
+        let key = pinned_peers_key(account_id);
+        match peers {
+            Some(peers) => self.inner().write(ACCOUNTS_TABLE, &key, &peers.to_vec()),
+            None => self.inner().delete(ACCOUNTS_TABLE, &key).map(|_| ()),
+        }
+    }
+
+    /// Set or revoke an API key for an account
+    ///
+    /// Setting an existing key ID replaces its secret
+    pub fn set_account_api_key(
+        &self,
+        account_id: &AccountId,
+        key_id: Uuid,
+        secret: Option<HmacKey>,
+    ) -> Result<(), StorageError> {
+        let key = api_keys_key(account_id);
+        let mut keys = self.get_account_api_keys(account_id)?;
+        keys.retain(|(id, _)| *id != key_id);
+        if let Some(secret) = secret {
+            keys.push((key_id, secret));
+        }
+
+        if keys.is_empty() {
+            self.inner().delete(ACCOUNTS_TABLE, &key).map(|_| ())
+        } else {
+            self.inner().write(ACCOUNTS_TABLE, &key, &keys)
+        }
+    }
+
+    /// Claim an account for a tenant, adding it to the tenant's index, or
+    /// outside of any tenant if `tenant` is `None`
+    ///
+    /// Claiming an account for the tenant it already belongs to is a no-op
+    pub fn set_account_tenant(
+        &self,
+        account_id: &AccountId,
+        tenant: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let key = tenant_key(account_id);
+        self.inner().write(ACCOUNTS_TABLE, &key, &tenant.map(str::to_string))?;
+        let Some(tenant) = tenant else {
+            return Ok(());
+        };
+
+        let mut ids = self.get_tenant_account_ids(tenant)?;
+        if !ids.contains(account_id) {
+            ids.push(*account_id);
+            self.inner().write(ACCOUNTS_TABLE, &tenant_index_key(tenant), &ids)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add matched volume to an account's running total for the given day
+    ///
+    /// The total resets when the day changes
+    pub fn add_account_daily_volume(
+        &self,
+        account_id: &AccountId,
+        day: u64,
+        volume: Amount,
+    ) -> Result<(), StorageError> {
+        let current = self.get_account_daily_volume(account_id, day)?;
+        let updated = DailyMatchVolume { day, volume: current.saturating_add(volume) };
+        let key = daily_volume_key(account_id);
+        self.inner().write(ACCOUNTS_TABLE, &key, &updated)
+    }
+
+    /// Set the time in milliseconds of an account's last match
+    pub fn set_account_last_match_time(
+        &self,
+        account_id: &AccountId,
+        timestamp: u64,
+    ) -> Result<(), StorageError> {
+        let key = last_match_key(account_id);
+        self.inner().write(ACCOUNTS_TABLE, &key, &timestamp)
+    }
+
+    /// Set an account's version
+    pub fn set_account_version(
+        &self,
+        account_id: &AccountId,
+        version: u64,
+    ) -> Result<(), StorageError> {
+        let key = version_key(account_id);
+        self.inner().write(ACCOUNTS_TABLE, &key, &version)
+    }
+
     /// Remove an order from an account
     ///
     /// This deletes both the order data and the order->account index
@@ -510,9 +962,7 @@ impl StateTxn<'_, RW> {
         self.inner().delete(ACCOUNTS_TABLE, &key)?;
 
         // Delete the order -> account index
-        let index_key = order_index_key(order_id);
-        self.inner().delete(ACCOUNTS_TABLE, &index_key)?;
-        Ok(())
+        self.delete_order_index(order_id)
     }
 
     /// Remove all storage artifacts for an order
@@ -586,7 +1036,9 @@ mod test {
     };
     use types_core::AccountId;
 
-    use crate::{ACCOUNTS_TABLE, test_helpers::mock_db};
+    use crate::{ACCOUNTS_TABLE, NODE_METADATA_TABLE, test_helpers::mock_db};
+
+    use super::{LegacyOrder, LegacyOrderMetadata, ORDER_LAYOUT, ORDER_LAYOUT_VERSION, order_key};
 
     /// Create a mock account
     fn mock_account() -> Account {
@@ -916,6 +1368,43 @@ mod test {
         assert_eq!(acc3.balances.len(), 0);
     }
 
+    // --- Tenant Tests ---
+
+    /// Tests assigning accounts to tenants and listing a tenant's accounts
+    #[test]
+    fn test_account_tenants() {
+        let db = mock_db();
+        db.create_table(ACCOUNTS_TABLE).unwrap();
+
+        let account1 = mock_account();
+        let account2 = mock_account();
+        let account3 = mock_account();
+        let tx = db.new_write_tx().unwrap();
+        let account4 = mock_account();
+        tx.set_account_tenant(&account1.id, Some("tenant-a")).unwrap();
+        tx.set_account_tenant(&account2.id, Some("tenant-a")).unwrap();
+        tx.set_account_tenant(&account2.id, Some("tenant-a")).unwrap();
+        tx.set_account_tenant(&account3.id, Some("tenant-b")).unwrap();
+        tx.set_account_tenant(&account4.id, None).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert_eq!(tx.get_account_tenant(&account1.id).unwrap().as_deref(), Some("tenant-a"));
+        assert_eq!(tx.get_account_tenant(&account3.id).unwrap().as_deref(), Some("tenant-b"));
+        assert_eq!(tx.get_account_tenant(&mock_account().id).unwrap(), None);
+
+        // An account claimed outside of any tenant is distinguished from an
+        // unclaimed one
+        assert_eq!(tx.get_account_tenant(&account4.id).unwrap(), None);
+        assert_eq!(tx.get_account_tenant_claim(&account4.id).unwrap(), Some(None));
+        assert_eq!(tx.get_account_tenant_claim(&mock_account().id).unwrap(), None);
+
+        let tenant_a = tx.get_tenant_account_ids("tenant-a").unwrap();
+        assert_eq!(tenant_a, vec![account1.id, account2.id]);
+        assert_eq!(tx.get_tenant_account_ids("tenant-b").unwrap(), vec![account3.id]);
+        assert!(tx.get_tenant_account_ids("tenant-c").unwrap().is_empty());
+    }
+
     // --- Owner Index Tests ---
 
     /// Tests setting and getting owner index
@@ -1047,4 +1536,66 @@ mod test {
         let tx = db.new_read_tx().unwrap();
         assert!(tx.get_order_by_intent_hash(&intent_hash).unwrap().is_none());
     }
+
+    /// Tests that an account's daily volume accumulates and resets by day
+    #[test]
+    fn test_daily_volume() {
+        let db = mock_db();
+        db.create_table(ACCOUNTS_TABLE).unwrap();
+        let account = mock_account();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.new_account(&account).unwrap();
+        tx.add_account_daily_volume(&account.id, 1 /* day */, 100).unwrap();
+        tx.add_account_daily_volume(&account.id, 1 /* day */, 50).unwrap();
+        assert_eq!(tx.get_account_daily_volume(&account.id, 1 /* day */).unwrap(), 150);
+
+        // A new day resets the total
+        assert_eq!(tx.get_account_daily_volume(&account.id, 2 /* day */).unwrap(), 0);
+        tx.add_account_daily_volume(&account.id, 2 /* day */, 10).unwrap();
+        assert_eq!(tx.get_account_daily_volume(&account.id, 2 /* day */).unwrap(), 10);
+        tx.commit().unwrap();
+    }
+
+    /// Tests migrating orders stored in the layout that predates client order
+    /// ids and tags
+    #[test]
+    fn test_migrate_legacy_orders() {
+        let db = mock_db();
+        db.create_table(ACCOUNTS_TABLE).unwrap();
+        db.create_table(NODE_METADATA_TABLE).unwrap();
+
+        // Write an account holding an order in the legacy layout
+        let account = mock_account();
+        let mut order = mock_order();
+        order.metadata.has_been_filled = true;
+        let legacy = LegacyOrder {
+            id: order.id,
+            intent: order.intent.clone(),
+            ring: order.ring,
+            metadata: LegacyOrderMetadata {
+                min_fill_size: order.metadata.min_fill_size,
+                allow_external_matches: order.metadata.allow_external_matches,
+                has_been_filled: order.metadata.has_been_filled,
+            },
+        };
+
+        let tx = db.new_write_tx().unwrap();
+        tx.new_account(&account).unwrap();
+        tx.inner().write(ACCOUNTS_TABLE, &order_key(&account.id, &order.id), &legacy).unwrap();
+        assert_eq!(tx.migrate_legacy_orders().unwrap(), 1);
+        tx.commit().unwrap();
+
+        // The order now reads in the current layout
+        let tx = db.new_read_tx().unwrap();
+        let orders = tx.get_account_orders(&account.id).unwrap();
+        assert_eq!(orders, vec![order]);
+        assert_eq!(tx.get_layout_version(ORDER_LAYOUT).unwrap(), ORDER_LAYOUT_VERSION);
+        tx.commit().unwrap();
+
+        // A second run leaves the migrated orders alone
+        let tx = db.new_write_tx().unwrap();
+        assert_eq!(tx.migrate_legacy_orders().unwrap(), 0);
+        tx.commit().unwrap();
+    }
 }
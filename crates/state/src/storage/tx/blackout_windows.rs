@@ -0,0 +1,96 @@
+//! Storage helpers for matching blackout windows
+//!
+//! A node only ever holds a handful of windows, so they are stored as a single
+//! list
+
+use libmdbx::{RW, TransactionKind};
+use types_core::BlackoutWindow;
+use uuid::Uuid;
+
+use crate::{BLACKOUT_WINDOWS_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The key under which the list of blackout windows is stored
+const BLACKOUT_WINDOWS_KEY: &str = "windows";
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get all blackout windows
+    pub fn get_blackout_windows(&self) -> Result<Vec<BlackoutWindow>, StorageError> {
+        let key = BLACKOUT_WINDOWS_KEY.to_string();
+        let windows = self
+            .inner()
+            .read::<_, Vec<BlackoutWindow>>(BLACKOUT_WINDOWS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())??;
+        Ok(windows.unwrap_or_default())
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Add a blackout window, replacing any window with the same ID
+    pub fn add_blackout_window(&self, window: BlackoutWindow) -> Result<(), StorageError> {
+        let mut windows = self.get_blackout_windows()?;
+        windows.retain(|w| w.id != window.id);
+        windows.push(window);
+        self.write_blackout_windows(windows)
+    }
+
+    /// Remove a blackout window, returning whether it existed
+    pub fn remove_blackout_window(&self, id: Uuid) -> Result<bool, StorageError> {
+        let mut windows = self.get_blackout_windows()?;
+        let n_windows = windows.len();
+        windows.retain(|w| w.id != id);
+        if windows.len() == n_windows {
+            return Ok(false);
+        }
+
+        self.write_blackout_windows(windows)?;
+        Ok(true)
+    }
+
+    /// Overwrite the list of blackout windows
+    #[allow(clippy::needless_pass_by_value)]
+    fn write_blackout_windows(&self, windows: Vec<BlackoutWindow>) -> Result<(), StorageError> {
+        let key = BLACKOUT_WINDOWS_KEY.to_string();
+        self.inner().write(BLACKOUT_WINDOWS_TABLE, &key, &windows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_core::BlackoutWindow;
+
+    use crate::test_helpers::mock_db;
+
+    /// Tests adding and removing blackout windows
+    #[test]
+    fn test_blackout_windows() {
+        let db = mock_db();
+        let window1 = BlackoutWindow::new(100, 200, None, "maintenance".to_string()).unwrap();
+        let window2 = BlackoutWindow::new(0, 10, Some(1_000), "oracle".to_string()).unwrap();
+
+        let tx = db.new_write_tx().unwrap();
+        assert!(tx.get_blackout_windows().unwrap().is_empty());
+        tx.add_blackout_window(window1.clone()).unwrap();
+        tx.add_blackout_window(window2.clone()).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_write_tx().unwrap();
+        assert_eq!(tx.get_blackout_windows().unwrap(), vec![window1.clone(), window2.clone()]);
+        assert!(tx.remove_blackout_window(window1.id).unwrap());
+        assert!(!tx.remove_blackout_window(window1.id).unwrap());
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert_eq!(tx.get_blackout_windows().unwrap(), vec![window2]);
+        tx.commit().unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Storage helpers for the chain events listener's checkpoint
+//!
+//! The checkpoint is the cursor of the last on-chain event the local node has
+//! fully processed, from which the listener resumes after a restart. It is
+//! stored in the node metadata table, which is local to each node and excluded
+//! from raft snapshots
+
+use libmdbx::{RW, TransactionKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{NODE_METADATA_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The key of the chain events checkpoint in the node metadata table
+const CHAIN_EVENTS_CHECKPOINT_KEY: &str = "chain-events-checkpoint";
+
+// ---------
+// | Types |
+// ---------
+
+/// The position of an event log on-chain
+///
+/// Cursors are ordered by block, then by the log's index within the block
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub struct ChainEventCursor {
+    /// The block in which the log was emitted
+    pub block_number: u64,
+    /// The index of the log within its block
+    pub log_index: u64,
+}
+
+impl ChainEventCursor {
+    /// Construct a cursor
+    pub fn new(block_number: u64, log_index: u64) -> Self {
+        Self { block_number, log_index }
+    }
+
+    /// The cursor ordered after every log in the given block
+    pub fn end_of_block(block_number: u64) -> Self {
+        Self { block_number, log_index: u64::MAX }
+    }
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the chain events checkpoint, if one has been recorded
+    pub fn get_chain_events_checkpoint(&self) -> Result<Option<ChainEventCursor>, StorageError> {
+        let key = CHAIN_EVENTS_CHECKPOINT_KEY.to_string();
+        self.inner()
+            .read::<_, ChainEventCursor>(NODE_METADATA_TABLE, &key)?
+            .map(|cursor| cursor.deserialize())
+            .transpose()
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Set the chain events checkpoint
+    pub fn set_chain_events_checkpoint(
+        &self,
+        cursor: &ChainEventCursor,
+    ) -> Result<(), StorageError> {
+        let key = CHAIN_EVENTS_CHECKPOINT_KEY.to_string();
+        self.inner().write(NODE_METADATA_TABLE, &key, cursor)
+    }
+
+    /// Clear the chain events checkpoint
+    pub fn clear_chain_events_checkpoint(&self) -> Result<(), StorageError> {
+        let key = CHAIN_EVENTS_CHECKPOINT_KEY.to_string();
+        self.inner().delete(NODE_METADATA_TABLE, &key)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::mock_db;
+
+    use super::ChainEventCursor;
+
+    /// Tests setting, overwriting, and clearing the checkpoint
+    #[test]
+    fn test_chain_events_checkpoint() {
+        let db = mock_db();
+        let tx = db.new_write_tx().unwrap();
+        assert_eq!(tx.get_chain_events_checkpoint().unwrap(), None);
+
+        let first = ChainEventCursor::new(100, 2);
+        tx.set_chain_events_checkpoint(&first).unwrap();
+        assert_eq!(tx.get_chain_events_checkpoint().unwrap(), Some(first));
+
+        let second = ChainEventCursor::new(101, 0);
+        tx.set_chain_events_checkpoint(&second).unwrap();
+        assert_eq!(tx.get_chain_events_checkpoint().unwrap(), Some(second));
+
+        tx.clear_chain_events_checkpoint().unwrap();
+        assert_eq!(tx.get_chain_events_checkpoint().unwrap(), None);
+        tx.commit().unwrap();
+    }
+
+    /// Tests that cursors order by block, then by log index
+    #[test]
+    fn test_cursor_ordering() {
+        assert!(ChainEventCursor::new(100, 5) < ChainEventCursor::new(101, 0));
+        assert!(ChainEventCursor::new(100, 0) < ChainEventCursor::new(100, 1));
+        assert!(ChainEventCursor::new(100, 9) < ChainEventCursor::end_of_block(100));
+    }
+}
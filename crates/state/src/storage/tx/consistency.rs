@@ -0,0 +1,317 @@
+//! Cross-checks of the invariants between the state's tables and indices
+//!
+//! Several tables hold derived indices alongside the data they index; these
+//! are kept in sync by the state transitions that write them. The checks here
+//! detect indices that have drifted from their data (e.g. after a crash or a
+//! bug in a transition) and repair them from the data they index
+
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use circuit_types::Nullifier;
+use libmdbx::{RW, TransactionKind};
+use types_account::{OrderId, order::Order};
+use types_core::AccountId;
+use types_gossip::network_order::NetworkOrder;
+use types_tasks::{TaskIdentifier, TaskQueueKey};
+
+use crate::{
+    ACCOUNTS_TABLE, TASK_QUEUE_TABLE,
+    storage::{error::StorageError, tx::task_queue::queue_type::TaskQueue},
+};
+
+use super::{
+    StateTxn,
+    account_index::{ORDER_INDEX_PREFIX, order_key as account_order_key},
+    task_queue::storage::TASK_QUEUE_KEY_PREFIX,
+};
+
+/// An inconsistency between the state's tables and indices
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    /// An account's order is missing from the order -> account index, or the
+    /// index maps it to another account
+    OrderIndexMismatch {
+        /// The account holding the order
+        account_id: AccountId,
+        /// The order ID
+        order_id: OrderId,
+    },
+    /// The order -> account index maps an order to an account that does not
+    /// hold it
+    StaleOrderIndex {
+        /// The order ID
+        order_id: OrderId,
+    },
+    /// An order book entry is missing from the nullifier -> order index, or
+    /// the index maps its nullifier to another order
+    NullifierIndexMismatch {
+        /// The order ID
+        order_id: OrderId,
+        /// The order's nullifier
+        nullifier: Nullifier,
+    },
+    /// The locally managed order set references an order not in the book
+    StaleLocalOrder {
+        /// The order ID
+        order_id: OrderId,
+    },
+    /// A task queue references a task that does not exist
+    MissingQueuedTask {
+        /// The queue referencing the task
+        queue: TaskQueueKey,
+        /// The task ID
+        task_id: TaskIdentifier,
+    },
+    /// A task queue references a task whose task -> queue index omits the
+    /// queue
+    TaskQueueIndexMismatch {
+        /// The queue referencing the task
+        queue: TaskQueueKey,
+        /// The task ID
+        task_id: TaskIdentifier,
+    },
+}
+
+impl Display for ConsistencyIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::OrderIndexMismatch { account_id, order_id } => {
+                write!(f, "order {order_id} of account {account_id} missing from order index")
+            },
+            Self::StaleOrderIndex { order_id } => {
+                write!(f, "order index references missing order {order_id}")
+            },
+            Self::NullifierIndexMismatch { order_id, nullifier } => {
+                write!(f, "nullifier {nullifier} of order {order_id} missing from nullifier index")
+            },
+            Self::StaleLocalOrder { order_id } => {
+                write!(f, "local order set references missing order {order_id}")
+            },
+            Self::MissingQueuedTask { queue, task_id } => {
+                write!(f, "task queue {queue} references missing task {task_id}")
+            },
+            Self::TaskQueueIndexMismatch { queue, task_id } => {
+                write!(f, "task {task_id} in queue {queue} missing from task index")
+            },
+        }
+    }
+}
+
+// ----------
+// | Checks |
+// ----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Check the invariants between the state's tables and indices
+    ///
+    /// Warning: this scans every account, order, and task queue, and is slow
+    /// on a large state
+    pub fn check_consistency(&self) -> Result<Vec<ConsistencyIssue>, StorageError> {
+        let mut issues = Vec::new();
+        self.check_order_index(&mut issues)?;
+        self.check_nullifier_index(&mut issues)?;
+        self.check_task_queues(&mut issues)?;
+        Ok(issues)
+    }
+
+    /// Check the order -> account index against the accounts' orders
+    fn check_order_index(&self, issues: &mut Vec<ConsistencyIssue>) -> Result<(), StorageError> {
+        // Every account order must be indexed to its account
+        let mut mismatched = HashSet::new();
+        for account_id in self.get_all_account_ids()? {
+            for order in self.get_account_orders(&account_id)? {
+                if self.get_account_id_for_order(&order.id)? != Some(account_id) {
+                    let order_id = order.id;
+                    mismatched.insert(order_id);
+                    issues.push(ConsistencyIssue::OrderIndexMismatch { account_id, order_id });
+                }
+            }
+        }
+
+        // Every index entry must reference an order its account holds. Entries
+        // already reported as mismatched are repaired by rewriting them, so
+        // they are not also reported as stale
+        let cursor = self
+            .inner()
+            .cursor::<String, AccountId>(ACCOUNTS_TABLE)?
+            .with_key_prefix(ORDER_INDEX_PREFIX);
+        for entry in cursor.into_iter() {
+            let (key, account_id) = entry?;
+            let order_id = parse_key_suffix(&key, ORDER_INDEX_PREFIX)?;
+            if mismatched.contains(&order_id) {
+                continue;
+            }
+
+            let account_id = account_id.deserialize()?;
+
+            let order_key = account_order_key(&account_id, &order_id);
+            if self.inner().read::<_, Order>(ACCOUNTS_TABLE, &order_key)?.is_none() {
+                issues.push(ConsistencyIssue::StaleOrderIndex { order_id });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the order book's nullifier index and local order set against the
+    /// orders in the book
+    fn check_nullifier_index(
+        &self,
+        issues: &mut Vec<ConsistencyIssue>,
+    ) -> Result<(), StorageError> {
+        for order in self.get_all_orders()? {
+            let NetworkOrder { id: order_id, nullifier, .. } = order.deserialize()?;
+            let indexed = self.get_order_by_nullifier(nullifier)?.map(|id| id.deserialize());
+            if indexed.transpose()? != Some(order_id) {
+                issues.push(ConsistencyIssue::NullifierIndexMismatch { order_id, nullifier });
+            }
+        }
+
+        let local_orders = self.get_local_orders()?.map(|set| set.deserialize()).transpose()?;
+        for order_id in local_orders.unwrap_or_default() {
+            if !self.contains_order(&order_id)? {
+                issues.push(ConsistencyIssue::StaleLocalOrder { order_id });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the task queues against the tasks they reference
+    fn check_task_queues(&self, issues: &mut Vec<ConsistencyIssue>) -> Result<(), StorageError> {
+        let cursor = self
+            .inner()
+            .cursor::<String, TaskQueue>(TASK_QUEUE_TABLE)?
+            .with_key_prefix(TASK_QUEUE_KEY_PREFIX);
+        for entry in cursor.into_iter() {
+            let (key, task_queue) = entry?;
+            let queue = parse_key_suffix(&key, TASK_QUEUE_KEY_PREFIX)?;
+            for task_id in task_queue.all_tasks() {
+                if self.get_task(&task_id)?.is_none() {
+                    issues.push(ConsistencyIssue::MissingQueuedTask { queue, task_id });
+                } else if !self.get_queue_keys_for_task(&task_id)?.contains(&queue) {
+                    issues.push(ConsistencyIssue::TaskQueueIndexMismatch { queue, task_id });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// -----------
+// | Repairs |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Check the invariants between the state's tables and indices and repair
+    /// the inconsistencies found
+    ///
+    /// Returns the inconsistencies found, before any repair
+    pub fn repair_consistency(&self) -> Result<Vec<ConsistencyIssue>, StorageError> {
+        let issues = self.check_consistency()?;
+        for issue in issues.iter() {
+            self.repair_consistency_issue(issue)?;
+        }
+
+        Ok(issues)
+    }
+
+    /// Repair an inconsistency by rebuilding the index entry from the data it
+    /// indexes
+    pub fn repair_consistency_issue(&self, issue: &ConsistencyIssue) -> Result<(), StorageError> {
+        match issue {
+            ConsistencyIssue::OrderIndexMismatch { account_id, order_id } => {
+                self.write_order_index(order_id, account_id)
+            },
+            ConsistencyIssue::StaleOrderIndex { order_id } => self.delete_order_index(order_id),
+            ConsistencyIssue::NullifierIndexMismatch { order_id, nullifier } => {
+                self.set_nullifier_mapping(*nullifier, order_id)
+            },
+            ConsistencyIssue::StaleLocalOrder { order_id } => self.remove_local_order(order_id),
+            ConsistencyIssue::MissingQueuedTask { queue, task_id } => {
+                let mut task_queue = self.get_task_queue_deserialized(queue)?;
+                task_queue.pop_task(task_id);
+                self.write_task_queue(queue, &task_queue)
+            },
+            ConsistencyIssue::TaskQueueIndexMismatch { queue, task_id } => {
+                let mut queues = self.get_queue_keys_for_task(task_id)?;
+                queues.push(*queue);
+                self.update_task_to_queues(task_id, queues)
+            },
+        }
+    }
+}
+
+/// Parse the ID following a key prefix
+fn parse_key_suffix<I: std::str::FromStr>(key: &str, prefix: &str) -> Result<I, StorageError> {
+    key.strip_prefix(prefix)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| StorageError::InvalidKey(key.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use types_account::{account::mocks::mock_empty_account, order::mocks::mock_order};
+
+    use crate::test_helpers::mock_db;
+
+    use super::ConsistencyIssue;
+
+    /// Tests detecting and repairing a dropped order index entry
+    #[test]
+    fn test_repair_order_index() {
+        let db = mock_db();
+        let account = mock_empty_account();
+        let order = mock_order();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.new_account(&account).unwrap();
+        tx.add_order(&account.id, &order).unwrap();
+        assert!(tx.check_consistency().unwrap().is_empty());
+
+        // Drop the index entry
+        tx.delete_order_index(&order.id).unwrap();
+        let issues = tx.check_consistency().unwrap();
+        let expected =
+            ConsistencyIssue::OrderIndexMismatch { account_id: account.id, order_id: order.id };
+        assert_eq!(issues, vec![expected]);
+
+        // Repair the index
+        for issue in issues.iter() {
+            tx.repair_consistency_issue(issue).unwrap();
+        }
+        assert!(tx.check_consistency().unwrap().is_empty());
+        assert_eq!(tx.get_account_id_for_order(&order.id).unwrap(), Some(account.id));
+        tx.commit().unwrap();
+    }
+
+    /// Tests that an order indexed to the wrong account is reported once, and
+    /// that the repair points the index at the account holding the order
+    #[test]
+    fn test_repair_misdirected_order_index() {
+        let db = mock_db();
+        let account = mock_empty_account();
+        let other = mock_empty_account();
+        let order = mock_order();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.new_account(&account).unwrap();
+        tx.new_account(&other).unwrap();
+        tx.add_order(&account.id, &order).unwrap();
+
+        // Point the index at an account that does not hold the order
+        tx.write_order_index(&order.id, &other.id).unwrap();
+        let issues = tx.repair_consistency().unwrap();
+        let expected =
+            ConsistencyIssue::OrderIndexMismatch { account_id: account.id, order_id: order.id };
+        assert_eq!(issues, vec![expected]);
+
+        assert!(tx.check_consistency().unwrap().is_empty());
+        assert_eq!(tx.get_account_id_for_order(&order.id).unwrap(), Some(account.id));
+        tx.commit().unwrap();
+    }
+}
@@ -0,0 +1,442 @@
+//! Walks the exportable tables, flattening each entry into a row of columns
+//!
+//! Tables are read in pages of a bounded number of entries, each of which
+//! resumes after the last key of the previous page, so that an export need
+//! not hold a read transaction open for the length of a table. Rows are handed
+//! to a sink one at a time as the cursor advances, so an export never holds a
+//! full table in memory. Nested values that have no flat representation (e.g.
+//! a task's description) are written as JSON strings
+
+use external_api::http::admin::ExportTable;
+use libmdbx::TransactionKind;
+use serde::Serialize;
+use types_account::order::Order;
+use types_tasks::HistoricalTask;
+
+use crate::{
+    ACCOUNTS_TABLE, MATCH_AUDIT_TABLE, TASK_HISTORY_TABLE,
+    storage::{ArchivedValue, error::StorageError, traits::Value, tx::match_audit::MatchAttempt},
+};
+
+use super::StateTxn;
+
+/// The infix separating an account ID from an order ID in an order key
+const ORDER_KEY_INFIX: &str = ":orders:";
+/// The prefix of the keys of match attempts in the audit table
+const MATCH_ATTEMPT_KEY_PREFIX: &str = "match-attempt/";
+/// The infix separating a queue key from a task ID in a task history key
+const TASK_HISTORY_KEY_INFIX: &str = "-history-task-";
+
+// ---------
+// | Types |
+// ---------
+
+/// The type of an exported column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportColumnType {
+    /// A UTF-8 string
+    String,
+    /// An unsigned 64-bit integer
+    UInt64,
+    /// A boolean
+    Bool,
+}
+
+/// A column of an exported table
+pub type ExportColumn = (&'static str, ExportColumnType);
+
+/// A single value in an exported row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportValue {
+    /// A string value
+    String(String),
+    /// An unsigned 64-bit integer
+    UInt64(u64),
+    /// A boolean
+    Bool(bool),
+    /// A missing value
+    Null,
+}
+
+impl From<String> for ExportValue {
+    fn from(value: String) -> Self {
+        ExportValue::String(value)
+    }
+}
+
+impl From<u64> for ExportValue {
+    fn from(value: u64) -> Self {
+        ExportValue::UInt64(value)
+    }
+}
+
+impl From<bool> for ExportValue {
+    fn from(value: bool) -> Self {
+        ExportValue::Bool(value)
+    }
+}
+
+impl<T: Into<ExportValue>> From<Option<T>> for ExportValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(ExportValue::Null)
+    }
+}
+
+/// A page of an exported table
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportPage {
+    /// The number of rows written
+    pub rows: u64,
+    /// The raw key of the last entry scanned, after which the next page
+    /// resumes, or `None` once the table is exhausted
+    pub resume_after: Option<Vec<u8>>,
+}
+
+/// A destination for exported rows
+pub trait ExportSink {
+    /// Write a row, whose values are in the order of the table's columns
+    fn write_row(&mut self, row: Vec<ExportValue>) -> Result<(), StorageError>;
+}
+
+/// The columns of an exported table
+pub fn export_columns(table: ExportTable) -> &'static [ExportColumn] {
+    use ExportColumnType::*;
+    match table {
+        ExportTable::Orders => &[
+            ("order_id", String),
+            ("account_id", String),
+            ("owner", String),
+            ("in_token", String),
+            ("out_token", String),
+            ("amount_in", String),
+            ("min_price", String),
+            ("min_fill_size", String),
+            ("ring", String),
+            ("allow_external_matches", Bool),
+            ("has_been_filled", Bool),
+            ("client_order_id", String),
+            ("tags", String),
+        ],
+        ExportTable::MatchAttempts => &[
+            ("attempt_id", String),
+            ("peer_id", String),
+            ("order_id", String),
+            ("other_order_id", String),
+            ("phase", String),
+            ("failure_reason", String),
+            ("started_at", UInt64),
+            ("duration_ms", UInt64),
+        ],
+        ExportTable::TaskHistory => &[
+            ("task_id", String),
+            ("queue_key", String),
+            ("state", String),
+            ("created_at", UInt64),
+            ("description", String),
+            ("gas_costs", String),
+        ],
+    }
+}
+
+/// Parse the account ID out of a key in the accounts table, if the key is
+/// that of an order
+fn parse_order_key(key: &str) -> Option<&str> {
+    key.split_once(ORDER_KEY_INFIX).map(|(account_id, _)| account_id)
+}
+
+/// Parse the queue key out of a key in the task history table, if the key is
+/// that of a historical task
+fn parse_task_history_key(key: &str) -> Option<&str> {
+    key.split_once(TASK_HISTORY_KEY_INFIX).map(|(queue_key, _)| queue_key)
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Write the rows of a page of the given table to the sink
+    ///
+    /// The page scans at most `limit` entries, starting after the raw key
+    /// `after`, or from the start of the table if `None`
+    pub fn export_table_page<S: ExportSink>(
+        &self,
+        table: ExportTable,
+        after: Option<&[u8]>,
+        limit: usize,
+        sink: &mut S,
+    ) -> Result<ExportPage, StorageError> {
+        match table {
+            ExportTable::Orders => self.export_orders(after, limit, sink),
+            ExportTable::MatchAttempts => self.export_match_attempts(after, limit, sink),
+            ExportTable::TaskHistory => self.export_task_history(after, limit, sink),
+        }
+    }
+
+    /// Write a page of the orders of all accounts to the sink
+    fn export_orders<S: ExportSink>(
+        &self,
+        after: Option<&[u8]>,
+        limit: usize,
+        sink: &mut S,
+    ) -> Result<ExportPage, StorageError> {
+        // The accounts table holds several kinds of entry, so only values
+        // under an order key are deserialized
+        self.scan_page::<Order>(ACCOUNTS_TABLE, "", after, limit, |key, order| {
+            let Some(account_id) = parse_order_key(key) else {
+                return Ok(false);
+            };
+
+            let order: Order = order.deserialize()?;
+            let metadata = order.metadata();
+            sink.write_row(vec![
+                order.id.to_string().into(),
+                account_id.to_string().into(),
+                format!("{:?}", order.intent().owner).into(),
+                format!("{:?}", order.input_token()).into(),
+                format!("{:?}", order.output_token()).into(),
+                order.amount_in().to_string().into(),
+                order.min_price().to_f64().to_string().into(),
+                order.min_fill_size().to_string().into(),
+                format!("{:?}", order.ring).into(),
+                order.allow_external_matches().into(),
+                metadata.has_been_filled.into(),
+                metadata.client_order_id.clone().into(),
+                metadata.tags.join(";").into(),
+            ])?;
+            Ok(true)
+        })
+    }
+
+    /// Write a page of the match attempts in the audit trail to the sink
+    fn export_match_attempts<S: ExportSink>(
+        &self,
+        after: Option<&[u8]>,
+        limit: usize,
+        sink: &mut S,
+    ) -> Result<ExportPage, StorageError> {
+        let prefix = MATCH_ATTEMPT_KEY_PREFIX;
+        self.scan_page::<MatchAttempt>(MATCH_AUDIT_TABLE, prefix, after, limit, |_, value| {
+            let attempt: MatchAttempt = value.deserialize()?;
+            sink.write_row(vec![
+                attempt.id.to_string().into(),
+                attempt.peer_id.to_string().into(),
+                attempt.order_id.to_string().into(),
+                attempt.other_order_id.map(|id| id.to_string()).into(),
+                format!("{:?}", attempt.phase).into(),
+                attempt.failure_reason.into(),
+                attempt.started_at.into(),
+                attempt.duration_ms.into(),
+            ])?;
+            Ok(true)
+        })
+    }
+
+    /// Write a page of the task history of all queues to the sink
+    fn export_task_history<S: ExportSink>(
+        &self,
+        after: Option<&[u8]>,
+        limit: usize,
+        sink: &mut S,
+    ) -> Result<ExportPage, StorageError> {
+        // The task history table also holds each queue's list of task IDs,
+        // which are skipped
+        self.scan_page::<HistoricalTask>(TASK_HISTORY_TABLE, "", after, limit, |key, task| {
+            let Some(queue_key) = parse_task_history_key(key) else {
+                return Ok(false);
+            };
+
+            let task: HistoricalTask = task.deserialize()?;
+            sink.write_row(vec![
+                task.id.to_string().into(),
+                queue_key.to_string().into(),
+                task.state.display_description().into(),
+                task.created_at.into(),
+                to_json(&task.task_info)?.into(),
+                to_json(&self.get_task_gas_costs(&task.id)?)?.into(),
+            ])?;
+            Ok(true)
+        })
+    }
+
+    /// Scan at most `limit` entries of a table under the given key prefix,
+    /// starting after the raw key `after`, passing each to `export_entry`
+    ///
+    /// `export_entry` returns whether it wrote a row for the entry
+    fn scan_page<V: Value>(
+        &self,
+        table: &str,
+        prefix: &str,
+        after: Option<&[u8]>,
+        limit: usize,
+        mut export_entry: impl FnMut(&str, &ArchivedValue<'_, V>) -> Result<bool, StorageError>,
+    ) -> Result<ExportPage, StorageError> {
+        let mut cursor = self.inner().cursor::<String, V>(table)?;
+        let mut found = cursor.seek_raw(after.unwrap_or(prefix.as_bytes()))?;
+
+        // Resume strictly after the last key of the previous page
+        if found
+            && let Some(after) = after
+            && cursor.get_current_raw()?.is_some_and(|(key, _)| key.as_ref() == after)
+        {
+            found = !cursor.seek_next_raw()?;
+        }
+
+        let mut page = ExportPage::default();
+        let mut scanned = 0;
+        while found {
+            let Some((key, value)) = cursor.get_current()? else { break };
+            if !key.as_bytes().starts_with(prefix.as_bytes()) {
+                break;
+            }
+
+            if export_entry(key.as_str(), &value)? {
+                page.rows += 1;
+            }
+
+            scanned += 1;
+            if scanned >= limit {
+                page.resume_after = Some(key.as_bytes().to_vec());
+                break;
+            }
+            found = !cursor.seek_next_raw()?;
+        }
+
+        Ok(page)
+    }
+}
+
+/// Serialize a nested value into a JSON column
+fn to_json<V: Serialize>(value: &V) -> Result<String, StorageError> {
+    serde_json::to_string(value).map_err(StorageError::serialization)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use external_api::http::admin::ExportTable;
+    use types_gossip::WrappedPeerId;
+    use uuid::Uuid;
+
+    use crate::{
+        storage::{
+            error::StorageError,
+            tx::{
+                account_index::order_key,
+                match_audit::MatchAttempt,
+                task_history::{task_history_item_key, task_history_key},
+            },
+        },
+        test_helpers::mock_db,
+    };
+
+    use super::{ExportSink, ExportValue, export_columns, parse_order_key, parse_task_history_key};
+
+    /// A sink collecting rows in memory
+    #[derive(Default)]
+    struct VecSink(Vec<Vec<ExportValue>>);
+
+    impl ExportSink for VecSink {
+        fn write_row(&mut self, row: Vec<ExportValue>) -> Result<(), StorageError> {
+            self.0.push(row);
+            Ok(())
+        }
+    }
+
+    /// Tests that exporting match attempts skips the audit trail's ID list and
+    /// writes one full row per attempt
+    #[test]
+    fn test_export_match_attempts() {
+        let db = mock_db();
+        let peer_id = WrappedPeerId::random();
+
+        let tx = db.new_write_tx().unwrap();
+        for i in 0..3 {
+            let attempt = MatchAttempt::new(peer_id, Uuid::new_v4(), i);
+            tx.append_match_attempt(&attempt).unwrap();
+        }
+
+        let mut sink = VecSink::default();
+        let page =
+            tx.export_table_page(ExportTable::MatchAttempts, None, usize::MAX, &mut sink).unwrap();
+        assert_eq!(page.rows, 3);
+        assert_eq!(page.resume_after, None);
+        assert_eq!(sink.0.len(), 3);
+
+        let n_columns = export_columns(ExportTable::MatchAttempts).len();
+        assert!(sink.0.iter().all(|row| row.len() == n_columns));
+
+        // No orders have been written
+        let mut sink = VecSink::default();
+        let page = tx.export_table_page(ExportTable::Orders, None, usize::MAX, &mut sink).unwrap();
+        assert_eq!(page.rows, 0);
+        tx.commit().unwrap();
+    }
+
+    /// Tests that a paged export resumes after the last key of each page,
+    /// writing every row exactly once
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_export_match_attempts__paged() {
+        let db = mock_db();
+        let peer_id = WrappedPeerId::random();
+
+        let tx = db.new_write_tx().unwrap();
+        for i in 0..5 {
+            let attempt = MatchAttempt::new(peer_id, Uuid::new_v4(), i);
+            tx.append_match_attempt(&attempt).unwrap();
+        }
+
+        let mut sink = VecSink::default();
+        let mut after = None;
+        let mut page_rows = Vec::new();
+        loop {
+            let page = tx
+                .export_table_page(ExportTable::MatchAttempts, after.as_deref(), 2, &mut sink)
+                .unwrap();
+            page_rows.push(page.rows);
+            after = page.resume_after;
+            if after.is_none() {
+                break;
+            }
+        }
+        tx.commit().unwrap();
+
+        // Pages of two, followed by the remainder
+        assert_eq!(page_rows, vec![2, 2, 1]);
+        let attempt_ids: HashSet<_> = sink
+            .0
+            .iter()
+            .filter_map(|row| match &row[0] {
+                ExportValue::String(id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sink.0.len(), 5);
+        assert_eq!(attempt_ids.len(), 5);
+    }
+
+    /// Tests parsing the account ID out of the keys of the accounts table
+    #[test]
+    fn test_parse_order_key() {
+        let account_id = Uuid::new_v4();
+        let key = order_key(&account_id, &Uuid::new_v4());
+        assert_eq!(parse_order_key(&key), Some(account_id.to_string().as_str()));
+
+        // Other entries of the accounts table are not orders
+        assert_eq!(parse_order_key(&format!("{account_id}:header")), None);
+        assert_eq!(parse_order_key(&format!("{account_id}:balances:darkpool:0x1")), None);
+    }
+
+    /// Tests parsing the queue key out of the keys of the task history table
+    #[test]
+    fn test_parse_task_history_key() {
+        let queue_key = Uuid::new_v4();
+        let key = task_history_item_key(&queue_key, &Uuid::new_v4());
+        assert_eq!(parse_task_history_key(&key), Some(queue_key.to_string().as_str()));
+
+        // A queue's list of task IDs is not a historical task
+        assert_eq!(parse_task_history_key(&task_history_key(&queue_key)), None);
+    }
+}
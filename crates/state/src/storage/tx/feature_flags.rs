@@ -0,0 +1,73 @@
+//! Storage helpers for runtime feature flags
+//!
+//! Flags are keyed by name; a flag that has never been set takes its default
+//! value
+
+use libmdbx::{RW, TransactionKind};
+use types_core::FeatureFlag;
+
+use crate::{FEATURE_FLAGS_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the value of a feature flag, falling back to its default if unset
+    pub fn get_feature_flag(&self, flag: FeatureFlag) -> Result<bool, StorageError> {
+        let key = flag.to_string();
+        let value = self.inner().read::<_, bool>(FEATURE_FLAGS_TABLE, &key)?;
+        Ok(value.map(|v| *v).unwrap_or(flag.default_value()))
+    }
+
+    /// Get the values of all feature flags
+    pub fn get_all_feature_flags(&self) -> Result<Vec<(FeatureFlag, bool)>, StorageError> {
+        FeatureFlag::all()
+            .into_iter()
+            .map(|flag| self.get_feature_flag(flag).map(|enabled| (flag, enabled)))
+            .collect()
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Set the value of a feature flag
+    pub fn set_feature_flag(&self, flag: FeatureFlag, enabled: bool) -> Result<(), StorageError> {
+        let key = flag.to_string();
+        self.inner().write(FEATURE_FLAGS_TABLE, &key, &enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_core::FeatureFlag;
+
+    use crate::test_helpers::mock_db;
+
+    /// Tests that unset flags take their defaults and set flags override them
+    #[test]
+    fn test_feature_flags() {
+        let db = mock_db();
+        let tx = db.new_read_tx().unwrap();
+        for flag in FeatureFlag::all() {
+            assert_eq!(tx.get_feature_flag(flag).unwrap(), flag.default_value());
+        }
+        tx.commit().unwrap();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.set_feature_flag(FeatureFlag::ExternalMatches, false).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert!(!tx.get_feature_flag(FeatureFlag::ExternalMatches).unwrap());
+        let flags = tx.get_all_feature_flags().unwrap();
+        assert!(flags.contains(&(FeatureFlag::ExternalMatches, false)));
+        assert!(flags.contains(&(FeatureFlag::PauseAccountCreation, false)));
+        tx.commit().unwrap();
+    }
+}
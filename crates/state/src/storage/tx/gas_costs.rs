@@ -0,0 +1,203 @@
+//! Storage helpers for aggregate gas costs of on-chain submissions
+//!
+//! Totals are kept per account and per task type, and are updated as tasks
+//! report the gas used by their submissions. Each task's own submissions are
+//! kept alongside the totals, keyed by the task's ID
+
+use libmdbx::{RW, TransactionKind};
+use types_core::AccountId;
+use types_tasks::{ChainSubmission, GasCostTotals, TaskIdentifier};
+
+use crate::{GAS_COSTS_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The key under which the list of task types with recorded costs is stored
+const GAS_COST_TASK_TYPES_KEY: &str = "task-types";
+
+// ---------------
+// | Key Helpers |
+// ---------------
+
+/// Build the key for an account's gas cost totals
+fn account_gas_costs_key(account_id: &AccountId) -> String {
+    format!("account/{account_id}")
+}
+
+/// Build the key for a task type's gas cost totals
+fn task_type_gas_costs_key(task_type: &str) -> String {
+    format!("task-type/{task_type}")
+}
+
+/// Build the key for the submissions made by a task
+fn task_gas_costs_key(task_id: &TaskIdentifier) -> String {
+    format!("task/{task_id}")
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the gas cost totals for an account
+    pub fn get_account_gas_costs(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<GasCostTotals, StorageError> {
+        self.read_gas_cost_totals(&account_gas_costs_key(account_id))
+    }
+
+    /// Get the gas cost totals for each task type with recorded costs
+    pub fn get_task_type_gas_costs(&self) -> Result<Vec<(String, GasCostTotals)>, StorageError> {
+        let task_types = self.get_gas_cost_task_types()?;
+        let mut totals = Vec::with_capacity(task_types.len());
+        for task_type in task_types {
+            let task_totals = self.read_gas_cost_totals(&task_type_gas_costs_key(&task_type))?;
+            totals.push((task_type, task_totals));
+        }
+
+        Ok(totals)
+    }
+
+    /// Get the on-chain submissions made by a task
+    pub fn get_task_gas_costs(
+        &self,
+        task_id: &TaskIdentifier,
+    ) -> Result<Vec<ChainSubmission>, StorageError> {
+        let key = task_gas_costs_key(task_id);
+        let costs = self.inner().read::<_, Vec<ChainSubmission>>(GAS_COSTS_TABLE, &key)?;
+        Ok(costs.map(|c| c.deserialize()).transpose()?.unwrap_or_default())
+    }
+
+    /// Get the task types with recorded costs
+    fn get_gas_cost_task_types(&self) -> Result<Vec<String>, StorageError> {
+        let key = GAS_COST_TASK_TYPES_KEY.to_string();
+        let types = self.inner().read::<_, Vec<String>>(GAS_COSTS_TABLE, &key)?;
+        Ok(types.map(|t| t.deserialize()).transpose()?.unwrap_or_default())
+    }
+
+    /// Read the totals stored under a key, defaulting to zero
+    fn read_gas_cost_totals(&self, key: &str) -> Result<GasCostTotals, StorageError> {
+        let key = key.to_string();
+        let totals = self.inner().read::<_, GasCostTotals>(GAS_COSTS_TABLE, &key)?;
+        Ok(totals.map(|t| t.deserialize()).transpose()?.unwrap_or_default())
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Add a task's submissions to the totals of the task's type and each of
+    /// the accounts it affected
+    pub fn add_gas_costs(
+        &self,
+        task_type: &str,
+        account_ids: &[AccountId],
+        costs: &[ChainSubmission],
+    ) -> Result<(), StorageError> {
+        let mut keys: Vec<String> = account_ids.iter().map(account_gas_costs_key).collect();
+        keys.push(task_type_gas_costs_key(task_type));
+        for key in keys.iter() {
+            let mut totals = self.read_gas_cost_totals(key)?;
+            costs.iter().for_each(|cost| totals.add(cost));
+            self.inner().write(GAS_COSTS_TABLE, key, &totals)?;
+        }
+
+        // Index the task type so that its totals may be listed
+        let mut task_types = self.get_gas_cost_task_types()?;
+        if !task_types.iter().any(|t| t == task_type) {
+            task_types.push(task_type.to_string());
+            let key = GAS_COST_TASK_TYPES_KEY.to_string();
+            self.inner().write(GAS_COSTS_TABLE, &key, &task_types)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the on-chain submissions made by a task
+    pub fn append_task_gas_costs(
+        &self,
+        task_id: &TaskIdentifier,
+        costs: &[ChainSubmission],
+    ) -> Result<(), StorageError> {
+        let mut task_costs = self.get_task_gas_costs(task_id)?;
+        task_costs.extend_from_slice(costs);
+        self.inner().write(GAS_COSTS_TABLE, &task_gas_costs_key(task_id), &task_costs)
+    }
+
+    /// Delete the submissions recorded for a task
+    pub fn delete_task_gas_costs(&self, task_id: &TaskIdentifier) -> Result<(), StorageError> {
+        self.inner().delete(GAS_COSTS_TABLE, &task_gas_costs_key(task_id)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_core::AccountId;
+    use types_tasks::{ChainSubmission, GasCostTotals, TaskIdentifier};
+
+    use crate::test_helpers::mock_db;
+
+    /// Build a mock submission
+    fn mock_submission(gas_used: u64) -> ChainSubmission {
+        let effective_gas_price = 10;
+        ChainSubmission {
+            tx_hash: String::new(),
+            gas_used,
+            effective_gas_price,
+            fee_paid: gas_used as u128 * effective_gas_price,
+        }
+    }
+
+    /// Tests that costs are aggregated per account and per task type
+    #[test]
+    fn test_add_gas_costs() {
+        let db = mock_db();
+        let account1 = AccountId::new_v4();
+        let account2 = AccountId::new_v4();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.add_gas_costs("Deposit", &[account1], &[mock_submission(100)]).unwrap();
+        tx.add_gas_costs("Settle", &[account1, account2], &[mock_submission(200)]).unwrap();
+        tx.add_gas_costs("Settle", &[account2], &[mock_submission(300)]).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        let totals1 = tx.get_account_gas_costs(&account1).unwrap();
+        assert_eq!(totals1, GasCostTotals { num_submissions: 2, gas_used: 300, fee_paid: 3_000 });
+        let totals2 = tx.get_account_gas_costs(&account2).unwrap();
+        assert_eq!(totals2, GasCostTotals { num_submissions: 2, gas_used: 500, fee_paid: 5_000 });
+
+        let by_type = tx.get_task_type_gas_costs().unwrap();
+        assert_eq!(by_type.len(), 2);
+        assert_eq!(by_type[0].0, "Deposit");
+        assert_eq!(by_type[0].1.gas_used, 100);
+        assert_eq!(by_type[1].0, "Settle");
+        assert_eq!(by_type[1].1.gas_used, 500);
+        tx.commit().unwrap();
+    }
+
+    /// Tests recording and deleting the submissions made by a task
+    #[test]
+    fn test_task_gas_costs() {
+        let db = mock_db();
+        let task_id = TaskIdentifier::new_v4();
+        let costs = vec![mock_submission(100), mock_submission(200)];
+
+        let tx = db.new_write_tx().unwrap();
+        tx.append_task_gas_costs(&task_id, &costs[..1]).unwrap();
+        tx.append_task_gas_costs(&task_id, &costs[1..]).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert_eq!(tx.get_task_gas_costs(&task_id).unwrap(), costs);
+        tx.commit().unwrap();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.delete_task_gas_costs(&task_id).unwrap();
+        assert!(tx.get_task_gas_costs(&task_id).unwrap().is_empty());
+        tx.commit().unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+//! Storage helpers for the match attempt audit trail
+//!
+//! Every internal matching attempt run by the local node is recorded here so
+//! that operators can investigate why an order is not filling. The table is
+//! local to each node and is not replicated through raft.
+//!
+//! Attempts are keyed as `match-attempt/{id}` -> `MatchAttempt`, with the IDs
+//! of all retained attempts kept most-recent-first under a single list key.
+//! The list is bounded; the oldest attempts are evicted as new ones arrive
+
+use libmdbx::{RW, TransactionKind};
+use serde::{Deserialize, Serialize};
+use types_account::OrderId;
+use types_gossip::WrappedPeerId;
+use uuid::Uuid;
+
+use crate::{MATCH_AUDIT_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The maximum number of match attempts retained in the audit trail
+pub const MAX_MATCH_AUDIT_ENTRIES: usize = 1_000;
+
+/// The key under which the list of retained attempt IDs is stored
+const MATCH_ATTEMPT_IDS_KEY: &str = "match-attempt-ids";
+
+// ---------
+// | Types |
+// ---------
+
+/// The phases of an internal matching attempt, in the order they are reached
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub enum MatchPhase {
+    /// The matching engine began processing the order
+    Started,
+    /// A crossing counterparty order was found
+    MatchFound,
+    /// The match passed both accounts' risk checks
+    RiskChecked,
+    /// The settlement task was enqueued
+    SettlementQueued,
+    /// The match was recorded as an indicative fill without settling, as the
+    /// matching engine is in quote-only mode
+    IndicativeFill,
+}
+
+/// A record of a single internal matching attempt
+#[derive(
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub struct MatchAttempt {
+    /// The ID of the attempt
+    pub id: Uuid,
+    /// The peer that ran the attempt
+    pub peer_id: WrappedPeerId,
+    /// The order the matching engine was run on
+    pub order_id: OrderId,
+    /// The counterparty order, if a match was found
+    pub other_order_id: Option<OrderId>,
+    /// The furthest phase the attempt reached
+    pub phase: MatchPhase,
+    /// The reason the attempt did not settle, if it failed or was skipped
+    pub failure_reason: Option<String>,
+    /// The time (in milliseconds) at which the attempt started
+    pub started_at: u64,
+    /// The duration of the attempt in milliseconds
+    pub duration_ms: u64,
+}
+
+impl MatchAttempt {
+    /// Begin a new attempt on an order
+    pub fn new(peer_id: WrappedPeerId, order_id: OrderId, started_at: u64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            peer_id,
+            order_id,
+            other_order_id: None,
+            phase: MatchPhase::Started,
+            failure_reason: None,
+            started_at,
+            duration_ms: 0,
+        }
+    }
+
+    /// Whether the attempt involved the given order on either side
+    pub fn involves(&self, order_id: &OrderId) -> bool {
+        self.order_id == *order_id || self.other_order_id.as_ref() == Some(order_id)
+    }
+}
+
+// ---------------
+// | Key Helpers |
+// ---------------
+
+/// Build the key for a match attempt
+fn match_attempt_key(id: &Uuid) -> String {
+    format!("match-attempt/{id}")
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the retained match attempts, most recent first
+    pub fn get_match_attempts(&self) -> Result<Vec<MatchAttempt>, StorageError> {
+        let ids = self.get_match_attempt_ids()?;
+        let mut attempts = Vec::with_capacity(ids.len());
+        for id in ids.iter() {
+            let key = match_attempt_key(id);
+            if let Some(attempt) = self.inner().read::<_, MatchAttempt>(MATCH_AUDIT_TABLE, &key)? {
+                attempts.push(attempt.deserialize()?);
+            }
+        }
+
+        Ok(attempts)
+    }
+
+    /// Get the IDs of the retained match attempts, most recent first
+    fn get_match_attempt_ids(&self) -> Result<Vec<Uuid>, StorageError> {
+        let key = MATCH_ATTEMPT_IDS_KEY.to_string();
+        let ids = self.inner().read::<_, Vec<Uuid>>(MATCH_AUDIT_TABLE, &key)?;
+        Ok(ids.map(|ids| ids.deserialize()).transpose()?.unwrap_or_default())
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Append a match attempt to the audit trail, evicting the oldest
+    /// attempts beyond the retention bound
+    pub fn append_match_attempt(&self, attempt: &MatchAttempt) -> Result<(), StorageError> {
+        self.inner().write(MATCH_AUDIT_TABLE, &match_attempt_key(&attempt.id), attempt)?;
+
+        let mut ids = self.get_match_attempt_ids()?;
+        ids.insert(0, attempt.id);
+        if ids.len() > MAX_MATCH_AUDIT_ENTRIES {
+            for evicted in ids.split_off(MAX_MATCH_AUDIT_ENTRIES) {
+                self.inner().delete(MATCH_AUDIT_TABLE, &match_attempt_key(&evicted))?;
+            }
+        }
+
+        self.inner().write(MATCH_AUDIT_TABLE, &MATCH_ATTEMPT_IDS_KEY.to_string(), &ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_gossip::WrappedPeerId;
+    use uuid::Uuid;
+
+    use crate::test_helpers::mock_db;
+
+    use super::{MAX_MATCH_AUDIT_ENTRIES, MatchAttempt, MatchPhase};
+
+    /// Tests that attempts are returned most recent first and that the trail
+    /// is bounded
+    #[test]
+    fn test_match_attempts_bounded() {
+        let db = mock_db();
+        let peer_id = WrappedPeerId::random();
+
+        let tx = db.new_write_tx().unwrap();
+        let attempts: Vec<_> = (0..MAX_MATCH_AUDIT_ENTRIES + 5)
+            .map(|i| MatchAttempt::new(peer_id, Uuid::new_v4(), i as u64))
+            .collect();
+        for attempt in attempts.iter() {
+            tx.append_match_attempt(attempt).unwrap();
+        }
+
+        let stored = tx.get_match_attempts().unwrap();
+        assert_eq!(stored.len(), MAX_MATCH_AUDIT_ENTRIES);
+        assert_eq!(stored.first(), attempts.last());
+        assert_eq!(stored.last(), attempts.get(5));
+        tx.commit().unwrap();
+    }
+
+    /// Tests that an indicative fill recorded in quote-only mode is stored
+    /// with its counterparty
+    #[test]
+    fn test_indicative_fill() {
+        let db = mock_db();
+        let (order_id, other_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut attempt = MatchAttempt::new(WrappedPeerId::random(), order_id, 0);
+        attempt.other_order_id = Some(other_id);
+        attempt.phase = MatchPhase::IndicativeFill;
+
+        let tx = db.new_write_tx().unwrap();
+        tx.append_match_attempt(&attempt).unwrap();
+        let stored = tx.get_match_attempts().unwrap();
+        tx.commit().unwrap();
+
+        assert_eq!(stored, vec![attempt]);
+        assert_eq!(stored[0].phase, MatchPhase::IndicativeFill);
+        assert!(stored[0].involves(&other_id));
+    }
+}
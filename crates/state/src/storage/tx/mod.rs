@@ -10,18 +10,28 @@
 #![allow(mismatched_lifetime_syntaxes)]
 
 pub mod account_index;
+pub mod blackout_windows;
+pub mod chain_events;
+pub mod consistency;
+pub mod export;
+pub mod feature_flags;
+pub mod gas_costs;
+pub mod match_audit;
 pub mod matching_pools;
 pub mod merkle_proofs;
 pub mod node_metadata;
 pub mod order_auth;
 pub mod order_book;
 pub mod peer_index;
+pub mod price_history;
 pub mod proofs;
 pub mod raft_log;
 pub mod relayer_fees;
+pub mod settlement_locks;
 pub mod task_assignments;
 pub mod task_history;
 pub mod task_queue;
+pub mod token_listings;
 
 use libmdbx::{
     Error as MdbxError, RW, Table, TableFlags, Transaction, TransactionKind, WriteFlags, WriteMap,
@@ -44,6 +44,9 @@ const HISTORICAL_STATE_ENABLED_KEY: &str = "historical-state-enabled";
 /// The key for the executor private key in the node metadata table
 const EXECUTOR_KEY: &str = "executor-key";
 
+/// The prefix of the keys holding the version of a stored layout
+const LAYOUT_VERSION_PREFIX: &str = "layout-version";
+
 /// A type alias for a with-wrapped fixed point
 type WithFixedPoint = RkyvWith<FixedPoint, FixedPointDef>;
 /// A type alias for a with-wrapped address
@@ -63,6 +66,11 @@ fn err_not_found(key: &str) -> StorageError {
     StorageError::NotFound(format!("node metadata key {key} not found"))
 }
 
+/// Build the key for the version of the given stored layout
+fn layout_version_key(layout: &str) -> String {
+    format!("{LAYOUT_VERSION_PREFIX}:{layout}")
+}
+
 // -----------
 // | Getters |
 // -----------
@@ -154,6 +162,18 @@ impl<T: TransactionKind> StateTxn<'_, T> {
             .deserialize()?;
         PrivateKeySigner::from_str(&hex_str).map_err(err_str!(StorageError::Other))
     }
+
+    /// Get the version of the given stored layout
+    ///
+    /// Databases written before the layout was versioned hold no entry, which
+    /// is version zero
+    pub fn get_layout_version(&self, layout: &str) -> Result<u32, StorageError> {
+        self.inner()
+            .read::<_, u32>(NODE_METADATA_TABLE, &layout_version_key(layout))?
+            .map(|archived| archived.deserialize())
+            .transpose()
+            .map(|version| version.unwrap_or_default())
+    }
 }
 
 // -----------
@@ -221,4 +241,9 @@ impl StateTxn<'_, RW> {
         let hex_str = util::hex::bytes_to_hex_string(secret_key_bytes.as_slice());
         self.inner().write(NODE_METADATA_TABLE, &EXECUTOR_KEY.to_string(), &hex_str)
     }
+
+    /// Set the version of the given stored layout
+    pub fn set_layout_version(&self, layout: &str, version: u32) -> Result<(), StorageError> {
+        self.inner().write(NODE_METADATA_TABLE, &layout_version_key(layout), &version)
+    }
 }
@@ -1,13 +1,14 @@
 //! Helpers for accessing network order book information in the database
 
-use alloy_primitives::map::HashSet;
+use alloy_primitives::map::{HashMap, HashSet};
 use circuit_types::Nullifier;
 use libmdbx::{RW, TransactionKind};
 use types_account::OrderId;
 use types_gossip::{
     ClusterId,
     network_order::{
-        CLUSTER_DEFAULT_PRIORITY, NetworkOrder, ORDER_DEFAULT_PRIORITY, OrderPriority,
+        CLUSTER_DEFAULT_PRIORITY, NetworkOrder, NetworkOrderState, ORDER_DEFAULT_PRIORITY,
+        OrderPriority,
     },
 };
 use uuid::Uuid;
@@ -57,6 +58,27 @@ pub fn locally_managed_key() -> String {
     "local-orders".to_string()
 }
 
+/// The prefix of the keys of removed orders' tombstones
+const ORDER_TOMBSTONE_PREFIX: &str = "order-tombstone:";
+
+/// The key for the tombstone of the order version with the given nullifier
+///
+/// Nullifiers are unique to an order version, so each removal of an order
+/// leaves its own tombstone
+pub fn order_tombstone_key(nullifier: Nullifier) -> String {
+    format!("{ORDER_TOMBSTONE_PREFIX}{nullifier}")
+}
+
+/// The prefix of the keys indexing the order book by order state
+const ORDER_STATE_INDEX_PREFIX: &str = "order-state:";
+
+/// The key for the set of orders in a given state
+///
+/// All `Matched` orders share a key, regardless of which node matched them
+pub fn order_state_key(state: &NetworkOrderState) -> String {
+    format!("{ORDER_STATE_INDEX_PREFIX}{state}")
+}
+
 // -----------
 // | Getters |
 // -----------
@@ -112,6 +134,29 @@ impl<T: TransactionKind> StateTxn<'_, T> {
         self.inner().read(ORDERS_TABLE, &key)
     }
 
+    /// Get the IDs of the orders in the book, grouped by the name of their
+    /// state
+    ///
+    /// Reads the order state index rather than the orders themselves
+    pub fn get_order_ids_by_state(&self) -> Result<Vec<(String, Vec<OrderId>)>, StorageError> {
+        let cursor = self
+            .inner()
+            .cursor::<String, HashSet<Uuid>>(ORDERS_TABLE)?
+            .with_key_prefix(ORDER_STATE_INDEX_PREFIX);
+
+        let mut res = Vec::new();
+        for entry in cursor.into_iter() {
+            let (key, order_ids) = entry?;
+            let state = key.trim_start_matches(ORDER_STATE_INDEX_PREFIX).to_string();
+            let order_ids: HashSet<Uuid> = order_ids.deserialize()?;
+            if !order_ids.is_empty() {
+                res.push((state, order_ids.into_iter().collect()));
+            }
+        }
+
+        Ok(res)
+    }
+
     /// Get all orders in the book
     ///
     /// Warning: this can be very slow when the state has a medium to large
@@ -131,6 +176,29 @@ impl<T: TransactionKind> StateTxn<'_, T> {
         Ok(res)
     }
 
+    /// Whether the order version with the given nullifier has been removed
+    pub fn is_order_tombstoned(&self, nullifier: Nullifier) -> Result<bool, StorageError> {
+        let key = order_tombstone_key(nullifier);
+        let tombstone = self.inner().read::<_, NetworkOrder>(ORDERS_TABLE, &key)?;
+        Ok(tombstone.is_some())
+    }
+
+    /// Get the tombstones of all removed orders, each holding the order as it
+    /// was when removed
+    pub fn get_order_tombstones(&self) -> Result<Vec<NetworkOrderValue>, StorageError> {
+        let cursor = self
+            .inner()
+            .cursor::<String, NetworkOrder>(ORDERS_TABLE)?
+            .with_key_prefix(ORDER_TOMBSTONE_PREFIX);
+
+        let mut res = Vec::new();
+        for elem in cursor.into_iter().values() {
+            res.push(elem?);
+        }
+
+        Ok(res)
+    }
+
     // --- Helpers --- //
 
     /// Get an order and error if it is not present
@@ -162,6 +230,16 @@ impl StateTxn<'_, RW> {
         // First, update the nullifier -> order mapping
         self.update_order_nullifier(&order.id, order.nullifier)?;
 
+        // Move the order between state index sets if its state changed
+        let new_state_key = order_state_key(&order.state);
+        if let Some(prev) = self.get_order_info(&order.id)? {
+            let prev_state_key = order_state_key(&prev.deserialize()?.state);
+            if prev_state_key != new_state_key {
+                self.remove_from_order_set(&prev_state_key, &order.id)?;
+            }
+        }
+        self.add_to_order_set(&new_state_key, &order.id)?;
+
         let key = order_key(&order.id);
         let order = order.clone();
         self.inner().write(ORDERS_TABLE, &key, &order)
@@ -210,7 +288,9 @@ impl StateTxn<'_, RW> {
         // Deserialize the nullifier before deleting the order
         let nullifier: Nullifier = WithScalar::from_archived(&order_info.nullifier)?.into_inner();
         let is_local = order_info.local;
+        let state_key = order_state_key(&order_info.deserialize()?.state);
         self.inner().delete(ORDERS_TABLE, &order_key)?;
+        self.remove_from_order_set(&state_key, order_id)?;
 
         // Remove from the nullifier mapping
         self.remove_nullifier_mapping(nullifier)?;
@@ -227,6 +307,9 @@ impl StateTxn<'_, RW> {
 
     /// Nullify the order indexed by the given nullifier.
     /// Returns the ID of the deleted order if one was found.
+    ///
+    /// The nullified version of the order is kept as a tombstone, so that it is
+    /// not re-added by a peer that has not yet seen the nullifier spent
     pub fn nullify_order(&self, nullifier: Nullifier) -> Result<Option<OrderId>, StorageError> {
         // Get the order for this nullifier
         let order_id = match self.get_order_by_nullifier(nullifier)? {
@@ -234,7 +317,11 @@ impl StateTxn<'_, RW> {
             None => return Ok(None),
         };
 
-        // Delete the order; this also removes the nullifier mapping
+        // Write the tombstone, then delete the order; this also removes the
+        // nullifier mapping
+        let mut tombstone = self.get_order_info_or_err(&order_id)?.deserialize()?;
+        tombstone.state = NetworkOrderState::Cancelled;
+        self.inner().write(ORDERS_TABLE, &order_tombstone_key(nullifier), &tombstone)?;
         self.delete_order(&order_id)?;
         Ok(Some(order_id))
     }
@@ -257,36 +344,67 @@ impl StateTxn<'_, RW> {
 
     /// Add an order to the locally managed orders set
     pub fn mark_order_local(&self, order_id: &OrderId) -> Result<(), StorageError> {
-        let key = locally_managed_key();
-
-        // Read-modify-write the local orders set
-        let mut set = match self.get_local_orders()? {
-            Some(archived) => archived.deserialize()?,
-            None => HashSet::default(),
-        };
-        set.insert(*order_id);
-        self.inner().write(ORDERS_TABLE, &key, &set)
+        self.add_to_order_set(&locally_managed_key(), order_id)
     }
 
     /// Remove an order from the locally managed orders set
     pub fn remove_local_order(&self, order_id: &OrderId) -> Result<(), StorageError> {
-        let key = locally_managed_key();
+        self.remove_from_order_set(&locally_managed_key(), order_id)
+    }
+
+    /// Build the order state index from the orders in the book if the index
+    /// is empty
+    ///
+    /// Books written before the index was introduced hold orders but no index
+    /// entries. Returns the number of orders indexed
+    pub fn backfill_order_state_index(&self) -> Result<usize, StorageError> {
+        if !self.get_order_ids_by_state()?.is_empty() {
+            return Ok(0);
+        }
 
-        // Read-modify-write the local orders set
-        let set = match self.get_local_orders()? {
+        let mut index: HashMap<String, HashSet<Uuid>> = HashMap::default();
+        for order in self.get_all_orders()? {
+            let order = order.deserialize()?;
+            index.entry(order_state_key(&order.state)).or_default().insert(order.id);
+        }
+
+        let n_orders = index.values().map(|set| set.len()).sum();
+        for (key, set) in index {
+            self.inner().write(ORDERS_TABLE, &key, &set)?;
+        }
+
+        Ok(n_orders)
+    }
+
+    // --- Helpers --- //
+
+    /// Add an order to the order set at the given key
+    fn add_to_order_set(&self, key: &str, order_id: &OrderId) -> Result<(), StorageError> {
+        // Read-modify-write the set
+        let key = key.to_string();
+        let mut set = match self.inner().read::<_, HashSet<Uuid>>(ORDERS_TABLE, &key)? {
             Some(archived) => archived.deserialize()?,
-            None => return Ok(()),
+            None => HashSet::default(),
         };
+        set.insert(*order_id);
+        self.inner().write(ORDERS_TABLE, &key, &set)
+    }
 
-        let mut set: HashSet<Uuid> = set;
+    /// Remove an order from the order set at the given key
+    fn remove_from_order_set(&self, key: &str, order_id: &OrderId) -> Result<(), StorageError> {
+        // Read-modify-write the set
+        let key = key.to_string();
+        let mut set: HashSet<Uuid> =
+            match self.inner().read::<_, HashSet<Uuid>>(ORDERS_TABLE, &key)? {
+                Some(archived) => archived.deserialize()?,
+                None => return Ok(()),
+            };
         set.remove(order_id);
         self.inner().write(ORDERS_TABLE, &key, &set)
     }
 
-    // --- Helpers --- //
-
     /// Set a nullifier -> order mapping
-    fn set_nullifier_mapping(
+    pub(crate) fn set_nullifier_mapping(
         &self,
         nullifier: Nullifier,
         order_id: &OrderId,
@@ -308,11 +426,13 @@ mod test {
     use itertools::Itertools;
     use rand::thread_rng;
     use types_gossip::network_order::{
-        ArchivedNetworkOrderState, test_helpers::dummy_network_order,
+        ArchivedNetworkOrderState, NetworkOrderState, test_helpers::dummy_network_order,
     };
 
     use crate::{ORDERS_TABLE, PRIORITIES_TABLE, test_helpers::mock_db};
 
+    use super::order_state_key;
+
     /// Tests adding an order to the order book
     #[test]
     fn test_write_order() {
@@ -428,6 +548,14 @@ mod test {
         assert_eq!(stored_order.deserialize().unwrap(), order2);
         assert!(order_by_nullifier.is_some());
         assert_eq!(order_by_nullifier.unwrap().deserialize().unwrap(), order2.id);
+        // Only the nullified order leaves a tombstone
+        assert!(tx.is_order_tombstoned(order1.nullifier).unwrap());
+        assert!(!tx.is_order_tombstoned(order2.nullifier).unwrap());
+        let tombstones = tx.get_order_tombstones().unwrap();
+        assert_eq!(tombstones.len(), 1);
+        let tombstone = tombstones[0].deserialize().unwrap();
+        assert_eq!(tombstone.id, order1.id);
+        assert!(tombstone.is_cancelled());
     }
 
     /// Tests adding and removing local orders
@@ -504,4 +632,73 @@ mod test {
         let priority = tx.inner().read::<_, OrderPriority>(PRIORITIES_TABLE, &order.id).unwrap();
         assert!(priority.is_none());
     }
+
+    /// Tests that the order state index follows orders through their states
+    #[test]
+    fn test_order_state_index() {
+        let db = mock_db();
+        db.create_table(ORDERS_TABLE).unwrap();
+        db.create_table(PRIORITIES_TABLE).unwrap();
+
+        // Write two orders to the book and verify one of them
+        let order1 = dummy_network_order();
+        let order2 = dummy_network_order();
+        let tx = db.new_write_tx().unwrap();
+        tx.write_order(&order1).unwrap();
+        tx.write_order(&order2).unwrap();
+        tx.attach_validity_proof(&order2.id, Scalar::random(&mut thread_rng())).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        let mut by_state = tx.get_order_ids_by_state().unwrap();
+        by_state.sort();
+        assert_eq!(
+            by_state,
+            vec![
+                ("Received".to_string(), vec![order1.id]),
+                ("Verified".to_string(), vec![order2.id]),
+            ]
+        );
+        drop(tx);
+
+        // Delete the verified order
+        let tx = db.new_write_tx().unwrap();
+        tx.delete_order(&order2.id).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        let by_state = tx.get_order_ids_by_state().unwrap();
+        assert_eq!(by_state, vec![("Received".to_string(), vec![order1.id])]);
+    }
+
+    /// Tests backfilling the order state index for orders written before it
+    /// existed
+    #[test]
+    fn test_backfill_order_state_index() {
+        let db = mock_db();
+        db.create_table(ORDERS_TABLE).unwrap();
+
+        // Write orders to the book, then drop the index as an older book would
+        // lack it
+        let orders = (0..3).map(|_| dummy_network_order()).collect_vec();
+        let tx = db.new_write_tx().unwrap();
+        for order in orders.iter() {
+            tx.write_order(order).unwrap();
+        }
+        let key = order_state_key(&NetworkOrderState::Received);
+        tx.inner().delete(ORDERS_TABLE, &key).unwrap();
+        assert!(tx.get_order_ids_by_state().unwrap().is_empty());
+
+        // The backfill indexes every order, and is a no-op once the index exists
+        assert_eq!(tx.backfill_order_state_index().unwrap(), orders.len());
+        assert_eq!(tx.backfill_order_state_index().unwrap(), 0);
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        let by_state = tx.get_order_ids_by_state().unwrap();
+        let expected = orders.iter().map(|o| o.id).sorted().collect_vec();
+        assert_eq!(by_state.len(), 1);
+        assert_eq!(by_state[0].0, "Received");
+        assert_eq!(by_state[0].1.iter().copied().sorted().collect_vec(), expected);
+    }
 }
@@ -4,7 +4,7 @@ use std::collections::{HashMap, HashSet};
 
 use libmdbx::{RW, TransactionKind};
 use libp2p::core::Multiaddr;
-use types_gossip::{ClusterId, PeerInfo, WrappedPeerId};
+use types_gossip::{ClusterId, MultiaddrDef, PeerInfo, WrappedPeerId};
 
 use crate::{
     CLUSTER_MEMBERSHIP_TABLE, PEER_INFO_TABLE, storage::ArchivedValue, storage::error::StorageError,
@@ -21,6 +21,45 @@ pub type ClusterPeersValue<'a> = ArchivedValue<'a, HashSet<WrappedPeerId>>;
 /// table
 const PEER_NOT_FOUND_ERR: &str = "could not find peer in peer info table";
 
+/// The name of the stored peer info layout, see `migrate_legacy_peers`
+pub(crate) const PEER_INFO_LAYOUT: &str = "peer-info";
+/// The current version of the stored peer info layout
+///
+/// Version 1 added the peer's advertised metadata
+pub(crate) const PEER_INFO_LAYOUT_VERSION: u32 = 1;
+
+/// The stored layout of a peer's info before it carried the peer's metadata
+///
+/// Only read when migrating peers written by earlier versions
+#[derive(Clone, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+struct LegacyPeerInfo {
+    /// The identifier used by libp2p for a peer
+    peer_id: WrappedPeerId,
+    /// The multiaddr of the peer
+    #[rkyv(with = MultiaddrDef)]
+    addr: Multiaddr,
+    /// Last time a successful heartbeat was received from this peer
+    last_heartbeat: u64,
+    /// The ID of the cluster the peer belongs to
+    cluster_id: ClusterId,
+    /// The signature of the peer's ID with their cluster private key
+    cluster_auth_signature: Vec<u8>,
+}
+
+impl From<LegacyPeerInfo> for PeerInfo {
+    fn from(legacy: LegacyPeerInfo) -> Self {
+        Self {
+            peer_id: legacy.peer_id,
+            addr: legacy.addr,
+            last_heartbeat: legacy.last_heartbeat,
+            cluster_id: legacy.cluster_id,
+            cluster_auth_signature: legacy.cluster_auth_signature,
+            metadata: None,
+        }
+    }
+}
+
 // -----------
 // | Getters |
 // -----------
@@ -140,6 +179,32 @@ impl StateTxn<'_, RW> {
         peer_info.addr = addr;
         self.write_peer(&peer_info)
     }
+
+    /// Rewrite the peers stored in a layout older than
+    /// `PEER_INFO_LAYOUT_VERSION`
+    ///
+    /// Runs once per database, recording the layout version in the node
+    /// metadata when done. Returns the number of peers migrated
+    pub fn migrate_legacy_peers(&self) -> Result<usize, StorageError> {
+        if self.get_layout_version(PEER_INFO_LAYOUT)? >= PEER_INFO_LAYOUT_VERSION {
+            return Ok(0);
+        }
+
+        let legacy_peers = self
+            .inner()
+            .cursor::<WrappedPeerId, LegacyPeerInfo>(PEER_INFO_TABLE)?
+            .into_iter()
+            .map(|res| res.and_then(|(_key, val)| val.deserialize()))
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        let n_migrated = legacy_peers.len();
+        for legacy in legacy_peers {
+            self.write_peer(&PeerInfo::from(legacy))?;
+        }
+
+        self.set_layout_version(PEER_INFO_LAYOUT, PEER_INFO_LAYOUT_VERSION)?;
+        Ok(n_migrated)
+    }
 }
 
 #[cfg(test)]
@@ -148,9 +213,12 @@ mod test {
     use std::net::{IpAddr, Ipv4Addr};
 
     use libp2p::Multiaddr;
-    use types_gossip::mocks::mock_peer;
+    use types_gossip::{PeerMetadata, mocks::mock_peer};
 
-    use crate::{CLUSTER_MEMBERSHIP_TABLE, PEER_INFO_TABLE, test_helpers::mock_db};
+    use super::{LegacyPeerInfo, PEER_INFO_LAYOUT, PEER_INFO_LAYOUT_VERSION};
+    use crate::{
+        CLUSTER_MEMBERSHIP_TABLE, NODE_METADATA_TABLE, PEER_INFO_TABLE, test_helpers::mock_db,
+    };
 
     /// Test adding a peer to the index
     #[test]
@@ -307,4 +375,45 @@ mod test {
         let retrieved = res.deserialize().unwrap();
         assert_eq!(retrieved.addr, new_addr);
     }
+
+    /// Tests migrating peers stored in the layout that predates peer metadata
+    #[test]
+    fn test_migrate_legacy_peers() {
+        let db = mock_db();
+        db.create_table(PEER_INFO_TABLE).unwrap();
+        db.create_table(NODE_METADATA_TABLE).unwrap();
+
+        // Write a peer in the legacy layout
+        let peer = mock_peer();
+        let legacy = LegacyPeerInfo {
+            peer_id: peer.peer_id,
+            addr: peer.addr.clone(),
+            last_heartbeat: peer.last_heartbeat,
+            cluster_id: peer.cluster_id.clone(),
+            cluster_auth_signature: peer.cluster_auth_signature.clone(),
+        };
+
+        let tx = db.new_write_tx().unwrap();
+        tx.inner().write(PEER_INFO_TABLE, &peer.peer_id, &legacy).unwrap();
+        assert_eq!(tx.migrate_legacy_peers().unwrap(), 1);
+        tx.commit().unwrap();
+
+        // The peer now reads in the current layout, and can carry metadata
+        let tx = db.new_write_tx().unwrap();
+        let migrated = tx.get_peer_info(&peer.peer_id).unwrap().unwrap().deserialize().unwrap();
+        assert_eq!(migrated, peer);
+        assert_eq!(tx.get_layout_version(PEER_INFO_LAYOUT).unwrap(), PEER_INFO_LAYOUT_VERSION);
+
+        let mut with_metadata = migrated;
+        with_metadata.set_metadata(PeerMetadata::local());
+        tx.write_peer(&with_metadata).unwrap();
+        tx.commit().unwrap();
+
+        // A second run leaves the migrated peers alone
+        let tx = db.new_write_tx().unwrap();
+        assert_eq!(tx.migrate_legacy_peers().unwrap(), 0);
+        let stored = tx.get_peer_info(&peer.peer_id).unwrap().unwrap().deserialize().unwrap();
+        assert_eq!(stored, with_metadata);
+        tx.commit().unwrap();
+    }
 }
@@ -0,0 +1,246 @@
+//! Storage helpers for the local node's price history
+//!
+//! Prices sampled from the price reporter are recorded here so that clients
+//! can chart a pair's price without their own exchange connections. The table
+//! is local to each node and is not replicated through raft.
+//!
+//! Samples are grouped into one-minute buckets, keyed as
+//! `price-history/{base}/{quote}/{bucket}` -> `Vec<PriceSample>`, so that a
+//! window is read bucket by bucket. The range of retained buckets of each pair
+//! is tracked so that reads stop at the newest bucket, and so that buckets past
+//! the retention window are evicted as new samples arrive
+
+use alloy_primitives::Address;
+use libmdbx::{RW, TransactionKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{PRICE_HISTORY_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The length of the time span covered by a bucket of samples, in
+/// milliseconds
+pub const PRICE_BUCKET_MS: u64 = 60_000; // 1 minute
+
+// ---------
+// | Types |
+// ---------
+
+/// A price sampled from the price reporter
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub struct PriceSample {
+    /// The time (in milliseconds) at which the price was reported
+    pub timestamp: u64,
+    /// The price of the base token in units of the quote token
+    pub price: f64,
+}
+
+/// The range of buckets retained in a pair's price history
+#[derive(Clone, Copy, Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug))]
+struct PriceBucketRange {
+    /// The oldest retained bucket
+    oldest: u64,
+    /// The newest retained bucket
+    newest: u64,
+}
+
+// ---------------
+// | Key Helpers |
+// ---------------
+
+/// Build the key for a bucket of a pair's samples
+fn price_bucket_key(base: &Address, quote: &Address, bucket: u64) -> String {
+    format!("price-history/{base}/{quote}/{bucket}")
+}
+
+/// Build the key for the range of retained buckets of a pair's samples
+fn bucket_range_key(base: &Address, quote: &Address) -> String {
+    format!("price-history-range/{base}/{quote}")
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the samples of a pair's price reported within `[start, end]`,
+    /// oldest first
+    pub fn get_price_samples(
+        &self,
+        base: &Address,
+        quote: &Address,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<PriceSample>, StorageError> {
+        let Some(range) = self.get_price_bucket_range(base, quote)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut samples = Vec::new();
+        let first_bucket = (start / PRICE_BUCKET_MS).max(range.oldest);
+        let last_bucket = (end / PRICE_BUCKET_MS).min(range.newest);
+        for bucket in first_bucket..=last_bucket {
+            let bucket_samples = self.get_price_bucket(base, quote, bucket)?;
+            let in_window =
+                bucket_samples.into_iter().filter(|s| (start..=end).contains(&s.timestamp));
+            samples.extend(in_window);
+        }
+
+        Ok(samples)
+    }
+
+    /// Get the samples in a bucket of a pair's price history
+    fn get_price_bucket(
+        &self,
+        base: &Address,
+        quote: &Address,
+        bucket: u64,
+    ) -> Result<Vec<PriceSample>, StorageError> {
+        let key = price_bucket_key(base, quote, bucket);
+        let samples = self.inner().read::<_, Vec<PriceSample>>(PRICE_HISTORY_TABLE, &key)?;
+        Ok(samples.map(|s| s.deserialize()).transpose()?.unwrap_or_default())
+    }
+
+    /// Get the range of retained buckets of a pair's price history, if any
+    /// samples have been recorded
+    fn get_price_bucket_range(
+        &self,
+        base: &Address,
+        quote: &Address,
+    ) -> Result<Option<PriceBucketRange>, StorageError> {
+        let key = bucket_range_key(base, quote);
+        let range = self.inner().read::<_, PriceBucketRange>(PRICE_HISTORY_TABLE, &key)?;
+        range.map(|r| r.deserialize()).transpose()
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Append a sample to a pair's price history, evicting the buckets that
+    /// fall outside the retention window
+    ///
+    /// Samples are expected to be appended in timestamp order. A late sample
+    /// that already falls outside the retention window of the pair's newest
+    /// bucket is dropped rather than written to a bucket no eviction would
+    /// reach
+    pub fn append_price_sample(
+        &self,
+        base: &Address,
+        quote: &Address,
+        sample: PriceSample,
+        retention_ms: u64,
+    ) -> Result<(), StorageError> {
+        let bucket = sample.timestamp / PRICE_BUCKET_MS;
+        let range = self.get_price_bucket_range(base, quote)?;
+        let oldest = range.map_or(bucket, |r| r.oldest);
+        let prev_newest = range.map_or(bucket, |r| r.newest);
+        let newest = prev_newest.max(bucket);
+
+        // The retention window ends at the newer of the sample and the newest
+        // bucket, so that a late sample cannot pull the window back
+        let window_end = sample.timestamp.max(prev_newest * PRICE_BUCKET_MS);
+        let cutoff = window_end.saturating_sub(retention_ms) / PRICE_BUCKET_MS;
+        if bucket < cutoff {
+            return Ok(());
+        }
+
+        let mut samples = self.get_price_bucket(base, quote, bucket)?;
+        samples.push(sample);
+        self.inner().write(
+            PRICE_HISTORY_TABLE,
+            &price_bucket_key(base, quote, bucket),
+            &samples,
+        )?;
+
+        // Evict the buckets that end before the retention window begins
+        for evicted in oldest..cutoff.min(prev_newest + 1) {
+            self.inner().delete(PRICE_HISTORY_TABLE, &price_bucket_key(base, quote, evicted))?;
+        }
+
+        let range = PriceBucketRange { oldest: oldest.max(cutoff).min(bucket), newest };
+        self.inner().write(PRICE_HISTORY_TABLE, &bucket_range_key(base, quote), &range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Address;
+
+    use crate::test_helpers::mock_db;
+
+    use super::{PRICE_BUCKET_MS, PriceSample};
+
+    /// Tests reading a window of samples and evicting samples past the
+    /// retention window
+    #[test]
+    fn test_price_samples_window() {
+        let db = mock_db();
+        let (base, quote) = (Address::random(), Address::random());
+        let retention_ms = 10 * PRICE_BUCKET_MS;
+
+        // Record a sample every 30 seconds for 20 minutes
+        let tx = db.new_write_tx().unwrap();
+        let samples: Vec<_> = (0..40)
+            .map(|i| PriceSample { timestamp: i * PRICE_BUCKET_MS / 2, price: i as f64 })
+            .collect();
+        for sample in samples.iter() {
+            tx.append_price_sample(&base, &quote, *sample, retention_ms).unwrap();
+        }
+
+        // A window within the retention period is read in full
+        let start = 12 * PRICE_BUCKET_MS;
+        let end = 15 * PRICE_BUCKET_MS;
+        let window = tx.get_price_samples(&base, &quote, start, end).unwrap();
+        assert_eq!(window, samples[24..=30].to_vec());
+
+        // Samples older than the retention period are evicted
+        let all = tx.get_price_samples(&base, &quote, 0, u64::MAX).unwrap();
+        assert_eq!(all.first(), samples.get(18));
+        assert_eq!(all.last(), samples.last());
+
+        // Other pairs are unaffected
+        let other = tx.get_price_samples(&quote, &base, 0, end).unwrap();
+        assert!(other.is_empty());
+        tx.commit().unwrap();
+    }
+
+    /// Tests that a late sample outside the retention window is dropped
+    /// rather than left in a bucket that is never evicted
+    #[test]
+    fn test_late_price_sample() {
+        let db = mock_db();
+        let (base, quote) = (Address::random(), Address::random());
+        let retention_ms = 10 * PRICE_BUCKET_MS;
+
+        let tx = db.new_write_tx().unwrap();
+        let sample = PriceSample { timestamp: 100 * PRICE_BUCKET_MS, price: 1. };
+        tx.append_price_sample(&base, &quote, sample, retention_ms).unwrap();
+
+        // A sample outside the window is not written
+        let late = PriceSample { timestamp: 50 * PRICE_BUCKET_MS, price: 2. };
+        tx.append_price_sample(&base, &quote, late, retention_ms).unwrap();
+        assert!(tx.get_price_bucket(&base, &quote, 50).unwrap().is_empty());
+
+        // A late sample within the window is kept
+        let recent = PriceSample { timestamp: 95 * PRICE_BUCKET_MS, price: 3. };
+        tx.append_price_sample(&base, &quote, recent, retention_ms).unwrap();
+        let all = tx.get_price_samples(&base, &quote, 0, u64::MAX).unwrap();
+        assert_eq!(all, vec![recent, sample]);
+        tx.commit().unwrap();
+    }
+}
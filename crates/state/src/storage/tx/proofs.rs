@@ -61,6 +61,25 @@ impl<T: TransactionKind> StateTxn<'_, T> {
         }
     }
 
+    /// Check whether a validity proof bundle is stored, without deserializing
+    /// it
+    pub fn has_validity_proof<P: StoredValidityProof>(
+        &self,
+        locator: &ValidityProofLocator,
+    ) -> Result<bool, StorageError> {
+        let key = storage_key(P::PROOF_TYPE_KEY, locator);
+        Ok(self.inner().read_bytes(PROOFS_TABLE, &key)?.is_some())
+    }
+
+    /// Check whether a validity witness is stored, without deserializing it
+    pub fn has_validity_witness<W: StoredValidityWitness>(
+        &self,
+        locator: &ValidityProofLocator,
+    ) -> Result<bool, StorageError> {
+        let key = storage_key(W::WITNESS_TYPE_KEY, locator);
+        Ok(self.inner().read_bytes(PROOFS_TABLE, &key)?.is_some())
+    }
+
     /// Check whether any output balance validity proof exists for a locator
     pub fn has_output_balance_validity_proof(
         &self,
@@ -185,7 +204,9 @@ mod test {
     use types_account::account::OrderId;
     use types_core::AccountId;
     use types_proofs::{
-        IntentOnlyValidityBundle, ValidityProofLocator, mocks::mock_intent_only_validity_bundle,
+        IntentOnlyFirstFillValidityBundle, IntentOnlyValidityBundle,
+        SizedIntentOnlyValidityWitness, ValidityProofLocator,
+        mocks::mock_intent_only_validity_bundle,
     };
 
     use crate::{PROOFS_TABLE, test_helpers::mock_db};
@@ -223,6 +244,25 @@ mod test {
         assert!(retrieved.is_none());
     }
 
+    /// Tests checking for a stored proof by type without deserializing it
+    #[test]
+    fn test_has_validity_proof() {
+        let db = mock_db();
+        db.create_table(PROOFS_TABLE).unwrap();
+
+        let locator = ValidityProofLocator::Intent { order_id: OrderId::new_v4() };
+        let bundle = mock_intent_only_validity_bundle();
+
+        let tx = db.new_write_tx().unwrap();
+        tx.write_validity_proof::<IntentOnlyValidityBundle>(&locator, &bundle).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert!(tx.has_validity_proof::<IntentOnlyValidityBundle>(&locator).unwrap());
+        assert!(!tx.has_validity_proof::<IntentOnlyFirstFillValidityBundle>(&locator).unwrap());
+        assert!(!tx.has_validity_witness::<SizedIntentOnlyValidityWitness>(&locator).unwrap());
+    }
+
     /// Tests deleting a specific validity proof type
     #[test]
     fn test_delete_validity_proof() {
@@ -231,9 +231,15 @@ impl StateTxn<'_, RW> {
 #[cfg(test)]
 mod test {
 
+    use itertools::Itertools;
     use openraft::{EntryPayload, LeaderId, LogId};
 
-    use crate::{replication::Entry, storage::tx::raft_log::parse_lsn, test_helpers::mock_db};
+    use crate::{
+        replication::Entry,
+        state_transition::{Proposal, StateTransition},
+        storage::tx::raft_log::parse_lsn,
+        test_helpers::mock_db,
+    };
 
     /// Get an empty log entry with the given index
     fn empty_entry(idx: u64, term: u64, node_id: u64) -> Entry {
@@ -337,4 +343,43 @@ mod test {
             assert!(entry.is_err());
         }
     }
+
+    /// Tests that a batch of proposals is read back from the log intact
+    #[test]
+    fn test_batched_entry() {
+        const N: usize = 3;
+        let db = mock_db();
+
+        // Append a single entry carrying a batch of proposals
+        let proposals = (0..N)
+            .map(|i| {
+                Proposal::from(StateTransition::CreateMatchingPool { pool_name: i.to_string() })
+            })
+            .collect_vec();
+        let ids = proposals.iter().map(|p| p.id).collect_vec();
+        let batch = Proposal::coalesce(proposals).unwrap();
+
+        let leader_id = LeaderId::new(1 /* term */, 1 /* node */);
+        let log_id = LogId::new(leader_id, 1 /* index */);
+        let entry = Entry { log_id, payload: EntryPayload::Normal(batch) };
+        let tx = db.new_write_tx().unwrap();
+        tx.append_log_entries(vec![entry]).unwrap();
+        tx.commit().unwrap();
+
+        // Read the entry back and split it into its proposals
+        let tx = db.new_read_tx().unwrap();
+        let entry = tx.read_log_entry(1).unwrap().deserialize_with().unwrap();
+        let EntryPayload::Normal(proposal) = entry.payload else {
+            panic!("expected a normal entry");
+        };
+
+        let parts = proposal.into_parts();
+        assert_eq!(parts.iter().map(|(id, _)| *id).collect_vec(), ids);
+        for (i, (_, transition)) in parts.into_iter().enumerate() {
+            let StateTransition::CreateMatchingPool { pool_name } = *transition else {
+                panic!("expected a matching pool creation");
+            };
+            assert_eq!(pool_name, i.to_string());
+        }
+    }
 }
@@ -0,0 +1,121 @@
+//! Storage helpers for cluster-wide settlement locks
+//!
+//! A settlement lock is held on an order while a cluster peer settles a match
+//! on it, so that two replicas do not submit conflicting settlements for
+//! overlapping order pairs. Locks are keyed as
+//! `settlement-lock/{order_id}` -> `SettlementLock`
+
+use libmdbx::{RW, TransactionKind};
+use serde::{Deserialize, Serialize};
+use types_account::OrderId;
+use types_gossip::WrappedPeerId;
+
+use crate::{SETTLEMENT_LOCKS_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+// ---------
+// | Types |
+// ---------
+
+/// A lock held by a cluster peer on an order while it settles a match
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[rkyv(derive(Debug))]
+pub struct SettlementLock {
+    /// The peer holding the lock
+    pub holder: WrappedPeerId,
+    /// The timestamp (in milliseconds) after which the lock may be taken over
+    pub expires_at: u64,
+}
+
+impl SettlementLock {
+    /// Whether the lock has expired at the given timestamp
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+// ---------------
+// | Key Helpers |
+// ---------------
+
+/// Build the key for an order's settlement lock
+fn settlement_lock_key(order_id: &OrderId) -> String {
+    format!("settlement-lock/{order_id}")
+}
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get the settlement lock on an order, if one exists
+    pub fn get_settlement_lock(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Option<SettlementLock>, StorageError> {
+        let key = settlement_lock_key(order_id);
+        let lock = self.inner().read::<_, SettlementLock>(SETTLEMENT_LOCKS_TABLE, &key)?;
+        lock.map(|l| l.deserialize()).transpose()
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Write the settlement lock on an order
+    pub fn write_settlement_lock(
+        &self,
+        order_id: &OrderId,
+        lock: &SettlementLock,
+    ) -> Result<(), StorageError> {
+        let key = settlement_lock_key(order_id);
+        self.inner().write(SETTLEMENT_LOCKS_TABLE, &key, lock)
+    }
+
+    /// Delete the settlement lock on an order
+    pub fn delete_settlement_lock(&self, order_id: &OrderId) -> Result<(), StorageError> {
+        let key = settlement_lock_key(order_id);
+        self.inner().delete(SETTLEMENT_LOCKS_TABLE, &key).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_gossip::WrappedPeerId;
+    use uuid::Uuid;
+
+    use crate::test_helpers::mock_db;
+
+    use super::SettlementLock;
+
+    /// Tests writing, reading, and deleting a settlement lock
+    #[test]
+    fn test_settlement_lock_roundtrip() {
+        let db = mock_db();
+        let order_id = Uuid::new_v4();
+        let lock = SettlementLock { holder: WrappedPeerId::random(), expires_at: 100 };
+
+        let tx = db.new_write_tx().unwrap();
+        assert!(tx.get_settlement_lock(&order_id).unwrap().is_none());
+        tx.write_settlement_lock(&order_id, &lock).unwrap();
+        assert_eq!(tx.get_settlement_lock(&order_id).unwrap(), Some(lock));
+
+        tx.delete_settlement_lock(&order_id).unwrap();
+        assert!(tx.get_settlement_lock(&order_id).unwrap().is_none());
+        tx.commit().unwrap();
+    }
+}
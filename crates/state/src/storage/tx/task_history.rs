@@ -3,7 +3,7 @@
 use std::cmp::Reverse;
 
 use libmdbx::{RW, TransactionKind};
-use types_tasks::{HistoricalTask, TaskIdentifier, TaskQueueKey};
+use types_tasks::{HistoricalTask, TaskHistoryFilter, TaskIdentifier, TaskQueueKey};
 
 use crate::{
     NODE_METADATA_TABLE, TASK_HISTORY_TABLE,
@@ -22,12 +22,12 @@ type HistoricalTaskValue<'a> = ArchivedValue<'a, HistoricalTask>;
 type TaskIdListValue<'a> = ArchivedValue<'a, Vec<TaskIdentifier>>;
 
 /// Get the key for a given queue's history
-fn task_history_key(key: &TaskQueueKey) -> String {
+pub(crate) fn task_history_key(key: &TaskQueueKey) -> String {
     format!("{key}-history")
 }
 
 /// Get the key for a specific task in the history
-fn task_history_item_key(key: &TaskQueueKey, task_id: &TaskIdentifier) -> String {
+pub(crate) fn task_history_item_key(key: &TaskQueueKey, task_id: &TaskIdentifier) -> String {
     format!("{key}-history-task-{task_id}")
 }
 
@@ -76,6 +76,57 @@ impl<T: TransactionKind> StateTxn<'_, T> {
         Ok(tasks)
     }
 
+    /// Get up to `limit` tasks from the task history that match the filter,
+    /// starting after the task with ID `cursor` if one is given
+    ///
+    /// Tasks are visited most recent first and only read until the page is
+    /// full. Returns the page along with a flag indicating whether more
+    /// matching tasks remain. A cursor that is not in the history yields an
+    /// empty page
+    pub fn get_task_history_page(
+        &self,
+        key: &TaskQueueKey,
+        filter: &TaskHistoryFilter,
+        cursor: Option<TaskIdentifier>,
+        limit: usize,
+    ) -> Result<(Vec<HistoricalTask>, bool), StorageError> {
+        let ids_value = self.get_task_ids_in_history(key)?;
+        let Some(ids_value) = ids_value else {
+            return Ok((Vec::new(), false));
+        };
+        let ids = &*ids_value;
+
+        // Skip past the cursor if one is given
+        let start = match cursor {
+            Some(cursor) => match ids.iter().position(|id| *id == cursor) {
+                Some(idx) => idx + 1,
+                None => return Ok((Vec::new(), false)),
+            },
+            None => 0,
+        };
+
+        let mut tasks = Vec::new();
+        for task_id in ids.iter().skip(start) {
+            let item_key = task_history_item_key(key, task_id);
+            let task_value =
+                self.inner().read::<_, HistoricalTask>(TASK_HISTORY_TABLE, &item_key)?;
+            let Some(task) = task_value.map(|v| v.deserialize()).transpose()? else {
+                continue;
+            };
+
+            if !filter.matches(&task) {
+                continue;
+            }
+
+            if tasks.len() == limit {
+                return Ok((tasks, true));
+            }
+            tasks.push(task);
+        }
+
+        Ok((tasks, false))
+    }
+
     /// Check that the task history table is enabled, throwing an error if not
     fn check_task_history_enabled(&self) -> Result<(), StorageError> {
         // If the flag doesn't exist, treat it as disabled
@@ -142,10 +193,11 @@ impl StateTxn<'_, RW> {
         let ids_value = self.get_task_ids_in_history(key)?;
         let task_ids = ids_value.map(|a| a.deserialize()).transpose()?.unwrap_or_default();
 
-        // Delete each individual task
+        // Delete each individual task along with its recorded gas costs
         for task_id in task_ids {
             let item_key = task_history_item_key(key, &task_id);
             self.inner().delete(TASK_HISTORY_TABLE, &item_key)?;
+            self.delete_task_gas_costs(&task_id)?;
         }
 
         // Delete the history list
@@ -158,7 +210,7 @@ mod tests {
     use std::cmp::Reverse;
 
     use itertools::Itertools;
-    use types_tasks::{HistoricalTask, TaskQueueKey};
+    use types_tasks::{HistoricalTask, TaskHistoryFilter, TaskQueueKey};
 
     use crate::test_helpers::mock_db;
 
@@ -218,6 +270,47 @@ mod tests {
         tx.commit().unwrap();
     }
 
+    /// Tests paging through the task history with a cursor and filter
+    #[test]
+    fn test_history_pages() {
+        const N: usize = 10;
+        const PAGE_SIZE: usize = 3;
+        let db = mock_db();
+        let wallet_id = TaskQueueKey::new_v4();
+
+        let tasks = (0..N).map(|_| HistoricalTask::mock()).collect_vec();
+        let tx = db.new_write_tx().unwrap();
+        for task in tasks.iter() {
+            tx.append_task_to_history(&wallet_id, task).unwrap();
+        }
+        tx.commit().unwrap();
+
+        // Only match the most recent half of the tasks
+        let filter = TaskHistoryFilter {
+            created_after: Some(tasks[N / 2].created_at),
+            ..Default::default()
+        };
+        let expected = tasks[N / 2..].iter().rev().map(|t| t.id).collect_vec();
+
+        // Page through the history
+        let tx = db.new_read_tx().unwrap();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, has_more) =
+                tx.get_task_history_page(&wallet_id, &filter, cursor, PAGE_SIZE).unwrap();
+            assert!(page.len() <= PAGE_SIZE);
+            seen.extend(page.iter().map(|t| t.id));
+            cursor = page.last().map(|t| t.id);
+            if !has_more {
+                break;
+            }
+        }
+        tx.commit().unwrap();
+
+        assert_eq!(seen, expected);
+    }
+
     /// Tests purging task history
     #[test]
     fn test_purge_history() {
@@ -215,9 +215,12 @@ pub(crate) struct PendingEntry {
     pub seq: u64,
 }
 
+/// The prefix of the task queue storage keys
+pub(crate) const TASK_QUEUE_KEY_PREFIX: &str = "task-queue-";
+
 /// Get the storage key for a task queue
 pub fn task_queue_key(key: &TaskQueueKey) -> String {
-    format!("task-queue-{}", key)
+    format!("{TASK_QUEUE_KEY_PREFIX}{key}")
 }
 
 /// Get the storage key for a task
@@ -246,6 +249,11 @@ fn yield_count_key(key: &TaskQueueKey) -> String {
     format!("yield-count-{key}")
 }
 
+/// Get the storage key for a queue's operator pause marker
+fn operator_pause_key(key: &TaskQueueKey) -> String {
+    format!("operator-paused-{key}")
+}
+
 // -----------------
 // | Query Methods |
 // -----------------
@@ -335,10 +343,15 @@ impl<T: TransactionKind> StateTxn<'_, T> {
 
     /// Check whether the given task can run on its queues
     ///
-    /// A task may run iff it can run on all queues it is indexed into
+    /// A task may run iff it can run on all queues it is indexed into, and
+    /// none of those queues is paused by an operator
     pub fn can_task_run(&self, id: &TaskIdentifier) -> Result<bool, StorageError> {
         let queues = self.get_queue_keys_for_task(id)?;
         for queue_key in queues.iter() {
+            if self.is_queue_paused_by_operator(queue_key)? {
+                return Ok(false);
+            }
+
             let queue = self.get_task_queue(queue_key)?;
             let Some(queue) = queue else {
                 // Task not in queue, can't run
@@ -353,6 +366,32 @@ impl<T: TransactionKind> StateTxn<'_, T> {
         Ok(true)
     }
 
+    /// Whether an operator has paused the given queue
+    ///
+    /// Tasks may still be enqueued onto a paused queue, but none are started
+    /// until the queue is resumed
+    pub fn is_queue_paused_by_operator(&self, key: &TaskQueueKey) -> Result<bool, StorageError> {
+        let pause_key = operator_pause_key(key);
+        let value = self.inner().read::<_, bool>(TASK_QUEUE_TABLE, &pause_key)?;
+        Ok(value.map(|v| *v).unwrap_or(false))
+    }
+
+    /// Get the total number of tasks queued across all task queues
+    pub fn get_total_queued_tasks(&self) -> Result<usize, StorageError> {
+        let cursor = self
+            .inner()
+            .cursor::<String, TaskQueue>(TASK_QUEUE_TABLE)?
+            .with_key_prefix(TASK_QUEUE_KEY_PREFIX);
+
+        let mut total = 0;
+        for entry in cursor.into_iter() {
+            let (_, queue) = entry?;
+            total += queue.all_tasks().len();
+        }
+
+        Ok(total)
+    }
+
     /// Get the queued tasks for a given key
     pub fn get_queued_tasks(&self, key: &TaskQueueKey) -> Result<Vec<TaskValue<'_>>, StorageError> {
         let queue = self.get_task_queue(key)?;
@@ -791,10 +830,29 @@ impl StateTxn<'_, RW> {
         self.update_task(id, &task)
     }
 
+    /// Pause or resume a queue on behalf of an operator
+    pub fn set_queue_paused_by_operator(
+        &self,
+        key: &TaskQueueKey,
+        paused: bool,
+    ) -> Result<(), StorageError> {
+        let pause_key = operator_pause_key(key);
+        if paused {
+            self.inner().write(TASK_QUEUE_TABLE, &pause_key, &paused)
+        } else {
+            self.inner().delete(TASK_QUEUE_TABLE, &pause_key)?;
+            Ok(())
+        }
+    }
+
     // --- Helpers --- //
 
     /// Write the task queue to storage
-    fn write_task_queue(&self, key: &TaskQueueKey, queue: &TaskQueue) -> Result<(), StorageError> {
+    pub(crate) fn write_task_queue(
+        &self,
+        key: &TaskQueueKey,
+        queue: &TaskQueue,
+    ) -> Result<(), StorageError> {
         queue.check_invariants()?;
         let key = task_queue_key(key);
         self.inner().write(TASK_QUEUE_TABLE, &key, queue)
@@ -908,7 +966,7 @@ impl StateTxn<'_, RW> {
 
     /// Update the task -> queues mapping
     #[allow(clippy::needless_pass_by_value)]
-    fn update_task_to_queues(
+    pub(crate) fn update_task_to_queues(
         &self,
         id: &TaskIdentifier,
         queues: Vec<TaskQueueKey>,
@@ -0,0 +1,81 @@
+//! Storage helpers for tokens listed at runtime
+//!
+//! A node only ever holds a handful of listings, so they are stored as a single
+//! list
+
+use libmdbx::{RW, TransactionKind};
+use types_core::TokenListing;
+
+use crate::{TOKEN_LISTINGS_TABLE, storage::error::StorageError};
+
+use super::StateTxn;
+
+/// The key under which the list of token listings is stored
+const TOKEN_LISTINGS_KEY: &str = "listings";
+
+// -----------
+// | Getters |
+// -----------
+
+impl<T: TransactionKind> StateTxn<'_, T> {
+    /// Get all token listings, in the order they were added
+    pub fn get_token_listings(&self) -> Result<Vec<TokenListing>, StorageError> {
+        let key = TOKEN_LISTINGS_KEY.to_string();
+        let listings = self
+            .inner()
+            .read::<_, Vec<TokenListing>>(TOKEN_LISTINGS_TABLE, &key)
+            .map(|opt| opt.map(|archived| archived.deserialize()).transpose())??;
+        Ok(listings.unwrap_or_default())
+    }
+}
+
+// -----------
+// | Setters |
+// -----------
+
+impl StateTxn<'_, RW> {
+    /// Add a token listing, replacing any listing with the same ticker
+    pub fn add_token_listing(&self, listing: TokenListing) -> Result<(), StorageError> {
+        let mut listings = self.get_token_listings()?;
+        listings.retain(|l| l.ticker != listing.ticker);
+        listings.push(listing);
+
+        let key = TOKEN_LISTINGS_KEY.to_string();
+        self.inner().write(TOKEN_LISTINGS_TABLE, &key, &listings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types_core::{Chain, Exchange, TokenListing};
+
+    use crate::test_helpers::mock_db;
+
+    /// Build a listing with the given ticker and decimals
+    fn listing(ticker: &str, decimals: u8) -> TokenListing {
+        TokenListing {
+            name: ticker.to_string(),
+            ticker: ticker.to_string(),
+            decimals,
+            addresses: vec![(Chain::Devnet, "0xabcd".to_string())],
+            supported_exchanges: vec![(Exchange::Binance, ticker.to_string())],
+            canonical_exchange: Exchange::Binance,
+        }
+    }
+
+    /// Tests adding and re-adding token listings
+    #[test]
+    fn test_token_listings() {
+        let db = mock_db();
+        let tx = db.new_write_tx().unwrap();
+        assert!(tx.get_token_listings().unwrap().is_empty());
+        tx.add_token_listing(listing("AAA", 18)).unwrap();
+        tx.add_token_listing(listing("BBB", 6)).unwrap();
+        tx.add_token_listing(listing("AAA", 18)).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.new_read_tx().unwrap();
+        assert_eq!(tx.get_token_listings().unwrap(), vec![listing("BBB", 6), listing("AAA", 18)]);
+        tx.commit().unwrap();
+    }
+}
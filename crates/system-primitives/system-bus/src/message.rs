@@ -9,9 +9,9 @@ use types_account::{
     balance::Balance,
     order::Order,
 };
-use types_core::AccountId;
+use types_core::{AccountId, Exchange, FeatureFlag, Token};
 use types_gossip::{PeerInfo, WrappedPeerId};
-use types_tasks::TaskIdentifier;
+use types_tasks::{QueuedTaskState, TaskDescriptor, TaskIdentifier, TaskQueueKey};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -34,6 +34,22 @@ pub const ADMIN_BALANCE_UPDATES_TOPIC: &str = "admin-balance-updates";
 /// This notifies the chain-events worker to refresh its Transfer event
 /// subscriptions to include the new owner address
 pub const OWNER_INDEX_CHANGED_TOPIC: &str = "owner-index-changed";
+/// The system bus topic published to when the chain events checkpoint is reset
+pub const CHAIN_EVENTS_CHECKPOINT_TOPIC: &str = "chain-events-checkpoint";
+/// The system bus topic published to when the state of a price reporter
+/// connection changes
+pub const PRICE_REPORTER_CONNECTION_TOPIC: &str = "price-reporter-connection";
+/// The system bus topic published to when a price stream becomes stale or
+/// recovers
+pub const PRICE_STALENESS_TOPIC: &str = "price-staleness";
+/// The system bus topic published to when an exchange price is excluded for
+/// deviating from the other exchanges, or is included again
+pub const PRICE_DEVIATION_TOPIC: &str = "price-deviation";
+/// The system bus topic published to when a feature flag is set
+pub const FEATURE_FLAGS_TOPIC: &str = "feature-flags";
+/// The system bus topic published to for all task status updates, not those
+/// given by ID
+pub const ALL_TASK_UPDATES_TOPIC: &str = "all-task-updates";
 
 /// Get the topic name for a given wallet
 pub fn account_topic(account_id: &AccountId) -> String {
@@ -53,6 +69,20 @@ pub fn account_fills_topic(account_id: &AccountId) -> String {
     format!("/v2/account/{account_id}/fills")
 }
 
+/// Get the topic name for updates to an account's balances
+///
+/// Equals the URL path of the websocket route serving the updates
+pub fn account_balances_topic(account_id: &AccountId) -> String {
+    format!("/v2/account/{account_id}/balances")
+}
+
+/// Get the topic name for an account's journaled event stream
+///
+/// Equals the URL path of the websocket route serving the stream
+pub fn account_events_topic(account_id: &AccountId) -> String {
+    format!("/v2/account/{account_id}/events")
+}
+
 /// Get a topic name for an atomic match response
 pub fn gen_atomic_match_response_topic() -> String {
     format!("atomic-match-{}", Uuid::new_v4())
@@ -71,6 +101,12 @@ pub struct TaskStatus {
     pub status: String,
     /// The task description
     pub description: Option<String>,
+    /// The state the task transitioned to
+    pub state: QueuedTaskState,
+    /// The time the task was created
+    pub created_at: u64,
+    /// The descriptor of the task
+    pub descriptor: Box<TaskDescriptor>,
 }
 
 /// A message type for generic system bus messages, broadcast to all modules
@@ -116,6 +152,13 @@ pub enum SystemBusMessage {
         /// The updated status of the task
         status: TaskStatus,
     },
+    /// A message indicating that a task has been updated on one of its queues
+    QueuedTaskStatusUpdate {
+        /// The queue the task is on
+        queue_key: TaskQueueKey,
+        /// The updated status of the task
+        status: TaskStatus,
+    },
 
     // -- Account Updates -- //
     /// A message indicating that an account has been updated
@@ -178,6 +221,39 @@ pub enum SystemBusMessage {
         filled: bool,
     },
 
+    // --- Account Balances --- //
+    /// An update to one of the account's balances
+    BalanceUpdate {
+        /// The account that owns the balance
+        account_id: AccountId,
+        /// The updated balance
+        balance: Box<Balance>,
+    },
+    /// A sweep of one of the account's balances requested under the account's
+    /// sweep policy
+    ///
+    /// The withdrawal must be signed by the balance owner, so the request is
+    /// left to the owner's tooling to fulfill
+    BalanceSweepRequested {
+        /// The account that owns the balance
+        account_id: AccountId,
+        /// The mint of the balance
+        mint: Address,
+        /// The amount to sweep
+        amount: Amount,
+        /// The address to which the balance is swept
+        cold_address: Address,
+    },
+
+    // --- Account Events --- //
+    /// An account event recorded in the API server's event journal
+    AccountEvent {
+        /// The journal cursor of the event, increasing across all accounts
+        cursor: u64,
+        /// The journaled event
+        event: Box<SystemBusMessage>,
+    },
+
     // --- Chain Events -- //
     /// A message indicating that the owner index changed
     ///
@@ -188,6 +264,57 @@ pub enum SystemBusMessage {
         /// Whether the owner was added (true) or removed (false)
         added: bool,
     },
+    /// A message indicating that the chain events checkpoint was reset
+    ///
+    /// Signals the chain-events worker to resume from the new checkpoint
+    ChainEventsCheckpointReset,
+
+    // --- Price Reporter --- //
+    /// A message indicating that the state of a price reporter connection
+    /// changed
+    PriceReporterConnectionUpdate {
+        /// The name of the connection
+        connection: String,
+        /// The new state of the connection
+        state: PriceReporterConnectionState,
+    },
+    /// A message indicating that a price stream became stale or recovered
+    ///
+    /// The matching engine re-runs the orders it skipped on a stale pair once
+    /// the pair recovers
+    PriceStreamStalenessUpdate {
+        /// The exchange of the stream
+        exchange: Exchange,
+        /// The base token of the stream
+        base: Token,
+        /// The quote token of the stream
+        quote: Token,
+        /// Whether the stream is stale
+        stale: bool,
+    },
+    /// A message indicating that an exchange's price for a pair was excluded
+    /// for deviating from the median of the other exchanges, or was included
+    /// again
+    PriceSourceDeviationUpdate {
+        /// The exchange of the stream
+        exchange: Exchange,
+        /// The base token of the stream
+        base: Token,
+        /// The quote token of the stream
+        quote: Token,
+        /// The stream's deviation from the median as a fraction, `None` once
+        /// the stream is included again
+        deviation: Option<f64>,
+    },
+
+    // --- Feature Flags --- //
+    /// A message indicating that a feature flag was set
+    FeatureFlagUpdated {
+        /// The flag that was set
+        flag: FeatureFlag,
+        /// The new value of the flag
+        enabled: bool,
+    },
 }
 
 /// The type of admin order update
@@ -202,6 +329,27 @@ pub enum AdminOrderUpdateType {
     Cancelled,
 }
 
+/// The state of a price reporter connection
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceReporterConnectionState {
+    /// The connection is established and delivering messages
+    Connected,
+    /// The connection is down, and will be retried after a backoff
+    Reconnecting {
+        /// The number of consecutive reconnect attempts, including this one
+        attempt: u32,
+        /// The backoff before the attempt, in milliseconds
+        backoff_ms: u64,
+    },
+    /// The reconnect attempt is held back, as too many reconnects were
+    /// attempted recently across all connections
+    Throttled {
+        /// The time until the attempt may proceed, in milliseconds
+        wait_ms: u64,
+    },
+}
+
 /// A wrapper around a SystemBusMessage containing the topic, used for
 /// serializing websocket messages to clients
 #[derive(Clone, Debug, Serialize, Deserialize)]
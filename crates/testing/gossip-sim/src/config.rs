@@ -0,0 +1,57 @@
+//! Configuration of a simulated gossip network
+
+/// The interval between heartbeat rounds, matching the gossip server's default
+/// interval for non-cluster peers
+pub const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = types_gossip::DEFAULT_HEARTBEAT_INTERVAL_MS;
+
+/// The configuration of a simulated gossip network
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    /// The seed from which node keys, latencies, and losses are drawn
+    pub seed: u64,
+    /// The number of nodes in the network
+    pub n_nodes: usize,
+    /// The minimum one-way latency of a message
+    pub min_latency_ms: u64,
+    /// The maximum one-way latency of a message
+    pub max_latency_ms: u64,
+    /// The probability that any one message is lost
+    pub loss_rate: f64,
+    /// The virtual time between heartbeat rounds
+    pub heartbeat_interval_ms: u64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            n_nodes: 4,
+            min_latency_ms: 5,
+            max_latency_ms: 50,
+            loss_rate: 0.,
+            heartbeat_interval_ms: DEFAULT_HEARTBEAT_INTERVAL_MS,
+        }
+    }
+}
+
+impl SimConfig {
+    /// Construct a config for a network of the given size
+    pub fn new(seed: u64, n_nodes: usize) -> Self {
+        Self { seed, n_nodes, ..Default::default() }
+    }
+
+    /// Set the range of one-way message latencies
+    pub fn with_latency(mut self, min_latency_ms: u64, max_latency_ms: u64) -> Self {
+        assert!(min_latency_ms <= max_latency_ms, "invalid latency range");
+        self.min_latency_ms = min_latency_ms;
+        self.max_latency_ms = max_latency_ms;
+        self
+    }
+
+    /// Set the probability that any one message is lost
+    pub fn with_loss_rate(mut self, loss_rate: f64) -> Self {
+        assert!((0. ..=1.).contains(&loss_rate), "loss rate must be a probability");
+        self.loss_rate = loss_rate;
+        self
+    }
+}
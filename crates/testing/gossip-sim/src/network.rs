@@ -0,0 +1,518 @@
+//! The in-memory network connecting simulated nodes
+//!
+//! The network stands in for every node's network manager. It drains the jobs
+//! each executor sends its network manager and turns them into scheduled
+//! deliveries, then delivers them in order of their virtual arrival time by
+//! invoking the recipient executor's handlers directly
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    time::Duration,
+};
+
+use eyre::Result;
+use gossip_api::{
+    GossipDestination,
+    pubsub::PubsubMessage,
+    request_response::{GossipRequest, GossipResponse, GossipResponseType},
+};
+use job_types::network_manager::{
+    NetworkManagerControlSignal, NetworkManagerJob, NetworkResponseChannel,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use types_gossip::{CLUSTER_MANAGEMENT_TOPIC_PREFIX, WrappedPeerId};
+
+use crate::{config::SimConfig, node::SimNode};
+
+/// The time given to the nodes' background tasks (dial-backs and the database
+/// writes they trigger) to make progress between deliveries
+///
+/// On the paused clock a sleep only completes once every other task is idle
+const SETTLE_POLL_MS: u64 = 1;
+/// The number of consecutive polls with no new jobs after which the nodes are
+/// considered idle
+const QUIESCENT_POLLS: usize = 3;
+
+// ----------
+// | Events |
+// ----------
+
+/// A message in flight between two nodes
+enum SimMessage {
+    /// A request, optionally awaited by the sender on a response channel
+    Request {
+        /// The request
+        req: GossipRequest,
+        /// The channel on which the sender awaits the response
+        response_channel: Option<NetworkResponseChannel>,
+    },
+    /// A response to a request
+    Response {
+        /// The response
+        resp: GossipResponse,
+        /// The channel on which the original sender awaits the response
+        response_channel: Option<NetworkResponseChannel>,
+    },
+    /// A pubsub message
+    Pubsub(PubsubMessage),
+}
+
+/// A message scheduled for delivery at a point in virtual time
+struct ScheduledMessage {
+    /// The virtual time at which the message arrives
+    deliver_at: u64,
+    /// The order in which the message was scheduled, breaks ties between
+    /// messages arriving at the same time
+    seq: u64,
+    /// The index of the sending node
+    from: usize,
+    /// The index of the receiving node
+    to: usize,
+    /// The message
+    message: SimMessage,
+}
+
+impl PartialEq for ScheduledMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledMessage {}
+
+impl PartialOrd for ScheduledMessage {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledMessage {
+    // Reversed so that the max-heap yields the earliest message first
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deliver_at, other.seq).cmp(&(self.deliver_at, self.seq))
+    }
+}
+
+// ---------
+// | Stats |
+// ---------
+
+/// Counts of the network's message outcomes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimStats {
+    /// The number of messages delivered
+    pub delivered: usize,
+    /// The number of messages dropped by random loss
+    pub lost: usize,
+    /// The number of messages dropped because their link was partitioned
+    pub partitioned: usize,
+    /// The number of requests dropped because the sender had no address for
+    /// the recipient
+    pub undialable: usize,
+    /// The errors returned by the nodes' handlers, in order
+    pub handler_errors: Vec<String>,
+}
+
+// -----------
+// | Network |
+// -----------
+
+/// A simulated gossip network
+pub struct SimNetwork {
+    /// The simulation's config
+    config: SimConfig,
+    /// The number of messages sent on each link, seeds the fault draws of the
+    /// link's next message
+    link_counters: HashMap<(usize, usize), u64>,
+    /// The nodes in the network
+    nodes: Vec<SimNode>,
+    /// The index of each node by its peer ID
+    node_indices: HashMap<WrappedPeerId, usize>,
+    /// The messages in flight
+    in_flight: BinaryHeap<ScheduledMessage>,
+    /// The links that are partitioned, each stored in both directions
+    partitioned_links: HashSet<(usize, usize)>,
+    /// The current virtual time
+    now_ms: u64,
+    /// The sequence number of the next scheduled message
+    next_seq: u64,
+    /// The outcomes of the messages sent so far
+    stats: SimStats,
+}
+
+impl SimNetwork {
+    /// Create a network of unconnected nodes
+    pub async fn new(config: SimConfig) -> Result<Self> {
+        let mut key_rng = StdRng::seed_from_u64(config.seed);
+        let mut nodes = Vec::with_capacity(config.n_nodes);
+        for index in 0..config.n_nodes {
+            nodes.push(SimNode::new(index, &mut key_rng).await?);
+        }
+
+        let node_indices = nodes.iter().map(|node| (node.peer_id, node.index)).collect();
+        Ok(Self {
+            config,
+            link_counters: HashMap::new(),
+            nodes,
+            node_indices,
+            in_flight: BinaryHeap::new(),
+            partitioned_links: HashSet::new(),
+            now_ms: 0,
+            next_seq: 0,
+            stats: SimStats::default(),
+        })
+    }
+
+    /// Create a network in which every node is bootstrapped from the first
+    pub async fn bootstrapped(config: SimConfig) -> Result<Self> {
+        let mut network = Self::new(config).await?;
+        network.bootstrap_from(0).await?;
+        Ok(network)
+    }
+
+    // -------------
+    // | Accessors |
+    // -------------
+
+    /// The nodes in the network
+    pub fn nodes(&self) -> &[SimNode] {
+        &self.nodes
+    }
+
+    /// The node at the given index
+    pub fn node(&self, index: usize) -> &SimNode {
+        &self.nodes[index]
+    }
+
+    /// The current virtual time
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    /// The outcomes of the messages sent so far
+    pub fn stats(&self) -> &SimStats {
+        &self.stats
+    }
+
+    /// Whether every node in the given set knows every other node in it
+    pub async fn is_fully_connected(&self, indices: &[usize]) -> Result<bool> {
+        for &i in indices {
+            for &j in indices {
+                if i != j && !self.nodes[i].knows(&self.nodes[j].peer_id).await? {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    // ----------
+    // | Faults |
+    // ----------
+
+    /// Partition the network between two groups of nodes
+    ///
+    /// Links within each group are unaffected, and earlier partitions remain
+    pub fn partition(&mut self, group_a: &[usize], group_b: &[usize]) {
+        for &a in group_a {
+            for &b in group_b {
+                self.partitioned_links.insert((a, b));
+                self.partitioned_links.insert((b, a));
+            }
+        }
+    }
+
+    /// Isolate a node from every other node
+    pub fn isolate(&mut self, index: usize) {
+        let others: Vec<usize> = (0..self.nodes.len()).filter(|&i| i != index).collect();
+        self.partition(&[index], &others);
+    }
+
+    /// Heal all partitions
+    pub fn heal(&mut self) {
+        self.partitioned_links.clear();
+    }
+
+    /// Set the probability that any one message is lost
+    pub fn set_loss_rate(&mut self, loss_rate: f64) {
+        assert!((0. ..=1.).contains(&loss_rate), "loss rate must be a probability");
+        self.config.loss_rate = loss_rate;
+    }
+
+    // -----------
+    // | Driving |
+    // -----------
+
+    /// Bootstrap every node from the node at the given index, as though it
+    /// were the only configured bootstrap server
+    pub async fn bootstrap_from(&mut self, seed: usize) -> Result<()> {
+        let seed_info = self.nodes[seed].info.clone();
+        for node in self.nodes.iter_mut().filter(|node| node.index != seed) {
+            node.learn_peer(seed_info.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Run heartbeat rounds, advancing virtual time by the heartbeat interval
+    /// before each and delivering all resulting messages
+    pub async fn run_rounds(&mut self, n_rounds: usize) -> Result<()> {
+        for _ in 0..n_rounds {
+            let interval = self.config.heartbeat_interval_ms;
+            self.advance(interval).await?;
+            self.heartbeat_round().await?;
+            self.run_until_idle().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Have every node heartbeat every peer in its index, in node order
+    ///
+    /// Sending a heartbeat also checks the recipient for expiry, as on the
+    /// gossip server's heartbeat timer
+    pub async fn heartbeat_round(&mut self) -> Result<()> {
+        for node in self.nodes.iter() {
+            for peer_id in node.known_peers().await? {
+                if let Err(e) = node.executor.send_heartbeat(peer_id).await {
+                    self.stats.handler_errors.push(e.to_string());
+                }
+            }
+        }
+
+        self.settle().await
+    }
+
+    /// Have a node reconcile its order book with a peer, delivering all
+    /// resulting messages
+    pub async fn sync_order_book(&mut self, from: usize, to: usize) -> Result<()> {
+        let peer_id = self.nodes[to].peer_id;
+        if let Err(e) = self.nodes[from].executor.send_order_book_sync(peer_id).await {
+            self.stats.handler_errors.push(e.to_string());
+        }
+
+        self.run_until_idle().await
+    }
+
+    /// Advance virtual time, moving the paused Tokio clock and aging each
+    /// node's recorded heartbeats to match
+    ///
+    /// Panics if the runtime's clock is not paused
+    pub async fn advance(&mut self, ms: u64) -> Result<()> {
+        if ms == 0 {
+            return Ok(());
+        }
+
+        self.now_ms += ms;
+        let dur = Duration::from_millis(ms);
+        tokio::time::advance(dur).await;
+        for node in self.nodes.iter() {
+            node.age_heartbeats(dur).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deliver messages until none are in flight and no node has more to send
+    pub async fn run_until_idle(&mut self) -> Result<()> {
+        loop {
+            self.settle().await?;
+            let Some(msg) = self.in_flight.pop() else {
+                return Ok(());
+            };
+
+            let wait = msg.deliver_at.saturating_sub(self.now_ms);
+            self.advance(wait).await?;
+            self.deliver(msg).await;
+        }
+    }
+
+    // ------------
+    // | Delivery |
+    // ------------
+
+    /// Wait for the nodes' background tasks to quiesce, scheduling every job
+    /// they send in the meantime
+    async fn settle(&mut self) -> Result<()> {
+        let mut quiet_polls = 0;
+        while quiet_polls < QUIESCENT_POLLS {
+            tokio::time::sleep(Duration::from_millis(SETTLE_POLL_MS)).await;
+            if self.drain_outboxes() {
+                quiet_polls = 0;
+            } else {
+                quiet_polls += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Schedule the jobs every node has sent its network manager, in node
+    /// order
+    ///
+    /// Returns whether any job was found
+    fn drain_outboxes(&mut self) -> bool {
+        let mut found = false;
+        for from in 0..self.nodes.len() {
+            while let Ok(job) = self.nodes[from].outbox.try_recv() {
+                found = true;
+                self.handle_job(from, job.consume());
+            }
+        }
+
+        found
+    }
+
+    /// Handle a job sent by a node to its network manager
+    fn handle_job(&mut self, from: usize, job: NetworkManagerJob) {
+        match job {
+            NetworkManagerJob::Request(peer_id, req, response_channel) => {
+                let to = match self.node_indices.get(&peer_id) {
+                    Some(&to) if self.nodes[from].can_dial(&peer_id) => to,
+                    _ => {
+                        self.stats.undialable += 1;
+                        return;
+                    },
+                };
+
+                self.schedule(from, to, SimMessage::Request { req, response_channel });
+            },
+            NetworkManagerJob::Pubsub(topic, msg) => {
+                for to in self.subscribers(&topic) {
+                    if to != from {
+                        self.schedule(from, to, SimMessage::Pubsub(msg.clone()));
+                    }
+                }
+            },
+            NetworkManagerJob::Internal(signal) => {
+                let node = &mut self.nodes[from];
+                match signal {
+                    NetworkManagerControlSignal::NewAddr { peer_id, address } => {
+                        node.address_book.insert(peer_id, address);
+                    },
+                    NetworkManagerControlSignal::PeerExpired { peer_id } => {
+                        node.address_book.remove(&peer_id);
+                    },
+                    NetworkManagerControlSignal::GossipWarmupComplete => {},
+                }
+            },
+            // Executors only respond to requests through the network manager's
+            // inbound path, which the simulation replaces
+            NetworkManagerJob::Response(..) => {},
+        }
+    }
+
+    /// The nodes subscribed to a pubsub topic
+    ///
+    /// Cluster management topics reach the cluster's members, all other topics
+    /// reach every node
+    fn subscribers(&self, topic: &str) -> Vec<usize> {
+        if !topic.starts_with(CLUSTER_MANAGEMENT_TOPIC_PREFIX) {
+            return (0..self.nodes.len()).collect();
+        }
+
+        self.nodes
+            .iter()
+            .filter(|node| node.cluster_id.get_management_topic() == topic)
+            .map(|node| node.index)
+            .collect()
+    }
+
+    /// Schedule a message on the link between two nodes, applying the link's
+    /// partition state, random loss, and latency
+    fn schedule(&mut self, from: usize, to: usize, message: SimMessage) {
+        let mut rng = self.link_rng(from, to);
+        let lost = rng.gen_bool(self.config.loss_rate);
+        let latency = rng.gen_range(self.config.min_latency_ms..=self.config.max_latency_ms);
+
+        if self.partitioned_links.contains(&(from, to)) {
+            self.stats.partitioned += 1;
+            return;
+        }
+        if lost {
+            self.stats.lost += 1;
+            return;
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.in_flight.push(ScheduledMessage {
+            deliver_at: self.now_ms + latency,
+            seq,
+            from,
+            to,
+            message,
+        });
+    }
+
+    /// The RNG drawing the faults of the next message on a link
+    ///
+    /// Each message's draws depend only on the seed, the link, and the
+    /// message's position on the link. Nodes run some protocol steps (e.g.
+    /// dial-backs) as background tasks, so the interleaving of messages across
+    /// links is not fixed; seeding per link keeps the fault schedule
+    /// independent of it
+    fn link_rng(&mut self, from: usize, to: usize) -> StdRng {
+        let counter = self.link_counters.entry((from, to)).or_default();
+        let position = *counter;
+        *counter += 1;
+
+        let mut hasher = DefaultHasher::new();
+        (self.config.seed, from, to, position).hash(&mut hasher);
+        StdRng::seed_from_u64(hasher.finish())
+    }
+
+    /// Deliver a message to its recipient's handlers
+    ///
+    /// A lost request or response drops the sender's response channel, so a
+    /// dial-back fails as soon as its message is lost rather than at its
+    /// timeout
+    async fn deliver(&mut self, msg: ScheduledMessage) {
+        self.stats.delivered += 1;
+        let ScheduledMessage { from, to, message, .. } = msg;
+        let sender = self.nodes[from].peer_id;
+        let executor = self.nodes[to].executor.clone();
+
+        // The recipient's network manager learns the sender's address from the
+        // identify protocol once connected, so it may reply on the link
+        let sender_addr = self.nodes[from].info.get_addr();
+        self.nodes[to].address_book.insert(sender, sender_addr);
+
+        match message {
+            SimMessage::Request { req, response_channel } => {
+                if matches!(req.destination(), GossipDestination::NetworkManager) {
+                    let resp = GossipResponseType::Ack.into();
+                    self.schedule(to, from, SimMessage::Response { resp, response_channel });
+                    return;
+                }
+
+                match executor.handle_request(sender, req).await {
+                    Ok(resp) => {
+                        let resp = resp.into();
+                        self.schedule(to, from, SimMessage::Response { resp, response_channel });
+                    },
+                    Err(e) => self.stats.handler_errors.push(e.to_string()),
+                }
+            },
+            SimMessage::Response { resp, response_channel } => {
+                if let Some(chan) = response_channel {
+                    let _ = chan.send(resp.clone());
+                }
+
+                if matches!(resp.destination(), GossipDestination::GossipServer)
+                    && let Err(e) = executor.handle_response(sender, resp).await
+                {
+                    self.stats.handler_errors.push(e.to_string());
+                }
+            },
+            SimMessage::Pubsub(msg) => {
+                if let Err(e) = executor.handle_pubsub(sender, msg).await {
+                    self.stats.handler_errors.push(e.to_string());
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! A simulated relayer node: a gossip protocol executor over a mock state
+
+use std::{collections::HashMap, net::Ipv4Addr, time::Duration};
+
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use config::RelayerConfig;
+use darkpool_client::{
+    DarkpoolClient, client::DarkpoolClientConfig, constants::BLOCK_POLLING_INTERVAL,
+};
+use ed25519_dalek::{Keypair as DalekKeypair, PublicKey, SecretKey};
+use eyre::{Result, eyre};
+use gossip_server::{
+    rate_limit::GossipRateLimits, server::GossipProtocolExecutor, worker::GossipServerConfig,
+};
+use job_types::{
+    gossip_server::new_gossip_server_queue,
+    network_manager::{NetworkManagerReceiver, new_network_manager_queue},
+};
+use libp2p::{Multiaddr, identity::Keypair, multiaddr::Protocol};
+use rand::{RngCore, rngs::StdRng};
+use state::{
+    State,
+    test_helpers::{mock_relayer_config, mock_state_with_config},
+};
+use tokio::sync::watch::Sender as WatchSender;
+use types_core::Chain;
+use types_gossip::{
+    ClusterAsymmetricKeypair, ClusterId, HeartbeatSettings, PeerAccessList, PeerInfo, WrappedPeerId,
+};
+use types_runtime::new_cancel_channel;
+use util::DefaultWrapper;
+
+/// The UDP port of the first node's address, later nodes count up from it
+const BASE_PORT: u16 = 9_000;
+/// The RPC URL given to each node's darkpool client; the gossip layer never
+/// dials it
+const UNUSED_RPC_URL: &str = "http://127.0.0.1:8545";
+
+/// A simulated relayer node
+pub struct SimNode {
+    /// The index of the node in the simulation
+    pub index: usize,
+    /// The node's peer ID
+    pub peer_id: WrappedPeerId,
+    /// The ID of the node's cluster
+    pub cluster_id: ClusterId,
+    /// The node's advertised peer info
+    pub info: PeerInfo,
+    /// The node's gossip protocol executor
+    pub executor: GossipProtocolExecutor,
+    /// The node's state
+    pub state: State,
+    /// The queue on which the executor sends jobs to its network manager,
+    /// drained by the simulated network
+    pub(crate) outbox: NetworkManagerReceiver,
+    /// The addresses the node's network manager knows how to dial
+    pub(crate) address_book: HashMap<WrappedPeerId, Multiaddr>,
+    /// Held so that the executor's cancel channel stays open
+    _cancel_sender: WatchSender<()>,
+}
+
+impl SimNode {
+    /// Create a node, drawing its keys from the given RNG
+    pub async fn new(index: usize, rng: &mut StdRng) -> Result<Self> {
+        let p2p_key = Keypair::ed25519_from_bytes(random_bytes(rng))?;
+        let cluster_keypair = cluster_keypair_from_seed(random_bytes(rng))?;
+        let cluster_id = ClusterId::new(&cluster_keypair.public);
+
+        let relayer_config = RelayerConfig {
+            p2p_key,
+            cluster_keypair: cluster_keypair.clone(),
+            cluster_id: cluster_id.clone(),
+            ..mock_relayer_config()
+        };
+        let peer_id = relayer_config.peer_id();
+        let state = mock_state_with_config(&relayer_config).await;
+
+        // Advertise a local address, as the network manager would once bound
+        let addr = Multiaddr::from(Ipv4Addr::LOCALHOST)
+            .with(Protocol::Udp(BASE_PORT + index as u16))
+            .with(Protocol::QuicV1);
+        let info = PeerInfo::new_with_cluster_secret_key(
+            peer_id,
+            cluster_id.clone(),
+            addr.clone(),
+            &cluster_keypair,
+        );
+        state.set_local_peer_info(info.clone()).await?;
+
+        // Build the executor
+        let (network_sender, outbox) = new_network_manager_queue();
+        let (job_sender, job_receiver) = new_gossip_server_queue();
+        let (cancel_sender, cancel_channel) = new_cancel_channel();
+        let config = GossipServerConfig {
+            local_peer_id: peer_id,
+            local_addr: addr,
+            cluster_id: cluster_id.clone(),
+            bootstrap_servers: Vec::new(),
+            bootstrap_dns: Vec::new(),
+            bootstrap_dns_refresh_ms: relayer_config.bootstrap_dns_refresh_ms,
+            heartbeat_settings: HeartbeatSettings::new(relayer_config.heartbeat_intervals),
+            peer_access: PeerAccessList::default(),
+            max_cluster_clock_skew_ms: relayer_config.max_cluster_clock_skew_ms,
+            reject_clock_skewed_peers: relayer_config.reject_clock_skewed_peers,
+            order_book_sync_interval_ms: relayer_config.order_book_sync_interval_ms,
+            rate_limits: GossipRateLimits {
+                heartbeats_per_minute: relayer_config.gossip_heartbeat_rate_limit,
+                orders_per_minute: relayer_config.gossip_order_rate_limit,
+                proofs_per_minute: relayer_config.gossip_proof_rate_limit,
+                penalty: Duration::from_millis(relayer_config.gossip_rate_limit_penalty_ms),
+            },
+            darkpool_client: unused_darkpool_client()?,
+            global_state: state.clone(),
+            job_sender,
+            job_receiver: DefaultWrapper::new(None),
+            network_sender: network_sender.clone(),
+            cancel_channel: cancel_channel.clone(),
+        };
+        let executor = GossipProtocolExecutor::new(
+            network_sender,
+            job_receiver,
+            state.clone(),
+            config,
+            cancel_channel,
+        )
+        .map_err(|e| eyre!("error building gossip executor: {e}"))?;
+
+        Ok(Self {
+            index,
+            peer_id,
+            cluster_id,
+            info,
+            executor,
+            state,
+            outbox,
+            address_book: HashMap::new(),
+            _cancel_sender: cancel_sender,
+        })
+    }
+
+    /// Index a peer and its address, as when the peer is given as a bootstrap
+    /// server
+    pub async fn learn_peer(&mut self, info: PeerInfo) -> Result<()> {
+        self.address_book.insert(info.peer_id, info.get_addr());
+        self.state.add_peer(info).await?;
+        Ok(())
+    }
+
+    /// The peers in the node's peer index, excluding the node itself, in
+    /// sorted order
+    pub async fn known_peers(&self) -> Result<Vec<WrappedPeerId>> {
+        let mut peers = self.state.get_all_peers_ids(false /* include_self */).await?;
+        peers.sort();
+        Ok(peers)
+    }
+
+    /// Whether the node's peer index contains the given peer
+    pub async fn knows(&self, peer_id: &WrappedPeerId) -> Result<bool> {
+        Ok(self.state.get_peer_info(peer_id).await?.is_some())
+    }
+
+    /// Whether the given peer is in the node's invisibility window
+    pub async fn is_invisible(&self, peer_id: &WrappedPeerId) -> bool {
+        self.executor.expiry_buffer.is_invisible(peer_id).await
+    }
+
+    /// Whether the node's network manager may dial the given peer
+    pub fn can_dial(&self, peer_id: &WrappedPeerId) -> bool {
+        self.address_book.contains_key(peer_id)
+    }
+
+    /// Age the node's recorded heartbeats as though the given duration had
+    /// passed
+    ///
+    /// Heartbeats are timestamped with the system clock, which the paused Tokio
+    /// clock does not move
+    pub(crate) async fn age_heartbeats(&self, dur: Duration) -> Result<()> {
+        let elapsed_ms = dur.as_millis() as u64;
+        for (peer_id, mut info) in self.state.get_peer_info_map().await? {
+            if peer_id == self.peer_id {
+                continue;
+            }
+
+            info.last_heartbeat = info.last_heartbeat.saturating_sub(elapsed_ms);
+            self.state.set_peer_info(info).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Draw 32 bytes of key material from the RNG
+fn random_bytes(rng: &mut StdRng) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Build a cluster keypair from a secret key seed
+fn cluster_keypair_from_seed(seed: [u8; 32]) -> Result<ClusterAsymmetricKeypair> {
+    let secret = SecretKey::from_bytes(&seed)?;
+    let public = PublicKey::from(&secret);
+    Ok(ClusterAsymmetricKeypair::new(DalekKeypair { secret, public }))
+}
+
+/// Build a darkpool client for the gossip server config
+///
+/// The provider connects lazily and the gossip layer does not currently query
+/// the chain, so no RPC node is needed
+fn unused_darkpool_client() -> Result<DarkpoolClient> {
+    let conf = DarkpoolClientConfig {
+        darkpool_addr: Address::ZERO,
+        permit2_addr: Address::ZERO,
+        chain: Chain::Devnet,
+        rpc_url: UNUSED_RPC_URL.to_string(),
+        private_key: PrivateKeySigner::random(),
+        block_polling_interval: BLOCK_POLLING_INTERVAL,
+        confirmation_depth: Chain::Devnet.default_confirmation_depth(),
+    };
+
+    Ok(DarkpoolClient::new(conf)?)
+}
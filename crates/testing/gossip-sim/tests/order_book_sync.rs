@@ -0,0 +1,129 @@
+//! Simulated order book anti-entropy scenarios, covering which versions of an
+//! order a node accepts from its peers
+
+use std::str::FromStr;
+
+use circuit_types::Nullifier;
+use eyre::{Result, eyre};
+use gossip_api::request_response::{
+    GossipRequestType, GossipResponseType,
+    orderbook::{OrderBookDigest, OrderBookSyncRequest, OrderInfoResponse, OrderSyncEntry},
+};
+use gossip_sim::{SimConfig, SimNetwork};
+use types_gossip::{
+    ClusterId,
+    network_order::{NetworkOrder, NetworkOrderState},
+};
+use uuid::Uuid;
+
+/// The seed shared by the scenarios
+const SEED: u64 = 1543;
+/// The number of nodes in each scenario
+const N_NODES: usize = 2;
+/// The number of heartbeat rounds after which the nodes know one another
+const DISCOVERY_ROUNDS: usize = 3;
+
+/// An order managed by a cluster outside the simulation
+fn remote_order(nullifier: u64) -> NetworkOrder {
+    let cluster = ClusterId::from_str("remote").unwrap();
+    NetworkOrder::new(Uuid::new_v4(), Nullifier::from(nullifier), cluster, false)
+}
+
+/// A network whose nodes know one another
+async fn connected_network() -> Result<SimNetwork> {
+    let mut network = SimNetwork::bootstrapped(SimConfig::new(SEED, N_NODES)).await?;
+    network.run_rounds(DISCOVERY_ROUNDS).await?;
+    Ok(network)
+}
+
+/// Deliver a peer's order info response to node zero
+async fn deliver_order_info(network: &SimNetwork, order: NetworkOrder) -> Result<()> {
+    let resp = GossipResponseType::OrderInfo(OrderInfoResponse { order_info: vec![order.into()] });
+    let sender = network.node(1).peer_id;
+    network
+        .node(0)
+        .executor
+        .handle_response(sender, resp.into())
+        .await
+        .map_err(|e| eyre!("error handling order info: {e}"))
+}
+
+/// A node fetches the orders it is missing from a peer
+#[tokio::test(start_paused = true)]
+async fn missing_orders_are_synced() -> Result<()> {
+    let mut network = connected_network().await?;
+    let order = remote_order(1);
+    network.node(1).state.add_order(order.clone()).await?;
+
+    network.sync_order_book(0, 1).await?;
+    let synced = network.node(0).state.get_network_order(&order.id).await?;
+    assert_eq!(synced.map(|o| o.nullifier), Some(order.nullifier));
+    assert!(network.stats().handler_errors.is_empty(), "{:?}", network.stats().handler_errors);
+    Ok(())
+}
+
+/// A node does not take back an order it has removed from a peer that still
+/// holds it
+#[tokio::test(start_paused = true)]
+async fn removed_orders_are_not_resurrected() -> Result<()> {
+    let mut network = connected_network().await?;
+    let order = remote_order(1);
+    network.node(0).state.add_order(order.clone()).await?;
+    network.node(1).state.add_order(order.clone()).await?;
+    network.node(0).state.nullify_orders(order.nullifier).await?;
+
+    network.sync_order_book(0, 1).await?;
+    assert!(network.node(0).state.get_network_order(&order.id).await?.is_none());
+    assert!(network.stats().handler_errors.is_empty(), "{:?}", network.stats().handler_errors);
+    Ok(())
+}
+
+/// A peer's copy of an order version held locally does not reset the local
+/// order's state
+#[tokio::test(start_paused = true)]
+async fn up_to_date_order_keeps_its_state() -> Result<()> {
+    let network = connected_network().await?;
+    let mut order = remote_order(1);
+    order.state = NetworkOrderState::Verified;
+    network.node(0).state.add_order(order.clone()).await?;
+
+    let mut peer_copy = order.clone();
+    peer_copy.transition_received();
+    deliver_order_info(&network, peer_copy).await?;
+    let stored = network.node(0).state.get_network_order(&order.id).await?.unwrap();
+    assert_eq!(stored.state, NetworkOrderState::Verified);
+    Ok(())
+}
+
+/// A peer's removed version of an order does not replace the newer version
+/// held locally
+#[tokio::test(start_paused = true)]
+async fn removed_version_is_not_reapplied() -> Result<()> {
+    let network = connected_network().await?;
+    let old_version = remote_order(1);
+    let mut new_version = old_version.clone();
+    new_version.nullifier = Nullifier::from(2u64);
+
+    let state = &network.node(0).state;
+    state.add_order(old_version.clone()).await?;
+    state.nullify_orders(old_version.nullifier).await?;
+    state.add_order(new_version.clone()).await?;
+
+    deliver_order_info(&network, old_version.clone()).await?;
+    let stored = state.get_network_order(&old_version.id).await?.unwrap();
+    assert_eq!(stored.nullifier, new_version.nullifier);
+    Ok(())
+}
+
+/// A peer outside the node's peer index may not sync with it
+#[tokio::test(start_paused = true)]
+async fn sync_requires_indexed_peer() -> Result<()> {
+    let network = SimNetwork::new(SimConfig::new(SEED, N_NODES)).await?;
+    let digest = OrderBookDigest::compute(&Vec::<OrderSyncEntry>::new());
+    let req = GossipRequestType::OrderBookSync(OrderBookSyncRequest { digest });
+
+    let sender = network.node(1).peer_id;
+    let res = network.node(0).executor.handle_request(sender, req.into()).await;
+    assert!(res.is_err());
+    Ok(())
+}
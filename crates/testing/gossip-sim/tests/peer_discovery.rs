@@ -0,0 +1,125 @@
+//! Simulated peer discovery, expiry, and invisibility scenarios. Every run is
+//! seeded, so a failing seed replays the same latencies and losses.
+
+use eyre::Result;
+use gossip_sim::{SimConfig, SimNetwork};
+
+/// The seed shared by the scenarios
+const SEED: u64 = 1522;
+/// The number of nodes in each scenario
+const N_NODES: usize = 4;
+/// The indices of every node
+const ALL_NODES: [usize; N_NODES] = [0, 1, 2, 3];
+/// The number of heartbeat rounds after which a silent non-cluster peer is
+/// expired; the gossip server expires such peers after 30 seconds
+const EXPIRY_ROUNDS: usize = 3;
+/// The number of heartbeat rounds spanning an expired peer's invisibility
+/// window of one minute
+const INVISIBILITY_ROUNDS: usize = 6;
+/// The number of heartbeat rounds within which discovery converges under loss
+const LOSSY_CONVERGENCE_ROUNDS: usize = 10;
+
+/// Assert that every one of the given nodes knows every other
+async fn assert_converged(network: &SimNetwork, indices: &[usize]) -> Result<()> {
+    assert!(network.is_fully_connected(indices).await?, "nodes {indices:?} did not converge");
+    Ok(())
+}
+
+/// Nodes bootstrapped from a single seed node learn the full topology
+#[tokio::test(start_paused = true)]
+async fn discovery_converges() -> Result<()> {
+    let mut network = SimNetwork::bootstrapped(SimConfig::new(SEED, N_NODES)).await?;
+    assert!(!network.is_fully_connected(&ALL_NODES).await?);
+
+    network.run_rounds(3).await?;
+    assert_converged(&network, &ALL_NODES).await?;
+    assert!(network.stats().handler_errors.is_empty(), "{:?}", network.stats().handler_errors);
+    Ok(())
+}
+
+/// Discovery converges despite message loss, as each heartbeat round retries
+/// whatever was lost
+#[tokio::test(start_paused = true)]
+async fn discovery_converges_under_loss() -> Result<()> {
+    let config = SimConfig::new(SEED, N_NODES).with_loss_rate(0.3);
+    let mut network = SimNetwork::bootstrapped(config).await?;
+
+    network.run_rounds(LOSSY_CONVERGENCE_ROUNDS).await?;
+    assert!(network.stats().lost > 0);
+    assert_converged(&network, &ALL_NODES).await
+}
+
+/// A node cut off from the network is expired by every other node, which
+/// remain connected to one another
+#[tokio::test(start_paused = true)]
+async fn isolated_node_is_expired() -> Result<()> {
+    let mut network = SimNetwork::bootstrapped(SimConfig::new(SEED, N_NODES)).await?;
+    network.run_rounds(3).await?;
+    assert_converged(&network, &ALL_NODES).await?;
+
+    network.isolate(3);
+    network.run_rounds(EXPIRY_ROUNDS - 1).await?;
+    assert!(network.node(0).knows(&network.node(3).peer_id).await?);
+
+    network.run_rounds(1).await?;
+    let isolated = network.node(3).peer_id;
+    for node in &network.nodes()[..3] {
+        assert!(!node.knows(&isolated).await?);
+        assert!(node.is_invisible(&isolated).await);
+        assert!(!node.can_dial(&isolated));
+    }
+    assert!(network.node(3).known_peers().await?.is_empty());
+    assert_converged(&network, &[0, 1, 2]).await
+}
+
+/// A peer expired by one node is not re-indexed while in its invisibility
+/// window, even though other nodes still advertise it, and is re-indexed once
+/// the window has passed
+#[tokio::test(start_paused = true)]
+async fn invisibility_window_blocks_readvertised_peer() -> Result<()> {
+    let mut network = SimNetwork::bootstrapped(SimConfig::new(SEED, N_NODES)).await?;
+    network.run_rounds(3).await?;
+    assert_converged(&network, &ALL_NODES).await?;
+    let peer3 = network.node(3).peer_id;
+
+    // Only the link between nodes 0 and 3 fails
+    network.partition(&[0], &[3]);
+    network.run_rounds(EXPIRY_ROUNDS).await?;
+    assert!(!network.node(0).knows(&peer3).await?);
+    assert!(network.node(1).knows(&peer3).await?);
+
+    // Node 1 keeps advertising node 3 to node 0 in its heartbeats
+    network.heal();
+    network.run_rounds(INVISIBILITY_ROUNDS - 2).await?;
+    assert!(network.node(0).is_invisible(&peer3).await);
+    assert!(!network.node(0).knows(&peer3).await?);
+
+    // Once the window passes, node 0 verifies and re-indexes node 3
+    network.run_rounds(3).await?;
+    assert!(!network.node(0).is_invisible(&peer3).await);
+    assert_converged(&network, &ALL_NODES).await
+}
+
+/// Two runs with the same seed reach the same partial topology and the same
+/// fault counts, and both go on to converge
+#[tokio::test(start_paused = true)]
+async fn same_seed_reproduces_topology() -> Result<()> {
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let config = SimConfig::new(SEED, N_NODES).with_loss_rate(0.2).with_latency(1, 200);
+        let mut network = SimNetwork::bootstrapped(config).await?;
+        network.run_rounds(2).await?;
+
+        let mut topology = Vec::new();
+        for node in network.nodes() {
+            topology.push((node.peer_id, node.known_peers().await?));
+        }
+        runs.push((topology, network.stats().lost));
+
+        network.run_rounds(LOSSY_CONVERGENCE_ROUNDS).await?;
+        assert_converged(&network, &ALL_NODES).await?;
+    }
+
+    assert_eq!(runs[0], runs[1]);
+    Ok(())
+}
@@ -7,7 +7,7 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![allow(incomplete_features)]
 
-use std::mem;
+use std::{collections::HashMap, mem, path::PathBuf, time::Duration};
 
 use api_server::worker::{ApiServer, ApiServerConfig};
 use chain_events::{OnChainEventListener, OnChainEventListenerConfig};
@@ -17,7 +17,9 @@ use darkpool_client::{
 };
 use eyre::Result;
 use futures::Future;
-use gossip_server::{server::GossipServer, worker::GossipServerConfig};
+use gossip_server::{
+    rate_limit::GossipRateLimits, server::GossipServer, worker::GossipServerConfig,
+};
 use job_types::{
     event_manager::{EventManagerQueue, EventManagerReceiver, new_event_manager_queue},
     gossip_server::{
@@ -41,9 +43,12 @@ use matching_engine_worker::worker::{MatchingEngineConfig, MatchingEngineManager
 use network_manager::worker::{NetworkManager, NetworkManagerConfig};
 use price_reporter::{
     mock::MockPriceReporter,
-    worker::{ExchangeConnectionsConfig, PriceReporterConfig},
+    worker::{
+        ExchangeConnectionsConfig, PriceReporterConfig, exchange_credentials_from_keys,
+        synthetic_routes_from_tickers,
+    },
 };
-use price_state::PriceStreamStates;
+use price_state::{PriceStreamStates, aggregation::AggregationStrategies};
 use proof_manager::{
     mock::MockProofManager,
     worker::{ProofManager, ProofManagerConfig},
@@ -56,8 +61,9 @@ use system_clock::SystemClock;
 use task_driver::worker::{TaskDriver, TaskDriverConfig};
 use test_helpers::mocks::mock_cancel;
 use tokio::runtime::Handle;
-use types_core::Price;
-use types_runtime::{Worker, new_worker_failure_channel};
+use types_core::{Exchange, Price};
+use types_gossip::{HeartbeatSettings, PeerAccessList, PeerLatencies};
+use types_runtime::{ShutdownProgress, Worker, WorkerLiveness, new_worker_failure_channel};
 use util::{DefaultOption, default_option};
 
 /// A helper that creates a dummy runtime and blocks a task on it
@@ -95,6 +101,12 @@ pub struct MockNodeController {
     darkpool_client: Option<DarkpoolClient>,
     /// The price streams
     price_streams: PriceStreamStates,
+    /// The table of round trip times to peers
+    peer_latencies: PeerLatencies,
+    /// The heartbeat intervals, shared by the gossip and API servers
+    heartbeat_settings: HeartbeatSettings,
+    /// The peer access lists, shared by the network, gossip, and API servers
+    peer_access: PeerAccessList,
     /// The system bus
     bus: SystemBus,
     /// The system clock
@@ -137,12 +149,18 @@ impl MockNodeController {
         // Setup the price streams
         let cfg = Self::get_price_reporter_config(&config);
         let price_streams = MockPriceReporter::build_price_streams(&cfg);
+        let heartbeat_settings = HeartbeatSettings::new(config.heartbeat_intervals);
+        let peer_access =
+            PeerAccessList::new(config.peer_allowlist.clone(), config.peer_blocklist.clone());
 
         Self {
             config,
             local_addr: Multiaddr::empty(),
             darkpool_client: None,
             price_streams,
+            peer_latencies: PeerLatencies::new(),
+            heartbeat_settings,
+            peer_access,
             bus,
             clock,
             state: None,
@@ -164,11 +182,28 @@ impl MockNodeController {
         PriceReporterConfig {
             system_bus: SystemBus::new(),
             exchange_conn_config: ExchangeConnectionsConfig {
-                coinbase_key_name: relayer_config.coinbase_key_name.clone(),
-                coinbase_key_secret: relayer_config.coinbase_key_secret.clone(),
+                credentials: exchange_credentials_from_keys(vec![
+                    (
+                        Exchange::Coinbase,
+                        relayer_config.coinbase_key_name.clone(),
+                        relayer_config.coinbase_key_secret.clone(),
+                    ),
+                    (Exchange::Binance, relayer_config.binance_api_key.clone(), None),
+                ]),
                 eth_websocket_addr: None, // Disables UniswapV3 exchange
+                uniswap_twap_pools: HashMap::new(),
+                rest_fallback_interval_ms: relayer_config.price_rest_fallback_interval_ms,
             },
             price_reporter_url: relayer_config.price_reporter_url.clone(),
+            staleness_threshold_ms: relayer_config.price_staleness_threshold_ms,
+            aggregation_strategies: AggregationStrategies::from_tickers(
+                relayer_config.price_aggregation_strategy,
+                &relayer_config.price_aggregation_strategies,
+            )
+            .expect("invalid price aggregation strategies"),
+            max_source_deviation: relayer_config.price_max_source_deviation_pct / 100.,
+            synthetic_routes: synthetic_routes_from_tickers(&relayer_config.synthetic_price_routes),
+            darkpool_client: None, // Disables the Chainlink exchange
             disabled: false,
             disabled_exchanges: vec![],
             cancel_channel: mock_cancel(),
@@ -321,6 +356,7 @@ impl MockNodeController {
             rpc_url: self.config.rpc_url.clone().unwrap(),
             private_key: self.config.relayer_wallet_key().clone(),
             block_polling_interval: BLOCK_POLLING_INTERVAL,
+            confirmation_depth: self.config.tx_confirmation_depth,
         };
 
         // Expects to be running in a Tokio runtime
@@ -410,6 +446,9 @@ impl MockNodeController {
             gossip_work_queue: gossip_sender,
             system_bus: self.bus.clone(),
             global_state: self.state.clone().expect("State not initialized"),
+            peer_latencies: self.peer_latencies.clone(),
+            peer_access: self.peer_access.clone(),
+            allow_unsigned_order_book_messages: config.allow_unsigned_order_book_messages,
             cancel_channel,
         };
         let mut manager =
@@ -439,6 +478,19 @@ impl MockNodeController {
             local_addr: self.local_addr.clone(),
             cluster_id: config.cluster_id.clone(),
             bootstrap_servers: config.bootstrap_servers.clone(),
+            bootstrap_dns: config.bootstrap_dns.clone(),
+            bootstrap_dns_refresh_ms: config.bootstrap_dns_refresh_ms,
+            heartbeat_settings: self.heartbeat_settings.clone(),
+            peer_access: self.peer_access.clone(),
+            max_cluster_clock_skew_ms: config.max_cluster_clock_skew_ms,
+            reject_clock_skewed_peers: config.reject_clock_skewed_peers,
+            order_book_sync_interval_ms: config.order_book_sync_interval_ms,
+            rate_limits: GossipRateLimits {
+                heartbeats_per_minute: config.gossip_heartbeat_rate_limit,
+                orders_per_minute: config.gossip_order_rate_limit,
+                proofs_per_minute: config.gossip_proof_rate_limit,
+                penalty: Duration::from_millis(config.gossip_rate_limit_penalty_ms),
+            },
             darkpool_client,
             global_state: state,
             job_sender,
@@ -463,8 +515,14 @@ impl MockNodeController {
 
         let conf = MatchingEngineConfig {
             disabled_assets: vec![],
+            allowed_assets: vec![],
             min_fill_size: self.config.min_fill_size,
+            quote_only: self.config.quote_only_matching,
             external_match_validity_window: self.config.external_match_validity_window,
+            min_price_confidence: self.config.min_price_confidence,
+            vwap_pricing_threshold: self.config.vwap_pricing_threshold,
+            vwap_pricing_window: self.config.vwap_pricing_window,
+            blackout_windows: self.config.matching_blackout_windows.clone(),
             state: state.clone(),
             matching_engine: state.matching_engine().clone(),
             price_streams,
@@ -533,17 +591,37 @@ impl MockNodeController {
             websocket_port: config.websocket_port,
             health_port: config.http_port + 1,
             admin_api_key: config.admin_api_key,
+            tenant_keys: config.api_tenant_keys.clone(),
             min_transfer_amount: config.min_transfer_amount,
             min_order_size: config.min_fill_size_decimal_adjusted(),
             chain: config.chain_id,
             compliance_service_url: config.compliance_service_url.clone(),
             wallet_task_rate_limit: config.wallet_task_rate_limit,
+            read_rate_limit: config.api_read_rate_limit,
+            write_rate_limit: config.api_write_rate_limit,
+            trust_forwarded_for: config.api_trust_forwarded_for,
+            websocket_send_queue_size: config.websocket_send_queue_size,
+            close_slow_websocket_clients: config.websocket_close_slow_clients,
+            websocket_max_connections: config.websocket_max_connections,
+            websocket_max_connections_per_ip: config.websocket_max_connections_per_ip,
+            compression_min_size: config.api_compression_min_size,
+            compression_content_types: config.api_compression_content_types.clone(),
+            cors_allowed_origins: config.api_cors_allowed_origins.clone(),
+            cors_allow_credentials: config.api_cors_allow_credentials,
+            price_history_retention_hours: config.price_history_retention_hours,
+            state_export_path: PathBuf::from(&config.state_export_path),
             disabled_assets: config.disabled_assets.clone(),
+            allowed_assets: config.allowed_assets.clone(),
             darkpool_client,
             network_sender,
             state,
             system_bus,
             price_streams,
+            peer_latencies: self.peer_latencies.clone(),
+            heartbeat_settings: self.heartbeat_settings.clone(),
+            peer_access: self.peer_access.clone(),
+            worker_liveness: WorkerLiveness::new(),
+            shutdown: ShutdownProgress::new(),
             proof_generation_work_queue,
             matching_engine_worker_queue,
             task_queue: self.task_queue.0.clone(),
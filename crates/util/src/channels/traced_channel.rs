@@ -113,6 +113,11 @@ impl<T> TracedTokioReceiver<T> {
     pub async fn recv(&mut self) -> Option<TracedMessage<T>> {
         self.inner.recv().await
     }
+
+    /// Try to receive a message from the channel (non-blocking)
+    pub fn try_recv(&mut self) -> Result<TracedMessage<T>, tokio::sync::mpsc::error::TryRecvError> {
+        self.inner.try_recv()
+    }
 }
 
 // ----------------------
@@ -149,14 +149,14 @@ where
 }
 
 /// An adapter to allow a `std::fmt::Write` to be used as an `io::Write`
-struct WriteAdapter<'a> {
+pub(crate) struct WriteAdapter<'a> {
     /// The `std::fmt::Write` to write to
     fmt_write: &'a mut dyn std::fmt::Write,
 }
 
 impl<'a> WriteAdapter<'a> {
     /// Create a new `WriteAdapter` that writes to the given `std::fmt::Write`
-    fn new(fmt_write: &'a mut dyn std::fmt::Write) -> Self {
+    pub(crate) fn new(fmt_write: &'a mut dyn std::fmt::Write) -> Self {
         Self { fmt_write }
     }
 }
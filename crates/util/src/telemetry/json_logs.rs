@@ -0,0 +1,269 @@
+//! A JSON log format carrying correlation fields
+//!
+//! Every record is emitted as a single JSON object holding the event's fields
+//! alongside the fields that correlate it with the rest of the relayer's
+//! telemetry: the worker that emitted it, the node's peer and cluster IDs, the
+//! request and task IDs of the spans it was emitted in, and its span and trace
+//! context. Log aggregation systems can then index relayer logs without
+//! parsing messages
+
+use std::{fmt::Display, str::FromStr, sync::OnceLock};
+
+use chrono::Utc;
+use opentelemetry::trace::{SpanId, TraceContextExt, TraceId};
+use serde::{
+    Deserialize, Serialize,
+    ser::{SerializeMap, Serializer as _},
+};
+use tracing::{Event, Subscriber};
+use tracing_opentelemetry::OtelData;
+use tracing_serde::AsSerde;
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, format::Writer},
+    registry::{LookupSpan, SpanRef},
+};
+
+use super::{datadog::formatter::WriteAdapter, request_id::REQUEST_ID_FIELD};
+
+/// The span field a task ID is recorded under
+pub const TASK_ID_FIELD: &str = "task_id";
+
+/// The node-wide fields attached to every record
+static LOG_CONTEXT: OnceLock<LogContext> = OnceLock::new();
+
+/// The format in which logs are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable, multi-line records
+    #[default]
+    Pretty,
+    /// One JSON object per record, carrying correlation fields
+    Json,
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {s}")),
+        }
+    }
+}
+
+/// The node-wide fields attached to every JSON record
+#[derive(Clone, Debug)]
+pub struct LogContext {
+    /// The local peer's ID
+    pub peer_id: String,
+    /// The ID of the cluster the local peer belongs to
+    pub cluster_id: String,
+}
+
+/// Set the node-wide fields attached to every JSON record
+///
+/// The context may only be set once; later calls are ignored
+pub fn set_log_context<P: Display, C: Display>(peer_id: P, cluster_id: C) {
+    let _ = LOG_CONTEXT
+        .set(LogContext { peer_id: peer_id.to_string(), cluster_id: cluster_id.to_string() });
+}
+
+/// The event formatter that writes each record as a JSON object carrying its
+/// correlation fields
+///
+/// Span fields are read from their JSON formatting, so the formatter must be
+/// paired with the JSON field formatter, e.g. `fmt::layer().json()`
+pub struct CorrelatedJsonFormatter;
+
+impl<S, N> FormatEvent<S, N> for CorrelatedJsonFormatter
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let meta = event.metadata();
+
+        let mut visit = || {
+            let mut serializer = serde_json::Serializer::new(WriteAdapter::new(&mut writer));
+            let mut serializer = serializer.serialize_map(None)?;
+            serializer.serialize_entry("timestamp", &Utc::now().to_rfc3339())?;
+            serializer.serialize_entry("level", &meta.level().as_serde())?;
+            serializer.serialize_entry("target", meta.target())?;
+
+            if let Some(filename) = meta.file() {
+                serializer.serialize_entry("filename", filename)?;
+            }
+
+            if let Some(line_number) = meta.line() {
+                serializer.serialize_entry("line_number", &line_number)?;
+            }
+
+            // Workers run on threads named after them
+            if let Some(worker) = std::thread::current().name() {
+                serializer.serialize_entry("worker", worker)?;
+            }
+
+            if let Some(log_context) = LOG_CONTEXT.get() {
+                serializer.serialize_entry("peer_id", &log_context.peer_id)?;
+                serializer.serialize_entry("cluster_id", &log_context.cluster_id)?;
+            }
+
+            let mut visitor = tracing_serde::SerdeMapVisitor::new(serializer);
+            event.record(&mut visitor);
+            serializer = visitor.take_serializer()?;
+
+            let Some(scope) = ctx.event_scope() else {
+                return serializer.end();
+            };
+
+            // Collect the fields of every span the event was emitted in, so that
+            // e.g. a request ID recorded on an outer span reaches the record. An
+            // inner span's field shadows an outer one of the same name
+            let spans: Vec<_> = scope.from_root().collect();
+            let mut span_fields = serde_json::Map::new();
+            for span in &spans {
+                if let Some(fmt_fields) = span.extensions().get::<FormattedFields<N>>()
+                    && let Ok(serde_json::Value::Object(fields)) =
+                        serde_json::from_str::<serde_json::Value>(fmt_fields)
+                {
+                    span_fields.extend(fields);
+                }
+            }
+
+            // Emit the correlation IDs under fixed keys, followed by the remaining
+            // span fields
+            for key in [REQUEST_ID_FIELD, TASK_ID_FIELD] {
+                if let Some(value) = span_fields.remove(key) {
+                    serializer.serialize_entry(key, &value)?;
+                }
+            }
+            for (key, value) in span_fields {
+                serializer.serialize_entry(&key, &value)?;
+            }
+
+            let span_names: Vec<_> = spans.iter().map(|span| span.name()).collect();
+            if let Some(current) = span_names.last() {
+                serializer.serialize_entry("span", current)?;
+            }
+            serializer.serialize_entry("spans", &span_names)?;
+
+            if let Some((trace_id, span_id)) = spans.last().and_then(lookup_trace_context) {
+                serializer.serialize_entry("trace_id", &trace_id.to_string())?;
+                serializer.serialize_entry("span_id", &span_id.to_string())?;
+            }
+
+            serializer.end()
+        };
+
+        visit().map_err(|_| std::fmt::Error)?;
+        writeln!(writer)
+    }
+}
+
+/// Look up the OpenTelemetry trace and span IDs of the given span, if it is
+/// traced
+fn lookup_trace_context<S>(span_ref: &SpanRef<S>) -> Option<(TraceId, SpanId)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let extensions = span_ref.extensions();
+    let otel_data = extensions.get::<OtelData>()?;
+    let ids = if otel_data.parent_cx.has_active_span() {
+        let span = otel_data.parent_cx.span();
+        let span_context = span.span_context();
+        (span_context.trace_id(), span_context.span_id())
+    } else {
+        (
+            otel_data.builder.trace_id.unwrap_or(TraceId::INVALID),
+            otel_data.builder.span_id.unwrap_or(SpanId::INVALID),
+        )
+    };
+
+    Some(ids)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{Result as IoResult, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{info, info_span};
+
+    use super::{CorrelatedJsonFormatter, LogFormat, set_log_context};
+
+    /// A writer collecting the formatted records in memory
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests that a record carries the node's peer and cluster IDs, and the
+    /// request and task IDs of the spans it was emitted in
+    #[test]
+    fn test_correlated_json_format() {
+        set_log_context("test-peer", "test-cluster");
+
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .event_format(CorrelatedJsonFormatter)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request_span = info_span!("request", request_id = "test-request");
+            let _request = request_span.enter();
+            let task_span = info_span!("task", task_id = "test-task");
+            let _task = task_span.enter();
+            info!(amount = 1, "test message");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(record["peer_id"], "test-peer");
+        assert_eq!(record["cluster_id"], "test-cluster");
+        assert_eq!(record["request_id"], "test-request");
+        assert_eq!(record["task_id"], "test-task");
+        assert_eq!(record["message"], "test message");
+        assert_eq!(record["amount"], 1);
+        assert_eq!(record["span"], "task");
+        assert_eq!(record["spans"], serde_json::json!(["request", "task"]));
+    }
+
+    /// Tests that log formats round trip through their string representation
+    #[test]
+    fn test_log_format_round_trip() {
+        for format in [LogFormat::Pretty, LogFormat::Json] {
+            assert_eq!(format.to_string().parse::<LogFormat>().unwrap(), format);
+        }
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
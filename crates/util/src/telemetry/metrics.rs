@@ -1,14 +1,16 @@
-//! Configures a metrics recorder to send metrics to a statsd server
+//! Configures a metrics recorder to send metrics to a statsd server and record
+//! them in the Prometheus registry
 
 use metrics_exporter_statsd::StatsdBuilder;
 use metrics_tracing_context::TracingContextLayer;
-use metrics_util::layers::Layer;
+use metrics_util::layers::{FanoutBuilder, Layer};
 
 use crate::err_str;
 
 use super::{
     TelemetrySetupError,
     datadog::{SERVICE_TAG, UnifiedServiceTags, get_unified_service_tags},
+    prometheus::build_prometheus_recorder,
 };
 
 /// Default metrics prefix used for the relayer
@@ -47,6 +49,10 @@ impl Default for MetricsConfig {
 }
 
 /// Configures a statsd metrics recorder with custom configuration
+///
+/// Metrics are also recorded in the Prometheus registry. Only statsd metrics
+/// are tagged with the tracing context, as the span fields would otherwise
+/// become unbounded Prometheus labels
 pub fn configure_metrics_statsd_recorder_with_config(
     datadog_enabled: bool,
     statsd_host: &str,
@@ -65,11 +71,15 @@ pub fn configure_metrics_statsd_recorder_with_config(
             .with_default_tag("version", version);
     };
 
-    let recorder = TracingContextLayer::all().layer(
+    let statsd_recorder = TracingContextLayer::all().layer(
         builder
             .build(Some(&config.metrics_prefix))
             .map_err(err_str!(TelemetrySetupError::Metrics))?,
     );
+    let recorder = FanoutBuilder::default()
+        .add_recorder(statsd_recorder)
+        .add_recorder(build_prometheus_recorder())
+        .build();
 
     metrics::set_global_recorder(recorder).unwrap();
 
@@ -1,7 +1,7 @@
 //! Defines helpers for logging
 
 use std::{error::Error, fmt::Display, sync::OnceLock};
-use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
 use tracing_subscriber::{
     EnvFilter, Layer, Registry, fmt, layer::SubscriberExt, util::SubscriberInitExt,
 };
@@ -14,13 +14,17 @@ use tracing_subscriber::{
 /// the writer alive without threading the guard through every
 /// `configure_telemetry` caller.
 static LOG_WORKER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+pub use json_logs::{LogFormat, set_log_context};
 pub use tracing_subscriber::{filter::LevelFilter, fmt::format::Format};
 
 pub mod datadog;
 pub mod helpers;
+pub mod json_logs;
 pub mod metrics;
 pub mod otlp_tracer;
+pub mod prometheus;
 pub mod propagation;
+pub mod request_id;
 
 /// Possible errors that occur when setting up telemetry
 /// for the relayer
@@ -46,9 +50,35 @@ impl Display for TelemetrySetupError {
     }
 }
 
-/// Initialize a logger at the given log level
-pub fn setup_system_logger(level: LevelFilter) {
-    tracing_subscriber::fmt().event_format(Format::default().pretty()).with_max_level(level).init();
+/// Initialize a logger at the given log level, writing records in the given
+/// format
+pub fn setup_system_logger(level: LevelFilter, format: LogFormat) {
+    match format {
+        LogFormat::Pretty => tracing_subscriber::fmt()
+            .event_format(Format::default().pretty())
+            .with_max_level(level)
+            .init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .event_format(json_logs::CorrelatedJsonFormatter)
+            .with_max_level(level)
+            .init(),
+    }
+}
+
+/// Build a non-blocking, background-flushed writer to stdout
+///
+/// The default fmt writer holds the global stdout lock and writes
+/// synchronously, so under high log volume a slow consumer (e.g. the docker
+/// json-file driver) blocks every runtime thread that logs, wedging the node
+/// until the write drains -- observed as /v2/ping health-check timeouts and ECS
+/// killing the task. `non_blocking` drains a bounded queue on a dedicated
+/// thread and drops on overflow (lossy by default), so logging can never block
+/// the runtime.
+fn non_blocking_stdout() -> NonBlocking {
+    let (writer, guard) = tracing_appender::non_blocking(std::io::stdout());
+    let _ = LOG_WORKER_GUARD.set(guard);
+    writer
 }
 
 /// A builder for configuring telemetry for the relayer
@@ -66,28 +96,26 @@ impl TelemetryBuilder {
     }
 
     /// Configure logging for the relayer
-    pub fn with_logging(self, datadog_enabled: bool) -> Self {
+    ///
+    /// Datadog-formatted logs take precedence over the given log format
+    pub fn with_logging(self, datadog_enabled: bool, log_format: LogFormat) -> Self {
         if datadog_enabled {
             opentelemetry::global::set_text_map_propagator(
                 opentelemetry_datadog::DatadogPropagator::new(),
             );
 
-            // Write JSON logs through a non-blocking, background-flushed writer.
-            // The default fmt writer holds the global stdout lock and writes
-            // synchronously, so under high log volume a slow consumer (e.g. the
-            // docker json-file driver) blocks every runtime thread that logs,
-            // wedging the node until the write drains -- observed as /v2/ping
-            // health-check timeouts and ECS killing the task. `non_blocking`
-            // drains a bounded queue on a dedicated thread and drops on overflow
-            // (lossy by default), so logging can never block the runtime.
-            let (writer, guard) = tracing_appender::non_blocking(std::io::stdout());
-            let _ = LOG_WORKER_GUARD.set(guard);
-
             self.with_layer(
                 fmt::layer()
                     .json()
                     .event_format(datadog::formatter::DatadogFormatter)
-                    .with_writer(writer),
+                    .with_writer(non_blocking_stdout()),
+            )
+        } else if log_format == LogFormat::Json {
+            self.with_layer(
+                fmt::layer()
+                    .json()
+                    .event_format(json_logs::CorrelatedJsonFormatter)
+                    .with_writer(non_blocking_stdout()),
             )
         } else {
             self.with_layer(fmt::layer().pretty())
@@ -137,6 +165,7 @@ impl TelemetryBuilder {
 /// based on the compilation features enabled
 pub fn configure_telemetry(
     datadog_enabled: bool,
+    log_format: LogFormat,
     otlp_enabled: bool,
     metrics_enabled: bool,
     collector_endpoint: String,
@@ -145,6 +174,7 @@ pub fn configure_telemetry(
 ) -> Result<(), TelemetrySetupError> {
     configure_telemetry_with_metrics_config(
         datadog_enabled,
+        log_format,
         otlp_enabled,
         metrics_enabled,
         collector_endpoint,
@@ -158,6 +188,7 @@ pub fn configure_telemetry(
 /// metrics configuration
 pub fn configure_telemetry_with_metrics_config(
     datadog_enabled: bool,
+    log_format: LogFormat,
     otlp_enabled: bool,
     metrics_enabled: bool,
     collector_endpoint: String,
@@ -171,7 +202,7 @@ pub fn configure_telemetry_with_metrics_config(
         .as_ref()
         .map_or(metrics::DEFAULT_RELAYER_METRICS_PREFIX.to_string(), |c| c.metrics_prefix.clone());
 
-    let mut telemetry = TelemetryBuilder::default().with_logging(datadog_enabled);
+    let mut telemetry = TelemetryBuilder::default().with_logging(datadog_enabled, log_format);
 
     if otlp_enabled {
         telemetry = telemetry.with_tracing(datadog_enabled, collector_endpoint)?;
@@ -0,0 +1,30 @@
+//! A registry of the relayer's metrics, rendered in the Prometheus text
+//! exposition format
+//!
+//! The registry's recorder is installed alongside the statsd recorder, so every
+//! metric emitted through the `metrics` macros may also be scraped, e.g. from
+//! the API server's `/metrics` route
+
+use std::sync::OnceLock;
+
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle, PrometheusRecorder};
+
+/// The handle used to render the registry, set when its recorder is built
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Build the registry's recorder, to be installed in the global recorder
+pub(super) fn build_prometheus_recorder() -> PrometheusRecorder {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let _ = PROMETHEUS_HANDLE.set(recorder.handle());
+    recorder
+}
+
+/// Render the registry's metrics in the Prometheus text exposition format
+///
+/// Returns `None` if the registry was never installed, i.e. metrics are
+/// disabled
+pub fn render_prometheus_metrics() -> Option<String> {
+    let handle = PROMETHEUS_HANDLE.get()?;
+    handle.run_upkeep();
+    Some(handle.render())
+}
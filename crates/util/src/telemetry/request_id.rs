@@ -0,0 +1,99 @@
+//! Helpers for assigning request IDs to inbound API requests
+//!
+//! A request ID is taken from the client's `x-request-id` header if it sent a
+//! usable one, and generated otherwise. The ID is recorded on the request's
+//! span so that every log emitted while handling the request carries it, and
+//! is returned to the client so that failures can be correlated with logs
+
+use http::HeaderMap;
+
+/// The header carrying a request's ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// The span field a request ID is recorded under
+pub const REQUEST_ID_FIELD: &str = "request_id";
+/// The maximum length of a client-provided request ID
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Generate a new request ID
+pub fn new_request_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Get the ID of a request from its headers, generating one if the client did
+/// not provide a valid ID
+pub fn request_id_from_headers(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .and_then(validate_request_id)
+        .unwrap_or_else(new_request_id)
+}
+
+/// Get the ID of a request from string-keyed headers, e.g. those of a
+/// websocket message, generating one if the client did not provide a valid ID
+pub fn request_id_from_string_headers<'a, I>(headers: I) -> String
+where
+    I: IntoIterator<Item = (&'a String, &'a String)>,
+{
+    headers
+        .into_iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(REQUEST_ID_HEADER))
+        .and_then(|(_, v)| validate_request_id(v))
+        .unwrap_or_else(new_request_id)
+}
+
+/// Record a request ID on the current span
+///
+/// The span must declare the `request_id` field for the ID to be recorded
+pub fn record_request_id(request_id: &str) {
+    tracing::Span::current().record(REQUEST_ID_FIELD, request_id);
+}
+
+/// Accept a client-provided request ID if it is non-empty, bounded in length,
+/// and made up of visible ASCII characters
+fn validate_request_id(request_id: &str) -> Option<String> {
+    let valid = !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LEN
+        && request_id.chars().all(|c| c.is_ascii_graphic());
+    valid.then(|| request_id.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use http::{HeaderMap, HeaderValue};
+
+    use super::{
+        MAX_REQUEST_ID_LEN, REQUEST_ID_HEADER, request_id_from_headers,
+        request_id_from_string_headers,
+    };
+
+    /// Tests that a valid client-provided request ID is kept
+    #[test]
+    fn test_client_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("client-id-1"));
+        assert_eq!(request_id_from_headers(&headers), "client-id-1");
+
+        let headers = HashMap::from([("X-Request-Id".to_string(), "client-id-2".to_string())]);
+        assert_eq!(request_id_from_string_headers(&headers), "client-id-2");
+    }
+
+    /// Tests that an ID is generated when the client does not provide a valid
+    /// one
+    #[test]
+    fn test_generated_request_id() {
+        let id = request_id_from_headers(&HeaderMap::new());
+        assert_eq!(id.len(), 32);
+        assert_ne!(id, request_id_from_headers(&HeaderMap::new()));
+
+        let mut headers = HeaderMap::new();
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&too_long).unwrap());
+        assert_ne!(request_id_from_headers(&headers), too_long);
+
+        let headers = HashMap::from([(REQUEST_ID_HEADER.to_string(), "has space".to_string())]);
+        assert_ne!(request_id_from_string_headers(&headers), "has space");
+    }
+}
@@ -24,8 +24,11 @@ use test_helpers::{
     integration_test, integration_test_async, integration_test_main, types::TestVerbosity,
 };
 use types_account::account::mocks::mock_empty_account;
-use types_core::HmacKey;
-use util::{on_chain::set_protocol_fee, telemetry::LevelFilter};
+use types_core::{HmacKey, Price};
+use util::{
+    on_chain::set_protocol_fee,
+    telemetry::{LevelFilter, LogFormat},
+};
 
 // -------
 // | CLI |
@@ -98,7 +101,7 @@ impl From<CliArgs> for IntegrationTestArgs {
             .with_darkpool_client()
             .with_state()
             .with_matching_engine_manager()
-            .with_mock_price_reporter(0.0001 /* price */)
+            .with_mock_price_reporter(Price::from_integer_ratio(1, 10_000).unwrap())
             .with_task_driver()
             .with_mock_proof_generation(true /* skip_constraints */)
             .with_api_server();
@@ -119,7 +122,7 @@ fn setup_integration_tests(test_args: &IntegrationTestArgs) {
 
     // Configure logging
     if matches!(test_args.verbosity, TestVerbosity::Full) {
-        util::telemetry::setup_system_logger(LevelFilter::INFO);
+        util::telemetry::setup_system_logger(LevelFilter::INFO, LogFormat::Pretty);
     }
 }
 
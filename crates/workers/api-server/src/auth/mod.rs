@@ -1,17 +1,40 @@
 //! Defines authentication primitives for the API server
 
-use external_api::auth::validate_expiring_auth;
+use std::{collections::HashMap, sync::Arc};
+
+use external_api::{
+    RENEGADE_API_KEY_ID_HEADER_NAME, RENEGADE_TENANT_ID_HEADER_NAME, auth::validate_expiring_auth,
+};
 use hyper::HeaderMap;
 use state::State;
+use types_account::TenantId;
 use types_core::{AccountId, HmacKey};
+use uuid::Uuid;
 
 use crate::{
-    error::{ApiServerError, not_found},
+    error::{ApiServerError, not_found, unauthorized},
     router::ERR_WALLET_NOT_FOUND,
 };
 
 /// Error message emitted when the admin API is disabled
 const ERR_ADMIN_API_DISABLED: &str = "Admin API is disabled";
+/// Error message emitted when a request names an API key the account does not
+/// have
+const ERR_UNKNOWN_API_KEY: &str = "unknown API key";
+/// Error message emitted when a request names a tenant the relayer does not
+/// serve
+const ERR_UNKNOWN_TENANT: &str = "unknown tenant";
+/// Error message emitted when a tenant route is requested without a tenant
+const ERR_TENANT_REQUIRED: &str = "request must be made on behalf of a tenant";
+
+/// Get the tenant a request is made on behalf of, if any
+///
+/// The header is only trusted once the router has authenticated the request
+/// against the tenant's key
+pub(crate) fn request_tenant(headers: &HeaderMap) -> Option<TenantId> {
+    let tenant = headers.get(RENEGADE_TENANT_ID_HEADER_NAME)?;
+    tenant.to_str().ok().map(str::to_string)
+}
 
 /// Represents the auth type required for a request
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -23,6 +46,11 @@ pub enum AuthType {
     /// Validates the signature if the account exists,
     /// otherwise allows the request through
     AccountIfExists,
+    /// A request made on behalf of a tenant
+    Tenant,
+    /// Validates the tenant's signature if the request names a tenant,
+    /// otherwise allows the request through
+    TenantIfPresent,
     /// No authentication is required
     None,
 }
@@ -32,14 +60,20 @@ pub enum AuthType {
 pub struct AuthMiddleware {
     /// The admin auth key, if enabled
     admin_key: Option<HmacKey>,
+    /// The keys of the tenants the API serves, keyed by tenant ID
+    tenant_keys: Arc<HashMap<TenantId, HmacKey>>,
     /// A handle on the relayer-global state
     state: State,
 }
 
 impl AuthMiddleware {
     /// Create a new authentication middleware
-    pub fn new(admin_key: Option<HmacKey>, state: State) -> Self {
-        Self { admin_key, state }
+    pub fn new(
+        admin_key: Option<HmacKey>,
+        tenant_keys: HashMap<TenantId, HmacKey>,
+        state: State,
+    ) -> Self {
+        Self { admin_key, tenant_keys: Arc::new(tenant_keys), state }
     }
 
     /// Whether or not admin auth is enabled
@@ -55,10 +89,14 @@ impl AuthMiddleware {
         headers: &HeaderMap,
         payload: &[u8],
     ) -> Result<(), ApiServerError> {
+        // A tenant may access only its own accounts
+        if let Some(tenant) = self.authenticate_tenant_request(path, headers, payload)? {
+            return self.check_account_tenant(account_id, &tenant, false /* allow_missing */).await;
+        }
+
         // Look up the verification key in the global state
         let key = self
-            .state
-            .get_account_symmetric_key(&account_id)
+            .get_verification_key(account_id, headers)
             .await?
             .ok_or_else(|| not_found(ERR_WALLET_NOT_FOUND.to_string()))?;
 
@@ -77,7 +115,11 @@ impl AuthMiddleware {
         headers: &HeaderMap,
         payload: &[u8],
     ) -> Result<(), ApiServerError> {
-        let key = self.state.get_account_symmetric_key(&account_id).await?;
+        if let Some(tenant) = self.authenticate_tenant_request(path, headers, payload)? {
+            return self.check_account_tenant(account_id, &tenant, true /* allow_missing */).await;
+        }
+
+        let key = self.get_verification_key(account_id, headers).await?;
         if let Some(key) = key {
             validate_expiring_auth(path, headers, payload, &key)?;
         }
@@ -100,6 +142,80 @@ impl AuthMiddleware {
         validate_expiring_auth(path, headers, payload, admin_key)?;
         Ok(())
     }
+
+    /// Authenticate a request made on behalf of a tenant
+    ///
+    /// Returns `None` if the request does not name a tenant
+    pub fn authenticate_tenant_request(
+        &self,
+        path: &str,
+        headers: &HeaderMap,
+        payload: &[u8],
+    ) -> Result<Option<TenantId>, ApiServerError> {
+        let Some(tenant) = request_tenant(headers) else {
+            return Ok(None);
+        };
+
+        let key = self.tenant_keys.get(&tenant).ok_or_else(|| unauthorized(ERR_UNKNOWN_TENANT))?;
+        validate_expiring_auth(path, headers, payload, key)?;
+        Ok(Some(tenant))
+    }
+
+    /// Authenticate a request on a route served only to tenants
+    pub fn authenticate_required_tenant_request(
+        &self,
+        path: &str,
+        headers: &HeaderMap,
+        payload: &[u8],
+    ) -> Result<TenantId, ApiServerError> {
+        self.authenticate_tenant_request(path, headers, payload)?
+            .ok_or_else(|| unauthorized(ERR_TENANT_REQUIRED))
+    }
+
+    /// Check that an account belongs to the given tenant
+    ///
+    /// Accounts of other tenants, or of no tenant, are reported as missing, so
+    /// that a tenant cannot probe for them. If `allow_missing` is set,
+    /// unclaimed accounts that do not yet exist are allowed through
+    async fn check_account_tenant(
+        &self,
+        account_id: AccountId,
+        tenant: &TenantId,
+        allow_missing: bool,
+    ) -> Result<(), ApiServerError> {
+        match self.state.get_account_tenant_claim(&account_id).await? {
+            Some(Some(owner)) if owner == *tenant => Ok(()),
+            None if allow_missing && !self.state.contains_account(&account_id).await? => Ok(()),
+            _ => Err(not_found(ERR_WALLET_NOT_FOUND.to_string())),
+        }
+    }
+
+    /// Get the key an account request is signed with
+    ///
+    /// Requests that name an API key in their headers are verified against
+    /// that key, all others against the account's symmetric key. Returns
+    /// `None` if the account does not exist
+    async fn get_verification_key(
+        &self,
+        account_id: AccountId,
+        headers: &HeaderMap,
+    ) -> Result<Option<HmacKey>, ApiServerError> {
+        let Some(key_id) = headers.get(RENEGADE_API_KEY_ID_HEADER_NAME) else {
+            return Ok(self.state.get_account_symmetric_key(&account_id).await?);
+        };
+
+        let key_id = key_id
+            .to_str()
+            .ok()
+            .and_then(|s| Uuid::parse_str(s).ok())
+            .ok_or_else(|| unauthorized(ERR_UNKNOWN_API_KEY))?;
+        if !self.state.contains_account(&account_id).await? {
+            return Ok(None);
+        }
+
+        let key = self.state.get_account_api_key(&account_id, &key_id).await?;
+        key.ok_or_else(|| unauthorized(ERR_UNKNOWN_API_KEY)).map(Some)
+    }
 }
 
 #[cfg(test)]
@@ -220,4 +336,74 @@ mod test {
         headers.insert("x-renegade-extra-header", "extra".parse().unwrap());
         validate_expiring_auth(path, &headers, payload, &key).unwrap();
     }
+
+    // --- Tenant Tests --- //
+
+    /// The tenant used in tests
+    const TENANT: &str = "tenant-a";
+
+    /// Build an auth middleware serving a single tenant with the given key
+    async fn tenant_middleware(tenant_key: HmacKey) -> AuthMiddleware {
+        let state = state::test_helpers::mock_state().await;
+        let tenant_keys = HashMap::from([(TENANT.to_string(), tenant_key)]);
+        AuthMiddleware::new(None /* admin_key */, tenant_keys, state)
+    }
+
+    /// Build headers naming the given tenant, signed with the given key
+    fn signed_tenant_headers(tenant: &str, key: &HmacKey) -> HeaderMap {
+        let (path, mut headers, payload) = get_dummy_path_header_map_payload();
+        headers.insert(RENEGADE_TENANT_ID_HEADER_NAME, tenant.parse().unwrap());
+        sign_request_add_expiration(path, &mut headers, payload, key);
+        headers
+    }
+
+    /// Tests authenticating requests made on behalf of a tenant
+    #[tokio::test]
+    async fn test_authenticate_tenant_request() {
+        let key = HmacKey::random();
+        let middleware = tenant_middleware(key).await;
+        let (path, headers, payload) = get_dummy_path_header_map_payload();
+
+        // Requests that name no tenant are passed through
+        let res = middleware.authenticate_tenant_request(path, &headers, payload).unwrap();
+        assert_eq!(res, None);
+
+        let headers = signed_tenant_headers(TENANT, &key);
+        let res = middleware.authenticate_tenant_request(path, &headers, payload).unwrap();
+        assert_eq!(res.as_deref(), Some(TENANT));
+
+        // Unknown tenants and requests signed with another key are rejected
+        let headers = signed_tenant_headers("tenant-b", &key);
+        assert!(middleware.authenticate_tenant_request(path, &headers, payload).is_err());
+        let headers = signed_tenant_headers(TENANT, &HmacKey::random());
+        assert!(middleware.authenticate_tenant_request(path, &headers, payload).is_err());
+    }
+
+    /// Tests that a tenant may only access the accounts it has claimed
+    #[tokio::test]
+    async fn test_check_account_tenant() {
+        let middleware = tenant_middleware(HmacKey::random()).await;
+        let state = &middleware.state;
+        let tenant = TENANT.to_string();
+
+        let owned = AccountId::new_v4();
+        let other = AccountId::new_v4();
+        let untenanted = AccountId::new_v4();
+        state.set_account_tenant(owned, Some(tenant.clone())).await.unwrap().await.unwrap();
+        let other_tenant = Some("tenant-b".to_string());
+        state.set_account_tenant(other, other_tenant).await.unwrap().await.unwrap();
+        state.set_account_tenant(untenanted, None).await.unwrap().await.unwrap();
+
+        for allow_missing in [false, true] {
+            assert!(middleware.check_account_tenant(owned, &tenant, allow_missing).await.is_ok());
+            assert!(middleware.check_account_tenant(other, &tenant, allow_missing).await.is_err());
+            let res = middleware.check_account_tenant(untenanted, &tenant, allow_missing).await;
+            assert!(res.is_err());
+        }
+
+        // Unclaimed accounts that do not exist are allowed only if requested
+        let unclaimed = AccountId::new_v4();
+        assert!(middleware.check_account_tenant(unclaimed, &tenant, true).await.is_ok());
+        assert!(middleware.check_account_tenant(unclaimed, &tenant, false).await.is_err());
+    }
 }
@@ -14,6 +14,8 @@ use super::router::{ResponseBody, build_500_response, build_response_from_status
 
 /// The error message for rate limit exceeded errors
 pub(crate) const ERR_RATE_LIMIT_EXCEEDED: &str = "Rate limit exceeded";
+/// The error message for writes refused while the node drains for shutdown
+pub(crate) const ERR_SHUTTING_DOWN: &str = "node is shutting down";
 /// The error message for account not found
 pub(crate) const ERR_ACCOUNT_NOT_FOUND: &str = "account not found";
 /// The error message for balance not found
@@ -76,7 +78,11 @@ impl From<hyper::Error> for ApiServerError {
 
 impl From<StateError> for ApiServerError {
     fn from(value: StateError) -> Self {
-        ApiServerError::State(value)
+        match value {
+            // Writes are refused while the node is in safe mode, reads are served
+            StateError::QuorumLost => service_unavailable(value),
+            _ => ApiServerError::State(value),
+        }
     }
 }
 
@@ -143,6 +149,12 @@ pub(crate) fn conflict<E: ToString>(e: E) -> ApiServerError {
     ApiServerError::HttpStatusCode(StatusCode::CONFLICT, e.to_string())
 }
 
+/// Create an `ApiServerError` with a 503 service unavailable code
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn service_unavailable<E: ToString>(e: E) -> ApiServerError {
+    ApiServerError::HttpStatusCode(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+}
+
 /// Create an `ApiServerError` with a 500 internal server error code
 #[allow(clippy::needless_pass_by_value)]
 pub(crate) fn internal_error<E: ToString>(e: E) -> ApiServerError {
@@ -29,6 +29,27 @@
 //! read nor the self-probe is starved by request load, so a busy-but-healthy node
 //! still answers 200 promptly -- preserving the anti-flap property above; only a
 //! real wedge (or a real loss of membership) trips the 503.
+//!
+//! Orchestrators that distinguish restarting a node from draining it probe two
+//! further paths on the same port:
+//!   - `/healthz` (liveness) answers 503 when a watched worker (gossip server,
+//!     network manager, price reporter, proof manager, ...) has exited or the
+//!     main runtime is wedged; the node must be restarted to recover. A node
+//!     that is merely unready, e.g. still joining the cluster, stays live.
+//!   - `/readyz` (readiness) additionally requires raft readiness, a reachable
+//!     quorum (the node is not in safe mode), and a state machine within
+//!     `MAX_READY_APPLY_LAG` entries of its raft log; the node should be drained
+//!     until it recovers.
+//!
+//! The same port serves the Prometheus scrape at `/metrics`, so that metrics
+//! are reachable from the node's internal network only, never through the
+//! public API.
+//!
+//! Once the coordinator begins its shutdown sequence, the load balancer check
+//! and `/readyz` fail so that traffic moves off the node, while `/healthz`
+//! stays live through the drain even as workers are stopped. Every probe
+//! reports the drain's phase, so an orchestrator can wait for `stopped` before
+//! killing the process.
 
 use std::{net::SocketAddr, time::Duration};
 
@@ -40,16 +61,28 @@ use hyper::{
     service::service_fn,
 };
 use hyper_util::rt::{TokioIo, TokioTimer};
+use price_state::PriceStreamStates;
+use serde_json::json;
 use state::State;
 use tokio::net::{TcpListener, TcpStream};
+use types_runtime::{ShutdownPhase, ShutdownProgress, WorkerLiveness};
 use util::get_current_time_millis;
 
-use crate::error::ApiServerError;
+use crate::{error::ApiServerError, prometheus::scrape_metrics};
 
 /// Timeout for the self-probe of the main HTTP server. Kept well under the ELB
 /// health-check timeout so a wedged main runtime is reported as 503 within a
 /// single check, but long enough that normal request latency never trips it.
 const MAIN_SERVER_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// The path of the liveness probe
+const LIVENESS_PATH: &str = "/healthz";
+/// The path of the readiness probe
+const READINESS_PATH: &str = "/readyz";
+/// The path of the Prometheus scrape
+const METRICS_PATH: &str = "/metrics";
+/// The maximum number of unapplied raft log entries at which a node is still
+/// ready to serve requests
+const MAX_READY_APPLY_LAG: u64 = 1_000;
 
 /// A minimal HTTP server that answers the ELB health check on a dedicated port.
 #[derive(Clone)]
@@ -65,17 +98,31 @@ pub struct HealthServer {
     /// HTTP client used to self-probe the main server. A bounded request that
     /// errors or times out means the main request-serving runtime is wedged.
     probe_client: reqwest::Client,
+    /// The liveness of the relayer's workers, reported by the liveness and
+    /// readiness probes
+    worker_liveness: WorkerLiveness,
+    /// The relayer's shutdown progress, reported by every probe
+    shutdown: ShutdownProgress,
+    /// The price streams, sampled for staleness on each metrics scrape
+    price_streams: PriceStreamStates,
 }
 
 impl HealthServer {
     /// Create a new health server bound to `port` that self-probes the main HTTP
     /// server on `http_port`
-    pub fn new(port: u16, http_port: u16, state: State) -> Self {
+    pub fn new(
+        port: u16,
+        http_port: u16,
+        state: State,
+        worker_liveness: WorkerLiveness,
+        shutdown: ShutdownProgress,
+        price_streams: PriceStreamStates,
+    ) -> Self {
         let probe_client = reqwest::Client::builder()
             .timeout(MAIN_SERVER_PROBE_TIMEOUT)
             .build()
             .expect("building the health probe client cannot fail");
-        Self { port, http_port, state, probe_client }
+        Self { port, http_port, state, probe_client, worker_liveness, shutdown, price_streams }
     }
 
     /// Accept connections and answer the health check, forever
@@ -93,35 +140,23 @@ impl HealthServer {
         }
     }
 
-    /// Serve a single connection, replying 200 only when this node is both a
-    /// ready raft member AND its main request-serving runtime answers a bounded
-    /// self-probe; 503 otherwise so the load balancer drains (and ECS recycles)
-    /// an unready or wedged node.
+    /// Serve a single connection, answering the liveness and readiness probes
+    /// and the metrics scrape on their paths and the load balancer health check
+    /// on any other
     async fn handle_stream(self, stream: TcpStream) -> Result<(), ApiServerError> {
-        let state = self.state.clone();
-        let probe_client = self.probe_client.clone();
-        let http_port = self.http_port;
-        let service = service_fn(move |_req: Request<IncomingBody>| {
-            let state = state.clone();
-            let probe_client = probe_client.clone();
+        let service = service_fn(move |req: Request<IncomingBody>| {
+            let server = self.clone();
             async move {
-                let raft_ready = state.is_raft_ready();
-                // Only probe once adopted: before then the main server has not
-                // bound its port yet, so a failed probe would be expected noise.
-                let serving = if raft_ready {
-                    let url = format!("http://127.0.0.1:{http_port}/v2/network");
-                    // Any HTTP response (even an error status) proves the main
-                    // runtime is making progress; a timeout/error means it is wedged.
-                    probe_client.get(url).send().await.is_ok()
-                } else {
-                    false
+                if req.uri().path() == METRICS_PATH {
+                    return Ok::<_, HyperError>(scrape_metrics(&server.price_streams));
+                }
+
+                let (ok, body) = match req.uri().path() {
+                    LIVENESS_PATH => server.liveness().await,
+                    READINESS_PATH => server.readiness().await,
+                    _ => server.load_balancer_check().await,
                 };
-                let ok = raft_ready && serving;
                 let status = if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
-                let body = format!(
-                    "{{\"timestamp\":{},\"ready\":{raft_ready},\"serving\":{serving}}}",
-                    get_current_time_millis()
-                );
                 let resp = Response::builder()
                     .status(status)
                     .body(Full::new(BytesBody::from(body)))
@@ -135,4 +170,192 @@ impl HealthServer {
         Http1Builder::new().timer(timer).serve_connection(stream_io, service).await?;
         Ok(())
     }
+
+    /// The load balancer health check, passing only when this node is both a
+    /// ready raft member AND its main request-serving runtime answers a bounded
+    /// self-probe, so the load balancer drains (and ECS recycles) an unready or
+    /// wedged node. A node that is shutting down also fails the check.
+    async fn load_balancer_check(&self) -> (bool, String) {
+        let raft_ready = self.state.is_raft_ready();
+        let phase = self.shutdown.phase();
+        // Only probe once adopted: before then the main server has not
+        // bound its port yet, so a failed probe would be expected noise.
+        let serving = raft_ready && self.main_server_serving().await;
+        let body = format!(
+            "{{\"timestamp\":{},\"ready\":{raft_ready},\"serving\":{serving},\"shutdown_phase\":\"{phase}\"}}",
+            get_current_time_millis()
+        );
+        (raft_ready && serving && phase == ShutdownPhase::Running, body)
+    }
+
+    /// The liveness probe, passing unless a watched worker has exited or the
+    /// main runtime is wedged
+    ///
+    /// Workers stopped by the shutdown sequence do not fail the probe, so the
+    /// node is not restarted mid-drain
+    async fn liveness(&self) -> (bool, String) {
+        let failed_workers = self.worker_liveness.failed_workers();
+        let draining = self.shutdown.is_draining();
+        // The main server is only bound once the node is adopted, so an unready
+        // node is not probed
+        let serving = !self.state.is_raft_ready() || self.main_server_serving().await;
+        let live = (failed_workers.is_empty() || draining) && serving;
+        let body = json!({
+            "timestamp": get_current_time_millis(),
+            "live": live,
+            "failed_workers": failed_workers,
+            "serving": serving,
+            "shutdown_phase": self.shutdown.phase().as_str(),
+        });
+        (live, body.to_string())
+    }
+
+    /// The readiness probe, passing when the node is a ready raft member with a
+    /// reachable quorum and a caught up state machine, every worker is live,
+    /// and the main runtime is serving. A node that is shutting down is never
+    /// ready
+    async fn readiness(&self) -> (bool, String) {
+        let raft_ready = self.state.is_raft_ready();
+        let safe_mode = self.state.is_safe_mode();
+        let apply_lag = self.state.raft_apply_lag();
+        let failed_workers = self.worker_liveness.failed_workers();
+        let serving = raft_ready && self.main_server_serving().await;
+
+        let phase = self.shutdown.phase();
+
+        let ready = raft_ready
+            && phase == ShutdownPhase::Running
+            && !safe_mode
+            && apply_lag <= MAX_READY_APPLY_LAG
+            && failed_workers.is_empty()
+            && serving;
+        let body = json!({
+            "timestamp": get_current_time_millis(),
+            "ready": ready,
+            "raft_ready": raft_ready,
+            "leader": self.state.is_leader(),
+            "safe_mode": safe_mode,
+            "apply_lag": apply_lag,
+            "failed_workers": failed_workers,
+            "serving": serving,
+            "shutdown_phase": phase.as_str(),
+            "in_flight_tasks": self.shutdown.in_flight_tasks(),
+        });
+        (ready, body.to_string())
+    }
+
+    /// Whether the main request-serving runtime answers a bounded self-probe
+    async fn main_server_serving(&self) -> bool {
+        let url = format!("http://127.0.0.1:{}/v2/network", self.http_port);
+        // Any HTTP response (even an error status) proves the main
+        // runtime is making progress; a timeout/error means it is wedged.
+        self.probe_client.get(url).send().await.is_ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use http_body_util::Full;
+    use hyper::{
+        Request, Response,
+        body::{Bytes as BytesBody, Incoming as IncomingBody},
+        service::service_fn,
+    };
+    use hyper_util::rt::TokioIo;
+    use price_state::{PriceStreamStates, aggregation::AggregationStrategies};
+    use state::test_helpers::mock_state;
+    use tokio::net::TcpListener;
+    use types_runtime::{ShutdownPhase, ShutdownProgress, WorkerLiveness};
+
+    use super::{HealthServer, Http1Builder};
+
+    /// Spawn a stand-in for the main HTTP server that answers every request,
+    /// returning its port
+    async fn spawn_main_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let service = service_fn(|_req: Request<IncomingBody>| async {
+                    Ok::<_, Infallible>(Response::new(Full::new(BytesBody::new())))
+                });
+                tokio::spawn(Http1Builder::new().serve_connection(TokioIo::new(stream), service));
+            }
+        });
+        port
+    }
+
+    /// Build a health server probing the main server on the given port
+    async fn health_server(
+        http_port: u16,
+        worker_liveness: WorkerLiveness,
+        shutdown: ShutdownProgress,
+    ) -> HealthServer {
+        let price_streams = PriceStreamStates::new(
+            vec![],
+            vec![],
+            AggregationStrategies::default(),
+            0.,
+            HashMap::new(),
+        );
+        let state = mock_state().await;
+        HealthServer::new(0, http_port, state, worker_liveness, shutdown, price_streams)
+    }
+
+    /// Tests that an exited worker fails both the liveness and readiness
+    /// probes
+    #[tokio::test]
+    async fn test_worker_exit() {
+        let http_port = spawn_main_server().await;
+        let worker_liveness = WorkerLiveness::new();
+        let server =
+            health_server(http_port, worker_liveness.clone(), ShutdownProgress::new()).await;
+        assert!(server.liveness().await.0);
+        assert!(server.readiness().await.0);
+
+        worker_liveness.mark_failed("gossip-server".to_string());
+        let (live, body) = server.liveness().await;
+        assert!(!live);
+        assert!(body.contains("gossip-server"));
+        assert!(!server.readiness().await.0);
+    }
+
+    /// Tests that a main runtime which does not answer its self-probe fails
+    /// the liveness probe
+    #[tokio::test]
+    async fn test_wedged_main_server() {
+        // Bind and release a port so that nothing answers on it
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let server = health_server(http_port, WorkerLiveness::new(), ShutdownProgress::new()).await;
+        assert!(!server.liveness().await.0);
+        assert!(!server.readiness().await.0);
+    }
+
+    /// Tests that a draining node fails the readiness probe and load balancer
+    /// check but stays live as its workers are stopped
+    #[tokio::test]
+    async fn test_shutdown_drain() {
+        let http_port = spawn_main_server().await;
+        let (worker_liveness, shutdown) = (WorkerLiveness::new(), ShutdownProgress::new());
+        let server = health_server(http_port, worker_liveness.clone(), shutdown.clone()).await;
+        assert!(server.load_balancer_check().await.0);
+
+        shutdown.advance(ShutdownPhase::StoppingWrites);
+        assert!(!server.load_balancer_check().await.0);
+        assert!(!server.readiness().await.0);
+        assert!(server.liveness().await.0);
+
+        // Workers stopped by the drain do not fail the liveness probe
+        shutdown.advance(ShutdownPhase::StoppingNetwork);
+        worker_liveness.mark_failed("gossip-server".to_string());
+        let (live, body) = server.liveness().await;
+        assert!(live);
+        assert!(body.contains("stopping-network"));
+    }
 }
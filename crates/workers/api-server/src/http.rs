@@ -1,47 +1,86 @@
 //! Groups handlers for the HTTP API
 
 mod account;
+mod account_versions;
 mod admin;
 pub(super) mod asset_filter;
 mod balance;
+mod cluster_admin;
+mod compression;
+mod cors;
+#[cfg(feature = "devnet")]
+mod devnet;
 mod external_match;
+mod fee_estimation;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod helpers;
 mod market;
 mod metadata;
 mod network;
 mod order;
-mod rate_limit;
+mod price_history;
+pub(crate) mod rate_limit;
+mod state_export;
 mod task;
 
 use account::{
-    CreateAccountHandler, GetAccountByIdHandler, GetAccountSeedsHandler, SyncAccountHandler,
+    CreateAccountApiKeyHandler, CreateAccountHandler, GetAccountApiKeysHandler,
+    GetAccountByIdHandler, GetAccountPinnedPeersHandler, GetAccountSeedsHandler,
+    GetAccountSummaryHandler, GetTenantAccountsHandler, RevokeAccountApiKeyHandler,
+    RotateAccountApiKeyHandler, SetAccountPinnedPeersHandler, SyncAccountHandler,
 };
+use account_versions::AccountVersions;
 use admin::{
-    AdminAssignOrderToPoolHandler, AdminCreateMatchingPoolHandler, AdminCreateOrderInPoolHandler,
-    AdminDestroyMatchingPoolHandler, AdminGetAccountOrdersHandler, AdminGetDisabledAssetsHandler,
-    AdminGetOrderByIdHandler, AdminGetOrdersHandler, AdminGetTaskQueuePausedHandler,
-    AdminRefreshMatchFeesHandler, AdminRefreshTokenMappingHandler,
-    AdminSetAccountDefaultPoolHandler, AdminTriggerSnapshotHandler, IsLeaderHandler,
+    AdminAddBlackoutWindowHandler, AdminAddTokenHandler, AdminAssignOrderToPoolHandler,
+    AdminCheckStateConsistencyHandler, AdminCreateMatchingPoolHandler,
+    AdminCreateOrderInPoolHandler, AdminDestroyMatchingPoolHandler, AdminExportStateHandler,
+    AdminGetAccountGasCostsHandler, AdminGetAccountOrdersHandler, AdminGetBlackoutWindowsHandler,
+    AdminGetChainEventsCheckpointHandler, AdminGetDisabledAssetsHandler,
+    AdminGetFeatureFlagsHandler, AdminGetMatchAttemptsHandler, AdminGetOrderByIdHandler,
+    AdminGetOrderMatchAttemptsHandler, AdminGetOrdersHandler, AdminGetTaskGasCostsHandler,
+    AdminGetTaskQueuePausedHandler, AdminRefreshMatchFeesHandler, AdminRefreshTokenMappingHandler,
+    AdminRemoveBlackoutWindowHandler, AdminResetChainEventsCheckpointHandler,
+    AdminSetAccountDefaultPoolHandler, AdminSetAccountRiskConfigHandler,
+    AdminSetAccountSweepPolicyHandler, AdminSetFeatureFlagHandler, AdminTriggerSnapshotHandler,
+    IsLeaderHandler,
 };
 use async_trait::async_trait;
 use balance::{
     DepositBalanceHandler, GetBalanceByMintHandler, GetBalancesHandler, WithdrawBalanceHandler,
 };
+use cluster_admin::{
+    AdminExpirePeerHandler, AdminGetHeartbeatIntervalsHandler, AdminGetPeerAccessHandler,
+    AdminGetPeersHandler, AdminPauseTaskQueueHandler, AdminResumeTaskQueueHandler,
+    AdminSetHeartbeatIntervalsHandler, AdminSetPeerAccessHandler,
+};
 use external_api::{
     EmptyRequestResponse,
     http::{
         PingResponse,
         account::{
-            CREATE_ACCOUNT_ROUTE, GET_ACCOUNT_BY_ID_ROUTE, GET_ACCOUNT_SEEDS_ROUTE,
+            ACCOUNT_API_KEYS_ROUTE, ACCOUNT_PINNED_PEERS_ROUTE, CREATE_ACCOUNT_ROUTE,
+            GET_ACCOUNT_BY_ID_ROUTE, GET_ACCOUNT_SEEDS_ROUTE, GET_ACCOUNT_SUMMARY_ROUTE,
+            GET_TENANT_ACCOUNTS_ROUTE, REVOKE_ACCOUNT_API_KEY_ROUTE, ROTATE_ACCOUNT_API_KEY_ROUTE,
             SYNC_ACCOUNT_ROUTE,
         },
         admin::{
-            ADMIN_ASSIGN_ORDER_TO_POOL_ROUTE, ADMIN_CREATE_ORDER_IN_POOL_ROUTE,
-            ADMIN_GET_ACCOUNT_ORDERS_ROUTE, ADMIN_GET_DISABLED_ASSETS_ROUTE,
-            ADMIN_GET_ORDER_BY_ID_ROUTE, ADMIN_GET_ORDERS_ROUTE, ADMIN_GET_TASK_QUEUE_PAUSED_ROUTE,
+            ADMIN_ADD_TOKEN_ROUTE, ADMIN_ASSIGN_ORDER_TO_POOL_ROUTE,
+            ADMIN_CHECK_STATE_CONSISTENCY_ROUTE, ADMIN_CREATE_ORDER_IN_POOL_ROUTE,
+            ADMIN_EXPIRE_PEER_ROUTE, ADMIN_EXPORT_STATE_ROUTE, ADMIN_GET_ACCOUNT_GAS_COSTS_ROUTE,
+            ADMIN_GET_ACCOUNT_ORDERS_ROUTE, ADMIN_GET_CHAIN_EVENTS_CHECKPOINT_ROUTE,
+            ADMIN_GET_DISABLED_ASSETS_ROUTE, ADMIN_GET_FEATURE_FLAGS_ROUTE,
+            ADMIN_GET_MATCH_ATTEMPTS_ROUTE, ADMIN_GET_ORDER_BY_ID_ROUTE,
+            ADMIN_GET_ORDER_MATCH_ATTEMPTS_ROUTE, ADMIN_GET_ORDERS_ROUTE, ADMIN_GET_PEERS_ROUTE,
+            ADMIN_GET_TASK_GAS_COSTS_ROUTE, ADMIN_GET_TASK_QUEUE_PAUSED_ROUTE,
+            ADMIN_HEARTBEAT_INTERVALS_ROUTE, ADMIN_MATCHING_BLACKOUTS_ROUTE,
             ADMIN_MATCHING_POOL_CREATE_ROUTE, ADMIN_MATCHING_POOL_DESTROY_ROUTE,
-            ADMIN_REFRESH_MATCH_FEES_ROUTE, ADMIN_REFRESH_TOKEN_MAPPING_ROUTE,
-            ADMIN_SET_ACCOUNT_DEFAULT_POOL_ROUTE, ADMIN_TRIGGER_SNAPSHOT_ROUTE, IS_LEADER_ROUTE,
+            ADMIN_PAUSE_TASK_QUEUE_ROUTE, ADMIN_PEER_ACCESS_ROUTE, ADMIN_REFRESH_MATCH_FEES_ROUTE,
+            ADMIN_REFRESH_TOKEN_MAPPING_ROUTE, ADMIN_REMOVE_MATCHING_BLACKOUT_ROUTE,
+            ADMIN_RESET_CHAIN_EVENTS_CHECKPOINT_ROUTE, ADMIN_RESUME_TASK_QUEUE_ROUTE,
+            ADMIN_SET_ACCOUNT_DEFAULT_POOL_ROUTE, ADMIN_SET_ACCOUNT_RISK_CONFIG_ROUTE,
+            ADMIN_SET_ACCOUNT_SWEEP_POLICY_ROUTE, ADMIN_SET_FEATURE_FLAG_ROUTE,
+            ADMIN_TRIGGER_SNAPSHOT_ROUTE, IS_LEADER_ROUTE,
         },
         balance::{
             DEPOSIT_BALANCE_ROUTE, GET_BALANCE_BY_MINT_ROUTE, GET_BALANCES_ROUTE,
@@ -49,22 +88,23 @@ use external_api::{
         },
         external_match::{ASSEMBLE_MATCH_BUNDLE_ROUTE, GET_EXTERNAL_MATCH_QUOTE_ROUTE},
         market::{
-            GET_MARKET_DEPTH_BY_MINT_ROUTE, GET_MARKET_PRICE_ROUTE, GET_MARKETS_DEPTH_ROUTE,
-            GET_MARKETS_ROUTE,
+            GET_FEE_ESTIMATE_ROUTE, GET_MARKET_DEPTH_BY_MINT_ROUTE, GET_MARKET_PRICE_ROUTE,
+            GET_MARKETS_DEPTH_ROUTE, GET_MARKETS_ROUTE, GET_PRICE_HISTORY_ROUTE,
         },
         metadata::GET_EXCHANGE_METADATA_ROUTE,
-        network::GET_NETWORK_TOPOLOGY_ROUTE,
+        network::{GET_NETWORK_TOPOLOGY_ROUTE, GET_ORDER_BOOK_SNAPSHOT_ROUTE},
         order::{
-            CANCEL_ORDER_ROUTE, CREATE_ORDER_ROUTE, GET_ORDER_BY_ID_ROUTE, GET_ORDERS_ROUTE,
-            UPDATE_ORDER_ROUTE,
+            CANCEL_ORDER_ROUTE, CREATE_ORDER_ROUTE, CREATE_ORDERS_BATCH_ROUTE,
+            GET_ORDER_BY_ID_ROUTE, GET_ORDERS_ROUTE, UPDATE_ORDER_ROUTE,
         },
-        task::{GET_TASK_BY_ID_ROUTE, GET_TASKS_ROUTE},
+        task::{GET_TASK_BY_ID_ROUTE, GET_TASK_HISTORY_ROUTE, GET_TASKS_ROUTE},
     },
 };
 use external_match::handlers::{AssembleMatchBundleHandler, GetExternalMatchQuoteHandler};
+use fee_estimation::{FeeEstimator, GetFeeEstimateHandler};
 use hyper::{
     Error as HyperError, HeaderMap, Method, Request, body::Incoming as IncomingBody,
-    server::conn::http1::Builder as Http1Builder, service::service_fn,
+    header::ACCEPT_ENCODING, server::conn::http1::Builder as Http1Builder, service::service_fn,
 };
 use hyper_util::rt::{TokioIo, TokioTimer};
 use market::{
@@ -72,21 +112,27 @@ use market::{
     MarketDataCalculator,
 };
 use metadata::GetExchangeMetadataHandler;
-use network::GetNetworkTopologyHandler;
+use network::{GetNetworkTopologyHandler, GetOrderBookSnapshotHandler};
 use order::{
-    CancelOrderHandler, CreateOrderHandler, GetOrderByIdHandler, GetOrdersHandler,
-    UpdateOrderHandler,
+    CancelOrderHandler, CreateOrderHandler, CreateOrdersBatchHandler, GetOrderByIdHandler,
+    GetOrdersHandler, UpdateOrderHandler,
 };
+use price_history::{GetPriceHistoryHandler, PriceHistoryRecorder};
+use state::feature_flags::FeatureFlagView;
 use std::{net::SocketAddr, sync::Arc};
-use task::{GetTaskByIdHandler, GetTasksHandler};
+use task::{GetTaskByIdHandler, GetTaskHistoryHandler, GetTasksHandler};
 use tokio::net::{TcpListener, TcpStream};
 use types_core::HmacKey;
 use util::get_current_time_millis;
 
-use self::asset_filter::AssetFilter;
+use self::{
+    asset_filter::AssetFilter, compression::ResponseCompressor, cors::CorsPolicy,
+    rate_limit::RequestRateLimiter,
+};
 
 use crate::{
-    auth::AuthType, http::external_match::processor::ExternalMatchProcessor, router::QueryParams,
+    auth::AuthType, http::external_match::processor::ExternalMatchProcessor,
+    openapi::OpenApiHandler, router::QueryParams,
 };
 
 use super::{
@@ -97,6 +143,8 @@ use super::{
 
 /// Health check
 pub const PING_ROUTE: &str = "/v2/ping";
+/// The OpenAPI spec of the routes served
+pub const OPENAPI_ROUTE: &str = "/openapi.json";
 
 /// A wrapper around the router and task management operations that
 /// the worker may delegate to
@@ -105,30 +153,51 @@ pub const PING_ROUTE: &str = "/v2/ping";
 pub(super) struct HttpServer {
     /// The http router, used to dispatch requests to handlers
     router: Arc<Router>,
+    /// The response compressor, applied to every response
+    compressor: ResponseCompressor,
+    /// The CORS policy, applied to every response
+    cors: CorsPolicy,
     /// The API server config
     config: ApiServerConfig,
 }
 
 impl HttpServer {
     /// Create a new http server
-    pub(super) fn new(config: ApiServerConfig) -> Result<Self, ApiServerError> {
+    pub(super) fn new(
+        config: ApiServerConfig,
+        feature_flags: FeatureFlagView,
+    ) -> Result<Self, ApiServerError> {
         // Build the router, server, and register routes
-        let router = Self::build_router(&config)?;
-        Ok(Self { router: Arc::new(router), config })
+        let router = Self::build_router(&config, feature_flags)?;
+        let compressor =
+            ResponseCompressor::new(config.compression_min_size, &config.compression_content_types);
+        let cors = CorsPolicy::new(&config.cors_allowed_origins, config.cors_allow_credentials);
+        Ok(Self { router: Arc::new(router), compressor, cors, config })
     }
 
     /// Build a router and register routes on it
-    fn build_router(config: &ApiServerConfig) -> Result<Router, ApiServerError> {
+    fn build_router(
+        config: &ApiServerConfig,
+        feature_flags: FeatureFlagView,
+    ) -> Result<Router, ApiServerError> {
         // Build the router and register its routes
-        let mut router = Router::new(config.admin_api_key, config.state.clone());
+        let rate_limiter = RequestRateLimiter::new(config.read_rate_limit, config.write_rate_limit);
+        let mut router = Router::new(
+            config.admin_api_key,
+            config.tenant_keys.clone(),
+            config.state.clone(),
+            rate_limiter,
+            config.trust_forwarded_for,
+            config.shutdown.clone(),
+        );
         let state = &config.state;
         let darkpool_client = &config.darkpool_client;
-        let bus = &config.system_bus;
         let matching_engine_worker_queue = &config.matching_engine_worker_queue;
         let task_queue = &config.task_queue;
 
         // Resolve disabled asset tickers to addresses once
-        let asset_filter = AssetFilter::new(&config.disabled_assets);
+        let asset_filter = AssetFilter::new(&config.disabled_assets, &config.allowed_assets);
+        let account_versions = AccountVersions::new(state.clone());
 
         // --- Misc Routes --- //
 
@@ -138,17 +207,32 @@ impl HttpServer {
         // --- Account Routes (v2) --- //
 
         // POST /v2/account
-        router.add_unauthenticated_route(
+        router.add_route(
             &Method::POST,
             CREATE_ACCOUNT_ROUTE.to_string(),
-            CreateAccountHandler::new(state.clone(), task_queue.clone()),
+            AuthType::TenantIfPresent,
+            CreateAccountHandler::new(state.clone(), task_queue.clone(), feature_flags.clone()),
+        );
+
+        // GET /v2/tenant/accounts
+        router.add_tenant_authenticated_route(
+            &Method::GET,
+            GET_TENANT_ACCOUNTS_ROUTE.to_string(),
+            GetTenantAccountsHandler::new(state.clone()),
         );
 
         // GET /v2/account/:account_id
         router.add_account_authenticated_route(
             &Method::GET,
             GET_ACCOUNT_BY_ID_ROUTE.to_string(),
-            GetAccountByIdHandler::new(state.clone()),
+            GetAccountByIdHandler::new(state.clone(), account_versions.clone()),
+        );
+
+        // GET /v2/account/:account_id/summary
+        router.add_account_authenticated_route(
+            &Method::GET,
+            GET_ACCOUNT_SUMMARY_ROUTE.to_string(),
+            GetAccountSummaryHandler::new(state.clone(), config.price_streams.clone()),
         );
 
         // GET /v2/account/:account_id/seeds
@@ -166,6 +250,48 @@ impl HttpServer {
             SyncAccountHandler::new(state.clone(), task_queue.clone()),
         );
 
+        // GET /v2/account/:account_id/pinned-peers
+        router.add_account_authenticated_route(
+            &Method::GET,
+            ACCOUNT_PINNED_PEERS_ROUTE.to_string(),
+            GetAccountPinnedPeersHandler::new(state.clone()),
+        );
+
+        // POST /v2/account/:account_id/pinned-peers
+        router.add_account_authenticated_route(
+            &Method::POST,
+            ACCOUNT_PINNED_PEERS_ROUTE.to_string(),
+            SetAccountPinnedPeersHandler::new(state.clone()),
+        );
+
+        // GET /v2/account/:account_id/api-keys
+        router.add_account_authenticated_route(
+            &Method::GET,
+            ACCOUNT_API_KEYS_ROUTE.to_string(),
+            GetAccountApiKeysHandler::new(state.clone()),
+        );
+
+        // POST /v2/account/:account_id/api-keys
+        router.add_account_authenticated_route(
+            &Method::POST,
+            ACCOUNT_API_KEYS_ROUTE.to_string(),
+            CreateAccountApiKeyHandler::new(state.clone()),
+        );
+
+        // POST /v2/account/:account_id/api-keys/:key_id/rotate
+        router.add_account_authenticated_route(
+            &Method::POST,
+            ROTATE_ACCOUNT_API_KEY_ROUTE.to_string(),
+            RotateAccountApiKeyHandler::new(state.clone()),
+        );
+
+        // POST /v2/account/:account_id/api-keys/:key_id/revoke
+        router.add_account_authenticated_route(
+            &Method::POST,
+            REVOKE_ACCOUNT_API_KEY_ROUTE.to_string(),
+            RevokeAccountApiKeyHandler::new(state.clone()),
+        );
+
         // --- Order Routes (v2) --- //
 
         // GET /v2/account/:account_id/orders
@@ -184,7 +310,30 @@ impl HttpServer {
                 executor,
                 asset_filter.clone(),
                 state.clone(),
+                config.price_streams.clone(),
                 task_queue.clone(),
+                account_versions.clone(),
+            ),
+        );
+
+        // POST /v2/account/:account_id/orders/batch
+        router.add_account_authenticated_route(
+            &Method::POST,
+            CREATE_ORDERS_BATCH_ROUTE.to_string(),
+            CreateOrdersBatchHandler::new(
+                CreateOrderHandler::new(
+                    executor,
+                    asset_filter.clone(),
+                    state.clone(),
+                    config.price_streams.clone(),
+                    task_queue.clone(),
+                    account_versions.clone(),
+                ),
+                CancelOrderHandler::new(
+                    state.clone(),
+                    task_queue.clone(),
+                    account_versions.clone(),
+                ),
             ),
         );
 
@@ -206,7 +355,7 @@ impl HttpServer {
         router.add_account_authenticated_route(
             &Method::POST,
             CANCEL_ORDER_ROUTE.to_string(),
-            CancelOrderHandler::new(state.clone(), task_queue.clone()),
+            CancelOrderHandler::new(state.clone(), task_queue.clone(), account_versions.clone()),
         );
 
         // --- Balance Routes (v2) --- //
@@ -229,14 +378,19 @@ impl HttpServer {
         router.add_account_authenticated_route(
             &Method::POST,
             DEPOSIT_BALANCE_ROUTE.to_string(),
-            DepositBalanceHandler::new(asset_filter.clone(), state.clone(), task_queue.clone()),
+            DepositBalanceHandler::new(
+                asset_filter.clone(),
+                state.clone(),
+                task_queue.clone(),
+                account_versions.clone(),
+            ),
         );
 
         // POST /v2/account/:account_id/balances/:mint/withdraw
         router.add_account_authenticated_route(
             &Method::POST,
             WITHDRAW_BALANCE_ROUTE.to_string(),
-            WithdrawBalanceHandler::new(state.clone(), task_queue.clone()),
+            WithdrawBalanceHandler::new(state.clone(), task_queue.clone(), account_versions),
         );
 
         // --- Task Routes (v2) --- //
@@ -255,19 +409,19 @@ impl HttpServer {
             GetTaskByIdHandler::new(),
         );
 
+        // GET /v2/account/:account_id/task-history
+        router.add_account_authenticated_route(
+            &Method::GET,
+            GET_TASK_HISTORY_ROUTE.to_string(),
+            GetTaskHistoryHandler::new(state.clone()),
+        );
+
         // --- External Match Routes (v2) --- //
         // If the admin API key is not set, these endpoints are disabled, so a random
         // default is used instead
         let admin_key = config.admin_api_key.unwrap_or_else(HmacKey::random);
-        let processor = ExternalMatchProcessor::new(
-            admin_key,
-            asset_filter.clone(),
-            darkpool_client.clone(),
-            bus.clone(),
-            matching_engine_worker_queue.clone(),
-            config.price_streams.clone(),
-            state.clone(),
-        );
+        let processor =
+            ExternalMatchProcessor::new(config, admin_key, asset_filter.clone(), feature_flags);
 
         // POST /v2/external-matches/get-quote
         router.add_admin_authenticated_route(
@@ -319,6 +473,22 @@ impl HttpServer {
             GetMarketPriceHandler::new(asset_filter.clone(), config.price_streams.clone()),
         );
 
+        // GET /v2/markets/:mint/price-history
+        router.add_unauthenticated_route(
+            &Method::GET,
+            GET_PRICE_HISTORY_ROUTE.to_string(),
+            GetPriceHistoryHandler::new(asset_filter.clone(), state.clone()),
+        );
+
+        // GET /v2/markets/:mint/fee-estimate
+        let fee_estimator =
+            FeeEstimator::new(state.clone(), darkpool_client.clone(), market_calculator.clone());
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            GET_FEE_ESTIMATE_ROUTE.to_string(),
+            GetFeeEstimateHandler::new(fee_estimator),
+        );
+
         // --- Metadata Routes (v2) --- //
 
         // GET /v2/metadata/exchange
@@ -341,6 +511,13 @@ impl HttpServer {
             GetNetworkTopologyHandler::new(config.chain, state.clone()),
         );
 
+        // GET /v2/network/order-book
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            GET_ORDER_BOOK_SNAPSHOT_ROUTE.to_string(),
+            GetOrderBookSnapshotHandler::new(state.clone()),
+        );
+
         // --- Admin Routes --- //
 
         // GET /v2/admin/is-leader (preserved)
@@ -357,11 +534,78 @@ impl HttpServer {
             AdminTriggerSnapshotHandler::new(state.clone()),
         );
 
+        // POST /v2/admin/check-state-consistency
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_CHECK_STATE_CONSISTENCY_ROUTE.to_string(),
+            AdminCheckStateConsistencyHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/export-state
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_EXPORT_STATE_ROUTE.to_string(),
+            AdminExportStateHandler::new(state.clone(), config.state_export_path.clone()),
+        );
+
+        // GET /v2/admin/peers
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_PEERS_ROUTE.to_string(),
+            AdminGetPeersHandler::new(state.clone(), config.peer_latencies.clone()),
+        );
+
+        // POST /v2/admin/peers/:peer_id/expire
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_EXPIRE_PEER_ROUTE.to_string(),
+            AdminExpirePeerHandler::new(state.clone(), config.network_sender.clone()),
+        );
+
+        // GET /v2/admin/peer-access
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_PEER_ACCESS_ROUTE.to_string(),
+            AdminGetPeerAccessHandler::new(config.peer_access.clone()),
+        );
+
+        // POST /v2/admin/peer-access
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_PEER_ACCESS_ROUTE.to_string(),
+            AdminSetPeerAccessHandler::new(
+                state.clone(),
+                config.peer_access.clone(),
+                config.network_sender.clone(),
+            ),
+        );
+
+        // GET /v2/admin/heartbeat-intervals
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_HEARTBEAT_INTERVALS_ROUTE.to_string(),
+            AdminGetHeartbeatIntervalsHandler::new(config.heartbeat_settings.clone()),
+        );
+
+        // POST /v2/admin/heartbeat-intervals
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_HEARTBEAT_INTERVALS_ROUTE.to_string(),
+            AdminSetHeartbeatIntervalsHandler::new(config.heartbeat_settings.clone()),
+        );
+
         // POST /v2/admin/refresh-token-mapping (preserved)
         router.add_admin_authenticated_route(
             &Method::POST,
             ADMIN_REFRESH_TOKEN_MAPPING_ROUTE.to_string(),
-            AdminRefreshTokenMappingHandler::new(config.chain),
+            AdminRefreshTokenMappingHandler::new(config.chain, state.clone()),
+        );
+
+        // POST /v2/admin/tokens
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_ADD_TOKEN_ROUTE.to_string(),
+            AdminAddTokenHandler::new(state.clone()),
         );
 
         // POST /v2/admin/refresh-match-fees (preserved)
@@ -378,6 +622,55 @@ impl HttpServer {
             AdminGetDisabledAssetsHandler::new(config.disabled_assets.clone()),
         );
 
+        // GET /v2/admin/feature-flags
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_FEATURE_FLAGS_ROUTE.to_string(),
+            AdminGetFeatureFlagsHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/feature-flags/:flag
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_SET_FEATURE_FLAG_ROUTE.to_string(),
+            AdminSetFeatureFlagHandler::new(state.clone()),
+        );
+
+        // GET /v2/admin/matching-blackouts
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_MATCHING_BLACKOUTS_ROUTE.to_string(),
+            AdminGetBlackoutWindowsHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/matching-blackouts
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_MATCHING_BLACKOUTS_ROUTE.to_string(),
+            AdminAddBlackoutWindowHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/matching-blackouts/:window_id/remove
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_REMOVE_MATCHING_BLACKOUT_ROUTE.to_string(),
+            AdminRemoveBlackoutWindowHandler::new(state.clone()),
+        );
+
+        // GET /v2/admin/chain-events/checkpoint
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_CHAIN_EVENTS_CHECKPOINT_ROUTE.to_string(),
+            AdminGetChainEventsCheckpointHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/chain-events/checkpoint/reset
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_RESET_CHAIN_EVENTS_CHECKPOINT_ROUTE.to_string(),
+            AdminResetChainEventsCheckpointHandler::new(state.clone()),
+        );
+
         // GET /v2/relayer-admin/orders (v2)
         router.add_admin_authenticated_route(
             &Method::GET,
@@ -392,6 +685,20 @@ impl HttpServer {
             AdminGetOrderByIdHandler::new(state.clone()),
         );
 
+        // GET /v2/relayer-admin/orders/:order_id/match-attempts
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_ORDER_MATCH_ATTEMPTS_ROUTE.to_string(),
+            AdminGetOrderMatchAttemptsHandler::new(state.clone()),
+        );
+
+        // GET /v2/relayer-admin/match-attempts
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_MATCH_ATTEMPTS_ROUTE.to_string(),
+            AdminGetMatchAttemptsHandler::new(state.clone()),
+        );
+
         // GET /v2/relayer-admin/account/:account_id/orders
         router.add_admin_authenticated_route(
             &Method::GET,
@@ -406,6 +713,34 @@ impl HttpServer {
             AdminGetTaskQueuePausedHandler::new(state.clone()),
         );
 
+        // POST /v2/relayer-admin/account/:account_id/tasks/pause
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_PAUSE_TASK_QUEUE_ROUTE.to_string(),
+            AdminPauseTaskQueueHandler::new(state.clone()),
+        );
+
+        // POST /v2/relayer-admin/account/:account_id/tasks/resume
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_RESUME_TASK_QUEUE_ROUTE.to_string(),
+            AdminResumeTaskQueueHandler::new(state.clone()),
+        );
+
+        // GET /v2/relayer-admin/account/:account_id/gas-costs
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_ACCOUNT_GAS_COSTS_ROUTE.to_string(),
+            AdminGetAccountGasCostsHandler::new(state.clone()),
+        );
+
+        // GET /v2/relayer-admin/gas-costs
+        router.add_admin_authenticated_route(
+            &Method::GET,
+            ADMIN_GET_TASK_GAS_COSTS_ROUTE.to_string(),
+            AdminGetTaskGasCostsHandler::new(state.clone()),
+        );
+
         // --- Matching Pool Routes (v2) --- //
 
         // POST /v2/admin/matching-pools/:matching_pool
@@ -443,6 +778,61 @@ impl HttpServer {
             AdminSetAccountDefaultPoolHandler::new(state.clone()),
         );
 
+        // POST /v2/admin/account/:account_id/risk-config
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_SET_ACCOUNT_RISK_CONFIG_ROUTE.to_string(),
+            AdminSetAccountRiskConfigHandler::new(state.clone()),
+        );
+
+        // POST /v2/admin/account/:account_id/sweep-policy
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            ADMIN_SET_ACCOUNT_SWEEP_POLICY_ROUTE.to_string(),
+            AdminSetAccountSweepPolicyHandler::new(state.clone()),
+        );
+
+        // --- GraphQL Routes --- //
+
+        // POST /v2/admin/graphql
+        #[cfg(feature = "graphql")]
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            graphql::GRAPHQL_ROUTE.to_string(),
+            graphql::GraphqlHandler::new(state.clone()),
+        );
+
+        // --- Devnet Routes --- //
+
+        // POST /v2/devnet/account/:account_id/balances/:mint/faucet
+        #[cfg(feature = "devnet")]
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            external_api::http::devnet::DEVNET_FAUCET_ROUTE.to_string(),
+            devnet::DevnetFaucetHandler::new(state.clone()),
+        );
+
+        // POST /v2/devnet/account/:account_id/orders
+        #[cfg(feature = "devnet")]
+        router.add_admin_authenticated_route(
+            &Method::POST,
+            external_api::http::devnet::DEVNET_FABRICATE_ORDER_ROUTE.to_string(),
+            devnet::DevnetFabricateOrderHandler::new(
+                state.clone(),
+                config.proof_generation_work_queue.clone(),
+            ),
+        );
+
+        // --- OpenAPI Spec --- //
+
+        // GET /openapi.json, registered last so that the spec covers every route
+        let spec = router.openapi_spec();
+        router.add_unauthenticated_route(
+            &Method::GET,
+            OPENAPI_ROUTE.to_string(),
+            OpenApiHandler::new(spec),
+        );
+
         Ok(router)
     }
 
@@ -455,23 +845,43 @@ impl HttpServer {
             .map_err(ApiServerError::server_failure)?;
         let listener = TcpListener::bind(addr).await.map_err(ApiServerError::server_failure)?;
 
+        // Start recording price history, unless disabled
+        if self.config.price_history_retention_hours > 0 {
+            let recorder = PriceHistoryRecorder::new(
+                self.config.state.clone(),
+                self.config.price_streams.clone(),
+                AssetFilter::new(&self.config.disabled_assets, &self.config.allowed_assets),
+                self.config.price_history_retention_hours,
+            );
+            tokio::spawn(recorder.run());
+        }
+
         // Main execution loop
         loop {
-            let (stream, _) = listener.accept().await.map_err(ApiServerError::server_failure)?;
+            let (stream, remote_addr) =
+                listener.accept().await.map_err(ApiServerError::server_failure)?;
             let self_clone = self.clone();
-            tokio::spawn(async move { self_clone.handle_stream(stream).await });
+            tokio::spawn(async move { self_clone.handle_stream(stream, remote_addr).await });
         }
     }
 
     /// Handle an incoming TCP stream from a client
-    async fn handle_stream(&self, stream: TcpStream) -> Result<(), ApiServerError> {
+    async fn handle_stream(
+        &self,
+        stream: TcpStream,
+        remote_addr: SocketAddr,
+    ) -> Result<(), ApiServerError> {
         let service_fn = service_fn(move |req: Request<IncomingBody>| {
             let self_clone = self.clone();
             async move {
-                let resp = self_clone
+                let accept_encoding = req.headers().get(ACCEPT_ENCODING).cloned();
+                let cors_headers = self_clone.cors.response_headers(req.method(), req.headers());
+                let mut resp = self_clone
                     .router
-                    .handle_req(req.method().to_owned(), req.uri().clone(), req)
+                    .handle_req(req.method().to_owned(), req.uri().clone(), remote_addr, req)
                     .await;
+                resp.headers_mut().extend(cors_headers);
+                let resp = self_clone.compressor.compress(accept_encoding.as_ref(), resp).await;
 
                 Ok::<_, HyperError>(resp)
             }
@@ -2,22 +2,35 @@
 
 use async_trait::async_trait;
 use external_api::{
-    EmptyRequestResponse,
+    EmptyRequestResponse, RENEGADE_API_KEY_ID_HEADER_NAME,
     http::account::{
-        CreateAccountRequest, GetAccountResponse, GetAccountSeedsResponse, SyncAccountRequest,
-        SyncAccountResponse,
+        AccountApiKeyResponse, ApiBalanceValue, ApiReplicationHealth, CreateAccountRequest,
+        GetAccountApiKeysResponse, GetAccountPinnedPeersResponse, GetAccountResponse,
+        GetAccountSeedsResponse, GetAccountSummaryResponse, GetTenantAccountsResponse,
+        SetAccountPinnedPeersRequest, SyncAccountRequest, SyncAccountResponse,
     },
 };
 use hyper::HeaderMap;
+use itertools::Itertools;
 use job_types::task_driver::TaskDriverQueue;
-use state::State;
-use types_account::keychain::{KeyChain, PrivateKeyChain};
-use types_tasks::{NewAccountTaskDescriptor, RefreshAccountTaskDescriptor};
+use price_state::PriceStreamStates;
+use state::{State, feature_flags::FeatureFlagView};
+use types_account::{
+    balance::{Balance, BalanceLocation},
+    keychain::{KeyChain, PrivateKeyChain},
+};
+use types_core::{AccountId, FeatureFlag, HmacKey, Token};
+use types_gossip::WrappedPeerId;
+use types_tasks::{HistoricalTask, NewAccountTaskDescriptor, RefreshAccountTaskDescriptor};
+use uuid::Uuid;
 
 use crate::{
-    error::{ApiServerError, conflict, not_found},
-    http::helpers::append_task,
-    param_parsing::{parse_account_id_from_params, should_block_on_task},
+    auth::request_tenant,
+    error::{ApiServerError, bad_request, conflict, not_found, service_unavailable, unauthorized},
+    http::{account_versions::AccountVersions, helpers::append_task, task::to_api_task},
+    param_parsing::{
+        parse_account_id_from_params, parse_api_key_id_from_params, should_block_on_task,
+    },
     router::{QueryParams, TypedHandler, UrlParams},
 };
 
@@ -35,6 +48,71 @@ pub(crate) fn account_already_exists() -> ApiServerError {
     conflict("account already exists")
 }
 
+/// Create an account creation paused error
+fn account_creation_paused() -> ApiServerError {
+    service_unavailable("new account creation is paused")
+}
+
+/// The maximum number of peers an account may pin
+const MAX_PINNED_PEERS: usize = 16;
+/// Error message emitted when too many peers are pinned
+const ERR_TOO_MANY_PINNED_PEERS: &str = "too many pinned peers";
+/// Error message emitted when a pinned peer ID is invalid
+const ERR_INVALID_PINNED_PEER: &str = "invalid pinned peer id";
+
+/// The maximum number of API keys an account may hold at once
+const MAX_API_KEYS: usize = 8;
+/// Error message emitted when an account already holds the maximum number of
+/// API keys
+const ERR_TOO_MANY_API_KEYS: &str = "too many api keys";
+
+/// Create an API key not found error
+fn api_key_not_found() -> ApiServerError {
+    not_found("api key not found")
+}
+
+/// Reject a request signed with an API key rather than the account's own key
+///
+/// API keys may not be used to manage API keys, so that a leaked key cannot be
+/// used to mint or rotate others
+fn check_signed_with_account_key(headers: &HeaderMap) -> Result<(), ApiServerError> {
+    if headers.contains_key(RENEGADE_API_KEY_ID_HEADER_NAME) {
+        return Err(unauthorized("api keys cannot manage api keys"));
+    }
+
+    Ok(())
+}
+
+/// Claim an account for the tenant a request is made on behalf of, or outside
+/// of any tenant if the request names none
+///
+/// The claim is committed before the account is created, so that an account
+/// created by a tenant is never visible outside of it. Accounts claimed by
+/// another caller are reported as missing, so that a caller cannot probe for
+/// them. Concurrent claims are ordered by raft, and all but the first are
+/// rejected by the applicator
+async fn claim_account_tenant(
+    account_id: AccountId,
+    headers: &HeaderMap,
+    state: &State,
+) -> Result<(), ApiServerError> {
+    let tenant = request_tenant(headers);
+    if let Some(claim) = state.get_account_tenant_claim(&account_id).await? {
+        return if claim == tenant { Ok(()) } else { Err(account_not_found()) };
+    }
+
+    // Accounts created before claims were recorded belong to no tenant
+    if state.contains_account(&account_id).await? {
+        return if tenant.is_none() { Ok(()) } else { Err(account_not_found()) };
+    }
+
+    let waiter = state.set_account_tenant(account_id, tenant).await?;
+    match waiter.await {
+        Err(e) if e.is_rejection() => Err(account_not_found()),
+        res => res.map(|_| ()).map_err(ApiServerError::from),
+    }
+}
+
 // --------------------
 // | Account Handlers |
 // --------------------
@@ -43,12 +121,14 @@ pub(crate) fn account_already_exists() -> ApiServerError {
 pub struct GetAccountByIdHandler {
     /// A handle to the relayer's state
     state: State,
+    /// The versions of accounts mutated through the API
+    account_versions: AccountVersions,
 }
 
 impl GetAccountByIdHandler {
     /// Constructor
-    pub fn new(state: State) -> Self {
-        Self { state }
+    pub fn new(state: State, account_versions: AccountVersions) -> Self {
+        Self { state, account_versions }
     }
 }
 
@@ -67,7 +147,91 @@ impl TypedHandler for GetAccountByIdHandler {
         let account_id = parse_account_id_from_params(&params)?;
         let acct = self.state.get_account(&account_id).await?.ok_or_else(account_not_found)?;
 
-        Ok(GetAccountResponse { account: acct.into() })
+        let version = self.account_versions.current(&account_id).await?;
+        Ok(GetAccountResponse { account: acct.into(), version })
+    }
+}
+
+/// Handler for GET /v2/account/:account_id/summary
+pub struct GetAccountSummaryHandler {
+    /// A handle to the relayer's state
+    state: State,
+    /// The price streams, used to value the account's balances
+    price_streams: PriceStreamStates,
+}
+
+impl GetAccountSummaryHandler {
+    /// Constructor
+    pub fn new(state: State, price_streams: PriceStreamStates) -> Self {
+        Self { state, price_streams }
+    }
+
+    /// Estimate the USD value of a balance
+    ///
+    /// Returns `None` if the balance's token is not priced
+    fn usd_value(&self, balance: &Balance) -> Option<f64> {
+        let token = Token::from_alloy_address(&balance.mint());
+        let amount = token.convert_to_decimal(balance.amount());
+        if token.is_stablecoin() {
+            return Some(amount);
+        }
+
+        let price = self.price_streams.peek_price(&token).ok()?;
+        (!price.is_zero()).then_some(amount * price.to_f64())
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetAccountSummaryHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetAccountSummaryResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let account = self.state.get_account(&account_id).await?.ok_or_else(account_not_found)?;
+
+        // Value the account's darkpool balances
+        let balances = account
+            .get_all_balances()
+            .into_iter()
+            .filter(|b| b.location == BalanceLocation::Darkpool)
+            .map(|balance| {
+                let usd_value = self.usd_value(&balance);
+                ApiBalanceValue { balance: balance.into(), usd_value }
+            })
+            .collect_vec();
+        let total_usd_value = balances.iter().filter_map(|b| b.usd_value).sum();
+
+        let pending_tasks = self
+            .state
+            .get_queued_tasks(&account_id)
+            .await?
+            .into_iter()
+            .filter_map(|task| HistoricalTask::from_queued_task(account_id, task))
+            .map(to_api_task)
+            .collect_vec();
+
+        let last_match_timestamp = self.state.get_account_last_match_time(&account_id).await?;
+        let replication = ApiReplicationHealth {
+            ready: self.state.is_raft_ready(),
+            apply_lag: self.state.raft_apply_lag(),
+            cluster_size: self.state.cluster_size(),
+        };
+
+        Ok(GetAccountSummaryResponse {
+            orders: account.orders.into_values().map(Into::into).collect(),
+            balances,
+            total_usd_value,
+            pending_tasks,
+            last_match_timestamp,
+            replication,
+        })
     }
 }
 
@@ -77,12 +241,14 @@ pub struct CreateAccountHandler {
     state: State,
     /// The task driver queue
     task_queue: TaskDriverQueue,
+    /// The feature flags, checked before creating an account
+    feature_flags: FeatureFlagView,
 }
 
 impl CreateAccountHandler {
     /// Constructor
-    pub fn new(state: State, task_queue: TaskDriverQueue) -> Self {
-        Self { state, task_queue }
+    pub fn new(state: State, task_queue: TaskDriverQueue, feature_flags: FeatureFlagView) -> Self {
+        Self { state, task_queue, feature_flags }
     }
 }
 
@@ -93,16 +259,22 @@ impl TypedHandler for CreateAccountHandler {
 
     async fn handle_typed(
         &self,
-        _headers: HeaderMap,
+        headers: HeaderMap,
         req: Self::Request,
         _params: UrlParams,
         query_params: QueryParams,
     ) -> Result<Self::Response, ApiServerError> {
+        if self.feature_flags.is_enabled(FeatureFlag::PauseAccountCreation) {
+            return Err(account_creation_paused());
+        }
+
         // Check if account already exists
         if self.state.get_account(&req.account_id).await?.is_some() {
             return Err(account_already_exists());
         }
 
+        claim_account_tenant(req.account_id, &headers, &self.state).await?;
+
         let blocking = should_block_on_task(&query_params);
         let private_keys = PrivateKeyChain::new(req.auth_hmac_key, req.master_view_seed);
         let keychain = KeyChain::new(private_keys, req.schnorr_public_key);
@@ -171,7 +343,7 @@ impl TypedHandler for SyncAccountHandler {
 
     async fn handle_typed(
         &self,
-        _headers: HeaderMap,
+        headers: HeaderMap,
         req: Self::Request,
         params: UrlParams,
         query_params: QueryParams,
@@ -181,6 +353,9 @@ impl TypedHandler for SyncAccountHandler {
         // Parse account_id from URL params
         let account_id = parse_account_id_from_params(&params)?;
 
+        // An account synced by a tenant belongs to it
+        claim_account_tenant(account_id, &headers, &self.state).await?;
+
         // Build keychain from request
         let private_keys = PrivateKeyChain::new(req.auth_hmac_key, req.master_view_seed);
         let keychain = KeyChain::new(private_keys, req.schnorr_public_key);
@@ -194,3 +369,277 @@ impl TypedHandler for SyncAccountHandler {
         Ok(SyncAccountResponse { task_id, completed: true })
     }
 }
+
+/// Handler for GET /v2/tenant/accounts
+pub struct GetTenantAccountsHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl GetTenantAccountsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetTenantAccountsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetTenantAccountsResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let tenant = request_tenant(&headers).ok_or_else(|| unauthorized("missing tenant"))?;
+
+        // Accounts are assigned before they are created, so skip those whose
+        // creation has not completed
+        let mut account_ids = Vec::new();
+        for account_id in self.state.get_tenant_account_ids(&tenant).await? {
+            if self.state.contains_account(&account_id).await? {
+                account_ids.push(account_id);
+            }
+        }
+
+        Ok(GetTenantAccountsResponse { account_ids })
+    }
+}
+
+/// Handler for GET /v2/account/:account_id/pinned-peers
+pub struct GetAccountPinnedPeersHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl GetAccountPinnedPeersHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetAccountPinnedPeersHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetAccountPinnedPeersResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let peers = self.state.get_account_pinned_peers(&account_id).await?.unwrap_or_default();
+        let peers = peers.iter().map(WrappedPeerId::to_string).collect();
+        Ok(GetAccountPinnedPeersResponse { peers })
+    }
+}
+
+/// Handler for POST /v2/account/:account_id/pinned-peers
+///
+/// Pinned peers are preferred when assigning the account's tasks after a peer
+/// failure and when selecting the peer that matches the account's orders
+pub struct SetAccountPinnedPeersHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl SetAccountPinnedPeersHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for SetAccountPinnedPeersHandler {
+    type Request = SetAccountPinnedPeersRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        if req.peers.len() > MAX_PINNED_PEERS {
+            return Err(bad_request(ERR_TOO_MANY_PINNED_PEERS));
+        }
+
+        let mut peers: Vec<WrappedPeerId> = Vec::with_capacity(req.peers.len());
+        for peer in req.peers.iter() {
+            let peer_id = peer.parse().map_err(|_| bad_request(ERR_INVALID_PINNED_PEER))?;
+            if !peers.contains(&peer_id) {
+                peers.push(peer_id);
+            }
+        }
+
+        let peers = if peers.is_empty() { None } else { Some(peers) };
+        let waiter = self.state.set_account_pinned_peers(account_id, peers).await?;
+        waiter.await?;
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+/// Handler for GET /v2/account/:account_id/api-keys
+pub struct GetAccountApiKeysHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl GetAccountApiKeysHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetAccountApiKeysHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetAccountApiKeysResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let key_ids = self.state.get_account_api_key_ids(&account_id).await?;
+        Ok(GetAccountApiKeysResponse { key_ids })
+    }
+}
+
+/// Handler for POST /v2/account/:account_id/api-keys
+///
+/// Requests signed with the returned key are authorized for the account just
+/// as if they were signed with the account's own HMAC key
+pub struct CreateAccountApiKeyHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl CreateAccountApiKeyHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for CreateAccountApiKeyHandler {
+    type Request = EmptyRequestResponse;
+    type Response = AccountApiKeyResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        check_signed_with_account_key(&headers)?;
+        let account_id = parse_account_id_from_params(&params)?;
+        let key_ids = self.state.get_account_api_key_ids(&account_id).await?;
+        if key_ids.len() >= MAX_API_KEYS {
+            return Err(bad_request(ERR_TOO_MANY_API_KEYS));
+        }
+
+        let key_id = Uuid::new_v4();
+        let secret = HmacKey::random();
+        let waiter = self.state.set_account_api_key(account_id, key_id, Some(secret)).await?;
+        waiter.await?;
+        Ok(AccountApiKeyResponse { key_id, secret })
+    }
+}
+
+/// Handler for POST /v2/account/:account_id/api-keys/:key_id/rotate
+///
+/// Replaces the key's secret, after which requests signed with the old secret
+/// are rejected
+pub struct RotateAccountApiKeyHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl RotateAccountApiKeyHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for RotateAccountApiKeyHandler {
+    type Request = EmptyRequestResponse;
+    type Response = AccountApiKeyResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        check_signed_with_account_key(&headers)?;
+        let account_id = parse_account_id_from_params(&params)?;
+        let key_id = parse_api_key_id_from_params(&params)?;
+        if self.state.get_account_api_key(&account_id, &key_id).await?.is_none() {
+            return Err(api_key_not_found());
+        }
+
+        let secret = HmacKey::random();
+        let waiter = self.state.set_account_api_key(account_id, key_id, Some(secret)).await?;
+        waiter.await?;
+        Ok(AccountApiKeyResponse { key_id, secret })
+    }
+}
+
+/// Handler for POST /v2/account/:account_id/api-keys/:key_id/revoke
+pub struct RevokeAccountApiKeyHandler {
+    /// A handle to the relayer's state
+    state: State,
+}
+
+impl RevokeAccountApiKeyHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for RevokeAccountApiKeyHandler {
+    type Request = EmptyRequestResponse;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        check_signed_with_account_key(&headers)?;
+        let account_id = parse_account_id_from_params(&params)?;
+        let key_id = parse_api_key_id_from_params(&params)?;
+        if self.state.get_account_api_key(&account_id, &key_id).await?.is_none() {
+            return Err(api_key_not_found());
+        }
+
+        let waiter = self.state.set_account_api_key(account_id, key_id, None).await?;
+        waiter.await?;
+        Ok(EmptyRequestResponse {})
+    }
+}
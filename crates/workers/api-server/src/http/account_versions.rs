@@ -0,0 +1,116 @@
+//! Optimistic concurrency control for account mutations
+//!
+//! Each account has a version that is returned by account reads and advanced
+//! each time the API enqueues a task that mutates the account. A mutating
+//! request may carry the version it was built against in the
+//! `x-renegade-account-version` header; if the account has since been mutated
+//! the request is rejected with a conflict rather than queueing a task that
+//! contradicts the one enqueued before it.
+//!
+//! Versions are held in the replicated state and advanced through raft, so
+//! they survive restarts and are consistent across the relayers of a cluster
+
+use external_api::RENEGADE_ACCOUNT_VERSION_HEADER_NAME;
+use hyper::HeaderMap;
+use state::State;
+use types_core::AccountId;
+
+use crate::error::{ApiServerError, bad_request, conflict};
+
+/// The error message given when a request's account version is stale
+const ERR_STALE_ACCOUNT_VERSION: &str =
+    "account was modified since the given version, re-read the account and retry";
+/// The error message given when the account version header is malformed
+const ERR_INVALID_ACCOUNT_VERSION: &str = "invalid account version header";
+
+/// The versions of the accounts mutated through the API
+#[derive(Clone)]
+pub struct AccountVersions {
+    /// The relayer state, which holds the versions
+    state: State,
+}
+
+impl AccountVersions {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+
+    /// Get the current version of an account
+    pub async fn current(&self, account_id: &AccountId) -> Result<u64, ApiServerError> {
+        Ok(self.state.get_account_version(account_id).await?)
+    }
+
+    /// Check the version a request was built against and advance the
+    /// account's version
+    ///
+    /// Requests without a version header are not checked but still advance the
+    /// version. This should be called immediately before the mutating task is
+    /// enqueued, once the request has otherwise been validated
+    pub async fn check_and_advance(
+        &self,
+        account_id: &AccountId,
+        headers: &HeaderMap,
+    ) -> Result<(), ApiServerError> {
+        let expected = parse_account_version(headers)?;
+        let waiter = self.state.advance_account_version(*account_id, expected).await?;
+        match waiter.await {
+            Err(e) if e.is_rejection() => Err(conflict(ERR_STALE_ACCOUNT_VERSION)),
+            res => res.map(|_| ()).map_err(ApiServerError::from),
+        }
+    }
+}
+
+/// Parse the account version from a request's headers, if one is given
+fn parse_account_version(headers: &HeaderMap) -> Result<Option<u64>, ApiServerError> {
+    let Some(value) = headers.get(RENEGADE_ACCOUNT_VERSION_HEADER_NAME) else {
+        return Ok(None);
+    };
+
+    let version = value.to_str().ok().and_then(|v| v.parse().ok());
+    version.map(Some).ok_or_else(|| bad_request(ERR_INVALID_ACCOUNT_VERSION))
+}
+
+#[cfg(test)]
+mod test {
+    use external_api::RENEGADE_ACCOUNT_VERSION_HEADER_NAME;
+    use hyper::{HeaderMap, StatusCode};
+    use state::test_helpers::mock_state;
+    use uuid::Uuid;
+
+    use crate::error::ApiServerError;
+
+    use super::AccountVersions;
+
+    /// Build headers carrying the given account version
+    fn version_headers(version: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RENEGADE_ACCOUNT_VERSION_HEADER_NAME, version.parse().unwrap());
+        headers
+    }
+
+    /// Tests that concurrent mutations against the same version conflict
+    #[tokio::test]
+    async fn test_concurrent_mutations_conflict() {
+        let versions = AccountVersions::new(mock_state().await);
+        let account_id = Uuid::new_v4();
+        assert_eq!(versions.current(&account_id).await.unwrap(), 0);
+
+        // Two clients read version zero, the first to mutate wins
+        let headers = version_headers("0");
+        versions.check_and_advance(&account_id, &headers).await.unwrap();
+        let err = versions.check_and_advance(&account_id, &headers).await.unwrap_err();
+        assert!(matches!(err, ApiServerError::HttpStatusCode(StatusCode::CONFLICT, _)));
+
+        // The second client re-reads and retries
+        let version = versions.current(&account_id).await.unwrap();
+        assert_eq!(version, 1);
+        let headers = version_headers(&version.to_string());
+        versions.check_and_advance(&account_id, &headers).await.unwrap();
+
+        // Unversioned requests are not checked
+        versions.check_and_advance(&account_id, &HeaderMap::new()).await.unwrap();
+        assert_eq!(versions.current(&account_id).await.unwrap(), 3);
+        assert!(versions.check_and_advance(&account_id, &version_headers("three")).await.is_err());
+    }
+}
@@ -1,22 +1,32 @@
 //! Route handlers for the admin API
 
+use std::path::PathBuf;
+
 use alloy::primitives::Address;
 use async_trait::async_trait;
-use config::setup_token_remaps;
+use config::{check_token_listing, setup_token_remaps};
 use constants::GLOBAL_MATCHING_POOL;
 use darkpool_client::DarkpoolClient;
 use external_api::{
     EmptyRequestResponse,
     http::{
         admin::{
-            AssignOrderToPoolRequest, GetDisabledAssetsResponse, IsLeaderResponse,
-            SetAccountDefaultMatchingPoolRequest,
+            AddBlackoutWindowRequest, AddBlackoutWindowResponse, AddTokenRequest,
+            ApiBlackoutWindow, ApiChainEventCursor, ApiFeatureFlag, AssignOrderToPoolRequest,
+            CheckStateConsistencyRequest, CheckStateConsistencyResponse, ExportStateRequest,
+            ExportStateResponse, ExportTable, ExportedTable, GetBlackoutWindowsResponse,
+            GetChainEventsCheckpointResponse, GetDisabledAssetsResponse, GetFeatureFlagsResponse,
+            IsLeaderResponse, ResetChainEventsCheckpointRequest,
+            SetAccountDefaultMatchingPoolRequest, SetAccountRiskConfigRequest,
+            SetAccountSweepPolicyRequest, SetFeatureFlagRequest,
         },
         order::{CreateOrderInPoolRequest, CreateOrderResponse},
     },
     types::{
-        ApiAdminOrder, GetOrderAdminResponse, GetOrdersAdminResponse, OrderType,
-        TaskQueuePausedResponse, order::ApiOrder,
+        ApiAdminOrder, ApiGasCostTotals, ApiMatchAttempt, ApiMatchPhase, ApiTaskTypeGasCosts,
+        GetAccountGasCostsResponse, GetMatchAttemptsResponse, GetOrderAdminResponse,
+        GetOrdersAdminResponse, GetTaskTypeGasCostsResponse, OrderType, TaskQueuePausedResponse,
+        order::ApiOrder,
     },
 };
 use hyper::HeaderMap;
@@ -24,21 +34,31 @@ use job_types::{
     matching_engine::{MatchingEngineWorkerJob, MatchingEngineWorkerQueue},
     task_driver::TaskDriverQueue,
 };
-use state::State;
-use types_core::{Chain, Token, get_all_tokens};
-use util::log_task;
+use state::{
+    State,
+    storage::tx::match_audit::{MAX_MATCH_AUDIT_ENTRIES, MatchAttempt, MatchPhase},
+};
+use types_core::{BlackoutWindow, Chain, Token, TokenListing, get_all_tokens};
+use types_tasks::GasCostTotals;
 use util::logging::Outcome;
 use util::on_chain::{set_default_protocol_fee, set_protocol_fee};
+use util::{get_current_time_millis, log_task};
 
-use types_account::OrderId;
+use types_account::{OrderId, sweep::AccountSweepPolicy};
+use uuid::Uuid;
 
 use crate::{
     error::{ApiServerError, bad_request, conflict, internal_error, not_found},
-    http::helpers::append_create_order_task,
+    http::{
+        helpers::{append_create_order_task, check_client_order_fields},
+        state_export::{remove_local_export, upload_export},
+    },
     logging::Task,
     param_parsing::{
-        parse_account_id_from_params, parse_matching_pool_from_query_params,
-        parse_matching_pool_from_url_params, parse_order_id_from_params, should_block_on_task,
+        parse_account_id_from_params, parse_blackout_window_id_from_params,
+        parse_feature_flag_from_params, parse_matching_pool_from_query_params,
+        parse_matching_pool_from_url_params, parse_order_filter_from_query_params,
+        parse_order_id_from_params, should_block_on_task,
     },
     router::{QueryParams, TypedHandler, UrlParams},
 };
@@ -136,16 +156,118 @@ impl TypedHandler for AdminTriggerSnapshotHandler {
     }
 }
 
+/// Handler for the POST /v2/admin/check-state-consistency route
+///
+/// The check reads this node's replica; repairs are proposed through raft and
+/// applied by every replica
+pub struct AdminCheckStateConsistencyHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminCheckStateConsistencyHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminCheckStateConsistencyHandler {
+    type Request = CheckStateConsistencyRequest;
+    type Response = CheckStateConsistencyResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let issues = self.state.check_consistency(req.repair).await?;
+        let repaired = req.repair && !issues.is_empty();
+        let issues = issues.iter().map(ToString::to_string).collect();
+        Ok(CheckStateConsistencyResponse { issues, repaired })
+    }
+}
+
+/// Handler for the POST /v2/admin/export-state route
+///
+/// The export is read from this node's replica of the state
+pub struct AdminExportStateHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// The directory in which exports are written
+    export_path: PathBuf,
+}
+
+impl AdminExportStateHandler {
+    /// Constructor
+    pub fn new(state: State, export_path: PathBuf) -> Self {
+        Self { state, export_path }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminExportStateHandler {
+    type Request = ExportStateRequest;
+    type Response = ExportStateResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        // Export every table if none are given, each at most once
+        let requested = if req.tables.is_empty() { ExportTable::ALL.to_vec() } else { req.tables };
+        let mut tables = Vec::with_capacity(requested.len());
+        for table in requested {
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+
+        let export_id = Uuid::new_v4();
+        let dir = self.export_path.join(export_id.to_string());
+        let exports = self.state.export_tables(tables, req.format, dir.clone()).await?;
+
+        let locations = match req.s3_bucket {
+            Some(bucket) => {
+                let prefix = req.s3_prefix.as_deref();
+                let res = upload_export(&bucket, prefix, export_id, &exports).await;
+                remove_local_export(&dir).await;
+                res?
+            },
+            None => exports.iter().map(|e| e.path.display().to_string()).collect(),
+        };
+
+        let tables = exports
+            .into_iter()
+            .zip(locations)
+            .map(|(export, location)| ExportedTable {
+                table: export.table,
+                rows: export.rows,
+                location,
+            })
+            .collect();
+        Ok(ExportStateResponse { export_id, tables })
+    }
+}
+
 /// Handler for the POST /v2/admin/refresh-token-mapping route
 pub struct AdminRefreshTokenMappingHandler {
     /// The chain to fetch a token mapping for
     chain: Chain,
+    /// A handle to the relayer state, holding the tokens listed at runtime
+    state: State,
 }
 
 impl AdminRefreshTokenMappingHandler {
     /// Constructor
-    pub fn new(chain: Chain) -> Self {
-        Self { chain }
+    pub fn new(chain: Chain, state: State) -> Self {
+        Self { chain, state }
     }
 }
 
@@ -173,6 +295,59 @@ impl TypedHandler for AdminRefreshTokenMappingHandler {
             .map_err(internal_error) // Tokio join error
             .and_then(|r| r.map_err(internal_error))?; // Token remap setup error
 
+        // The refreshed mapping overwrites the tokens listed at runtime
+        self.state.apply_token_listings().await?;
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+/// Handler for the POST /v2/admin/tokens route
+///
+/// The listing is replicated through raft, every node adds the token to its
+/// token mapping and re-applies it after the mapping is refreshed. The price
+/// reporter begins streaming the token's prices on its next listing refresh
+pub struct AdminAddTokenHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminAddTokenHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminAddTokenHandler {
+    type Request = AddTokenRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        // Validate the listing locally before proposing it, the applicator
+        // checks it again against each node's token mapping
+        let listing = TokenListing::from(req);
+        check_token_listing(&listing).map_err(bad_request)?;
+
+        let waiter = self.state.add_token_listing(listing.clone()).await?;
+        match waiter.await {
+            Err(e) if e.is_rejection() => return Err(bad_request(e)),
+            res => res?,
+        };
+
+        log_task!(
+            Task::AddToken,
+            Outcome::Ok,
+            ticker = %listing.ticker,
+            addresses = ?listing.addresses,
+            "token added to token mapping"
+        );
         Ok(EmptyRequestResponse {})
     }
 }
@@ -258,6 +433,7 @@ impl TypedHandler for AdminGetOrdersHandler {
         query_params: QueryParams,
     ) -> Result<Self::Response, ApiServerError> {
         let pool_filter = parse_matching_pool_from_query_params(&query_params);
+        let order_filter = parse_order_filter_from_query_params(&query_params);
 
         // Get all orders with their admin metadata
         let orders_data =
@@ -266,8 +442,10 @@ impl TypedHandler for AdminGetOrdersHandler {
         // Convert to API types, filtering by matching pool if specified
         let orders: Vec<ApiAdminOrder> = orders_data
             .into_iter()
-            .filter(|(_, _, matching_pool, _)| {
-                pool_filter.as_ref().is_none_or(|filter| matching_pool == filter)
+            .filter(|(order, _, matching_pool, _)| {
+                let pool_matches =
+                    pool_filter.as_ref().is_none_or(|filter| matching_pool == filter);
+                pool_matches && order_filter.matches(order)
             })
             .map(|(order, account_id, matching_pool, matchable_amount)| ApiAdminOrder {
                 order: ApiOrder::from(order),
@@ -423,8 +601,89 @@ impl TypedHandler for AdminGetTaskQueuePausedHandler {
         let account_id = parse_account_id_from_params(&params)?;
         let paused =
             self.state.is_queue_paused_serial(&account_id).await.map_err(internal_error)?;
+        let paused_by_operator =
+            self.state.is_queue_paused_by_operator(&account_id).await.map_err(internal_error)?;
+
+        Ok(TaskQueuePausedResponse { paused, paused_by_operator })
+    }
+}
+
+/// Convert aggregate gas costs to their API representation
+fn to_api_gas_cost_totals(totals: GasCostTotals) -> ApiGasCostTotals {
+    ApiGasCostTotals {
+        num_submissions: totals.num_submissions,
+        gas_used: totals.gas_used,
+        fee_paid: totals.fee_paid,
+    }
+}
+
+/// Handler for GET /v2/relayer-admin/account/:account_id/gas-costs
+pub struct AdminGetAccountGasCostsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetAccountGasCostsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
 
-        Ok(TaskQueuePausedResponse { paused })
+#[async_trait]
+impl TypedHandler for AdminGetAccountGasCostsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetAccountGasCostsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let totals = self.state.get_account_gas_costs(&account_id).await.map_err(internal_error)?;
+
+        Ok(GetAccountGasCostsResponse { account_id, totals: to_api_gas_cost_totals(totals) })
+    }
+}
+
+/// Handler for GET /v2/relayer-admin/gas-costs
+pub struct AdminGetTaskGasCostsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetTaskGasCostsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetTaskGasCostsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetTaskTypeGasCostsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let totals = self.state.get_task_type_gas_costs().await.map_err(internal_error)?;
+        let task_types = totals
+            .into_iter()
+            .map(|(task_type, totals)| ApiTaskTypeGasCosts {
+                task_type,
+                totals: to_api_gas_cost_totals(totals),
+            })
+            .collect();
+
+        Ok(GetTaskTypeGasCostsResponse { task_types })
     }
 }
 
@@ -586,6 +845,7 @@ impl TypedHandler for AdminCreateOrderInPoolHandler {
         if self.state.get_account_order(&order_id).await?.is_some() {
             return Err(conflict(ERR_ORDER_ALREADY_EXISTS));
         }
+        check_client_order_fields(account_id, &req.order, &self.state).await?;
 
         // Validate matching pool exists
         let matching_pool = req.matching_pool.clone();
@@ -721,3 +981,473 @@ impl TypedHandler for AdminSetAccountDefaultPoolHandler {
         Ok(EmptyRequestResponse {})
     }
 }
+
+// -------------------------------------
+// | Handler: Set Account Risk Config  |
+// -------------------------------------
+
+/// Handler for POST /v2/admin/account/:account_id/risk-config
+pub struct AdminSetAccountRiskConfigHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminSetAccountRiskConfigHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminSetAccountRiskConfigHandler {
+    type Request = SetAccountRiskConfigRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+
+        // Verify the account exists
+        if !self.state.contains_account(&account_id).await? {
+            return Err(not_found(format!("account {account_id} not found")));
+        }
+
+        let config = req.risk_config.map(Into::into);
+        let waiter = self.state.set_account_risk_config(account_id, config).await?;
+        waiter.await?;
+
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+// --------------------------------------
+// | Handler: Set Account Sweep Policy  |
+// --------------------------------------
+
+/// Handler for POST /v2/admin/account/:account_id/sweep-policy
+pub struct AdminSetAccountSweepPolicyHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminSetAccountSweepPolicyHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminSetAccountSweepPolicyHandler {
+    type Request = SetAccountSweepPolicyRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+
+        // Verify the account exists
+        if !self.state.contains_account(&account_id).await? {
+            return Err(not_found(format!("account {account_id} not found")));
+        }
+
+        let policy: Option<AccountSweepPolicy> = req.sweep_policy.map(Into::into);
+        if let Some(policy) = &policy {
+            policy.validate().map_err(bad_request)?;
+        }
+
+        let waiter = self.state.set_account_sweep_policy(account_id, policy).await?;
+        waiter.await?;
+
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+// -------------------------
+// | Feature Flag Handlers |
+// -------------------------
+
+/// Handler for GET /v2/admin/feature-flags
+pub struct AdminGetFeatureFlagsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetFeatureFlagsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetFeatureFlagsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetFeatureFlagsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let flags = self
+            .state
+            .get_feature_flags()
+            .await?
+            .into_iter()
+            .map(|(flag, enabled)| ApiFeatureFlag {
+                flag: flag.to_string(),
+                enabled,
+                default: flag.default_value(),
+            })
+            .collect();
+
+        Ok(GetFeatureFlagsResponse { flags })
+    }
+}
+
+/// Handler for POST /v2/admin/feature-flags/:flag
+pub struct AdminSetFeatureFlagHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminSetFeatureFlagHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminSetFeatureFlagHandler {
+    type Request = SetFeatureFlagRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let flag = parse_feature_flag_from_params(&params)?;
+        let waiter = self.state.set_feature_flag(flag, req.enabled).await?;
+        waiter.await?;
+
+        log_task!(
+            Task::SetFeatureFlag,
+            Outcome::Ok,
+            flag = %flag,
+            enabled = req.enabled,
+            "feature flag set"
+        );
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+// ------------------------------
+// | Matching Blackout Handlers |
+// ------------------------------
+
+/// Handler for GET /v2/admin/matching-blackouts
+pub struct AdminGetBlackoutWindowsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetBlackoutWindowsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetBlackoutWindowsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetBlackoutWindowsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let windows = self.state.get_blackout_windows().await?;
+        let now = get_current_time_millis();
+        let active = windows.iter().any(|w| w.contains(now));
+        let windows = windows.into_iter().map(ApiBlackoutWindow::from).collect();
+
+        Ok(GetBlackoutWindowsResponse { windows, active })
+    }
+}
+
+/// Handler for POST /v2/admin/matching-blackouts
+pub struct AdminAddBlackoutWindowHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminAddBlackoutWindowHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminAddBlackoutWindowHandler {
+    type Request = AddBlackoutWindowRequest;
+    type Response = AddBlackoutWindowResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let window =
+            BlackoutWindow::new(req.start, req.end, req.period, req.reason).map_err(bad_request)?;
+        let waiter = self.state.add_blackout_window(window.clone()).await?;
+        waiter.await?;
+
+        log_task!(
+            Task::SetMatchingBlackout,
+            Outcome::Ok,
+            window_id = %window.id,
+            window = %window,
+            reason = %window.reason,
+            "matching blackout window added"
+        );
+        Ok(AddBlackoutWindowResponse { window: window.into() })
+    }
+}
+
+/// Handler for POST /v2/admin/matching-blackouts/:window_id/remove
+pub struct AdminRemoveBlackoutWindowHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminRemoveBlackoutWindowHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminRemoveBlackoutWindowHandler {
+    type Request = EmptyRequestResponse;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let window_id = parse_blackout_window_id_from_params(&params)?;
+        let exists = self.state.get_blackout_windows().await?.iter().any(|w| w.id == window_id);
+        if !exists {
+            return Err(not_found(format!("blackout window {window_id} not found")));
+        }
+
+        let waiter = self.state.remove_blackout_window(window_id).await?;
+        waiter.await?;
+
+        log_task!(
+            Task::SetMatchingBlackout,
+            Outcome::Ok,
+            window_id = %window_id,
+            "matching blackout window removed"
+        );
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+// -------------------------------------
+// | Chain Events Checkpoint Handlers |
+// -------------------------------------
+
+/// Handler for GET /v2/admin/chain-events/checkpoint
+pub struct AdminGetChainEventsCheckpointHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetChainEventsCheckpointHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetChainEventsCheckpointHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetChainEventsCheckpointResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let checkpoint = self.state.get_chain_events_checkpoint().await?.map(|cursor| {
+            ApiChainEventCursor { block_number: cursor.block_number, log_index: cursor.log_index }
+        });
+
+        Ok(GetChainEventsCheckpointResponse { checkpoint })
+    }
+}
+
+/// Handler for POST /v2/admin/chain-events/checkpoint/reset
+///
+/// The checkpoint is local to the node serving the request
+pub struct AdminResetChainEventsCheckpointHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminResetChainEventsCheckpointHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminResetChainEventsCheckpointHandler {
+    type Request = ResetChainEventsCheckpointRequest;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        self.state.reset_chain_events_checkpoint(req.from_block).await?;
+
+        log_task!(
+            Task::ResetChainEventsCheckpoint,
+            Outcome::Ok,
+            from_block = ?req.from_block,
+            "chain events checkpoint reset"
+        );
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+// -------------------------------
+// | Match Attempt Audit Handlers |
+// -------------------------------
+
+/// The number of recent match attempts returned when not filtering by order
+const DEFAULT_MATCH_ATTEMPTS_LIMIT: usize = 100;
+
+/// Convert a match attempt from the audit trail to its API representation
+fn to_api_match_attempt(attempt: MatchAttempt) -> ApiMatchAttempt {
+    let phase = match attempt.phase {
+        MatchPhase::Started => ApiMatchPhase::Started,
+        MatchPhase::MatchFound => ApiMatchPhase::MatchFound,
+        MatchPhase::RiskChecked => ApiMatchPhase::RiskChecked,
+        MatchPhase::SettlementQueued => ApiMatchPhase::SettlementQueued,
+        MatchPhase::IndicativeFill => ApiMatchPhase::IndicativeFill,
+    };
+
+    ApiMatchAttempt {
+        id: attempt.id,
+        peer_id: attempt.peer_id.to_string(),
+        order_id: attempt.order_id,
+        other_order_id: attempt.other_order_id,
+        phase,
+        failure_reason: attempt.failure_reason,
+        started_at: attempt.started_at,
+        duration_ms: attempt.duration_ms,
+    }
+}
+
+/// Handler for GET /v2/relayer-admin/match-attempts
+pub struct AdminGetMatchAttemptsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetMatchAttemptsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetMatchAttemptsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetMatchAttemptsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let attempts = self.state.get_match_attempts(None, DEFAULT_MATCH_ATTEMPTS_LIMIT).await?;
+        let attempts = attempts.into_iter().map(to_api_match_attempt).collect();
+        Ok(GetMatchAttemptsResponse { attempts })
+    }
+}
+
+/// Handler for GET /v2/relayer-admin/orders/:order_id/match-attempts
+pub struct AdminGetOrderMatchAttemptsHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminGetOrderMatchAttemptsHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetOrderMatchAttemptsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetMatchAttemptsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let order_id = parse_order_id_from_params(&params)?;
+        let attempts =
+            self.state.get_match_attempts(Some(order_id), MAX_MATCH_AUDIT_ENTRIES).await?;
+        let attempts = attempts.into_iter().map(to_api_match_attempt).collect();
+        Ok(GetMatchAttemptsResponse { attempts })
+    }
+}
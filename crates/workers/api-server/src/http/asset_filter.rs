@@ -1,4 +1,4 @@
-//! Centralizes disabled-asset policy for the API layer
+//! Centralizes disabled and allowed asset policy for the API layer
 
 use std::collections::HashSet;
 
@@ -10,24 +10,36 @@ use crate::error::{ApiServerError, bad_request};
 /// Error returned when a request references a disabled token
 const ERR_TOKEN_DISABLED: &str = "token is not supported";
 
-/// Holds the resolved set of disabled asset addresses and exposes
-/// a small API for checking and filtering tokens.
+/// Holds the resolved sets of disabled and allowed asset addresses and
+/// exposes a small API for checking and filtering tokens.
 #[derive(Clone, Debug)]
 pub(crate) struct AssetFilter {
     /// Set of disabled asset addresses
     disabled: HashSet<Address>,
+    /// Set of allowed asset addresses, if the relayer is restricted to a set
+    allowed: Option<HashSet<Address>>,
 }
 
 impl AssetFilter {
     /// Build an `AssetFilter` by resolving ticker strings to addresses
-    pub fn new(tickers: &[String]) -> Self {
-        let disabled = tickers.iter().map(|t| Token::from_ticker(t).get_alloy_address()).collect();
-        Self { disabled }
+    ///
+    /// An empty allowlist allows all assets that are not disabled
+    pub fn new(disabled_tickers: &[String], allowed_tickers: &[String]) -> Self {
+        let disabled = resolve_tickers(disabled_tickers);
+        let allowed = (!allowed_tickers.is_empty()).then(|| resolve_tickers(allowed_tickers));
+        Self { disabled, allowed }
+    }
+
+    /// Whether `addr` is disabled, either explicitly or by its absence from
+    /// the allowlist
+    fn is_disabled(&self, addr: &Address) -> bool {
+        let not_allowed = self.allowed.as_ref().is_some_and(|allowed| !allowed.contains(addr));
+        not_allowed || self.disabled.contains(addr)
     }
 
     /// Reject if `addr` is disabled
     pub fn check_token(&self, addr: &Address) -> Result<(), ApiServerError> {
-        if self.disabled.contains(addr) {
+        if self.is_disabled(addr) {
             return Err(bad_request(ERR_TOKEN_DISABLED));
         }
         Ok(())
@@ -35,7 +47,7 @@ impl AssetFilter {
 
     /// Reject if either of two tokens is disabled
     pub fn check_pair(&self, token_a: &Address, token_b: &Address) -> Result<(), ApiServerError> {
-        if self.disabled.contains(token_a) || self.disabled.contains(token_b) {
+        if self.is_disabled(token_a) || self.is_disabled(token_b) {
             return Err(bad_request(ERR_TOKEN_DISABLED));
         }
         Ok(())
@@ -45,7 +57,55 @@ impl AssetFilter {
     pub fn enabled_base_tokens(&self) -> Vec<Token> {
         get_all_base_tokens()
             .into_iter()
-            .filter(|t| !self.disabled.contains(&t.get_alloy_address()))
+            .filter(|t| !self.is_disabled(&t.get_alloy_address()))
             .collect()
     }
 }
+
+/// Resolve ticker strings to token addresses
+fn resolve_tickers(tickers: &[String]) -> HashSet<Address> {
+    tickers.iter().map(|t| Token::from_ticker(t).get_alloy_address()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use alloy::primitives::Address;
+
+    use super::AssetFilter;
+
+    /// Tests that the allowlist restricts assets and that disabled assets
+    /// are refused even when allowed
+    #[test]
+    fn test_asset_filter() {
+        let (allowed, disabled, other) =
+            (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+
+        // Without an allowlist, only disabled assets are refused
+        let filter = AssetFilter { disabled: HashSet::from([disabled]), allowed: None };
+        assert!(filter.check_token(&allowed).is_ok());
+        assert!(filter.check_token(&other).is_ok());
+        assert!(filter.check_token(&disabled).is_err());
+
+        // With an allowlist, assets off the list are refused too
+        let filter = AssetFilter {
+            disabled: HashSet::from([disabled]),
+            allowed: Some(HashSet::from([allowed, disabled])),
+        };
+        assert!(filter.check_token(&allowed).is_ok());
+        assert!(filter.check_token(&other).is_err());
+        assert!(filter.check_token(&disabled).is_err());
+        assert!(filter.check_pair(&allowed, &allowed).is_ok());
+        assert!(filter.check_pair(&allowed, &other).is_err());
+        assert!(filter.check_pair(&disabled, &allowed).is_err());
+    }
+
+    /// Tests that an empty allowlist leaves all assets allowed
+    #[test]
+    fn test_empty_allowlist() {
+        let filter = AssetFilter::new(&[], &[]);
+        assert!(filter.allowed.is_none());
+        assert!(filter.check_token(&Address::repeat_byte(1)).is_ok());
+    }
+}
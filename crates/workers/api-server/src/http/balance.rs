@@ -31,7 +31,7 @@ use crate::{
         ApiServerError, ERR_ACCOUNT_NOT_FOUND, ERR_BALANCE_NOT_FOUND, bad_request, internal_error,
         not_found,
     },
-    http::{asset_filter::AssetFilter, helpers::append_task},
+    http::{account_versions::AccountVersions, asset_filter::AssetFilter, helpers::append_task},
     param_parsing::{parse_account_id_from_params, parse_mint_from_params, should_block_on_task},
     router::{QueryParams, TypedHandler, UrlParams},
 };
@@ -120,12 +120,19 @@ pub struct DepositBalanceHandler {
     state: State,
     /// The task driver queue
     task_queue: TaskDriverQueue,
+    /// The versions of accounts mutated through the API
+    account_versions: AccountVersions,
 }
 
 impl DepositBalanceHandler {
     /// Constructor
-    pub fn new(asset_filter: AssetFilter, state: State, task_queue: TaskDriverQueue) -> Self {
-        Self { asset_filter, state, task_queue }
+    pub fn new(
+        asset_filter: AssetFilter,
+        state: State,
+        task_queue: TaskDriverQueue,
+        account_versions: AccountVersions,
+    ) -> Self {
+        Self { asset_filter, state, task_queue, account_versions }
     }
 }
 
@@ -136,7 +143,7 @@ impl TypedHandler for DepositBalanceHandler {
 
     async fn handle_typed(
         &self,
-        _headers: HeaderMap,
+        headers: HeaderMap,
         req: Self::Request,
         params: UrlParams,
         query_params: QueryParams,
@@ -185,6 +192,7 @@ impl TypedHandler for DepositBalanceHandler {
             DepositTaskDescriptor::new(account_id, from_address, token, amount, auth, authority)
                 .into()
         };
+        self.account_versions.check_and_advance(&account_id, &headers).await?;
         let task_id = append_task(descriptor, blocking, &self.state, &self.task_queue).await?;
 
         Ok(DepositBalanceResponse { task_id, balance: updated_balance.into(), completed: true })
@@ -197,6 +205,8 @@ pub struct WithdrawBalanceHandler {
     state: State,
     /// The task driver queue
     task_queue: TaskDriverQueue,
+    /// The versions of accounts mutated through the API
+    account_versions: AccountVersions,
 }
 
 #[async_trait]
@@ -206,7 +216,7 @@ impl TypedHandler for WithdrawBalanceHandler {
 
     async fn handle_typed(
         &self,
-        _headers: HeaderMap,
+        headers: HeaderMap,
         req: Self::Request,
         params: UrlParams,
         query_params: QueryParams,
@@ -221,6 +231,7 @@ impl TypedHandler for WithdrawBalanceHandler {
         // Enqueue the withdrawal task
         let descriptor: TaskDescriptor =
             WithdrawTaskDescriptor::new(account_id, token, req.amount, req.signature).into();
+        self.account_versions.check_and_advance(&account_id, &headers).await?;
         let task_id = append_task(descriptor, blocking, &self.state, &self.task_queue).await?;
 
         Ok(WithdrawBalanceResponse { task_id, completed: true })
@@ -229,8 +240,12 @@ impl TypedHandler for WithdrawBalanceHandler {
 
 impl WithdrawBalanceHandler {
     /// Constructor
-    pub fn new(state: State, task_queue: TaskDriverQueue) -> Self {
-        Self { state, task_queue }
+    pub fn new(
+        state: State,
+        task_queue: TaskDriverQueue,
+        account_versions: AccountVersions,
+    ) -> Self {
+        Self { state, task_queue, account_versions }
     }
 
     /// Validate a withdrawal
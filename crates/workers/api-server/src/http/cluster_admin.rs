@@ -0,0 +1,442 @@
+//! Route handlers for cluster operator actions on the admin API
+//!
+//! These cover operations that would otherwise require restarting the node or
+//! editing its database directly: inspecting and expiring peers, managing the
+//! peer access lists, adjusting the heartbeat intervals, and pausing or
+//! resuming task queues
+
+use async_trait::async_trait;
+use external_api::{
+    EmptyRequestResponse,
+    http::admin::{
+        ApiAdminPeer, GetPeersAdminResponse, HeartbeatIntervalsResponse, PeerAccessResponse,
+        SetHeartbeatIntervalsRequest, SetPeerAccessRequest,
+    },
+};
+use hyper::HeaderMap;
+use job_types::network_manager::{
+    NetworkManagerControlSignal, NetworkManagerJob, NetworkManagerQueue,
+};
+use state::State;
+use types_gossip::{
+    ClusterId, HeartbeatSettings, PeerAccessAdjustment, PeerAccessList, PeerInfo, PeerLatencies,
+    WrappedPeerId,
+};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    error::{ApiServerError, bad_request, internal_error, not_found},
+    logging::Task,
+    param_parsing::{parse_account_id_from_params, parse_peer_id_from_params},
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+/// Error message emitted when an operator attempts to expire the local peer
+const ERR_EXPIRE_LOCAL_PEER: &str = "cannot expire the local peer";
+/// Error message emitted when a peer is not in the peer index
+const ERR_PEER_NOT_FOUND: &str = "peer not found";
+/// Error message emitted when an operator attempts to exclude the local peer
+const ERR_EXCLUDE_LOCAL_PEER: &str = "cannot exclude the local peer";
+/// Error message emitted when an adjustment would exclude a peer of the local
+/// cluster, breaking raft replication to it
+const ERR_EXCLUDE_CLUSTER_PEER: &str = "cannot exclude cluster peers";
+
+/// Convert a peer's info to its admin API representation
+fn to_api_admin_peer(
+    info: &PeerInfo,
+    local_cluster: &ClusterId,
+    latencies: &PeerLatencies,
+) -> ApiAdminPeer {
+    let rtt_ms = latencies.get(&info.get_peer_id()).map(|rtt| rtt.as_millis() as u64);
+    let metadata = info.metadata.as_ref();
+    ApiAdminPeer {
+        peer_id: info.get_peer_id().to_string(),
+        cluster_id: info.get_cluster_id().to_string(),
+        addr: info.get_addr().to_string(),
+        last_heartbeat: info.get_last_heartbeat(),
+        is_cluster_peer: info.get_cluster_id() == *local_cluster,
+        rtt_ms,
+        version: metadata.map(|m| m.version.clone()),
+        circuit_versions: metadata.map(|m| m.circuit_versions.clone()).unwrap_or_default(),
+        feature_bits: metadata.map(|m| m.feature_bits),
+    }
+}
+
+// -----------------
+// | Peer Handlers |
+// -----------------
+
+/// Handler for GET /v2/admin/peers
+pub struct AdminGetPeersHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// The round trip times to peers, measured by the network manager
+    peer_latencies: PeerLatencies,
+}
+
+impl AdminGetPeersHandler {
+    /// Constructor
+    pub fn new(state: State, peer_latencies: PeerLatencies) -> Self {
+        Self { state, peer_latencies }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetPeersHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetPeersAdminResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let local_cluster = self.state.get_cluster_id()?;
+        let peers = self.state.get_peer_info_map().await?;
+
+        let mut peers: Vec<ApiAdminPeer> = peers
+            .values()
+            .map(|info| to_api_admin_peer(info, &local_cluster, &self.peer_latencies))
+            .collect();
+        peers.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        Ok(GetPeersAdminResponse { peers })
+    }
+}
+
+/// Handler for POST /v2/admin/peers/:peer_id/expire
+///
+/// Removes the peer from the peer index (and the raft if it is a cluster peer)
+/// and from the network manager's DHT. A peer that is still alive may be
+/// re-added by the gossip layer once it is heard from again
+pub struct AdminExpirePeerHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// The channel on which to notify the network manager of the expiry
+    network_sender: NetworkManagerQueue,
+}
+
+impl AdminExpirePeerHandler {
+    /// Constructor
+    pub fn new(state: State, network_sender: NetworkManagerQueue) -> Self {
+        Self { state, network_sender }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminExpirePeerHandler {
+    type Request = EmptyRequestResponse;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let peer_id = parse_peer_id_from_params(&params)?;
+        if peer_id == self.state.get_peer_id()? {
+            return Err(bad_request(ERR_EXPIRE_LOCAL_PEER));
+        }
+
+        if self.state.get_peer_info(&peer_id).await?.is_none() {
+            return Err(not_found(ERR_PEER_NOT_FOUND));
+        }
+
+        self.state.remove_peer(peer_id).await?;
+        let signal = NetworkManagerControlSignal::PeerExpired { peer_id };
+        self.network_sender.send(NetworkManagerJob::internal(signal)).map_err(internal_error)?;
+
+        log_task!(Task::ExpirePeer, Outcome::Ok, subject = %peer_id, "peer expired by operator");
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+/// Parse a list of peer IDs from an admin request
+fn parse_peer_ids(ids: &[String]) -> Result<Vec<WrappedPeerId>, ApiServerError> {
+    ids.iter()
+        .map(|id| id.parse().map_err(|_| bad_request(format!("invalid peer ID: {id}"))))
+        .collect()
+}
+
+/// Convert the peer access lists to their admin API representation
+fn to_peer_access_response(access: &PeerAccessList) -> PeerAccessResponse {
+    let to_strings = |ids: Vec<WrappedPeerId>| ids.iter().map(ToString::to_string).collect();
+    PeerAccessResponse {
+        allowlist: to_strings(access.allowlist()),
+        blocklist: to_strings(access.blocklist()),
+    }
+}
+
+// ------------------------
+// | Peer Access Handlers |
+// ------------------------
+
+/// Handler for GET /v2/admin/peer-access
+pub struct AdminGetPeerAccessHandler {
+    /// The peer access lists
+    peer_access: PeerAccessList,
+}
+
+impl AdminGetPeerAccessHandler {
+    /// Constructor
+    pub fn new(peer_access: PeerAccessList) -> Self {
+        Self { peer_access }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetPeerAccessHandler {
+    type Request = EmptyRequestResponse;
+    type Response = PeerAccessResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        Ok(to_peer_access_response(&self.peer_access))
+    }
+}
+
+/// Handler for POST /v2/admin/peer-access
+///
+/// Adjustments that would exclude a peer of the local cluster are refused.
+/// Indexed peers excluded by the adjusted lists are expired as by
+/// `AdminExpirePeerHandler`. The lists are local to the node and are not
+/// persisted; a restart reverts them to the configured values
+pub struct AdminSetPeerAccessHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// The peer access lists
+    peer_access: PeerAccessList,
+    /// The channel on which to notify the network manager of expiries
+    network_sender: NetworkManagerQueue,
+}
+
+impl AdminSetPeerAccessHandler {
+    /// Constructor
+    pub fn new(
+        state: State,
+        peer_access: PeerAccessList,
+        network_sender: NetworkManagerQueue,
+    ) -> Self {
+        Self { state, peer_access, network_sender }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminSetPeerAccessHandler {
+    type Request = SetPeerAccessRequest;
+    type Response = PeerAccessResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let adjustment = PeerAccessAdjustment {
+            block: parse_peer_ids(&req.block)?,
+            unblock: parse_peer_ids(&req.unblock)?,
+            allow: parse_peer_ids(&req.allow)?,
+            disallow: parse_peer_ids(&req.disallow)?,
+        };
+
+        let local_peer_id = self.state.get_peer_id()?;
+        if adjustment.block.contains(&local_peer_id) {
+            return Err(bad_request(ERR_EXCLUDE_LOCAL_PEER));
+        }
+
+        // Refuse to cut the local node off from the rest of its raft cluster
+        let cluster_id = self.state.get_cluster_id()?;
+        let mut cluster_peers = self.state.get_cluster_peers(&cluster_id).await?;
+        cluster_peers.retain(|peer_id| *peer_id != local_peer_id);
+        if let Err(excluded) = self.peer_access.adjust(&adjustment, &cluster_peers) {
+            let excluded = excluded.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+            return Err(bad_request(format!("{ERR_EXCLUDE_CLUSTER_PEER}: {excluded}")));
+        }
+
+        // Expire the indexed peers the adjusted lists exclude
+        let excluded = self
+            .state
+            .get_all_peers_ids(false /* include_self */)
+            .await?
+            .into_iter()
+            .filter(|peer_id| !self.peer_access.is_allowed(peer_id));
+        for peer_id in excluded {
+            self.state.remove_peer(peer_id).await?;
+            let signal = NetworkManagerControlSignal::PeerExpired { peer_id };
+            self.network_sender
+                .send(NetworkManagerJob::internal(signal))
+                .map_err(internal_error)?;
+            log_task!(Task::SetPeerAccess, Outcome::Ok, subject = %peer_id, "expired peer excluded by access lists");
+        }
+
+        let resp = to_peer_access_response(&self.peer_access);
+        log_task!(
+            Task::SetPeerAccess,
+            Outcome::Ok,
+            allowlist = ?resp.allowlist,
+            blocklist = ?resp.blocklist,
+            "peer access lists adjusted"
+        );
+        Ok(resp)
+    }
+}
+
+// ----------------------
+// | Heartbeat Handlers |
+// ----------------------
+
+/// Handler for GET /v2/admin/heartbeat-intervals
+pub struct AdminGetHeartbeatIntervalsHandler {
+    /// The heartbeat intervals used by the gossip server
+    heartbeat_settings: HeartbeatSettings,
+}
+
+impl AdminGetHeartbeatIntervalsHandler {
+    /// Constructor
+    pub fn new(heartbeat_settings: HeartbeatSettings) -> Self {
+        Self { heartbeat_settings }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminGetHeartbeatIntervalsHandler {
+    type Request = EmptyRequestResponse;
+    type Response = HeartbeatIntervalsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let intervals = self.heartbeat_settings.get().into();
+        Ok(HeartbeatIntervalsResponse { intervals })
+    }
+}
+
+/// Handler for POST /v2/admin/heartbeat-intervals
+///
+/// The intervals are local to the node and are not persisted; a restart
+/// reverts them to the configured values
+pub struct AdminSetHeartbeatIntervalsHandler {
+    /// The heartbeat intervals used by the gossip server
+    heartbeat_settings: HeartbeatSettings,
+}
+
+impl AdminSetHeartbeatIntervalsHandler {
+    /// Constructor
+    pub fn new(heartbeat_settings: HeartbeatSettings) -> Self {
+        Self { heartbeat_settings }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminSetHeartbeatIntervalsHandler {
+    type Request = SetHeartbeatIntervalsRequest;
+    type Response = HeartbeatIntervalsResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let intervals = req.apply(self.heartbeat_settings.get());
+        self.heartbeat_settings.set(intervals).map_err(bad_request)?;
+
+        log_task!(
+            Task::SetHeartbeatIntervals,
+            Outcome::Ok,
+            intervals = ?intervals,
+            "heartbeat intervals adjusted"
+        );
+        Ok(HeartbeatIntervalsResponse { intervals: intervals.into() })
+    }
+}
+
+// -----------------------
+// | Task Queue Handlers |
+// -----------------------
+
+/// Handler for POST /v2/relayer-admin/account/:account_id/tasks/pause
+///
+/// Running tasks are left to finish; queued and newly enqueued tasks are held
+/// until the queue is resumed
+pub struct AdminPauseTaskQueueHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminPauseTaskQueueHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminPauseTaskQueueHandler {
+    type Request = EmptyRequestResponse;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let waiter = self.state.pause_task_queue(&account_id).await?;
+        waiter.await?;
+
+        log_task!(Task::PauseTaskQueue, Outcome::Ok, account_id = %account_id, "task queue paused");
+        Ok(EmptyRequestResponse {})
+    }
+}
+
+/// Handler for POST /v2/relayer-admin/account/:account_id/tasks/resume
+pub struct AdminResumeTaskQueueHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl AdminResumeTaskQueueHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for AdminResumeTaskQueueHandler {
+    type Request = EmptyRequestResponse;
+    type Response = EmptyRequestResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let waiter = self.state.resume_task_queue(&account_id).await?;
+        waiter.await?;
+
+        log_task!(Task::PauseTaskQueue, Outcome::Ok, account_id = %account_id, "task queue resumed");
+        Ok(EmptyRequestResponse {})
+    }
+}
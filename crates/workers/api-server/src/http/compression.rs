@@ -0,0 +1,257 @@
+//! Negotiated compression of HTTP response bodies
+//!
+//! Large responses (e.g. order book pages, task history) are compressed with
+//! gzip or deflate when the client accepts either encoding, the response's
+//! content type is configured as compressible, and its body meets the
+//! configured minimum size
+
+use std::io::Write;
+
+use flate2::{
+    Compression,
+    write::{GzEncoder, ZlibEncoder},
+};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Response,
+    body::Bytes as BytesBody,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, HeaderValue, VARY},
+};
+
+use crate::router::ResponseBody;
+
+/// The `Accept-Encoding` header value under which compressed responses vary
+const ACCEPT_ENCODING_VARY: &str = "accept-encoding";
+
+/// A content encoding the server may compress responses with
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ContentEncoding {
+    /// The gzip format
+    Gzip,
+    /// The zlib format, named `deflate` in HTTP
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// The name of the encoding in HTTP headers
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+        }
+    }
+
+    /// Negotiate an encoding from an `Accept-Encoding` header value
+    ///
+    /// Gzip is preferred over deflate when the client accepts both. Encodings
+    /// given a quality of zero are treated as refused
+    fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let mut accepted = Vec::new();
+        for entry in accept_encoding.split(',') {
+            let mut parts = entry.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let refused = parts
+                .filter_map(|p| p.strip_prefix("q="))
+                .any(|q| q.parse::<f32>().is_ok_and(|q| q == 0.));
+            if refused {
+                continue;
+            }
+
+            match name.as_str() {
+                "gzip" | "*" => accepted.push(Self::Gzip),
+                "deflate" => accepted.push(Self::Deflate),
+                _ => {},
+            }
+        }
+
+        [Self::Gzip, Self::Deflate].into_iter().find(|e| accepted.contains(e))
+    }
+
+    /// Compress a body with the encoding
+    fn encode(&self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            },
+            Self::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            },
+        }
+    }
+}
+
+/// Compresses HTTP responses according to the client's accepted encodings
+#[derive(Clone, Debug)]
+pub(crate) struct ResponseCompressor {
+    /// The minimum size in bytes of a body for it to be compressed
+    min_size: usize,
+    /// The content types that may be compressed
+    content_types: Vec<String>,
+}
+
+impl ResponseCompressor {
+    /// Constructor
+    pub fn new(min_size: usize, content_types: &[String]) -> Self {
+        let content_types = content_types.iter().map(|t| t.to_ascii_lowercase()).collect();
+        Self { min_size, content_types }
+    }
+
+    /// Compress a response if the client accepts a supported encoding and the
+    /// response is eligible, otherwise return it unchanged
+    pub async fn compress(
+        &self,
+        accept_encoding: Option<&HeaderValue>,
+        mut resp: Response<ResponseBody>,
+    ) -> Response<ResponseBody> {
+        if !self.is_compressible(&resp) {
+            return resp;
+        }
+
+        // The response's encoding depends on the request's accepted encodings
+        resp.headers_mut().append(VARY, HeaderValue::from_static(ACCEPT_ENCODING_VARY));
+        let encoding = match accept_encoding
+            .and_then(|h| h.to_str().ok())
+            .and_then(ContentEncoding::negotiate)
+        {
+            Some(encoding) => encoding,
+            None => return resp,
+        };
+
+        let (mut parts, body) = resp.into_parts();
+        let Ok(collected) = body.collect().await;
+        let body = collected.to_bytes();
+        if body.len() < self.min_size {
+            return Response::from_parts(parts, Full::new(body));
+        }
+
+        // Fall back to the uncompressed body if encoding fails
+        let compressed = match encoding.encode(&body) {
+            Ok(compressed) => compressed,
+            Err(_) => return Response::from_parts(parts, Full::new(body)),
+        };
+
+        parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+        parts.headers.remove(CONTENT_LENGTH);
+        Response::from_parts(parts, Full::new(BytesBody::from(compressed)))
+    }
+
+    /// Whether a response's content type is compressible and it has not
+    /// already been encoded
+    fn is_compressible(&self, resp: &Response<ResponseBody>) -> bool {
+        if resp.headers().contains_key(CONTENT_ENCODING) {
+            return false;
+        }
+
+        // Compare the media type, ignoring parameters such as the charset
+        let content_type = match resp.headers().get(CONTENT_TYPE).and_then(|h| h.to_str().ok()) {
+            Some(content_type) => content_type,
+            None => return false,
+        };
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        self.content_types.iter().any(|t| t.eq_ignore_ascii_case(media_type))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use flate2::read::{GzDecoder, ZlibDecoder};
+    use http_body_util::{BodyExt, Full};
+    use hyper::{
+        Response,
+        body::Bytes as BytesBody,
+        header::{CONTENT_ENCODING, CONTENT_TYPE, HeaderValue, VARY},
+    };
+
+    use super::{ContentEncoding, ResponseCompressor};
+    use crate::router::ResponseBody;
+
+    /// The minimum body size used in tests
+    const MIN_SIZE: usize = 64;
+
+    /// Build a compressor accepting JSON responses
+    fn json_compressor() -> ResponseCompressor {
+        ResponseCompressor::new(MIN_SIZE, &["application/json".to_string()])
+    }
+
+    /// Build a response with the given content type and body
+    fn mock_response(content_type: &str, body: Vec<u8>) -> Response<ResponseBody> {
+        Response::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(Full::new(BytesBody::from(body)))
+            .unwrap()
+    }
+
+    /// Collect a response's body
+    async fn body_bytes(resp: Response<ResponseBody>) -> Vec<u8> {
+        let Ok(collected) = resp.into_body().collect().await;
+        collected.to_bytes().to_vec()
+    }
+
+    /// Tests negotiating an encoding from an `Accept-Encoding` header
+    #[test]
+    fn test_negotiate_encoding() {
+        assert_eq!(ContentEncoding::negotiate("gzip, deflate, br"), Some(ContentEncoding::Gzip));
+        assert_eq!(ContentEncoding::negotiate("deflate"), Some(ContentEncoding::Deflate));
+        assert_eq!(ContentEncoding::negotiate("gzip;q=0, deflate"), Some(ContentEncoding::Deflate));
+        assert_eq!(ContentEncoding::negotiate("*"), Some(ContentEncoding::Gzip));
+        assert_eq!(ContentEncoding::negotiate("br, identity"), None);
+    }
+
+    /// Tests that large eligible responses are compressed in the negotiated
+    /// encoding
+    #[tokio::test]
+    async fn test_compress_large_response() {
+        let compressor = json_compressor();
+        let body = vec![b'a'; MIN_SIZE * 4];
+
+        let accept = HeaderValue::from_static("gzip");
+        let resp = mock_response("application/json", body.clone());
+        let resp = compressor.compress(Some(&accept), resp).await;
+        assert_eq!(resp.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(resp.headers().get(VARY).unwrap(), "accept-encoding");
+        let mut decoded = Vec::new();
+        GzDecoder::new(body_bytes(resp).await.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+
+        let accept = HeaderValue::from_static("deflate");
+        let resp = mock_response("application/json; charset=utf-8", body.clone());
+        let resp = compressor.compress(Some(&accept), resp).await;
+        assert_eq!(resp.headers().get(CONTENT_ENCODING).unwrap(), "deflate");
+        let mut decoded = Vec::new();
+        ZlibDecoder::new(body_bytes(resp).await.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+    }
+
+    /// Tests that ineligible responses are returned unchanged
+    #[tokio::test]
+    async fn test_skip_ineligible_response() {
+        let compressor = json_compressor();
+        let accept = HeaderValue::from_static("gzip");
+        let large_body = vec![b'a'; MIN_SIZE * 4];
+
+        // A body below the minimum size
+        let small_body = vec![b'a'; MIN_SIZE - 1];
+        let resp = mock_response("application/json", small_body.clone());
+        let resp = compressor.compress(Some(&accept), resp).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(body_bytes(resp).await, small_body);
+
+        // A content type that is not configured as compressible
+        let resp = mock_response("text/plain", large_body.clone());
+        let resp = compressor.compress(Some(&accept), resp).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(body_bytes(resp).await, large_body);
+
+        // A client that does not accept a supported encoding
+        let resp = mock_response("application/json", large_body.clone());
+        let resp = compressor.compress(None, resp).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(body_bytes(resp).await, large_body);
+    }
+}
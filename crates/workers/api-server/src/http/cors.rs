@@ -0,0 +1,170 @@
+//! Cross-origin resource sharing (CORS) policy for HTTP responses
+//!
+//! Browser-based trading UIs call the API from a different origin than the
+//! relayer's own. The policy decides which origins may read responses and
+//! whether credentials are allowed. By default every origin is allowed, as a
+//! locally-run relayer is reached from a UI served on another port
+
+use hyper::{
+    HeaderMap, Method,
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS, HeaderValue, ORIGIN, VARY,
+    },
+};
+
+/// The configured origin that allows every origin
+const ANY_ORIGIN: &str = "*";
+/// The `Origin` header value under which responses to listed origins vary
+const ORIGIN_VARY: &str = "origin";
+
+/// The origins allowed to read responses
+#[derive(Clone, Debug)]
+enum AllowedOrigins {
+    /// Every origin is allowed
+    Any,
+    /// Only the listed origins are allowed, lowercased
+    List(Vec<String>),
+}
+
+/// Computes the CORS headers of responses to a request
+#[derive(Clone, Debug)]
+pub(crate) struct CorsPolicy {
+    /// The origins allowed to read responses
+    allowed_origins: AllowedOrigins,
+    /// Whether allowed origins may send credentials
+    allow_credentials: bool,
+}
+
+impl CorsPolicy {
+    /// Constructor
+    ///
+    /// An origin of `*` allows every origin
+    pub fn new(allowed_origins: &[String], allow_credentials: bool) -> Self {
+        let allowed_origins = if allowed_origins.iter().any(|o| o == ANY_ORIGIN) {
+            AllowedOrigins::Any
+        } else {
+            AllowedOrigins::List(allowed_origins.iter().map(|o| o.to_ascii_lowercase()).collect())
+        };
+
+        Self { allowed_origins, allow_credentials }
+    }
+
+    /// Compute the CORS headers of the response to a request
+    ///
+    /// Returns no headers if the request's origin is not allowed
+    pub fn response_headers(&self, method: &Method, req_headers: &HeaderMap) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let origin = req_headers.get(ORIGIN);
+        let Some(allow_origin) = self.allow_origin(origin) else {
+            return headers;
+        };
+
+        // A response naming its origin varies with the request's origin
+        if allow_origin != ANY_ORIGIN {
+            headers.insert(VARY, HeaderValue::from_static(ORIGIN_VARY));
+        }
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.allow_credentials {
+            headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+
+        // Preflights are answered with the headers the client asked to send
+        if method == Method::OPTIONS {
+            let allow_headers = req_headers
+                .get(ACCESS_CONTROL_REQUEST_HEADERS)
+                .cloned()
+                .unwrap_or_else(|| HeaderValue::from_static("*"));
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+
+        headers
+    }
+
+    /// Get the `Access-Control-Allow-Origin` value for a request's origin, if
+    /// the origin is allowed
+    ///
+    /// Credentialed responses may not use the `*` wildcard, so the request's
+    /// origin is echoed instead
+    fn allow_origin(&self, origin: Option<&HeaderValue>) -> Option<HeaderValue> {
+        match &self.allowed_origins {
+            AllowedOrigins::Any if !self.allow_credentials => {
+                Some(HeaderValue::from_static(ANY_ORIGIN))
+            },
+            AllowedOrigins::Any => origin.cloned(),
+            AllowedOrigins::List(allowed) => {
+                let origin = origin?;
+                let origin_str = origin.to_str().ok()?;
+                allowed.iter().any(|o| o.eq_ignore_ascii_case(origin_str)).then(|| origin.clone())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyper::{
+        HeaderMap, Method,
+        header::{
+            ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+            ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS, HeaderValue, ORIGIN, VARY,
+        },
+    };
+
+    use super::CorsPolicy;
+
+    /// The origin of the mock UI
+    const UI_ORIGIN: &str = "https://trade.example.com";
+
+    /// Build request headers with the given origin
+    fn request_headers(origin: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ORIGIN, HeaderValue::from_str(origin).unwrap());
+        headers
+    }
+
+    /// Tests that the default policy allows every origin
+    #[test]
+    fn test_any_origin() {
+        let policy = CorsPolicy::new(&["*".to_string()], false /* allow_credentials */);
+        let headers = policy.response_headers(&Method::GET, &request_headers(UI_ORIGIN));
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "*");
+        assert!(headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+        assert!(headers.get(VARY).is_none());
+
+        // Credentialed responses echo the origin rather than the wildcard
+        let policy = CorsPolicy::new(&["*".to_string()], true /* allow_credentials */);
+        let headers = policy.response_headers(&Method::GET, &request_headers(UI_ORIGIN));
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), UI_ORIGIN);
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+    }
+
+    /// Tests that only listed origins are allowed
+    #[test]
+    fn test_listed_origins() {
+        let policy =
+            CorsPolicy::new(&[UI_ORIGIN.to_uppercase()], false /* allow_credentials */);
+        let headers = policy.response_headers(&Method::GET, &request_headers(UI_ORIGIN));
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), UI_ORIGIN);
+        assert_eq!(headers.get(VARY).unwrap(), "origin");
+
+        let headers = policy.response_headers(&Method::GET, &request_headers("https://evil.com"));
+        assert!(headers.is_empty());
+        let headers = policy.response_headers(&Method::GET, &HeaderMap::new());
+        assert!(headers.is_empty());
+    }
+
+    /// Tests that preflights allow the headers the client asked to send
+    #[test]
+    fn test_preflight_headers() {
+        let policy = CorsPolicy::new(&[UI_ORIGIN.to_string()], true /* allow_credentials */);
+        let mut req_headers = request_headers(UI_ORIGIN);
+        req_headers
+            .insert(ACCESS_CONTROL_REQUEST_HEADERS, HeaderValue::from_static("x-renegade-auth"));
+
+        let headers = policy.response_headers(&Method::OPTIONS, &req_headers);
+        assert_eq!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).unwrap(), "x-renegade-auth");
+        let headers = policy.response_headers(&Method::GET, &req_headers);
+        assert!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).is_none());
+    }
+}
@@ -0,0 +1,457 @@
+//! Devnet seeding endpoints
+//!
+//! Integration environments need funded accounts with live orders before they
+//! can exercise matching. These handlers write balances and orders straight
+//! into the relayer's state, skipping the on-chain deposit and the owner's
+//! signature, so an environment can be seeded programmatically. Fabricated
+//! orders still go through the raft applicator and are proven by the proof
+//! manager (a mock proof manager in most devnet deployments) like any other
+//! order.
+//!
+//! The routes are compiled only with the `devnet` feature and require admin
+//! authentication
+
+use alloy::primitives::{Bytes, U256};
+use async_trait::async_trait;
+use circuit_types::{
+    Amount, schnorr::SchnorrPublicKey, validate_amount_bitlength, validate_price_bitlength,
+};
+use circuits_core::zk_circuits::validity_proofs::intent_only_first_fill::{
+    IntentOnlyFirstFillValidityStatement, IntentOnlyFirstFillValidityWitness,
+};
+use constants::{GLOBAL_MATCHING_POOL, Scalar};
+use darkpool_types::{
+    balance::DarkpoolBalance,
+    intent::{DarkpoolStateIntent, Intent},
+    state_wrapper::StateWrapper,
+};
+use external_api::http::devnet::{
+    DevnetFabricateOrderRequest, DevnetFabricateOrderResponse, DevnetFaucetRequest,
+    DevnetFaucetResponse,
+};
+use external_api::types::ApiIntent;
+use hyper::HeaderMap;
+use job_types::proof_manager::{ProofJob, ProofManagerJob, ProofManagerQueue};
+use renegade_solidity_abi::v2::IDarkpoolV2::SignatureWithNonce;
+use state::State;
+use tokio::sync::oneshot;
+use types_account::{
+    OrderId,
+    balance::Balance,
+    order::{Order, OrderMetadata, PrivacyRing},
+    order_auth::OrderAuth,
+};
+use types_core::AccountId;
+use types_proofs::{IntentOnlyFirstFillValidityBundle, ValidityProofBundle};
+
+use crate::{
+    error::{ApiServerError, bad_request, internal_error, not_found},
+    http::account::account_not_found,
+    param_parsing::{parse_account_id_from_params, parse_mint_from_params},
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+/// The length of the mock ECDSA signature attached to fabricated orders
+const MOCK_SIGNATURE_LEN: usize = 65;
+
+/// Error message emitted when a matching pool does not exist
+const ERR_MATCHING_POOL_NOT_FOUND: &str = "matching pool does not exist";
+/// Error message emitted when the proof manager does not respond
+const ERR_PROOF_MANAGER_UNAVAILABLE: &str = "proof manager unavailable";
+/// Error message emitted when a faucet or order amount is zero
+const ERR_ZERO_AMOUNT: &str = "amount must be positive";
+/// Error message emitted when a faucet or order amount exceeds the circuits'
+/// amount bitlength
+const ERR_AMOUNT_TOO_LARGE: &str = "amount exceeds the maximum amount";
+/// Error message emitted when an order's input and output tokens are the same
+const ERR_SAME_TOKEN: &str = "order input and output tokens must differ";
+/// Error message emitted when an order's minimum price exceeds the circuits'
+/// price bitlength
+const ERR_PRICE_TOO_LARGE: &str = "order minimum price exceeds the maximum price";
+/// Error message emitted when a balance is missing after being credited
+const ERR_BALANCE_NOT_FOUND: &str = "balance not found after faucet";
+
+// ------------------
+// | Faucet Handler |
+// ------------------
+
+/// Handler for POST /v2/devnet/account/:account_id/balances/:mint/faucet
+///
+/// Credits the account's EOA balance of the mint, creating the balance if the
+/// account holds none. The credit is applied by the state applicator, so
+/// concurrent faucet requests against one balance are all applied
+pub struct DevnetFaucetHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl DevnetFaucetHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for DevnetFaucetHandler {
+    type Request = DevnetFaucetRequest;
+    type Response = DevnetFaucetResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let mint = parse_mint_from_params(&params)?;
+        check_amount(req.amount)?;
+        if !self.state.contains_account(&account_id).await? {
+            return Err(account_not_found());
+        }
+
+        // The credit becomes the balance if the account holds none of the mint;
+        // EOA balances carry no share or recovery streams
+        let relayer_fee_recipient = self.state.get_relayer_fee_addr()?;
+        let inner = DarkpoolBalance::new(
+            mint,
+            req.owner,
+            relayer_fee_recipient,
+            SchnorrPublicKey::default(),
+        );
+        let mut credit = Balance::new_eoa(StateWrapper::new(inner, Scalar::zero(), Scalar::zero()));
+        *credit.amount_mut() = req.amount;
+
+        let waiter = self.state.credit_account_balance(account_id, credit).await?;
+        match waiter.await {
+            Err(e) if e.is_rejection() => return Err(bad_request(e)),
+            res => res?,
+        };
+
+        let balance = self
+            .state
+            .get_account_eoa_balance(&account_id, &mint)
+            .await?
+            .ok_or_else(|| internal_error(ERR_BALANCE_NOT_FOUND))?;
+        Ok(DevnetFaucetResponse { balance: balance.into() })
+    }
+}
+
+// --------------------------
+// | Fabricate Order Handler |
+// --------------------------
+
+/// Handler for POST /v2/devnet/account/:account_id/orders
+///
+/// Creates a ring 1 order with a mock owner signature and proves its first fill
+/// validity, leaving the order ready to match
+pub struct DevnetFabricateOrderHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// The proof manager's job queue
+    proof_queue: ProofManagerQueue,
+}
+
+impl DevnetFabricateOrderHandler {
+    /// Constructor
+    pub fn new(state: State, proof_queue: ProofManagerQueue) -> Self {
+        Self { state, proof_queue }
+    }
+
+    /// Get the matching pool for the order, defaulting to the account's
+    async fn matching_pool(
+        &self,
+        account_id: AccountId,
+        requested: Option<String>,
+    ) -> Result<String, ApiServerError> {
+        if let Some(pool) = requested {
+            if !self.state.matching_pool_exists(pool.clone()).await? {
+                return Err(not_found(ERR_MATCHING_POOL_NOT_FOUND));
+            }
+            return Ok(pool);
+        }
+
+        let account = self.state.get_account(&account_id).await?.ok_or_else(account_not_found)?;
+        Ok(account.default_matching_pool.unwrap_or_else(|| GLOBAL_MATCHING_POOL.to_string()))
+    }
+
+    /// Wrap the intent in the account's next share and recovery streams
+    async fn create_state_intent(
+        &self,
+        account_id: AccountId,
+        intent: Intent,
+    ) -> Result<DarkpoolStateIntent, ApiServerError> {
+        let mut keychain =
+            self.state.get_account_keychain(&account_id).await?.ok_or_else(account_not_found)?;
+        let share_stream = keychain.sample_share_stream();
+        let recovery_stream = keychain.sample_recovery_id_stream();
+
+        // Persist the keychain, as its streams were advanced
+        let waiter = self.state.update_account_keychain(account_id, keychain).await?;
+        waiter.await?;
+
+        Ok(DarkpoolStateIntent::new(intent, share_stream.seed, recovery_stream.seed))
+    }
+
+    /// Prove the order's first fill validity and store the proof
+    async fn prove_first_fill(&self, order: &Order) -> Result<(), ApiServerError> {
+        let (witness, statement) = first_fill_witness_statement(order);
+        let (response_sender, response_receiver) = oneshot::channel();
+        let job = ProofJob::IntentOnlyFirstFillValidity { witness: witness.clone(), statement };
+        self.proof_queue
+            .send(ProofManagerJob { type_: job, response_channel: response_sender })
+            .map_err(|_| internal_error(ERR_PROOF_MANAGER_UNAVAILABLE))?;
+
+        let bundle: IntentOnlyFirstFillValidityBundle = response_receiver
+            .await
+            .map_err(|_| internal_error(ERR_PROOF_MANAGER_UNAVAILABLE))?
+            .into();
+        let validity_bundle = ValidityProofBundle::IntentOnlyFirstFill { bundle, witness };
+        let waiter = self.state.add_intent_validity_proof(order.id, validity_bundle).await?;
+        waiter.await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TypedHandler for DevnetFabricateOrderHandler {
+    type Request = DevnetFabricateOrderRequest;
+    type Response = DevnetFabricateOrderResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        validate_intent(&req.intent)?;
+        if !self.state.contains_account(&account_id).await? {
+            return Err(account_not_found());
+        }
+        let matching_pool = self.matching_pool(account_id, req.matching_pool).await?;
+
+        // Create the order through the applicator, as the create order task does
+        let state_intent = self.create_state_intent(account_id, req.intent.into()).await?;
+        let order = Order::new_with_ring(
+            OrderId::new_v4(),
+            state_intent,
+            OrderMetadata::default(),
+            PrivacyRing::Ring1,
+        );
+        let auth = OrderAuth::NativelySettledPrivateOrder { intent_signature: mock_signature() };
+        let waiter =
+            self.state.add_order_to_account(account_id, order.clone(), auth, matching_pool).await?;
+        waiter.await?;
+
+        self.prove_first_fill(&order).await?;
+        Ok(DevnetFabricateOrderResponse { order: order.into() })
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Check that an amount is positive and fits in the circuits' amount type
+fn check_amount(amount: Amount) -> Result<(), ApiServerError> {
+    if amount == 0 {
+        return Err(bad_request(ERR_ZERO_AMOUNT));
+    }
+    if !validate_amount_bitlength(amount) {
+        return Err(bad_request(ERR_AMOUNT_TOO_LARGE));
+    }
+
+    Ok(())
+}
+
+/// Validate a fabricated order's intent
+///
+/// The order skips the owner's signature but not its validity proof, so an
+/// intent the circuits cannot prove is rejected here rather than by the prover
+fn validate_intent(intent: &ApiIntent) -> Result<(), ApiServerError> {
+    check_amount(intent.amount_in)?;
+    if intent.in_token == intent.out_token {
+        return Err(bad_request(ERR_SAME_TOKEN));
+    }
+    if !validate_price_bitlength(intent.min_price) {
+        return Err(bad_request(ERR_PRICE_TOO_LARGE));
+    }
+
+    Ok(())
+}
+
+/// A mock owner signature over an intent
+fn mock_signature() -> SignatureWithNonce {
+    SignatureWithNonce { nonce: U256::ZERO, signature: Bytes::from(vec![0u8; MOCK_SIGNATURE_LEN]) }
+}
+
+/// Build the witness and statement proving an order's first fill validity
+fn first_fill_witness_statement(
+    order: &Order,
+) -> (IntentOnlyFirstFillValidityWitness, IntentOnlyFirstFillValidityStatement) {
+    let intent = &order.intent;
+    let mut intent_clone = intent.clone();
+    let recovery_id = intent_clone.compute_recovery_id();
+    let intent_private_commitment = intent_clone.compute_private_commitment();
+
+    let witness = IntentOnlyFirstFillValidityWitness {
+        intent: intent.inner.clone(),
+        initial_intent_share_stream: intent.share_stream.clone(),
+        initial_intent_recovery_stream: intent.recovery_stream.clone(),
+        private_shares: intent.private_shares(),
+    };
+    let statement = IntentOnlyFirstFillValidityStatement {
+        owner: intent.inner.owner,
+        intent_private_commitment,
+        recovery_id,
+        intent_public_share: intent.public_share(),
+    };
+
+    (witness, statement)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::primitives::Address;
+    use circuit_types::fixed_point::FixedPoint;
+    use external_api::{
+        http::devnet::{DevnetFabricateOrderRequest, DevnetFaucetRequest},
+        types::ApiIntent,
+    };
+    use hyper::{HeaderMap, StatusCode};
+    use job_types::proof_manager::new_proof_manager_queue;
+    use state::{State, test_helpers::mock_state};
+    use types_account::account::mocks::mock_empty_account;
+    use types_core::AccountId;
+
+    use crate::{
+        error::ApiServerError,
+        router::{QueryParams, TypedHandler, UrlParams},
+    };
+
+    use super::{DevnetFabricateOrderHandler, DevnetFaucetHandler, validate_intent};
+
+    /// The mint credited in the tests
+    const MINT: &str = "0x0000000000000000000000000000000000000001";
+
+    /// Create a mock state holding a single account
+    async fn state_with_account() -> (State, AccountId) {
+        let state = mock_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+        (state, account.id)
+    }
+
+    /// Build the URL params addressing an account's balance of the test mint
+    fn balance_params(account_id: AccountId) -> UrlParams {
+        let mut params = UrlParams::new();
+        params.insert("account_id".to_string(), account_id.to_string());
+        params.insert("mint".to_string(), MINT.to_string());
+        params
+    }
+
+    /// Send a faucet request for the given amount
+    async fn faucet(
+        handler: &DevnetFaucetHandler,
+        account_id: AccountId,
+        amount: u128,
+    ) -> Result<u128, ApiServerError> {
+        let req = DevnetFaucetRequest { owner: Address::ZERO, amount };
+        let params = balance_params(account_id);
+        let resp = handler.handle_typed(HeaderMap::new(), req, params, QueryParams::new()).await?;
+        Ok(resp.balance.amount)
+    }
+
+    /// A valid intent between two distinct tokens
+    fn mock_intent() -> ApiIntent {
+        ApiIntent {
+            in_token: Address::from([1u8; 20]),
+            out_token: Address::from([2u8; 20]),
+            owner: Address::ZERO,
+            min_price: FixedPoint::from_f64_round_down(1.),
+            amount_in: 100,
+        }
+    }
+
+    /// Tests that faucet requests create and then credit a balance
+    #[tokio::test]
+    async fn test_faucet() {
+        let (state, account_id) = state_with_account().await;
+        let handler = DevnetFaucetHandler::new(state);
+
+        assert_eq!(faucet(&handler, account_id, 100).await.unwrap(), 100);
+        assert_eq!(faucet(&handler, account_id, 50).await.unwrap(), 150);
+
+        // Zero amounts and unknown accounts are rejected
+        let err = faucet(&handler, account_id, 0).await.unwrap_err();
+        assert!(matches!(err, ApiServerError::HttpStatusCode(StatusCode::BAD_REQUEST, _)));
+        let err = faucet(&handler, AccountId::new_v4(), 100).await.unwrap_err();
+        assert!(matches!(err, ApiServerError::HttpStatusCode(StatusCode::NOT_FOUND, _)));
+    }
+
+    /// Tests that concurrent faucet requests against one balance are all
+    /// applied
+    #[tokio::test]
+    async fn test_concurrent_faucet() {
+        const N_REQUESTS: u128 = 10;
+        let (state, account_id) = state_with_account().await;
+        let handler = DevnetFaucetHandler::new(state.clone());
+
+        let reqs = (0..N_REQUESTS).map(|_| faucet(&handler, account_id, 10));
+        for res in futures::future::join_all(reqs).await {
+            res.unwrap();
+        }
+
+        let mint: Address = MINT.parse().unwrap();
+        let balance = state.get_account_eoa_balance(&account_id, &mint).await.unwrap().unwrap();
+        assert_eq!(balance.amount(), N_REQUESTS * 10);
+    }
+
+    /// Tests validating a fabricated order's intent
+    #[test]
+    fn test_validate_intent() {
+        validate_intent(&mock_intent()).unwrap();
+
+        let zero = ApiIntent { amount_in: 0, ..mock_intent() };
+        assert!(validate_intent(&zero).is_err());
+
+        let too_large = ApiIntent { amount_in: u128::MAX, ..mock_intent() };
+        assert!(validate_intent(&too_large).is_err());
+
+        let intent = mock_intent();
+        let same_token = ApiIntent { out_token: intent.in_token, ..intent };
+        assert!(validate_intent(&same_token).is_err());
+    }
+
+    /// Tests that invalid fabricated orders are rejected before they are
+    /// created or proven
+    #[tokio::test]
+    async fn test_fabricate_order_rejected() {
+        let (state, account_id) = state_with_account().await;
+        let (proof_queue, _proof_recv) = new_proof_manager_queue();
+        let handler = DevnetFabricateOrderHandler::new(state.clone(), proof_queue);
+
+        let mut params = UrlParams::new();
+        params.insert("account_id".to_string(), account_id.to_string());
+        let fabricate = |intent: ApiIntent, params: UrlParams| {
+            let req = DevnetFabricateOrderRequest { intent, matching_pool: None };
+            handler.handle_typed(HeaderMap::new(), req, params, QueryParams::new())
+        };
+
+        let invalid = ApiIntent { amount_in: 0, ..mock_intent() };
+        let err = fabricate(invalid, params.clone()).await.unwrap_err();
+        assert!(matches!(err, ApiServerError::HttpStatusCode(StatusCode::BAD_REQUEST, _)));
+
+        let mut unknown = UrlParams::new();
+        unknown.insert("account_id".to_string(), AccountId::new_v4().to_string());
+        let err = fabricate(mock_intent(), unknown).await.unwrap_err();
+        assert!(matches!(err, ApiServerError::HttpStatusCode(StatusCode::NOT_FOUND, _)));
+
+        // Neither request created an order
+        let account = state.get_account(&account_id).await.unwrap().unwrap();
+        assert!(account.orders.is_empty());
+    }
+}
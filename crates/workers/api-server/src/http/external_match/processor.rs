@@ -24,15 +24,16 @@ use job_types::matching_engine::{
 };
 use price_state::PriceStreamStates;
 use renegade_solidity_abi::v2::IDarkpoolV2::{self, SettlementBundle};
-use state::State;
+use state::{State, feature_flags::FeatureFlagView};
 use system_bus::{SystemBus, SystemBusMessage};
 use types_account::{order::Order, pair::Pair};
-use types_core::{HmacKey, TimestampedPrice, TimestampedPriceFp};
+use types_core::{FeatureFlag, HmacKey, TimestampedPriceFp};
 use util::{get_current_time_millis, on_chain::get_protocol_fee};
 
 use crate::{
-    error::{ApiServerError, internal_error, no_content, unauthorized},
+    error::{ApiServerError, internal_error, no_content, service_unavailable, unauthorized},
     http::asset_filter::AssetFilter,
+    worker::ApiServerConfig,
 };
 
 // -------------
@@ -46,6 +47,8 @@ const QUOTE_DEADLINE: Duration = Duration::from_secs(10);
 
 /// Error message for no external match found
 const ERR_NO_EXTERNAL_MATCH_FOUND: &str = "no external match found";
+/// Error message emitted when external matches are disabled
+const ERR_EXTERNAL_MATCHES_DISABLED: &str = "external matches are disabled";
 
 // -------------
 // | Processor |
@@ -58,6 +61,8 @@ pub struct ExternalMatchProcessor {
     admin_key: HmacKey,
     /// Asset filter for checking disabled tokens
     asset_filter: AssetFilter,
+    /// The feature flags, checked before quoting or assembling a match
+    feature_flags: FeatureFlagView,
     /// The darkpool client
     darkpool_client: DarkpoolClient,
     /// The system bus
@@ -73,25 +78,32 @@ pub struct ExternalMatchProcessor {
 impl ExternalMatchProcessor {
     /// Constructor
     pub fn new(
+        config: &ApiServerConfig,
         admin_key: HmacKey,
         asset_filter: AssetFilter,
-        darkpool_client: DarkpoolClient,
-        bus: SystemBus,
-        matching_engine_worker_queue: MatchingEngineWorkerQueue,
-        price_streams: PriceStreamStates,
-        state: State,
+        feature_flags: FeatureFlagView,
     ) -> Self {
         Self {
             admin_key,
             asset_filter,
-            darkpool_client,
-            bus,
-            matching_engine_worker_queue,
-            price_streams,
-            state,
+            feature_flags,
+            darkpool_client: config.darkpool_client.clone(),
+            bus: config.system_bus.clone(),
+            matching_engine_worker_queue: config.matching_engine_worker_queue.clone(),
+            price_streams: config.price_streams.clone(),
+            state: config.state.clone(),
         }
     }
 
+    /// Validate that external matches are enabled
+    fn validate_external_matches_enabled(&self) -> Result<(), ApiServerError> {
+        if !self.feature_flags.is_enabled(FeatureFlag::ExternalMatches) {
+            return Err(service_unavailable(ERR_EXTERNAL_MATCHES_DISABLED));
+        }
+
+        Ok(())
+    }
+
     /// Validate that neither token in the pair is disabled
     fn validate_pair_not_disabled(&self, pair: &Pair) -> Result<(), ApiServerError> {
         self.asset_filter.check_pair(&pair.in_token, &pair.out_token)
@@ -104,6 +116,7 @@ impl ExternalMatchProcessor {
         &self,
         req: ExternalQuoteRequest,
     ) -> Result<ApiSignedQuote, ApiServerError> {
+        self.validate_external_matches_enabled()?;
         let quote = self.fetch_quote(req).await?;
         let deadline = quote.timestamp + QUOTE_DEADLINE.as_millis() as u64;
 
@@ -156,7 +169,6 @@ impl ExternalMatchProcessor {
         let send = ApiExternalAssetTransfer::new(obligation.input_token, obligation.amount_in);
         let receive = ApiExternalAssetTransfer::new(obligation.output_token, net_out);
 
-        let price = TimestampedPrice::from(price_fp);
         Ok(ApiExternalQuote {
             order: req.external_order,
             match_result: ApiExternalMatchResult {
@@ -169,7 +181,7 @@ impl ExternalMatchProcessor {
             fees: fee_take.into(),
             send,
             receive,
-            price: price.into(),
+            price: price_fp.into(),
             timestamp: get_current_time_millis(),
         })
     }
@@ -196,7 +208,9 @@ impl ExternalMatchProcessor {
         &self,
         req: AssembleExternalMatchRequest,
     ) -> Result<BoundedExternalMatchApiBundle, ApiServerError> {
-        // First, verify the quote signature if a quote is provided
+        self.validate_external_matches_enabled()?;
+
+        // Verify the quote signature if a quote is provided
         self.verify_quote_signature(&req)?;
 
         // Resolve price once, then use it consistently across
@@ -0,0 +1,226 @@
+//! Route handlers and helpers for estimating the cost of order actions
+//!
+//! An estimate combines the expected gas cost of the action's on-chain
+//! submissions with the relayer and protocol fees charged on a match
+
+use async_trait::async_trait;
+use circuit_types::Amount;
+use crypto::fields::scalar_to_u128;
+use darkpool_client::DarkpoolClient;
+use external_api::{
+    EmptyRequestResponse,
+    http::market::GetFeeEstimateResponse,
+    types::{ApiOrderAction, ApiToken, FeeEstimate, GasEstimateSource},
+};
+use hyper::HeaderMap;
+use state::State;
+use types_core::Token;
+use types_tasks::GasCostTotals;
+
+use crate::{
+    error::{ApiServerError, internal_error},
+    http::market::MarketDataCalculator,
+    param_parsing::{
+        parse_amount_from_query_params, parse_order_action_from_query_params,
+        parse_token_from_params,
+    },
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+// -------------
+// | Constants |
+// -------------
+
+/// The task type under which order placements record their gas costs
+const CREATE_ORDER_TASK_TYPE: &str = "create-order";
+/// The task type under which order cancellations record their gas costs
+const CANCEL_ORDER_TASK_TYPE: &str = "cancel-order";
+/// The task types under which a local order's matches record their gas costs
+const MATCH_TASK_TYPES: &[&str] = &["settle-internal-match", "settle-private-match"];
+
+/// The gas assumed for an order placement with no recorded history
+///
+/// Orders are placed with the relayer rather than on-chain, so placement
+/// submits no transaction
+const DEFAULT_PLACE_GAS: u64 = 0;
+/// The gas assumed for an order cancellation with no recorded history
+const DEFAULT_CANCEL_GAS: u64 = 150_000;
+/// The gas assumed for a match settlement with no recorded history
+const DEFAULT_MATCH_GAS: u64 = 1_500_000;
+
+// ----------------
+// | FeeEstimator |
+// ----------------
+
+/// Helper for estimating the cost of order actions
+#[derive(Clone)]
+pub(super) struct FeeEstimator {
+    /// The relayer state
+    state: State,
+    /// The darkpool client, used to query the chain's gas price
+    darkpool_client: DarkpoolClient,
+    /// The market data calculator, used for fee rates
+    calculator: MarketDataCalculator,
+}
+
+impl FeeEstimator {
+    /// Constructor
+    pub fn new(
+        state: State,
+        darkpool_client: DarkpoolClient,
+        calculator: MarketDataCalculator,
+    ) -> Self {
+        Self { state, darkpool_client, calculator }
+    }
+
+    /// Estimate the cost of an action on an order of the given amount in the
+    /// token's market
+    pub async fn estimate(
+        &self,
+        token: &Token,
+        action: ApiOrderAction,
+        amount: Amount,
+    ) -> Result<FeeEstimate, ApiServerError> {
+        self.calculator.check_token(&token.get_alloy_address())?;
+        let (gas_units, gas_source) = self.expected_gas(action).await?;
+        let gas_price = self.darkpool_client.estimate_gas_price().await.map_err(internal_error)?;
+        let gas_cost = (gas_units as u128).saturating_mul(gas_price);
+
+        // Fees are only charged when an order is matched
+        let fee_rates = self.calculator.get_fee_rates(token)?;
+        let (relayer_fee, protocol_fee) = match action {
+            ApiOrderAction::Match => (
+                scalar_to_u128(&fee_rates.relayer_fee_rate.floor_mul_int(amount)),
+                scalar_to_u128(&fee_rates.protocol_fee_rate.floor_mul_int(amount)),
+            ),
+            ApiOrderAction::Place | ApiOrderAction::Cancel => (0, 0),
+        };
+
+        Ok(FeeEstimate {
+            action,
+            base: ApiToken::from(token.clone()),
+            quote: ApiToken::from(Token::usdc()),
+            amount,
+            gas_units,
+            gas_source,
+            gas_price,
+            gas_cost,
+            fee_rates,
+            relayer_fee,
+            protocol_fee,
+        })
+    }
+
+    /// Get the gas expected to be used by an action
+    ///
+    /// Uses the average gas of the relayer's past submissions for the action,
+    /// falling back to a static default when none have been recorded
+    async fn expected_gas(
+        &self,
+        action: ApiOrderAction,
+    ) -> Result<(u64, GasEstimateSource), ApiServerError> {
+        let (task_types, default_gas) = match action {
+            ApiOrderAction::Place => (&[CREATE_ORDER_TASK_TYPE][..], DEFAULT_PLACE_GAS),
+            ApiOrderAction::Cancel => (&[CANCEL_ORDER_TASK_TYPE][..], DEFAULT_CANCEL_GAS),
+            ApiOrderAction::Match => (MATCH_TASK_TYPES, DEFAULT_MATCH_GAS),
+        };
+
+        let totals = self.state.get_task_type_gas_costs().await?;
+        Ok(average_gas(&totals, task_types, default_gas))
+    }
+}
+
+/// Average the gas used by past submissions of the given task types, falling
+/// back to the given default when none have been recorded
+fn average_gas(
+    totals: &[(String, GasCostTotals)],
+    task_types: &[&str],
+    default_gas: u64,
+) -> (u64, GasEstimateSource) {
+    let (mut num_submissions, mut gas_used) = (0u64, 0u64);
+    for (_, t) in totals.iter().filter(|(ty, _)| task_types.contains(&ty.as_str())) {
+        num_submissions += t.num_submissions;
+        gas_used = gas_used.saturating_add(t.gas_used);
+    }
+
+    if num_submissions == 0 {
+        return (default_gas, GasEstimateSource::Default);
+    }
+
+    (gas_used / num_submissions, GasEstimateSource::Historical)
+}
+
+// ------------
+// | Handlers |
+// ------------
+
+/// Handler for GET /v2/markets/:mint/fee-estimate
+pub struct GetFeeEstimateHandler {
+    /// The fee estimator
+    estimator: FeeEstimator,
+}
+
+impl GetFeeEstimateHandler {
+    /// Constructor
+    pub(super) fn new(estimator: FeeEstimator) -> Self {
+        Self { estimator }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetFeeEstimateHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetFeeEstimateResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let token = parse_token_from_params(&params)?;
+        let action = parse_order_action_from_query_params(&query_params)?;
+        let amount = parse_amount_from_query_params(&query_params)?.unwrap_or_default();
+
+        let estimate = self.estimator.estimate(&token, action, amount).await?;
+        Ok(GetFeeEstimateResponse { estimate })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use external_api::types::GasEstimateSource;
+    use types_tasks::GasCostTotals;
+
+    use super::{
+        CREATE_ORDER_TASK_TYPE, DEFAULT_MATCH_GAS, DEFAULT_PLACE_GAS, MATCH_TASK_TYPES, average_gas,
+    };
+
+    /// Build the gas totals of a task type
+    fn totals(task_type: &str, num_submissions: u64, gas_used: u64) -> (String, GasCostTotals) {
+        let totals = GasCostTotals { num_submissions, gas_used, ..Default::default() };
+        (task_type.to_string(), totals)
+    }
+
+    /// Tests that placing an order with no history is estimated to use no gas
+    #[test]
+    fn test_place_gas_default() {
+        let history = vec![totals("settle-internal-match", 2, 2_000_000)];
+        let gas = average_gas(&history, &[CREATE_ORDER_TASK_TYPE], DEFAULT_PLACE_GAS);
+        assert_eq!(gas, (0, GasEstimateSource::Default));
+    }
+
+    /// Tests that the expected gas averages over all of an action's task types
+    #[test]
+    fn test_average_gas_historical() {
+        let history = vec![
+            totals("settle-internal-match", 2, 2_000_000),
+            totals("settle-private-match", 1, 1_600_000),
+            totals(CREATE_ORDER_TASK_TYPE, 10, 0),
+        ];
+
+        let gas = average_gas(&history, MATCH_TASK_TYPES, DEFAULT_MATCH_GAS);
+        assert_eq!(gas, (1_200_000, GasEstimateSource::Historical));
+    }
+}
@@ -0,0 +1,391 @@
+//! A GraphQL read API over the relayer state
+//!
+//! Lets operators query accounts, orders, tasks, and peers with field selection
+//! and nested queries, e.g. an account's orders and recent tasks in a single
+//! request. Resolvers read through the same state interface as the REST
+//! handlers, so the two APIs serve identical data. The schema is read-only;
+//! all mutations go through the REST API
+
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Json, Object, Request, Response, Result, Schema,
+};
+use async_trait::async_trait;
+use external_api::types::{
+    ApiAccount, ApiBalance, ApiOrder, ApiOrderCore, ApiPartialOrderFill, ApiTask,
+    ApiTaskDescription, OrderState, Peer,
+};
+use hyper::HeaderMap;
+use schemars::{JsonSchema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Serialize};
+use state::State;
+use types_account::OrderId;
+use types_core::AccountId;
+use types_tasks::{HistoricalTask, TaskIdentifier};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiServerError,
+    http::{network::peer_info_to_peer, task::to_api_task},
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+/// The route serving GraphQL queries
+pub const GRAPHQL_ROUTE: &str = "/v2/admin/graphql";
+
+/// The maximum nesting depth of a query
+const MAX_QUERY_DEPTH: usize = 8;
+/// The maximum complexity of a query, roughly the number of fields resolved
+const MAX_QUERY_COMPLEXITY: usize = 1_000;
+/// The number of tasks returned for an account when a query gives no limit
+const DEFAULT_TASK_LIMIT: usize = 50;
+/// The maximum number of tasks returned for an account
+const MAX_TASK_LIMIT: usize = 500;
+
+/// The schema of the GraphQL API
+pub type RelayerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the GraphQL schema, resolving against the given state
+pub fn build_schema(state: State) -> RelayerSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+// ---------
+// | Query |
+// ---------
+
+/// The root of the GraphQL query type
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Get an account by ID
+    async fn account(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<AccountNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let account = state.get_account(&id).await?;
+        Ok(account.map(|a| AccountNode(a.into())))
+    }
+
+    /// Get an order by ID
+    async fn order(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<OrderNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let order = state.get_account_order(&id).await?;
+        Ok(order.map(|o| OrderNode(o.into())))
+    }
+
+    /// Get a queued or running task by ID
+    async fn task(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<TaskNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let Some(task) = state.get_task(&id).await? else {
+            return Ok(None);
+        };
+
+        let key = task.descriptor.queue_key();
+        Ok(HistoricalTask::from_queued_task(key, task).map(|t| TaskNode(to_api_task(t))))
+    }
+
+    /// Get the peers known to the local node
+    async fn peers(&self, ctx: &Context<'_>) -> Result<Vec<PeerNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let peers = state.get_peer_info_map().await?;
+        Ok(peers.values().map(|p| PeerNode(peer_info_to_peer(p))).collect())
+    }
+}
+
+// ---------
+// | Nodes |
+// ---------
+
+/// An account in the GraphQL schema
+pub struct AccountNode(ApiAccount);
+
+#[Object]
+impl AccountNode {
+    /// The account's ID
+    async fn id(&self) -> AccountId {
+        self.0.id
+    }
+
+    /// The account's orders
+    async fn orders(&self) -> Vec<OrderNode> {
+        self.0.orders.iter().cloned().map(OrderNode).collect()
+    }
+
+    /// The account's balances
+    async fn balances(&self) -> Json<&Vec<ApiBalance>> {
+        Json(&self.0.balances)
+    }
+
+    /// The account's tasks, running tasks first and then the most recent
+    /// historical tasks
+    async fn tasks(&self, ctx: &Context<'_>, limit: Option<usize>) -> Result<Vec<TaskNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let limit = limit.unwrap_or(DEFAULT_TASK_LIMIT).min(MAX_TASK_LIMIT);
+        let tasks = state.get_task_history(limit, &self.0.id).await?;
+        Ok(tasks.into_iter().map(|t| TaskNode(to_api_task(t))).collect())
+    }
+}
+
+/// An order in the GraphQL schema
+pub struct OrderNode(ApiOrder);
+
+#[Object]
+impl OrderNode {
+    /// The order's ID
+    async fn id(&self) -> OrderId {
+        self.0.id
+    }
+
+    /// The order's core data, e.g. its intent
+    async fn order(&self) -> Json<&ApiOrderCore> {
+        Json(&self.0.order)
+    }
+
+    /// The order's current state
+    async fn state(&self) -> Json<&OrderState> {
+        Json(&self.0.state)
+    }
+
+    /// The fills that have occurred on the order
+    async fn fills(&self) -> Json<&Vec<ApiPartialOrderFill>> {
+        Json(&self.0.fills)
+    }
+
+    /// The time at which the order was created
+    async fn created(&self) -> u64 {
+        self.0.created
+    }
+
+    /// The account that owns the order
+    async fn account(&self, ctx: &Context<'_>) -> Result<Option<AccountNode>> {
+        let state = ctx.data_unchecked::<State>();
+        let Some(account_id) = state.get_account_id_for_order(&self.0.id).await? else {
+            return Ok(None);
+        };
+
+        let account = state.get_account(&account_id).await?;
+        Ok(account.map(|a| AccountNode(a.into())))
+    }
+}
+
+/// A task in the GraphQL schema
+pub struct TaskNode(ApiTask);
+
+#[Object]
+impl TaskNode {
+    /// The task's ID
+    async fn id(&self) -> TaskIdentifier {
+        self.0.id
+    }
+
+    /// The task's current state
+    async fn state(&self) -> &str {
+        &self.0.state
+    }
+
+    /// The time at which the task was created
+    async fn created_at(&self) -> u64 {
+        self.0.created_at
+    }
+
+    /// The task's type
+    async fn task_info(&self) -> Json<&ApiTaskDescription> {
+        Json(&self.0.task_info)
+    }
+}
+
+/// A peer in the GraphQL schema
+pub struct PeerNode(Peer);
+
+#[Object]
+impl PeerNode {
+    /// The peer's ID
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    /// The ID of the peer's cluster
+    async fn cluster_id(&self) -> &str {
+        &self.0.cluster_id
+    }
+
+    /// The peer's dialable libp2p address
+    async fn addr(&self) -> &str {
+        &self.0.addr
+    }
+}
+
+// ----------
+// | Bodies |
+// ----------
+
+/// The body of a GraphQL request
+///
+/// Wraps the executor's request type so that the OpenAPI spec can describe
+/// its fields
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub struct GraphqlRequest(Request);
+
+impl JsonSchema for GraphqlRequest {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "GraphqlRequest".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> schemars::Schema {
+        json_schema!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": { "type": "string" },
+                "operationName": { "type": "string" },
+                "variables": { "type": "object" },
+            },
+        })
+    }
+}
+
+/// The body of a GraphQL response
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct GraphqlResponse(Response);
+
+impl JsonSchema for GraphqlResponse {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "GraphqlResponse".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> schemars::Schema {
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "data": {},
+                "errors": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["message"],
+                        "properties": { "message": { "type": "string" } },
+                    },
+                },
+            },
+        })
+    }
+}
+
+// -----------
+// | Handler |
+// -----------
+
+/// Handler for POST /v2/admin/graphql
+pub struct GraphqlHandler {
+    /// The GraphQL schema
+    schema: RelayerSchema,
+}
+
+impl GraphqlHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { schema: build_schema(state) }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GraphqlHandler {
+    type Request = GraphqlRequest;
+    type Response = GraphqlResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        // Query errors are reported in the response body, per the GraphQL spec
+        Ok(GraphqlResponse(self.schema.execute(req.0).await))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use constants::GLOBAL_MATCHING_POOL;
+    use serde_json::json;
+    use state::{State, test_helpers::mock_state};
+    use types_account::{
+        account::mocks::mock_empty_account, order::mocks::mock_order,
+        order_auth::mocks::mock_order_auth,
+    };
+    use types_core::AccountId;
+    use uuid::Uuid;
+
+    use super::{MAX_QUERY_DEPTH, build_schema};
+
+    /// Create a mock state holding an account with a single order
+    async fn state_with_order() -> (State, AccountId, Uuid) {
+        let state = mock_state().await;
+        let account = mock_empty_account();
+        state.new_account(account.clone()).await.unwrap().await.unwrap();
+
+        let order = mock_order();
+        let (auth, pool_name) = (mock_order_auth(), GLOBAL_MATCHING_POOL.to_string());
+        let waiter =
+            state.add_order_to_account(account.id, order.clone(), auth, pool_name).await.unwrap();
+        waiter.await.unwrap();
+        (state, account.id, order.id)
+    }
+
+    /// Tests resolving an account's orders and an order's owning account in
+    /// a single query
+    #[tokio::test]
+    async fn test_nested_query() {
+        let (state, account_id, order_id) = state_with_order().await;
+        let schema = build_schema(state);
+
+        let query = format!(
+            r#"{{ account(id: "{account_id}") {{ id orders {{ id account {{ id }} }} }} }}"#
+        );
+        let resp = schema.execute(query).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+
+        let expected = json!({
+            "account": {
+                "id": account_id.to_string(),
+                "orders": [{ "id": order_id.to_string(), "account": { "id": account_id.to_string() } }],
+            },
+        });
+        assert_eq!(resp.data.into_json().unwrap(), expected);
+    }
+
+    /// Tests that an unknown account resolves to null
+    #[tokio::test]
+    async fn test_missing_account() {
+        let schema = build_schema(mock_state().await);
+        let query = format!(r#"{{ account(id: "{}") {{ id }} }}"#, Uuid::new_v4());
+        let resp = schema.execute(query).await;
+
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        assert_eq!(resp.data.into_json().unwrap(), json!({ "account": null }));
+    }
+
+    /// Tests that a query nested beyond the maximum depth is refused
+    #[tokio::test]
+    async fn test_query_depth_limit() {
+        let (state, _, order_id) = state_with_order().await;
+        let schema = build_schema(state);
+
+        // Alternate between an order and its account until the depth limit is
+        // exceeded
+        let mut selection = "id".to_string();
+        for i in 0..MAX_QUERY_DEPTH {
+            let field = if i % 2 == 0 { "account" } else { "orders" };
+            selection = format!("{field} {{ {selection} }}");
+        }
+        let query = format!(r#"{{ order(id: "{order_id}") {{ {selection} }} }}"#);
+        let resp = schema.execute(query).await;
+        assert!(!resp.errors.is_empty());
+    }
+}
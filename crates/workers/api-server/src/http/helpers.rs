@@ -12,19 +12,22 @@ use state::State;
 use tokio::time::timeout;
 use types_account::{
     MatchingPoolName, OrderId,
-    order::{OrderMetadata, PrivacyRing},
+    order::{OrderFilter, OrderMetadata, PrivacyRing},
     order_auth::OrderAuth,
 };
 use types_core::AccountId;
 use types_tasks::{CreateOrderTaskDescriptor, TaskDescriptor, TaskIdentifier};
 
 use crate::{
-    error::{ApiServerError, bad_request, internal_error},
+    error::{ApiServerError, bad_request, conflict, internal_error},
     http::account::account_not_found,
 };
 
 /// The timeout for awaiting a blocking task completion
 const BLOCKING_TASK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Error message for a client order id already used by one of the account's
+/// orders
+const ERR_CLIENT_ORDER_ID_EXISTS: &str = "an order with this client order id already exists";
 
 /// Append a task to the task queue
 ///
@@ -39,22 +42,73 @@ pub async fn append_task(
     let (tid, waiter) = state.append_task(task).await?;
     waiter.await?;
 
-    if !blocking {
-        return Ok(tid);
+    if blocking {
+        await_task_completion(tid, task_queue).await?;
     }
+    Ok(tid)
+}
+
+/// Append a set of tasks to the task queue in a single proposal, so that
+/// either all of them are enqueued or none are
+///
+/// If `blocking` is true, the function will await each task's completion
+/// with a 30-second timeout.
+pub async fn append_tasks(
+    tasks: Vec<TaskDescriptor>,
+    blocking: bool,
+    state: &State,
+    task_queue: &TaskDriverQueue,
+) -> Result<Vec<TaskIdentifier>, ApiServerError> {
+    let (tids, waiter) = state.append_tasks(tasks).await?;
+    waiter.await?;
+
+    if blocking {
+        for tid in tids.iter().copied() {
+            await_task_completion(tid, task_queue).await?;
+        }
+    }
+    Ok(tids)
+}
 
+/// Await a task's completion with a 30-second timeout
+async fn await_task_completion(
+    tid: TaskIdentifier,
+    task_queue: &TaskDriverQueue,
+) -> Result<(), ApiServerError> {
     // Register for task completion notification and await with timeout
     let (rx, job) = new_task_notification(tid);
     task_queue.send(job).map_err(|e| internal_error(e.to_string()))?;
 
     match timeout(BLOCKING_TASK_TIMEOUT, rx).await {
-        Ok(Ok(Ok(()))) => Ok(tid),
+        Ok(Ok(Ok(()))) => Ok(()),
         Ok(Ok(Err(e))) => Err(internal_error(e)),
         Ok(Err(_recv_err)) => Err(internal_error("task notification channel closed unexpectedly")),
         Err(_timeout) => Err(internal_error("task timeout")),
     }
 }
 
+/// Validate the client order id and tags of a new order
+///
+/// A client order id must be unique among the account's orders, so that it
+/// identifies a single order
+pub(crate) async fn check_client_order_fields(
+    account_id: AccountId,
+    order: &ApiOrderCore,
+    state: &State,
+) -> Result<(), ApiServerError> {
+    order.get_order_metadata().validate_client_fields().map_err(bad_request)?;
+
+    let Some(client_order_id) = order.client_order_id.clone() else {
+        return Ok(());
+    };
+    let filter = OrderFilter { client_order_id: Some(client_order_id), tags: Vec::new() };
+    if !state.get_account_orders_filtered(&account_id, filter).await?.is_empty() {
+        return Err(conflict(ERR_CLIENT_ORDER_ID_EXISTS));
+    }
+
+    Ok(())
+}
+
 /// Append a create order task to the task queue
 pub(crate) async fn append_create_order_task(
     account_id: AccountId,
@@ -65,6 +119,19 @@ pub(crate) async fn append_create_order_task(
     state: &State,
     task_queue: &TaskDriverQueue,
 ) -> Result<TaskIdentifier, ApiServerError> {
+    let descriptor =
+        create_order_task_descriptor(account_id, order, auth, matching_pool, state).await?;
+    append_task(descriptor, blocking, state, task_queue).await
+}
+
+/// Build the create order task descriptor for an order's privacy ring
+pub(crate) async fn create_order_task_descriptor(
+    account_id: AccountId,
+    order: ApiOrderCore,
+    auth: OrderAuth,
+    matching_pool: MatchingPoolName,
+    state: &State,
+) -> Result<TaskDescriptor, ApiServerError> {
     let order_id = order.id;
     let (intent, ring, metadata) = order.into_order_components();
     let descriptor = match ring {
@@ -106,7 +173,7 @@ pub(crate) async fn append_create_order_task(
         },
     }?;
 
-    append_task(descriptor.into(), blocking, state, task_queue).await
+    Ok(descriptor.into())
 }
 
 /// Create a ring 0 order task descriptor
@@ -47,7 +47,10 @@ impl MarketDataCalculator {
     }
 
     /// Reject if a token is disabled
-    fn check_token(&self, addr: &alloy::primitives::Address) -> Result<(), ApiServerError> {
+    pub(super) fn check_token(
+        &self,
+        addr: &alloy::primitives::Address,
+    ) -> Result<(), ApiServerError> {
         self.asset_filter.check_token(addr)
     }
 
@@ -57,7 +60,7 @@ impl MarketDataCalculator {
     }
 
     /// Get fee rates for a token
-    fn get_fee_rates(&self, token: &Token) -> Result<FeeTakeRate, ApiServerError> {
+    pub(super) fn get_fee_rates(&self, token: &Token) -> Result<FeeTakeRate, ApiServerError> {
         let ticker = token.get_ticker().unwrap_or_default();
         let relayer_fee: FixedPoint = self.state.get_relayer_fee(&ticker)?;
         let protocol_fee: FixedPoint =
@@ -71,12 +74,14 @@ impl MarketDataCalculator {
         let base = ApiToken::from(token.clone());
         let quote = ApiToken::from(Token::usdc());
         let price: ApiTimestampedPrice = self.price_streams.peek_timestamped_price(token)?.into();
+        let price_confidence = self.price_streams.get_state(token, &Token::usdc()).confidence();
         let fees = self.get_fee_rates(token)?;
 
         Ok(MarketInfo {
             base,
             quote,
             price,
+            price_confidence,
             internal_match_fee_rates: fees.clone(),
             // At the moment, the relayer does not differentiate between internal and external match
             // fee rates
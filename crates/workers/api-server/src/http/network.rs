@@ -3,9 +3,13 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
-use external_api::{EmptyRequestResponse, http::network::GetNetworkTopologyResponse, types::Peer};
+use external_api::{
+    EmptyRequestResponse,
+    http::network::{GetNetworkTopologyResponse, GetOrderBookSnapshotResponse},
+    types::{ApiToken, OrderBookGroup, Peer},
+};
 use hyper::HeaderMap;
-use state::State;
+use state::{State, order_book::OrderBookGroup as StateOrderBookGroup};
 use types_core::Chain;
 use types_gossip::PeerInfo;
 
@@ -19,7 +23,7 @@ use crate::{
 // -----------
 
 /// Convert a PeerInfo to a Peer API type
-fn peer_info_to_peer(peer_info: &PeerInfo) -> Peer {
+pub(crate) fn peer_info_to_peer(peer_info: &PeerInfo) -> Peer {
     Peer {
         id: peer_info.get_peer_id().to_string(),
         cluster_id: peer_info.get_cluster_id().to_string(),
@@ -28,6 +32,13 @@ fn peer_info_to_peer(peer_info: &PeerInfo) -> Peer {
     }
 }
 
+/// Convert an order book group in the state to its API type
+fn order_book_group_to_api(group: StateOrderBookGroup) -> OrderBookGroup {
+    let base = group.pair.map(|pair| ApiToken::from(pair.in_token()));
+    let quote = group.pair.map(|pair| ApiToken::from(pair.out_token()));
+    OrderBookGroup { base, quote, state: group.state, num_orders: group.num_orders }
+}
+
 // ------------------
 // | Route Handlers |
 // ------------------
@@ -89,3 +100,35 @@ impl TypedHandler for GetNetworkTopologyHandler {
         Ok(GetNetworkTopologyResponse { local_cluster_id, network })
     }
 }
+
+/// Handler for the GET "/v2/network/order-book" route
+#[derive(Clone)]
+pub struct GetOrderBookSnapshotHandler {
+    /// A copy of the relayer-global state
+    state: State,
+}
+
+impl GetOrderBookSnapshotHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetOrderBookSnapshotHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetOrderBookSnapshotResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let snapshot = self.state.get_order_book_snapshot().await?;
+        let groups = snapshot.into_iter().map(order_book_group_to_api).collect();
+        Ok(GetOrderBookSnapshotResponse { groups })
+    }
+}
@@ -2,31 +2,43 @@
 
 use alloy::primitives::Address;
 use async_trait::async_trait;
+use circuit_types::Amount;
 use constants::GLOBAL_MATCHING_POOL;
+use crypto::fields::scalar_to_u128;
 use external_api::{
     EmptyRequestResponse,
     http::order::{
         CancelOrderRequest, CancelOrderResponse, CreateOrderRequest, CreateOrderResponse,
-        GetOrderByIdResponse, GetOrdersResponse, UpdateOrderRequest, UpdateOrderResponse,
+        CreateOrdersBatchRequest, CreateOrdersBatchResponse, GetOrderByIdResponse,
+        GetOrdersResponse, UpdateOrderRequest, UpdateOrderResponse,
     },
 };
 use hyper::HeaderMap;
 use itertools::Itertools;
 use job_types::task_driver::TaskDriverQueue;
+use price_state::PriceStreamStates;
 use renegade_solidity_abi::v2::IDarkpoolV2::SignatureWithNonce;
 use state::State;
-use types_account::{OrderId, order::PrivacyRing};
+use types_account::{
+    MatchingPoolName, OrderId, order::PrivacyRing, order_auth::OrderAuth, pair::Pair,
+};
 use types_core::AccountId;
-use types_tasks::CancelOrderTaskDescriptor;
+use types_tasks::{CancelOrderTaskDescriptor, TaskDescriptor};
+use uuid::Uuid;
 
 use crate::{
     error::{ApiServerError, bad_request, conflict, not_found},
     http::{
+        account_versions::AccountVersions,
         asset_filter::AssetFilter,
-        helpers::{append_create_order_task, append_task},
+        helpers::{
+            append_create_order_task, append_task, append_tasks, check_client_order_fields,
+            create_order_task_descriptor,
+        },
     },
     param_parsing::{
-        parse_account_id_from_params, parse_order_id_from_params, should_block_on_task,
+        parse_account_id_from_params, parse_order_filter_from_query_params,
+        parse_order_id_from_params, should_block_on_task,
     },
     router::{QueryParams, TypedHandler, UrlParams},
 };
@@ -37,6 +49,17 @@ use crate::{
 
 /// Error message for order already exists
 const ERR_ORDER_ALREADY_EXISTS: &str = "order already exists";
+/// Error message for a batch containing the same order twice
+const ERR_DUPLICATE_ORDER_IN_BATCH: &str = "batch contains duplicate order IDs";
+/// Error message for a batch containing the same client order id twice
+const ERR_DUPLICATE_CLIENT_ORDER_ID_IN_BATCH: &str = "batch contains duplicate client order IDs";
+/// Error message for an empty order batch
+const ERR_EMPTY_BATCH: &str = "batch contains no orders";
+/// Error message for an order batch exceeding the maximum size
+const ERR_BATCH_TOO_LARGE: &str = "batch exceeds the maximum number of orders";
+
+/// The maximum number of orders created or cancelled in a batch
+const MAX_ORDER_BATCH_SIZE: usize = 32;
 
 /// Error message for not implemented
 const ERR_NOT_IMPLEMENTED: &str = "not implemented";
@@ -47,12 +70,18 @@ const ERR_INVALID_ORDER_TYPE: &str =
     "only public orders (Ring0) can be cancelled via this endpoint";
 /// Error message for missing order auth
 const ERR_ORDER_AUTH_NOT_FOUND: &str = "order auth not found";
+/// Error message for an order on a pair the account may not trade
+const ERR_PAIR_NOT_ALLOWED: &str = "account is not permitted to trade this pair";
+/// Error message for an order exceeding the account's max notional
+const ERR_ORDER_NOTIONAL_EXCEEDED: &str = "order notional exceeds the account's limit";
 
 // -------------------
 // | Order Handlers  |
 // -------------------
 
 /// Handler for GET /v2/account/:account_id/orders
+///
+/// Orders may be filtered by the `client_order_id` and `tags` query params
 pub struct GetOrdersHandler {
     /// A handle to the relayer's state
     state: State,
@@ -75,10 +104,11 @@ impl TypedHandler for GetOrdersHandler {
         _headers: HeaderMap,
         _req: Self::Request,
         params: UrlParams,
-        _query_params: QueryParams,
+        query_params: QueryParams,
     ) -> Result<Self::Response, ApiServerError> {
         let account_id = parse_account_id_from_params(&params)?;
-        let orders = self.state.get_account_orders(&account_id).await?;
+        let filter = parse_order_filter_from_query_params(&query_params);
+        let orders = self.state.get_account_orders_filtered(&account_id, filter).await?;
         let orders = orders.into_iter().map(Into::into).collect_vec();
 
         // TODO: Paginate
@@ -138,8 +168,12 @@ pub struct CreateOrderHandler {
     asset_filter: AssetFilter,
     /// A handle to the relayer's state
     state: State,
+    /// The price streams, used to value orders against risk limits
+    price_streams: PriceStreamStates,
     /// The task driver queue
     task_queue: TaskDriverQueue,
+    /// The versions of accounts mutated through the API
+    account_versions: AccountVersions,
 }
 
 impl CreateOrderHandler {
@@ -148,50 +182,107 @@ impl CreateOrderHandler {
         executor: Address,
         asset_filter: AssetFilter,
         state: State,
+        price_streams: PriceStreamStates,
         task_queue: TaskDriverQueue,
+        account_versions: AccountVersions,
     ) -> Self {
-        Self { executor, asset_filter, state, task_queue }
+        Self { executor, asset_filter, state, price_streams, task_queue, account_versions }
     }
-}
-
-#[async_trait]
-impl TypedHandler for CreateOrderHandler {
-    type Request = CreateOrderRequest;
-    type Response = CreateOrderResponse;
 
-    async fn handle_typed(
+    /// Validate an order and get its authorization
+    async fn validate_order(
         &self,
-        _headers: HeaderMap,
-        req: Self::Request,
-        params: UrlParams,
-        query_params: QueryParams,
-    ) -> Result<Self::Response, ApiServerError> {
-        // Parse query and URL params
-        let blocking = should_block_on_task(&query_params);
-        let account_id = parse_account_id_from_params(&params)?;
-
+        account_id: AccountId,
+        req: &CreateOrderRequest,
+    ) -> Result<OrderAuth, ApiServerError> {
         // Check if either token in the order is disabled
         let intent = &req.order.intent;
         self.asset_filter.check_pair(&intent.in_token, &intent.out_token)?;
 
+        // Check the order against the account's risk limits
+        let pair = Pair::new(intent.in_token, intent.out_token);
+        self.check_risk_limits(account_id, &pair, intent.amount_in).await?;
+
         // Check if order already exists
         let order_id = OrderId::from(req.order.id);
         if self.state.get_account_order(&order_id).await?.is_some() {
             return Err(conflict(ERR_ORDER_ALREADY_EXISTS));
         }
+        check_client_order_fields(account_id, &req.order, &self.state).await?;
 
-        // Look up the account's default matching pool, falling back to global
+        req.get_order_auth(self.executor).map_err(bad_request)
+    }
+
+    /// Get the account's default matching pool, falling back to global
+    async fn account_matching_pool(
+        &self,
+        account_id: AccountId,
+    ) -> Result<MatchingPoolName, ApiServerError> {
         let account = self
             .state
             .get_account(&account_id)
             .await?
             .ok_or_else(|| not_found(format!("account {account_id} not found")))?;
-        let matching_pool = account
-            .default_matching_pool
-            .clone()
-            .unwrap_or_else(|| GLOBAL_MATCHING_POOL.to_string());
+        Ok(account.default_matching_pool.unwrap_or_else(|| GLOBAL_MATCHING_POOL.to_string()))
+    }
+
+    /// Check an order against the account's risk limits, if any are set
+    async fn check_risk_limits(
+        &self,
+        account_id: AccountId,
+        pair: &Pair,
+        amount_in: Amount,
+    ) -> Result<(), ApiServerError> {
+        let Some(config) = self.state.get_account_risk_config(&account_id).await? else {
+            return Ok(());
+        };
+
+        if !config.allows_pair(pair) {
+            return Err(bad_request(ERR_PAIR_NOT_ALLOWED));
+        }
+
+        if config.max_order_notional.is_some() {
+            let notional = self.order_notional(pair, amount_in)?;
+            if !config.allows_order_notional(notional) {
+                return Err(bad_request(ERR_ORDER_NOTIONAL_EXCEEDED));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Value an order's input amount in units of the quote token
+    fn order_notional(&self, pair: &Pair, amount_in: Amount) -> Result<Amount, ApiServerError> {
+        if pair.is_input_quote() {
+            return Ok(amount_in);
+        }
+
+        // The price is decimal corrected and in units of output / input
+        let price = self.price_streams.get_output_quoted_price(pair)?.price;
+        let notional = price.to_fixed_point().floor_mul_int(amount_in);
+        Ok(scalar_to_u128(&notional))
+    }
+}
 
-        let auth = req.get_order_auth(self.executor).map_err(bad_request)?;
+#[async_trait]
+impl TypedHandler for CreateOrderHandler {
+    type Request = CreateOrderRequest;
+    type Response = CreateOrderResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        // Parse query and URL params
+        let blocking = should_block_on_task(&query_params);
+        let account_id = parse_account_id_from_params(&params)?;
+
+        let auth = self.validate_order(account_id, &req).await?;
+        let matching_pool = self.account_matching_pool(account_id).await?;
+        self.account_versions.check_and_advance(&account_id, &headers).await?;
         let task_id = append_create_order_task(
             account_id,
             req.order,
@@ -208,6 +299,93 @@ impl TypedHandler for CreateOrderHandler {
     }
 }
 
+/// Handler for POST /v2/account/:account_id/orders/batch
+pub struct CreateOrdersBatchHandler {
+    /// The single order handler, used to validate each creation in the batch
+    inner: CreateOrderHandler,
+    /// The cancel order handler, used to validate each cancellation in the
+    /// batch
+    cancel: CancelOrderHandler,
+}
+
+impl CreateOrdersBatchHandler {
+    /// Constructor
+    pub fn new(inner: CreateOrderHandler, cancel: CancelOrderHandler) -> Self {
+        Self { inner, cancel }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for CreateOrdersBatchHandler {
+    type Request = CreateOrdersBatchRequest;
+    type Response = CreateOrdersBatchResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let blocking = should_block_on_task(&query_params);
+        let account_id = parse_account_id_from_params(&params)?;
+        let batch_size = req.orders.len() + req.cancellations.len();
+        if batch_size == 0 {
+            return Err(bad_request(ERR_EMPTY_BATCH));
+        }
+        if batch_size > MAX_ORDER_BATCH_SIZE {
+            return Err(bad_request(ERR_BATCH_TOO_LARGE));
+        }
+
+        // An order may appear at most once, as either a creation or a cancellation
+        let creation_ids = req.orders.iter().map(|order| order.order.id);
+        let cancellation_ids = req.cancellations.iter().map(|cancel| cancel.order_id);
+        let client_order_ids =
+            req.orders.iter().filter_map(|order| order.order.client_order_id.as_deref());
+        check_batch_ids(creation_ids.chain(cancellation_ids), client_order_ids)?;
+
+        // Validate every operation and build its task before enqueueing any, so
+        // that a rejected operation leaves the account untouched
+        let mut tasks: Vec<TaskDescriptor> = Vec::with_capacity(batch_size);
+        for cancel in req.cancellations {
+            let order_id = OrderId::from(cancel.order_id);
+            let signature = cancel.cancel_signature.into();
+            let descriptor =
+                self.cancel.build_cancellation(account_id, order_id, signature).await?;
+            tasks.push(descriptor.into());
+        }
+        let num_cancellations = tasks.len();
+
+        let mut auths = Vec::with_capacity(req.orders.len());
+        for order in req.orders.iter() {
+            auths.push(self.inner.validate_order(account_id, order).await?);
+        }
+
+        let matching_pool = self.inner.account_matching_pool(account_id).await?;
+        for (order, auth) in req.orders.into_iter().zip(auths) {
+            let descriptor = create_order_task_descriptor(
+                account_id,
+                order.order,
+                auth,
+                matching_pool.clone(),
+                &self.inner.state,
+            )
+            .await?;
+            tasks.push(descriptor);
+        }
+        self.inner.account_versions.check_and_advance(&account_id, &headers).await?;
+
+        // Enqueue the tasks in a single proposal, the account's task queue
+        // serializes them. Cancellations go first so that replaced quotes leave
+        // the book before their replacements enter it
+        let mut cancel_task_ids =
+            append_tasks(tasks, blocking, &self.inner.state, &self.inner.task_queue).await?;
+        let task_ids = cancel_task_ids.split_off(num_cancellations);
+
+        Ok(CreateOrdersBatchResponse { task_ids, cancel_task_ids, completed: blocking })
+    }
+}
+
 /// Handler for POST /v2/account/:account_id/orders/:order_id/update
 pub struct UpdateOrderHandler;
 impl UpdateOrderHandler {
@@ -239,37 +417,29 @@ pub struct CancelOrderHandler {
     state: State,
     /// The task driver queue
     task_queue: TaskDriverQueue,
+    /// The versions of accounts mutated through the API
+    account_versions: AccountVersions,
 }
 
 impl CancelOrderHandler {
     /// Constructor
-    pub fn new(state: State, task_queue: TaskDriverQueue) -> Self {
-        Self { state, task_queue }
+    pub fn new(
+        state: State,
+        task_queue: TaskDriverQueue,
+        account_versions: AccountVersions,
+    ) -> Self {
+        Self { state, task_queue, account_versions }
     }
-}
-
-#[async_trait]
-impl TypedHandler for CancelOrderHandler {
-    type Request = CancelOrderRequest;
-    type Response = CancelOrderResponse;
 
-    async fn handle_typed(
+    /// Validate the cancellation of an order and build its task descriptor
+    async fn build_cancellation(
         &self,
-        _headers: HeaderMap,
-        req: Self::Request,
-        params: UrlParams,
-        query_params: QueryParams,
-    ) -> Result<Self::Response, ApiServerError> {
-        let blocking = should_block_on_task(&query_params);
-
-        // Parse account_id and order_id from URL params
-        let account_id = parse_account_id_from_params(&params)?;
-        let order_id = parse_order_id_from_params(&params)?;
+        account_id: AccountId,
+        order_id: OrderId,
+        cancel_signature: SignatureWithNonce,
+    ) -> Result<CancelOrderTaskDescriptor, ApiServerError> {
         verify_order_belongs_to_account(order_id, account_id, &self.state).await?;
 
-        // Convert the cancel signature from the request
-        let cancel_signature: SignatureWithNonce = req.cancel_signature.into();
-
         // Fetch the order and verify it
         let order = self
             .state
@@ -288,11 +458,35 @@ impl TypedHandler for CancelOrderHandler {
             .ok_or(not_found(ERR_ORDER_AUTH_NOT_FOUND))?;
 
         // Create the task descriptor
-        let descriptor =
-            CancelOrderTaskDescriptor::new(account_id, order_id, order_auth, cancel_signature)
-                .map_err(bad_request)?;
+        CancelOrderTaskDescriptor::new(account_id, order_id, order_auth, cancel_signature)
+            .map_err(bad_request)
+    }
+}
+
+#[async_trait]
+impl TypedHandler for CancelOrderHandler {
+    type Request = CancelOrderRequest;
+    type Response = CancelOrderResponse;
+
+    async fn handle_typed(
+        &self,
+        headers: HeaderMap,
+        req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let blocking = should_block_on_task(&query_params);
+
+        // Parse account_id and order_id from URL params
+        let account_id = parse_account_id_from_params(&params)?;
+        let order_id = parse_order_id_from_params(&params)?;
+
+        // Convert the cancel signature from the request
+        let cancel_signature: SignatureWithNonce = req.cancel_signature.into();
+        let descriptor = self.build_cancellation(account_id, order_id, cancel_signature).await?;
 
         // Append the task and return the task ID
+        self.account_versions.check_and_advance(&account_id, &headers).await?;
         let task_id =
             append_task(descriptor.into(), blocking, &self.state, &self.task_queue).await?;
         Ok(CancelOrderResponse { task_id, completed: true })
@@ -303,6 +497,25 @@ impl TypedHandler for CancelOrderHandler {
 // | Helpers |
 // -----------
 
+/// Check that each order in a batch appears once, and that no two orders
+/// created by the batch share a client order id
+///
+/// Client order ids are checked against the account's stored orders when each
+/// order is validated, which does not see the batch's other orders
+fn check_batch_ids<'a>(
+    order_ids: impl IntoIterator<Item = Uuid>,
+    client_order_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<(), ApiServerError> {
+    if !order_ids.into_iter().all_unique() {
+        return Err(bad_request(ERR_DUPLICATE_ORDER_IN_BATCH));
+    }
+    if !client_order_ids.into_iter().all_unique() {
+        return Err(bad_request(ERR_DUPLICATE_CLIENT_ORDER_ID_IN_BATCH));
+    }
+
+    Ok(())
+}
+
 /// Verify that an order belongs to a given account
 async fn verify_order_belongs_to_account(
     order_id: OrderId,
@@ -320,3 +533,35 @@ async fn verify_order_belongs_to_account(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::check_batch_ids;
+
+    /// Tests that a batch of distinct orders passes the id checks
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_check_batch_ids__distinct() {
+        let order_ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        assert!(check_batch_ids(order_ids, ["a", "b"]).is_ok());
+        assert!(check_batch_ids(order_ids, Vec::<&str>::new()).is_ok());
+    }
+
+    /// Tests that an order appearing twice in a batch is rejected
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_check_batch_ids__duplicate_order() {
+        let order_id = Uuid::new_v4();
+        assert!(check_batch_ids([order_id, Uuid::new_v4(), order_id], Vec::<&str>::new()).is_err());
+    }
+
+    /// Tests that two orders sharing a client order id are rejected
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_check_batch_ids__duplicate_client_order_id() {
+        let order_ids = [Uuid::new_v4(), Uuid::new_v4()];
+        assert!(check_batch_ids(order_ids, ["a", "a"]).is_err());
+    }
+}
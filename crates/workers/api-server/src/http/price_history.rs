@@ -0,0 +1,280 @@
+//! Route handlers and helpers for a market's price history
+//!
+//! The relayer periodically samples each enabled market's price from the
+//! price reporter into its local database, so that clients can chart a
+//! market's price without their own exchange connections. The history is
+//! served as candles summarizing the prices sampled in each interval
+
+use std::{collections::HashMap, time::Duration};
+
+use alloy::primitives::Address;
+use async_trait::async_trait;
+use external_api::{
+    EmptyRequestResponse,
+    http::market::GetPriceHistoryResponse,
+    types::{ApiToken, PriceCandle},
+};
+use hyper::HeaderMap;
+use price_state::PriceStreamStates;
+use state::{State, storage::tx::price_history::PriceSample};
+use types_core::Token;
+use util::{get_current_time_millis, log_task, logging::Outcome};
+
+use crate::{
+    error::{ApiServerError, bad_request},
+    http::asset_filter::AssetFilter,
+    logging::Task,
+    param_parsing::{
+        parse_interval_from_query_params, parse_time_window_from_query_params,
+        parse_token_from_params,
+    },
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+// -------------
+// | Constants |
+// -------------
+
+/// The interval at which prices are sampled into the price history
+const PRICE_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+/// The number of milliseconds in an hour
+const MS_PER_HOUR: u64 = 60 * 60 * 1000;
+
+/// The length of the window served when a request does not give a start
+const DEFAULT_WINDOW_MS: u64 = MS_PER_HOUR;
+/// The length of a candle when a request does not give an interval
+const DEFAULT_INTERVAL_MS: u64 = 60_000; // 1 minute
+/// The maximum number of candles a single request may span
+const MAX_CANDLES: u64 = 1_000;
+
+/// The error message given when a window's start does not precede its end
+const ERR_INVALID_WINDOW: &str = "window start must precede its end";
+/// The error message given when a candle interval is zero
+const ERR_ZERO_INTERVAL: &str = "interval must be positive";
+/// The error message given when a window spans too many candles
+const ERR_TOO_MANY_CANDLES: &str = "window spans too many candles, increase the interval";
+
+// ------------
+// | Recorder |
+// ------------
+
+/// Samples the price of each enabled market into the local price history
+pub(crate) struct PriceHistoryRecorder {
+    /// The relayer state
+    state: State,
+    /// The price stream states
+    price_streams: PriceStreamStates,
+    /// Asset filter selecting the markets to record
+    asset_filter: AssetFilter,
+    /// The length of the history to retain, in milliseconds
+    retention_ms: u64,
+}
+
+impl PriceHistoryRecorder {
+    /// Constructor
+    pub fn new(
+        state: State,
+        price_streams: PriceStreamStates,
+        asset_filter: AssetFilter,
+        retention_hours: u64,
+    ) -> Self {
+        let retention_ms = retention_hours.saturating_mul(MS_PER_HOUR);
+        Self { state, price_streams, asset_filter, retention_ms }
+    }
+
+    /// Sample prices into the price history on a fixed interval
+    pub async fn run(self) {
+        // The timestamp of the last price recorded for each base token
+        let mut last_recorded = HashMap::new();
+        let mut ticker = tokio::time::interval(PRICE_SAMPLE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let samples = self.sample_prices(&mut last_recorded);
+            if samples.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self.state.record_price_samples(samples, self.retention_ms).await {
+                log_task!(
+                    Task::RecordPriceHistory,
+                    Outcome::Failed,
+                    error = %e,
+                    "failed to record price history"
+                );
+            }
+        }
+    }
+
+    /// Sample the latest price of each enabled market
+    ///
+    /// Markets whose price has not been reported, or has not been updated
+    /// since the last sample, are skipped
+    fn sample_prices(
+        &self,
+        last_recorded: &mut HashMap<Address, u64>,
+    ) -> Vec<(Address, Address, PriceSample)> {
+        let quote = Token::usdc().get_alloy_address();
+        let mut samples = Vec::new();
+        for token in self.asset_filter.enabled_base_tokens() {
+            let Ok(price) = self.price_streams.peek_timestamped_price(&token) else {
+                continue;
+            };
+
+            let base = token.get_alloy_address();
+            if price.timestamp == 0 || last_recorded.get(&base) == Some(&price.timestamp) {
+                continue;
+            }
+
+            last_recorded.insert(base, price.timestamp);
+            let sample = PriceSample { timestamp: price.timestamp, price: price.price.to_f64() };
+            samples.push((base, quote, sample));
+        }
+
+        samples
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Summarize a window's samples into candles of the given interval, aligned to
+/// the window's start
+///
+/// Samples must be ordered by timestamp and fall within the window
+fn build_candles(samples: &[PriceSample], start: u64, interval_ms: u64) -> Vec<PriceCandle> {
+    let candle_index = |s: &PriceSample| (s.timestamp - start) / interval_ms;
+    samples
+        .chunk_by(|a, b| candle_index(a) == candle_index(b))
+        .map(|chunk| {
+            let candle_start = start + candle_index(&chunk[0]) * interval_ms;
+            summarize_samples(chunk, candle_start, candle_start + interval_ms)
+        })
+        .collect()
+}
+
+/// Summarize a non-empty, ordered set of samples into a candle
+fn summarize_samples(samples: &[PriceSample], start: u64, end: u64) -> PriceCandle {
+    let mut prices: Vec<f64> = samples.iter().map(|s| s.price).collect();
+    prices.sort_by(f64::total_cmp);
+
+    let mid = prices.len() / 2;
+    let median =
+        if prices.len() % 2 == 0 { (prices[mid - 1] + prices[mid]) / 2. } else { prices[mid] };
+
+    PriceCandle {
+        start,
+        end,
+        open: samples[0].price,
+        high: prices[prices.len() - 1],
+        low: prices[0],
+        close: samples[samples.len() - 1].price,
+        median,
+        num_samples: samples.len(),
+    }
+}
+
+// ------------
+// | Handlers |
+// ------------
+
+/// Handler for GET /v2/markets/:mint/price-history
+pub struct GetPriceHistoryHandler {
+    /// Asset filter for checking disabled tokens
+    asset_filter: AssetFilter,
+    /// The relayer state
+    state: State,
+}
+
+impl GetPriceHistoryHandler {
+    /// Constructor
+    pub fn new(asset_filter: AssetFilter, state: State) -> Self {
+        Self { asset_filter, state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetPriceHistoryHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetPriceHistoryResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let token = parse_token_from_params(&params)?;
+        let base = token.get_alloy_address();
+        self.asset_filter.check_token(&base)?;
+
+        // Default to the most recent window
+        let (start, end) = parse_time_window_from_query_params(&query_params)?;
+        let end = end.unwrap_or_else(get_current_time_millis);
+        let start = start.unwrap_or_else(|| end.saturating_sub(DEFAULT_WINDOW_MS));
+        let interval_ms = parse_interval_from_query_params(&query_params)?;
+        let interval_ms = interval_ms.unwrap_or(DEFAULT_INTERVAL_MS);
+        if start >= end {
+            return Err(bad_request(ERR_INVALID_WINDOW));
+        }
+        if interval_ms == 0 {
+            return Err(bad_request(ERR_ZERO_INTERVAL));
+        }
+        if (end - start).div_ceil(interval_ms) > MAX_CANDLES {
+            return Err(bad_request(ERR_TOO_MANY_CANDLES));
+        }
+
+        // The window excludes its end
+        let quote = Token::usdc();
+        let samples =
+            self.state.get_price_history(base, quote.get_alloy_address(), start, end - 1).await?;
+        let candles = build_candles(&samples, start, interval_ms);
+
+        Ok(GetPriceHistoryResponse {
+            base: ApiToken::from(token),
+            quote: ApiToken::from(quote),
+            interval_ms,
+            candles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use state::storage::tx::price_history::PriceSample;
+
+    use super::build_candles;
+
+    /// Build a sample at the given timestamp and price
+    fn sample(timestamp: u64, price: f64) -> PriceSample {
+        PriceSample { timestamp, price }
+    }
+
+    /// Tests summarizing samples into candles
+    #[test]
+    fn test_build_candles() {
+        let samples = [
+            sample(1_000, 10.),
+            sample(1_200, 13.),
+            sample(1_400, 9.),
+            sample(1_800, 11.),
+            // No samples in [2_000, 3_000)
+            sample(3_500, 20.),
+        ];
+
+        let candles = build_candles(&samples, 1_000 /* start */, 1_000 /* interval_ms */);
+        assert_eq!(candles.len(), 2);
+
+        let first = &candles[0];
+        assert_eq!((first.start, first.end), (1_000, 2_000));
+        assert_eq!((first.open, first.high, first.low, first.close), (10., 13., 9., 11.));
+        assert_eq!(first.median, 10.5);
+        assert_eq!(first.num_samples, 4);
+
+        let second = &candles[1];
+        assert_eq!((second.start, second.end), (3_000, 4_000));
+        assert_eq!((second.open, second.close, second.median), (20., 20., 20.));
+        assert_eq!(second.num_samples, 1);
+    }
+}
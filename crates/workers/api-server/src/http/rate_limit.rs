@@ -1,10 +1,21 @@
-//! Per wallet task rate limiting implementation
+//! Rate limiting implementations for the HTTP API
+//!
+//! Defines a per wallet task rate limiter, and a per client request rate
+//! limiter that the router applies to every route
 
 use crate::error::ApiServerError;
+use hyper::Method;
 use ratelimit_meter::{DirectRateLimiter, LeakyBucket};
-use std::{collections::HashMap, num::NonZeroU32, time::Duration};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    num::NonZeroU32,
+    time::{Duration, Instant},
+};
+use types_account::TenantId;
 use types_core::AccountId;
 use util::concurrency::{AsyncShared, new_async_shared};
+use uuid::Uuid;
 
 /// The rate limiter type for a single wallet
 type WalletLimiter = DirectRateLimiter<LeakyBucket>;
@@ -70,6 +81,165 @@ impl WalletTaskRateLimiter {
     }
 }
 
+// ------------------------
+// | Request Rate Limiter |
+// ------------------------
+
+/// The number of seconds in a minute
+const SECONDS_PER_MINUTE: f64 = 60.;
+/// The number of client buckets above which full buckets are pruned
+///
+/// A full bucket is indistinguishable from a fresh one, so pruning them bounds
+/// memory without loosening any client's limit
+const MAX_TRACKED_BUCKETS: usize = 100_000;
+
+/// The class of a route, which determines the budget it draws from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RouteClass {
+    /// A route that only reads state
+    Read,
+    /// A route that mutates state
+    Write,
+}
+
+impl RouteClass {
+    /// Get the class of routes served under the given method
+    pub fn from_method(method: &Method) -> Self {
+        if *method == Method::GET { Self::Read } else { Self::Write }
+    }
+}
+
+/// The client a request is rate limited against
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    /// A tenant, identified by the key it signs requests with
+    ///
+    /// All of a tenant's requests share its budget, whichever account they
+    /// access
+    Tenant(TenantId),
+    /// A client identified by the API key it signs requests with
+    ApiKey(Uuid),
+    /// A client identified by its source IP
+    Ip(IpAddr),
+}
+
+/// The outcome of a rate limit check, reported to clients in response headers
+#[derive(Copy, Clone, Debug)]
+pub struct RateLimitStatus {
+    /// The number of requests per minute the client is allowed
+    pub limit: u32,
+    /// The number of requests the client may make before being limited
+    pub remaining: u32,
+    /// The time until the client may retry, if the request was limited
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitStatus {
+    /// Whether the request was rate limited
+    pub fn is_limited(&self) -> bool {
+        self.retry_after.is_some()
+    }
+}
+
+/// A token bucket for a single client and route class
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    /// The number of tokens in the bucket
+    tokens: f64,
+    /// The last time the bucket was refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    fn new(capacity: u32) -> Self {
+        Self { tokens: capacity as f64, last_refill: Instant::now() }
+    }
+
+    /// Refill the bucket for the time elapsed since the last refill
+    fn refill(&mut self, capacity: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        let refill = elapsed * capacity as f64 / SECONDS_PER_MINUTE;
+        self.tokens = (self.tokens + refill).min(capacity as f64);
+        self.last_refill = now;
+    }
+
+    /// Whether the bucket is full as of its last refill
+    fn is_full(&self, capacity: u32) -> bool {
+        self.tokens >= capacity as f64
+    }
+}
+
+/// A token bucket rate limiter over API clients
+///
+/// Each client holds one bucket per route class, sized to the class's
+/// per-minute budget and refilled continuously. A budget of zero disables
+/// limiting for that class
+#[derive(Clone)]
+pub struct RequestRateLimiter {
+    /// The buckets of each client and route class
+    buckets: AsyncShared<HashMap<(RateLimitKey, RouteClass), TokenBucket>>,
+    /// The number of read requests per minute allowed each client
+    read_limit: u32,
+    /// The number of write requests per minute allowed each client
+    write_limit: u32,
+}
+
+impl RequestRateLimiter {
+    /// Create a new request rate limiter with the given per-minute budgets
+    pub fn new(read_limit: u32, write_limit: u32) -> Self {
+        Self { buckets: new_async_shared(HashMap::new()), read_limit, write_limit }
+    }
+
+    /// Take a token from the client's bucket for the given route class
+    ///
+    /// Returns `None` if the route class is not rate limited
+    pub async fn check(&self, key: RateLimitKey, class: RouteClass) -> Option<RateLimitStatus> {
+        let limit = match class {
+            RouteClass::Read => self.read_limit,
+            RouteClass::Write => self.write_limit,
+        };
+        if limit == 0 {
+            return None;
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.write().await;
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            self.prune_full_buckets(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry((key, class)).or_insert_with(|| TokenBucket::new(limit));
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1. {
+            bucket.tokens -= 1.;
+            let remaining = bucket.tokens.floor() as u32;
+            return Some(RateLimitStatus { limit, remaining, retry_after: None });
+        }
+
+        // Wait for the bucket to refill to a single token
+        let deficit = 1. - bucket.tokens;
+        let retry_after = Duration::from_secs_f64(deficit * SECONDS_PER_MINUTE / limit as f64);
+        Some(RateLimitStatus { limit, remaining: 0, retry_after: Some(retry_after) })
+    }
+
+    /// Remove the buckets that have refilled completely
+    fn prune_full_buckets(
+        &self,
+        buckets: &mut HashMap<(RateLimitKey, RouteClass), TokenBucket>,
+        now: Instant,
+    ) {
+        buckets.retain(|(_, class), bucket| {
+            let limit = match class {
+                RouteClass::Read => self.read_limit,
+                RouteClass::Write => self.write_limit,
+            };
+            bucket.refill(limit, now);
+            !bucket.is_full(limit)
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,4 +279,36 @@ mod tests {
         assert!(limiter.check_rate_limit(account1).await.is_err());
         assert!(limiter.check_rate_limit(account2).await.is_ok());
     }
+
+    /// Test that the request rate limiter limits each client and route class
+    /// separately
+    #[tokio::test]
+    async fn test_request_rate_limit() {
+        let limiter = RequestRateLimiter::new(2, 1);
+        let client1 = RateLimitKey::ApiKey(Uuid::new_v4());
+        let client2 = RateLimitKey::Ip(IpAddr::from([127, 0, 0, 1]));
+
+        let status = limiter.check(client1.clone(), RouteClass::Read).await.unwrap();
+        assert_eq!((status.limit, status.remaining), (2, 1));
+        assert!(!limiter.check(client1.clone(), RouteClass::Read).await.unwrap().is_limited());
+
+        let status = limiter.check(client1.clone(), RouteClass::Read).await.unwrap();
+        assert!(status.is_limited());
+        assert_eq!(status.remaining, 0);
+
+        // The write budget and other clients are unaffected
+        assert!(!limiter.check(client1.clone(), RouteClass::Write).await.unwrap().is_limited());
+        assert!(limiter.check(client1, RouteClass::Write).await.unwrap().is_limited());
+        assert!(!limiter.check(client2, RouteClass::Read).await.unwrap().is_limited());
+    }
+
+    /// Test that a zero budget disables the request rate limiter
+    #[tokio::test]
+    async fn test_request_rate_limit_disabled() {
+        let limiter = RequestRateLimiter::new(0, 1);
+        let client = RateLimitKey::Ip(IpAddr::from([127, 0, 0, 1]));
+        for _ in 0..10 {
+            assert!(limiter.check(client.clone(), RouteClass::Read).await.is_none());
+        }
+    }
 }
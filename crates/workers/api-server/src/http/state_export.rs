@@ -0,0 +1,77 @@
+//! Uploads of state exports to S3
+//!
+//! Exports are always written to the local export directory first; when a
+//! bucket is given, each file is then uploaded under
+//! `<prefix>/<export_id>/<file name>`, and the local files are removed once
+//! the upload finishes. Credentials are read from the environment, as for the
+//! snapshot sidecar
+
+use std::path::Path;
+
+use aws_sdk_s3::{Client as S3Client, primitives::ByteStream};
+use state::export::TableExport;
+use util::{log_task, logging::Outcome};
+use uuid::Uuid;
+
+use crate::{
+    error::{ApiServerError, internal_error},
+    logging::Task,
+};
+
+/// Upload the files of an export, returning the URI of each in order
+pub(crate) async fn upload_export(
+    bucket: &str,
+    prefix: Option<&str>,
+    export_id: Uuid,
+    exports: &[TableExport],
+) -> Result<Vec<String>, ApiServerError> {
+    let aws_config = aws_config::load_from_env().await;
+    let client = S3Client::new(&aws_config);
+
+    let mut uris = Vec::with_capacity(exports.len());
+    for export in exports {
+        let file_name = export
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| internal_error(format!("invalid export path: {:?}", export.path)))?;
+        let key = export_key(prefix, export_id, file_name);
+
+        let body = ByteStream::from_path(&export.path).await.map_err(internal_error)?;
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(&key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| internal_error(format!("failed to upload {key}: {e}")))?;
+        uris.push(format!("s3://{bucket}/{key}"));
+    }
+
+    Ok(uris)
+}
+
+/// Remove the local directory of an export
+///
+/// The local files only stage an upload, so they are removed whether or not
+/// the upload succeeded; a failed export is retried from scratch
+pub(crate) async fn remove_local_export(dir: &Path) {
+    if let Err(e) = tokio::fs::remove_dir_all(dir).await {
+        log_task!(
+            Task::UploadStateExport,
+            Outcome::Failed,
+            path = %dir.display(),
+            error = %e,
+            "failed to remove local export"
+        );
+    }
+}
+
+/// The key under which an export's file is uploaded
+fn export_key(prefix: Option<&str>, export_id: Uuid, file_name: &str) -> String {
+    match prefix.map(|p| p.trim_matches('/')).filter(|p| !p.is_empty()) {
+        Some(prefix) => format!("{prefix}/{export_id}/{file_name}"),
+        None => format!("{export_id}/{file_name}"),
+    }
+}
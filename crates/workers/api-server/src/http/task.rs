@@ -3,21 +3,53 @@
 use async_trait::async_trait;
 use external_api::{
     EmptyRequestResponse,
-    http::task::{GetTaskByIdResponse, GetTasksResponse},
+    http::task::{GetTaskByIdResponse, GetTaskHistoryResponse, GetTasksResponse},
+    types::{ApiTask, ApiTaskDescription},
 };
 use hyper::HeaderMap;
+use state::State;
+use types_tasks::{HistoricalTask, HistoricalTaskDescription};
 
 use crate::{
-    error::ApiServerError,
+    error::{ApiServerError, bad_request},
+    param_parsing::{
+        parse_account_id_from_params, parse_cursor_from_query_params,
+        parse_limit_from_query_params, parse_task_history_filter_from_query_params,
+    },
     router::{QueryParams, TypedHandler, UrlParams},
 };
 
+/// The default number of tasks returned in a page of task history
+const DEFAULT_TASK_HISTORY_PAGE_SIZE: usize = 50;
+/// The maximum number of tasks returned in a page of task history
+const MAX_TASK_HISTORY_PAGE_SIZE: usize = 500;
+
 // ------------------
 // | Error Messages |
 // ------------------
 
 /// Error message for not implemented
 const ERR_NOT_IMPLEMENTED: &str = "not implemented";
+/// Error message emitted when a page limit is out of range
+const ERR_INVALID_PAGE_LIMIT: &str = "limit must be between 1 and 500";
+
+/// Convert a historical task to its API representation
+pub(crate) fn to_api_task(task: HistoricalTask) -> ApiTask {
+    let task_info = match task.task_info {
+        HistoricalTaskDescription::NewAccount => ApiTaskDescription::CreateAccount,
+        HistoricalTaskDescription::Deposit { .. } => ApiTaskDescription::Deposit,
+        HistoricalTaskDescription::CreateBalance { .. } => ApiTaskDescription::CreateBalance,
+        HistoricalTaskDescription::CreateOrder { .. } => ApiTaskDescription::CreateOrder,
+        HistoricalTaskDescription::RefreshAccount { .. } => ApiTaskDescription::SyncAccount,
+    };
+
+    ApiTask {
+        id: task.id,
+        state: task.state.display_description(),
+        created_at: task.created_at,
+        task_info,
+    }
+}
 
 // ------------------
 // | Task Handlers  |
@@ -74,3 +106,48 @@ impl TypedHandler for GetTaskByIdHandler {
         Err(ApiServerError::not_implemented(ERR_NOT_IMPLEMENTED))
     }
 }
+
+/// Handler for GET /v2/account/:account_id/task-history
+///
+/// Pages through the account's running and historical tasks. Accepts the
+/// `cursor` and `limit` query params for pagination, and `task_type`,
+/// `status`, `created_after`, and `created_before` to filter the tasks
+pub struct GetTaskHistoryHandler {
+    /// A handle to the relayer state
+    state: State,
+}
+
+impl GetTaskHistoryHandler {
+    /// Constructor
+    pub fn new(state: State) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for GetTaskHistoryHandler {
+    type Request = EmptyRequestResponse;
+    type Response = GetTaskHistoryResponse;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        params: UrlParams,
+        query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        let account_id = parse_account_id_from_params(&params)?;
+        let cursor = parse_cursor_from_query_params(&query_params)?;
+        let filter = parse_task_history_filter_from_query_params(&query_params)?;
+        let limit =
+            parse_limit_from_query_params(&query_params)?.unwrap_or(DEFAULT_TASK_HISTORY_PAGE_SIZE);
+        if limit == 0 || limit > MAX_TASK_HISTORY_PAGE_SIZE {
+            return Err(bad_request(ERR_INVALID_PAGE_LIMIT));
+        }
+
+        let (tasks, next_cursor) =
+            self.state.get_task_history_page(&account_id, filter, cursor, limit).await?;
+        let tasks = tasks.into_iter().map(to_api_task).collect();
+        Ok(GetTaskHistoryResponse { tasks, next_cursor })
+    }
+}
@@ -14,7 +14,9 @@ pub mod error;
 mod health;
 pub mod http;
 mod logging;
+mod openapi;
 mod param_parsing;
+mod prometheus;
 mod router;
 mod websocket;
 pub mod worker;
@@ -14,8 +14,36 @@ pub enum Task {
     RegisterRoute,
     /// Refreshing the token remapping from the configured repository.
     RefreshTokenMapping,
+    /// Adding a token to the token remapping via the admin API.
+    AddToken,
     /// Refreshing the match fees from the darkpool contract.
     RefreshMatchFees,
+    /// Setting a feature flag via the admin API.
+    SetFeatureFlag,
+    /// Adding or removing a matching blackout window via the admin API.
+    SetMatchingBlackout,
+    /// Forcibly expiring a peer via the admin API.
+    ExpirePeer,
+    /// Adjusting the heartbeat intervals via the admin API.
+    SetHeartbeatIntervals,
+    /// Adjusting the peer access lists via the admin API.
+    SetPeerAccess,
+    /// Resetting the chain events checkpoint via the admin API.
+    ResetChainEventsCheckpoint,
+    /// Pausing or resuming a task queue via the admin API.
+    PauseTaskQueue,
+    /// Fanning out system bus events to websocket connections.
+    WebsocketFanout,
+    /// Serving a request on the HTTP API.
+    HttpRequest,
+    /// Serving a subscribe/unsubscribe request on the websocket API.
+    WebsocketRequest,
+    /// Admitting a websocket connection within the session limits.
+    WebsocketSession,
+    /// Recording sampled prices in the local price history.
+    RecordPriceHistory,
+    /// Uploading a state export to S3.
+    UploadStateExport,
 }
 
 impl LogTask for Task {
@@ -23,7 +51,21 @@ impl LogTask for Task {
         match self {
             Task::RegisterRoute => "register-route",
             Task::RefreshTokenMapping => "refresh-token-mapping",
+            Task::AddToken => "add-token",
             Task::RefreshMatchFees => "refresh-match-fees",
+            Task::SetFeatureFlag => "set-feature-flag",
+            Task::SetMatchingBlackout => "set-matching-blackout",
+            Task::ExpirePeer => "expire-peer",
+            Task::SetHeartbeatIntervals => "set-heartbeat-intervals",
+            Task::SetPeerAccess => "set-peer-access",
+            Task::ResetChainEventsCheckpoint => "reset-chain-events-checkpoint",
+            Task::PauseTaskQueue => "pause-task-queue",
+            Task::WebsocketFanout => "websocket-fanout",
+            Task::HttpRequest => "http-request",
+            Task::WebsocketRequest => "websocket-request",
+            Task::WebsocketSession => "websocket-session",
+            Task::RecordPriceHistory => "record-price-history",
+            Task::UploadStateExport => "upload-state-export",
         }
     }
 }
@@ -0,0 +1,288 @@
+//! Generation of an OpenAPI spec from the router's registered handlers
+//!
+//! Each route registered on the router records its method, path, auth type,
+//! and the request/response types of its `TypedHandler`. The spec is built
+//! from these records once all routes are registered, so it cannot drift from
+//! the routes the server actually serves.
+//!
+//! Request and response bodies are described by the JSON schemas of their
+//! types. Named types become component schemas, which are shared by every
+//! operation that references them
+
+use std::{any::type_name, collections::BTreeMap};
+
+use async_trait::async_trait;
+use external_api::{EmptyRequestResponse, RENEGADE_AUTH_HEADER_NAME};
+use hyper::{HeaderMap, Method};
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings};
+use serde_json::{Value, json};
+
+use crate::{
+    auth::AuthType,
+    error::ApiServerError,
+    router::{QueryParams, TypedHandler, UrlParams},
+};
+
+/// The OpenAPI version of the generated spec
+const OPENAPI_VERSION: &str = "3.0.3";
+/// The title of the generated spec
+const SPEC_TITLE: &str = "Renegade Relayer API";
+/// The name of the security scheme for account authenticated routes
+const ACCOUNT_AUTH_SCHEME: &str = "accountAuth";
+/// The name of the security scheme for admin authenticated routes
+const ADMIN_AUTH_SCHEME: &str = "adminAuth";
+/// The name of the security scheme for tenant authenticated routes
+const TENANT_AUTH_SCHEME: &str = "tenantAuth";
+
+// ---------
+// | Types |
+// ---------
+
+/// The type of a request or response body
+#[derive(Copy, Clone, Debug)]
+pub struct BodyType {
+    /// The full path of the Rust type
+    rust_type: &'static str,
+    /// Generates the type's schema, registering any named types it contains
+    /// as components
+    schema_fn: fn(&mut SchemaGenerator) -> Schema,
+}
+
+impl BodyType {
+    /// Get the body type of a Rust type
+    pub fn of<T: JsonSchema>() -> Self {
+        Self { rust_type: type_name::<T>(), schema_fn: subschema::<T> }
+    }
+
+    /// Whether the body is empty, i.e. serializes to `null`
+    fn is_empty(&self) -> bool {
+        self.rust_type == type_name::<EmptyRequestResponse>()
+    }
+
+    /// The body's schema, a reference to a component schema for named types
+    fn schema(&self, generator: &mut SchemaGenerator) -> Value {
+        match (self.schema_fn)(generator).to_value() {
+            // OpenAPI 3.0 has no boolean schemas; `true` accepts any value
+            Value::Bool(true) => json!({}),
+            schema => schema,
+        }
+    }
+}
+
+/// Generate the schema of a type
+fn subschema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+/// An operation served by the API, i.e. a method on a route
+#[derive(Clone, Debug)]
+pub struct ApiOperation {
+    /// The HTTP method of the operation
+    pub method: Method,
+    /// The route of the operation, in the router's `:param` syntax
+    pub route: String,
+    /// The authentication the operation requires
+    pub auth: AuthType,
+    /// The operation's request body
+    pub request: BodyType,
+    /// The operation's response body
+    pub response: BodyType,
+}
+
+impl ApiOperation {
+    /// The route in OpenAPI's `{param}` syntax, along with its path params
+    fn openapi_path(&self) -> (String, Vec<String>) {
+        let mut params = Vec::new();
+        let segments = self.route.split('/').map(|segment| {
+            match segment.strip_prefix(':').or_else(|| segment.strip_prefix('*')) {
+                Some(param) => {
+                    params.push(param.to_string());
+                    format!("{{{param}}}")
+                },
+                None => segment.to_string(),
+            }
+        });
+
+        let path = segments.collect::<Vec<_>>().join("/");
+        (path, params)
+    }
+
+    /// The operation's security requirements
+    fn security(&self) -> Value {
+        match self.auth {
+            AuthType::None => json!([]),
+            AuthType::Account => json!([{ ACCOUNT_AUTH_SCHEME: [] }]),
+            AuthType::Admin => json!([{ ADMIN_AUTH_SCHEME: [] }]),
+            // Auth is only checked if the account exists
+            AuthType::AccountIfExists => json!([{ ACCOUNT_AUTH_SCHEME: [] }, {}]),
+            AuthType::Tenant => json!([{ TENANT_AUTH_SCHEME: [] }]),
+            // Auth is only checked if the request names a tenant
+            AuthType::TenantIfPresent => json!([{ TENANT_AUTH_SCHEME: [] }, {}]),
+        }
+    }
+
+    /// Build the OpenAPI operation object
+    fn to_openapi(&self, params: &[String], generator: &mut SchemaGenerator) -> Value {
+        let parameters: Vec<Value> = params
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect();
+
+        let response = if self.response.is_empty() {
+            json!({ "description": "Success" })
+        } else {
+            json!({
+                "description": "Success",
+                "content": { "application/json": { "schema": self.response.schema(generator) } },
+            })
+        };
+
+        let mut operation = json!({
+            "parameters": parameters,
+            "security": self.security(),
+            "responses": { "200": response },
+        });
+        if !self.request.is_empty() {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": self.request.schema(generator) } },
+            });
+        }
+
+        operation
+    }
+}
+
+// -------------------
+// | Spec Generation |
+// -------------------
+
+/// Build an OpenAPI spec from the operations registered on the router
+pub fn build_openapi_spec(operations: &[ApiOperation]) -> Value {
+    let mut paths: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+    let mut generator = SchemaSettings::openapi3().into_generator();
+    for op in operations.iter() {
+        let (path, params) = op.openapi_path();
+        let method = op.method.as_str().to_lowercase();
+        paths.entry(path).or_default().insert(method, op.to_openapi(&params, &mut generator));
+    }
+    let schemas = generator.take_definitions(true /* apply_transforms */);
+
+    let auth_scheme =
+        json!({ "type": "apiKey", "in": "header", "name": RENEGADE_AUTH_HEADER_NAME });
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": { "title": SPEC_TITLE, "version": env!("CARGO_PKG_VERSION") },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                ACCOUNT_AUTH_SCHEME: auth_scheme,
+                ADMIN_AUTH_SCHEME: auth_scheme,
+                TENANT_AUTH_SCHEME: auth_scheme,
+            },
+        },
+    })
+}
+
+// -----------
+// | Handler |
+// -----------
+
+/// Handler for the GET /openapi.json route
+pub struct OpenApiHandler {
+    /// The generated spec
+    spec: Value,
+}
+
+impl OpenApiHandler {
+    /// Constructor
+    pub fn new(spec: Value) -> Self {
+        Self { spec }
+    }
+}
+
+#[async_trait]
+impl TypedHandler for OpenApiHandler {
+    type Request = EmptyRequestResponse;
+    type Response = Value;
+
+    async fn handle_typed(
+        &self,
+        _headers: HeaderMap,
+        _req: Self::Request,
+        _params: UrlParams,
+        _query_params: QueryParams,
+    ) -> Result<Self::Response, ApiServerError> {
+        Ok(self.spec.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use external_api::{
+        EmptyRequestResponse,
+        http::{PingResponse, balance::DepositBalanceRequest},
+    };
+    use hyper::Method;
+
+    use crate::auth::AuthType;
+
+    use super::{ApiOperation, BodyType, build_openapi_spec};
+
+    /// Tests that routes and bodies are translated into the spec
+    #[test]
+    fn test_build_spec() {
+        let op = ApiOperation {
+            method: Method::GET,
+            route: "/v2/account/:account_id/orders/:order_id".to_string(),
+            auth: AuthType::Account,
+            request: BodyType::of::<EmptyRequestResponse>(),
+            response: BodyType::of::<Vec<PingResponse>>(),
+        };
+        let spec = build_openapi_spec(&[op]);
+
+        let path = &spec["paths"]["/v2/account/{account_id}/orders/{order_id}"]["get"];
+        assert_eq!(path["parameters"].as_array().unwrap().len(), 2);
+        assert!(path.get("requestBody").is_none());
+
+        let schema = &path["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["items"]["$ref"], "#/components/schemas/PingResponse");
+
+        let ping = &spec["components"]["schemas"]["PingResponse"];
+        assert_eq!(ping["type"], "object");
+        assert_eq!(ping["properties"]["timestamp"]["type"], "integer");
+    }
+
+    /// Tests that bodies are described field by field, with string encoded
+    /// fields typed as strings
+    #[test]
+    fn test_field_schemas() {
+        let op = ApiOperation {
+            method: Method::POST,
+            route: "/v2/account/:account_id/balances/:mint/deposit".to_string(),
+            auth: AuthType::Account,
+            request: BodyType::of::<DepositBalanceRequest>(),
+            response: BodyType::of::<EmptyRequestResponse>(),
+        };
+        let spec = build_openapi_spec(&[op]);
+
+        let path = &spec["paths"]["/v2/account/{account_id}/balances/{mint}/deposit"]["post"];
+        let schema = &path["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(schema["$ref"], "#/components/schemas/DepositBalanceRequest");
+        assert!(path["responses"]["200"].get("content").is_none());
+
+        let request = &spec["components"]["schemas"]["DepositBalanceRequest"];
+        let required = request["required"].as_array().unwrap();
+        assert!(required.contains(&"amount".into()));
+        assert_eq!(request["properties"]["amount"]["type"], "string");
+    }
+}
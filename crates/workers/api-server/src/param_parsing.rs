@@ -7,10 +7,14 @@
 use alloy::primitives::Address;
 use circuit_types::Amount;
 use constants::Scalar;
-use types_account::MatchingPoolName;
-use types_core::{AccountId, Token};
+use external_api::types::ApiOrderAction;
+use types_account::{
+    MatchingPoolName,
+    order::{ORDER_TAG_SEPARATOR, OrderFilter},
+};
+use types_core::{AccountId, FeatureFlag, Token};
 use types_gossip::{ClusterId, WrappedPeerId};
-use types_tasks::TaskIdentifier;
+use types_tasks::{TaskHistoryFilter, TaskIdentifier};
 use util::hex::address_from_hex_string;
 use uuid::Uuid;
 
@@ -35,12 +39,28 @@ const ERR_CLUSTER_ID_PARSE: &str = "could not parse cluster id";
 const ERR_PEER_ID_PARSE: &str = "could not parse peer id";
 /// Error message displayed when parsing a task ID from URL fails
 const ERR_TASK_ID_PARSE: &str = "could not parse task id";
+/// Error message displayed when parsing an API key ID from URL fails
+const ERR_API_KEY_ID_PARSE: &str = "could not parse api key id";
 /// Error message displayed when parsing a matching pool name from URL fails
 const ERR_MATCHING_POOL_PARSE: &str = "could not parse matching pool name";
+/// Error message displayed when a feature flag cannot be parsed from URL
+const ERR_FEATURE_FLAG_PARSE: &str = "unknown feature flag";
+/// Error message displayed when a blackout window ID cannot be parsed from URL
+const ERR_BLACKOUT_WINDOW_ID_PARSE: &str = "could not parse blackout window id";
 /// Error message displayed when an invalid token is parsed from a URL param
 const ERR_INVALID_TOKEN_PARSE: &str = "invalid token";
 /// Error message displayed when parsing a list of tickers from a query string
 const ERR_TICKERS_PARSE: &str = "could not parse tickers";
+/// Error message displayed when a task history cursor cannot be parsed
+const ERR_CURSOR_PARSE: &str = "could not parse cursor";
+/// Error message displayed when a page limit cannot be parsed
+const ERR_LIMIT_PARSE: &str = "could not parse limit";
+/// Error message displayed when a timestamp query param cannot be parsed
+const ERR_TIMESTAMP_PARSE: &str = "could not parse timestamp";
+/// Error message displayed when a candle interval cannot be parsed
+const ERR_INTERVAL_PARSE: &str = "could not parse interval";
+/// Error message displayed when an order action is missing from a query
+const ERR_ORDER_ACTION_MISSING: &str = "missing order action";
 
 // ----------------
 // | URL Captures |
@@ -58,12 +78,44 @@ const CLUSTER_ID_URL_PARAM: &str = "cluster_id";
 const PEER_ID_URL_PARAM: &str = "peer_id";
 /// The :task_id param in a URL
 const TASK_ID_URL_PARAM: &str = "task_id";
+/// The :key_id param in a URL
+const API_KEY_ID_URL_PARAM: &str = "key_id";
 /// The :matching_pool param in a URL / query string
 const MATCHING_POOL_PARAM: &str = "matching_pool";
+/// The :flag param in a URL
+const FEATURE_FLAG_URL_PARAM: &str = "flag";
+/// The :window_id param in a URL
+const BLACKOUT_WINDOW_ID_URL_PARAM: &str = "window_id";
 /// The tickers param in a query string
 const TICKERS_PARAM: &str = "tickers";
 /// The non_blocking param in a query string
 const NON_BLOCKING_PARAM: &str = "non_blocking";
+/// The cursor param in a query string
+const CURSOR_PARAM: &str = "cursor";
+/// The limit param in a query string
+const LIMIT_PARAM: &str = "limit";
+/// The task_type param in a query string
+const TASK_TYPE_PARAM: &str = "task_type";
+/// The status param in a query string
+const STATUS_PARAM: &str = "status";
+/// The created_after param in a query string
+const CREATED_AFTER_PARAM: &str = "created_after";
+/// The created_before param in a query string
+const CREATED_BEFORE_PARAM: &str = "created_before";
+/// The order action param in a query string
+const ACTION_PARAM: &str = "action";
+/// The amount param in a query string
+const AMOUNT_PARAM: &str = "amount";
+/// The start param in a query string
+const START_PARAM: &str = "start";
+/// The end param in a query string
+const END_PARAM: &str = "end";
+/// The interval param in a query string
+const INTERVAL_PARAM: &str = "interval";
+/// The client order id param in a query string
+const CLIENT_ORDER_ID_PARAM: &str = "client_order_id";
+/// The tags param in a query string
+const TAGS_PARAM: &str = "tags";
 
 // -----------
 // | Parsing |
@@ -159,6 +211,15 @@ pub(super) fn parse_task_id_from_params(
         .map_err(|_| bad_request(ERR_TASK_ID_PARSE))
 }
 
+/// A helper to parse out an API key ID from a URL param
+pub(super) fn parse_api_key_id_from_params(params: &UrlParams) -> Result<Uuid, ApiServerError> {
+    params
+        .get(API_KEY_ID_URL_PARAM)
+        .ok_or_else(|| bad_request(ERR_API_KEY_ID_PARSE))?
+        .parse()
+        .map_err(|_| bad_request(ERR_API_KEY_ID_PARSE))
+}
+
 /// A helper to parse out a matching pool name from a URL param
 pub(super) fn parse_matching_pool_from_url_params(
     params: &UrlParams,
@@ -166,6 +227,28 @@ pub(super) fn parse_matching_pool_from_url_params(
     params.get(MATCHING_POOL_PARAM).ok_or_else(|| bad_request(ERR_MATCHING_POOL_PARSE)).cloned()
 }
 
+/// A helper to parse out a feature flag from a URL param
+pub(super) fn parse_feature_flag_from_params(
+    params: &UrlParams,
+) -> Result<FeatureFlag, ApiServerError> {
+    params
+        .get(FEATURE_FLAG_URL_PARAM)
+        .ok_or_else(|| bad_request(ERR_FEATURE_FLAG_PARSE))?
+        .parse()
+        .map_err(|_| bad_request(ERR_FEATURE_FLAG_PARSE))
+}
+
+/// A helper to parse out a blackout window ID from a URL param
+pub(super) fn parse_blackout_window_id_from_params(
+    params: &UrlParams,
+) -> Result<Uuid, ApiServerError> {
+    params
+        .get(BLACKOUT_WINDOW_ID_URL_PARAM)
+        .ok_or_else(|| bad_request(ERR_BLACKOUT_WINDOW_ID_PARSE))?
+        .parse()
+        .map_err(|_| bad_request(ERR_BLACKOUT_WINDOW_ID_PARSE))
+}
+
 // --- Query Params --- //
 
 /// A helper to parse out a matching pool name from a query string
@@ -192,3 +275,99 @@ pub(super) fn should_block_on_task(params: &QueryParams) -> bool {
         params.get(NON_BLOCKING_PARAM).is_some_and(|v| v.eq_ignore_ascii_case("true"));
     !non_blocking
 }
+
+/// Parse a pagination cursor from the query params, if one is given
+pub(super) fn parse_cursor_from_query_params(
+    params: &QueryParams,
+) -> Result<Option<TaskIdentifier>, ApiServerError> {
+    params
+        .get(CURSOR_PARAM)
+        .map(|c| Uuid::parse_str(c).map_err(|_| bad_request(ERR_CURSOR_PARSE)))
+        .transpose()
+}
+
+/// Parse a page limit from the query params, if one is given
+pub(super) fn parse_limit_from_query_params(
+    params: &QueryParams,
+) -> Result<Option<usize>, ApiServerError> {
+    params.get(LIMIT_PARAM).map(|l| l.parse().map_err(|_| bad_request(ERR_LIMIT_PARSE))).transpose()
+}
+
+/// Parse a task history filter from the query params
+///
+/// Each filter field is optional; missing fields match every task
+pub(super) fn parse_task_history_filter_from_query_params(
+    params: &QueryParams,
+) -> Result<TaskHistoryFilter, ApiServerError> {
+    let kind = params.get(TASK_TYPE_PARAM).map(|k| k.parse().map_err(bad_request)).transpose()?;
+    let status = params.get(STATUS_PARAM).map(|s| s.parse().map_err(bad_request)).transpose()?;
+    let parse_timestamp = |param: &str| {
+        params
+            .get(param)
+            .map(|t| t.parse().map_err(|_| bad_request(ERR_TIMESTAMP_PARSE)))
+            .transpose()
+    };
+
+    let created_after = parse_timestamp(CREATED_AFTER_PARAM)?;
+    let created_before = parse_timestamp(CREATED_BEFORE_PARAM)?;
+    Ok(TaskHistoryFilter { kind, status, created_after, created_before })
+}
+
+/// Parse an order filter from the query params
+///
+/// Tags are given as a comma separated list, all of which an order must carry
+pub(super) fn parse_order_filter_from_query_params(params: &QueryParams) -> OrderFilter {
+    let client_order_id = params.get(CLIENT_ORDER_ID_PARAM).cloned();
+    let tags = params
+        .get(TAGS_PARAM)
+        .map(|tags| {
+            tags.split(ORDER_TAG_SEPARATOR).filter(|t| !t.is_empty()).map(String::from).collect()
+        })
+        .unwrap_or_default();
+    OrderFilter { client_order_id, tags }
+}
+
+/// Parse an order action from the query params
+pub(super) fn parse_order_action_from_query_params(
+    params: &QueryParams,
+) -> Result<ApiOrderAction, ApiServerError> {
+    params
+        .get(ACTION_PARAM)
+        .ok_or_else(|| bad_request(ERR_ORDER_ACTION_MISSING))?
+        .parse()
+        .map_err(bad_request)
+}
+
+/// Parse an amount from the query params, if one is given
+pub(super) fn parse_amount_from_query_params(
+    params: &QueryParams,
+) -> Result<Option<Amount>, ApiServerError> {
+    params.get(AMOUNT_PARAM).map(|a| parse_amount_from_string(a)).transpose()
+}
+
+/// Parse the start and end timestamps of a time window from the query params
+///
+/// Either bound may be omitted
+pub(super) fn parse_time_window_from_query_params(
+    params: &QueryParams,
+) -> Result<(Option<u64>, Option<u64>), ApiServerError> {
+    let parse_timestamp = |param: &str| {
+        params
+            .get(param)
+            .map(|t| t.parse().map_err(|_| bad_request(ERR_TIMESTAMP_PARSE)))
+            .transpose()
+    };
+
+    Ok((parse_timestamp(START_PARAM)?, parse_timestamp(END_PARAM)?))
+}
+
+/// Parse an interval length in milliseconds from the query params, if one is
+/// given
+pub(super) fn parse_interval_from_query_params(
+    params: &QueryParams,
+) -> Result<Option<u64>, ApiServerError> {
+    params
+        .get(INTERVAL_PARAM)
+        .map(|i| i.parse().map_err(|_| bad_request(ERR_INTERVAL_PARSE)))
+        .transpose()
+}
@@ -0,0 +1,54 @@
+//! The Prometheus scrape, exporting the relayer's metrics
+//!
+//! The scrape is served by the health server, on its dedicated port, rather
+//! than by the public API
+
+use http_body_util::Full;
+use hyper::{Response, StatusCode, body::Bytes as BytesBody, header::CONTENT_TYPE};
+use price_state::PriceStreamStates;
+use util::telemetry::prometheus::render_prometheus_metrics;
+
+/// The content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// The metric describing the age of the latest price on a price stream
+const PRICE_STALENESS_METRIC: &str = "price_staleness_ms";
+/// The metric tag naming the exchange of a price stream
+const EXCHANGE_METRIC_TAG: &str = "exchange";
+/// The metric tag naming the base token of a price stream
+const BASE_METRIC_TAG: &str = "base";
+/// The metric tag naming the quote token of a price stream
+const QUOTE_METRIC_TAG: &str = "quote";
+
+/// The error message emitted when the metrics registry is not installed
+const ERR_METRICS_DISABLED: &str = "metrics are not enabled";
+
+/// Render the relayer's metrics in the Prometheus text exposition format
+///
+/// Responds 503 if the metrics registry is not installed
+pub(crate) fn scrape_metrics(price_streams: &PriceStreamStates) -> Response<Full<BytesBody>> {
+    record_price_staleness(price_streams);
+    let Some(body) = render_prometheus_metrics() else {
+        return Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Full::new(BytesBody::from(ERR_METRICS_DISABLED)))
+            .unwrap();
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)
+        .body(Full::new(BytesBody::from(body)))
+        .unwrap()
+}
+
+/// Record the age of the latest price on each price stream
+fn record_price_staleness(price_streams: &PriceStreamStates) {
+    for ((exchange, base, quote), age_ms) in price_streams.stream_ages_ms() {
+        let labels = [
+            (EXCHANGE_METRIC_TAG, exchange.to_string()),
+            (BASE_METRIC_TAG, base.ticker_or_addr()),
+            (QUOTE_METRIC_TAG, quote.ticker_or_addr()),
+        ];
+        metrics::gauge!(PRICE_STALENESS_METRIC, &labels).set(age_ms as f64);
+    }
+}
@@ -1,28 +1,42 @@
 //! Abstracts routing logic from the HTTP server
 
-use std::{collections::HashMap, iter};
+use std::{
+    collections::HashMap,
+    iter,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
+use external_api::RENEGADE_API_KEY_ID_HEADER_NAME;
 use http_body_util::{BodyExt, Full};
 use hyper::{
     HeaderMap, Method, Request, Response, StatusCode, Uri,
     body::{Bytes as BytesBody, Incoming as IncomingBody},
-    header::CONTENT_TYPE,
+    header::{CONTENT_TYPE, HeaderValue, RETRY_AFTER},
 };
 use itertools::Itertools;
 use matchit::{Params, Router as MatchRouter};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
 use state::State;
 use tracing::{debug, instrument};
+use types_account::TenantId;
 use types_core::HmacKey;
+use types_runtime::ShutdownProgress;
 use util::log_task;
 use util::logging::Outcome;
 use util::telemetry::propagation::set_parent_span_from_headers;
+use util::telemetry::request_id::{REQUEST_ID_HEADER, record_request_id, request_id_from_headers};
+use uuid::Uuid;
 
 use crate::{
-    auth::{AuthMiddleware, AuthType},
-    error::bad_request,
+    auth::{AuthMiddleware, AuthType, request_tenant},
+    error::{ERR_SHUTTING_DOWN, bad_request, service_unavailable},
+    http::rate_limit::{RateLimitKey, RateLimitStatus, RequestRateLimiter, RouteClass},
     logging::Task,
+    openapi::{ApiOperation, BodyType, build_openapi_spec},
 };
 
 use super::{error::ApiServerError, param_parsing::parse_account_id_from_params};
@@ -43,11 +57,82 @@ pub(super) const ERR_WALLET_NOT_FOUND: &str = "wallet not found";
 const ERR_INVALID_QUERY_PARAMS: &str = "invalid query params";
 /// Error message returned when the path is invalid
 const ERR_INVALID_PATH: &str = "invalid path";
+/// The header reporting a client's per-minute request budget
+const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+/// The header reporting the requests a client may make before being limited
+const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+/// The header carrying the originating client IP when behind a proxy
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
 
 // -----------
 // | Helpers |
 // -----------
 
+/// Identify the authenticated client a request is rate limited against
+///
+/// Requests made on behalf of a tenant share the tenant's budget. The tenant
+/// and API key headers are only trusted once the request's signature has been
+/// verified against them, so this must not be called before authentication.
+/// Returns `None` for routes that do not identify a client
+fn authenticated_rate_limit_key(auth_type: AuthType, headers: &HeaderMap) -> Option<RateLimitKey> {
+    if matches!(auth_type, AuthType::None | AuthType::Admin) {
+        return None;
+    }
+
+    if let Some(tenant) = request_tenant(headers) {
+        return Some(RateLimitKey::Tenant(tenant));
+    }
+
+    if auth_type != AuthType::Account {
+        return None;
+    }
+
+    headers
+        .get(RENEGADE_API_KEY_ID_HEADER_NAME)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .map(RateLimitKey::ApiKey)
+}
+
+/// Select the more restrictive of two rate limit checks to report to a client
+fn tightest_rate_limit(
+    a: Option<RateLimitStatus>,
+    b: Option<RateLimitStatus>,
+) -> Option<RateLimitStatus> {
+    a.into_iter().chain(b).min_by_key(|status| status.remaining)
+}
+
+/// Refuse a write request once the node begins draining for shutdown
+fn check_accepts_request(
+    shutdown: &ShutdownProgress,
+    method: &Method,
+) -> Result<(), ApiServerError> {
+    if shutdown.is_draining() && RouteClass::from_method(method) == RouteClass::Write {
+        return Err(service_unavailable(ERR_SHUTTING_DOWN));
+    }
+
+    Ok(())
+}
+
+/// Resolve the IP of the client that made a request
+///
+/// If the API is served behind a trusted proxy, this is the last forwarded
+/// entry, the one appended by the nearest proxy; earlier entries are client
+/// controlled
+pub(crate) fn client_ip(
+    remote_addr: SocketAddr,
+    headers: &HeaderMap,
+    trust_forwarded_for: bool,
+) -> IpAddr {
+    headers
+        .get(FORWARDED_FOR_HEADER)
+        .filter(|_| trust_forwarded_for)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit(',').next())
+        .and_then(|ip| ip.trim().parse().ok())
+        .unwrap_or(remote_addr.ip())
+}
+
 /// Builds an empty HTTP 400 (Bad Request) response
 pub(super) fn build_400_response(err: String) -> Response<ResponseBody> {
     build_response_from_status_code(StatusCode::BAD_REQUEST, err)
@@ -68,11 +153,7 @@ pub(super) fn build_response_from_status_code(
     status_code: StatusCode,
     err: String,
 ) -> Response<ResponseBody> {
-    Response::builder()
-        .status(status_code)
-        .header("Access-Control-Allow-Origin", "*")
-        .body(Full::new(BytesBody::from(err)))
-        .unwrap()
+    Response::builder().status(status_code).body(Full::new(BytesBody::from(err))).unwrap()
 }
 
 /// Parse key value pairs from query params string
@@ -90,6 +171,50 @@ fn parse_query_params(query_str: &str) -> Result<QueryParams, &'static str> {
     Ok(params)
 }
 
+/// Emit an access log line for a served request
+///
+/// Server errors are logged as failures, all other responses as successes
+fn log_access(
+    method: &Method,
+    route: &Uri,
+    remote_addr: SocketAddr,
+    status: StatusCode,
+    latency: Duration,
+    request_id: &str,
+) {
+    let outcome = if status.is_server_error() { Outcome::Failed } else { Outcome::Ok };
+    log_task!(
+        Task::HttpRequest,
+        outcome,
+        subject = %request_id,
+        method = %method,
+        path = %route.path(),
+        status = status.as_u16(),
+        latency_ms = latency.as_millis() as u64,
+        remote_addr = %remote_addr,
+        "served http request"
+    );
+}
+
+/// Report a rate limit check in a response's headers
+fn add_rate_limit_headers(resp: &mut Response<ResponseBody>, status: &RateLimitStatus) {
+    let headers = resp.headers_mut();
+    headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(status.limit));
+    headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(status.remaining));
+    if let Some(retry_after) = status.retry_after {
+        // Round up so that clients do not retry before a token is available
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        headers.insert(RETRY_AFTER, HeaderValue::from(secs));
+    }
+}
+
+/// Build the response to a rate limited request
+fn rate_limited_response(status: &RateLimitStatus) -> Response<ResponseBody> {
+    let mut resp: Response<ResponseBody> = ApiServerError::RateLimitExceeded.into();
+    add_rate_limit_headers(&mut resp, status);
+    resp
+}
+
 // -------------------------
 // | Trait Implementations |
 // -------------------------
@@ -106,6 +231,9 @@ pub trait Handler: Send + Sync {
         headers: HeaderMap,
         body: BytesBody,
     ) -> Response<ResponseBody>;
+
+    /// The types of the handler's request and response bodies
+    fn body_types(&self) -> (BodyType, BodyType);
 }
 
 /// A handler that has associated Request/Response type information attached to
@@ -115,9 +243,9 @@ pub trait Handler: Send + Sync {
 #[async_trait]
 pub trait TypedHandler: Send + Sync {
     /// The request type that the handler consumes
-    type Request: DeserializeOwned + for<'de> Deserialize<'de>;
+    type Request: DeserializeOwned + for<'de> Deserialize<'de> + JsonSchema;
     /// The response type that the handler returns
-    type Response: Serialize + Send;
+    type Response: Serialize + Send + JsonSchema;
 
     /// The handler logic, translate request into response
     async fn handle_typed(
@@ -134,11 +262,15 @@ pub trait TypedHandler: Send + Sync {
 /// response into a body
 #[async_trait]
 impl<
-    Req: DeserializeOwned + for<'de> Deserialize<'de> + Send,
-    Resp: Serialize,
+    Req: DeserializeOwned + for<'de> Deserialize<'de> + JsonSchema + Send,
+    Resp: Serialize + JsonSchema,
     T: TypedHandler<Request = Req, Response = Resp>,
 > Handler for T
 {
+    fn body_types(&self) -> (BodyType, BodyType) {
+        (BodyType::of::<Req>(), BodyType::of::<Resp>())
+    }
+
     async fn handle(
         &self,
         url_params: UrlParams,
@@ -160,17 +292,11 @@ impl<
 
         // Forward to the typed handler
         let res = self.handle_typed(headers, req_body, url_params, query_params).await;
-        let builder = Response::builder()
-            .header("Access-Control-Allow-Origin", "*")
-            .header(CONTENT_TYPE, "application/json");
+        let builder = Response::builder().header(CONTENT_TYPE, "application/json");
         match res {
             Ok(resp) => {
-                // Serialize the response into a body. We explicitly allow for all cross-origin
-                // requests, as users connecting to a locally-run node have a different origin
-                // port.
-
-                // TODO: Either remove this in the future, or ensure that no sensitive
-                // information can leak from cross-origin requests.
+                // Serialize the response into a body, CORS headers are added by the
+                // server's CORS policy
                 let body_bytes = serde_json::to_vec(&resp).unwrap();
                 builder.body(Full::new(BytesBody::from(body_bytes))).unwrap()
             },
@@ -187,16 +313,33 @@ pub struct Router {
     /// Holds a tuple of the handler and a boolean indicating whether
     /// wallet authentication (sk_root) signature is required for the request
     router: MatchRouter<(Box<dyn Handler>, AuthType)>,
+    /// The operations registered on the router, used to generate its OpenAPI
+    /// spec
+    operations: Vec<ApiOperation>,
     /// The auth middleware, authenticates a variety of requests
     auth_middleware: AuthMiddleware,
+    /// The per client request rate limiter
+    rate_limiter: RequestRateLimiter,
+    /// Whether to identify clients by the `X-Forwarded-For` header
+    trust_forwarded_for: bool,
+    /// The relayer's shutdown progress, writes are refused while it drains
+    shutdown: ShutdownProgress,
 }
 
 impl Router {
     /// Create a new router with no routes established
-    pub fn new(admin_key: Option<HmacKey>, state: State) -> Self {
+    pub fn new(
+        admin_key: Option<HmacKey>,
+        tenant_keys: HashMap<TenantId, HmacKey>,
+        state: State,
+        rate_limiter: RequestRateLimiter,
+        trust_forwarded_for: bool,
+        shutdown: ShutdownProgress,
+    ) -> Self {
         let router = MatchRouter::new();
-        let auth_middleware = AuthMiddleware::new(admin_key, state);
-        Self { router, auth_middleware }
+        let auth_middleware = AuthMiddleware::new(admin_key, tenant_keys, state);
+        let operations = Vec::new();
+        Self { router, auth_middleware, rate_limiter, trust_forwarded_for, shutdown, operations }
     }
 
     /// Helper to build a routable path from a method and a concrete route
@@ -225,13 +368,21 @@ impl Router {
         handler: H,
     ) {
         debug!("Attached handler to route {route} with method {method}");
-        let full_route = Self::create_full_route(method, route);
+        let (request, response) = handler.body_types();
+        let method = method.clone();
+        let full_route = Self::create_full_route(&method, route.clone());
+        self.operations.push(ApiOperation { method, route, auth, request, response });
 
         self.router
             .insert(full_route, (Box::new(handler), auth))
             .expect("error attaching handler to route");
     }
 
+    /// Generate an OpenAPI spec for the routes registered so far
+    pub fn openapi_spec(&self) -> Value {
+        build_openapi_spec(&self.operations)
+    }
+
     /// Add an unauthenticated route
     pub fn add_unauthenticated_route<H: Handler + 'static>(
         &mut self,
@@ -252,6 +403,16 @@ impl Router {
         self.add_route(method, route, AuthType::Account, handler);
     }
 
+    /// Add a route with tenant authentication
+    pub fn add_tenant_authenticated_route<H: Handler + 'static>(
+        &mut self,
+        method: &Method,
+        route: String,
+        handler: H,
+    ) {
+        self.add_route(method, route, AuthType::Tenant, handler);
+    }
+
     /// Add a route with admin authentication
     pub fn add_admin_authenticated_route<H: Handler + 'static>(
         &mut self,
@@ -276,15 +437,21 @@ impl Router {
         http.status_code,
         http.method = %method,
         http.route = %route,
+        request_id,
     ))]
     pub async fn handle_req(
         &self,
         method: Method,
         route: Uri,
+        remote_addr: SocketAddr,
         req: Request<IncomingBody>,
     ) -> Response<Full<BytesBody>> {
+        let start = Instant::now();
+        let request_id = request_id_from_headers(req.headers());
+        record_request_id(&request_id);
+
         let path = route.path();
-        let res = if method == Method::OPTIONS {
+        let mut res = if method == Method::OPTIONS {
             // If the request is an options request, handle it directly
             self.handle_options_req(path)
         } else {
@@ -293,7 +460,18 @@ impl Router {
             if let Ok(matched_path) = self.router.at(&full_route) {
                 let (handler, auth) = matched_path.value;
                 let params = matched_path.params;
-                match self.handle_req_inner(route, params, *auth, req, handler.as_ref()).await {
+                let res = self
+                    .handle_req_inner(
+                        &method,
+                        route,
+                        params,
+                        *auth,
+                        remote_addr,
+                        req,
+                        handler.as_ref(),
+                    )
+                    .await;
+                match res {
                     Ok(res) => res,
                     Err(e) => e.into(),
                 }
@@ -303,18 +481,28 @@ impl Router {
         };
 
         tracing::Span::current().record("http.status_code", res.status().as_str());
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            res.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+
+        log_access(&method, &route, remote_addr, res.status(), start.elapsed(), &request_id);
         res
     }
 
     /// Helper for handling a request
+    #[allow(clippy::too_many_arguments)]
     async fn handle_req_inner<'a>(
         &self,
+        method: &Method,
         route: Uri,
         params: Params<'a, 'a>,
         auth_type: AuthType,
+        remote_addr: SocketAddr,
         req: Request<IncomingBody>,
         handler: &dyn Handler,
     ) -> Result<Response<ResponseBody>, ApiServerError> {
+        check_accepts_request(&self.shutdown, method)?;
+
         // Clone the params to take ownership
         let mut params_map = HashMap::with_capacity(params.len());
         for (key, value) in params.iter() {
@@ -335,20 +523,40 @@ impl Router {
             None => return Err(bad_request(ERR_INVALID_PATH)),
         };
 
-        // Collect the headers and body
+        // Collect the headers
         let headers = req.headers().to_owned();
         // Setup tracing parent span from propagated headers, if any
         set_parent_span_from_headers(&headers);
+
+        // Check the client IP's rate limit before authenticating the request or
+        // reading its body
+        let ip_limit = self.check_ip_rate_limit(method, auth_type, remote_addr, &headers).await;
+        if let Some(status) = ip_limit.filter(RateLimitStatus::is_limited) {
+            return Ok(rate_limited_response(&status));
+        }
+
         let body = req.into_body().collect().await.map_err(bad_request)?;
         let body_bytes = body.to_bytes();
 
-        // Check auth then forward to handler
+        // Check auth and the authenticated client's rate limit, then forward to
+        // handler
         self.check_auth(auth_type, path_with_query, &params_map, &headers, &body_bytes).await?;
-        let resp = handler.handle(params_map, query_params, headers, body_bytes).await;
+        let client_limit = self.check_client_rate_limit(method, auth_type, &headers).await;
+        if let Some(status) = client_limit.filter(RateLimitStatus::is_limited) {
+            return Ok(rate_limited_response(&status));
+        }
+
+        let mut resp = handler.handle(params_map, query_params, headers, body_bytes).await;
+        if let Some(status) = tightest_rate_limit(ip_limit, client_limit) {
+            add_rate_limit_headers(&mut resp, &status);
+        }
         Ok(resp)
     }
 
     /// Handle an options request
+    ///
+    /// The preflight's origin and header grants are added by the server's CORS
+    /// policy
     fn handle_options_req(&self, route: &str) -> Response<ResponseBody> {
         // Get the set of allowed methods for this route
         let allowed_methods = vec![Method::GET, Method::POST]
@@ -366,15 +574,47 @@ impl Router {
         let allowed_methods_str = allowed_methods.iter().map(|method| method.as_str()).join(",");
 
         Response::builder()
-            .header("Access-Control-Allow-Origin", "*")
-            .header("Access-Control-Allow-Headers", "*")
             .header("Access-Control-Allow-Methods", allowed_methods_str)
-            .header("Access-Control-Allow-Credentials", "true")
             .header("Access-Control-Max-Age", PREFLIGHT_CACHE_TIME)
             .body(Full::new(BytesBody::from("")))
             .unwrap()
     }
 
+    /// Take a token from the client IP's rate limit budget for the route
+    ///
+    /// This check runs before the request is authenticated, so it keys on the
+    /// client's address alone. Admin requests are not rate limited. Returns
+    /// `None` if the request is not subject to a rate limit
+    async fn check_ip_rate_limit(
+        &self,
+        method: &Method,
+        auth_type: AuthType,
+        remote_addr: SocketAddr,
+        headers: &HeaderMap,
+    ) -> Option<RateLimitStatus> {
+        if auth_type == AuthType::Admin {
+            return None;
+        }
+
+        let ip = client_ip(remote_addr, headers, self.trust_forwarded_for);
+        self.rate_limiter.check(RateLimitKey::Ip(ip), RouteClass::from_method(method)).await
+    }
+
+    /// Take a token from the authenticated client's rate limit budget for the
+    /// route
+    ///
+    /// Returns `None` if the route does not identify a client or is not
+    /// subject to a rate limit
+    async fn check_client_rate_limit(
+        &self,
+        method: &Method,
+        auth_type: AuthType,
+        headers: &HeaderMap,
+    ) -> Option<RateLimitStatus> {
+        let key = authenticated_rate_limit_key(auth_type, headers)?;
+        self.rate_limiter.check(key, RouteClass::from_method(method)).await
+    }
+
     /// Validate a signature of the request's body by sk_root of the wallet
     async fn check_auth(
         &self,
@@ -401,9 +641,103 @@ impl Router {
             AuthType::Admin => {
                 self.auth_middleware.authenticate_admin_request(path, headers, req_body)?;
             },
+            AuthType::Tenant => {
+                self.auth_middleware
+                    .authenticate_required_tenant_request(path, headers, req_body)?;
+            },
+            AuthType::TenantIfPresent => {
+                self.auth_middleware.authenticate_tenant_request(path, headers, req_body)?;
+            },
             AuthType::None => {},
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, SocketAddr};
+
+    use external_api::{RENEGADE_API_KEY_ID_HEADER_NAME, RENEGADE_TENANT_ID_HEADER_NAME};
+    use hyper::{HeaderMap, Method};
+    use types_runtime::{ShutdownPhase, ShutdownProgress};
+    use uuid::Uuid;
+
+    use super::{
+        FORWARDED_FOR_HEADER, authenticated_rate_limit_key, check_accepts_request, client_ip,
+        tightest_rate_limit,
+    };
+    use crate::{
+        auth::AuthType,
+        http::rate_limit::{RateLimitKey, RateLimitStatus},
+    };
+
+    /// The remote address requests are received from
+    fn remote_addr() -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], 8080))
+    }
+
+    /// Tests that tenant and API key headers key the rate limit only on
+    /// authenticated routes
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_authenticated_rate_limit_key__headers() {
+        let key_id = Uuid::new_v4();
+        let mut headers = HeaderMap::new();
+        headers.insert(RENEGADE_API_KEY_ID_HEADER_NAME, key_id.to_string().parse().unwrap());
+
+        let key = authenticated_rate_limit_key(AuthType::Account, &headers);
+        assert_eq!(key, Some(RateLimitKey::ApiKey(key_id)));
+        assert_eq!(authenticated_rate_limit_key(AuthType::None, &headers), None);
+        assert_eq!(authenticated_rate_limit_key(AuthType::Admin, &headers), None);
+
+        // A tenant's requests share its budget, whichever API key they use
+        headers.insert(RENEGADE_TENANT_ID_HEADER_NAME, "tenant-a".parse().unwrap());
+        let key = authenticated_rate_limit_key(AuthType::Account, &headers);
+        assert_eq!(key, Some(RateLimitKey::Tenant("tenant-a".to_string())));
+        assert_eq!(authenticated_rate_limit_key(AuthType::None, &headers), None);
+    }
+
+    /// Tests that the more restrictive of the IP and client rate limits is
+    /// reported
+    #[test]
+    fn test_tightest_rate_limit() {
+        let status = |remaining| RateLimitStatus { limit: 10, remaining, retry_after: None };
+        let (ip, client) = (status(5), status(2));
+
+        assert_eq!(tightest_rate_limit(Some(ip), Some(client)).unwrap().remaining, 2);
+        assert_eq!(tightest_rate_limit(Some(ip), None).unwrap().remaining, 5);
+        assert!(tightest_rate_limit(None, None).is_none());
+    }
+
+    /// Tests that forwarded IPs are only trusted if configured, and that the
+    /// entry appended by the nearest proxy is used
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_client_ip__forwarded() {
+        let mut headers = HeaderMap::new();
+        headers.insert(FORWARDED_FOR_HEADER, "1.1.1.1, 2.2.2.2".parse().unwrap());
+
+        let proxied = IpAddr::from([2, 2, 2, 2]);
+        assert_eq!(client_ip(remote_addr(), &headers, true /* trust */), proxied);
+        assert_eq!(client_ip(remote_addr(), &headers, false /* trust */), remote_addr().ip());
+
+        // Unparsable entries fall back to the remote address
+        headers.insert(FORWARDED_FOR_HEADER, "not-an-ip".parse().unwrap());
+        assert_eq!(client_ip(remote_addr(), &headers, true /* trust */), remote_addr().ip());
+    }
+
+    /// Tests that writes are refused once the node begins draining for
+    /// shutdown, while reads are still served
+    #[test]
+    fn test_check_accepts_request() {
+        let shutdown = ShutdownProgress::new();
+        assert!(check_accepts_request(&shutdown, &Method::POST).is_ok());
+
+        shutdown.advance(ShutdownPhase::StoppingWrites);
+        assert!(check_accepts_request(&shutdown, &Method::GET).is_ok());
+        assert!(check_accepts_request(&shutdown, &Method::POST).is_err());
+        assert!(check_accepts_request(&shutdown, &Method::DELETE).is_err());
+    }
+}
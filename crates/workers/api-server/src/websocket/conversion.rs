@@ -1,18 +1,35 @@
 //! Conversion from system bus messages to websocket message bodies
 
 use external_api::types::{
-    AdminBalanceUpdateMessage, AdminOrderUpdateMessage, ApiAdminOrder, ApiBalance, ApiOrder,
-    ApiOrderCore, ApiOrderUpdateType, ApiPartialOrderFill, ApiTimestampedPriceFloat, FeeTake,
-    FillMessage, ServerWebsocketMessageBody,
+    AccountEventMessage, AdminBalanceUpdateMessage, AdminOrderUpdateMessage,
+    AdminPriceDeviationMessage, AdminPriceReporterConnectionMessage, ApiAccountEvent,
+    ApiAdminOrder, ApiBalance, ApiOrder, ApiOrderCore, ApiOrderUpdateType, ApiPartialOrderFill,
+    ApiPriceReporterConnectionState, ApiTask, ApiTaskDescription, ApiTimestampedPriceFloat,
+    BalanceSweepMessage, BalanceUpdateMessage, FeeTake, FillMessage, OrderUpdateMessage,
+    ServerWebsocketMessageBody, TaskUpdateMessage,
 };
-use system_bus::{AdminOrderUpdateType, SystemBusMessage};
+use system_bus::{
+    AdminOrderUpdateType, PriceReporterConnectionState, SystemBusMessage, TaskStatus,
+};
+use types_tasks::TaskDescriptor;
+
+use crate::error::ApiServerError;
+
+/// The error message emitted when a message type is not intended for
+/// websocket consumption
+const ERR_NOT_WEBSOCKET_MESSAGE: &str =
+    "invalid websocket bus subscription: message type not intended for websocket";
+/// The error message emitted when an account event wraps a message type that
+/// is not journaled
+const ERR_EVENT_NOT_JOURNALED: &str = "invalid account event: message type not journaled";
 
 /// Convert a system bus message to a websocket message body
 ///
-/// # Panics
-/// Panics if the message type is not intended for websocket consumption
-pub fn system_bus_message_to_websocket_body(msg: SystemBusMessage) -> ServerWebsocketMessageBody {
-    match msg {
+/// Errors if the message type is not intended for websocket consumption
+pub fn system_bus_message_to_websocket_body(
+    msg: SystemBusMessage,
+) -> Result<ServerWebsocketMessageBody, ApiServerError> {
+    let body = match msg {
         SystemBusMessage::AdminOrderUpdate {
             account_id,
             order,
@@ -29,23 +46,58 @@ pub fn system_bus_message_to_websocket_body(msg: SystemBusMessage) -> ServerWebs
         SystemBusMessage::AdminBalanceUpdate { account_id, balance } => {
             convert_admin_balance_update(account_id, *balance)
         },
+        SystemBusMessage::BalanceUpdate { account_id: _, balance } => {
+            convert_balance_update(*balance)
+        },
+        SystemBusMessage::BalanceSweepRequested { account_id: _, mint, amount, cold_address } => {
+            ServerWebsocketMessageBody::BalanceSweep(BalanceSweepMessage {
+                mint,
+                amount,
+                cold_address,
+            })
+        },
         SystemBusMessage::Fill { account_id: _, order, fill_amount, filled } => {
             convert_fill(*order, fill_amount, filled)
         },
+        SystemBusMessage::TaskStatusUpdate { status } => convert_task_status_update(status),
+        SystemBusMessage::AccountEvent { cursor, event } => convert_account_event(cursor, *event)?,
+        SystemBusMessage::PriceSourceDeviationUpdate { exchange, base, quote, deviation } => {
+            ServerWebsocketMessageBody::AdminPriceDeviation(AdminPriceDeviationMessage {
+                exchange,
+                base: base.get_addr(),
+                quote: quote.get_addr(),
+                deviation,
+            })
+        },
+        SystemBusMessage::PriceReporterConnectionUpdate { connection, state } => {
+            ServerWebsocketMessageBody::AdminPriceReporterConnection(
+                AdminPriceReporterConnectionMessage {
+                    connection,
+                    state: convert_price_reporter_connection_state(state),
+                },
+            )
+        },
         // Other message types are not intended for websocket consumption
         SystemBusMessage::HandshakeInProgress { .. }
         | SystemBusMessage::HandshakeCompleted { .. }
         | SystemBusMessage::NewPeer { .. }
         | SystemBusMessage::PeerExpired { .. }
-        | SystemBusMessage::TaskStatusUpdate { .. }
+        | SystemBusMessage::QueuedTaskStatusUpdate { .. }
         | SystemBusMessage::AccountUpdate { .. }
         | SystemBusMessage::ExternalOrderQuote { .. }
         | SystemBusMessage::ExternalOrderBundle { .. }
         | SystemBusMessage::NoExternalMatchFound
-        | SystemBusMessage::OwnerIndexChanged { .. } => {
-            panic!("invalid websocket bus subscription: message type not intended for websocket")
+        | SystemBusMessage::OwnerIndexChanged { .. }
+        | SystemBusMessage::ChainEventsCheckpointReset
+        | SystemBusMessage::PriceStreamStalenessUpdate { .. }
+        | SystemBusMessage::FeatureFlagUpdated { .. } => {
+            return Err(ApiServerError::WebsocketServerFailure(
+                ERR_NOT_WEBSOCKET_MESSAGE.to_string(),
+            ));
         },
-    }
+    };
+
+    Ok(body)
 }
 
 /// Convert an AdminOrderUpdate system bus message to a websocket message body
@@ -82,6 +134,12 @@ fn convert_admin_balance_update(
     })
 }
 
+/// Convert a BalanceUpdate system bus message to a websocket message body
+fn convert_balance_update(balance: types_account::balance::Balance) -> ServerWebsocketMessageBody {
+    let balance: ApiBalance = balance.into();
+    ServerWebsocketMessageBody::BalanceUpdate(BalanceUpdateMessage { balance })
+}
+
 /// Convert a Fill system bus message to a websocket message body.
 ///
 /// The relayer's per-order state does not yet carry a price/fee/tx_hash for
@@ -103,6 +161,71 @@ fn convert_fill(
     ServerWebsocketMessageBody::Fill(FillMessage { fill, order: api_order_core, filled })
 }
 
+/// Convert a TaskStatusUpdate system bus message to a websocket message body
+fn convert_task_status_update(status: TaskStatus) -> ServerWebsocketMessageBody {
+    let task = convert_task_status(status);
+    ServerWebsocketMessageBody::TaskUpdate(TaskUpdateMessage { task })
+}
+
+/// Convert a task status to the API's task type
+#[allow(clippy::needless_pass_by_value)]
+fn convert_task_status(status: TaskStatus) -> ApiTask {
+    ApiTask {
+        id: status.id,
+        state: status.state.display_description(),
+        created_at: status.created_at,
+        task_info: convert_task_descriptor(&status.descriptor),
+    }
+}
+
+/// Convert a journaled account event to a websocket message body
+///
+/// Errors if the event type is not journaled
+fn convert_account_event(
+    cursor: u64,
+    event: SystemBusMessage,
+) -> Result<ServerWebsocketMessageBody, ApiServerError> {
+    let event = match event {
+        SystemBusMessage::AdminOrderUpdate { order, update_type, .. } => {
+            let order: ApiOrder = (*order).into();
+            let update_type = convert_admin_order_update_type(update_type);
+            ApiAccountEvent::OrderUpdate(OrderUpdateMessage { order, update_type })
+        },
+        SystemBusMessage::AdminBalanceUpdate { balance, .. } => {
+            let balance: ApiBalance = (*balance).into();
+            ApiAccountEvent::BalanceUpdate(BalanceUpdateMessage { balance })
+        },
+        SystemBusMessage::TaskStatusUpdate { status } => {
+            ApiAccountEvent::TaskUpdate(TaskUpdateMessage { task: convert_task_status(status) })
+        },
+        _ => {
+            return Err(ApiServerError::WebsocketServerFailure(
+                ERR_EVENT_NOT_JOURNALED.to_string(),
+            ));
+        },
+    };
+
+    Ok(ServerWebsocketMessageBody::AccountEvent(AccountEventMessage { cursor, event }))
+}
+
+/// Convert a task descriptor to the API's task description
+fn convert_task_descriptor(descriptor: &TaskDescriptor) -> ApiTaskDescription {
+    match descriptor {
+        TaskDescriptor::NewAccount(_) => ApiTaskDescription::CreateAccount,
+        TaskDescriptor::NodeStartup(_) | TaskDescriptor::RefreshAccount(_) => {
+            ApiTaskDescription::SyncAccount
+        },
+        TaskDescriptor::Deposit(_) => ApiTaskDescription::Deposit,
+        TaskDescriptor::CreateBalance(_) => ApiTaskDescription::CreateBalance,
+        TaskDescriptor::CreateOrder(_) => ApiTaskDescription::CreateOrder,
+        TaskDescriptor::CancelOrder(_) => ApiTaskDescription::CancelOrder,
+        TaskDescriptor::SettleInternalMatch(_)
+        | TaskDescriptor::SettleExternalMatch(_)
+        | TaskDescriptor::SettlePrivateMatch(_) => ApiTaskDescription::SettleMatch,
+        TaskDescriptor::Withdraw(_) => ApiTaskDescription::Withdraw,
+    }
+}
+
 /// Convert an AdminOrderUpdateType to an ApiOrderUpdateType
 #[allow(clippy::needless_pass_by_value)]
 fn convert_admin_order_update_type(update_type: AdminOrderUpdateType) -> ApiOrderUpdateType {
@@ -112,3 +235,39 @@ fn convert_admin_order_update_type(update_type: AdminOrderUpdateType) -> ApiOrde
         AdminOrderUpdateType::Cancelled => ApiOrderUpdateType::Cancelled,
     }
 }
+
+/// Convert a price reporter connection state to its API type
+#[allow(clippy::needless_pass_by_value)]
+fn convert_price_reporter_connection_state(
+    state: PriceReporterConnectionState,
+) -> ApiPriceReporterConnectionState {
+    match state {
+        PriceReporterConnectionState::Connected => ApiPriceReporterConnectionState::Connected,
+        PriceReporterConnectionState::Reconnecting { attempt, backoff_ms } => {
+            ApiPriceReporterConnectionState::Reconnecting { attempt, backoff_ms }
+        },
+        PriceReporterConnectionState::Throttled { wait_ms } => {
+            ApiPriceReporterConnectionState::Throttled { wait_ms }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use system_bus::SystemBusMessage;
+
+    use super::system_bus_message_to_websocket_body;
+
+    /// Tests that messages which cannot be sent on a websocket are refused
+    /// with an error
+    #[test]
+    fn test_unsupported_message() {
+        assert!(
+            system_bus_message_to_websocket_body(SystemBusMessage::NoExternalMatchFound).is_err()
+        );
+
+        let event = Box::new(SystemBusMessage::NoExternalMatchFound);
+        let msg = SystemBusMessage::AccountEvent { cursor: 1, event };
+        assert!(system_bus_message_to_websocket_body(msg).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! A bounded, in-memory journal of account events
+//!
+//! The journal listens to the order, balance, and task update topics on the
+//! system bus, assigns each account event a cursor, and republishes it on the
+//! account's event topic. Clients resubscribing with the cursor of the last
+//! event they processed are replayed the journaled events after it.
+//!
+//! The journal is local to a relayer and is not persisted; cursors are seeded
+//! from the wall clock at startup so that they increase across restarts, and a
+//! cursor older than the journal's oldest event is rejected so that the client
+//! resyncs rather than silently missing events
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, RwLock},
+};
+
+use futures::StreamExt;
+use system_bus::{
+    ADMIN_BALANCE_UPDATES_TOPIC, ADMIN_ORDER_UPDATES_TOPIC, ALL_TASK_UPDATES_TOPIC, SystemBus,
+    SystemBusMessage, account_events_topic,
+};
+use tokio_stream::StreamMap;
+use types_core::AccountId;
+use util::get_current_time_millis;
+
+use crate::error::{ApiServerError, bad_request};
+
+/// The number of events held in the journal
+const EVENT_JOURNAL_CAPACITY: usize = 10_000;
+/// The number of cursors allotted to each millisecond when seeding the journal
+const CURSORS_PER_MILLI: u64 = 1_000;
+/// The error message given when a cursor precedes the journal's oldest event
+const ERR_CURSOR_EXPIRED: &str = "cursor expired, resync required";
+
+/// A journaled account event
+#[derive(Clone, Debug)]
+struct JournaledEvent {
+    /// The cursor of the event
+    cursor: u64,
+    /// The account the event belongs to
+    account_id: AccountId,
+    /// The event
+    event: SystemBusMessage,
+}
+
+/// The journal's events and the next cursor to assign
+#[derive(Debug)]
+struct JournalInner {
+    /// The cursor assigned to the next event
+    next_cursor: u64,
+    /// The journaled events, in cursor order
+    events: VecDeque<JournaledEvent>,
+}
+
+/// The account event journal
+#[derive(Clone, Debug)]
+pub struct AccountEventJournal {
+    /// The journal's events
+    inner: Arc<RwLock<JournalInner>>,
+    /// A handle to the system bus
+    system_bus: SystemBus,
+}
+
+impl AccountEventJournal {
+    /// Constructor
+    pub fn new(system_bus: SystemBus) -> Self {
+        let next_cursor = get_current_time_millis() * CURSORS_PER_MILLI;
+        let inner = JournalInner { next_cursor, events: VecDeque::new() };
+        Self { inner: Arc::new(RwLock::new(inner)), system_bus }
+    }
+
+    /// Journal account events as they are published on the system bus
+    pub async fn run(self) {
+        let mut readers = StreamMap::new();
+        for topic in
+            [ADMIN_ORDER_UPDATES_TOPIC, ADMIN_BALANCE_UPDATES_TOPIC, ALL_TASK_UPDATES_TOPIC]
+        {
+            readers.insert(topic, self.system_bus.subscribe(topic.to_string()));
+        }
+
+        while let Some((_, message)) = readers.next().await {
+            let (account_id, event) = match message {
+                SystemBusMessage::AdminOrderUpdate { account_id, .. }
+                | SystemBusMessage::AdminBalanceUpdate { account_id, .. } => (account_id, message),
+                SystemBusMessage::QueuedTaskStatusUpdate { queue_key, status } => {
+                    (queue_key, SystemBusMessage::TaskStatusUpdate { status })
+                },
+                _ => continue,
+            };
+
+            self.record(account_id, event);
+        }
+    }
+
+    /// Record an event and publish it on the account's event topic
+    ///
+    /// The event is journaled before it is published, so a subscriber sees it
+    /// either in its replay or on its subscription, possibly both
+    fn record(&self, account_id: AccountId, event: SystemBusMessage) {
+        let cursor = {
+            let mut inner = self.inner.write().expect("event journal lock poisoned");
+            let cursor = inner.next_cursor;
+            inner.next_cursor += 1;
+
+            if inner.events.len() == EVENT_JOURNAL_CAPACITY {
+                inner.events.pop_front();
+            }
+            inner.events.push_back(JournaledEvent { cursor, account_id, event: event.clone() });
+            cursor
+        }; // inner released
+
+        let msg = SystemBusMessage::AccountEvent { cursor, event: Box::new(event) };
+        self.system_bus.publish(account_events_topic(&account_id), msg);
+    }
+
+    /// Get the account's journaled events after the given cursor
+    pub fn events_after(
+        &self,
+        account_id: AccountId,
+        cursor: u64,
+    ) -> Result<Vec<SystemBusMessage>, ApiServerError> {
+        let inner = self.inner.read().expect("event journal lock poisoned");
+
+        // Events between the cursor and the oldest journaled event may have been
+        // evicted
+        let oldest = inner.events.front().map(|e| e.cursor).unwrap_or(inner.next_cursor);
+        if cursor.saturating_add(1) < oldest {
+            return Err(bad_request(ERR_CURSOR_EXPIRED));
+        }
+
+        let events = inner
+            .events
+            .iter()
+            .filter(|e| e.cursor > cursor && e.account_id == account_id)
+            .map(|e| SystemBusMessage::AccountEvent {
+                cursor: e.cursor,
+                event: Box::new(e.event.clone()),
+            })
+            .collect();
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use system_bus::{SystemBus, SystemBusMessage};
+    use uuid::Uuid;
+
+    use super::{AccountEventJournal, EVENT_JOURNAL_CAPACITY};
+
+    /// Get the cursor of a journaled event
+    fn cursor_of(msg: &SystemBusMessage) -> u64 {
+        match msg {
+            SystemBusMessage::AccountEvent { cursor, .. } => *cursor,
+            _ => panic!("expected an account event"),
+        }
+    }
+
+    /// Tests replaying an account's events after a cursor
+    #[test]
+    fn test_events_after() {
+        let journal = AccountEventJournal::new(SystemBus::new());
+        let (account1, account2) = (Uuid::new_v4(), Uuid::new_v4());
+        for account_id in [account1, account2, account1] {
+            journal.record(account_id, SystemBusMessage::NoExternalMatchFound);
+        }
+
+        let err = journal.events_after(account1, 0).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+
+        let first = journal.inner.read().unwrap().events[0].cursor;
+        let events = journal.events_after(account1, first - 1).unwrap();
+        assert_eq!(events.iter().map(cursor_of).collect::<Vec<_>>(), vec![first, first + 2]);
+
+        let events = journal.events_after(account1, first).unwrap();
+        assert_eq!(events.iter().map(cursor_of).collect::<Vec<_>>(), vec![first + 2]);
+    }
+
+    /// Tests that cursors preceding evicted events are rejected
+    #[test]
+    fn test_cursor_expired() {
+        let journal = AccountEventJournal::new(SystemBus::new());
+        let account_id = Uuid::new_v4();
+        for _ in 0..=EVENT_JOURNAL_CAPACITY {
+            journal.record(account_id, SystemBusMessage::NoExternalMatchFound);
+        }
+
+        let first = journal.inner.read().unwrap().events[0].cursor;
+        assert!(journal.events_after(account_id, first - 2).is_err());
+        assert_eq!(
+            journal.events_after(account_id, first - 1).unwrap().len(),
+            EVENT_JOURNAL_CAPACITY
+        );
+    }
+}
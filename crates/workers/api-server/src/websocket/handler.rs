@@ -7,10 +7,24 @@
 //! Plus, this more closely follows the practice taken in the HTTP router
 
 use async_trait::async_trait;
+use state::State;
 use system_bus::SystemBusMessage;
-use system_bus::{SystemBus, TopicReader};
+use system_bus::{SystemBus, TopicReader, account_events_topic, task_topic};
 
-use crate::{auth::AuthType, error::ApiServerError, router::UrlParams};
+use crate::{
+    auth::AuthType,
+    error::{ApiServerError, bad_request, not_found},
+    param_parsing::{parse_account_id_from_params, parse_task_id_from_params},
+    router::UrlParams,
+};
+
+use super::event_journal::AccountEventJournal;
+
+/// The error message given when a subscribed task is not queued for the account
+const ERR_TASK_NOT_FOUND: &str = "task not found";
+/// The error message given when a cursor is given for a topic that does not
+/// journal its events
+const ERR_CURSOR_UNSUPPORTED: &str = "topic does not support cursors";
 
 /// The main trait that route handlers implement for their topic, handles any
 /// custom logic required to process a websocket subscribe/unsubscribe request
@@ -29,6 +43,18 @@ pub trait WebsocketTopicHandler: Send + Sync {
         route_params: &UrlParams,
     ) -> Result<(), ApiServerError>;
 
+    /// Get the events after the given cursor, to replay to a resubscribing
+    /// client
+    ///
+    /// Only topics that journal their events support cursors
+    async fn handle_replay(
+        &self,
+        _route_params: &UrlParams,
+        _cursor: u64,
+    ) -> Result<Vec<SystemBusMessage>, ApiServerError> {
+        Err(bad_request(ERR_CURSOR_UNSUPPORTED))
+    }
+
     /// The type of authentication required by the route
     fn auth_type(&self) -> AuthType;
 }
@@ -90,3 +116,112 @@ impl WebsocketTopicHandler for DefaultHandler {
         self.auth_type
     }
 }
+
+/// The handler for per-task status updates
+///
+/// Subscribes to the task's bus topic, on which the state applicator publishes
+/// each transition of the task's state. Only tasks still queued for the
+/// subscribing account may be subscribed to
+#[derive(Clone)]
+pub struct TaskStatusHandler {
+    /// A handle to the relayer state
+    state: State,
+    /// A reference to the relayer-global system bus
+    system_bus: SystemBus,
+}
+
+impl TaskStatusHandler {
+    /// Constructor
+    pub fn new(state: State, system_bus: SystemBus) -> Self {
+        Self { state, system_bus }
+    }
+}
+
+#[async_trait]
+impl WebsocketTopicHandler for TaskStatusHandler {
+    /// Check that the task belongs to the account, then subscribe to its topic
+    async fn handle_subscribe_message(
+        &self,
+        _topic: String,
+        route_params: &UrlParams,
+    ) -> Result<TopicReader<SystemBusMessage>, ApiServerError> {
+        let account_id = parse_account_id_from_params(route_params)?;
+        let task_id = parse_task_id_from_params(route_params)?;
+
+        let task = self.state.get_task(&task_id).await?;
+        if !task.is_some_and(|t| t.descriptor.queue_key() == account_id) {
+            return Err(not_found(ERR_TASK_NOT_FOUND));
+        }
+
+        Ok(self.system_bus.subscribe(task_topic(&task_id)))
+    }
+
+    /// Unsubscribe does nothing, the `TopicReader` is cleaned up on drop
+    async fn handle_unsubscribe_message(
+        &self,
+        _topic: String,
+        _route_params: &UrlParams,
+    ) -> Result<(), ApiServerError> {
+        Ok(())
+    }
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::Account
+    }
+}
+
+/// The handler for an account's journaled event stream
+///
+/// Multiplexes the account's order, balance, and task events, each tagged with
+/// a cursor that a resubscribing client may resume from
+#[derive(Clone)]
+pub struct AccountEventsHandler {
+    /// The account event journal
+    journal: AccountEventJournal,
+    /// A reference to the relayer-global system bus
+    system_bus: SystemBus,
+}
+
+impl AccountEventsHandler {
+    /// Constructor
+    pub fn new(journal: AccountEventJournal, system_bus: SystemBus) -> Self {
+        Self { journal, system_bus }
+    }
+}
+
+#[async_trait]
+impl WebsocketTopicHandler for AccountEventsHandler {
+    /// Subscribe to the account's event topic, on which the journal
+    /// republishes each event it records
+    async fn handle_subscribe_message(
+        &self,
+        _topic: String,
+        route_params: &UrlParams,
+    ) -> Result<TopicReader<SystemBusMessage>, ApiServerError> {
+        let account_id = parse_account_id_from_params(route_params)?;
+        Ok(self.system_bus.subscribe(account_events_topic(&account_id)))
+    }
+
+    /// Unsubscribe does nothing, the `TopicReader` is cleaned up on drop
+    async fn handle_unsubscribe_message(
+        &self,
+        _topic: String,
+        _route_params: &UrlParams,
+    ) -> Result<(), ApiServerError> {
+        Ok(())
+    }
+
+    /// Replay the account's journaled events after the cursor
+    async fn handle_replay(
+        &self,
+        route_params: &UrlParams,
+        cursor: u64,
+    ) -> Result<Vec<SystemBusMessage>, ApiServerError> {
+        let account_id = parse_account_id_from_params(route_params)?;
+        self.journal.events_after(account_id, cursor)
+    }
+
+    fn auth_type(&self) -> AuthType {
+        AuthType::Account
+    }
+}
@@ -1,7 +1,10 @@
 //! Websocket API server implementation
 
 mod conversion;
+mod event_journal;
 mod handler;
+mod send_queue;
 mod server;
+mod session_limits;
 
 pub use self::server::WebsocketServer;
@@ -0,0 +1,285 @@
+//! A per-connection send queue decoupling websocket writes from the
+//! connection's system bus subscriptions
+//!
+//! System bus topics are broadcast to all of their readers, so a connection
+//! that stops draining its subscriptions backs up the topic for every other
+//! subscriber. Each connection therefore drains its subscriptions into a
+//! bounded queue, and a dedicated task writes the queue onto the websocket.
+//! When a client falls far enough behind that the queue fills, the
+//! connection's slow client policy decides whether to drop new messages or to
+//! close the connection. Responses and replayed events are never dropped; the
+//! connection waits for the client to make room for them instead
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use futures::{Sink, SinkExt};
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender, error::TrySendError},
+    time::timeout,
+};
+use tungstenite::Message;
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{error::ApiServerError, logging::Task};
+
+/// The metric describing the number of messages queued but not yet written,
+/// summed over all websocket connections
+const SEND_QUEUE_LAG_METRIC: &str = "websocket_send_queue_lag";
+/// The metric counting messages dropped for slow websocket clients
+const DROPPED_MESSAGES_METRIC: &str = "websocket_dropped_messages";
+/// The metric counting websocket connections closed for being slow
+const SLOW_CLIENT_CLOSES_METRIC: &str = "websocket_slow_client_closes";
+
+/// How long to wait for a slow client to make room for a message that may not
+/// be dropped before closing its connection
+const RELIABLE_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The ID assigned to the next websocket connection
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The error message given when a slow client's connection is closed
+const ERR_SLOW_CLIENT: &str = "websocket client too slow, closing connection";
+/// The error message given when the connection's writer has stopped
+const ERR_WRITER_STOPPED: &str = "websocket writer stopped";
+
+/// The policy applied to a connection whose send queue is full
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SlowClientPolicy {
+    /// Drop messages that do not fit in the queue
+    Drop,
+    /// Close the connection
+    Close,
+}
+
+impl SlowClientPolicy {
+    /// Get the policy from whether slow clients should be closed
+    pub fn from_close_slow_clients(close_slow_clients: bool) -> Self {
+        if close_slow_clients { Self::Close } else { Self::Drop }
+    }
+}
+
+/// A bounded queue of messages awaiting a write to a websocket connection
+pub struct ConnectionSendQueue {
+    /// The ID of the connection, used to identify it in logs
+    connection_id: String,
+    /// The sender onto the connection's writer task
+    sender: Sender<Message>,
+    /// The policy applied when the queue is full
+    policy: SlowClientPolicy,
+    /// Whether messages have been dropped since the queue was last drained
+    dropping: bool,
+    /// The lag last added to the send queue lag metric
+    reported_lag: usize,
+}
+
+impl ConnectionSendQueue {
+    /// Create a send queue for a connection, spawning the task that writes
+    /// the queue onto the websocket
+    pub fn new<S>(write_stream: S, capacity: usize, policy: SlowClientPolicy) -> Self
+    where
+        S: Sink<Message> + Send + Unpin + 'static,
+    {
+        let connection_id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed).to_string();
+        // A bounded channel requires a non-zero capacity
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        tokio::spawn(Self::write_loop(receiver, write_stream));
+
+        Self { connection_id, sender, policy, dropping: false, reported_lag: 0 }
+    }
+
+    /// The number of messages queued but not yet written to the websocket
+    pub fn lag(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
+    /// Enqueue a message to write to the websocket
+    ///
+    /// Returns an error if the connection should be closed, either because
+    /// its writer stopped or because it is slow under the `Close` policy
+    pub fn enqueue(&mut self, message: Message) -> Result<(), ApiServerError> {
+        let res = match self.sender.try_send(message) {
+            Ok(()) => {
+                self.dropping = false;
+                Ok(())
+            },
+            Err(TrySendError::Full(_)) => self.handle_full_queue(),
+            Err(TrySendError::Closed(_)) => {
+                Err(ApiServerError::WebsocketServerFailure(ERR_WRITER_STOPPED.to_string()))
+            },
+        };
+
+        self.record_lag();
+        res
+    }
+
+    /// Enqueue a message that may not be dropped, such as the response to a
+    /// request or a replayed event, waiting for room in the queue
+    ///
+    /// Returns an error if the connection should be closed, either because
+    /// its writer stopped or because the client did not make room in time
+    pub async fn enqueue_reliable(&mut self, message: Message) -> Result<(), ApiServerError> {
+        let res = match timeout(RELIABLE_SEND_TIMEOUT, self.sender.send(message)).await {
+            Ok(Ok(())) => {
+                self.dropping = false;
+                Ok(())
+            },
+            Ok(Err(_)) => {
+                Err(ApiServerError::WebsocketServerFailure(ERR_WRITER_STOPPED.to_string()))
+            },
+            Err(_) => {
+                log_task!(
+                    Task::WebsocketFanout,
+                    Outcome::Failed,
+                    subject = %self.connection_id,
+                    "websocket client made no room for a reliable message, closing slow client"
+                );
+
+                metrics::counter!(SLOW_CLIENT_CLOSES_METRIC).increment(1);
+                Err(ApiServerError::WebsocketServerFailure(ERR_SLOW_CLIENT.to_string()))
+            },
+        };
+
+        self.record_lag();
+        res
+    }
+
+    /// Apply the slow client policy to a message that does not fit in the
+    /// queue
+    fn handle_full_queue(&mut self) -> Result<(), ApiServerError> {
+        match self.policy {
+            SlowClientPolicy::Drop => {
+                if !self.dropping {
+                    log_task!(
+                        Task::WebsocketFanout,
+                        Outcome::Partial,
+                        subject = %self.connection_id,
+                        "websocket send queue full, dropping messages for slow client"
+                    );
+                    self.dropping = true;
+                }
+
+                metrics::counter!(DROPPED_MESSAGES_METRIC).increment(1);
+                Ok(())
+            },
+            SlowClientPolicy::Close => {
+                log_task!(
+                    Task::WebsocketFanout,
+                    Outcome::Failed,
+                    subject = %self.connection_id,
+                    "websocket send queue full, closing slow client"
+                );
+
+                metrics::counter!(SLOW_CLIENT_CLOSES_METRIC).increment(1);
+                Err(ApiServerError::WebsocketServerFailure(ERR_SLOW_CLIENT.to_string()))
+            },
+        }
+    }
+
+    /// Add the change in the connection's lag to the send queue lag metric
+    fn record_lag(&mut self) {
+        let lag = self.lag();
+        let delta = lag as f64 - self.reported_lag as f64;
+        metrics::gauge!(SEND_QUEUE_LAG_METRIC).increment(delta);
+        self.reported_lag = lag;
+    }
+
+    /// Write queued messages onto the websocket until the queue is dropped or
+    /// a write fails
+    async fn write_loop<S>(mut receiver: Receiver<Message>, mut write_stream: S)
+    where
+        S: Sink<Message> + Unpin,
+    {
+        while let Some(message) = receiver.recv().await {
+            if write_stream.send(message).await.is_err() {
+                break;
+            }
+        }
+
+        // Close the receiver so that the connection observes the stopped writer
+        receiver.close();
+    }
+}
+
+impl Drop for ConnectionSendQueue {
+    fn drop(&mut self) {
+        // Remove the closed connection's lag from the metric
+        metrics::gauge!(SEND_QUEUE_LAG_METRIC).decrement(self.reported_lag as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt, channel::mpsc as sink_channel};
+
+    use super::*;
+
+    /// Build a text message
+    fn text(i: usize) -> Message {
+        Message::Text(i.to_string())
+    }
+
+    /// Tests that a full queue drops messages under the `Drop` policy
+    #[tokio::test]
+    async fn test_drop_policy() {
+        // The client never reads, so the queue fills
+        let (sink, _client) = sink_channel::channel(0);
+        let mut queue = ConnectionSendQueue::new(sink, 2, SlowClientPolicy::Drop);
+        for i in 0..10 {
+            queue.enqueue(text(i)).unwrap();
+        }
+
+        assert!(queue.dropping);
+        assert_eq!(queue.lag(), 2);
+    }
+
+    /// Tests that a full queue closes the connection under the `Close` policy
+    #[tokio::test]
+    async fn test_close_policy() {
+        let (sink, _client) = sink_channel::channel(0);
+        let mut queue = ConnectionSendQueue::new(sink, 2, SlowClientPolicy::Close);
+        let res = (0..10).try_for_each(|i| queue.enqueue(text(i)));
+        assert!(res.is_err());
+    }
+
+    /// Tests that reliable messages wait for the client to make room rather
+    /// than being dropped
+    #[tokio::test]
+    async fn test_reliable_messages_not_dropped() {
+        let (sink, client) = sink_channel::channel(0);
+        let mut queue = ConnectionSendQueue::new(sink, 1, SlowClientPolicy::Drop);
+
+        // The client only begins reading once the queue has filled
+        let reader = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.take(5).collect::<Vec<_>>().await
+        });
+
+        for i in 0..5 {
+            queue.enqueue_reliable(text(i)).await.unwrap();
+        }
+
+        let received = reader.await.unwrap();
+        assert_eq!(received, (0..5).map(text).collect::<Vec<_>>());
+    }
+
+    /// Tests that a client that never makes room for a reliable message is
+    /// closed
+    #[tokio::test(start_paused = true)]
+    async fn test_reliable_message_timeout() {
+        let (sink, _client) = sink_channel::channel(0);
+        let mut queue = ConnectionSendQueue::new(sink, 1, SlowClientPolicy::Drop);
+
+        let res = async {
+            for i in 0..5 {
+                queue.enqueue_reliable(text(i)).await?;
+            }
+            Ok::<_, ApiServerError>(())
+        }
+        .await;
+        assert!(res.is_err());
+    }
+}
@@ -1,36 +1,66 @@
 //! Groups logic for managing websocket connections
 
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 
 use constants::in_bootstrap_mode;
 use external_api::{
     types::ServerWebsocketMessage,
-    websocket::{ClientWebsocketMessage, SubscriptionResponse, WebsocketMessage},
+    websocket::{
+        ClientWebsocketMessage, SubscriptionResponse, WebsocketErrorResponse, WebsocketMessage,
+    },
+};
+use futures::StreamExt;
+use hyper::{
+    HeaderMap,
+    http::{HeaderName, HeaderValue},
 };
-use futures::{SinkExt, StreamExt, stream::SplitSink};
-use hyper::{HeaderMap, http::HeaderValue};
 use matchit::Router;
 use system_bus::{SystemBusMessage, TopicReader};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_stream::StreamMap;
-use tokio_tungstenite::{WebSocketStream, accept_async};
-use tungstenite::Message;
+use tokio_tungstenite::accept_hdr_async;
+use tracing::{Instrument, info_span};
+use tungstenite::{
+    Message,
+    handshake::server::{
+        ErrorResponse as HandshakeErrorResponse, Request as HandshakeRequest,
+        Response as HandshakeResponse,
+    },
+    http::StatusCode as HandshakeStatusCode,
+};
+use util::log_task;
+use util::logging::Outcome;
+use util::telemetry::request_id::{new_request_id, request_id_from_string_headers};
 
 use super::conversion::system_bus_message_to_websocket_body;
 
 use crate::error::ApiServerError;
+use crate::logging::Task;
 use crate::param_parsing::parse_account_id_from_params;
-use crate::router::UrlParams;
+use crate::router::{UrlParams, client_ip};
 use crate::worker::ApiServerConfig;
 use crate::{
     auth::{AuthMiddleware, AuthType},
     error::{bad_request, not_found},
 };
 
-use super::handler::{DefaultHandler, WebsocketTopicHandler};
+use super::event_journal::AccountEventJournal;
+use super::handler::{
+    AccountEventsHandler, DefaultHandler, TaskStatusHandler, WebsocketTopicHandler,
+};
+use super::send_queue::{ConnectionSendQueue, SlowClientPolicy};
+use super::session_limits::{Session, SessionLimit, SessionLimiter};
 
 /// The matchit router with generics specified for websocket use
-type WebsocketRouter = Router<Box<dyn WebsocketTopicHandler>>;
+type WebsocketRouter = Router<WebsocketRoute>;
+/// The cursor of the last journaled event pushed on each topic of a connection
+type TopicCursors = HashMap<String, u64>;
+/// The events replayed to a client on a subscription, and their topic
+type Replay = (String, Vec<SystemBusMessage>);
 
 /// The dummy stream used to seed the websocket subscriptions `StreamMap`
 const DUMMY_SUBSCRIPTION_TOPIC: &str = "dummy-topic";
@@ -38,6 +68,8 @@ const DUMMY_SUBSCRIPTION_TOPIC: &str = "dummy-topic";
 const ERR_INVALID_TOPIC: &str = "invalid topic";
 /// The error message given when a header map cannot be parsed for a request
 const ERR_HEADER_PARSE: &str = "error parsing headers";
+/// The error message given when a connection is refused by a session limit
+const ERR_SESSION_LIMIT: &str = "too many websocket connections";
 
 // ----------
 // | Topics |
@@ -49,10 +81,42 @@ const ADMIN_ORDER_UPDATES_ROUTE: &str = "/v2/admin/orders";
 /// The admin balance updates topic, streams granular balance updates for all
 /// accounts
 const ADMIN_BALANCE_UPDATES_ROUTE: &str = "/v2/admin/balances";
+/// The admin price deviation topic, streams the exchange prices excluded by
+/// the price deviation circuit breaker and their recoveries
+const ADMIN_PRICE_DEVIATIONS_ROUTE: &str = "/v2/admin/price-deviations";
+/// The admin price reporter connections topic, streams the state changes of
+/// the price reporter's connections as they disconnect and reconnect
+const ADMIN_PRICE_REPORTER_CONNECTIONS_ROUTE: &str = "/v2/admin/price-reporter-connections";
 /// Per-account fills topic; streams `Fill` events for orders owned by the
 /// caller's account. The bus topic equals the subscribed URL, which the
 /// applicator constructs via `system_bus::account_fills_topic`.
 const ACCOUNT_FILLS_ROUTE: &str = "/v2/account/:account_id/fills";
+/// Per-account balances topic; streams `BalanceUpdate` events each time one
+/// of the caller's balances is committed to state. The bus topic equals the
+/// subscribed URL, see `system_bus::account_balances_topic`
+const ACCOUNT_BALANCES_ROUTE: &str = "/v2/account/:account_id/balances";
+/// Per-task status topic; streams `TaskUpdate` events for each transition of
+/// a task queued on the caller's account
+const TASK_STATUS_ROUTE: &str = "/v2/account/:account_id/tasks/:task_id";
+/// Per-account journaled event topic; streams the account's order, balance,
+/// and task events with cursors that a resubscribing client may resume from
+const ACCOUNT_EVENTS_ROUTE: &str = "/v2/account/:account_id/events";
+
+/// A topic route registered on the websocket router
+struct WebsocketRoute {
+    /// The route's pattern, used to tag subscription metrics without the
+    /// unbounded cardinality of the topics themselves
+    pattern: &'static str,
+    /// The handler for subscriptions on the route
+    handler: Box<dyn WebsocketTopicHandler>,
+}
+
+impl WebsocketRoute {
+    /// Constructor
+    fn new(pattern: &'static str, handler: Box<dyn WebsocketTopicHandler>) -> Self {
+        Self { pattern, handler }
+    }
+}
 
 // --------------------
 // | Websocket Server |
@@ -67,29 +131,45 @@ pub struct WebsocketServer {
     router: Arc<WebsocketRouter>,
     /// The authentication middleware
     auth_middleware: AuthMiddleware,
+    /// The account event journal
+    journal: AccountEventJournal,
+    /// The limits on concurrent connections
+    session_limiter: SessionLimiter,
 }
 
 impl WebsocketServer {
     /// Create a new websocket server
     pub fn new(config: ApiServerConfig) -> Self {
-        let router = Arc::new(Self::setup_routes(&config));
-        let auth_middleware = AuthMiddleware::new(config.admin_api_key, config.state.clone());
-        Self { config, router, auth_middleware }
+        let journal = AccountEventJournal::new(config.system_bus.clone());
+        let router = Arc::new(Self::setup_routes(&config, &journal));
+        let auth_middleware = AuthMiddleware::new(
+            config.admin_api_key,
+            config.tenant_keys.clone(),
+            config.state.clone(),
+        );
+        let session_limiter = SessionLimiter::new(
+            config.websocket_max_connections,
+            config.websocket_max_connections_per_ip,
+        );
+        Self { config, router, auth_middleware, journal, session_limiter }
     }
 
     /// Setup the websocket routes for the server
-    fn setup_routes(config: &ApiServerConfig) -> WebsocketRouter {
+    fn setup_routes(config: &ApiServerConfig, journal: &AccountEventJournal) -> WebsocketRouter {
         let mut router = WebsocketRouter::new();
 
         // The "/v2/admin/orders" route
         router
             .insert(
                 ADMIN_ORDER_UPDATES_ROUTE,
-                Box::new(DefaultHandler::new_with_remap(
-                    AuthType::Admin,
-                    system_bus::ADMIN_ORDER_UPDATES_TOPIC.to_string(),
-                    config.system_bus.clone(),
-                )),
+                WebsocketRoute::new(
+                    ADMIN_ORDER_UPDATES_ROUTE,
+                    Box::new(DefaultHandler::new_with_remap(
+                        AuthType::Admin,
+                        system_bus::ADMIN_ORDER_UPDATES_TOPIC.to_string(),
+                        config.system_bus.clone(),
+                    )),
+                ),
             )
             .expect("failed to insert admin order updates route");
 
@@ -97,22 +177,94 @@ impl WebsocketServer {
         router
             .insert(
                 ADMIN_BALANCE_UPDATES_ROUTE,
-                Box::new(DefaultHandler::new_with_remap(
-                    AuthType::Admin,
-                    system_bus::ADMIN_BALANCE_UPDATES_TOPIC.to_string(),
-                    config.system_bus.clone(),
-                )),
+                WebsocketRoute::new(
+                    ADMIN_BALANCE_UPDATES_ROUTE,
+                    Box::new(DefaultHandler::new_with_remap(
+                        AuthType::Admin,
+                        system_bus::ADMIN_BALANCE_UPDATES_TOPIC.to_string(),
+                        config.system_bus.clone(),
+                    )),
+                ),
             )
             .expect("failed to insert admin balance updates route");
 
+        // The "/v2/admin/price-deviations" route
+        router
+            .insert(
+                ADMIN_PRICE_DEVIATIONS_ROUTE,
+                WebsocketRoute::new(
+                    ADMIN_PRICE_DEVIATIONS_ROUTE,
+                    Box::new(DefaultHandler::new_with_remap(
+                        AuthType::Admin,
+                        system_bus::PRICE_DEVIATION_TOPIC.to_string(),
+                        config.system_bus.clone(),
+                    )),
+                ),
+            )
+            .expect("failed to insert admin price deviations route");
+
+        // The "/v2/admin/price-reporter-connections" route
+        router
+            .insert(
+                ADMIN_PRICE_REPORTER_CONNECTIONS_ROUTE,
+                WebsocketRoute::new(
+                    ADMIN_PRICE_REPORTER_CONNECTIONS_ROUTE,
+                    Box::new(DefaultHandler::new_with_remap(
+                        AuthType::Admin,
+                        system_bus::PRICE_REPORTER_CONNECTION_TOPIC.to_string(),
+                        config.system_bus.clone(),
+                    )),
+                ),
+            )
+            .expect("failed to insert admin price reporter connections route");
+
         // The "/v2/account/:account_id/fills" route
         router
             .insert(
                 ACCOUNT_FILLS_ROUTE,
-                Box::new(DefaultHandler::new(AuthType::Account, config.system_bus.clone())),
+                WebsocketRoute::new(
+                    ACCOUNT_FILLS_ROUTE,
+                    Box::new(DefaultHandler::new(AuthType::Account, config.system_bus.clone())),
+                ),
             )
             .expect("failed to insert account fills route");
 
+        // The "/v2/account/:account_id/balances" route
+        router
+            .insert(
+                ACCOUNT_BALANCES_ROUTE,
+                WebsocketRoute::new(
+                    ACCOUNT_BALANCES_ROUTE,
+                    Box::new(DefaultHandler::new(AuthType::Account, config.system_bus.clone())),
+                ),
+            )
+            .expect("failed to insert account balances route");
+
+        // The "/v2/account/:account_id/tasks/:task_id" route
+        router
+            .insert(
+                TASK_STATUS_ROUTE,
+                WebsocketRoute::new(
+                    TASK_STATUS_ROUTE,
+                    Box::new(TaskStatusHandler::new(
+                        config.state.clone(),
+                        config.system_bus.clone(),
+                    )),
+                ),
+            )
+            .expect("failed to insert task status route");
+
+        // The "/v2/account/:account_id/events" route
+        router
+            .insert(
+                ACCOUNT_EVENTS_ROUTE,
+                WebsocketRoute::new(
+                    ACCOUNT_EVENTS_ROUTE,
+                    Box::new(AccountEventsHandler::new(journal.clone(), config.system_bus.clone())),
+                ),
+            )
+            .expect("failed to insert account events route");
+
         router
     }
 
@@ -124,12 +276,15 @@ impl WebsocketServer {
         let listener =
             TcpListener::bind(addr).await.map_err(|err| ApiServerError::Setup(err.to_string()))?;
 
+        // Start journaling account events
+        tokio::spawn(self.journal.clone().run());
+
         // Await incoming websocket connections
-        while let Ok((stream, _)) = listener.accept().await {
+        while let Ok((stream, peer_addr)) = listener.accept().await {
             // Create a new handler on this stream
             let self_clone = self.clone();
             #[allow(clippy::redundant_async_block)]
-            tokio::spawn(async move { self_clone.handle_connection(stream).await });
+            tokio::spawn(async move { self_clone.handle_connection(stream, peer_addr).await });
         }
 
         // If the listener fails, the server has failed
@@ -141,23 +296,53 @@ impl WebsocketServer {
     /// Handle a websocket connection
     ///
     /// Manages subscriptions to internal channels and dispatches
-    /// subscribe/unsubscribe requests
-    async fn handle_connection(&self, stream: TcpStream) -> Result<(), ApiServerError> {
+    /// subscribe/unsubscribe requests. The connection's session is held until
+    /// the connection closes
+    async fn handle_connection(
+        &self,
+        stream: TcpStream,
+        peer_addr: SocketAddr,
+    ) -> Result<(), ApiServerError> {
         // Ignore connections in bootstrap mode
         if in_bootstrap_mode() {
             return Ok(());
         }
 
-        // Accept the websocket upgrade and split into read/write streams
-        let websocket_stream = accept_async(stream)
+        // Accept the websocket upgrade and split into read/write streams. The
+        // connection is admitted within the session limits during the
+        // handshake, where the forwarded client IP is available
+        let mut session = None;
+        let admit = |req: &HandshakeRequest, resp: HandshakeResponse| {
+            let ip = handshake_client_ip(peer_addr, req, self.config.trust_forwarded_for);
+            match self.session_limiter.try_open(ip) {
+                Ok(admitted) => {
+                    session = Some(admitted);
+                    Ok(resp)
+                },
+                Err(limit) => {
+                    log_task!(Task::WebsocketSession, Outcome::Failed, subject = %ip, limit = limit.as_str(), "websocket session limit reached, refusing connection");
+                    Err(session_limit_response(limit))
+                },
+            }
+        };
+        let websocket_stream = accept_hdr_async(stream, admit)
             .await
             .map_err(|err| ApiServerError::WebsocketServerFailure(err.to_string()))?;
-        let (mut write_stream, mut read_stream) = websocket_stream.split();
+        let mut session = session.expect("session is admitted by the handshake");
+        let (write_stream, mut read_stream) = websocket_stream.split();
+
+        // Writes are queued onto a dedicated task so that a slow client never
+        // stalls this loop's consumption of its system bus subscriptions
+        let policy =
+            SlowClientPolicy::from_close_slow_clients(self.config.close_slow_websocket_clients);
+        let mut send_queue =
+            ConnectionSendQueue::new(write_stream, self.config.websocket_send_queue_size, policy);
 
         // The websocket client will add subscriptions throughout the communication;
         // this tracks the active subscriptions that the local connection has
         // open
         let mut subscriptions = StreamMap::new();
+        let mut cursors = TopicCursors::new();
 
         // The `StreamMap` future implementation will return `Poll::Ready(None)` if no
         // streams are registered, indicating that the mapped stream is empty.
@@ -171,7 +356,7 @@ impl WebsocketServer {
             tokio::select! {
                 // Next subscription event from the system bus
                 Some((topic, event)) = subscriptions.next() => {
-                    self.push_subscribed_event(topic, event, &mut write_stream).await?;
+                    self.push_subscribed_event(topic, event, &mut cursors, &mut send_queue)?;
                 }
 
                 // Next message from the client side of the websocket
@@ -186,7 +371,7 @@ impl WebsocketServer {
                             match message_unwrapped {
                                 Message::Close(_) => break,
                                 _ => {
-                                    self.handle_incoming_ws_message(message_unwrapped, &mut subscriptions, &mut write_stream).await?;
+                                    self.handle_incoming_ws_message(message_unwrapped, &mut subscriptions, &mut cursors, &mut session, &mut send_queue).await?;
                                 }
                             };
                         }
@@ -207,52 +392,96 @@ impl WebsocketServer {
         &self,
         message: Message,
         client_subscriptions: &mut StreamMap<String, TopicReader<SystemBusMessage>>,
-        write_stream: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+        cursors: &mut TopicCursors,
+        session: &mut Session,
+        send_queue: &mut ConnectionSendQueue,
     ) -> Result<(), ApiServerError> {
         if let Message::Text(msg_text) = message {
             // Deserialize the message body and dispatch to a handler for a response
+            let mut replay = None;
             let deserialized: Result<ClientWebsocketMessage, _> = serde_json::from_str(&msg_text);
             let resp = match deserialized {
                 // Valid message body
                 Ok(message) => {
-                    let response =
-                        match self.handle_subscription_message(message, client_subscriptions).await
-                        {
-                            Ok(resp) => serde_json::to_string(&resp).map_err(|err| {
+                    let request_id = request_id_from_string_headers(&message.headers);
+                    let (method, topic) = match &message.body {
+                        WebsocketMessage::Subscribe { topic, .. } => ("subscribe", topic.clone()),
+                        WebsocketMessage::Unsubscribe { topic } => ("unsubscribe", topic.clone()),
+                    };
+
+                    let span = info_span!("handle_websocket_request", request_id = %request_id);
+                    let res = self
+                        .handle_subscription_message(
+                            message,
+                            client_subscriptions,
+                            cursors,
+                            session,
+                        )
+                        .instrument(span)
+                        .await;
+                    log_request(method, &topic, res.as_ref().err(), &request_id);
+
+                    let response = match res {
+                        Ok((mut resp, events)) => {
+                            replay = events;
+                            resp.request_id = Some(request_id);
+                            serde_json::to_string(&resp).map_err(|err| {
                                 ApiServerError::WebsocketServerFailure(err.to_string())
-                            })?,
+                            })?
+                        },
 
-                            Err(e) => e.to_string(),
-                        };
+                        Err(e) => error_response(e, request_id)?,
+                    };
 
                     Message::Text(response)
                 },
 
                 // Respond with an error if deserialization fails
-                Err(e) => Message::Text(format!("Invalid request: {}", e)),
+                Err(e) => {
+                    let request_id = new_request_id();
+                    log_task!(Task::WebsocketRequest, Outcome::Failed, subject = %request_id, error = %e, "invalid websocket request");
+                    Message::Text(error_response(format!("Invalid request: {e}"), request_id)?)
+                },
             };
 
-            // Write the response onto the websocket
-            write_stream
-                .send(resp)
-                .await
-                .map_err(|err| ApiServerError::WebsocketServerFailure(err.to_string()))?;
+            // Queue the response onto the websocket, responses are never dropped
+            send_queue.enqueue_reliable(resp).await?;
+
+            // Replay any journaled events the client requested after the response.
+            // The client cannot detect a gap in its replay, so these are never
+            // dropped either
+            if let Some((topic, events)) = replay {
+                for event in events {
+                    if let Some(message) =
+                        self.subscribed_event_message(topic.clone(), event, cursors)?
+                    {
+                        send_queue.enqueue_reliable(message).await?;
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
     /// Handles an incoming subscribe/unsubscribe message
+    ///
+    /// Returns the journaled events to replay if the client subscribed with a
+    /// cursor
     async fn handle_subscription_message(
         &self,
         message: ClientWebsocketMessage,
         client_subscriptions: &mut StreamMap<String, TopicReader<SystemBusMessage>>,
-    ) -> Result<SubscriptionResponse, ApiServerError> {
+        cursors: &mut TopicCursors,
+        session: &mut Session,
+    ) -> Result<(SubscriptionResponse, Option<Replay>), ApiServerError> {
         // Update local subscriptions
+        let mut replay = None;
         match message.body {
-            WebsocketMessage::Subscribe { ref topic } => {
+            WebsocketMessage::Subscribe { ref topic, cursor } => {
                 // Find the handler for the given topic
-                let (params, route_handler) = self.parse_route_and_params(topic)?;
+                let (params, route) = self.parse_route_and_params(topic)?;
+                let route_handler = route.handler.as_ref();
 
                 // Validate auth
                 self.authenticate_subscription(route_handler.auth_type(), topic, &params, &message)
@@ -261,33 +490,49 @@ impl WebsocketServer {
                 // Register the topic subscription in the system bus and in the stream
                 // map that the listener loop polls
                 let reader = route_handler.handle_subscribe_message(topic.clone(), &params).await?;
+
+                // Fetch the events to replay only once subscribed, so that no event falls
+                // between the replay and the subscription. Events in both are pushed once,
+                // deduplicated by their cursor
+                cursors.remove(topic);
+                if let Some(cursor) = cursor {
+                    let events = route_handler.handle_replay(&params, cursor).await?;
+                    cursors.insert(topic.clone(), cursor);
+                    replay = Some((topic.clone(), events));
+                }
+
                 client_subscriptions.insert(topic.clone(), reader);
+                session.subscribe(topic.clone(), route.pattern);
             },
 
             WebsocketMessage::Unsubscribe { topic } => {
                 // Parse the route and apply a handler to it
-                let (params, route_handler) = self.parse_route_and_params(&topic)?;
-                route_handler.handle_unsubscribe_message(topic.clone(), &params).await?;
+                let (params, route) = self.parse_route_and_params(&topic)?;
+                route.handler.handle_unsubscribe_message(topic.clone(), &params).await?;
 
                 // Remove the topic subscription from the stream map
                 client_subscriptions.remove(&topic);
+                cursors.remove(&topic);
+                session.unsubscribe(&topic);
             },
         };
 
-        Ok(SubscriptionResponse {
+        let resp = SubscriptionResponse {
             subscriptions: client_subscriptions
                 .keys()
                 .filter(|&key| DUMMY_SUBSCRIPTION_TOPIC.to_string().ne(key))
                 .cloned()
                 .collect(),
-        })
+            request_id: None,
+        };
+        Ok((resp, replay))
     }
 
     /// Route a subscribe/unsubscribe message
     fn parse_route_and_params(
         &self,
         topic: &str,
-    ) -> Result<(UrlParams, &dyn WebsocketTopicHandler), ApiServerError> {
+    ) -> Result<(UrlParams, &WebsocketRoute), ApiServerError> {
         // Find the route
         let route = self.router.at(topic).map_err(|_| not_found(ERR_INVALID_TOPIC.to_string()))?;
 
@@ -297,7 +542,7 @@ impl WebsocketServer {
             params.insert(param_name.to_string(), param_value.to_string());
         }
 
-        Ok((params, route.value.as_ref()))
+        Ok((params, route.value))
     }
 
     /// Authenticate a websocket subscription
@@ -330,30 +575,154 @@ impl WebsocketServer {
             AuthType::Admin => {
                 self.auth_middleware.authenticate_admin_request(topic, &headers, &body_serialized)
             },
-            AuthType::AccountIfExists | AuthType::None => unreachable!(),
+            AuthType::AccountIfExists
+            | AuthType::Tenant
+            | AuthType::TenantIfPresent
+            | AuthType::None => unreachable!(),
         }
     }
 
-    /// Push an internal event that the client is subscribed to onto the
+    /// Queue an internal event that the client is subscribed to onto the
     /// websocket
-    async fn push_subscribed_event(
+    fn push_subscribed_event(
         &self,
         topic: String,
         event: SystemBusMessage,
-        write_stream: &mut SplitSink<WebSocketStream<TcpStream>, Message>,
+        cursors: &mut TopicCursors,
+        send_queue: &mut ConnectionSendQueue,
     ) -> Result<(), ApiServerError> {
+        match self.subscribed_event_message(topic, event, cursors)? {
+            Some(message) => send_queue.enqueue(message),
+            None => Ok(()),
+        }
+    }
+
+    /// Build the websocket message for an internal event that the client is
+    /// subscribed to
+    ///
+    /// Returns `None` if the event was already pushed on the topic
+    fn subscribed_event_message(
+        &self,
+        topic: String,
+        event: SystemBusMessage,
+        cursors: &mut TopicCursors,
+    ) -> Result<Option<Message>, ApiServerError> {
+        // Skip journaled events already pushed on the topic
+        if let SystemBusMessage::AccountEvent { cursor, .. } = event {
+            let last_cursor = cursors.entry(topic.clone()).or_default();
+            if cursor <= *last_cursor {
+                return Ok(None);
+            }
+            *last_cursor = cursor;
+        }
+
         // Convert the system bus message to a websocket message body
-        let body = system_bus_message_to_websocket_body(event);
+        let body = system_bus_message_to_websocket_body(event)?;
 
-        // Serialize and push onto the stream
+        // Serialize the message
         let ws_message = ServerWebsocketMessage { topic, body };
         let event_serialized = serde_json::to_string(&ws_message)
             .map_err(|err| ApiServerError::WebsocketServerFailure(err.to_string()))?;
-        let message = Message::Text(event_serialized);
+        Ok(Some(Message::Text(event_serialized)))
+    }
+}
 
-        write_stream
-            .send(message)
-            .await
-            .map_err(|err| ApiServerError::WebsocketServerFailure(err.to_string()))
+// -----------
+// | Helpers |
+// -----------
+
+/// Serialize the response to a request that could not be served
+fn error_response(error: impl ToString, request_id: String) -> Result<String, ApiServerError> {
+    let resp = WebsocketErrorResponse { error: error.to_string(), request_id };
+    serde_json::to_string(&resp)
+        .map_err(|err| ApiServerError::WebsocketServerFailure(err.to_string()))
+}
+
+/// Emit an access log line for a subscribe/unsubscribe request
+fn log_request(method: &str, topic: &str, error: Option<&ApiServerError>, request_id: &str) {
+    match error {
+        Some(e) => log_task!(
+            Task::WebsocketRequest,
+            Outcome::Failed,
+            subject = %request_id,
+            method = method,
+            topic = %topic,
+            error = %e,
+            "websocket request failed"
+        ),
+        None => log_task!(
+            Task::WebsocketRequest,
+            Outcome::Ok,
+            subject = %request_id,
+            method = method,
+            topic = %topic,
+            "served websocket request"
+        ),
+    }
+}
+
+/// Resolve the IP of the client opening a websocket from its handshake
+///
+/// The handshake's headers are converted to the API's header map so that
+/// websocket clients are identified the same way as HTTP clients
+fn handshake_client_ip(
+    peer_addr: SocketAddr,
+    req: &HandshakeRequest,
+    trust_forwarded_for: bool,
+) -> IpAddr {
+    let headers: HeaderMap = req
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            let name = HeaderName::from_bytes(name.as_str().as_bytes()).ok()?;
+            let value = HeaderValue::from_bytes(value.as_bytes()).ok()?;
+            Some((name, value))
+        })
+        .collect();
+    client_ip(peer_addr, &headers, trust_forwarded_for)
+}
+
+/// Build the handshake response refusing a connection beyond a session limit
+fn session_limit_response(limit: SessionLimit) -> HandshakeErrorResponse {
+    let status = match limit {
+        SessionLimit::Total => HandshakeStatusCode::SERVICE_UNAVAILABLE,
+        SessionLimit::PerIp => HandshakeStatusCode::TOO_MANY_REQUESTS,
+    };
+
+    let mut resp = HandshakeErrorResponse::new(Some(ERR_SESSION_LIMIT.to_string()));
+    *resp.status_mut() = status;
+    resp
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, SocketAddr};
+
+    use external_api::websocket::WebsocketErrorResponse;
+
+    use super::{HandshakeRequest, error_response, handshake_client_ip};
+
+    /// Tests that websocket clients are identified by the forwarded IP when
+    /// it is trusted
+    #[test]
+    fn test_handshake_client_ip() {
+        let peer_addr: SocketAddr = "10.0.0.1:9000".parse().unwrap();
+        let req = HandshakeRequest::builder()
+            .header("x-forwarded-for", "1.2.3.4, 5.6.7.8")
+            .body(())
+            .unwrap();
+
+        let forwarded: IpAddr = "5.6.7.8".parse().unwrap();
+        assert_eq!(handshake_client_ip(peer_addr, &req, true /* trust */), forwarded);
+        assert_eq!(handshake_client_ip(peer_addr, &req, false /* trust */), peer_addr.ip());
+    }
+
+    /// Tests that error responses carry the request's ID
+    #[test]
+    fn test_error_response() {
+        let resp = error_response("unknown topic", "req-1".to_string()).unwrap();
+        let resp: WebsocketErrorResponse = serde_json::from_str(&resp).unwrap();
+        assert_eq!(resp.error, "unknown topic");
+        assert_eq!(resp.request_id, "req-1");
     }
 }
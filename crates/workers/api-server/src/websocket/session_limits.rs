@@ -0,0 +1,187 @@
+//! Limits on concurrent websocket sessions, and gauges of the open sessions
+//!
+//! A websocket connection holds a task and a send queue for as long as it is
+//! open, even if it never subscribes. The server caps the number of open
+//! connections in total and per source IP, so that a single client cannot
+//! exhaust the server by holding idle sockets. Connections beyond either cap
+//! are refused during the websocket handshake, once the client's IP is known
+
+use std::{collections::HashMap, net::IpAddr};
+
+use renegade_metrics::labels::{
+    SESSION_LIMIT_METRIC_TAG, WEBSOCKET_ACTIVE_CONNECTIONS_METRIC,
+    WEBSOCKET_ACTIVE_SUBSCRIPTIONS_METRIC, WEBSOCKET_REJECTED_CONNECTIONS_METRIC,
+    WEBSOCKET_ROUTE_METRIC_TAG,
+};
+use util::concurrency::{Shared, new_shared};
+
+/// The session limit that rejected a connection
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionLimit {
+    /// The cap on connections across all clients
+    Total,
+    /// The cap on connections from a single IP
+    PerIp,
+}
+
+impl SessionLimit {
+    /// The name of the limit, used to tag metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionLimit::Total => "total",
+            SessionLimit::PerIp => "per-ip",
+        }
+    }
+}
+
+/// The open websocket connections
+#[derive(Debug, Default)]
+struct OpenSessions {
+    /// The number of open connections
+    total: usize,
+    /// The number of open connections from each IP
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+/// Admits websocket connections within the configured session limits
+#[derive(Clone)]
+pub struct SessionLimiter {
+    /// The open connections
+    sessions: Shared<OpenSessions>,
+    /// The maximum number of open connections, zero if unlimited
+    max_connections: usize,
+    /// The maximum number of open connections from a single IP, zero if
+    /// unlimited
+    max_connections_per_ip: usize,
+}
+
+impl SessionLimiter {
+    /// Constructor
+    pub fn new(max_connections: usize, max_connections_per_ip: usize) -> Self {
+        let sessions = new_shared(OpenSessions::default());
+        Self { sessions, max_connections, max_connections_per_ip }
+    }
+
+    /// Admit a connection from the given IP
+    ///
+    /// Returns the connection's session, which releases its slot when dropped,
+    /// or the limit that rejected it
+    pub fn try_open(&self, ip: IpAddr) -> Result<Session, SessionLimit> {
+        let mut sessions = self.sessions.write().expect("sessions lock poisoned");
+        let n_from_ip = sessions.per_ip.get(&ip).copied().unwrap_or_default();
+
+        let limit = if self.max_connections > 0 && sessions.total >= self.max_connections {
+            Some(SessionLimit::Total)
+        } else if self.max_connections_per_ip > 0 && n_from_ip >= self.max_connections_per_ip {
+            Some(SessionLimit::PerIp)
+        } else {
+            None
+        };
+
+        if let Some(limit) = limit {
+            let labels = [(SESSION_LIMIT_METRIC_TAG, limit.as_str())];
+            metrics::counter!(WEBSOCKET_REJECTED_CONNECTIONS_METRIC, &labels).increment(1);
+            return Err(limit);
+        }
+
+        sessions.total += 1;
+        *sessions.per_ip.entry(ip).or_default() += 1;
+        metrics::gauge!(WEBSOCKET_ACTIVE_CONNECTIONS_METRIC).set(sessions.total as f64);
+
+        Ok(Session { limiter: self.clone(), ip, subscriptions: HashMap::new() })
+    }
+
+    /// Release the slot of a closed connection
+    fn close(&self, ip: IpAddr) {
+        let mut sessions = self.sessions.write().expect("sessions lock poisoned");
+        sessions.total = sessions.total.saturating_sub(1);
+        if let Some(n) = sessions.per_ip.get_mut(&ip) {
+            *n -= 1;
+            if *n == 0 {
+                sessions.per_ip.remove(&ip);
+            }
+        }
+
+        metrics::gauge!(WEBSOCKET_ACTIVE_CONNECTIONS_METRIC).set(sessions.total as f64);
+    }
+}
+
+/// An open websocket connection, tracking its subscriptions for the topic
+/// gauges
+///
+/// Dropping the session releases its connection slot and its subscriptions
+pub struct Session {
+    /// The limiter that admitted the connection
+    limiter: SessionLimiter,
+    /// The IP the connection was opened from
+    ip: IpAddr,
+    /// The route of each topic the connection is subscribed to
+    subscriptions: HashMap<String, &'static str>,
+}
+
+impl Session {
+    /// Record a subscription to a topic on the given route
+    ///
+    /// Resubscribing to a topic the connection is already subscribed to is not
+    /// counted twice
+    pub fn subscribe(&mut self, topic: String, route: &'static str) {
+        if self.subscriptions.insert(topic, route).is_none() {
+            let labels = [(WEBSOCKET_ROUTE_METRIC_TAG, route)];
+            metrics::gauge!(WEBSOCKET_ACTIVE_SUBSCRIPTIONS_METRIC, &labels).increment(1.);
+        }
+    }
+
+    /// Record an unsubscription from a topic
+    pub fn unsubscribe(&mut self, topic: &str) {
+        if let Some(route) = self.subscriptions.remove(topic) {
+            let labels = [(WEBSOCKET_ROUTE_METRIC_TAG, route)];
+            metrics::gauge!(WEBSOCKET_ACTIVE_SUBSCRIPTIONS_METRIC, &labels).decrement(1.);
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for route in self.subscriptions.values() {
+            let labels = [(WEBSOCKET_ROUTE_METRIC_TAG, *route)];
+            metrics::gauge!(WEBSOCKET_ACTIVE_SUBSCRIPTIONS_METRIC, &labels).decrement(1.);
+        }
+
+        self.limiter.close(self.ip);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::{SessionLimit, SessionLimiter};
+
+    /// Tests that the per-IP cap applies to each IP separately
+    #[test]
+    fn test_per_ip_limit() {
+        let limiter = SessionLimiter::new(0 /* max_connections */, 2);
+        let ip1 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let ip2 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+        let _s1 = limiter.try_open(ip1).unwrap();
+        let s2 = limiter.try_open(ip1).unwrap();
+        assert_eq!(limiter.try_open(ip1).err(), Some(SessionLimit::PerIp));
+        assert!(limiter.try_open(ip2).is_ok());
+
+        // Closing a session frees its slot
+        drop(s2);
+        assert!(limiter.try_open(ip1).is_ok());
+    }
+
+    /// Tests that the total cap applies across IPs
+    #[test]
+    fn test_total_limit() {
+        let limiter = SessionLimiter::new(2, 0 /* max_connections_per_ip */);
+        let _s1 = limiter.try_open(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))).unwrap();
+        let _s2 = limiter.try_open(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))).unwrap();
+
+        let res = limiter.try_open(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)));
+        assert_eq!(res.err(), Some(SessionLimit::Total));
+    }
+}
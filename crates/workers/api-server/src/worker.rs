@@ -10,14 +10,19 @@ use job_types::{
 use price_state::PriceStreamStates;
 use reqwest::Url;
 use state::State;
-use std::thread::{self, JoinHandle};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+};
 use system_bus::SystemBus;
 use tokio::{
     runtime::{Builder as TokioBuilder, Runtime},
     task::JoinHandle as TokioJoinHandle,
 };
 use types_core::{Chain, HmacKey};
-use types_runtime::{CancelChannel, Worker};
+use types_gossip::{HeartbeatSettings, PeerAccessList, PeerLatencies};
+use types_runtime::{CancelChannel, ShutdownProgress, Worker, WorkerLiveness};
 
 use super::{
     error::ApiServerError, health::HealthServer, http::HttpServer, websocket::WebsocketServer,
@@ -84,8 +89,43 @@ pub struct ApiServerConfig {
     pub health_port: u16,
     /// The admin key, if one is set
     pub admin_api_key: Option<HmacKey>,
+    /// The HMAC keys of the tenants the API serves, keyed by tenant ID
+    pub tenant_keys: HashMap<String, HmacKey>,
     /// The number of tasks per hour a given wallet is allowed to make
     pub wallet_task_rate_limit: u32,
+    /// The number of read requests per minute a given API client is allowed to
+    /// make, zero if unlimited
+    pub read_rate_limit: u32,
+    /// The number of mutating requests per minute a given API client is
+    /// allowed to make, zero if unlimited
+    pub write_rate_limit: u32,
+    /// Whether to identify API clients by the `X-Forwarded-For` header rather
+    /// than the connection's source IP
+    pub trust_forwarded_for: bool,
+    /// The number of messages that may be queued for a websocket connection
+    /// before its client is considered slow
+    pub websocket_send_queue_size: usize,
+    /// Whether to close slow websocket clients rather than drop the messages
+    /// that do not fit in their send queue
+    pub close_slow_websocket_clients: bool,
+    /// The maximum number of open websocket connections, zero if unlimited
+    pub websocket_max_connections: usize,
+    /// The maximum number of open websocket connections from a single IP,
+    /// zero if unlimited
+    pub websocket_max_connections_per_ip: usize,
+    /// The minimum size in bytes of an HTTP response body for it to be
+    /// compressed
+    pub compression_min_size: usize,
+    /// The content types of HTTP responses that may be compressed
+    pub compression_content_types: Vec<String>,
+    /// The origins allowed to make cross-origin requests, `*` for any origin
+    pub cors_allowed_origins: Vec<String>,
+    /// Whether cross-origin requests may carry credentials
+    pub cors_allow_credentials: bool,
+    /// The number of hours of price history to record, zero if disabled
+    pub price_history_retention_hours: u64,
+    /// The directory in which state exports are written
+    pub state_export_path: PathBuf,
     /// The minimum usdc denominated value for a deposit or withdrawal
     pub min_transfer_amount: f64,
     /// The minimum usdc denominated order size
@@ -98,6 +138,9 @@ pub struct ApiServerConfig {
     pub compliance_service_url: Option<Url>,
     /// The list of disabled assets
     pub disabled_assets: Vec<String>,
+    /// The list of allowed assets, all assets not disabled are allowed if
+    /// empty
+    pub allowed_assets: Vec<String>,
     /// A handle on the darkpool RPC client
     pub darkpool_client: DarkpoolClient,
     /// A sender to the network manager's work queue
@@ -112,6 +155,18 @@ pub struct ApiServerConfig {
     pub task_queue: TaskDriverQueue,
     /// The relayer-global state
     pub state: State,
+    /// The table of round trip times to peers, measured by the network manager
+    pub peer_latencies: PeerLatencies,
+    /// The heartbeat intervals used by the gossip server, adjustable through
+    /// the admin API
+    pub heartbeat_settings: HeartbeatSettings,
+    /// The peers the relayer may talk to, adjustable through the admin API
+    pub peer_access: PeerAccessList,
+    /// The liveness of the relayer's workers, reported by the health probes
+    pub worker_liveness: WorkerLiveness,
+    /// The relayer's shutdown progress; writes are refused while the node
+    /// drains, and the health probes report the drain's phase
+    pub shutdown: ShutdownProgress,
     /// The system pubsub bus that all workers have access to
     /// The ApiServer uses this bus to forward internal events onto open
     /// websocket connections
@@ -148,8 +203,10 @@ impl Worker for ApiServer {
             .build()
             .map_err(|err| ApiServerError::Setup(err.to_string()))?;
 
-        // Build the http server
-        let http_server = HttpServer::new(self.config.clone())?;
+        // Build the http server, its handlers read the feature flags from a view
+        // kept current by flag updates on the system bus
+        let feature_flags = tokio_runtime.block_on(self.config.state.watch_feature_flags())?;
+        let http_server = HttpServer::new(self.config.clone(), feature_flags)?;
         let http_thread_handle = tokio_runtime.spawn_blocking(move || {
             let err = block_on(http_server.execution_loop()).err().unwrap();
             ApiServerError::HttpServerFailure(err.to_string())
@@ -174,6 +231,9 @@ impl Worker for ApiServer {
             self.config.health_port,
             self.config.http_port,
             self.config.state.clone(),
+            self.config.worker_liveness.clone(),
+            self.config.shutdown.clone(),
+            self.config.price_streams.clone(),
         );
         let health_thread_handle = health_runtime.spawn_blocking(move || {
             let err = block_on(health_server.execution_loop()).err().unwrap();
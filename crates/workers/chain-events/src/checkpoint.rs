@@ -0,0 +1,257 @@
+//! Checkpointing of processed chain events
+//!
+//! The listener persists the cursor of the last event it has fully processed,
+//! and on startup replays every event emitted since that cursor before relying
+//! on its live subscriptions alone. Events are handled concurrently and may
+//! complete out of order, so the checkpoint only advances past an event once
+//! every event dispatched before it has completed.
+//!
+//! Events are delivered by several independent subscriptions, so an event
+//! from an earlier block may arrive after one from a later block. To avoid
+//! checkpointing past an event that has yet to arrive, events in the newest
+//! block seen are not checkpointed until an event from a later block arrives.
+//! An event that fails to be handled still completes; handler failures are
+//! logged rather than retried, as they are without checkpointing
+
+use std::{cmp::min, collections::BTreeSet};
+
+use alloy::{
+    providers::{DynProvider, Provider},
+    rpc::types::{Filter, Log},
+};
+use state::storage::tx::chain_events::ChainEventCursor;
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    error::OnChainEventListenerError,
+    executor::{EventKind, OnChainEventListenerExecutor},
+    logging::Task,
+};
+
+/// The maximum number of blocks requested in a single `eth_getLogs` call while
+/// replaying events, as RPC providers bound the range of a log query
+const MAX_REPLAY_BLOCK_RANGE: u64 = 10_000;
+
+/// Get the cursor of a log, or `None` if the log is pending
+pub(crate) fn log_cursor(log: &Log) -> Option<ChainEventCursor> {
+    Some(ChainEventCursor::new(log.block_number?, log.log_index?))
+}
+
+/// Tracks dispatched and completed events to advance the checkpoint
+#[derive(Debug, Default)]
+pub(crate) struct CheckpointTracker {
+    /// The cursor of the last event known to be fully processed
+    checkpoint: Option<ChainEventCursor>,
+    /// Whether advancing the checkpoint is held, while events since the
+    /// checkpoint are being replayed
+    held: bool,
+    /// The events dispatched and not yet completed
+    in_flight: BTreeSet<ChainEventCursor>,
+    /// The events completed but not yet checkpointed
+    completed: BTreeSet<ChainEventCursor>,
+    /// The newest block from which an event has been dispatched
+    latest_block: u64,
+}
+
+impl CheckpointTracker {
+    /// Create a tracker resuming from the given checkpoint
+    ///
+    /// The checkpoint is held until the events since it have been replayed
+    pub(crate) fn new(checkpoint: Option<ChainEventCursor>) -> Self {
+        Self { checkpoint, held: checkpoint.is_some(), ..Default::default() }
+    }
+
+    /// The cursor of the last event known to be fully processed
+    pub(crate) fn checkpoint(&self) -> Option<ChainEventCursor> {
+        self.checkpoint
+    }
+
+    /// Record that an event was dispatched
+    ///
+    /// Returns `false` if the event was already dispatched, as when it is both
+    /// replayed and delivered by a live subscription
+    pub(crate) fn begin(&mut self, cursor: ChainEventCursor) -> bool {
+        if self.in_flight.contains(&cursor) || self.completed.contains(&cursor) {
+            return false;
+        }
+
+        self.latest_block = self.latest_block.max(cursor.block_number);
+        self.in_flight.insert(cursor)
+    }
+
+    /// Record that an event completed
+    ///
+    /// Returns the new checkpoint if it advanced
+    pub(crate) fn complete(&mut self, cursor: ChainEventCursor) -> Option<ChainEventCursor> {
+        self.in_flight.remove(&cursor);
+        self.completed.insert(cursor);
+        self.advance()
+    }
+
+    /// Release the hold on the checkpoint once replay has dispatched every
+    /// event since it
+    ///
+    /// Returns the new checkpoint if it advanced
+    pub(crate) fn release(&mut self) -> Option<ChainEventCursor> {
+        self.held = false;
+        self.advance()
+    }
+
+    /// Advance the checkpoint past every completed event that no in-flight
+    /// event precedes
+    fn advance(&mut self) -> Option<ChainEventCursor> {
+        if self.held {
+            return None;
+        }
+
+        let prev = self.checkpoint;
+        while let Some(&next) = self.completed.first() {
+            let preceded = self.in_flight.first().is_some_and(|first| *first < next);
+            if preceded || next.block_number >= self.latest_block {
+                break;
+            }
+
+            self.completed.pop_first();
+            self.checkpoint = self.checkpoint.max(Some(next));
+        }
+
+        (self.checkpoint != prev).then_some(self.checkpoint).flatten()
+    }
+}
+
+impl OnChainEventListenerExecutor {
+    /// Record that an event was dispatched
+    ///
+    /// Returns `false` if the event was already dispatched
+    pub(crate) async fn begin_event(&self, cursor: ChainEventCursor) -> bool {
+        self.checkpoint.lock().await.begin(cursor)
+    }
+
+    /// Record that an event completed, persisting the checkpoint if it advanced
+    pub(crate) async fn complete_event(&self, cursor: ChainEventCursor) {
+        // Hold the tracker while persisting so that checkpoints are written in
+        // order
+        let mut tracker = self.checkpoint.lock().await;
+        if let Some(checkpoint) = tracker.complete(cursor) {
+            self.persist_checkpoint(checkpoint).await;
+        }
+    }
+
+    /// Replace the checkpoint tracker with one resuming from the checkpoint in
+    /// state
+    pub(crate) async fn load_checkpoint(&self) -> Result<(), OnChainEventListenerError> {
+        let checkpoint = self.state().get_chain_events_checkpoint().await?;
+        *self.checkpoint.lock().await = CheckpointTracker::new(checkpoint);
+        Ok(())
+    }
+
+    /// Replay the events emitted since the loaded checkpoint, then release the
+    /// hold on it
+    ///
+    /// On failure the checkpoint stays held, so that a restart replays the
+    /// same events
+    pub(crate) async fn replay_since_checkpoint(
+        &self,
+        client: &DynProvider,
+    ) -> Result<(), OnChainEventListenerError> {
+        let checkpoint = self.checkpoint.lock().await.checkpoint();
+        let Some(checkpoint) = checkpoint else {
+            log_task!(
+                Task::ResumeFromCheckpoint,
+                Outcome::Skipped,
+                "no chain events checkpoint; processing new events only"
+            );
+            return Ok(());
+        };
+
+        let logs = self.logs_since(client, checkpoint).await?;
+        log_task!(
+            Task::ResumeFromCheckpoint,
+            Outcome::Started,
+            block = checkpoint.block_number,
+            log_index = checkpoint.log_index,
+            count = logs.len(),
+            "replaying chain events since checkpoint"
+        );
+        for (kind, log) in logs {
+            self.dispatch_event(kind, log).await;
+        }
+
+        let mut tracker = self.checkpoint.lock().await;
+        if let Some(checkpoint) = tracker.release() {
+            self.persist_checkpoint(checkpoint).await;
+        }
+        Ok(())
+    }
+
+    /// Fetch every event after the checkpoint, in the order they were emitted
+    async fn logs_since(
+        &self,
+        client: &DynProvider,
+        checkpoint: ChainEventCursor,
+    ) -> Result<Vec<(EventKind, Log)>, OnChainEventListenerError> {
+        let owners = self.get_tracked_owners();
+        let mut filters = vec![(EventKind::Darkpool, self.darkpool_filter())];
+        if let Some((from, to)) = self.transfer_filters(&owners) {
+            filters.push((EventKind::TransferFrom, from));
+            filters.push((EventKind::TransferTo, to));
+        }
+        if let Some((approval, permit)) = self.permit2_filters(&owners) {
+            filters.push((EventKind::Permit2Approval, approval));
+            filters.push((EventKind::Permit2Permit, permit));
+        }
+
+        let head = client.get_block_number().await?;
+        let mut logs = Vec::new();
+        for (kind, filter) in filters {
+            for log in
+                Self::get_logs_in_range(client, &filter, checkpoint.block_number, head).await?
+            {
+                if log_cursor(&log).is_some_and(|cursor| cursor > checkpoint) {
+                    logs.push((kind, log));
+                }
+            }
+        }
+
+        logs.sort_by_key(|(_, log)| log_cursor(log));
+        Ok(logs)
+    }
+
+    /// Get the logs matching a filter in a block range, splitting the range
+    /// into chunks the RPC provider will serve
+    async fn get_logs_in_range(
+        client: &DynProvider,
+        filter: &Filter,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, OnChainEventListenerError> {
+        let mut logs = Vec::new();
+        let mut start = from_block;
+        while start <= to_block {
+            let end = min(start + MAX_REPLAY_BLOCK_RANGE - 1, to_block);
+            let chunk_filter = filter.clone().from_block(start).to_block(end);
+            logs.extend(client.get_logs(&chunk_filter).await?);
+            start = end + 1;
+        }
+
+        Ok(logs)
+    }
+
+    /// Persist the checkpoint to state, logging a failure
+    ///
+    /// A failed write leaves an older checkpoint in place, from which a restart
+    /// replays events that were already processed
+    async fn persist_checkpoint(&self, checkpoint: ChainEventCursor) {
+        if let Err(e) = self.state().set_chain_events_checkpoint(checkpoint).await {
+            log_task!(
+                Task::SaveCheckpoint,
+                Outcome::Failed,
+                error = %e,
+                block = checkpoint.block_number,
+                log_index = checkpoint.log_index,
+                "failed to persist chain events checkpoint"
+            );
+        }
+    }
+}
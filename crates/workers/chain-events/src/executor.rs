@@ -2,6 +2,7 @@
 
 use std::{
     collections::HashSet,
+    fmt::{self, Display},
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -9,24 +10,70 @@ use std::{
 use alloy::{
     primitives::{Address, TxHash},
     providers::{DynProvider, ProviderBuilder, WsConnect},
+    rpc::types::Log,
 };
 use constants::in_bootstrap_mode;
 use darkpool_client::DarkpoolClient;
 use futures_util::StreamExt;
 use rand::Rng;
 use state::State;
-use system_bus::{OWNER_INDEX_CHANGED_TOPIC, SystemBus, SystemBusMessage};
+use system_bus::{
+    CHAIN_EVENTS_CHECKPOINT_TOPIC, OWNER_INDEX_CHANGED_TOPIC, SystemBus, SystemBusMessage,
+};
 use util::concurrency::runtime::sleep_forever_async;
 use util::log_task;
 use util::logging::Outcome;
 
-use crate::{error::OnChainEventListenerError, logging::Task, worker::OnChainEventListenerConfig};
+use crate::{
+    checkpoint::{CheckpointTracker, log_cursor},
+    error::OnChainEventListenerError,
+    logging::Task,
+    worker::OnChainEventListenerConfig,
+};
 
 /// Minimum delay before non-selected nodes process an event (crash recovery)
 const MIN_CRASH_RECOVERY_DELAY_S: u64 = 20;
 /// Maximum delay before non-selected nodes process an event (crash recovery)
 const MAX_CRASH_RECOVERY_DELAY_S: u64 = 40;
 
+/// The kinds of chain events the listener subscribes to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EventKind {
+    /// An ERC20 transfer from a tracked owner
+    TransferFrom,
+    /// An ERC20 transfer to a tracked owner
+    TransferTo,
+    /// A Permit2 approval by a tracked owner
+    Permit2Approval,
+    /// A Permit2 signature-based approval by a tracked owner
+    Permit2Permit,
+    /// A darkpool intent update or cancellation
+    Darkpool,
+}
+
+impl EventKind {
+    /// The task under which the event's handling is logged
+    fn task(&self) -> Task {
+        match self {
+            EventKind::TransferFrom | EventKind::TransferTo => Task::HandleTransferEvent,
+            EventKind::Permit2Approval | EventKind::Permit2Permit => Task::HandlePermit2Event,
+            EventKind::Darkpool => Task::HandleDarkpoolEvent,
+        }
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EventKind::TransferFrom | EventKind::TransferTo => "transfer",
+            EventKind::Permit2Approval => "permit2 approval",
+            EventKind::Permit2Permit => "permit2 permit",
+            EventKind::Darkpool => "darkpool",
+        };
+        write!(f, "{name}")
+    }
+}
+
 /// The executor that runs in a thread and polls events from on-chain state
 #[derive(Clone)]
 pub struct OnChainEventListenerExecutor {
@@ -35,12 +82,18 @@ pub struct OnChainEventListenerExecutor {
     /// In-memory set of tracked owners, initialized from DB at startup and
     /// kept in sync via system bus messages
     tracked_owners: Arc<RwLock<HashSet<Address>>>,
+    /// Tracks processed events to advance the persisted checkpoint
+    pub(crate) checkpoint: Arc<tokio::sync::Mutex<CheckpointTracker>>,
 }
 
 impl OnChainEventListenerExecutor {
     /// Create a new executor
     pub fn new(config: OnChainEventListenerConfig) -> Self {
-        Self { config, tracked_owners: Arc::new(RwLock::new(HashSet::new())) }
+        Self {
+            config,
+            tracked_owners: Arc::new(RwLock::new(HashSet::new())),
+            checkpoint: Arc::new(tokio::sync::Mutex::new(CheckpointTracker::default())),
+        }
     }
 
     /// Get the tracked owners from the in-memory cache
@@ -114,8 +167,9 @@ impl OnChainEventListenerExecutor {
             "listening for chain events via websocket"
         );
 
-        // Initialize the tracked owners cache from DB
+        // Initialize the tracked owners cache and the checkpoint from DB
         self.init_tracked_owners().await?;
+        self.load_checkpoint().await?;
 
         // Create websocket client and subscribe to all event streams
         let client = self.create_ws_client().await?;
@@ -127,83 +181,52 @@ impl OnChainEventListenerExecutor {
 
         // Subscribe to internal notifications for owner index changes
         let mut owner_changes = self.system_bus().subscribe(OWNER_INDEX_CHANGED_TOPIC.to_string());
+        let mut checkpoint_resets =
+            self.system_bus().subscribe(CHAIN_EVENTS_CHECKPOINT_TOPIC.to_string());
         let mut cancel = self.config.cancel_channel.clone();
 
+        // Replay the events emitted since the checkpoint, now that the live
+        // subscriptions are open
+        self.spawn_replay(&client);
+
         loop {
             tokio::select! {
                 // Handle ERC20 transfers from tracked owners
                 Some(log) = transfer_from.next() => {
-                    let executor = self.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = executor.handle_transfer_event(log).await {
-                            log_task!(
-                                Task::HandleTransferEvent,
-                                Outcome::Failed,
-                                error = %e,
-                                "error handling transfer event"
-                            );
-                        }
-                    });
+                    self.dispatch_event(EventKind::TransferFrom, log).await;
                 }
 
                 // Handle ERC20 transfers to tracked owners
                 Some(log) = transfer_to.next() => {
-                    let executor = self.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = executor.handle_transfer_event(log).await {
-                            log_task!(
-                                Task::HandleTransferEvent,
-                                Outcome::Failed,
-                                error = %e,
-                                "error handling transfer event"
-                            );
-                        }
-                    });
+                    self.dispatch_event(EventKind::TransferTo, log).await;
                 }
 
                 // Handle Permit2 Approval events
                 Some(log) = permit2_approval.next() => {
-                    let executor = self.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = executor.handle_permit2_event(log).await {
-                            log_task!(
-                                Task::HandlePermit2Event,
-                                Outcome::Failed,
-                                error = %e,
-                                "error handling permit2 approval event"
-                            );
-                        }
-                    });
+                    self.dispatch_event(EventKind::Permit2Approval, log).await;
                 }
 
                 // Handle Permit2 Permit events (signature-based approvals)
                 Some(log) = permit2_permit.next() => {
-                    let executor = self.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = executor.handle_permit2_event(log).await {
-                            log_task!(
-                                Task::HandlePermit2Event,
-                                Outcome::Failed,
-                                error = %e,
-                                "error handling permit2 permit event"
-                            );
-                        }
-                    });
+                    self.dispatch_event(EventKind::Permit2Permit, log).await;
                 }
 
                 // Handle darkpool contract events (intent updates/cancellations)
                 Some(log) = darkpool.next() => {
-                    let executor = self.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = executor.dispatch_darkpool_event(log).await {
-                            log_task!(
-                                Task::HandleDarkpoolEvent,
-                                Outcome::Failed,
-                                error = %e,
-                                "error handling darkpool event"
-                            );
-                        }
-                    });
+                    self.dispatch_event(EventKind::Darkpool, log).await;
+                }
+
+                // Resume from the new checkpoint when it is reset
+                _ = checkpoint_resets.next_message() => {
+                    match self.load_checkpoint().await {
+                        Ok(()) => self.spawn_replay(&client),
+                        Err(e) => log_task!(
+                            Task::ResumeFromCheckpoint,
+                            Outcome::Failed,
+                            error = %e,
+                            "failed to load reset chain events checkpoint"
+                        ),
+                    }
                 }
 
                 // Update cache and refresh subscriptions when owner set changes
@@ -265,6 +288,57 @@ impl OnChainEventListenerExecutor {
         Err(OnChainEventListenerError::StreamEnded)
     }
 
+    /// Handle an event in a new task, recording its completion against the
+    /// checkpoint
+    ///
+    /// An event that was already dispatched is skipped
+    pub(crate) async fn dispatch_event(&self, kind: EventKind, log: Log) {
+        let cursor = log_cursor(&log);
+        if let Some(cursor) = cursor
+            && !self.begin_event(cursor).await
+        {
+            return;
+        }
+
+        let executor = self.clone();
+        tokio::task::spawn(async move {
+            let res = match kind {
+                EventKind::TransferFrom | EventKind::TransferTo => {
+                    executor.handle_transfer_event(log).await
+                },
+                EventKind::Permit2Approval | EventKind::Permit2Permit => {
+                    executor.handle_permit2_event(log).await
+                },
+                EventKind::Darkpool => executor.dispatch_darkpool_event(log).await,
+            };
+
+            if let Err(e) = res {
+                log_task!(kind.task(), Outcome::Failed, error = %e, "error handling {kind} event");
+            }
+
+            if let Some(cursor) = cursor {
+                executor.complete_event(cursor).await;
+            }
+        });
+    }
+
+    /// Replay the events since the loaded checkpoint in a new task, logging a
+    /// failure
+    fn spawn_replay(&self, client: &DynProvider) {
+        let executor = self.clone();
+        let client = client.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = executor.replay_since_checkpoint(&client).await {
+                log_task!(
+                    Task::ResumeFromCheckpoint,
+                    Outcome::Failed,
+                    error = %e,
+                    "failed to replay chain events since checkpoint"
+                );
+            }
+        });
+    }
+
     /// Create a new websocket client
     async fn create_ws_client(&self) -> Result<DynProvider, OnChainEventListenerError> {
         let Some(ref addr) = self.config.websocket_addr else {
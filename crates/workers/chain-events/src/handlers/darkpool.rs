@@ -36,17 +36,19 @@ impl OnChainEventListenerExecutor {
         &self,
         client: &DynProvider,
     ) -> Result<impl Stream<Item = Log>, OnChainEventListenerError> {
-        // Subscribe to both intent update and cancellation events
-        let filter =
-            Filter::new().address(self.darkpool_client().darkpool_addr()).event_signature(vec![
-                PublicIntentUpdated::SIGNATURE_HASH,
-                PublicIntentCancelled::SIGNATURE_HASH,
-            ]);
-
-        let stream = client.subscribe_logs(&filter).await?.into_stream();
+        let stream = client.subscribe_logs(&self.darkpool_filter()).await?.into_stream();
         Ok(stream)
     }
 
+    /// Build the filter for darkpool events, matching both intent update and
+    /// cancellation events
+    pub(crate) fn darkpool_filter(&self) -> Filter {
+        Filter::new().address(self.darkpool_client().darkpool_addr()).event_signature(vec![
+            PublicIntentUpdated::SIGNATURE_HASH,
+            PublicIntentCancelled::SIGNATURE_HASH,
+        ])
+    }
+
     /// Dispatch darkpool events by topic0 to the appropriate handler
     pub(crate) async fn dispatch_darkpool_event(
         &self,
@@ -137,7 +139,6 @@ impl OnChainEventListenerExecutor {
                 tx_hash,
                 event.intentHash,
                 fill_amount,
-                account_id,
                 external_matches,
             )
         } else {
@@ -221,7 +222,6 @@ impl OnChainEventListenerExecutor {
         tx_hash: TxHash,
         intent_hash: B256,
         fill_amount: Amount,
-        account_id: AccountId,
         external_matches: &[(B256, BoundedMatchResult, Amount)],
     ) {
         let Some((match_res, external_amount_in)) =
@@ -260,11 +260,7 @@ impl OnChainEventListenerExecutor {
             amount_out: external_amount_in,
         };
 
-        record_match_volume_from_obligation(
-            &obligation,
-            true, // is_external_match
-            &[account_id],
-        );
+        record_match_volume_from_obligation(&obligation, true /* is_external_match */);
     }
 
     /// Handle a PublicIntentCancelled event emitted when a user cancels their
@@ -42,17 +42,32 @@ impl OnChainEventListenerExecutor {
             "tracking owners"
         );
 
-        // Convert owners to topic format for log filtering
-        let owner_topics: Vec<B256> = owners.into_iter().map(|addr| addr.into_word()).collect();
-        if owner_topics.is_empty() {
+        let Some((from_filter, to_filter)) = self.transfer_filters(&owners) else {
             log_task!(
                 Task::CreateTransferSubscriptions,
                 Outcome::Skipped,
                 "no tracked owners; skipping erc20 transfer subscriptions"
             );
             return Ok((stream::empty().boxed_local(), stream::empty().boxed_local()));
+        };
+
+        // Subscribe to both streams
+        let from_stream = client.subscribe_logs(&from_filter).await?.into_stream().boxed_local();
+        let to_stream = client.subscribe_logs(&to_filter).await?.into_stream().boxed_local();
+
+        Ok((from_stream, to_stream))
+    }
+
+    /// Build the filters for ERC20 transfers from and to the given owners
+    ///
+    /// Returns `None` if there are no owners to filter on
+    pub(crate) fn transfer_filters(&self, owners: &[Address]) -> Option<(Filter, Filter)> {
+        if owners.is_empty() {
+            return None;
         }
 
+        // Convert owners to topic format for log filtering
+        let owner_topics: Vec<B256> = owners.iter().map(|addr| addr.into_word()).collect();
         let token_addresses: Vec<Address> =
             get_all_tokens().into_iter().map(|t| t.get_alloy_address()).collect();
 
@@ -66,11 +81,7 @@ impl OnChainEventListenerExecutor {
             .event_signature(IERC20::Transfer::SIGNATURE_HASH)
             .topic2(owner_topics);
 
-        // Subscribe to both streams
-        let from_stream = client.subscribe_logs(&from_filter).await?.into_stream().boxed_local();
-        let to_stream = client.subscribe_logs(&to_filter).await?.into_stream().boxed_local();
-
-        Ok((from_stream, to_stream))
+        Some((from_filter, to_filter))
     }
 
     /// Handle a Transfer event
@@ -229,6 +240,13 @@ impl OnChainEventListenerExecutor {
         // orders that use public balance - private orders aren't affected by ERC20
         // changes
         for order_id in order_ids {
+            // Every cluster peer observes the transfer, so only the order's matching
+            // owner runs the engine to avoid racing settlements on the same pair
+            if !self.state().is_matching_owner(&order_id).await? {
+                debug!("skipping matching engine for order {order_id}, not the matching owner");
+                continue;
+            }
+
             let job = MatchingEngineWorkerJob::run_internal_engine(account_id, order_id);
             self.config
                 .matching_engine_queue
@@ -39,16 +39,33 @@ impl OnChainEventListenerExecutor {
             "tracking owners for permit2"
         );
 
-        let owner_topics: Vec<B256> = owners.into_iter().map(|addr| addr.into_word()).collect();
-        if owner_topics.is_empty() {
+        let Some((approval_filter, permit_filter)) = self.permit2_filters(&owners) else {
             log_task!(
                 Task::CreatePermit2Subscriptions,
                 Outcome::Skipped,
                 "no tracked owners; skipping permit2 subscriptions"
             );
             return Ok((stream::empty().boxed_local(), stream::empty().boxed_local()));
+        };
+
+        let approval_stream =
+            client.subscribe_logs(&approval_filter).await?.into_stream().boxed_local();
+        let permit_stream =
+            client.subscribe_logs(&permit_filter).await?.into_stream().boxed_local();
+
+        Ok((approval_stream, permit_stream))
+    }
+
+    /// Build the filters for Permit2 Approval and Permit events by the given
+    /// owners
+    ///
+    /// Returns `None` if there are no owners to filter on
+    pub(crate) fn permit2_filters(&self, owners: &[Address]) -> Option<(Filter, Filter)> {
+        if owners.is_empty() {
+            return None;
         }
 
+        let owner_topics: Vec<B256> = owners.iter().map(|addr| addr.into_word()).collect();
         let permit2_addr = self.darkpool_client().permit2_addr();
         let spender_topic = self.darkpool_client().darkpool_addr().into_word();
 
@@ -66,12 +83,7 @@ impl OnChainEventListenerExecutor {
             .topic1(owner_topics)
             .topic3(vec![spender_topic]);
 
-        let approval_stream =
-            client.subscribe_logs(&approval_filter).await?.into_stream().boxed_local();
-        let permit_stream =
-            client.subscribe_logs(&permit_filter).await?.into_stream().boxed_local();
-
-        Ok((approval_stream, permit_stream))
+        Some((approval_filter, permit_filter))
     }
 
     /// Handle a Permit2 Approval or Permit event
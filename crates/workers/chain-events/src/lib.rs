@@ -17,6 +17,7 @@
 #![deny(clippy::needless_pass_by_ref_mut)]
 #![allow(incomplete_features)]
 
+mod checkpoint;
 pub mod error;
 mod executor;
 mod handlers;
@@ -29,6 +29,10 @@ pub enum Task {
     HandlePublicIntentUpdated,
     /// Handling a PublicIntentCancelled darkpool event.
     HandlePublicIntentCancelled,
+    /// Replaying the events emitted since the persisted checkpoint.
+    ResumeFromCheckpoint,
+    /// Persisting the checkpoint of processed events.
+    SaveCheckpoint,
 }
 
 impl LogTask for Task {
@@ -45,6 +49,8 @@ impl LogTask for Task {
             Task::HandleDarkpoolEvent => "handle-darkpool-event",
             Task::HandlePublicIntentUpdated => "handle-public-intent-updated",
             Task::HandlePublicIntentCancelled => "handle-public-intent-cancelled",
+            Task::ResumeFromCheckpoint => "resume-from-checkpoint",
+            Task::SaveCheckpoint => "save-checkpoint",
         }
     }
 }
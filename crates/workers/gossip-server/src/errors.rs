@@ -9,6 +9,8 @@ use state::error::StateError;
 pub enum GossipError {
     /// An error resulting from a cancellation signal
     Cancelled(String),
+    /// An error resolving bootstrap peers from DNS
+    BootstrapDns(String),
     /// An error validating the proof link between `VALID COMMITMENTS` and
     /// `VALID REBLIND`
     CommitmentsReblindLinkVerification(String),
@@ -28,6 +30,8 @@ pub enum GossipError {
     Darkpool(String),
     /// Timer failed to send a heartbeat
     TimerFailed(String),
+    /// A request was received from a peer that may not make it
+    Unauthorized(String),
     /// An unhandled request type was received
     UnhandledRequest(String),
     /// An error verifying a peer's proof of `VALID COMMITMENTS`
@@ -6,6 +6,8 @@
 pub mod errors;
 mod logging;
 mod orderbook;
+mod orderbook_sync;
 pub(crate) mod peer_discovery;
+pub mod rate_limit;
 pub mod server;
 pub mod worker;
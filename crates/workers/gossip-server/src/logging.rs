@@ -20,8 +20,22 @@ pub enum Task {
     PeerIndexing,
     /// Proposing, rejecting, and applying expiry of peers that have timed out.
     PeerExpiry,
+    /// Dialing back newly advertised peers before indexing them.
+    PeerVerification,
     /// Recording the number of local and remote peers as metrics.
     PeerMetrics,
+    /// Estimating clock skew between the local node and its peers from
+    /// heartbeat timestamps.
+    ClockSkew,
+    /// Exchanging heartbeats with peers, including decoding delta encoded
+    /// heartbeats.
+    Heartbeat,
+    /// Resolving bootstrap peers from DNS and bootstrapping into them.
+    BootstrapDiscovery,
+    /// Reconciling the order book with peers by exchanging digests.
+    OrderBookSync,
+    /// Dropping inbound messages from peers that exceed their rate limits.
+    RateLimit,
 }
 
 impl LogTask for Task {
@@ -31,7 +45,13 @@ impl LogTask for Task {
             Task::JobDispatch => "job-dispatch",
             Task::PeerIndexing => "peer-indexing",
             Task::PeerExpiry => "peer-expiry",
+            Task::PeerVerification => "peer-verification",
             Task::PeerMetrics => "peer-metrics",
+            Task::ClockSkew => "clock-skew",
+            Task::Heartbeat => "heartbeat",
+            Task::BootstrapDiscovery => "bootstrap-discovery",
+            Task::OrderBookSync => "order-book-sync",
+            Task::RateLimit => "rate-limit",
         }
     }
 }
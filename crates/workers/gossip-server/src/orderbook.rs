@@ -14,6 +14,7 @@ use tracing::debug;
 use types_account::OrderId;
 use types_gossip::{ClusterId, network_order::NetworkOrder};
 use types_proofs::OrderValidityProofBundle;
+use util::err_str;
 
 use super::{errors::GossipError, server::GossipProtocolExecutor};
 
@@ -62,6 +63,27 @@ impl GossipProtocolExecutor {
                 continue;
             }
 
+            // Never re-add a version of an order that has been removed
+            if self.state.is_order_tombstoned(order.nullifier).await? {
+                debug!("skipping removed version of order {order_id}");
+                continue;
+            }
+
+            // Never roll back the version held locally. An order's nullifier is spent
+            // when its intent is updated, so of two differing versions only the newer
+            // is unspent
+            if let Some(existing) = self.state.get_network_order(&order_id).await? {
+                if existing.nullifier == order.nullifier {
+                    debug!("order {order_id} is up to date");
+                    continue;
+                }
+
+                if self.is_nullifier_spent(order.nullifier).await? {
+                    debug!("skipping stale version of order {order_id}");
+                    continue;
+                }
+            }
+
             // Move fields out of `order_info` before transferring ownership
             let proof = info.validity_proofs;
 
@@ -230,19 +252,22 @@ impl GossipProtocolExecutor {
 
     /// Assert that a nullifier is unused in the contract, returns a GossipError
     /// if the nullifier has been used
-    async fn assert_nullifier_unused(&self, _nullifier: Nullifier) -> Result<(), GossipError> {
-        todo!("Implement `assert_nullifier_unused`");
-        // self.darkpool_client()
-        //     .check_nullifier_used(nullifier)
-        //     .await
-        //     .map(|res| {
-        //         if res {
-        //
-        // Err(GossipError::NullifierUsed(ERR_NULLIFIER_USED.to_string()))
-        //         } else {
-        //             Ok(())
-        //         }
-        //     })
-        //     .map_err(err_str!(GossipError::Darkpool))?
+    async fn assert_nullifier_unused(&self, nullifier: Nullifier) -> Result<(), GossipError> {
+        if self.is_nullifier_spent(nullifier).await? {
+            return Err(GossipError::NullifierUsed(ERR_NULLIFIER_USED.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Whether a nullifier has been spent in the contract
+    pub(crate) async fn is_nullifier_spent(
+        &self,
+        nullifier: Nullifier,
+    ) -> Result<bool, GossipError> {
+        self.darkpool_client()
+            .is_nullifier_spent(nullifier)
+            .await
+            .map_err(err_str!(GossipError::Darkpool))
     }
 }
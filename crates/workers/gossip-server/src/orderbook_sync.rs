@@ -0,0 +1,296 @@
+//! Order book anti-entropy
+//!
+//! Order book updates are broadcast over pubsub, so a node that misses a
+//! message never learns of the update. To converge, each node periodically
+//! sends a peer a digest of its order book; the peer lists its orders in the
+//! buckets where the digests differ, and the node requests only the orders it
+//! is missing or holds a different version of
+//!
+//! Removed orders are kept as tombstones and included in the digest, so that a
+//! peer which has not yet seen an order removed cannot hand it back, and so
+//! that the peer learns of the removal in turn
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use gossip_api::request_response::{
+    GossipRequestType, GossipResponseType,
+    orderbook::{
+        OrderBookDigest, OrderBookSyncRequest, OrderBookSyncResponse, OrderFingerprint,
+        OrderInfoRequest, OrderSyncEntry,
+    },
+};
+use job_types::{
+    gossip_server::{GossipServerJob, GossipServerQueue},
+    network_manager::NetworkManagerJob,
+};
+use state::State;
+use types_account::OrderId;
+use types_gossip::{WrappedPeerId, network_order::NetworkOrder};
+use types_runtime::CancelChannel;
+use util::concurrency::{AsyncShared, new_async_shared};
+use util::err_str;
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{errors::GossipError, logging::Task, server::GossipProtocolExecutor};
+
+/// Error message emitted when a peer outside the peer index requests a sync
+const ERR_UNKNOWN_SYNC_PEER: &str = "order book sync requested by unindexed peer";
+
+impl GossipProtocolExecutor {
+    // ------------
+    // | Outbound |
+    // ------------
+
+    /// Send a digest of the local order book to a peer to reconcile with it
+    ///
+    /// Public so that simulation harnesses may drive syncs without the timer
+    pub async fn send_order_book_sync(&self, peer: WrappedPeerId) -> Result<(), GossipError> {
+        let digest = self.order_book_versions.get(&self.state).await?.digest.clone();
+
+        let req = GossipRequestType::OrderBookSync(OrderBookSyncRequest { digest });
+        self.network_channel
+            .send(NetworkManagerJob::request(peer, req))
+            .map_err(err_str!(GossipError::SendMessage))
+    }
+
+    // -----------
+    // | Inbound |
+    // -----------
+
+    /// Handle a peer's order book digest, listing the local orders in the
+    /// buckets where the digests differ
+    ///
+    /// Only indexed peers, whose cluster membership has been verified, may
+    /// sync
+    pub(crate) async fn handle_order_book_sync_request(
+        &self,
+        peer: WrappedPeerId,
+        req: OrderBookSyncRequest,
+    ) -> Result<GossipResponseType, GossipError> {
+        if self.state.get_peer_info(&peer).await?.is_none() {
+            return Err(GossipError::Unauthorized(format!("{ERR_UNKNOWN_SYNC_PEER}: {peer}")));
+        }
+
+        let local = self.order_book_versions.get(&self.state).await?;
+        let mut divergent = vec![false; local.digest.buckets.len()];
+        for bucket in local.digest.divergent_buckets(&req.digest) {
+            divergent[bucket] = true;
+        }
+
+        let orders = local
+            .entries
+            .iter()
+            .filter(|e| divergent[OrderBookDigest::bucket_of(&e.id)])
+            .copied()
+            .collect();
+        Ok(GossipResponseType::OrderBookSync(OrderBookSyncResponse { orders }))
+    }
+
+    /// Handle a peer's orders in the divergent buckets
+    ///
+    /// Orders that are missing locally or differ from the local version are
+    /// requested, unless the peer's version has been removed locally. Orders
+    /// the peer has removed are removed locally once their nullifier is
+    /// confirmed spent
+    pub(crate) async fn handle_order_book_sync_response(
+        &self,
+        peer: WrappedPeerId,
+        resp: OrderBookSyncResponse,
+    ) -> Result<(), GossipError> {
+        let order_ids = resp.orders.iter().map(|e| e.id).collect::<HashSet<_>>();
+        let order_ids = order_ids.into_iter().collect::<Vec<_>>();
+        let local_orders: HashMap<OrderId, NetworkOrder> = self
+            .state
+            .get_network_orders(&order_ids)
+            .await?
+            .into_iter()
+            .map(|o| (o.id, o))
+            .collect();
+        let local = self.order_book_versions.get(&self.state).await?;
+
+        let mut stale = Vec::new();
+        for entry in resp.orders {
+            let order = local_orders.get(&entry.id);
+
+            // Local orders are updated through raft, never from the network
+            if order.is_some_and(|o| o.local) {
+                continue;
+            }
+
+            if entry.removed {
+                if let Some(order) = order
+                    && OrderFingerprint::of_tombstone(&order.id, order.nullifier)
+                        == entry.fingerprint
+                {
+                    self.remove_spent_order(order).await?;
+                }
+                continue;
+            }
+
+            let up_to_date =
+                order.is_some_and(|o| OrderFingerprint::compute(o) == entry.fingerprint);
+            if !up_to_date && !local.removed.contains(&entry.fingerprint) {
+                stale.push(entry.id);
+            }
+        }
+
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        log_task!(Task::OrderBookSync, Outcome::Ok, subject = %peer, n_orders = stale.len(), "requesting missing or divergent orders");
+        let req = GossipRequestType::OrderInfo(OrderInfoRequest { order_ids: stale });
+        self.network_channel
+            .send(NetworkManagerJob::request(peer, req))
+            .map_err(err_str!(GossipError::SendMessage))
+    }
+
+    /// Remove a version of an order that a peer reports removed, if its
+    /// nullifier is spent
+    ///
+    /// Peers are not trusted to remove orders, so the nullifier is checked
+    /// against the contract first
+    async fn remove_spent_order(&self, order: &NetworkOrder) -> Result<(), GossipError> {
+        if !self.is_nullifier_spent(order.nullifier).await? {
+            return Ok(());
+        }
+
+        log_task!(Task::OrderBookSync, Outcome::Ok, order_id = %order.id, "removing order with spent nullifier");
+        self.state.nullify_orders(order.nullifier).await?;
+        Ok(())
+    }
+}
+
+// ----------------------
+// | Local Book Digests |
+// ----------------------
+
+/// The versions of the orders in the local book, as exchanged in a sync
+pub(crate) struct LocalOrderVersions {
+    /// The live and removed order versions
+    pub entries: Vec<OrderSyncEntry>,
+    /// The digest of the entries
+    pub digest: OrderBookDigest,
+    /// The fingerprints of the live versions that have since been removed
+    pub removed: HashSet<OrderFingerprint>,
+}
+
+impl LocalOrderVersions {
+    /// Build the versions from the orders and tombstones in the book
+    fn new(orders: &[NetworkOrder], tombstones: &[NetworkOrder]) -> Self {
+        let entries = orders
+            .iter()
+            .map(OrderSyncEntry::live)
+            .chain(tombstones.iter().map(OrderSyncEntry::tombstone))
+            .collect::<Vec<_>>();
+        let digest = OrderBookDigest::compute(&entries);
+        let removed = tombstones.iter().map(OrderFingerprint::compute).collect();
+
+        Self { entries, digest, removed }
+    }
+}
+
+/// A cache of the local order book's versions
+///
+/// Computing the versions scans the whole book, so the result is shared by
+/// the syncs of one interval rather than recomputed on each request. A sync
+/// may then miss an update made within the interval; the next sync picks it up
+#[derive(Clone)]
+pub struct OrderBookVersionCache {
+    /// The versions and the time at which they were computed
+    cached: AsyncShared<Option<(Instant, Arc<LocalOrderVersions>)>>,
+    /// How long the versions are reused for
+    max_age: Duration,
+}
+
+impl OrderBookVersionCache {
+    /// Constructor
+    pub fn new(max_age: Duration) -> Self {
+        Self { cached: new_async_shared(None), max_age }
+    }
+
+    /// Get the versions of the local book, recomputing them if they are stale
+    pub(crate) async fn get(&self, state: &State) -> Result<Arc<LocalOrderVersions>, GossipError> {
+        // Hold the write lock while scanning so that concurrent syncs wait on
+        // one scan rather than each starting their own
+        let mut cached = self.cached.write().await;
+        if let Some((computed_at, versions)) = cached.as_ref()
+            && computed_at.elapsed() < self.max_age
+        {
+            return Ok(versions.clone());
+        }
+
+        let (orders, tombstones) = state.get_order_book_versions().await?;
+        let versions = Arc::new(LocalOrderVersions::new(&orders, &tombstones));
+        *cached = Some((Instant::now(), versions.clone()));
+        Ok(versions)
+    }
+}
+
+// ---------
+// | Timer |
+// ---------
+
+/// Enqueues an order book sync with one peer per interval, cycling through the
+/// known peers
+pub(crate) struct OrderBookSyncTimer {
+    /// The gossip server's job queue
+    job_queue: GossipServerQueue,
+    /// The global state
+    state: State,
+    /// The interval between syncs
+    interval: Duration,
+    /// The index of the next peer to sync with
+    next_peer: usize,
+    /// The channel on which the coordinator cancels the gossip server
+    cancel_channel: CancelChannel,
+}
+
+impl OrderBookSyncTimer {
+    /// Constructor
+    pub fn new(
+        job_queue: GossipServerQueue,
+        state: State,
+        interval_ms: u64,
+        cancel_channel: CancelChannel,
+    ) -> Self {
+        let interval = Duration::from_millis(interval_ms);
+        Self { job_queue, state, interval, next_peer: 0, cancel_channel }
+    }
+
+    /// Enqueue order book syncs on the interval until cancelled
+    pub async fn run(mut self) {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.interval) => {},
+                _ = self.cancel_channel.changed() => {
+                    log_task!(Task::OrderBookSync, Outcome::Ok, "order book sync timer cancelled");
+                    return;
+                },
+            }
+
+            if let Err(e) = self.enqueue_sync().await {
+                log_task!(Task::OrderBookSync, Outcome::Failed, error = %e, "failed to enqueue order book sync");
+            }
+        }
+    }
+
+    /// Enqueue a sync with the next peer in the cycle
+    async fn enqueue_sync(&mut self) -> Result<(), GossipError> {
+        let peers = self.state.get_all_peers_ids(false /* include_self */).await?;
+        if peers.is_empty() {
+            return Ok(());
+        }
+
+        let peer = peers[self.next_peer % peers.len()];
+        self.next_peer = self.next_peer.wrapping_add(1);
+        self.job_queue
+            .send(GossipServerJob::ExecuteOrderBookSync(peer))
+            .map_err(err_str!(GossipError::SendMessage))
+    }
+}
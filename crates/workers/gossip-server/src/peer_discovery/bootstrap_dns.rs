@@ -0,0 +1,294 @@
+//! Resolves bootstrap peers from DNS
+//!
+//! The configured DNS names are resolved at startup and re-resolved on an
+//! interval. Each newly resolved peer, or known peer at a new address, is
+//! bootstrapped into as the statically configured bootstrap servers are. A
+//! peer is only recorded as known once it answers the bootstrap request, so
+//! peers that could not be reached are retried on the next refresh
+
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+
+use futures::future::join_all;
+use gossip_api::request_response::{GossipRequestType, GossipResponseType};
+use hickory_resolver::TokioAsyncResolver;
+use job_types::network_manager::NetworkManagerJob;
+use libp2p::{Multiaddr, PeerId, multiaddr::Protocol};
+use types_gossip::{BootstrapDnsSeed, SrvTransport, WrappedPeerId};
+use types_runtime::CancelChannel;
+use util::logging::Outcome;
+use util::{err_str, log_task};
+
+use crate::{
+    errors::GossipError,
+    logging::Task,
+    server::{bootstrap_request, forward_peer_addrs},
+    worker::GossipServerConfig,
+};
+
+/// The prefix of a TXT record holding a bootstrap peer's multiaddr
+const DNSADDR_PREFIX: &str = "dnsaddr=";
+/// The prefix of a TXT record holding a bootstrap peer's ID
+const PEER_ID_PREFIX: &str = "peer_id=";
+/// The amount of time a resolved peer is given to answer a bootstrap request
+const BOOTSTRAP_RESPONSE_TIMEOUT_MS: u64 = 10_000; // 10 seconds
+
+/// Periodically resolves the configured bootstrap DNS names
+pub(crate) struct DnsBootstrapper {
+    /// The DNS resolver
+    resolver: TokioAsyncResolver,
+    /// The gossip server's config
+    config: GossipServerConfig,
+    /// The address each bootstrap peer was last bootstrapped into at
+    known: HashMap<WrappedPeerId, Multiaddr>,
+    /// The channel on which the coordinator cancels the gossip server
+    cancel_channel: CancelChannel,
+}
+
+impl DnsBootstrapper {
+    /// Constructor, using the system's resolver configuration
+    pub fn new(
+        config: GossipServerConfig,
+        cancel_channel: CancelChannel,
+    ) -> Result<Self, GossipError> {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()
+            .map_err(|e| GossipError::ServerSetup(format!("dns resolver: {e}")))?;
+
+        Ok(Self { resolver, config, known: HashMap::new(), cancel_channel })
+    }
+
+    /// Resolve the bootstrap DNS names on the configured interval,
+    /// bootstrapping into each new peer, until cancelled
+    pub async fn run(mut self) {
+        let refresh_interval = Duration::from_millis(self.config.bootstrap_dns_refresh_ms);
+        loop {
+            if let Err(e) = self.refresh().await {
+                log_task!(Task::BootstrapDiscovery, Outcome::Failed, error = %e, "failed to bootstrap into dns peers");
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(refresh_interval) => {},
+                _ = self.cancel_channel.changed() => {
+                    log_task!(Task::BootstrapDiscovery, Outcome::Ok, "dns bootstrapper cancelled");
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Resolve all bootstrap DNS names and bootstrap into the new peers
+    async fn refresh(&mut self) -> Result<(), GossipError> {
+        let mut new_peers = Vec::new();
+        for seed in self.config.bootstrap_dns.iter() {
+            let peers = match self.resolve(seed).await {
+                Ok(peers) => peers,
+                Err(e) => {
+                    log_task!(Task::BootstrapDiscovery, Outcome::Failed, %seed, error = %e, "failed to resolve bootstrap dns name");
+                    continue;
+                },
+            };
+
+            for (peer_id, addr) in peers {
+                if !self.should_bootstrap(&peer_id, &addr) {
+                    continue;
+                }
+
+                new_peers.push((peer_id, addr));
+            }
+        }
+
+        if new_peers.is_empty() {
+            return Ok(());
+        }
+
+        log_task!(
+            Task::BootstrapDiscovery,
+            Outcome::Ok,
+            n_peers = new_peers.len(),
+            "bootstrapping into peers resolved from dns"
+        );
+        forward_peer_addrs(&self.config, &new_peers)?;
+
+        let req = bootstrap_request(&self.config).await?;
+        let responses =
+            join_all(new_peers.iter().map(|(peer_id, _)| self.bootstrap(*peer_id, req.clone())))
+                .await;
+
+        for ((peer_id, addr), bootstrapped) in new_peers.into_iter().zip(responses) {
+            if bootstrapped {
+                self.known.insert(peer_id, addr);
+            } else {
+                log_task!(Task::BootstrapDiscovery, Outcome::Retrying, subject = %peer_id, "dns bootstrap peer did not respond");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a bootstrap request to a peer, returning whether the peer answered
+    /// it before the timeout
+    async fn bootstrap(&self, peer_id: WrappedPeerId, req: GossipRequestType) -> bool {
+        let (job, response) = NetworkManagerJob::request_with_response(peer_id, req);
+        if self.config.network_sender.send(job).is_err() {
+            return false;
+        }
+
+        let timeout = Duration::from_millis(BOOTSTRAP_RESPONSE_TIMEOUT_MS);
+        match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(resp)) => !matches!(resp.body, GossipResponseType::VersionRejected(_)),
+            _ => false,
+        }
+    }
+
+    /// Whether to bootstrap into a resolved peer
+    ///
+    /// Skips the local peer, peers the relayer may not talk to, and peers
+    /// whose address has not changed since they were last resolved
+    fn should_bootstrap(&self, peer_id: &WrappedPeerId, addr: &Multiaddr) -> bool {
+        *peer_id != self.config.local_peer_id
+            && self.config.peer_access.is_allowed(peer_id)
+            && self.known.get(peer_id) != Some(addr)
+    }
+
+    /// Resolve the bootstrap peers published at a DNS name
+    async fn resolve(
+        &self,
+        seed: &BootstrapDnsSeed,
+    ) -> Result<Vec<(WrappedPeerId, Multiaddr)>, GossipError> {
+        match seed {
+            BootstrapDnsSeed::Txt(name) => Ok(self
+                .lookup_txt(name)
+                .await?
+                .iter()
+                .filter_map(|record| parse_dnsaddr_record(record))
+                .collect()),
+            BootstrapDnsSeed::Srv(name) => {
+                // Seeds are validated when parsed, an SRV seed always names its transport
+                let transport = seed.srv_transport().ok_or_else(|| {
+                    GossipError::BootstrapDns(format!("no transport in srv name: {name}"))
+                })?;
+                self.resolve_srv(name, transport).await
+            },
+        }
+    }
+
+    /// Resolve the bootstrap peers pointed to by the SRV records at a name
+    async fn resolve_srv(
+        &self,
+        name: &str,
+        transport: SrvTransport,
+    ) -> Result<Vec<(WrappedPeerId, Multiaddr)>, GossipError> {
+        let records =
+            self.resolver.srv_lookup(name).await.map_err(err_str!(GossipError::BootstrapDns))?;
+
+        let mut peers = Vec::new();
+        for record in records.iter() {
+            let target = record.target().to_utf8();
+            let peer_id =
+                self.lookup_txt(&target).await?.iter().find_map(|r| parse_peer_id_record(r));
+            let ip = self
+                .resolver
+                .lookup_ip(target.as_str())
+                .await
+                .map_err(err_str!(GossipError::BootstrapDns))?
+                .iter()
+                .next();
+
+            match (peer_id, ip) {
+                (Some(peer_id), Some(ip)) => {
+                    let addr = srv_peer_addr(ip, record.port(), transport, peer_id);
+                    peers.push((peer_id, addr));
+                },
+                _ => {
+                    log_task!(Task::BootstrapDiscovery, Outcome::Skipped, %target, "srv target has no peer id record or address");
+                },
+            }
+        }
+
+        Ok(peers)
+    }
+
+    /// Look up the TXT records at a name, each as a single string
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, GossipError> {
+        let records =
+            self.resolver.txt_lookup(name).await.map_err(err_str!(GossipError::BootstrapDns))?;
+        Ok(records
+            .iter()
+            .map(|txt| txt.txt_data().iter().map(|s| String::from_utf8_lossy(s)).collect())
+            .collect())
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Parse a `dnsaddr=<multiaddr>` TXT record into a bootstrap peer
+fn parse_dnsaddr_record(record: &str) -> Option<(WrappedPeerId, Multiaddr)> {
+    let addr: Multiaddr = record.strip_prefix(DNSADDR_PREFIX)?.parse().ok()?;
+    let peer_id = PeerId::try_from_multiaddr(&addr)?;
+    Some((WrappedPeerId(peer_id), addr))
+}
+
+/// Build the multiaddr of a peer named by an SRV record, listening on the
+/// transport given by the SRV name
+fn srv_peer_addr(
+    ip: IpAddr,
+    port: u16,
+    transport: SrvTransport,
+    peer_id: WrappedPeerId,
+) -> Multiaddr {
+    let addr = Multiaddr::from(ip);
+    let addr = match transport {
+        SrvTransport::Udp => addr.with(Protocol::Udp(port)).with(Protocol::QuicV1),
+        SrvTransport::Tcp => addr.with(Protocol::Tcp(port)),
+    };
+
+    addr.with(Protocol::P2p(peer_id.0.into()))
+}
+
+/// Parse a `peer_id=<peer id>` TXT record into a peer ID
+fn parse_peer_id_record(record: &str) -> Option<WrappedPeerId> {
+    record.strip_prefix(PEER_ID_PREFIX)?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use types_gossip::SrvTransport;
+
+    use super::{parse_dnsaddr_record, parse_peer_id_record, srv_peer_addr};
+
+    /// A peer ID used in the tests
+    const PEER_ID: &str = "12D3KooWKKahCLvwJnN4V7aCuzxcrtir58bSqre6qCB6Tjp9WVRu";
+
+    /// Tests parsing bootstrap peers from TXT records
+    #[test]
+    fn test_parse_records() {
+        let addr = format!("/ip4/127.0.0.1/udp/8000/quic-v1/p2p/{PEER_ID}");
+        let (peer_id, parsed_addr) = parse_dnsaddr_record(&format!("dnsaddr={addr}")).unwrap();
+        assert_eq!(peer_id.to_string(), PEER_ID);
+        assert_eq!(parsed_addr.to_string(), addr);
+
+        // A multiaddr without a peer ID, or a record of another kind, is ignored
+        assert!(parse_dnsaddr_record("dnsaddr=/ip4/127.0.0.1/udp/8000/quic-v1").is_none());
+        assert!(parse_dnsaddr_record(&addr).is_none());
+
+        let peer_id = parse_peer_id_record(&format!("peer_id={PEER_ID}")).unwrap();
+        assert_eq!(peer_id.to_string(), PEER_ID);
+        assert!(parse_peer_id_record("v=spf1 -all").is_none());
+    }
+
+    /// Tests building the multiaddr of an SRV peer for each transport
+    #[test]
+    fn test_srv_peer_addr() {
+        let peer_id = parse_peer_id_record(&format!("peer_id={PEER_ID}")).unwrap();
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        let quic = srv_peer_addr(ip, 8000, SrvTransport::Udp, peer_id);
+        assert_eq!(quic.to_string(), format!("/ip4/127.0.0.1/udp/8000/quic-v1/p2p/{PEER_ID}"));
+
+        let tcp = srv_peer_addr(ip, 8000, SrvTransport::Tcp, peer_id);
+        assert_eq!(tcp.to_string(), format!("/ip4/127.0.0.1/tcp/8000/p2p/{PEER_ID}"));
+    }
+}
@@ -0,0 +1,237 @@
+//! Estimates the clock skew between the local node and its peers from the
+//! timestamps carried on heartbeats
+//!
+//! A heartbeat's timestamp is the sender's wall clock time when the message was
+//! built, so the difference between it and the local receive time is the
+//! sender's skew less the one-way network latency. Samples are smoothed per
+//! peer, and the estimate is used to translate heartbeat timestamps attested
+//! by other peers into the local clock before comparing them
+
+use std::collections::HashMap;
+
+use renegade_metrics::labels::{PEER_CLOCK_SKEW_METRIC, PEER_ID_METRIC_TAG};
+use types_gossip::{HeartbeatIntervals, HeartbeatSettings, WrappedPeerId};
+use util::concurrency::{AsyncShared, new_async_shared};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::logging::Task;
+
+/// The skew above which a peer's timestamps are far enough off to distort task
+/// timestamps and event ordering
+pub(crate) const CLOCK_SKEW_WARN_THRESHOLD_MS: u64 = 1_000; // 1 second
+
+/// The skew above which a peer's heartbeat timestamps can no longer be compared
+/// against the expiry windows reliably
+///
+/// This is the window in which a cluster peer attests to a candidate's liveness
+/// under the current heartbeat intervals
+fn critical_threshold_ms(intervals: &HeartbeatIntervals) -> u64 {
+    intervals.cluster_failure_ms / 2
+}
+
+/// The weight given to each new sample when smoothing, as a divisor
+///
+/// I.e. each sample moves the estimate a quarter of the way towards it
+const SKEW_SMOOTHING_DIVISOR: i64 = 4;
+
+/// The severity of a peer's estimated skew
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SkewLevel {
+    /// The skew is within tolerance
+    Normal,
+    /// The skew exceeds the warning threshold
+    Warn,
+    /// The skew exceeds the critical threshold
+    Critical,
+}
+
+impl SkewLevel {
+    /// Classify a skew estimate against the given critical threshold
+    fn from_skew(skew_ms: i64, critical_threshold_ms: u64) -> Self {
+        let magnitude = skew_ms.unsigned_abs();
+        if magnitude > critical_threshold_ms {
+            SkewLevel::Critical
+        } else if magnitude > CLOCK_SKEW_WARN_THRESHOLD_MS {
+            SkewLevel::Warn
+        } else {
+            SkewLevel::Normal
+        }
+    }
+}
+
+/// The skew estimate for a single peer
+#[derive(Clone, Copy, Debug)]
+struct PeerSkew {
+    /// The smoothed skew estimate, positive if the peer's clock is ahead
+    skew_ms: i64,
+    /// The severity last reported for the peer
+    level: SkewLevel,
+}
+
+/// Tracks the estimated clock skew of each peer
+#[derive(Clone)]
+pub struct ClockSkewTracker {
+    /// The skew estimates, keyed by peer
+    skews: AsyncShared<HashMap<WrappedPeerId, PeerSkew>>,
+    /// The heartbeat intervals in use, from which the critical threshold is
+    /// derived
+    heartbeat_settings: HeartbeatSettings,
+}
+
+impl ClockSkewTracker {
+    /// Constructor
+    pub fn new(heartbeat_settings: HeartbeatSettings) -> Self {
+        Self { skews: new_async_shared(HashMap::new()), heartbeat_settings }
+    }
+
+    /// Record a heartbeat timestamp from a peer, received at the given local
+    /// time
+    ///
+    /// Heartbeats without a timestamp are ignored
+    pub async fn record_sample(&self, peer_id: WrappedPeerId, remote_ts: u64, local_ts: u64) {
+        if remote_ts == 0 {
+            return;
+        }
+
+        let sample = remote_ts as i64 - local_ts as i64;
+        let critical_ms = critical_threshold_ms(&self.heartbeat_settings.get());
+        let (skew_ms, prev_level, level) = {
+            let mut skews = self.skews.write().await;
+            let prev = skews.get(&peer_id).copied();
+            let skew_ms = smooth_skew(prev.map(|p| p.skew_ms), sample);
+            let level = SkewLevel::from_skew(skew_ms, critical_ms);
+            skews.insert(peer_id, PeerSkew { skew_ms, level });
+            let prev_level = prev.map(|p| p.level).unwrap_or(SkewLevel::Normal);
+            (skew_ms, prev_level, level)
+        };
+
+        let labels = [(PEER_ID_METRIC_TAG.to_string(), peer_id.to_string())];
+        metrics::gauge!(PEER_CLOCK_SKEW_METRIC, &labels).set(skew_ms as f64);
+        log_level_change(peer_id, skew_ms, prev_level, level);
+    }
+
+    /// Get the estimated skew of a peer, zero if no estimate exists
+    pub async fn get_skew(&self, peer_id: &WrappedPeerId) -> i64 {
+        self.skews.read().await.get(peer_id).map(|p| p.skew_ms).unwrap_or_default()
+    }
+
+    /// Translate a timestamp taken from a peer's clock into the local clock
+    pub async fn to_local_time(&self, peer_id: &WrappedPeerId, remote_ts: u64) -> u64 {
+        let skew = self.get_skew(peer_id).await;
+        remote_ts.saturating_add_signed(-skew)
+    }
+
+    /// Remove a peer's estimate, e.g. when the peer is expired
+    pub async fn remove(&self, peer_id: &WrappedPeerId) {
+        self.skews.write().await.remove(peer_id);
+    }
+}
+
+/// Fold a new sample into the previous skew estimate
+fn smooth_skew(prev: Option<i64>, sample: i64) -> i64 {
+    match prev {
+        Some(prev) => prev + (sample - prev) / SKEW_SMOOTHING_DIVISOR,
+        None => sample,
+    }
+}
+
+/// Log a change in the severity of a peer's skew
+fn log_level_change(peer_id: WrappedPeerId, skew_ms: i64, prev: SkewLevel, new: SkewLevel) {
+    if prev == new {
+        return;
+    }
+
+    match new {
+        SkewLevel::Critical => log_task!(
+            Task::ClockSkew, Outcome::Failed, subject = %peer_id, skew_ms = %skew_ms,
+            "peer clock skew exceeds expiry window tolerance"
+        ),
+        SkewLevel::Warn => log_task!(
+            Task::ClockSkew, Outcome::Partial, subject = %peer_id, skew_ms = %skew_ms,
+            "peer clock skew exceeds warning threshold"
+        ),
+        SkewLevel::Normal => log_task!(
+            Task::ClockSkew, Outcome::Ok, subject = %peer_id, skew_ms = %skew_ms,
+            "peer clock skew back within tolerance"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::{HeartbeatIntervals, HeartbeatSettings, WrappedPeerId};
+
+    use super::{
+        CLOCK_SKEW_WARN_THRESHOLD_MS, ClockSkewTracker, SkewLevel, critical_threshold_ms,
+        smooth_skew,
+    };
+
+    /// Tests folding samples into a skew estimate
+    #[test]
+    fn test_smooth_skew() {
+        assert_eq!(smooth_skew(None, 400), 400);
+        assert_eq!(smooth_skew(Some(400), 800), 500);
+        assert_eq!(smooth_skew(Some(400), 0), 300);
+        assert_eq!(smooth_skew(Some(-400), 400), -200);
+    }
+
+    /// Tests classifying skews by magnitude, in either direction
+    #[test]
+    fn test_skew_level() {
+        let critical_ms = 5_000;
+        let warn_ms = CLOCK_SKEW_WARN_THRESHOLD_MS as i64;
+        assert_eq!(SkewLevel::from_skew(0, critical_ms), SkewLevel::Normal);
+        assert_eq!(SkewLevel::from_skew(warn_ms, critical_ms), SkewLevel::Normal);
+        assert_eq!(SkewLevel::from_skew(warn_ms + 1, critical_ms), SkewLevel::Warn);
+        assert_eq!(SkewLevel::from_skew(-warn_ms - 1, critical_ms), SkewLevel::Warn);
+        assert_eq!(SkewLevel::from_skew(5_001, critical_ms), SkewLevel::Critical);
+        assert_eq!(SkewLevel::from_skew(-5_001, critical_ms), SkewLevel::Critical);
+    }
+
+    /// Tests estimating a peer's skew and translating its timestamps into the
+    /// local clock
+    #[tokio::test]
+    async fn test_to_local_time() {
+        let tracker = ClockSkewTracker::new(HeartbeatSettings::default());
+        let (ahead, behind) = (WrappedPeerId::random(), WrappedPeerId::random());
+
+        // Heartbeats without a timestamp are ignored
+        tracker.record_sample(ahead, 0 /* remote_ts */, 10_000).await;
+        assert_eq!(tracker.get_skew(&ahead).await, 0);
+
+        tracker.record_sample(ahead, 10_500, 10_000).await;
+        tracker.record_sample(behind, 9_800, 10_000).await;
+        assert_eq!(tracker.get_skew(&ahead).await, 500);
+        assert_eq!(tracker.get_skew(&behind).await, -200);
+        assert_eq!(tracker.to_local_time(&ahead, 20_500).await, 20_000);
+        assert_eq!(tracker.to_local_time(&behind, 19_800).await, 20_000);
+
+        // An expired peer's timestamps are taken as is
+        tracker.remove(&ahead).await;
+        assert_eq!(tracker.to_local_time(&ahead, 20_500).await, 20_500);
+    }
+
+    /// Tests that the critical threshold follows the runtime heartbeat
+    /// intervals
+    #[tokio::test]
+    async fn test_critical_threshold() {
+        let settings = HeartbeatSettings::default();
+        let tracker = ClockSkewTracker::new(settings.clone());
+        let peer = WrappedPeerId::random();
+
+        let skew_ms = critical_threshold_ms(&settings.get()) as i64 + 1;
+        tracker.record_sample(peer, skew_ms as u64 + 1, 1).await;
+        assert_eq!(tracker.skews.read().await[&peer].level, SkewLevel::Critical);
+
+        // Widening the cluster failure threshold widens the tolerated skew
+        let intervals = settings.get();
+        let widened = HeartbeatIntervals {
+            cluster_failure_ms: intervals.cluster_failure_ms * 4,
+            ..intervals
+        };
+        settings.set(widened).unwrap();
+        tracker.record_sample(peer, skew_ms as u64 + 1, 1).await;
+        assert_eq!(tracker.skews.read().await[&peer].level, SkewLevel::Warn);
+    }
+}
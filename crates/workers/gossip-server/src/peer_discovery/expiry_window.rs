@@ -1,13 +1,12 @@
 //! Defines a windowed buffer that manages the state transitions of peers in the
 //! network
 
-use std::{
-    collections::HashMap,
-    hash::Hash,
-    time::{Duration, Instant},
-};
+use std::{collections::HashMap, hash::Hash, time::Duration};
 
-use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
+use tokio::{
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+    time::Instant,
+};
 use types_gossip::WrappedPeerId;
 use util::concurrency::{AsyncShared, new_async_shared};
 
@@ -19,6 +18,9 @@ pub(crate) const EXPIRY_CANDIDATE_WINDOW_MS: u64 = 10_000; // 10 seconds
 pub(crate) const EXPIRY_INVISIBILITY_WINDOW_MS: u64 = 60_000; // 1 minute
 
 /// A buffer of time windows
+///
+/// Windows are measured on the Tokio clock, so a test that pauses the runtime's
+/// clock ages them by advancing it
 #[derive(Clone)]
 pub struct TimeWindowBuffer<T: Hash + Eq + PartialEq> {
     /// The set of time windows; maps a key to the time at which it should exit
@@ -70,6 +72,20 @@ impl<T: Hash + Eq + PartialEq> TimeWindowBuffer<T> {
         this.insert(key, expiry_time);
     }
 
+    /// Adds a key to the buffer unless it is still in its time window
+    ///
+    /// Returns whether the key was added
+    pub async fn add_if_absent(&self, key: T, dur: Duration) -> bool {
+        let now = Instant::now();
+        let mut this = self.write_windows().await;
+        if this.get(&key).is_some_and(|expiry_time| *expiry_time > now) {
+            return false;
+        }
+
+        this.insert(key, now + dur);
+        true
+    }
+
     /// Remove a key from the buffer
     pub async fn remove(&self, key: T) {
         let mut this = self.write_windows().await;
@@ -7,37 +7,24 @@ use gossip_api::{
     },
     request_response::{
         GossipRequestType,
-        heartbeat::{HeartbeatMessage, PeerInfoRequest},
+        heartbeat::{HeartbeatAck, HeartbeatMessage, PeerInfoRequest},
         orderbook::OrderInfoRequest,
     },
 };
 use job_types::network_manager::{NetworkManagerControlSignal, NetworkManagerJob};
 use tracing::instrument;
-use types_gossip::{PeerInfo, WrappedPeerId};
+use types_gossip::{FEATURE_HEARTBEAT_CLOCK, PeerInfo, PeerMetadata, WrappedPeerId};
 use util::log_task;
 use util::logging::Outcome;
 use util::{err_str, get_current_time_millis};
 
 use crate::{
-    errors::GossipError, logging::Task, peer_discovery::peer_metrics::record_num_peers_metrics,
+    errors::GossipError,
+    logging::Task,
+    peer_discovery::peer_metrics::{record_heartbeat_failure, record_num_peers_metrics},
     server::GossipProtocolExecutor,
 };
 
-// -------------
-// | Constants |
-// -------------
-
-/// The interval at which to send heartbeats to non-cluster known peers
-pub const HEARTBEAT_INTERVAL_MS: u64 = 10_000; // 10 seconds
-/// The interval at which to send heartbeats to cluster peer
-pub const CLUSTER_HEARTBEAT_INTERVAL_MS: u64 = 3_000; // 3 seconds
-/// The amount of time without a successful heartbeat before the local
-/// relayer should assume its peer has failed; for non-cluster peers
-pub const HEARTBEAT_FAILURE_MS: u64 = 30_000; // 30 seconds
-/// The amount of time without a successful heartbeat before the local
-/// relayer should assume its peer has failed; for cluster peers
-pub const CLUSTER_HEARTBEAT_FAILURE_MS: u64 = 15_000; // 15 seconds
-
 // -----------
 // | Helpers |
 // -----------
@@ -48,6 +35,30 @@ impl GossipProtocolExecutor {
     // | Outbound |
     // ------------
 
+    /// Sends a heartbeat to a peer if one is due
+    ///
+    /// Stable non-cluster peers are heartbeated at a backed off interval, so
+    /// the timer's heartbeats to them are skipped until they are due. The peer
+    /// is still checked for expiry
+    pub async fn maybe_send_heartbeat(
+        &self,
+        recipient_peer_id: WrappedPeerId,
+    ) -> Result<(), GossipError> {
+        let same_cluster = self.is_cluster_peer(&recipient_peer_id).await?;
+        let intervals = self.config.heartbeat_settings.get();
+        let now = get_current_time_millis();
+        let due = self
+            .heartbeat_backoff
+            .should_send(recipient_peer_id, same_cluster, &intervals, now)
+            .await;
+
+        if due {
+            self.send_heartbeat(recipient_peer_id).await
+        } else {
+            self.update_expiry_status(recipient_peer_id).await
+        }
+    }
+
     /// Sends heartbeat message to peers to exchange network information and
     /// ensure liveness
     #[instrument(name = "send_heartbeat", skip(self))]
@@ -59,7 +70,19 @@ impl GossipProtocolExecutor {
             return Ok(());
         }
 
-        let heartbeat_message = self.build_heartbeat().await?;
+        // Advertise the interval at which the recipient is heartbeated, so that it
+        // waits on the local node's backoff before expiring it
+        let same_cluster = self.is_cluster_peer(&recipient_peer_id).await?;
+        let intervals = self.config.heartbeat_settings.get();
+        let now = get_current_time_millis();
+        let mut heartbeat_message = self.build_heartbeat().await?;
+        heartbeat_message.interval_ms = Some(
+            self.heartbeat_backoff
+                .interval_ms(&recipient_peer_id, same_cluster, &intervals, now)
+                .await,
+        );
+        let heartbeat_message =
+            self.heartbeat_deltas.encode(recipient_peer_id, heartbeat_message).await;
         let msg = GossipRequestType::Heartbeat(heartbeat_message);
         let job = NetworkManagerJob::request(recipient_peer_id, msg);
 
@@ -72,14 +95,30 @@ impl GossipProtocolExecutor {
     // ---------------------
 
     /// Handle a heartbeat message from a peer
+    ///
+    /// Returns the acknowledgement to send the peer, if the heartbeat carried
+    /// a digest
     #[instrument(name = "handle_heartbeat", skip(self, message))]
     pub async fn handle_heartbeat(
         &self,
         peer: &WrappedPeerId,
-        message: &HeartbeatMessage,
-    ) -> Result<(), GossipError> {
-        // Record the heartbeat
-        self.record_heartbeat(peer).await?;
+        mut message: HeartbeatMessage,
+    ) -> Result<Option<HeartbeatAck>, GossipError> {
+        // Record the heartbeat and sample the sender's clock skew. A sender that
+        // advertises its capabilities without the clock feature does not send a
+        // timestamp that can be sampled
+        self.record_heartbeat(peer, message.metadata.clone()).await?;
+        let now = get_current_time_millis();
+        let sends_clock =
+            message.metadata.as_ref().is_none_or(|meta| meta.supports(FEATURE_HEARTBEAT_CLOCK));
+        if sends_clock {
+            self.clock_skew.record_sample(*peer, message.timestamp, now).await;
+        }
+        let same_cluster = self.is_cluster_peer(peer).await?;
+        let intervals = self.config.heartbeat_settings.get();
+        self.heartbeat_backoff
+            .record_received(*peer, same_cluster, message.interval_ms, &intervals, now)
+            .await;
 
         // If peer is an expiry candidate, remove it, & send expiry rejection to other
         // peers
@@ -90,8 +129,29 @@ impl GossipProtocolExecutor {
             }
         }
 
+        // Rebuild the sender's known peers if the heartbeat is delta encoded. If
+        // the delta cannot be applied, the sender resends in full next interval
+        let supports_delta = message.digest.is_some();
+        let digest = self.heartbeat_deltas.decode(*peer, &mut message).await;
+        let ack = supports_delta.then_some(HeartbeatAck { digest });
+        if digest.is_none() {
+            log_task!(Task::Heartbeat, Outcome::Retrying, subject = %peer, "could not apply heartbeat delta, requesting full heartbeat");
+            return Ok(ack);
+        }
+
         // Merge the peer info from the heartbeat into the local state
-        self.request_missing_peers(peer, message).await
+        self.request_missing_peers(peer, &message).await?;
+        Ok(ack)
+    }
+
+    /// Handle a peer's acknowledgement of a heartbeat
+    pub async fn handle_heartbeat_ack(
+        &self,
+        peer: &WrappedPeerId,
+        ack: HeartbeatAck,
+    ) -> Result<(), GossipError> {
+        self.heartbeat_deltas.record_ack(peer, ack.digest).await;
+        Ok(())
     }
 
     /// Request any missing orders in the heartbeat message from the given peer
@@ -130,12 +190,22 @@ impl GossipProtocolExecutor {
 
     // --- Heartbeat --- //
 
-    /// Records a successful heartbeat
+    /// Records a successful heartbeat and the metadata advertised in it
     pub(super) async fn record_heartbeat(
         &self,
         peer_id: &WrappedPeerId,
+        metadata: Option<PeerMetadata>,
     ) -> Result<(), GossipError> {
-        Ok(self.state.record_heartbeat(peer_id).await?)
+        Ok(self.state.record_heartbeat(peer_id, metadata).await?)
+    }
+
+    /// Whether the given peer is in the local cluster
+    ///
+    /// Peers not yet indexed are treated as non-cluster peers
+    async fn is_cluster_peer(&self, peer_id: &WrappedPeerId) -> Result<bool, GossipError> {
+        let cluster_id = self.state.get_cluster_id()?;
+        let info = self.state.get_peer_info(peer_id).await?;
+        Ok(info.is_some_and(|info| info.get_cluster_id() == cluster_id))
     }
 
     /// Build a heartbeat message
@@ -168,9 +238,10 @@ impl GossipProtocolExecutor {
         };
 
         // Check whether the expiry window has elapsed
-        if !self.should_expire_peer(&peer_info)? {
+        if !self.should_expire_peer(&peer_info).await? {
             return Ok(());
         }
+        record_heartbeat_failure();
 
         // If the node is outside the cluster expire it immediately
         let cluster_id = self.state.get_cluster_id()?;
@@ -194,22 +265,22 @@ impl GossipProtocolExecutor {
     }
 
     /// Check whether the expiry window for a peer has elapsed
-    fn should_expire_peer(&self, peer_info: &PeerInfo) -> Result<bool, GossipError> {
-        // Expire cluster peers sooner than non-cluster peers
+    ///
+    /// Cluster peers are expired sooner than non-cluster peers, and the window
+    /// of a stable non-cluster peer is backed off along with its interval
+    async fn should_expire_peer(&self, peer_info: &PeerInfo) -> Result<bool, GossipError> {
         let cluster_id = self.state.get_cluster_id()?;
         let same_cluster = peer_info.get_cluster_id() == cluster_id;
 
         let now = get_current_time_millis();
-        let last_heartbeat = now - peer_info.get_last_heartbeat();
-
-        #[allow(clippy::if_same_then_else)]
-        if same_cluster && last_heartbeat < CLUSTER_HEARTBEAT_FAILURE_MS {
-            Ok(false)
-        } else if !same_cluster && last_heartbeat < HEARTBEAT_FAILURE_MS {
-            Ok(false)
-        } else {
-            Ok(true)
-        }
+        let last_heartbeat = now.saturating_sub(peer_info.get_last_heartbeat());
+        let intervals = self.config.heartbeat_settings.get();
+        let failure_ms = self
+            .heartbeat_backoff
+            .failure_ms(&peer_info.get_peer_id(), same_cluster, &intervals, now)
+            .await;
+
+        Ok(last_heartbeat >= failure_ms)
     }
 
     /// Expire a peer that is already an expiry candidate if the attestation
@@ -239,6 +310,10 @@ impl GossipProtocolExecutor {
         // send us a heartbeat attesting to the expired peer's liveness,
         // having itself not expired the peer locally.
         self.expiry_buffer.mark_expired(peer_id).await;
+        self.clock_skew.remove(&peer_id).await;
+        self.heartbeat_deltas.remove(&peer_id).await;
+        self.heartbeat_backoff.remove(&peer_id).await;
+        self.rate_limiter.remove_peer(&peer_id).await;
         record_num_peers_metrics(&self.state).await;
         Ok(())
     }
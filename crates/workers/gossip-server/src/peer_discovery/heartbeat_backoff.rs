@@ -0,0 +1,268 @@
+//! Tracks how long each peer has heartbeated consistently, from which the
+//! interval and failure threshold of non-cluster peers are backed off
+//!
+//! The heartbeat timer enqueues a heartbeat for every peer once per base
+//! interval; heartbeats to backed off peers are skipped until the peer is due.
+//! Each heartbeat advertises the interval at which its sender heartbeats the
+//! recipient, so that the recipient waits on the sender's backoff rather than
+//! its own estimate of it. A peer's stability resets if the gap between its
+//! heartbeats exceeds twice its expected interval
+
+use std::collections::HashMap;
+
+use types_gossip::{HeartbeatIntervals, WrappedPeerId};
+use util::concurrency::{AsyncShared, new_async_shared};
+
+/// The heartbeat cadence of a single peer
+#[derive(Clone, Copy, Debug)]
+struct PeerCadence {
+    /// The time since which the peer has heartbeated consistently
+    stable_since: u64,
+    /// The time of the last heartbeat received from the peer
+    last_received: u64,
+    /// The time of the last heartbeat sent to the peer, if any
+    last_sent: Option<u64>,
+    /// The interval at which the peer last advertised heartbeating the local
+    /// node, if it advertises one
+    advertised_interval_ms: Option<u64>,
+}
+
+impl PeerCadence {
+    /// The time for which the peer has been stable
+    fn stable_for(&self, now: u64) -> u64 {
+        now.saturating_sub(self.stable_since)
+    }
+}
+
+/// Tracks the heartbeat cadence of each peer
+#[derive(Clone)]
+pub struct HeartbeatBackoff {
+    /// The cadence of each peer, keyed by peer
+    peers: AsyncShared<HashMap<WrappedPeerId, PeerCadence>>,
+}
+
+impl HeartbeatBackoff {
+    /// Constructor
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { peers: new_async_shared(HashMap::new()) }
+    }
+
+    /// Record a heartbeat received from a peer at the given time, along with
+    /// the interval at which the peer advertises heartbeating the local node
+    pub async fn record_received(
+        &self,
+        peer_id: WrappedPeerId,
+        same_cluster: bool,
+        advertised_interval_ms: Option<u64>,
+        intervals: &HeartbeatIntervals,
+        now: u64,
+    ) {
+        let mut peers = self.peers.write().await;
+        let cadence = peers.entry(peer_id).or_insert(PeerCadence {
+            stable_since: now,
+            last_received: now,
+            last_sent: None,
+            advertised_interval_ms: None,
+        });
+
+        // The gap is measured against the interval the peer advertised with its
+        // previous heartbeat, as the peer may have backed off since
+        let expected = match cadence.advertised_interval_ms {
+            Some(advertised) => capped_interval_ms(intervals, same_cluster, advertised),
+            None => intervals.peer_interval_ms(same_cluster, cadence.stable_for(now)),
+        };
+        if now.saturating_sub(cadence.last_received) > expected.saturating_mul(2) {
+            cadence.stable_since = now;
+        }
+        cadence.last_received = now;
+
+        // Heartbeats sent outside the timer, e.g. on bootstrap, advertise no interval
+        if advertised_interval_ms.is_some() {
+            cadence.advertised_interval_ms = advertised_interval_ms;
+        }
+    }
+
+    /// The interval at which to heartbeat the given peer, advertised to the
+    /// peer on each heartbeat
+    pub async fn interval_ms(
+        &self,
+        peer_id: &WrappedPeerId,
+        same_cluster: bool,
+        intervals: &HeartbeatIntervals,
+        now: u64,
+    ) -> u64 {
+        let stable_for =
+            self.peers.read().await.get(peer_id).map(|c| c.stable_for(now)).unwrap_or_default();
+        intervals.peer_interval_ms(same_cluster, stable_for)
+    }
+
+    /// Whether a heartbeat is due to the given peer, recording it as sent if
+    /// so
+    ///
+    /// Heartbeats due within half a base interval are sent early, as the timer
+    /// will not revisit the peer until a full base interval has passed
+    pub async fn should_send(
+        &self,
+        peer_id: WrappedPeerId,
+        same_cluster: bool,
+        intervals: &HeartbeatIntervals,
+        now: u64,
+    ) -> bool {
+        let mut peers = self.peers.write().await;
+        let Some(cadence) = peers.get_mut(&peer_id) else {
+            // The peer has not heartbeated yet, so it is not backed off
+            return true;
+        };
+
+        let interval = intervals.peer_interval_ms(same_cluster, cadence.stable_for(now));
+        let slack = intervals.interval_ms / 2;
+        let due = match cadence.last_sent {
+            Some(last_sent) => now.saturating_sub(last_sent).saturating_add(slack) >= interval,
+            None => true,
+        };
+
+        if due {
+            cadence.last_sent = Some(now);
+        }
+        due
+    }
+
+    /// The time without a heartbeat after which the given peer is assumed to
+    /// have failed
+    ///
+    /// If the peer advertises its interval, the threshold is scaled from it;
+    /// otherwise it is scaled from the local estimate of the peer's backoff
+    pub async fn failure_ms(
+        &self,
+        peer_id: &WrappedPeerId,
+        same_cluster: bool,
+        intervals: &HeartbeatIntervals,
+        now: u64,
+    ) -> u64 {
+        let cadence = self.peers.read().await.get(peer_id).copied();
+        if let Some(advertised) = cadence.and_then(|c| c.advertised_interval_ms) {
+            return advertised_failure_ms(intervals, same_cluster, advertised);
+        }
+
+        let stable_for = cadence.map(|c| c.stable_for(now)).unwrap_or_default();
+        intervals.peer_failure_ms(same_cluster, stable_for)
+    }
+
+    /// Remove a peer's cadence, e.g. when the peer is expired
+    pub async fn remove(&self, peer_id: &WrappedPeerId) {
+        self.peers.write().await.remove(peer_id);
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Cap a peer's advertised interval at the largest interval the local node
+/// would back off to, so that a peer cannot advertise its way out of expiry
+fn capped_interval_ms(intervals: &HeartbeatIntervals, same_cluster: bool, advertised: u64) -> u64 {
+    let max_interval = intervals.peer_interval_ms(same_cluster, u64::MAX);
+    advertised.min(max_interval)
+}
+
+/// The failure threshold of a peer heartbeating at the advertised interval
+///
+/// The threshold keeps the local ratio of failure threshold to interval
+fn advertised_failure_ms(
+    intervals: &HeartbeatIntervals,
+    same_cluster: bool,
+    advertised: u64,
+) -> u64 {
+    let interval = capped_interval_ms(intervals, same_cluster, advertised) as u128;
+    let base_interval = intervals.peer_interval_ms(same_cluster, 0).max(1) as u128;
+    let base_failure = intervals.peer_failure_ms(same_cluster, 0) as u128;
+
+    let failure = interval * base_failure / base_interval;
+    u64::try_from(failure).unwrap_or(u64::MAX).max(base_failure as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::{HeartbeatIntervals, WrappedPeerId};
+
+    use super::HeartbeatBackoff;
+
+    /// The intervals used in the tests
+    const INTERVALS: HeartbeatIntervals = HeartbeatIntervals {
+        interval_ms: 10,
+        cluster_interval_ms: 3,
+        failure_ms: 30,
+        cluster_failure_ms: 15,
+        max_backoff: 4,
+        backoff_after_ms: 100,
+    };
+
+    /// Record heartbeats from a peer at the base interval over the given span
+    async fn heartbeat_until(backoff: &HeartbeatBackoff, peer: WrappedPeerId, end: u64) {
+        for now in (0..=end).step_by(INTERVALS.interval_ms as usize) {
+            backoff.record_received(peer, false /* same_cluster */, None, &INTERVALS, now).await;
+        }
+    }
+
+    /// Tests that heartbeats to a stable peer are spaced at the backed off
+    /// interval
+    #[tokio::test]
+    async fn test_should_send() {
+        let backoff = HeartbeatBackoff::new();
+        let peer = WrappedPeerId::random();
+        assert!(backoff.should_send(peer, false /* same_cluster */, &INTERVALS, 0).await);
+
+        // Stable for two doublings, so backed off to the maximum of four intervals
+        heartbeat_until(&backoff, peer, 200).await;
+        assert_eq!(backoff.interval_ms(&peer, false /* same_cluster */, &INTERVALS, 200).await, 40);
+        assert!(backoff.should_send(peer, false /* same_cluster */, &INTERVALS, 200).await);
+        assert!(!backoff.should_send(peer, false /* same_cluster */, &INTERVALS, 210).await);
+
+        // Due within half a base interval of the backed off interval
+        assert!(backoff.should_send(peer, false /* same_cluster */, &INTERVALS, 235).await);
+
+        // Cluster peers are never backed off
+        assert_eq!(backoff.interval_ms(&peer, true /* same_cluster */, &INTERVALS, 200).await, 3);
+    }
+
+    /// Tests that a gap of more than twice the expected interval resets a
+    /// peer's stability
+    #[tokio::test]
+    #[allow(non_snake_case)]
+    async fn test_record_received__gap_resets() {
+        let backoff = HeartbeatBackoff::new();
+        let peer = WrappedPeerId::random();
+        heartbeat_until(&backoff, peer, 200).await;
+        assert_eq!(backoff.failure_ms(&peer, false /* same_cluster */, &INTERVALS, 200).await, 120);
+
+        // The expected interval is 40, so a gap of 100 resets the peer
+        backoff.record_received(peer, false /* same_cluster */, None, &INTERVALS, 300).await;
+        assert_eq!(backoff.failure_ms(&peer, false /* same_cluster */, &INTERVALS, 300).await, 30);
+    }
+
+    /// Tests that the interval a peer advertises sets the expected gap and the
+    /// failure threshold, capped at the maximum backoff
+    #[tokio::test]
+    #[allow(non_snake_case)]
+    async fn test_record_received__advertised_interval() {
+        let backoff = HeartbeatBackoff::new();
+        let peer = WrappedPeerId::random();
+        backoff.record_received(peer, false /* same_cluster */, Some(40), &INTERVALS, 0).await;
+
+        // A gap within twice the advertised interval keeps the peer stable, though
+        // the local estimate of its interval is the base interval
+        backoff.record_received(peer, false /* same_cluster */, Some(40), &INTERVALS, 70).await;
+        assert_eq!(backoff.interval_ms(&peer, false /* same_cluster */, &INTERVALS, 70).await, 10);
+        assert_eq!(backoff.failure_ms(&peer, false /* same_cluster */, &INTERVALS, 70).await, 120);
+
+        // An advertised interval beyond the maximum backoff is capped
+        backoff.record_received(peer, false /* same_cluster */, Some(1_000), &INTERVALS, 80).await;
+        assert_eq!(backoff.failure_ms(&peer, false /* same_cluster */, &INTERVALS, 80).await, 120);
+
+        // A gap beyond twice the capped interval resets the peer
+        backoff.record_received(peer, false /* same_cluster */, Some(10), &INTERVALS, 200).await;
+        assert_eq!(backoff.interval_ms(&peer, false /* same_cluster */, &INTERVALS, 200).await, 10);
+        assert_eq!(backoff.failure_ms(&peer, false /* same_cluster */, &INTERVALS, 200).await, 30);
+    }
+}
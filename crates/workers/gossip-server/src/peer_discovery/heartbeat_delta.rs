@@ -0,0 +1,164 @@
+//! Tracks the heartbeat state exchanged with each peer so that heartbeats may
+//! be delta encoded
+//!
+//! For each recipient, the sender remembers the snapshot of known peers and
+//! orders that the recipient last acknowledged, and sends only the changes
+//! since it. For each sender, the recipient remembers the snapshot it last
+//! reconstructed, against which the sender's deltas are applied. Either side
+//! losing its state, e.g. on restart, surfaces as a digest mismatch, after
+//! which the sender falls back to a full heartbeat
+
+use std::collections::{HashMap, VecDeque};
+
+use gossip_api::request_response::heartbeat::{
+    HeartbeatDigest, HeartbeatMessage, HeartbeatSnapshot,
+};
+use types_gossip::{PeerMetadata, WrappedPeerId};
+use util::concurrency::{AsyncShared, new_async_shared};
+
+/// The maximum number of unacknowledged heartbeats remembered per recipient
+///
+/// Heartbeats are acknowledged asynchronously, so several may be in flight
+/// when the interval is short relative to the round trip time
+const MAX_PENDING_HEARTBEATS: usize = 4;
+
+/// The heartbeat state exchanged with a recipient
+#[derive(Debug, Default)]
+struct OutboundState {
+    /// The snapshot last acknowledged by the recipient, and the metadata sent
+    /// alongside it
+    acked: Option<(HeartbeatSnapshot, Option<PeerMetadata>)>,
+    /// The snapshots sent but not yet acknowledged, oldest first
+    pending: VecDeque<(HeartbeatDigest, HeartbeatSnapshot, Option<PeerMetadata>)>,
+}
+
+/// Tracks the heartbeat snapshots exchanged with each peer
+#[derive(Clone)]
+pub struct HeartbeatDeltaTracker {
+    /// The state of heartbeats sent, keyed by recipient
+    outbound: AsyncShared<HashMap<WrappedPeerId, OutboundState>>,
+    /// The snapshot last reconstructed from each sender's heartbeats
+    inbound: AsyncShared<HashMap<WrappedPeerId, HeartbeatSnapshot>>,
+}
+
+impl HeartbeatDeltaTracker {
+    /// Constructor
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            outbound: new_async_shared(HashMap::new()),
+            inbound: new_async_shared(HashMap::new()),
+        }
+    }
+
+    // ------------
+    // | Outbound |
+    // ------------
+
+    /// Encode a full heartbeat for the given recipient
+    ///
+    /// The heartbeat is tagged with the digest of its known peers and orders.
+    ///
+    /// If the recipient has acknowledged a previous heartbeat, the known peers
+    /// and orders are replaced with the delta since it, and the metadata is
+    /// omitted if unchanged
+    pub async fn encode(
+        &self,
+        recipient: WrappedPeerId,
+        mut message: HeartbeatMessage,
+    ) -> HeartbeatMessage {
+        let snapshot = HeartbeatSnapshot::from_message(&message);
+        let digest = snapshot.digest();
+        message.digest = Some(digest);
+
+        let mut outbound = self.outbound.write().await;
+        let state = outbound.entry(recipient).or_default();
+        let metadata = message.metadata.clone();
+        if let Some((base, acked_metadata)) = &state.acked {
+            message.delta = Some(snapshot.delta_from(base));
+            message.known_peers.clear();
+            message.known_orders.clear();
+            if *acked_metadata == message.metadata {
+                message.metadata = None;
+            }
+        }
+
+        state.pending.retain(|(pending_digest, ..)| *pending_digest != digest);
+        state.pending.push_back((digest, snapshot, metadata));
+        if state.pending.len() > MAX_PENDING_HEARTBEATS {
+            state.pending.pop_front();
+        }
+
+        message
+    }
+
+    /// Record a recipient's acknowledgement of a heartbeat
+    ///
+    /// An empty acknowledgement means the recipient could not apply the
+    /// delta, so the next heartbeat is sent in full
+    pub async fn record_ack(&self, recipient: &WrappedPeerId, digest: Option<HeartbeatDigest>) {
+        let mut outbound = self.outbound.write().await;
+        let Some(state) = outbound.get_mut(recipient) else {
+            return;
+        };
+
+        let Some(digest) = digest else {
+            state.acked = None;
+            state.pending.clear();
+            return;
+        };
+
+        // Heartbeats sent before the acknowledged one will not be acked
+        if let Some(idx) = state.pending.iter().position(|(d, ..)| *d == digest) {
+            let (_, snapshot, metadata) = state.pending.drain(..=idx).next_back().unwrap();
+            state.acked = Some((snapshot, metadata));
+        }
+    }
+
+    // -----------
+    // | Inbound |
+    // -----------
+
+    /// Decode a heartbeat from a sender, filling in its known peers and
+    /// orders from the sender's previous heartbeat if it carries a delta
+    ///
+    /// Returns the digest of the sender's snapshot now held, or `None` if the
+    /// delta could not be applied
+    pub async fn decode(
+        &self,
+        sender: WrappedPeerId,
+        message: &mut HeartbeatMessage,
+    ) -> Option<HeartbeatDigest> {
+        let mut inbound = self.inbound.write().await;
+        let snapshot = match &message.delta {
+            None => HeartbeatSnapshot::from_message(message),
+            Some(delta) => {
+                let applied = inbound.get(&sender).and_then(|base| base.apply(delta));
+                let Some(snapshot) = applied else {
+                    inbound.remove(&sender);
+                    return None;
+                };
+
+                message.known_peers = snapshot.peers.iter().copied().collect();
+                message.known_orders = snapshot.orders.iter().copied().collect();
+                snapshot
+            },
+        };
+
+        // The reconstructed snapshot must match the sender's
+        let digest = snapshot.digest();
+        if message.digest.is_some_and(|expected| expected != digest) {
+            inbound.remove(&sender);
+            return None;
+        }
+
+        inbound.insert(sender, snapshot);
+        Some(digest)
+    }
+
+    /// Remove the state exchanged with a peer, e.g. when the peer is expired
+    pub async fn remove(&self, peer_id: &WrappedPeerId) {
+        self.outbound.write().await.remove(peer_id);
+        self.inbound.write().await.remove(peer_id);
+    }
+}
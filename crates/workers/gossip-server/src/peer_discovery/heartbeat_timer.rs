@@ -4,6 +4,7 @@ use std::{thread, time::Duration};
 
 use job_types::gossip_server::{GossipServerJob, GossipServerQueue};
 use state::State;
+use types_gossip::HeartbeatSettings;
 
 use crate::errors::GossipError;
 
@@ -14,20 +15,17 @@ pub struct HeartbeatTimer;
 
 impl HeartbeatTimer {
     /// Spawns two timers, one for sending intra-cluster heartbeat messages,
-    /// another for inter-cluster The interval parameters specify how often
-    /// the timers should cycle through all peers in their target list
+    /// another for inter-cluster. The heartbeat settings specify how often
+    /// the timers should cycle through all peers in their target list, and are
+    /// re-read on every cycle so that runtime adjustments take effect
     pub fn new(
         job_queue: GossipServerQueue,
-        intra_cluster_interval_ms: u64,
-        inter_cluster_interval_ms: u64,
+        heartbeat_settings: HeartbeatSettings,
         global_state: State,
     ) -> Self {
-        // Narrowing cast is okay, precision is not important here
-        let intra_cluster_wait_period = Duration::from_millis(intra_cluster_interval_ms);
-        let inter_cluster_wait_period = Duration::from_millis(inter_cluster_interval_ms);
-
         // Begin the timing loops
         let job_queue_clone = job_queue.clone();
+        let heartbeat_settings_clone = heartbeat_settings.clone();
         let global_state_clone = global_state.clone();
         thread::Builder::new()
             .name("intra-cluster-heartbeat-timer".to_string())
@@ -35,7 +33,7 @@ impl HeartbeatTimer {
                 Self::execution_loop(
                     true, // intra_cluster
                     job_queue_clone,
-                    intra_cluster_wait_period,
+                    heartbeat_settings_clone,
                     global_state_clone,
                 )
             })
@@ -47,7 +45,7 @@ impl HeartbeatTimer {
                 Self::execution_loop(
                     false, // intra_cluster
                     job_queue,
-                    inter_cluster_wait_period,
+                    heartbeat_settings,
                     global_state,
                 )
             })
@@ -65,14 +63,14 @@ impl HeartbeatTimer {
     /// We space out the heartbeat requests to give a better traffic pattern.
     /// This means that in each time quantum, one heartbeat is scheduled. We
     /// compute the length of a time quantum with respect to the heartbeat
-    /// period constant defined above. That is, we specify the interval in
-    /// between heartbeats for a given peer, and space out all heartbeats in
-    /// that interval
+    /// base heartbeat interval. That is, we specify the interval in between
+    /// heartbeats for a given peer, and space out all heartbeats in that
+    /// interval. Peers that are backed off skip the heartbeats they are not due
     #[allow(clippy::needless_pass_by_value)]
     fn execution_loop(
         intra_cluster: bool,
         job_queue: GossipServerQueue,
-        wait_period: Duration,
+        heartbeat_settings: HeartbeatSettings,
         global_state: State,
     ) -> Result<(), GossipError> {
         let rt = tokio::runtime::Builder::new_current_thread()
@@ -82,6 +80,11 @@ impl HeartbeatTimer {
         let cluster_id = global_state.get_cluster_id()?;
 
         loop {
+            let intervals = heartbeat_settings.get();
+            let interval_ms =
+                if intra_cluster { intervals.cluster_interval_ms } else { intervals.interval_ms };
+            let wait_period = Duration::from_millis(interval_ms);
+
             // Get all peers in the local peer's cluster
             let peers = if intra_cluster {
                 rt.block_on(global_state.get_cluster_peers(&cluster_id))?
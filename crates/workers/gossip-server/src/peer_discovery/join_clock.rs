@@ -0,0 +1,120 @@
+//! Checks the clocks of cluster peers as they join
+//!
+//! A cluster peer bootstrapping through the local node stamps its request with
+//! its wall clock time, which is compared against the local clock. If the
+//! relayer rejects skewed peers, a refused peer is remembered so that it is not
+//! indexed when it is later advertised by another peer; it is admitted again
+//! once it bootstraps with a clock within tolerance
+
+use std::collections::HashSet;
+
+use types_gossip::WrappedPeerId;
+use util::concurrency::{AsyncShared, new_async_shared};
+
+/// The outcome of checking a joining peer's clock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum JoinClockVerdict {
+    /// The peer's clock is within tolerance
+    Accept,
+    /// The peer's clock is beyond tolerance, or could not be checked, but the
+    /// relayer does not reject skewed peers
+    Warn,
+    /// The peer is refused
+    Reject,
+}
+
+/// Checks joining cluster peers' clocks and tracks the peers refused
+#[derive(Clone)]
+pub struct JoinClockGate {
+    /// The largest tolerated difference between a peer's clock and the local
+    /// clock, in milliseconds
+    max_skew_ms: u64,
+    /// Whether to refuse peers beyond the tolerance
+    reject_skewed: bool,
+    /// The peers refused for their clocks
+    rejected: AsyncShared<HashSet<WrappedPeerId>>,
+}
+
+impl JoinClockGate {
+    /// Constructor
+    pub fn new(max_skew_ms: u64, reject_skewed: bool) -> Self {
+        Self { max_skew_ms, reject_skewed, rejected: new_async_shared(HashSet::new()) }
+    }
+
+    /// Check the timestamp on a peer's bootstrap request against the local
+    /// time, recording the verdict for the peer
+    ///
+    /// Requesters that predate the request timestamp send zero; their clocks
+    /// cannot be checked, so they are refused if the relayer rejects skewed
+    /// peers
+    pub(crate) async fn check(
+        &self,
+        peer_id: WrappedPeerId,
+        remote_ts: u64,
+        now: u64,
+    ) -> JoinClockVerdict {
+        let within_tolerance = remote_ts != 0 && remote_ts.abs_diff(now) <= self.max_skew_ms;
+        let verdict = match (within_tolerance, self.reject_skewed) {
+            (true, _) => JoinClockVerdict::Accept,
+            (false, false) => JoinClockVerdict::Warn,
+            (false, true) => JoinClockVerdict::Reject,
+        };
+
+        let mut rejected = self.rejected.write().await;
+        if verdict == JoinClockVerdict::Reject {
+            rejected.insert(peer_id);
+        } else {
+            rejected.remove(&peer_id);
+        }
+        verdict
+    }
+
+    /// Whether the peer was refused for its clock when it last joined
+    pub(crate) async fn is_rejected(&self, peer_id: &WrappedPeerId) -> bool {
+        self.rejected.read().await.contains(peer_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::WrappedPeerId;
+
+    use super::{JoinClockGate, JoinClockVerdict};
+
+    /// The tolerance used in tests
+    const MAX_SKEW_MS: u64 = 1_000;
+    /// The local time used in tests
+    const NOW: u64 = 1_000_000;
+
+    /// Tests the verdicts given when skewed peers are only warned on
+    #[tokio::test]
+    async fn test_warn_skewed() {
+        let gate = JoinClockGate::new(MAX_SKEW_MS, false /* reject_skewed */);
+        let peer = WrappedPeerId::random();
+
+        assert_eq!(gate.check(peer, NOW + MAX_SKEW_MS, NOW).await, JoinClockVerdict::Accept);
+        assert_eq!(gate.check(peer, NOW - MAX_SKEW_MS - 1, NOW).await, JoinClockVerdict::Warn);
+        assert_eq!(gate.check(peer, 0, NOW).await, JoinClockVerdict::Warn);
+        assert!(!gate.is_rejected(&peer).await);
+    }
+
+    /// Tests that refused peers stay refused until they join with a clock
+    /// within tolerance
+    #[tokio::test]
+    async fn test_reject_skewed() {
+        let gate = JoinClockGate::new(MAX_SKEW_MS, true /* reject_skewed */);
+        let peer = WrappedPeerId::random();
+
+        assert_eq!(gate.check(peer, NOW + MAX_SKEW_MS + 1, NOW).await, JoinClockVerdict::Reject);
+        assert!(gate.is_rejected(&peer).await);
+
+        // A peer that cannot be checked is refused as well
+        let legacy = WrappedPeerId::random();
+        assert_eq!(gate.check(legacy, 0, NOW).await, JoinClockVerdict::Reject);
+        assert!(gate.is_rejected(&legacy).await);
+
+        // Rejoining with a corrected clock admits the peer
+        assert_eq!(gate.check(peer, NOW, NOW).await, JoinClockVerdict::Accept);
+        assert!(!gate.is_rejected(&peer).await);
+    }
+}
@@ -1,7 +1,13 @@
 //! Groups handlers for peer discovery and indexing
 
+pub(crate) mod bootstrap_dns;
+pub(crate) mod clock_skew;
 pub(crate) mod expiry_window;
 pub mod heartbeat;
+pub(crate) mod heartbeat_backoff;
+pub(crate) mod heartbeat_delta;
 pub mod heartbeat_timer;
+pub(crate) mod join_clock;
 pub(crate) mod peer_metrics;
 pub mod peers;
+pub(crate) mod verification;
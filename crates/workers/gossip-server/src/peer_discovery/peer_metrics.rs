@@ -1,6 +1,8 @@
 //! Helpers for tracking peer metrics
 
-use renegade_metrics::labels::{NUM_LOCAL_PEERS_METRIC, NUM_REMOTE_PEERS_METRIC};
+use renegade_metrics::labels::{
+    NUM_HEARTBEAT_FAILURES_METRIC, NUM_LOCAL_PEERS_METRIC, NUM_REMOTE_PEERS_METRIC,
+};
 use state::{State, error::StateError};
 use util::log_task;
 use util::logging::Outcome;
@@ -29,3 +31,8 @@ pub async fn record_num_peers_metrics(state: &State) {
     metrics::gauge!(NUM_LOCAL_PEERS_METRIC).set(num_local_peers as f64);
     metrics::gauge!(NUM_REMOTE_PEERS_METRIC).set(num_remote_peers as f64);
 }
+
+/// Record a peer whose heartbeats timed out
+pub fn record_heartbeat_failure() {
+    metrics::counter!(NUM_HEARTBEAT_FAILURES_METRIC).increment(1);
+}
@@ -12,14 +12,14 @@ use gossip_api::{
 };
 use job_types::network_manager::{NetworkManagerControlSignal, NetworkManagerJob};
 use tracing::instrument;
-use types_gossip::{PeerInfo, WrappedPeerId};
+use types_gossip::{PeerAccessList, PeerInfo, WrappedPeerId};
 use util::log_task;
 use util::logging::Outcome;
 use util::{err_str, get_current_time_millis};
 
 use crate::{errors::GossipError, logging::Task, server::GossipProtocolExecutor};
 
-use super::{heartbeat::CLUSTER_HEARTBEAT_FAILURE_MS, peer_metrics::record_num_peers_metrics};
+use super::{join_clock::JoinClockVerdict, peer_metrics::record_num_peers_metrics};
 
 impl GossipProtocolExecutor {
     // --------------------
@@ -51,6 +51,16 @@ impl GossipProtocolExecutor {
         &self,
         req: BootstrapRequest,
     ) -> Result<GossipResponseType, GossipError> {
+        // Check the clock of a peer joining the local cluster, once its cluster
+        // membership is verified
+        let peer_id = req.peer_info.peer_id;
+        if req.peer_info.get_cluster_id() == self.config.cluster_id
+            && req.peer_info.verify_cluster_auth_sig().is_ok()
+            && !self.check_join_clock(peer_id, req.timestamp).await
+        {
+            return Ok(GossipResponseType::Ack);
+        }
+
         // Add the peer to the index
         self.add_new_peers(vec![req.peer_info]).await?;
         let resp = self.build_heartbeat().await?;
@@ -77,8 +87,9 @@ impl GossipProtocolExecutor {
         // If the local peer has received a recent heartbeat from the candidate, notify
         // the sender that the expiry should not proceed
         let now = get_current_time_millis();
-        let time_since_last_heartbeat = now - info.last_heartbeat;
-        if time_since_last_heartbeat < CLUSTER_HEARTBEAT_FAILURE_MS / 2 {
+        let time_since_last_heartbeat = now.saturating_sub(info.last_heartbeat);
+        let cluster_failure_ms = self.config.heartbeat_settings.get().cluster_failure_ms;
+        if time_since_last_heartbeat < cluster_failure_ms / 2 {
             log_task!(
                 Task::PeerExpiry, Outcome::Ok, subject = %peer_id, sender = %sender,
                 time_since_last_heartbeat_ms = %time_since_last_heartbeat,
@@ -120,6 +131,10 @@ impl GossipProtocolExecutor {
             },
         };
 
+        // The attested heartbeat was recorded on the sender's clock, translate it
+        // into the local clock and never accept a heartbeat from the future
+        let now = get_current_time_millis();
+        let last_heartbeat = self.clock_skew.to_local_time(&sender, last_heartbeat).await.min(now);
         if info.last_heartbeat < last_heartbeat {
             info.last_heartbeat = last_heartbeat;
             self.state.set_peer_info(info).await?;
@@ -142,11 +157,44 @@ impl GossipProtocolExecutor {
     // | Helpers |
     // -----------
 
+    /// Check the clock of a cluster peer bootstrapping through the local node
+    ///
+    /// Returns whether the peer may join. A peer whose clock differs from the
+    /// local clock by more than the configured tolerance, or which does not
+    /// report its clock, is refused if the relayer rejects skewed peers, and
+    /// warned on otherwise. A refused peer is not indexed until it joins again
+    /// with its clock in tolerance
+    async fn check_join_clock(&self, peer_id: WrappedPeerId, remote_ts: u64) -> bool {
+        let now = get_current_time_millis();
+        let skew_ms = remote_ts as i64 - now as i64;
+        match self.join_clock.check(peer_id, remote_ts, now).await {
+            JoinClockVerdict::Accept => {},
+            JoinClockVerdict::Warn => log_task!(
+                Task::ClockSkew, Outcome::Partial, subject = %peer_id, skew_ms = %skew_ms,
+                "cluster peer joining with clock skew beyond tolerance or unreported clock"
+            ),
+            JoinClockVerdict::Reject => {
+                log_task!(
+                    Task::ClockSkew, Outcome::Failed, subject = %peer_id, skew_ms = %skew_ms,
+                    "refusing cluster peer with clock skew beyond tolerance or unreported clock"
+                );
+                return false;
+            },
+        }
+
+        self.clock_skew.record_sample(peer_id, remote_ts, now).await;
+        true
+    }
+
     /// Index a new peer if the peer has not been recently expired by the local
     /// party. This is necessary because if we expire a peer, the party sending
     /// a heartbeat may not have expired the faulty peer yet, and may still
     /// send the faulty peer as a known peer. So we
     /// exclude thought-to-be-faulty peers for an "invisibility window"
+    ///
+    /// Peers not yet in the index are only indexed once they respond to a
+    /// dial-back, see the `verification` module. Peers excluded by the
+    /// operator's access lists are never indexed
     async fn add_new_peers(&self, peers: Vec<PeerInfo>) -> Result<(), GossipError> {
         if peers.is_empty() {
             return Ok(());
@@ -160,19 +208,58 @@ impl GossipProtocolExecutor {
                 continue;
             }
 
+            // Check that the operator has not excluded the peer
+            if is_excluded(&peer.peer_id, &self.config.local_peer_id, &self.config.peer_access) {
+                if peer.get_cluster_id() == self.config.cluster_id {
+                    log_task!(Task::PeerIndexing, Outcome::Failed, subject = %peer.peer_id, "cluster peer excluded by access lists, raft cannot reach it");
+                } else {
+                    log_task!(Task::PeerIndexing, Outcome::Skipped, subject = %peer.peer_id, "peer excluded by access lists");
+                }
+                continue;
+            }
+
             // Check that the cluster auth signature on the peer is valid
             if peer.verify_cluster_auth_sig().is_err() {
                 log_task!(Task::PeerIndexing, Outcome::Skipped, subject = %peer.peer_id, "peer info has invalid cluster auth signature");
                 continue;
             }
 
+            // Check that the peer was not refused for its clock when joining the
+            // local cluster
+            if peer.get_cluster_id() == self.config.cluster_id
+                && self.join_clock.is_rejected(&peer.peer_id).await
+            {
+                log_task!(Task::PeerIndexing, Outcome::Skipped, subject = %peer.peer_id, "cluster peer refused for clock skew");
+                continue;
+            }
+
             filtered_peers.push(peer);
         }
 
-        // Add all filtered peers to the network manager's address table
-        self.add_new_addrs(&filtered_peers)?;
-        // Add all filtered peers to the global peer index
-        self.state.add_peer_batch(filtered_peers.clone()).await?;
+        // Peers already in the index were verified when first added, unknown peers
+        // must first be reached at their advertised address
+        let peer_info = self.state.get_peer_info_map().await?;
+        let local_peer_id = self.config.local_peer_id;
+        let (known_peers, unknown_peers): (Vec<_>, Vec<_>) =
+            filtered_peers.into_iter().partition(|peer| {
+                peer.peer_id == local_peer_id || peer_info.contains_key(&peer.peer_id)
+            });
+
+        self.index_peers(known_peers).await?;
+        self.verify_new_peers(unknown_peers).await
+    }
+
+    /// Add peers to the network manager's address table and the global peer
+    /// index
+    pub(super) async fn index_peers(&self, peers: Vec<PeerInfo>) -> Result<(), GossipError> {
+        if peers.is_empty() {
+            return Ok(());
+        }
+
+        // Add all peers to the network manager's address table
+        self.add_new_addrs(&peers)?;
+        // Add all peers to the global peer index
+        self.state.add_peer_batch(peers).await?;
 
         record_num_peers_metrics(&self.state).await;
 
@@ -181,7 +268,7 @@ impl GossipProtocolExecutor {
 
     /// Adds new addresses to the address index in the network manager so that
     /// they may be dialed on outbound
-    fn add_new_addrs(&self, peers: &[PeerInfo]) -> Result<(), GossipError> {
+    pub(super) fn add_new_addrs(&self, peers: &[PeerInfo]) -> Result<(), GossipError> {
         for peer in peers.iter() {
             let job = NetworkManagerJob::internal(NetworkManagerControlSignal::NewAddr {
                 peer_id: peer.peer_id,
@@ -209,3 +296,39 @@ impl GossipProtocolExecutor {
         self.network_channel.send(job).map_err(err_str!(GossipError::SendMessage))
     }
 }
+
+/// Whether the access lists exclude a peer from the index
+///
+/// The local peer is always indexed, whether or not the lists name it
+fn is_excluded(
+    peer_id: &WrappedPeerId,
+    local_peer_id: &WrappedPeerId,
+    peer_access: &PeerAccessList,
+) -> bool {
+    peer_id != local_peer_id && !peer_access.is_allowed(peer_id)
+}
+
+#[cfg(test)]
+mod test {
+    use types_gossip::{PeerAccessList, WrappedPeerId};
+
+    use super::is_excluded;
+
+    /// Tests that peers excluded by the access lists are not indexed, except
+    /// the local peer
+    #[test]
+    fn test_is_excluded() {
+        let (local_peer, allowed, blocked, other) = (
+            WrappedPeerId::random(),
+            WrappedPeerId::random(),
+            WrappedPeerId::random(),
+            WrappedPeerId::random(),
+        );
+        let access = PeerAccessList::new(vec![allowed], vec![blocked]);
+
+        assert!(!is_excluded(&local_peer, &local_peer, &access));
+        assert!(!is_excluded(&allowed, &local_peer, &access));
+        assert!(is_excluded(&blocked, &local_peer, &access));
+        assert!(is_excluded(&other, &local_peer, &access));
+    }
+}
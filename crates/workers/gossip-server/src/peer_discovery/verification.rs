@@ -0,0 +1,184 @@
+//! Dial-back verification of peers advertised by other peers
+//!
+//! A peer learned from a heartbeat or peer info response is only indexed once
+//! the local node has reached it at its advertised address. The address is
+//! handed to the network manager, which dials the peer to deliver a heartbeat.
+//! libp2p authenticates the remote's peer ID when the connection is
+//! established, so a response proves that the peer is reachable and owns the
+//! advertised ID.
+//!
+//! Peers that do not respond within the verification window are dropped from
+//! the routing table and are not dialed again until a backoff elapses. This
+//! keeps unreachable or spoofed addresses out of replica selection
+
+use std::time::Duration;
+
+use gossip_api::request_response::GossipRequestType;
+use job_types::network_manager::{NetworkManagerControlSignal, NetworkManagerJob};
+use types_gossip::{PeerInfo, WrappedPeerId};
+use util::err_str;
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{errors::GossipError, logging::Task, server::GossipProtocolExecutor};
+
+use super::expiry_window::TimeWindowBuffer;
+
+/// The amount of time a peer is given to respond to a dial-back
+pub(crate) const PEER_VERIFICATION_TIMEOUT_MS: u64 = 10_000; // 10 seconds
+/// The amount of time after a failed dial-back before the peer may be dialed
+/// again
+pub(crate) const PEER_VERIFICATION_BACKOFF_MS: u64 = 60_000; // 1 minute
+
+/// Tracks the peers being verified and those that recently failed verification
+#[derive(Clone)]
+pub struct PeerVerificationWindows {
+    /// The peers with a dial-back in flight
+    pending: TimeWindowBuffer<WrappedPeerId>,
+    /// The peers that failed verification and are in their backoff window
+    failed: TimeWindowBuffer<WrappedPeerId>,
+}
+
+impl PeerVerificationWindows {
+    /// Constructor
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { pending: TimeWindowBuffer::new(), failed: TimeWindowBuffer::new() }
+    }
+
+    /// Begin verifying a peer
+    ///
+    /// Returns `false` if the peer is already being verified or recently
+    /// failed verification
+    pub async fn begin(&self, peer_id: WrappedPeerId) -> bool {
+        if self.failed.in_window(&peer_id).await {
+            return false;
+        }
+
+        let dur = Duration::from_millis(PEER_VERIFICATION_TIMEOUT_MS);
+        self.pending.add_if_absent(peer_id, dur).await
+    }
+
+    /// Finish verifying a peer, backing off from the peer if it failed
+    pub async fn finish(&self, peer_id: WrappedPeerId, verified: bool) {
+        self.pending.remove(peer_id).await;
+        if !verified {
+            let dur = Duration::from_millis(PEER_VERIFICATION_BACKOFF_MS);
+            self.failed.add(peer_id, dur).await;
+        }
+    }
+}
+
+impl GossipProtocolExecutor {
+    /// Verify newly advertised peers in the background, indexing each peer
+    /// once it responds to a dial-back
+    pub(super) async fn verify_new_peers(&self, peers: Vec<PeerInfo>) -> Result<(), GossipError> {
+        for peer in peers {
+            if !self.peer_verification.begin(peer.peer_id).await {
+                continue;
+            }
+
+            // The network manager must know the address to dial the peer
+            self.add_new_addrs(std::slice::from_ref(&peer))?;
+            let self_clone = self.clone();
+            tokio::spawn(async move { self_clone.verify_peer(peer).await });
+        }
+
+        Ok(())
+    }
+
+    /// Dial back a peer, indexing it if it responds and dropping its address
+    /// otherwise
+    async fn verify_peer(&self, peer: PeerInfo) {
+        let peer_id = peer.peer_id;
+        let verified = self.dial_back(peer_id).await;
+        self.peer_verification.finish(peer_id, verified).await;
+
+        let res = if verified {
+            log_task!(Task::PeerVerification, Outcome::Ok, subject = %peer_id, "peer verified by dial-back");
+            self.index_peers(vec![peer]).await
+        } else {
+            log_task!(Task::PeerVerification, Outcome::Skipped, subject = %peer_id, "peer did not respond to dial-back, dropping");
+            let job =
+                NetworkManagerJob::internal(NetworkManagerControlSignal::PeerExpired { peer_id });
+            self.network_channel.send(job).map_err(err_str!(GossipError::SendMessage))
+        };
+
+        if let Err(e) = res {
+            log_task!(Task::PeerVerification, Outcome::Failed, subject = %peer_id, error = %e, "error completing peer verification");
+        }
+    }
+
+    /// Send a heartbeat to a peer and await its response
+    ///
+    /// Returns whether the peer responded within the verification window
+    async fn dial_back(&self, peer_id: WrappedPeerId) -> bool {
+        let heartbeat = match self.build_heartbeat().await {
+            Ok(heartbeat) => heartbeat,
+            Err(e) => {
+                log_task!(Task::PeerVerification, Outcome::Failed, subject = %peer_id, error = %e, "error building dial-back heartbeat");
+                return false;
+            },
+        };
+
+        let req = GossipRequestType::Heartbeat(heartbeat);
+        let (job, response) = NetworkManagerJob::request_with_response(peer_id, req);
+        if self.network_channel.send(job).is_err() {
+            return false;
+        }
+
+        let timeout = Duration::from_millis(PEER_VERIFICATION_TIMEOUT_MS);
+        matches!(tokio::time::timeout(timeout, response).await, Ok(Ok(_)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use types_gossip::WrappedPeerId;
+
+    use super::{
+        PEER_VERIFICATION_BACKOFF_MS, PEER_VERIFICATION_TIMEOUT_MS, PeerVerificationWindows,
+    };
+
+    /// Tests that a peer is only dialed back once at a time
+    #[tokio::test(start_paused = true)]
+    async fn test_begin_pending() {
+        let windows = PeerVerificationWindows::new();
+        let peer = WrappedPeerId::random();
+        assert!(windows.begin(peer).await);
+        assert!(!windows.begin(peer).await);
+
+        // A dial-back which outlives the verification window may be retried
+        tokio::time::advance(Duration::from_millis(PEER_VERIFICATION_TIMEOUT_MS + 1)).await;
+        assert!(windows.begin(peer).await);
+    }
+
+    /// Tests that a verified peer may be verified again immediately
+    #[tokio::test(start_paused = true)]
+    async fn test_finish_verified() {
+        let windows = PeerVerificationWindows::new();
+        let peer = WrappedPeerId::random();
+        assert!(windows.begin(peer).await);
+        windows.finish(peer, true /* verified */).await;
+        assert!(windows.begin(peer).await);
+    }
+
+    /// Tests that a peer which failed verification is not dialed again until
+    /// its backoff elapses
+    #[tokio::test(start_paused = true)]
+    async fn test_finish_failed() {
+        let windows = PeerVerificationWindows::new();
+        let peer = WrappedPeerId::random();
+        assert!(windows.begin(peer).await);
+        windows.finish(peer, false /* verified */).await;
+        assert!(!windows.begin(peer).await);
+
+        // Other peers are unaffected
+        assert!(windows.begin(WrappedPeerId::random()).await);
+
+        tokio::time::advance(Duration::from_millis(PEER_VERIFICATION_BACKOFF_MS + 1)).await;
+        assert!(windows.begin(peer).await);
+    }
+}
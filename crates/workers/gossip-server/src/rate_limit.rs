@@ -0,0 +1,361 @@
+//! Per-peer rate limits on inbound gossip
+//!
+//! Each peer holds a token bucket per class of message, sized to the class's
+//! per-minute budget and refilled continuously. A message beyond its peer's
+//! budget is dropped before it is handled, and a peer that repeatedly exceeds
+//! its budgets is penalized: all of its rate limited messages are dropped
+//! until the penalty expires. This keeps a single peer from flooding the proof
+//! verification path
+//!
+//! Only unsolicited messages, i.e. requests and pubsub, are limited; responses
+//! arrive at the rate the local node requests them. Any peer may send
+//! requests, indexed or not, so the state of peers that go idle is swept
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use gossip_api::{
+    pubsub::{PubsubMessage, orderbook::OrderBookManagementMessage},
+    request_response::GossipRequestType,
+};
+use renegade_metrics::labels::{
+    GOSSIP_MESSAGE_CLASS_METRIC_TAG, NUM_GOSSIP_PEERS_PENALIZED_METRIC,
+    NUM_GOSSIP_RATE_LIMITED_METRIC,
+};
+use types_gossip::WrappedPeerId;
+use util::concurrency::{AsyncShared, new_async_shared};
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::logging::Task;
+
+/// The number of seconds in a minute
+const SECONDS_PER_MINUTE: f64 = 60.;
+/// The number of rate limited messages after which a peer is penalized
+const STRIKES_BEFORE_PENALTY: u32 = 10;
+/// The time without a message after which a peer's state is swept
+///
+/// Buckets refill fully within a minute, so a peer idle for this long is
+/// indistinguishable from a new one unless it is penalized
+const IDLE_PEER_EXPIRY: Duration = Duration::from_secs(60);
+/// The interval at which idle peers are swept
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The class of an inbound gossip message, which determines the budget it
+/// draws from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MessageClass {
+    /// A heartbeat
+    Heartbeat,
+    /// An order book message without a proof
+    Order,
+    /// A message carrying a validity proof to verify
+    Proof,
+}
+
+impl MessageClass {
+    /// The name of the class, used to tag metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageClass::Heartbeat => "heartbeat",
+            MessageClass::Order => "order",
+            MessageClass::Proof => "proof",
+        }
+    }
+
+    /// The class of an inbound request, if it is rate limited
+    pub fn of_request(req: &GossipRequestType) -> Option<Self> {
+        match req {
+            GossipRequestType::Heartbeat(_) => Some(MessageClass::Heartbeat),
+            GossipRequestType::OrderInfo(_) | GossipRequestType::OrderBookSync(_) => {
+                Some(MessageClass::Order)
+            },
+            GossipRequestType::Ack
+            | GossipRequestType::Bootstrap(_)
+            | GossipRequestType::PeerInfo(_)
+            | GossipRequestType::Raft(_)
+            | GossipRequestType::Chunk(_) => None,
+        }
+    }
+
+    /// The class of an inbound pubsub message, if it is rate limited
+    pub fn of_pubsub(msg: &PubsubMessage) -> Option<Self> {
+        match msg {
+            PubsubMessage::Orderbook(OrderBookManagementMessage::OrderReceived { .. }) => {
+                Some(MessageClass::Order)
+            },
+            PubsubMessage::Orderbook(OrderBookManagementMessage::OrderProofUpdated { .. }) => {
+                Some(MessageClass::Proof)
+            },
+            PubsubMessage::Cluster(_) | PubsubMessage::Chunk(_) => None,
+        }
+    }
+}
+
+/// The inbound gossip budgets allowed each peer
+///
+/// A budget of zero disables limiting for that class
+#[derive(Copy, Clone, Debug)]
+pub struct GossipRateLimits {
+    /// The number of heartbeats per minute allowed each peer
+    pub heartbeats_per_minute: u32,
+    /// The number of order messages per minute allowed each peer
+    pub orders_per_minute: u32,
+    /// The number of proof carrying messages per minute allowed each peer
+    pub proofs_per_minute: u32,
+    /// How long a peer that repeatedly exceeds its budgets is penalized for
+    pub penalty: Duration,
+}
+
+impl GossipRateLimits {
+    /// The per-minute budget of a message class
+    fn limit(&self, class: MessageClass) -> u32 {
+        match class {
+            MessageClass::Heartbeat => self.heartbeats_per_minute,
+            MessageClass::Order => self.orders_per_minute,
+            MessageClass::Proof => self.proofs_per_minute,
+        }
+    }
+}
+
+/// A token bucket for a single peer and message class
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    /// The number of tokens in the bucket
+    tokens: f64,
+    /// The last time the bucket was refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    fn new(capacity: u32, now: Instant) -> Self {
+        Self { tokens: capacity as f64, last_refill: now }
+    }
+
+    /// Refill the bucket for the time elapsed since the last refill, then try
+    /// to take a token from it
+    fn try_take(&mut self, capacity: u32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        let refill = elapsed * capacity as f64 / SECONDS_PER_MINUTE;
+        self.tokens = (self.tokens + refill).min(capacity as f64);
+        self.last_refill = now;
+
+        if self.tokens < 1. {
+            return false;
+        }
+        self.tokens -= 1.;
+        true
+    }
+}
+
+/// The rate limiting state of a single peer
+#[derive(Clone, Debug, Default)]
+struct PeerLimits {
+    /// The peer's bucket for each message class
+    buckets: HashMap<MessageClass, TokenBucket>,
+    /// The number of messages dropped since the peer was last penalized
+    strikes: u32,
+    /// The time until which the peer is penalized, if it is
+    penalized_until: Option<Instant>,
+    /// The time of the peer's last rate limited message
+    last_seen: Option<Instant>,
+}
+
+impl PeerLimits {
+    /// Whether the peer's state may be swept at the given time
+    fn is_idle(&self, now: Instant) -> bool {
+        let penalized = self.penalized_until.is_some_and(|until| now < until);
+        let recent = self
+            .last_seen
+            .is_some_and(|seen| now.saturating_duration_since(seen) < IDLE_PEER_EXPIRY);
+        !penalized && !recent
+    }
+}
+
+/// The rate limiting state of all peers
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// The rate limiting state of each peer
+    peers: HashMap<WrappedPeerId, PeerLimits>,
+    /// The time of the last sweep of idle peers
+    last_sweep: Option<Instant>,
+}
+
+impl LimiterState {
+    /// Sweep idle peers if a sweep is due
+    fn maybe_sweep(&mut self, now: Instant) {
+        let due = self
+            .last_sweep
+            .is_none_or(|last| now.saturating_duration_since(last) >= SWEEP_INTERVAL);
+        if !due {
+            return;
+        }
+
+        self.peers.retain(|_, state| !state.is_idle(now));
+        self.last_sweep = Some(now);
+    }
+}
+
+/// Rate limits inbound gossip per peer
+#[derive(Clone)]
+pub struct GossipRateLimiter {
+    /// The rate limiting state
+    state: AsyncShared<LimiterState>,
+    /// The budgets allowed each peer
+    limits: GossipRateLimits,
+}
+
+impl GossipRateLimiter {
+    /// Constructor
+    pub fn new(limits: GossipRateLimits) -> Self {
+        Self { state: new_async_shared(LimiterState::default()), limits }
+    }
+
+    /// Take a message of the given class from the peer's budget
+    ///
+    /// Returns whether the message should be handled; messages from penalized
+    /// peers and messages beyond the peer's budget are dropped
+    pub async fn check(&self, peer: WrappedPeerId, class: MessageClass) -> bool {
+        self.check_at(peer, class, Instant::now()).await
+    }
+
+    /// Take a message of the given class from the peer's budget at the given
+    /// time
+    async fn check_at(&self, peer: WrappedPeerId, class: MessageClass, now: Instant) -> bool {
+        let limit = self.limits.limit(class);
+        if limit == 0 {
+            return true;
+        }
+
+        let mut limiter = self.state.write().await;
+        limiter.maybe_sweep(now);
+        let state = limiter.peers.entry(peer).or_default();
+        state.last_seen = Some(now);
+        if let Some(until) = state.penalized_until {
+            if now < until {
+                record_rate_limited(class);
+                return false;
+            }
+            state.penalized_until = None;
+        }
+
+        let bucket = state.buckets.entry(class).or_insert_with(|| TokenBucket::new(limit, now));
+        if bucket.try_take(limit, now) {
+            return true;
+        }
+
+        // Drop the message, penalizing the peer once it has struck out
+        record_rate_limited(class);
+        state.strikes += 1;
+        if state.strikes >= STRIKES_BEFORE_PENALTY {
+            state.strikes = 0;
+            state.penalized_until = Some(now + self.limits.penalty);
+            metrics::counter!(NUM_GOSSIP_PEERS_PENALIZED_METRIC).increment(1);
+            log_task!(Task::RateLimit, Outcome::Failed, subject = %peer, class = class.as_str(), penalty = ?self.limits.penalty, "peer exceeded gossip rate limits, penalizing");
+        }
+
+        false
+    }
+
+    /// Forget the rate limiting state of a peer, e.g. once it expires
+    pub async fn remove_peer(&self, peer: &WrappedPeerId) {
+        self.state.write().await.peers.remove(peer);
+    }
+}
+
+/// Record a dropped message
+fn record_rate_limited(class: MessageClass) {
+    let tag = class.as_str();
+    metrics::counter!(NUM_GOSSIP_RATE_LIMITED_METRIC, GOSSIP_MESSAGE_CLASS_METRIC_TAG => tag)
+        .increment(1);
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use libp2p::PeerId;
+    use types_gossip::WrappedPeerId;
+
+    use super::{
+        GossipRateLimiter, GossipRateLimits, IDLE_PEER_EXPIRY, MessageClass, STRIKES_BEFORE_PENALTY,
+    };
+
+    /// Build a rate limiter with the given per-minute budget for every class
+    fn limiter(limit: u32) -> GossipRateLimiter {
+        GossipRateLimiter::new(GossipRateLimits {
+            heartbeats_per_minute: limit,
+            orders_per_minute: limit,
+            proofs_per_minute: 0,
+            penalty: Duration::from_secs(60),
+        })
+    }
+
+    /// Tests that each peer and class draws from its own budget
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let limiter = limiter(2);
+        let peer1 = WrappedPeerId(PeerId::random());
+        let peer2 = WrappedPeerId(PeerId::random());
+
+        assert!(limiter.check(peer1, MessageClass::Heartbeat).await);
+        assert!(limiter.check(peer1, MessageClass::Heartbeat).await);
+        assert!(!limiter.check(peer1, MessageClass::Heartbeat).await);
+
+        // Other classes and peers are unaffected, and a zero budget disables
+        // limiting
+        assert!(limiter.check(peer1, MessageClass::Order).await);
+        assert!(limiter.check(peer2, MessageClass::Heartbeat).await);
+        for _ in 0..10 {
+            assert!(limiter.check(peer1, MessageClass::Proof).await);
+        }
+    }
+
+    /// Tests that a peer that repeatedly exceeds its budget is penalized
+    #[tokio::test]
+    async fn test_penalty() {
+        let limiter = limiter(1);
+        let peer = WrappedPeerId(PeerId::random());
+
+        assert!(limiter.check(peer, MessageClass::Heartbeat).await);
+        for _ in 0..STRIKES_BEFORE_PENALTY {
+            assert!(!limiter.check(peer, MessageClass::Heartbeat).await);
+        }
+
+        // The penalty applies to the peer's other classes
+        assert!(!limiter.check(peer, MessageClass::Order).await);
+        limiter.remove_peer(&peer).await;
+        assert!(limiter.check(peer, MessageClass::Order).await);
+    }
+
+    /// Tests that idle peers are swept, while penalized peers are kept until
+    /// their penalty expires
+    #[tokio::test]
+    async fn test_sweep_idle_peers() {
+        let limiter = GossipRateLimiter::new(GossipRateLimits {
+            penalty: IDLE_PEER_EXPIRY * 2,
+            ..limiter(1).limits
+        });
+        let idle = WrappedPeerId(PeerId::random());
+        let penalized = WrappedPeerId(PeerId::random());
+        let start = Instant::now();
+
+        assert!(limiter.check_at(idle, MessageClass::Heartbeat, start).await);
+        for _ in 0..=STRIKES_BEFORE_PENALTY {
+            limiter.check_at(penalized, MessageClass::Heartbeat, start).await;
+        }
+
+        // The penalty outlasts the idle expiry, so only the idle peer is swept
+        let later = start + IDLE_PEER_EXPIRY;
+        let new_peer = WrappedPeerId(PeerId::random());
+        assert!(limiter.check_at(new_peer, MessageClass::Heartbeat, later).await);
+
+        let state = limiter.state.read().await;
+        assert!(!state.peers.contains_key(&idle));
+        assert!(state.peers.contains_key(&penalized));
+        assert!(state.peers.contains_key(&new_peer));
+    }
+}
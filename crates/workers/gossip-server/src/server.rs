@@ -20,6 +20,7 @@ use job_types::{
     gossip_server::{GossipServerJob, GossipServerQueue, GossipServerReceiver},
     network_manager::{NetworkManagerControlSignal, NetworkManagerJob, NetworkManagerQueue},
 };
+use libp2p::Multiaddr;
 use state::State;
 use std::{
     thread::JoinHandle,
@@ -31,14 +32,17 @@ use types_runtime::CancelChannel;
 use util::DefaultWrapper;
 use util::log_task;
 use util::logging::Outcome;
-use util::{channels::TracedMessage, err_str};
+use util::{channels::TracedMessage, err_str, get_current_time_millis};
 
 use crate::logging::Task;
+use crate::orderbook_sync::{OrderBookSyncTimer, OrderBookVersionCache};
 use crate::peer_discovery::{
-    expiry_window::PeerExpiryWindows,
-    heartbeat::{CLUSTER_HEARTBEAT_INTERVAL_MS, HEARTBEAT_INTERVAL_MS},
-    heartbeat_timer::HeartbeatTimer,
+    bootstrap_dns::DnsBootstrapper, clock_skew::ClockSkewTracker, expiry_window::PeerExpiryWindows,
+    heartbeat_backoff::HeartbeatBackoff, heartbeat_delta::HeartbeatDeltaTracker,
+    heartbeat_timer::HeartbeatTimer, join_clock::JoinClockGate,
+    verification::PeerVerificationWindows,
 };
+use crate::rate_limit::{GossipRateLimiter, MessageClass};
 
 use super::{errors::GossipError, worker::GossipServerConfig};
 
@@ -76,22 +80,8 @@ impl GossipServer {
         // network manager time to index the peers in the case that these
         // messages are processed concurrently
 
-        // 1. Forward bootstrap addresses to the network manager
-        for (peer_id, address) in self.config.bootstrap_servers.iter().cloned() {
-            let cmd = NetworkManagerControlSignal::NewAddr { peer_id, address };
-            let job = NetworkManagerJob::internal(cmd);
-
-            self.config.network_sender.send(job).map_err(err_str!(GossipError::SendMessage))?;
-        }
-
-        // 2. Send bootstrap requests to all known peers
-        let my_id = self.config.local_peer_id;
-        let my_info = self.state().get_peer_info(&my_id).await?.unwrap();
-        let req = GossipRequestType::Bootstrap(BootstrapRequest { peer_info: my_info });
-        for (peer_id, _) in self.config.bootstrap_servers.iter() {
-            let req = NetworkManagerJob::request(*peer_id, req.clone());
-            self.config.network_sender.send(req).map_err(err_str!(GossipError::SendMessage))?;
-        }
+        // 1-2. Forward bootstrap addresses and send bootstrap requests
+        send_bootstrap_requests(&self.config, &self.config.bootstrap_servers).await?;
 
         // 3. Send heartbeats to all known peers to sync state
         let peer_ids = self.state().get_all_peers_ids(false /* include_self */).await?;
@@ -117,6 +107,22 @@ pub struct GossipProtocolExecutor {
     /// process of being expired or have been expired and are marked as
     /// "invisible"
     pub expiry_buffer: PeerExpiryWindows,
+    /// The estimated clock skew of each peer, derived from heartbeat timestamps
+    pub clock_skew: ClockSkewTracker,
+    /// The clock check on joining cluster peers, and the peers it refused
+    pub join_clock: JoinClockGate,
+    /// The heartbeat snapshots exchanged with each peer, used to delta encode
+    /// heartbeats
+    pub heartbeat_deltas: HeartbeatDeltaTracker,
+    /// How long each peer has heartbeated consistently, from which stable
+    /// non-cluster peers are backed off
+    pub heartbeat_backoff: HeartbeatBackoff,
+    /// The dial-back verification state of peers advertised by other peers
+    pub peer_verification: PeerVerificationWindows,
+    /// The per-peer rate limits on inbound gossip
+    pub rate_limiter: GossipRateLimiter,
+    /// The versions of the local order book exchanged in order book syncs
+    pub order_book_versions: OrderBookVersionCache,
     /// The channel on which to receive jobs
     pub job_receiver: DefaultWrapper<Option<GossipServerReceiver>>,
     /// The channel to send outbound network requests on
@@ -144,6 +150,18 @@ impl GossipProtocolExecutor {
 
         Ok(Self {
             expiry_buffer,
+            clock_skew: ClockSkewTracker::new(config.heartbeat_settings.clone()),
+            join_clock: JoinClockGate::new(
+                config.max_cluster_clock_skew_ms,
+                config.reject_clock_skewed_peers,
+            ),
+            heartbeat_deltas: HeartbeatDeltaTracker::new(),
+            heartbeat_backoff: HeartbeatBackoff::new(),
+            peer_verification: PeerVerificationWindows::new(),
+            rate_limiter: GossipRateLimiter::new(config.rate_limits),
+            order_book_versions: OrderBookVersionCache::new(Duration::from_millis(
+                config.order_book_sync_interval_ms,
+            )),
             job_receiver: DefaultWrapper::new(Some(job_receiver)),
             network_channel,
             state,
@@ -169,12 +187,25 @@ impl GossipProtocolExecutor {
         );
 
         // Start a timer to enqueue outbound heartbeats
-        HeartbeatTimer::new(
-            job_sender,
-            CLUSTER_HEARTBEAT_INTERVAL_MS,
-            HEARTBEAT_INTERVAL_MS,
-            self.state.clone(),
-        );
+        HeartbeatTimer::new(job_sender, self.config.heartbeat_settings.clone(), self.state.clone());
+
+        // Start resolving bootstrap peers from DNS
+        if !self.config.bootstrap_dns.is_empty() {
+            let bootstrapper =
+                DnsBootstrapper::new(self.config.clone(), self.cancel_channel.clone())?;
+            tokio::spawn(bootstrapper.run());
+        }
+
+        // Start a timer to periodically reconcile the order book with peers
+        if self.config.order_book_sync_interval_ms > 0 {
+            let timer = OrderBookSyncTimer::new(
+                self.config.job_sender.clone(),
+                self.state.clone(),
+                self.config.order_book_sync_interval_ms,
+                self.cancel_channel.clone(),
+            );
+            tokio::spawn(timer.run());
+        }
 
         // We check for cancels both before receiving a job (so that we don't sleep
         // after cancellation) and after a receiving a job (so that we avoid
@@ -206,17 +237,33 @@ impl GossipProtocolExecutor {
     async fn handle_job(&self, message: TracedMessage<GossipServerJob>) -> Result<(), GossipError> {
         let start = Instant::now();
         match message.consume() {
-            GossipServerJob::ExecuteHeartbeat(peer_id) => self.send_heartbeat(peer_id).await?,
+            GossipServerJob::ExecuteHeartbeat(peer_id) => {
+                self.maybe_send_heartbeat(peer_id).await?
+            },
+            GossipServerJob::ExecuteOrderBookSync(peer_id) => {
+                self.send_order_book_sync(peer_id).await?
+            },
             GossipServerJob::NetworkRequest(peer_id, req, response_chan) => {
-                let resp = self.handle_request(peer_id, req).await?;
+                // Ack requests over the peer's rate limit without handling them
+                let resp =
+                    if self.within_rate_limit(peer_id, MessageClass::of_request(&req.body)).await {
+                        self.handle_request(peer_id, req).await?
+                    } else {
+                        GossipResponseType::Ack
+                    };
                 let job = NetworkManagerJob::response(resp, response_chan);
 
                 self.network_channel.send(job).map_err(err_str!(GossipError::SendMessage))?;
             },
+            // Responses are solicited by the local node, so are not rate limited
             GossipServerJob::NetworkResponse(peer_id, resp) => {
                 self.handle_response(peer_id, resp).await?
             },
-            GossipServerJob::Pubsub(sender, msg) => self.handle_pubsub(sender, msg).await?,
+            GossipServerJob::Pubsub(sender, msg) => {
+                if self.within_rate_limit(sender, MessageClass::of_pubsub(&msg)).await {
+                    self.handle_pubsub(sender, msg).await?
+                }
+            },
         };
 
         // Log slow jobs
@@ -228,9 +275,23 @@ impl GossipProtocolExecutor {
         Ok(())
     }
 
+    /// Whether an inbound message of the given class is within its sender's
+    /// rate limit
+    ///
+    /// Messages of unlimited classes are always handled
+    async fn within_rate_limit(&self, peer: WrappedPeerId, class: Option<MessageClass>) -> bool {
+        match class {
+            Some(class) => self.rate_limiter.check(peer, class).await,
+            None => true,
+        }
+    }
+
     /// Handles a gossip request type from a peer
+    ///
+    /// Public so that simulation harnesses may deliver requests without a
+    /// network manager
     #[instrument(name = "handle_request", skip(self, req))]
-    async fn handle_request(
+    pub async fn handle_request(
         &self,
         peer: WrappedPeerId,
         req: GossipRequest,
@@ -241,21 +302,24 @@ impl GossipProtocolExecutor {
 
         match req.body {
             GossipRequestType::Bootstrap(req) => self.handle_bootstrap_req(req).await,
-            GossipRequestType::Heartbeat(req) => {
-                self.handle_heartbeat(&peer, &req).await?;
-                Ok(GossipResponseType::Ack)
+            GossipRequestType::Heartbeat(req) => match self.handle_heartbeat(&peer, req).await? {
+                Some(ack) => Ok(GossipResponseType::HeartbeatAck(ack)),
+                None => Ok(GossipResponseType::Ack),
             },
             GossipRequestType::PeerInfo(req) => self.handle_peer_info_req(req.peer_ids).await,
             GossipRequestType::OrderInfo(req) => {
                 self.handle_order_info_request(&req.order_ids).await
             },
+            GossipRequestType::OrderBookSync(req) => {
+                self.handle_order_book_sync_request(peer, req).await
+            },
             req => Err(GossipError::UnhandledRequest(format!("{req:?}"))),
         }
     }
 
     /// Handles a gossip response type from a peer
     #[instrument(name = "handle_response", skip(self, resp))]
-    async fn handle_response(
+    pub async fn handle_response(
         &self,
         peer: WrappedPeerId,
         resp: GossipResponse,
@@ -265,10 +329,17 @@ impl GossipProtocolExecutor {
         }
 
         match resp.body {
-            GossipResponseType::Heartbeat(resp) => self.handle_heartbeat(&peer, &resp).await,
+            GossipResponseType::Heartbeat(resp) => {
+                self.handle_heartbeat(&peer, resp).await?;
+                Ok(())
+            },
+            GossipResponseType::HeartbeatAck(ack) => self.handle_heartbeat_ack(&peer, ack).await,
             GossipResponseType::OrderInfo(resp) => {
                 self.handle_order_info_response(resp.order_info).await
             },
+            GossipResponseType::OrderBookSync(resp) => {
+                self.handle_order_book_sync_response(peer, resp).await
+            },
             GossipResponseType::PeerInfo(resp) => self.handle_peer_info_resp(resp.peer_info).await,
             resp => Err(GossipError::UnhandledRequest(format!("{resp:?}"))),
         }
@@ -276,7 +347,7 @@ impl GossipProtocolExecutor {
 
     /// Handles an inbound pubsub message from the network
     #[instrument(name = "handle_pubsub", skip(self, msg))]
-    async fn handle_pubsub(
+    pub async fn handle_pubsub(
         &self,
         sender: WrappedPeerId,
         msg: PubsubMessage,
@@ -297,6 +368,7 @@ impl GossipProtocolExecutor {
                     },
                 }
             },
+            msg @ PubsubMessage::Chunk(_) => Err(GossipError::UnhandledRequest(format!("{msg:?}"))),
         }
     }
 }
@@ -305,6 +377,49 @@ impl GossipProtocolExecutor {
 // | Helpers |
 // -----------
 
+/// Forward the addresses of the given bootstrap peers to the network manager so
+/// that it may dial them, then request to bootstrap from each peer
+pub(crate) async fn send_bootstrap_requests(
+    config: &GossipServerConfig,
+    peers: &[(WrappedPeerId, Multiaddr)],
+) -> Result<(), GossipError> {
+    forward_peer_addrs(config, peers)?;
+
+    let req = bootstrap_request(config).await?;
+    for (peer_id, _) in peers.iter() {
+        let req = NetworkManagerJob::request(*peer_id, req.clone());
+        config.network_sender.send(req).map_err(err_str!(GossipError::SendMessage))?;
+    }
+
+    Ok(())
+}
+
+/// Forward the addresses of the given peers to the network manager so that it
+/// may dial them
+pub(crate) fn forward_peer_addrs(
+    config: &GossipServerConfig,
+    peers: &[(WrappedPeerId, Multiaddr)],
+) -> Result<(), GossipError> {
+    for (peer_id, address) in peers.iter().cloned() {
+        let cmd = NetworkManagerControlSignal::NewAddr { peer_id, address };
+        let job = NetworkManagerJob::internal(cmd);
+
+        config.network_sender.send(job).map_err(err_str!(GossipError::SendMessage))?;
+    }
+
+    Ok(())
+}
+
+/// Build a request to bootstrap from a peer, carrying the local peer's info
+pub(crate) async fn bootstrap_request(
+    config: &GossipServerConfig,
+) -> Result<GossipRequestType, GossipError> {
+    let my_id = config.local_peer_id;
+    let my_info = config.global_state.get_peer_info(&my_id).await?.unwrap();
+    let timestamp = get_current_time_millis();
+    Ok(GossipRequestType::Bootstrap(BootstrapRequest { peer_info: my_info, timestamp }))
+}
+
 /// Whether or not the relayer should ignore a request
 fn should_ignore_request(req: &GossipRequest) -> bool {
     // Only bootstrap mode currently causes requests to be ignored
@@ -315,12 +430,13 @@ fn should_ignore_request(req: &GossipRequest) -> bool {
     // We intentionally do not have a default case here so that when new request
     // types are added, we will remember to update this function
     match req.body {
-        GossipRequestType::OrderInfo(_) => true,
+        GossipRequestType::OrderInfo(_) | GossipRequestType::OrderBookSync(_) => true,
         GossipRequestType::Ack
         | GossipRequestType::Bootstrap(_)
         | GossipRequestType::Heartbeat(_)
         | GossipRequestType::PeerInfo(_)
-        | GossipRequestType::Raft(_) => false,
+        | GossipRequestType::Raft(_)
+        | GossipRequestType::Chunk(_) => false,
     }
 }
 
@@ -334,11 +450,13 @@ fn should_ignore_response(resp: &GossipResponse) -> bool {
     // We intentionally do not have a default case here so that when new response
     // types are added, we will remember to update this function
     match resp.body {
-        GossipResponseType::OrderInfo(_) => true,
+        GossipResponseType::OrderInfo(_) | GossipResponseType::OrderBookSync(_) => true,
         GossipResponseType::Ack
         | GossipResponseType::Heartbeat(_)
+        | GossipResponseType::HeartbeatAck(_)
         | GossipResponseType::PeerInfo(_)
-        | GossipResponseType::Raft(_) => false,
+        | GossipResponseType::Raft(_)
+        | GossipResponseType::VersionRejected(_) => false,
     }
 }
 
@@ -351,6 +469,6 @@ fn should_ignore_pubsub(msg: &PubsubMessage) -> bool {
 
     match msg {
         PubsubMessage::Orderbook(_) => true,
-        PubsubMessage::Cluster(_) => false,
+        PubsubMessage::Cluster(_) | PubsubMessage::Chunk(_) => false,
     }
 }
@@ -9,7 +9,7 @@ use libp2p::Multiaddr;
 use state::State;
 use std::thread::{Builder, JoinHandle};
 use tokio::runtime::Builder as RuntimeBuilder;
-use types_gossip::{ClusterId, WrappedPeerId};
+use types_gossip::{BootstrapDnsSeed, ClusterId, HeartbeatSettings, PeerAccessList, WrappedPeerId};
 use types_runtime::CancelChannel;
 use types_runtime::Worker;
 use util::DefaultWrapper;
@@ -17,6 +17,7 @@ use util::DefaultWrapper;
 use super::server::{GOSSIP_EXECUTOR_N_BLOCKING_THREADS, GOSSIP_EXECUTOR_N_THREADS};
 use super::{
     errors::GossipError,
+    rate_limit::GossipRateLimits,
     server::{GossipProtocolExecutor, GossipServer},
 };
 
@@ -31,6 +32,28 @@ pub struct GossipServerConfig {
     pub cluster_id: ClusterId,
     /// The servers to bootstrap into the network with
     pub bootstrap_servers: Vec<(WrappedPeerId, Multiaddr)>,
+    /// The DNS names to resolve bootstrap peers from
+    pub bootstrap_dns: Vec<BootstrapDnsSeed>,
+    /// The interval at which to re-resolve the bootstrap DNS names, in
+    /// milliseconds
+    pub bootstrap_dns_refresh_ms: u64,
+    /// The heartbeat intervals and failure thresholds, shared with the API
+    /// server which may adjust them at runtime
+    pub heartbeat_settings: HeartbeatSettings,
+    /// The peers the relayer may index, shared with the network manager and
+    /// the API server
+    pub peer_access: PeerAccessList,
+    /// The largest clock difference tolerated between the local node and a
+    /// joining cluster peer, in milliseconds
+    pub max_cluster_clock_skew_ms: u64,
+    /// Whether to refuse joining cluster peers whose clocks differ by more
+    /// than `max_cluster_clock_skew_ms`, rather than warn on them
+    pub reject_clock_skewed_peers: bool,
+    /// The interval at which to reconcile the order book with a peer, in
+    /// milliseconds; zero disables order book sync
+    pub order_book_sync_interval_ms: u64,
+    /// The inbound gossip budgets allowed each peer
+    pub rate_limits: GossipRateLimits,
     /// The darkpool client used for querying contract state
     pub darkpool_client: DarkpoolClient,
     /// A reference to the relayer-global state
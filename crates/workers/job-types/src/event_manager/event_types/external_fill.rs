@@ -1,7 +1,7 @@
 use darkpool_types::{bounded_match_result::BoundedMatchResult, fee::FeeTake};
 use serde::{Deserialize, Serialize};
 use types_account::OrderId;
-use types_core::{AccountId, TimestampedPrice};
+use types_core::{AccountId, TimestampedPrice, timestamped_price_as_float};
 
 /// A fill event on an order, resulting from an external match
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +11,9 @@ pub struct ExternalFillEvent {
     /// The ID of the internal order that received the fill
     pub internal_order_id: OrderId,
     /// The price at which the fill was executed
+    ///
+    /// Exported with its price as a float, as event consumers expect
+    #[serde(with = "timestamped_price_as_float")]
     pub execution_price: TimestampedPrice,
     /// The external match result
     pub external_match_result: BoundedMatchResult,
@@ -1,7 +1,7 @@
 use darkpool_types::{fee::FeeTake, settlement_obligation::SettlementObligation};
 use serde::{Deserialize, Serialize};
 use types_account::account::OrderId;
-use types_core::{AccountId, TimestampedPrice};
+use types_core::{AccountId, TimestampedPrice, timestamped_price_as_float};
 
 /// A fill event on an order, resulting from an internal match
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +11,9 @@ pub struct FillEvent {
     /// The ID of the order that received the fill
     pub order_id: OrderId,
     /// The price at which the fill was executed
+    ///
+    /// Exported with its price as a float, as event consumers expect
+    #[serde(with = "timestamped_price_as_float")]
     pub execution_price: TimestampedPrice,
     /// The settlement obligation
     pub obligation: SettlementObligation,
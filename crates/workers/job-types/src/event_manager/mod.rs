@@ -5,11 +5,11 @@ use std::time::SystemTime;
 
 use renegade_metrics::labels::NUM_EVENT_SEND_FAILURES_METRIC;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::error::SendError;
 use types_core::{AccountId, Chain};
-use util::channels::{TracedTokioReceiver, TracedTokioSender, new_traced_tokio_channel};
 use uuid::Uuid;
 
+use crate::queue::{JobQueue, JobReceiver, QueuePolicy, QueueSendError, new_job_queue};
+
 mod event_types;
 
 pub use event_types::{
@@ -21,14 +21,17 @@ pub use event_types::{
 // | Queue Types |
 // ---------------
 
+/// The policy of the event manager's job queue
+const EVENT_MANAGER_QUEUE_POLICY: QueuePolicy = QueuePolicy::Unbounded;
+
 /// The queue sender type to send events to the event manager
-pub type EventManagerQueue = TracedTokioSender<RelayerEventType>;
+pub type EventManagerQueue = JobQueue<RelayerEventType>;
 /// The queue receiver type to receive events in the event manager
-pub type EventManagerReceiver = TracedTokioReceiver<RelayerEventType>;
+pub type EventManagerReceiver = JobReceiver<RelayerEventType>;
 
 /// Create a new event manager queue and receiver
 pub fn new_event_manager_queue() -> (EventManagerQueue, EventManagerReceiver) {
-    new_traced_tokio_channel()
+    new_job_queue("event_manager", EVENT_MANAGER_QUEUE_POLICY)
 }
 
 /// Send an event to the event manager queue, recording a failure metric if the
@@ -37,7 +40,7 @@ pub fn new_event_manager_queue() -> (EventManagerQueue, EventManagerReceiver) {
 pub fn try_send_event(
     event: RelayerEventType,
     queue: &EventManagerQueue,
-) -> Result<(), SendError<RelayerEventType>> {
+) -> Result<(), QueueSendError<RelayerEventType>> {
     let res = queue.send(event);
     if res.is_err() {
         metrics::counter!(NUM_EVENT_SEND_FAILURES_METRIC).increment(1);
@@ -7,16 +7,20 @@ use gossip_api::{
 };
 use libp2p::request_response::ResponseChannel;
 use types_gossip::WrappedPeerId;
-use util::channels::{TracedTokioReceiver, TracedTokioSender, new_traced_tokio_channel};
+
+use crate::queue::{JobQueue, JobReceiver, QueuePolicy, new_job_queue};
+
+/// The policy of the gossip server's job queue
+const GOSSIP_SERVER_QUEUE_POLICY: QueuePolicy = QueuePolicy::Unbounded;
 
 /// The queue sender type to send jobs to the gossip server
-pub type GossipServerQueue = TracedTokioSender<GossipServerJob>;
+pub type GossipServerQueue = JobQueue<GossipServerJob>;
 /// The queue receiver type to receive jobs from the gossip server
-pub type GossipServerReceiver = TracedTokioReceiver<GossipServerJob>;
+pub type GossipServerReceiver = JobReceiver<GossipServerJob>;
 
 /// Create a new gossip server queue and receiver
 pub fn new_gossip_server_queue() -> (GossipServerQueue, GossipServerReceiver) {
-    new_traced_tokio_channel()
+    new_job_queue("gossip_server", GOSSIP_SERVER_QUEUE_POLICY)
 }
 
 /// Defines a heartbeat job that can be enqueued by other workers in a relayer
@@ -24,6 +28,8 @@ pub fn new_gossip_server_queue() -> (GossipServerQueue, GossipServerReceiver) {
 pub enum GossipServerJob {
     /// Execute a heartbeat to a given peer
     ExecuteHeartbeat(WrappedPeerId),
+    /// Reconcile the order book with a given peer
+    ExecuteOrderBookSync(WrappedPeerId),
     /// An incoming gossip request
     NetworkRequest(WrappedPeerId, GossipRequest, ResponseChannel<AuthenticatedGossipResponse>),
     /// An incoming gossip response
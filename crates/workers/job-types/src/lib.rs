@@ -5,6 +5,7 @@ pub mod gossip_server;
 pub mod matching_engine;
 pub mod network_manager;
 pub mod proof_manager;
+pub mod queue;
 pub mod task_driver;
 
 use tokio::sync::oneshot::{
@@ -4,17 +4,24 @@ use circuit_types::{Amount, fixed_point::FixedPoint};
 use system_bus::gen_atomic_match_response_topic;
 use types_account::{MatchingPoolName, OrderId, order::Order};
 use types_core::{AccountId, TimestampedPriceFp};
-use util::channels::{TracedTokioReceiver, TracedTokioSender, new_traced_tokio_channel};
+
+use crate::queue::{JobQueue, JobReceiver, QueuePolicy, new_job_queue};
+
+/// The number of jobs that may wait in the matching engine worker's queue
+const MATCHING_ENGINE_QUEUE_CAPACITY: usize = 10_000;
+/// The policy of the matching engine worker's job queue
+const MATCHING_ENGINE_QUEUE_POLICY: QueuePolicy =
+    QueuePolicy::Bounded(MATCHING_ENGINE_QUEUE_CAPACITY);
 
 /// The job queue for the matching engine worker
-pub type MatchingEngineWorkerQueue = TracedTokioSender<MatchingEngineWorkerJob>;
+pub type MatchingEngineWorkerQueue = JobQueue<MatchingEngineWorkerJob>;
 /// The job queue receiver for the matching engine worker
-pub type MatchingEngineWorkerReceiver = TracedTokioReceiver<MatchingEngineWorkerJob>;
+pub type MatchingEngineWorkerReceiver = JobReceiver<MatchingEngineWorkerJob>;
 
 /// Create a new matching engine worker queue and receiver
 pub fn new_matching_engine_worker_queue()
 -> (MatchingEngineWorkerQueue, MatchingEngineWorkerReceiver) {
-    new_traced_tokio_channel()
+    new_job_queue("matching_engine", MATCHING_ENGINE_QUEUE_POLICY)
 }
 
 /// Represents a job for the matching engine worker's thread pool to execute
@@ -11,14 +11,19 @@ use libp2p::request_response::ResponseChannel;
 use libp2p_core::Multiaddr;
 use tokio::sync::oneshot::{Receiver as OneshotReceiver, Sender as OneshotSender};
 use types_gossip::WrappedPeerId;
-use util::channels::{TracedTokioReceiver, TracedTokioSender, new_traced_tokio_channel};
 
-use crate::new_response_channel;
+use crate::{
+    new_response_channel,
+    queue::{JobQueue, JobReceiver, QueuePolicy, new_job_queue},
+};
+
+/// The policy of the network manager's job queue
+const NETWORK_MANAGER_QUEUE_POLICY: QueuePolicy = QueuePolicy::Unbounded;
 
 /// The task queue type for the network manager
-pub type NetworkManagerQueue = TracedTokioSender<NetworkManagerJob>;
+pub type NetworkManagerQueue = JobQueue<NetworkManagerJob>;
 /// The task queue receiver type for the network manager
-pub type NetworkManagerReceiver = TracedTokioReceiver<NetworkManagerJob>;
+pub type NetworkManagerReceiver = JobReceiver<NetworkManagerJob>;
 /// The channel type on which the network manager forwards a response to a
 /// particular request
 pub type NetworkResponseChannel = OneshotSender<GossipResponse>;
@@ -28,7 +33,7 @@ pub type NetworkResponseReceiver = OneshotReceiver<GossipResponse>;
 
 /// Create a new network manager queue and receiver
 pub fn new_network_manager_queue() -> (NetworkManagerQueue, NetworkManagerReceiver) {
-    new_traced_tokio_channel()
+    new_job_queue("network_manager", NETWORK_MANAGER_QUEUE_POLICY)
 }
 
 /// The job type for the network manager
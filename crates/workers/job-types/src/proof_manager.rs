@@ -73,18 +73,25 @@ use types_proofs::{
     ValidPrivateProtocolFeePaymentBundle, ValidPrivateRelayerFeePaymentBundle,
     ValidPublicProtocolFeePaymentBundle, ValidPublicRelayerFeePaymentBundle, ValidWithdrawalBundle,
 };
-use util::channels::{
-    TracedCrossbeamReceiver, TracedCrossbeamSender, new_traced_crossbeam_channel,
-};
+
+use crate::queue::{JobQueue, JobReceiver, QueuePolicy, new_job_queue};
+
+/// The number of proof jobs that may wait in the proof manager's queue
+///
+/// Proofs are slow to generate, a burst beyond this is rejected at the sender
+/// rather than buffered without limit
+const PROOF_MANAGER_QUEUE_CAPACITY: usize = 1_000;
+/// The policy of the proof manager's job queue
+const PROOF_MANAGER_QUEUE_POLICY: QueuePolicy = QueuePolicy::Bounded(PROOF_MANAGER_QUEUE_CAPACITY);
 
 /// The queue type for the proof manager
-pub type ProofManagerQueue = TracedCrossbeamSender<ProofManagerJob>;
+pub type ProofManagerQueue = JobQueue<ProofManagerJob>;
 /// The receiver type for the proof manager
-pub type ProofManagerReceiver = TracedCrossbeamReceiver<ProofManagerJob>;
+pub type ProofManagerReceiver = JobReceiver<ProofManagerJob>;
 
 /// Create a new proof manager queue and receiver
 pub fn new_proof_manager_queue() -> (ProofManagerQueue, ProofManagerReceiver) {
-    new_traced_crossbeam_channel()
+    new_job_queue("proof_manager", PROOF_MANAGER_QUEUE_POLICY)
 }
 
 // ------------
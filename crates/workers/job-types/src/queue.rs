@@ -0,0 +1,292 @@
+//! Typed job queue handles used between workers
+//!
+//! Every Tokio-driven worker receives its jobs over a [`JobQueue`] /
+//! [`JobReceiver`] pair. The pair carries tracing context across the channel
+//! boundary, records the queue's length, and applies the queue's
+//! [`QueuePolicy`], so a worker's queue behavior is chosen where the queue is
+//! created rather than at each call site
+
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+
+use renegade_metrics::labels::{
+    JOB_QUEUE_LENGTH_METRIC, JOB_QUEUE_METRIC_TAG, NUM_JOB_QUEUE_REJECTIONS_METRIC,
+};
+use tokio::sync::mpsc::{
+    Receiver as BoundedReceiver, Sender as BoundedSender, UnboundedReceiver, UnboundedSender,
+    channel as bounded_channel,
+    error::{TryRecvError, TrySendError},
+    unbounded_channel,
+};
+use util::channels::TracedMessage;
+
+/// The policy a job queue applies when jobs are sent faster than they are
+/// handled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Buffer every job; the queue never rejects a send
+    Unbounded,
+    /// Buffer up to the given number of jobs and reject sends beyond it
+    Bounded(usize),
+}
+
+/// An error sending a job to a job queue
+///
+/// Holds the job that could not be enqueued
+pub enum QueueSendError<T> {
+    /// The queue is bounded and at capacity
+    Full(T),
+    /// The queue's receiver has been dropped
+    Closed(T),
+}
+
+impl<T> QueueSendError<T> {
+    /// Recover the job that could not be enqueued
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(job) | Self::Closed(job) => job,
+        }
+    }
+}
+
+impl<T> Debug for QueueSendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Full(_) => write!(f, "Full(..)"),
+            Self::Closed(_) => write!(f, "Closed(..)"),
+        }
+    }
+}
+
+impl<T> Display for QueueSendError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Full(_) => write!(f, "job queue is full"),
+            Self::Closed(_) => write!(f, "job queue is closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for QueueSendError<T> {}
+
+/// Create a new job queue with the given name and policy
+///
+/// The name labels the queue's metrics
+pub fn new_job_queue<T>(name: &'static str, policy: QueuePolicy) -> (JobQueue<T>, JobReceiver<T>) {
+    let (sender, receiver) = match policy {
+        QueuePolicy::Unbounded => {
+            let (tx, rx) = unbounded_channel();
+            (QueueSender::Unbounded(tx), QueueReceiver::Unbounded(rx))
+        },
+        QueuePolicy::Bounded(capacity) => {
+            let (tx, rx) = bounded_channel(capacity);
+            (QueueSender::Bounded(tx), QueueReceiver::Bounded(rx))
+        },
+    };
+
+    (JobQueue { sender, name }, JobReceiver { receiver, name })
+}
+
+// ----------
+// | Sender |
+// ----------
+
+/// The channel underlying a job queue's sender
+#[derive(Debug)]
+enum QueueSender<T> {
+    /// An unbounded channel
+    Unbounded(UnboundedSender<TracedMessage<T>>),
+    /// A bounded channel
+    Bounded(BoundedSender<TracedMessage<T>>),
+}
+
+/// The sending half of a worker's job queue
+#[derive(Debug)]
+pub struct JobQueue<T> {
+    /// The underlying channel
+    sender: QueueSender<T>,
+    /// The name of the queue
+    name: &'static str,
+}
+
+impl<T> Clone for JobQueue<T> {
+    fn clone(&self) -> Self {
+        let sender = match &self.sender {
+            QueueSender::Unbounded(tx) => QueueSender::Unbounded(tx.clone()),
+            QueueSender::Bounded(tx) => QueueSender::Bounded(tx.clone()),
+        };
+
+        Self { sender, name: self.name }
+    }
+}
+
+impl<T> JobQueue<T> {
+    /// The name of the queue
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Send a job to the queue
+    ///
+    /// Never blocks; a bounded queue at capacity rejects the job
+    pub fn send(&self, job: T) -> Result<(), QueueSendError<T>> {
+        let msg = TracedMessage::new(job);
+        let res = match &self.sender {
+            QueueSender::Unbounded(tx) => {
+                tx.send(msg).map_err(|e| QueueSendError::Closed(e.0.into_message()))
+            },
+            QueueSender::Bounded(tx) => tx.try_send(msg).map_err(|e| match e {
+                TrySendError::Full(msg) => QueueSendError::Full(msg.into_message()),
+                TrySendError::Closed(msg) => QueueSendError::Closed(msg.into_message()),
+            }),
+        };
+
+        match res {
+            Ok(()) => {
+                metrics::gauge!(JOB_QUEUE_LENGTH_METRIC, JOB_QUEUE_METRIC_TAG => self.name)
+                    .increment(1.0);
+            },
+            Err(QueueSendError::Full(_)) => {
+                metrics::counter!(NUM_JOB_QUEUE_REJECTIONS_METRIC, JOB_QUEUE_METRIC_TAG => self.name)
+                    .increment(1);
+            },
+            Err(QueueSendError::Closed(_)) => {},
+        }
+
+        res
+    }
+}
+
+// ------------
+// | Receiver |
+// ------------
+
+/// The channel underlying a job queue's receiver
+#[derive(Debug)]
+enum QueueReceiver<T> {
+    /// An unbounded channel
+    Unbounded(UnboundedReceiver<TracedMessage<T>>),
+    /// A bounded channel
+    Bounded(BoundedReceiver<TracedMessage<T>>),
+}
+
+/// The receiving half of a worker's job queue
+#[derive(Debug)]
+pub struct JobReceiver<T> {
+    /// The underlying channel
+    receiver: QueueReceiver<T>,
+    /// The name of the queue
+    name: &'static str,
+}
+
+impl<T> JobReceiver<T> {
+    /// The number of jobs waiting in the queue
+    pub fn len(&self) -> usize {
+        match &self.receiver {
+            QueueReceiver::Unbounded(rx) => rx.len(),
+            QueueReceiver::Bounded(rx) => rx.len(),
+        }
+    }
+
+    /// Check if the queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Receive a job from the queue
+    pub async fn recv(&mut self) -> Option<TracedMessage<T>> {
+        let job = match &mut self.receiver {
+            QueueReceiver::Unbounded(rx) => rx.recv().await,
+            QueueReceiver::Bounded(rx) => rx.recv().await,
+        };
+
+        job.inspect(|_| self.record_dequeue())
+    }
+
+    /// Receive a job from the queue, blocking the current thread
+    ///
+    /// For workers that consume their queue from a dedicated thread; panics if
+    /// called from within an async runtime
+    pub fn blocking_recv(&mut self) -> Option<TracedMessage<T>> {
+        let job = match &mut self.receiver {
+            QueueReceiver::Unbounded(rx) => rx.blocking_recv(),
+            QueueReceiver::Bounded(rx) => rx.blocking_recv(),
+        };
+
+        job.inspect(|_| self.record_dequeue())
+    }
+
+    /// Try to receive a job from the queue (non-blocking)
+    pub fn try_recv(&mut self) -> Result<TracedMessage<T>, TryRecvError> {
+        let job = match &mut self.receiver {
+            QueueReceiver::Unbounded(rx) => rx.try_recv(),
+            QueueReceiver::Bounded(rx) => rx.try_recv(),
+        };
+
+        job.inspect(|_| self.record_dequeue())
+    }
+
+    /// Record a job leaving the queue, the sender records each job entering it
+    fn record_dequeue(&self) {
+        metrics::gauge!(JOB_QUEUE_LENGTH_METRIC, JOB_QUEUE_METRIC_TAG => self.name).decrement(1.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::mpsc::error::TryRecvError;
+
+    use super::{QueuePolicy, QueueSendError, new_job_queue};
+
+    /// Tests that a bounded queue rejects jobs beyond its capacity and accepts
+    /// them again once drained
+    #[test]
+    fn test_bounded_queue() {
+        let (queue, mut receiver) = new_job_queue("test", QueuePolicy::Bounded(2));
+        queue.send(1).unwrap();
+        queue.send(2).unwrap();
+        assert_eq!(receiver.len(), 2);
+
+        let err = queue.send(3).unwrap_err();
+        assert!(matches!(err, QueueSendError::Full(3)));
+        assert_eq!(err.into_inner(), 3);
+
+        assert_eq!(receiver.try_recv().unwrap().consume(), 1);
+        queue.send(3).unwrap();
+        assert_eq!(receiver.try_recv().unwrap().consume(), 2);
+        assert_eq!(receiver.try_recv().unwrap().consume(), 3);
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    /// Tests that an unbounded queue never rejects a send
+    #[test]
+    fn test_unbounded_queue() {
+        let (queue, receiver) = new_job_queue("test", QueuePolicy::Unbounded);
+        for i in 0..1_000 {
+            queue.send(i).unwrap();
+        }
+
+        assert_eq!(receiver.len(), 1_000);
+    }
+
+    /// Tests that sends fail once the receiver is dropped, returning the job
+    #[test]
+    fn test_closed_queue() {
+        for policy in [QueuePolicy::Unbounded, QueuePolicy::Bounded(1)] {
+            let (queue, receiver) = new_job_queue("test", policy);
+            drop(receiver);
+
+            let err = queue.send(1).unwrap_err();
+            assert!(matches!(err, QueueSendError::Closed(1)));
+        }
+    }
+
+    /// Tests receiving from a dedicated thread
+    #[test]
+    fn test_blocking_recv() {
+        let (queue, mut receiver) = new_job_queue("test", QueuePolicy::Bounded(1));
+        let handle = std::thread::spawn(move || receiver.blocking_recv().map(|m| m.consume()));
+
+        queue.send(1).unwrap();
+        assert_eq!(handle.join().unwrap(), Some(1));
+    }
+}
@@ -14,6 +14,13 @@ pub enum MatchingEngineError {
     /// No valid bounds could be crated for a bounded match
     #[error("no valid bounds could be created for a bounded match")]
     NoValidBounds,
+    /// The confidence in a pair's price is below the configured minimum
+    #[error("price confidence {0:.2} below the minimum {1:.2}")]
+    LowPriceConfidence(f64, f64),
+    /// The price stream for a pair has not reported within the staleness
+    /// threshold
+    #[error("stale price: {0}")]
+    StalePrice(String),
     /// Error interacting with the price reporter
     #[error("price reporter error: {0}")]
     PriceReporter(String),
@@ -47,6 +54,12 @@ impl MatchingEngineError {
         MatchingEngineError::PriceReporter(e.to_string())
     }
 
+    /// Create a new error from a stale price stream
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn stale_price<T: ToString>(e: T) -> Self {
+        MatchingEngineError::StalePrice(e.to_string())
+    }
+
     /// Create a new error from a send message error
     #[allow(clippy::needless_pass_by_value)]
     pub fn send_message<T: ToString>(e: T) -> Self {
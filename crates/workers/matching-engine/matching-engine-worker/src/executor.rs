@@ -15,6 +15,7 @@ use price_state::PriceStreamStates;
 use state::State;
 use system_bus::SystemBus;
 use tracing::{Instrument, info_span, instrument};
+use types_core::{BlackoutWindow, VwapWindow};
 use util::log_task;
 use util::logging::Outcome;
 
@@ -22,7 +23,10 @@ use crate::logging::Task;
 use types_runtime::CancelChannel;
 use util::{DefaultOption, channels::TracedMessage, concurrency::runtime::sleep_forever_async};
 
-use crate::error::MatchingEngineError;
+use crate::{
+    error::MatchingEngineError,
+    manager::matching::{blackout::BlackoutWindows, staleness::StalePriceSkips},
+};
 
 // -------------
 // | Constants |
@@ -41,10 +45,26 @@ pub struct MatchingEngineExecutor {
     /// The minimum amount of the quote asset that the relayer should settle
     /// matches on
     pub(crate) min_fill_size: Amount,
+    /// Whether to record internal matches as indicative fills rather than
+    /// settling them
+    pub(crate) quote_only: bool,
     /// The number of blocks an external match bundle remains valid
     pub(crate) external_match_validity_window: u64,
+    /// The minimum confidence in a price at which the relayer will match
+    pub(crate) min_price_confidence: f64,
+    /// The amount of the quote asset at or above which a match is priced off
+    /// the VWAP, if enabled
+    pub(crate) vwap_pricing_threshold: Option<Amount>,
+    /// The trailing window over which large matches are priced off the VWAP
+    pub(crate) vwap_pricing_window: VwapWindow,
+    /// The windows during which no new matches are made
+    pub(crate) blackout_windows: BlackoutWindows,
+    /// The orders skipped on a stale price, re-run once it recovers
+    pub(crate) stale_price_skips: StalePriceSkips,
     /// Assets for which matching is disabled
     pub(crate) disabled_assets: HashSet<Address>,
+    /// The only assets for which matching is allowed, if restricted
+    pub(crate) allowed_assets: Option<HashSet<Address>>,
     /// The channel on which other workers enqueue jobs for the protocol
     /// executor
     pub(crate) job_channel: DefaultOption<MatchingEngineWorkerReceiver>,
@@ -68,8 +88,14 @@ impl MatchingEngineExecutor {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         min_fill_size: Amount,
+        quote_only: bool,
         external_match_validity_window: u64,
+        min_price_confidence: f64,
+        vwap_pricing_threshold: Option<Amount>,
+        vwap_pricing_window: VwapWindow,
+        blackout_windows: Vec<BlackoutWindow>,
         disabled_assets: HashSet<Address>,
+        allowed_assets: Option<HashSet<Address>>,
         job_channel: MatchingEngineWorkerReceiver,
         price_streams: PriceStreamStates,
         state: State,
@@ -80,8 +106,15 @@ impl MatchingEngineExecutor {
     ) -> Result<Self, MatchingEngineError> {
         Ok(Self {
             min_fill_size,
+            quote_only,
             external_match_validity_window,
+            min_price_confidence,
+            vwap_pricing_threshold,
+            vwap_pricing_window,
+            blackout_windows: BlackoutWindows::new(blackout_windows),
+            stale_price_skips: StalePriceSkips::new(),
             disabled_assets,
+            allowed_assets,
             job_channel: DefaultOption::new(Some(job_channel)),
             price_streams,
             state,
@@ -100,6 +133,8 @@ impl MatchingEngineExecutor {
         }
 
         let mut job_channel = self.job_channel.take().unwrap();
+        tokio::spawn(self.clone().blackout_monitor_loop());
+        tokio::spawn(self.clone().staleness_monitor_loop());
 
         loop {
             // Await the next job from the scheduler or elsewhere
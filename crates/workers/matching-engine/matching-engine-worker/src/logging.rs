@@ -24,6 +24,12 @@ pub(crate) enum Task {
     ForwardQuote,
     /// Checking whether an order is still valid for matching.
     CheckOrderValid,
+    /// Pricing a large match off the VWAP rather than the spot price.
+    VwapPricing,
+    /// Refreshing blackout windows and re-running orders skipped during them.
+    MatchingBlackout,
+    /// Re-running orders skipped on a stale price once it recovers.
+    StalePrice,
 }
 
 impl LogTask for Task {
@@ -36,6 +42,9 @@ impl LogTask for Task {
             Task::SettleInternalMatch => "settle-internal-match",
             Task::ForwardQuote => "forward-quote",
             Task::CheckOrderValid => "check-order-valid",
+            Task::VwapPricing => "vwap-pricing",
+            Task::MatchingBlackout => "matching-blackout",
+            Task::StalePrice => "stale-price",
         }
     }
 }
@@ -0,0 +1,149 @@
+//! Tracks the matching blackout windows for the matching engine
+//!
+//! The windows set at runtime are cached and refreshed from the state on an
+//! interval, rather than read on every match. Orders skipped during a window
+//! are recorded, and the internal engine is re-run on them once no window is
+//! active. Runtime windows that have passed and will not recur are pruned from
+//! the state by the cluster leader
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use types_account::OrderId;
+use types_core::{AccountId, BlackoutWindow};
+use util::{
+    concurrency::{AsyncShared, new_async_shared},
+    get_current_time_millis, log_task,
+    logging::Outcome,
+};
+
+use crate::{error::MatchingEngineError, executor::MatchingEngineExecutor, logging::Task};
+
+/// The interval at which the runtime windows are refreshed from the state
+const BLACKOUT_REFRESH_INTERVAL_MS: u64 = 1_000; // 1 second
+
+/// The blackout windows checked by the matching engine
+#[derive(Clone)]
+pub(crate) struct BlackoutWindows {
+    /// The windows given in the relayer's config
+    configured: Arc<Vec<BlackoutWindow>>,
+    /// The windows set at runtime, as of the last refresh, or `None` before
+    /// the first refresh
+    runtime: AsyncShared<Option<Vec<BlackoutWindow>>>,
+    /// The orders the internal engine skipped during a window
+    skipped: AsyncShared<HashSet<(AccountId, OrderId)>>,
+}
+
+impl BlackoutWindows {
+    /// Constructor
+    pub fn new(configured: Vec<BlackoutWindow>) -> Self {
+        Self {
+            configured: Arc::new(configured),
+            runtime: new_async_shared(None),
+            skipped: new_async_shared(HashSet::new()),
+        }
+    }
+
+    /// Record an order the internal engine skipped during a window
+    pub async fn record_skipped(&self, account_id: AccountId, order_id: OrderId) {
+        self.skipped.write().await.insert((account_id, order_id));
+    }
+}
+
+impl MatchingEngineExecutor {
+    /// Get the blackout window covering the current time, if matching is
+    /// blacked out
+    ///
+    /// Configured windows are checked before those set at runtime
+    pub(crate) async fn active_blackout_window(
+        &self,
+    ) -> Result<Option<BlackoutWindow>, MatchingEngineError> {
+        let now = get_current_time_millis();
+        let windows = &self.blackout_windows;
+        if let Some(window) = windows.configured.iter().find(|w| w.contains(now)) {
+            return Ok(Some(window.clone()));
+        }
+
+        // Read through to the state until the first refresh
+        if let Some(runtime) = windows.runtime.read().await.as_ref() {
+            return Ok(runtime.iter().find(|w| w.contains(now)).cloned());
+        }
+        Ok(self.state.get_active_blackout_window(now).await?)
+    }
+
+    /// Refresh the runtime windows and re-run the orders skipped during a
+    /// window once it ends, until cancelled
+    pub(crate) async fn blackout_monitor_loop(self) {
+        let mut cancel = self.cancel.clone();
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(BLACKOUT_REFRESH_INTERVAL_MS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = cancel.changed() => return,
+            }
+
+            if let Err(e) = self.refresh_blackout_windows().await {
+                log_task!(Task::MatchingBlackout, Outcome::Failed, error = %e, "failed to refresh blackout windows");
+            }
+
+            if let Err(e) = self.rerun_skipped_orders().await {
+                log_task!(Task::MatchingBlackout, Outcome::Failed, error = %e, "failed to re-run orders skipped during blackout");
+            }
+        }
+    }
+
+    /// Refresh the cached runtime windows, pruning expired windows from the
+    /// state if the local node is the leader
+    async fn refresh_blackout_windows(&self) -> Result<(), MatchingEngineError> {
+        let now = get_current_time_millis();
+        let (expired, live): (Vec<_>, Vec<_>) =
+            self.state.get_blackout_windows().await?.into_iter().partition(|w| w.is_expired(now));
+        *self.blackout_windows.runtime.write().await = Some(live);
+
+        // Every replica sees the same windows, only the leader prunes them
+        if !self.state.is_leader() {
+            return Ok(());
+        }
+
+        for window in expired {
+            self.state.remove_blackout_window(window.id).await?.await?;
+            log_task!(
+                Task::MatchingBlackout,
+                Outcome::Ok,
+                window_id = %window.id,
+                window = %window,
+                "pruned expired blackout window"
+            );
+        }
+        Ok(())
+    }
+
+    /// Re-run the internal engine on the orders skipped during a window, if
+    /// no window is active
+    async fn rerun_skipped_orders(&self) -> Result<(), MatchingEngineError> {
+        if self.active_blackout_window().await?.is_some() {
+            return Ok(());
+        }
+
+        let skipped = std::mem::take(&mut *self.blackout_windows.skipped.write().await);
+        if skipped.is_empty() {
+            return Ok(());
+        }
+
+        log_task!(
+            Task::MatchingBlackout,
+            Outcome::Ok,
+            n_orders = skipped.len(),
+            "blackout ended, re-running orders skipped during window"
+        );
+        for (account_id, order_id) in skipped {
+            let this = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = this.run_internal_matching_engine(account_id, order_id).await {
+                    log_task!(Task::InternalMatch, Outcome::Failed, subject = %order_id, error = %e, "error re-running internal matching engine after blackout");
+                }
+            });
+        }
+        Ok(())
+    }
+}
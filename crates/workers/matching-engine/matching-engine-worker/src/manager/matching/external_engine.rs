@@ -47,6 +47,18 @@ impl MatchingEngineExecutor {
         response_topic: String,
         options: ExternalMatchingEngineOptions,
     ) -> Result<(), MatchingEngineError> {
+        if let Some(window) = self.active_blackout_window().await? {
+            log_task!(
+                Task::ExternalMatch,
+                Outcome::Skipped,
+                window = %window,
+                reason = %window.reason,
+                "matching blacked out, skipping external matching engine"
+            );
+            self.handle_no_match(response_topic);
+            return Ok(());
+        }
+
         // Check if either asset in the pair is disabled for matching
         let pair = order.pair();
         if self.is_asset_disabled(&pair.in_token) || self.is_asset_disabled(&pair.out_token) {
@@ -3,15 +3,16 @@
 use circuit_types::Amount;
 use matching_engine_core::SuccessfulMatch;
 use renegade_metrics::record_internal_match_settle;
+use state::storage::tx::match_audit::{MatchAttempt, MatchPhase};
 use tracing::instrument;
 use types_account::order::PrivacyRing;
-use types_account::{MatchingPoolName, OrderId, order::Order};
+use types_account::{MatchingPoolName, OrderId, order::Order, pair::Pair};
 use types_core::AccountId;
 use types_tasks::{
     SettleInternalMatchTaskDescriptor, SettlePrivateMatchTaskDescriptor, TaskDescriptor,
 };
-use util::log_task;
 use util::logging::Outcome;
+use util::{get_current_time_millis, log_task};
 
 use crate::logging::Task;
 use crate::{error::MatchingEngineError, executor::MatchingEngineExecutor};
@@ -34,8 +35,46 @@ impl MatchingEngineExecutor {
         &self,
         account_id: AccountId,
         order_id: OrderId,
+    ) -> Result<(), MatchingEngineError> {
+        let peer_id = self.state.get_peer_id()?;
+        let mut attempt = MatchAttempt::new(peer_id, order_id, get_current_time_millis());
+        let res = self.run_internal_matching_engine_inner(account_id, order_id, &mut attempt).await;
+
+        // Record the attempt in the audit trail
+        if let Err(e) = &res {
+            attempt.failure_reason = Some(e.to_string());
+        }
+        attempt.duration_ms = get_current_time_millis().saturating_sub(attempt.started_at);
+        if let Err(e) = self.state.record_match_attempt(attempt).await {
+            log_task!(Task::InternalMatch, Outcome::Failed, subject = %order_id, error = %e, "failed to record match attempt");
+        }
+
+        res
+    }
+
+    /// Run the internal matching engine on the given order, recording the
+    /// phases reached in the given audit entry
+    async fn run_internal_matching_engine_inner(
+        &self,
+        account_id: AccountId,
+        order_id: OrderId,
+        attempt: &mut MatchAttempt,
     ) -> Result<(), MatchingEngineError> {
         log_task!(Task::InternalMatch, Outcome::Started, subject = %order_id, "running internal matching engine on order");
+        if let Some(window) = self.active_blackout_window().await? {
+            log_task!(
+                Task::InternalMatch,
+                Outcome::Skipped,
+                subject = %order_id,
+                window = %window,
+                reason = %window.reason,
+                "matching blacked out, skipping internal matching engine"
+            );
+            attempt.failure_reason = Some(format!("matching blacked out during {window}"));
+            self.blackout_windows.record_skipped(account_id, order_id).await;
+            return Ok(());
+        }
+
         // Lookup the order, matchable amount, and matching pool
         let (order, matchable_amount) = self.fetch_order_and_matchable_amount(&order_id).await?;
         let matching_pool = self.fetch_matching_pool(&order_id).await?;
@@ -58,11 +97,21 @@ impl MatchingEngineExecutor {
                 out_addr = %pair.out_token,
                 "asset disabled for matching, skipping internal matching engine for {in_tok}/{out_tok}"
             );
+            attempt.failure_reason =
+                Some(format!("asset disabled for matching: {in_tok}/{out_tok}"));
             return Ok(());
         }
 
-        // Find a match
-        let res = self.find_internal_match(account_id, &order, matchable_amount, matching_pool)?;
+        // Find a match, recording the order to re-run once the price recovers if
+        // the pair's price is stale
+        let res =
+            match self.find_internal_match(account_id, &order, matchable_amount, matching_pool) {
+                Err(e @ MatchingEngineError::StalePrice(_)) => {
+                    self.stale_price_skips.record_skipped(&pair, account_id, order_id).await;
+                    return Err(e);
+                },
+                res => res?,
+            };
         let successful_match = match res {
             Some(match_res) => match_res,
             None => {
@@ -76,17 +125,97 @@ impl MatchingEngineExecutor {
                     quote = %quote,
                     "no internal matches found for {base}/{quote} order"
                 );
+                attempt.failure_reason = Some("no crossing order found".to_string());
                 return Ok(());
             },
         };
 
+        // Check the match against both accounts' risk limits. Alongside, check
+        // that both orders' validity proofs have been stored, so that a match
+        // whose settlement would fail on a missing proof is dropped before either
+        // order is locked. The proofs themselves are read and verified by the
+        // settlement task
+        let other_id = successful_match.other_order_id;
+        attempt.other_order_id = Some(other_id);
+        attempt.phase = MatchPhase::MatchFound;
+        let other_account_id = self.get_account_id_for_order(&other_id).await?;
+        let volume = successful_match.match_result.quote_token_volume();
+        let risk_check =
+            self.account_over_risk_limits(&[account_id, other_account_id], &pair, volume);
+        let proof_check = async {
+            // Indicative fills are not settled, so need no proofs
+            if self.quote_only {
+                return Ok(None);
+            }
+            self.order_without_stored_validity_proof(&order, &other_id).await
+        };
+        let (over_limits, missing_proof) = tokio::try_join!(risk_check, proof_check)?;
+
+        if let Some(id) = over_limits {
+            log_task!(
+                Task::InternalMatch,
+                Outcome::Skipped,
+                subject = %order_id,
+                other_order_id = %other_id,
+                account_id = %id,
+                "match exceeds account risk limits, skipping settlement"
+            );
+            attempt.failure_reason = Some(format!("match exceeds risk limits of account {id}"));
+            return Ok(());
+        }
+        attempt.phase = MatchPhase::RiskChecked;
+
+        // In quote-only mode the match is recorded as an indicative fill in place
+        // of settlement
+        if self.quote_only {
+            log_task!(
+                Task::InternalMatch,
+                Outcome::Ok,
+                subject = %order_id,
+                other_order_id = %other_id,
+                quote_volume = volume,
+                "recorded indicative fill in quote-only mode"
+            );
+            attempt.phase = MatchPhase::IndicativeFill;
+            return Ok(());
+        }
+
+        if let Some(id) = missing_proof {
+            log_task!(
+                Task::InternalMatch,
+                Outcome::Skipped,
+                subject = %order_id,
+                other_order_id = %other_id,
+                unproven_order_id = %id,
+                "validity proof not yet stored, skipping settlement"
+            );
+            attempt.failure_reason = Some(format!("validity proof not stored for order {id}"));
+            return Ok(());
+        }
+
+        // Skip a match another peer is already settling, rather than proposing a
+        // settlement lock raft would reject
+        if self.state.is_locked_for_settlement(vec![order_id, other_id]).await? {
+            log_task!(
+                Task::InternalMatch,
+                Outcome::Skipped,
+                subject = %order_id,
+                other_order_id = %other_id,
+                "order locked for settlement by another peer, skipping settlement"
+            );
+            attempt.failure_reason =
+                Some("order locked for settlement by another peer".to_string());
+            return Ok(());
+        }
+
         // TODO: maybe iteratively attempt to find a match and blacklist an order if
         // settlement fails?
-        let other_id = successful_match.other_order_id;
         match self.try_settle_match(order_id, successful_match).await {
             Ok(()) => {
                 // Per-pool settlement demand signal (success).
                 record_internal_match_settle(&pool_label, true /* settled */);
+                attempt.phase = MatchPhase::SettlementQueued;
+                self.record_match_volume(&[account_id, other_account_id], volume).await;
                 // Stop matching if a match was found
                 return Ok(());
             },
@@ -95,6 +224,7 @@ impl MatchingEngineExecutor {
                 // contention under load). The failed/total ratio per pool is the
                 // conflict rate used to size sharding.
                 record_internal_match_settle(&pool_label, false /* settled */);
+                attempt.failure_reason = Some(e.to_string());
                 let base = pair.base_token().ticker_or_addr();
                 let quote = pair.quote_token().ticker_or_addr();
                 log_task!(
@@ -160,11 +290,118 @@ impl MatchingEngineExecutor {
             })
         };
 
-        // Enqueue the task as a preemptive task through raft
-        self.forward_queued_task(descriptor).await?;
+        // Lock both orders cluster-wide so that another replica does not settle an
+        // overlapping pair concurrently; the proposal is rejected if either order
+        // is already locked by another peer
+        let order_ids = vec![user_order, match_result.other_order_id];
+        self.state.acquire_settlement_lock(order_ids.clone()).await?.await?;
+
+        // Enqueue the task as a preemptive task through raft, then release the lock
+        // regardless of the outcome. A failed release is recovered by lock expiry
+        let res = self.forward_queued_task(descriptor).await;
+        if let Err(e) = self.release_settlement_lock(order_ids).await {
+            log_task!(Task::SettleInternalMatch, Outcome::Failed, subject = %user_order, error = %e, "failed to release settlement lock");
+        }
+
+        res
+    }
+
+    /// Release the local peer's settlement lock on a set of orders
+    async fn release_settlement_lock(
+        &self,
+        order_ids: Vec<OrderId>,
+    ) -> Result<(), MatchingEngineError> {
+        self.state.release_settlement_lock(order_ids).await?.await?;
         Ok(())
     }
 
+    // ---------------
+    // | Risk Limits |
+    // ---------------
+
+    /// Find the first of the given accounts whose risk limits do not permit a
+    /// match of the given quote volume on the given pair
+    async fn account_over_risk_limits(
+        &self,
+        account_ids: &[AccountId],
+        pair: &Pair,
+        volume: Amount,
+    ) -> Result<Option<AccountId>, MatchingEngineError> {
+        for id in account_ids.iter().copied() {
+            if !self.match_within_risk_limits(id, pair, volume).await? {
+                return Ok(Some(id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Whether an account's risk limits permit a match of the given quote
+    /// volume on the given pair
+    async fn match_within_risk_limits(
+        &self,
+        account_id: AccountId,
+        pair: &Pair,
+        volume: Amount,
+    ) -> Result<bool, MatchingEngineError> {
+        let Some(config) = self.state.get_account_risk_config(&account_id).await? else {
+            return Ok(true);
+        };
+
+        if !config.allows_pair(pair) {
+            return Ok(false);
+        }
+
+        let matched_today = self.state.get_account_daily_volume(&account_id).await?;
+        Ok(config.allows_daily_volume(matched_today, volume))
+    }
+
+    /// Record a settled match's quote volume against each account's daily
+    /// total
+    ///
+    /// Failures are logged rather than propagated, the match has already been
+    /// handed off for settlement
+    async fn record_match_volume(&self, account_ids: &[AccountId], volume: Amount) {
+        for account_id in account_ids.iter().copied() {
+            let res = match self.state.record_account_match_volume(account_id, volume).await {
+                Ok(waiter) => waiter.await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = res {
+                log_task!(Task::SettleInternalMatch, Outcome::Failed, account_id = %account_id, error = %e, "failed to record account match volume");
+            }
+        }
+    }
+
+    // ----------------
+    // | Proof Checks |
+    // ----------------
+
+    /// Find the first order in a match for which the validity proof settlement
+    /// reads has not been stored
+    ///
+    /// A proof is generated asynchronously after its order is placed or
+    /// filled, so a freshly matched order may not yet have one. Only the
+    /// presence of the proof is checked, it is not read or verified here
+    async fn order_without_stored_validity_proof(
+        &self,
+        order: &Order,
+        other_id: &OrderId,
+    ) -> Result<Option<OrderId>, MatchingEngineError> {
+        let other_order = self.state.get_account_order(other_id).await?.ok_or_else(|| {
+            MatchingEngineError::state(format!("no order found for order {other_id:?}"))
+        })?;
+
+        for order in [order, &other_order] {
+            if !self.state.is_settlement_validity_proof_stored(order).await? {
+                return Ok(Some(order.id));
+            }
+        }
+
+        Ok(None)
+    }
+
     // -----------
     // | Helpers |
     // -----------
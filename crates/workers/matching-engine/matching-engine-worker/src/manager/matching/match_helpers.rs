@@ -4,9 +4,10 @@ use alloy::primitives::Address;
 use circuit_types::Amount;
 use matching_engine_core::SuccessfulMatch;
 use types_account::{MatchingPoolName, account::order::Order, pair::Pair};
-use types_core::{AccountId, TimestampedPriceFp};
+use types_core::{AccountId, TimestampedPrice, TimestampedPriceFp};
+use util::{log_task, logging::Outcome};
 
-use crate::{error::MatchingEngineError, executor::MatchingEngineExecutor};
+use crate::{error::MatchingEngineError, executor::MatchingEngineExecutor, logging::Task};
 
 impl MatchingEngineExecutor {
     /// Find an internal match for an order
@@ -19,7 +20,7 @@ impl MatchingEngineExecutor {
     ) -> Result<Option<SuccessfulMatch>, MatchingEngineError> {
         // Sample a price to execute the match at
         let pair = order.pair();
-        let price = self.get_execution_price(&pair)?;
+        let price = self.get_execution_price(&pair, matchable)?;
 
         // Sanity check the input range
         let input_range = order.min_fill_size()..=matchable;
@@ -63,7 +64,7 @@ impl MatchingEngineExecutor {
         let pair = order.pair();
         let price = match price {
             Some(p) => p,
-            None => self.get_execution_price(&pair)?,
+            None => self.get_execution_price(&pair, matchable_amount)?,
         };
 
         // Sanity check the input range
@@ -91,15 +92,67 @@ impl MatchingEngineExecutor {
 
     /// Fetch the execution price for an order
     ///
-    /// Returns the price in units of output token / input token
+    /// Returns the price in units of output token / input token. Prices backed
+    /// by stale streams or below the minimum confidence are refused. Matches of
+    /// an input amount worth at least the VWAP pricing threshold are priced
+    /// off the VWAP, if enabled
     pub(crate) fn get_execution_price(
         &self,
         pair: &Pair,
+        amount_in: Amount,
     ) -> Result<TimestampedPriceFp, MatchingEngineError> {
-        self.price_streams
-            .get_output_quoted_price(pair)
-            .map(TimestampedPriceFp::from)
-            .map_err(MatchingEngineError::no_price)
+        let usdc_quoted = pair.to_usdc_quoted().map_err(MatchingEngineError::no_price)?;
+        let (base, quote) = (usdc_quoted.in_token(), usdc_quoted.out_token());
+        if self.price_streams.is_pair_stale(&base, &quote) {
+            return Err(MatchingEngineError::stale_price(format!("{base} / {quote}")));
+        }
+
+        let (price, confidence) = self
+            .price_streams
+            .get_output_quoted_price_with_confidence(pair)
+            .map_err(MatchingEngineError::no_price)?;
+        if confidence < self.min_price_confidence {
+            return Err(MatchingEngineError::LowPriceConfidence(
+                confidence,
+                self.min_price_confidence,
+            ));
+        }
+
+        let price = self.vwap_execution_price(pair, amount_in, price);
+        Ok(TimestampedPriceFp::from(price))
+    }
+
+    /// Replace the given spot price with the pair's VWAP if the quote amount
+    /// of the match meets the VWAP pricing threshold
+    ///
+    /// Falls back to the spot price while no VWAP is available for the pair
+    fn vwap_execution_price(
+        &self,
+        pair: &Pair,
+        amount_in: Amount,
+        spot_price: TimestampedPrice,
+    ) -> TimestampedPrice {
+        let Some(threshold) = self.vwap_pricing_threshold else {
+            return spot_price;
+        };
+
+        // The spot price is in units of output token / input token
+        let quote_amount = if pair.is_input_quote() {
+            amount_in as f64
+        } else {
+            amount_in as f64 * spot_price.price.to_f64()
+        };
+        if quote_amount < threshold as f64 {
+            return spot_price;
+        }
+
+        match self.price_streams.get_output_quoted_vwap(pair, self.vwap_pricing_window) {
+            Ok(vwap) => vwap,
+            Err(e) => {
+                log_task!(Task::VwapPricing, Outcome::Skipped, error = %e, window = %self.vwap_pricing_window, "no VWAP available, pricing match at spot");
+                spot_price
+            },
+        }
     }
 
     /// Validate that the minimum fill size is not violated by an order
@@ -108,8 +161,11 @@ impl MatchingEngineExecutor {
         quote_volume >= self.min_fill_size
     }
 
-    /// Check if an asset is disabled for matching
+    /// Check if an asset is disabled for matching, either explicitly or by
+    /// its absence from the allowlist
     pub(crate) fn is_asset_disabled(&self, addr: &Address) -> bool {
-        self.disabled_assets.contains(addr)
+        let not_allowed =
+            self.allowed_assets.as_ref().is_some_and(|allowed| !allowed.contains(addr));
+        not_allowed || self.disabled_assets.contains(addr)
     }
 }
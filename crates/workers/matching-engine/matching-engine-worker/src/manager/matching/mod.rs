@@ -1,5 +1,7 @@
 //! Matching engine implementations for the handshake manager
 
+pub(crate) mod blackout;
 pub mod external_engine;
 pub mod internal_engine;
 mod match_helpers;
+pub(crate) mod staleness;
@@ -0,0 +1,105 @@
+//! Tracks the orders the matching engine skipped on a stale price
+//!
+//! The matching engine refuses to price a match on a pair backed by stale
+//! data. Orders the internal engine skipped for this reason are recorded, and
+//! re-run once the price reporter publishes that a stream recovered and the
+//! pair is no longer stale
+
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use system_bus::{PRICE_STALENESS_TOPIC, SystemBusMessage};
+use types_account::{OrderId, pair::Pair};
+use types_core::{AccountId, Token};
+use util::{
+    concurrency::{AsyncShared, new_async_shared},
+    log_task,
+    logging::Outcome,
+};
+
+use crate::{executor::MatchingEngineExecutor, logging::Task};
+
+/// The orders the internal engine skipped on a stale price, keyed by the
+/// USDC quoted (base, quote) pair they were skipped on
+#[derive(Clone)]
+pub(crate) struct StalePriceSkips {
+    /// The skipped orders of each pair
+    skipped: AsyncShared<HashMap<(Token, Token), HashSet<(AccountId, OrderId)>>>,
+}
+
+impl StalePriceSkips {
+    /// Constructor
+    pub fn new() -> Self {
+        Self { skipped: new_async_shared(HashMap::new()) }
+    }
+
+    /// Record an order the internal engine skipped on a stale price for the
+    /// given pair
+    pub async fn record_skipped(&self, pair: &Pair, account_id: AccountId, order_id: OrderId) {
+        let Ok(usdc_quoted) = pair.to_usdc_quoted() else { return };
+        let key = (usdc_quoted.in_token(), usdc_quoted.out_token());
+        self.skipped.write().await.entry(key).or_default().insert((account_id, order_id));
+    }
+
+    /// Take the skipped orders of each pair which is not stale per the given
+    /// check
+    async fn take_recovered(
+        &self,
+        is_stale: impl Fn(&Token, &Token) -> bool,
+    ) -> Vec<((Token, Token), HashSet<(AccountId, OrderId)>)> {
+        let mut skipped = self.skipped.write().await;
+        let recovered =
+            skipped.keys().filter(|(base, quote)| !is_stale(base, quote)).cloned().collect_vec();
+        recovered.into_iter().filter_map(|pair| skipped.remove_entry(&pair)).collect()
+    }
+}
+
+impl MatchingEngineExecutor {
+    /// Follow the staleness transitions published by the price reporter,
+    /// re-running the orders skipped on each pair which recovers, until
+    /// cancelled
+    pub(crate) async fn staleness_monitor_loop(self) {
+        let mut updates = self.system_bus.subscribe(PRICE_STALENESS_TOPIC.to_string());
+        let mut cancel = self.cancel.clone();
+        loop {
+            let msg = tokio::select! {
+                msg = updates.next_message() => msg,
+                _ = cancel.changed() => return,
+            };
+
+            // Only a recovery can make a pair priceable again
+            if let SystemBusMessage::PriceStreamStalenessUpdate { stale: false, .. } = msg {
+                self.rerun_recovered_orders().await;
+            }
+        }
+    }
+
+    /// Re-run the internal engine on the orders skipped on each pair which is
+    /// no longer stale
+    async fn rerun_recovered_orders(&self) {
+        let price_streams = &self.price_streams;
+        let recovered = self
+            .stale_price_skips
+            .take_recovered(|base, quote| price_streams.is_pair_stale(base, quote))
+            .await;
+
+        for ((base, quote), orders) in recovered {
+            log_task!(
+                Task::StalePrice,
+                Outcome::Ok,
+                base = %base,
+                quote = %quote,
+                n_orders = orders.len(),
+                "price recovered, re-running orders skipped on stale price"
+            );
+            for (account_id, order_id) in orders {
+                let this = self.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = this.run_internal_matching_engine(account_id, order_id).await {
+                        log_task!(Task::InternalMatch, Outcome::Failed, subject = %order_id, error = %e, "error re-running internal matching engine after stale price");
+                    }
+                });
+            }
+        }
+    }
+}
@@ -10,7 +10,7 @@ use price_state::PriceStreamStates;
 use state::State;
 use system_bus::SystemBus;
 use tokio::runtime::Builder as RuntimeBuilder;
-use types_core::Token;
+use types_core::{BlackoutWindow, Token, VwapWindow};
 use util::log_task;
 use util::logging::Outcome;
 
@@ -30,10 +30,26 @@ pub struct MatchingEngineConfig {
     /// The minimum amount of the quote asset that the relayer should settle
     /// matches on
     pub min_fill_size: Amount,
+    /// Whether to record internal matches as indicative fills rather than
+    /// settling them
+    pub quote_only: bool,
     /// The number of blocks an external match bundle remains valid
     pub external_match_validity_window: u64,
+    /// The minimum confidence in a price at which the relayer will match
+    pub min_price_confidence: f64,
+    /// The amount of the quote asset at or above which a match is priced off
+    /// the VWAP rather than the instantaneous price, if enabled
+    pub vwap_pricing_threshold: Option<Amount>,
+    /// The trailing window over which large matches are priced off the VWAP
+    pub vwap_pricing_window: VwapWindow,
+    /// The configured windows during which no new matches are made, in
+    /// addition to those set at runtime in the global state
+    pub blackout_windows: Vec<BlackoutWindow>,
     /// Assets for which matching is disabled (by ticker)
     pub disabled_assets: Vec<String>,
+    /// The only assets for which matching is allowed (by ticker), all assets
+    /// not disabled are allowed if empty
+    pub allowed_assets: Vec<String>,
     /// The relayer-global state
     pub state: State,
     /// The matching engine instance
@@ -67,17 +83,30 @@ impl Worker for MatchingEngineManager {
     type Error = MatchingEngineError;
 
     async fn new(mut config: Self::WorkerConfig) -> Result<Self, Self::Error> {
-        // Convert disabled asset tickers to addresses
+        // Convert disabled and allowed asset tickers to addresses
         let disabled_assets = config
             .disabled_assets
             .iter()
             .map(|ticker| Token::from_ticker(ticker).get_alloy_address())
             .collect();
+        let allowed_assets = (!config.allowed_assets.is_empty()).then(|| {
+            config
+                .allowed_assets
+                .iter()
+                .map(|ticker| Token::from_ticker(ticker).get_alloy_address())
+                .collect()
+        });
 
         let executor = MatchingEngineExecutor::new(
             config.min_fill_size,
+            config.quote_only,
             config.external_match_validity_window,
+            config.min_price_confidence,
+            config.vwap_pricing_threshold,
+            config.vwap_pricing_window,
+            config.blackout_windows.clone(),
             disabled_assets,
+            allowed_assets,
             config.job_receiver.take().unwrap(),
             config.price_streams.clone(),
             config.state.clone(),
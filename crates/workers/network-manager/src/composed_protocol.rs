@@ -8,7 +8,16 @@
 //!         primitives.
 
 use async_trait::async_trait;
-use gossip_api::request_response::{AuthenticatedGossipRequest, AuthenticatedGossipResponse};
+use gossip_api::{
+    chunking::MAX_CHUNK_SIZE,
+    request_response::{
+        AuthenticatedGossipRequest, AuthenticatedGossipResponse,
+        version::{
+            CHUNKING_PROTOCOL_VERSION, GossipVersion, MIN_GOSSIP_PROTOCOL_VERSION, Versioned,
+            decode_versioned,
+        },
+    },
+};
 use libp2p::{
     PeerId,
     core::upgrade::{read_length_prefixed, write_length_prefixed},
@@ -27,6 +36,7 @@ use libp2p::{
     },
 };
 use libp2p_swarm_derive::NetworkBehaviour;
+use serde::de::DeserializeOwned;
 use std::{
     fmt::{Display, Formatter},
     io::{Error as IoError, ErrorKind},
@@ -34,14 +44,34 @@ use std::{
     time::Duration,
 };
 
-use super::error::NetworkManagerError;
+use util::{log_task, logging::Outcome};
+
+use crate::{error::NetworkManagerError, logging::Task};
 
 // -------------
 // | Constants |
 // -------------
 
-/// The maximum size libp2p should allocate buffer space for
-const MAX_MESSAGE_SIZE: usize = 1_000_000_000;
+/// The maximum message size of peers that predate chunking
+const LEGACY_MAX_MESSAGE_SIZE: usize = 1_000_000_000;
+/// The maximum size libp2p should allocate buffer space for a request or
+/// pubsub message
+///
+/// Peers that support chunking split larger payloads into chunks of at most
+/// `MAX_CHUNK_SIZE` bytes, this leaves room for the expansion of a chunk's
+/// bytes when serialized as JSON. Peers that predate chunking send payloads
+/// whole, so the legacy limit holds until they are no longer supported
+const MAX_MESSAGE_SIZE: usize = if MIN_GOSSIP_PROTOCOL_VERSION >= CHUNKING_PROTOCOL_VERSION {
+    16 * MAX_CHUNK_SIZE
+} else {
+    LEGACY_MAX_MESSAGE_SIZE
+};
+/// The maximum size libp2p should allocate buffer space for a response
+///
+/// Responses are not chunked, so they keep the legacy limit
+const MAX_RESPONSE_SIZE: usize = LEGACY_MAX_MESSAGE_SIZE;
+/// Error message emitted when an outbound message exceeds the maximum size
+const ERR_MESSAGE_TOO_LARGE: &str = "message exceeds the maximum message size";
 
 /// The timeout for inbound/outbound requests, in seconds
 const REQ_RES_TIMEOUT_SECS: u64 = 60;
@@ -217,8 +247,7 @@ impl RequestResponseCodec for RelayerGossipCodec {
             return Err(IoError::new(ErrorKind::InvalidData, "empty request"));
         }
 
-        let deserialized: AuthenticatedGossipRequest = serde_json::from_slice(&req_data).unwrap();
-        Ok(deserialized)
+        decode_message(&req_data, AuthenticatedGossipRequest::incompatible)
     }
 
     /// Deserializes a read response
@@ -230,13 +259,12 @@ impl RequestResponseCodec for RelayerGossipCodec {
     where
         T: AsyncRead + Unpin + Send,
     {
-        let resp_data = read_length_prefixed(io, MAX_MESSAGE_SIZE).await?;
+        let resp_data = read_length_prefixed(io, MAX_RESPONSE_SIZE).await?;
         if resp_data.is_empty() {
             return Err(IoError::new(ErrorKind::InvalidData, "empty response"));
         }
 
-        let deserialized: AuthenticatedGossipResponse = serde_json::from_slice(&resp_data).unwrap();
-        Ok(deserialized)
+        decode_message(&resp_data, AuthenticatedGossipResponse::incompatible)
     }
 
     /// Serializes a write request
@@ -251,6 +279,7 @@ impl RequestResponseCodec for RelayerGossipCodec {
     {
         // Serialize the data and write to socket
         let serialized = serde_json::to_string(&req).unwrap();
+        check_message_size(&serialized, MAX_MESSAGE_SIZE)?;
         write_length_prefixed(io, serialized.as_bytes()).await?;
 
         io.close().await?;
@@ -269,9 +298,75 @@ impl RequestResponseCodec for RelayerGossipCodec {
     {
         // Serialize the response and write to socket
         let serialized = serde_json::to_string(&resp).unwrap();
+        check_message_size(&serialized, MAX_RESPONSE_SIZE)?;
         write_length_prefixed(io, serialized.as_bytes()).await?;
 
         io.close().await?;
         Ok(())
     }
 }
+
+/// Decode a request or response
+///
+/// A message from a peer on an incompatible gossip protocol version is not
+/// decoded, it is replaced by a placeholder stamped with the peer's versions so
+/// that the network manager may refuse it explicitly rather than dropping the
+/// stream
+fn decode_message<T: DeserializeOwned>(
+    buf: &[u8],
+    incompatible: impl FnOnce(GossipVersion) -> T,
+) -> Result<T, IoError> {
+    let decoded = decode_versioned(buf).map_err(|err| {
+        log_task!(Task::DecodeMessage, Outcome::Failed, error = %err, "failed to decode inbound message");
+        IoError::new(ErrorKind::InvalidData, err)
+    })?;
+
+    match decoded {
+        Versioned::Compatible(msg) => Ok(msg),
+        Versioned::Incompatible(version) => {
+            let err = version.incompatibility();
+            log_task!(Task::DecodeMessage, Outcome::Skipped, error = %err, "refusing inbound message");
+            Ok(incompatible(version))
+        },
+    }
+}
+
+/// Check that a serialized message fits within the given maximum size
+///
+/// Surfaces the error on the sending side, rather than the message being
+/// dropped by the recipient
+fn check_message_size(serialized: &str, max_size: usize) -> Result<(), IoError> {
+    if serialized.len() > max_size {
+        return Err(IoError::new(ErrorKind::InvalidData, ERR_MESSAGE_TOO_LARGE));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use gossip_api::request_response::{
+        AuthenticatedGossipRequest, GossipRequestType, version::GossipVersion,
+    };
+
+    use super::decode_message;
+
+    /// Tests that a request from an incompatible peer decodes to a placeholder
+    /// stamped with the peer's versions, rather than failing the stream
+    #[test]
+    fn test_decode_incompatible_request() {
+        let req = AuthenticatedGossipRequest {
+            version: GossipVersion::default(),
+            sig: Vec::new(),
+            inner: GossipRequestType::Ack.into(),
+        };
+        let buf = serde_json::to_vec(&req).unwrap();
+
+        let decoded = decode_message(&buf, AuthenticatedGossipRequest::incompatible).unwrap();
+        assert_eq!(decoded.version, GossipVersion::default());
+        assert!(!decoded.version.is_compatible());
+
+        // Malformed messages still fail
+        assert!(decode_message(b"not json", AuthenticatedGossipRequest::incompatible).is_err());
+    }
+}
@@ -6,14 +6,20 @@ mod pubsub;
 mod request_response;
 
 use futures::StreamExt;
-use gossip_api::pubsub::PubsubMessage;
+use gossip_api::{
+    chunking::ChunkReassembler, pubsub::PubsubMessage,
+    request_response::AuthenticatedGossipResponse,
+};
 use job_types::{
     gossip_server::GossipServerQueue,
     network_manager::{NetworkManagerJob, NetworkManagerReceiver},
 };
 use libp2p::{
-    Multiaddr, Swarm, gossipsub::Event as GossipsubEvent, multiaddr::Protocol,
-    request_response::Event as RequestResponseEvent, swarm::SwarmEvent,
+    Multiaddr, Swarm,
+    gossipsub::Event as GossipsubEvent,
+    multiaddr::Protocol,
+    request_response::{Event as RequestResponseEvent, ResponseChannel},
+    swarm::SwarmEvent,
 };
 use state::State;
 use tracing::debug;
@@ -22,7 +28,7 @@ use util::logging::Outcome;
 
 use crate::logging::Task;
 use types_core::HmacKey;
-use types_gossip::WrappedPeerId;
+use types_gossip::{ClusterAsymmetricKeypair, PeerAccessList, PeerLatencies, WrappedPeerId};
 use types_runtime::CancelChannel;
 use util::{DefaultOption, DefaultWrapper};
 use util::{
@@ -32,7 +38,7 @@ use util::{
 
 use std::sync::{Arc, atomic::AtomicBool};
 
-use crate::waiters::ResponseWaiters;
+use crate::{latency::RequestTimer, versions::PeerVersions, waiters::ResponseWaiters};
 
 use self::behavior::{BehaviorReceiver, BehaviorSender, new_behavior_queue};
 
@@ -94,6 +100,12 @@ pub(super) struct NetworkManagerExecutor {
     local_peer_id: WrappedPeerId,
     /// The local cluster's keypair, used to sign and authenticate requests
     cluster_key: HmacKey,
+    /// The local cluster's asymmetric keypair, used to sign the origin of
+    /// messages published on behalf of the cluster
+    cluster_keypair: ClusterAsymmetricKeypair,
+    /// Whether to accept order book messages that carry no origin signature,
+    /// from peers that predate origin signing
+    allow_unsigned_order_book_messages: bool,
     /// Whether or not to allow peer discovery on the local node
     allow_local: bool,
     /// Whether the network manager has discovered the local peer's public,
@@ -105,6 +117,18 @@ pub(super) struct NetworkManagerExecutor {
     warmup_buffer: AsyncShared<Vec<BufferedPubsubMessage>>,
     /// The waiters on outbound requests
     response_waiters: ResponseWaiters,
+    /// Times outbound requests to measure the round trip time to peers
+    request_timer: RequestTimer,
+    /// The peers the relayer may talk to; all traffic with others is refused
+    peer_access: PeerAccessList,
+    /// The gossip protocol versions of connected peers, used to decide whether
+    /// oversized messages may be chunked
+    peer_versions: PeerVersions,
+    /// Reassembles inbound requests sent in chunks, holding the response
+    /// channel of each request's last chunk
+    request_chunks: AsyncShared<ChunkReassembler<ResponseChannel<AuthenticatedGossipResponse>>>,
+    /// Reassembles inbound pubsub messages published in chunks
+    pubsub_chunks: AsyncShared<ChunkReassembler<()>>,
     /// The behavior channel receiver, used to sequence access to the underlying
     /// swarm
     behavior_rx: DefaultOption<BehaviorReceiver>,
@@ -133,9 +157,13 @@ impl NetworkManagerExecutor {
         local_peer_id: WrappedPeerId,
         allow_local: bool,
         cluster_key: HmacKey,
+        cluster_keypair: ClusterAsymmetricKeypair,
         job_channel: NetworkManagerReceiver,
         gossip_work_queue: GossipServerQueue,
         global_state: State,
+        peer_latencies: PeerLatencies,
+        peer_access: PeerAccessList,
+        allow_unsigned_order_book_messages: bool,
         cancel: CancelChannel,
     ) -> Self {
         let (behavior_tx, behavior_rx) = new_behavior_queue();
@@ -144,10 +172,17 @@ impl NetworkManagerExecutor {
             local_peer_id,
             allow_local,
             cluster_key,
+            cluster_keypair,
+            allow_unsigned_order_book_messages,
             discovered_identity: Arc::new(AtomicBool::new(false)),
             warmup_finished: Arc::new(AtomicBool::new(false)),
             warmup_buffer: new_async_shared(Vec::new()),
             response_waiters: ResponseWaiters::new(),
+            request_timer: RequestTimer::new(peer_latencies),
+            peer_access,
+            peer_versions: PeerVersions::new(),
+            request_chunks: new_async_shared(ChunkReassembler::new()),
+            pubsub_chunks: new_async_shared(ChunkReassembler::new()),
             behavior_rx: DefaultWrapper::new(Some(behavior_rx)),
             behavior_tx,
             job_channel: DefaultWrapper::new(Some(job_channel)),
@@ -207,6 +242,15 @@ impl NetworkManagerExecutor {
                                 }
                             });
                         },
+                        SwarmEvent::ConnectionEstablished { peer_id, .. }
+                            if !self.peer_access.is_allowed(&WrappedPeerId(peer_id)) =>
+                        {
+                            log_task!(Task::PeerAccess, Outcome::Skipped, subject = %peer_id, "disconnecting peer excluded by access lists");
+                            let _ = swarm.disconnect_peer_id(peer_id);
+                        },
+                        SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                            self.peer_versions.remove(&WrappedPeerId(peer_id)).await;
+                        },
                         SwarmEvent::NewListenAddr { address, .. } => {
                             log_task!(Task::Listen, Outcome::Ok, subject = %address, local_peer_id = %self.local_peer_id, "listening on p2p address");
                         },
@@ -230,8 +274,14 @@ impl NetworkManagerExecutor {
     ) -> Result<(), NetworkManagerError> {
         match message {
             ComposedProtocolEvent::RequestResponse(request_response) => {
-                if let RequestResponseEvent::Message { peer, message, .. } = request_response {
-                    self.handle_inbound_request_response_message(peer, message).await?;
+                match request_response {
+                    RequestResponseEvent::Message { peer, message, .. } => {
+                        self.handle_inbound_request_response_message(peer, message).await?;
+                    },
+                    RequestResponseEvent::OutboundFailure { request_id, .. } => {
+                        self.request_timer.cancel(request_id).await;
+                    },
+                    _ => {},
                 }
 
                 Ok(())
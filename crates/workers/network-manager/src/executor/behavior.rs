@@ -11,6 +11,7 @@ use tokio::sync::mpsc::{
     UnboundedReceiver as TokioReceiver, UnboundedSender as TokioSender, unbounded_channel,
 };
 use tracing::instrument;
+use types_gossip::WrappedPeerId;
 use util::{err_str, telemetry::propagation::set_parent_span_from_context};
 
 use crate::{composed_protocol::ComposedNetworkBehavior, error::NetworkManagerError};
@@ -64,6 +65,7 @@ impl NetworkManagerExecutor {
                 set_parent_span_from_context(&req.inner.tracing_headers());
 
                 let rid = swarm.behaviour_mut().request_response.send_request(&peer_id, req);
+                self.request_timer.start(rid, WrappedPeerId(peer_id)).await;
                 if let Some(chan) = chan {
                     self.response_waiters.insert(rid, chan).await;
                 }
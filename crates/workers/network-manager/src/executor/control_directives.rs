@@ -7,6 +7,7 @@ use itertools::Itertools;
 use job_types::network_manager::NetworkManagerControlSignal;
 use libp2p::PeerId;
 use libp2p_core::Multiaddr;
+use types_gossip::WrappedPeerId;
 use util::log_task;
 use util::logging::Outcome;
 use util::networking::is_dialable_multiaddr;
@@ -35,6 +36,7 @@ impl NetworkManagerExecutor {
 
             // Remove a peer from the distributed routing tables
             NetworkManagerControlSignal::PeerExpired { peer_id } => {
+                self.request_timer.remove_peer(&peer_id);
                 self.handle_peer_expired(&peer_id.inner())?;
                 Ok(())
             },
@@ -60,6 +62,12 @@ impl NetworkManagerExecutor {
             return Ok(());
         }
 
+        // Never route to a peer excluded by the access lists
+        if !self.peer_access.is_allowed(&WrappedPeerId(peer_id)) {
+            log_task!(Task::PeerAccess, Outcome::Skipped, subject = %peer_id, "skipping addr of excluded peer");
+            return Ok(());
+        }
+
         self.send_behavior(BehaviorJob::AddAddress(peer_id, addr))
     }
 
@@ -80,3 +88,58 @@ impl NetworkManagerExecutor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use job_types::{
+        gossip_server::new_gossip_server_queue,
+        network_manager::{NetworkManagerControlSignal, new_network_manager_queue},
+    };
+    use libp2p_core::Multiaddr;
+    use state::test_helpers::mock_state;
+    use types_core::HmacKey;
+    use types_gossip::{ClusterAsymmetricKeypair, PeerAccessList, PeerLatencies, WrappedPeerId};
+    use types_runtime::new_cancel_channel;
+
+    use crate::executor::{NetworkManagerExecutor, behavior::BehaviorJob};
+
+    /// Tests that the addresses of peers excluded by the access lists are never
+    /// handed to the swarm to dial
+    #[tokio::test]
+    async fn test_new_addr_excluded_peer() {
+        let (allowed, blocked) = (WrappedPeerId::random(), WrappedPeerId::random());
+        let peer_access = PeerAccessList::new(vec![] /* allowlist */, vec![blocked]);
+
+        let (_job_sender, job_channel) = new_network_manager_queue();
+        let (gossip_queue, _gossip_receiver) = new_gossip_server_queue();
+        let (_cancel_sender, cancel) = new_cancel_channel();
+        let mut executor = NetworkManagerExecutor::new(
+            8000, // p2p_port
+            WrappedPeerId::random(),
+            true, // allow_local
+            HmacKey::random(),
+            ClusterAsymmetricKeypair::random(),
+            job_channel,
+            gossip_queue,
+            mock_state().await,
+            PeerLatencies::default(),
+            peer_access,
+            false, // allow_unsigned_order_book_messages
+            cancel,
+        );
+        let mut behavior_rx = executor.behavior_rx.take().unwrap();
+
+        let address: Multiaddr = "/ip4/127.0.0.1/udp/8000/quic-v1".parse().unwrap();
+        for peer_id in [blocked, allowed] {
+            let signal = NetworkManagerControlSignal::NewAddr { peer_id, address: address.clone() };
+            executor.handle_control_directive(signal).await.unwrap();
+        }
+
+        // Only the allowed peer's address reaches the swarm
+        let Ok(BehaviorJob::AddAddress(peer_id, _)) = behavior_rx.try_recv() else {
+            panic!("expected the allowed peer's address");
+        };
+        assert_eq!(peer_id, allowed.inner());
+        assert!(behavior_rx.try_recv().is_err());
+    }
+}
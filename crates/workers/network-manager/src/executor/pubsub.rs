@@ -4,19 +4,30 @@ use std::sync::atomic::Ordering;
 
 use gossip_api::{
     GossipDestination,
+    chunking::{GossipChunk, requires_chunking, split_payload},
     pubsub::{AuthenticatedPubsubMessage, PubsubMessage},
+    request_response::version::{Versioned, decode_versioned},
 };
 use job_types::gossip_server::GossipServerJob;
 use libp2p::gossipsub::{Message as GossipsubMessage, Sha256Topic};
+use renegade_metrics::labels::{
+    NUM_PUBSUB_ORIGIN_REJECTED_METRIC, NUM_PUBSUB_UNSIGNED_ACCEPTED_METRIC,
+};
+use types_core::HmacKey;
 use types_gossip::WrappedPeerId;
-use util::err_str;
+use util::{err_str, log_task, logging::Outcome};
 
-use crate::error::NetworkManagerError;
+use crate::{error::NetworkManagerError, logging::Task};
 
 use super::{BufferedPubsubMessage, NetworkManagerExecutor, behavior::BehaviorJob};
 
 /// Error emitted when a sender is missing from a pubsub message
 const ERR_MISSING_SENDER: &str = "missing sender in pubsub message";
+/// Error emitted when a reassembled pubsub message is itself a chunk
+const ERR_NESTED_CHUNK: &str = "reassembled pubsub message is a chunk";
+/// Error emitted when a pubsub message's origin signature does not match the
+/// cluster it claims to originate from
+const ERR_INVALID_ORIGIN: &str = "invalid origin signature on pubsub message";
 
 impl NetworkManagerExecutor {
     /// Forward an outbound pubsub message to the network
@@ -33,16 +44,34 @@ impl NetworkManagerExecutor {
             return Ok(());
         }
 
-        // If we require a signature on the message attach one
+        // If we require signatures on the message attach them, then split the
+        // message into chunks if it is too large and every peer can reassemble
+        // chunks
         let key = self.cluster_key;
-        let req_body = tokio::task::spawn_blocking(move || {
-            AuthenticatedPubsubMessage::new_with_body(message, &key)
+        let keypair = self.cluster_keypair.clone();
+        let chunking = self.peer_versions.all_support_chunking().await;
+        let msgs = tokio::task::spawn_blocking(move || {
+            let msg = AuthenticatedPubsubMessage::new_with_body(message, &key)
+                .with_origin_signature(&keypair);
+            if !chunking {
+                return Ok(vec![msg]);
+            }
+
+            chunk_pubsub(msg, &key)
         })
         .await
-        .unwrap();
+        .unwrap()?;
+
+        if msgs.len() > 1 {
+            log_task!(Task::ChunkMessage, Outcome::Ok, subject = %topic, chunks = msgs.len(), "publishing oversized pubsub message in chunks");
+        }
 
         let topic = Sha256Topic::new(topic);
-        self.send_behavior(BehaviorJob::SendPubsub(topic, req_body))
+        for msg in msgs {
+            self.send_behavior(BehaviorJob::SendPubsub(topic.clone(), msg))?;
+        }
+
+        Ok(())
     }
 
     /// Handle an incoming network request for a pubsub message
@@ -50,26 +79,54 @@ impl NetworkManagerExecutor {
         &self,
         message: GossipsubMessage,
     ) -> Result<(), NetworkManagerError> {
-        // Deserialize into API types and verify auth
-        let pkey = self.cluster_key;
-        let event: AuthenticatedPubsubMessage =
-            message.data.try_into().map_err(NetworkManagerError::Serialization)?;
+        // Deserialize into API types and verify auth, dropping messages from
+        // publishers on an incompatible protocol version
+        let event = match decode_versioned::<AuthenticatedPubsubMessage>(&message.data)
+            .map_err(NetworkManagerError::Serialization)?
+        {
+            Versioned::Compatible(event) => event,
+            Versioned::Incompatible(version) => {
+                log_task!(Task::DecodeMessage, Outcome::Skipped, source = ?message.source, error = %version.incompatibility(), "dropping pubsub message from incompatible publisher");
+                return Ok(());
+            },
+        };
+        let sender = message
+            .source
+            .map(WrappedPeerId)
+            .ok_or_else(|| NetworkManagerError::UnhandledRequest(ERR_MISSING_SENDER.to_string()))?;
+
+        // Chunks are authenticated before they are buffered and the reassembled
+        // message again once complete
+        let event = self.authenticate_pubsub(event).await?;
+        let event = match event.body {
+            PubsubMessage::Chunk(chunk) => match self.handle_pubsub_chunk(sender, chunk).await? {
+                Some(reassembled) => self.authenticate_pubsub(reassembled).await?,
+                None => return Ok(()),
+            },
+            _ => event,
+        };
 
         // Block on verification to avoid blocking the async pool
+        let allow_unsigned = self.allow_unsigned_order_book_messages;
         let event = tokio::task::spawn_blocking(move || {
-            event
-                .verify_cluster_auth(&pkey)
-                .then_some(event)
-                .ok_or_else(NetworkManagerError::hmac_error)
+            // Reject messages published under another cluster's name. While a
+            // cluster is upgraded, messages from peers that predate origin
+            // signing may be accepted unsigned
+            if !event.verify_origin_auth() {
+                if allow_unsigned && event.is_origin_unsigned() {
+                    metrics::counter!(NUM_PUBSUB_UNSIGNED_ACCEPTED_METRIC).increment(1);
+                    return Ok(event);
+                }
+
+                metrics::counter!(NUM_PUBSUB_ORIGIN_REJECTED_METRIC).increment(1);
+                return Err(NetworkManagerError::Authentication(ERR_INVALID_ORIGIN.to_string()));
+            }
+
+            Ok(event)
         })
         .await
         .unwrap()?;
 
-        let sender = message
-            .source
-            .map(WrappedPeerId)
-            .ok_or_else(|| NetworkManagerError::UnhandledRequest(ERR_MISSING_SENDER.to_string()))?;
-
         match event.body.destination() {
             GossipDestination::GossipServer => self
                 .gossip_work_queue
@@ -77,8 +134,76 @@ impl NetworkManagerExecutor {
                 .map_err(err_str!(NetworkManagerError::EnqueueJob)),
 
             GossipDestination::NetworkManager => {
-                unreachable!("network manager should not receive pubsub messages")
+                Err(NetworkManagerError::UnhandledRequest(ERR_NESTED_CHUNK.to_string()))
             },
         }
     }
+
+    /// Verify the cluster authentication of an inbound pubsub message
+    async fn authenticate_pubsub(
+        &self,
+        event: AuthenticatedPubsubMessage,
+    ) -> Result<AuthenticatedPubsubMessage, NetworkManagerError> {
+        let pkey = self.cluster_key;
+        tokio::task::spawn_blocking(move || {
+            event
+                .verify_cluster_auth(&pkey)
+                .then_some(event)
+                .ok_or_else(NetworkManagerError::hmac_error)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Handle a chunk of an inbound pubsub message
+    ///
+    /// Returns the reassembled message once all of its chunks have been
+    /// received
+    async fn handle_pubsub_chunk(
+        &self,
+        sender: WrappedPeerId,
+        chunk: GossipChunk,
+    ) -> Result<Option<AuthenticatedPubsubMessage>, NetworkManagerError> {
+        self.check_chunk_sender(&sender, &chunk).await?;
+        let reassembled = self
+            .pubsub_chunks
+            .write()
+            .await
+            .insert(sender, chunk, None)
+            .map_err(NetworkManagerError::Serialization)?;
+        let Some((payload, _)) = reassembled else {
+            return Ok(None);
+        };
+
+        let msg =
+            tokio::task::spawn_blocking(move || AuthenticatedPubsubMessage::try_from(payload))
+                .await
+                .unwrap()
+                .map_err(NetworkManagerError::Serialization)?;
+        Ok(Some(msg))
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Split an authenticated pubsub message into chunk messages if it is too
+/// large to be published in a single message
+fn chunk_pubsub(
+    msg: AuthenticatedPubsubMessage,
+    key: &HmacKey,
+) -> Result<Vec<AuthenticatedPubsubMessage>, NetworkManagerError> {
+    let buf = serde_json::to_vec(&msg).map_err(err_str!(NetworkManagerError::Serialization))?;
+    if !requires_chunking(&buf) {
+        return Ok(vec![msg]);
+    }
+
+    let cluster_auth = msg.body.requires_cluster_auth();
+    let chunks = split_payload(&buf, cluster_auth)
+        .into_iter()
+        .map(|chunk| AuthenticatedPubsubMessage::new_with_body(PubsubMessage::Chunk(chunk), key))
+        .collect();
+
+    Ok(chunks)
 }
@@ -4,9 +4,10 @@ use std::time::{Duration, Instant};
 
 use gossip_api::{
     GossipDestination,
+    chunking::{GossipChunk, requires_chunking, split_payload},
     request_response::{
         AuthenticatedGossipRequest, AuthenticatedGossipResponse, GossipRequest, GossipRequestType,
-        GossipResponse, GossipResponseType,
+        GossipResponse, GossipResponseType, version::GossipVersion,
     },
 };
 use job_types::{gossip_server::GossipServerJob, network_manager::NetworkResponseChannel};
@@ -18,6 +19,7 @@ use util::log_task;
 use util::logging::Outcome;
 
 use crate::logging::Task;
+use types_core::HmacKey;
 use types_gossip::WrappedPeerId;
 use util::{err_str, telemetry::propagation::set_parent_span_from_context};
 
@@ -25,6 +27,12 @@ use crate::error::NetworkManagerError;
 
 use super::{NetworkManagerExecutor, behavior::BehaviorJob};
 
+/// Error message emitted when sending a request to a peer excluded by the
+/// access lists
+const ERR_PEER_EXCLUDED: &str = "peer is excluded by the access lists";
+/// Error message emitted when a peer outside the peer index sends an
+/// unauthenticated chunk
+const ERR_UNKNOWN_CHUNK_SENDER: &str = "chunk sent by unindexed peer";
 /// The raft job execution latency at which we log a warning
 pub(super) const RAFT_JOB_LATENCY_WARNING_MS: Duration = Duration::from_millis(100);
 
@@ -41,26 +49,40 @@ impl NetworkManagerExecutor {
         message: RequestResponseMessage<AuthenticatedGossipRequest, AuthenticatedGossipResponse>,
     ) -> Result<(), NetworkManagerError> {
         let peer = WrappedPeerId(peer);
+        if !self.peer_access.is_allowed(&peer) {
+            log_task!(Task::PeerAccess, Outcome::Skipped, subject = %peer, "dropping message from excluded peer");
+            return Ok(());
+        }
 
         // Multiplex over request/response message types
         match message {
             // Handle inbound request from another peer
             RequestResponseMessage::Request { request, channel, .. } => {
+                // Refuse requests from peers on an incompatible protocol version,
+                // their bodies were not decoded
+                if let Some(rejection) = version_rejection(&request.version) {
+                    log_task!(Task::DecodeMessage, Outcome::Skipped, subject = %peer, error = %request.version.incompatibility(), "rejecting request from incompatible peer");
+                    return self.send_behavior(BehaviorJob::SendResp(channel, rejection));
+                }
+                self.peer_versions.record(peer, request.version).await;
+
                 // Use the request's span if provided
                 set_parent_span_from_context(&request.inner.tracing_headers());
 
-                // Authenticate the request
-                let pkey = self.cluster_key;
-                let ctx = tracing::Span::current().context().clone();
-                let request = tokio::task::spawn_blocking(move || {
-                    tracing::Span::current().set_parent(ctx);
-                    request
-                        .verify_cluster_auth(&pkey)
-                        .then_some(request)
-                        .ok_or_else(NetworkManagerError::hmac_error)
-                })
-                .await
-                .unwrap()?;
+                // Authenticate the request, chunks are authenticated before they
+                // are buffered and the reassembled request again once complete
+                let request = self.authenticate_request(request).await?;
+                let (request, channel) = match request.inner.body {
+                    GossipRequestType::Chunk(chunk) => {
+                        match self.handle_request_chunk(peer, chunk, channel).await? {
+                            Some((request, chan)) => {
+                                (self.authenticate_request(request).await?, chan)
+                            },
+                            None => return Ok(()),
+                        }
+                    },
+                    _ => (request, channel),
+                };
 
                 let body = request.inner;
                 match body.destination() {
@@ -78,6 +100,17 @@ impl NetworkManagerExecutor {
 
             // Handle inbound response
             RequestResponseMessage::Response { request_id, response } => {
+                self.request_timer.finish(request_id).await;
+
+                // A response from a peer on an incompatible protocol version was
+                // not decoded, dropping the waiter fails the request
+                if !response.version.is_compatible() {
+                    log_task!(Task::DecodeMessage, Outcome::Skipped, subject = %peer, error = %response.version.incompatibility(), "dropping response from incompatible peer");
+                    self.response_waiters.pop(request_id).await;
+                    return Ok(());
+                }
+                self.peer_versions.record(peer, response.version).await;
+
                 // Use the response's span if provided
                 set_parent_span_from_context(&response.inner.tracing_headers());
 
@@ -115,6 +148,82 @@ impl NetworkManagerExecutor {
         }
     }
 
+    /// Verify the cluster authentication of an inbound request
+    async fn authenticate_request(
+        &self,
+        request: AuthenticatedGossipRequest,
+    ) -> Result<AuthenticatedGossipRequest, NetworkManagerError> {
+        let pkey = self.cluster_key;
+        let ctx = tracing::Span::current().context().clone();
+        tokio::task::spawn_blocking(move || {
+            tracing::Span::current().set_parent(ctx);
+            request
+                .verify_cluster_auth(&pkey)
+                .then_some(request)
+                .ok_or_else(NetworkManagerError::hmac_error)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Handle a chunk of an inbound request
+    ///
+    /// Intermediate chunks are acked immediately, the reassembled request is
+    /// answered on the response channel of its last chunk. Returns the
+    /// reassembled request once all of its chunks have been received
+    ///
+    /// Chunks of requests that do not require cluster authentication are only
+    /// buffered for indexed peers
+    async fn handle_request_chunk(
+        &self,
+        peer: WrappedPeerId,
+        chunk: GossipChunk,
+        chan: ResponseChannel<AuthenticatedGossipResponse>,
+    ) -> Result<
+        Option<(AuthenticatedGossipRequest, ResponseChannel<AuthenticatedGossipResponse>)>,
+        NetworkManagerError,
+    > {
+        self.check_chunk_sender(&peer, &chunk).await?;
+        let reply = if chunk.is_last() {
+            Some(chan)
+        } else {
+            self.handle_outbound_resp(GossipResponseType::Ack.into(), chan).await?;
+            None
+        };
+
+        let reassembled = self
+            .request_chunks
+            .write()
+            .await
+            .insert(peer, chunk, reply)
+            .map_err(NetworkManagerError::Serialization)?;
+        let Some((payload, Some(chan))) = reassembled else {
+            return Ok(None);
+        };
+
+        let request = tokio::task::spawn_blocking(move || serde_json::from_slice(&payload))
+            .await
+            .unwrap()
+            .map_err(err_str!(NetworkManagerError::Serialization))?;
+        Ok(Some((request, chan)))
+    }
+
+    /// Check that a peer may send the given chunk
+    ///
+    /// A chunk that requires cluster authentication has already been verified,
+    /// other chunks are only accepted from indexed peers
+    pub(super) async fn check_chunk_sender(
+        &self,
+        peer: &WrappedPeerId,
+        chunk: &GossipChunk,
+    ) -> Result<(), NetworkManagerError> {
+        if chunk.cluster_auth || self.global_state.get_peer_info(peer).await?.is_some() {
+            return Ok(());
+        }
+
+        Err(NetworkManagerError::Authentication(format!("{ERR_UNKNOWN_CHUNK_SENDER}: {peer}")))
+    }
+
     /// Handle an internally routed request
     #[instrument(name = "handle_internal_network_request", skip_all, err)]
     async fn handle_internal_request(
@@ -138,6 +247,10 @@ impl NetworkManagerExecutor {
             GossipResponseType::Ack => Ok(()),
             // The response will be forwarded directly to the raft client via the waiters
             GossipResponseType::Raft(_) => Ok(()),
+            GossipResponseType::VersionRejected(version) => {
+                log_task!(Task::DecodeMessage, Outcome::Failed, error = %version.incompatibility(), "request rejected by peer on an incompatible protocol version");
+                Ok(())
+            },
             _ => Err(NetworkManagerError::UnhandledRequest(format!(
                 "unhandled internal response: {resp:?}",
             ))),
@@ -179,16 +292,36 @@ impl NetworkManagerExecutor {
         chan: Option<NetworkResponseChannel>,
     ) -> Result<(), NetworkManagerError> {
         set_parent_span_from_context(&req.tracing_headers());
+        if !self.peer_access.is_allowed(&WrappedPeerId(peer)) {
+            return Err(NetworkManagerError::Network(format!("{ERR_PEER_EXCLUDED}: {peer}")));
+        }
 
-        // Authenticate the request
+        // Authenticate the request, splitting it into chunks if it is too large
+        // and the peer can reassemble chunks
         let key = self.cluster_key;
-        let req_body = tokio::task::spawn_blocking(move || {
-            AuthenticatedGossipRequest::new_with_body(req, &key)
+        let chunking = self.peer_versions.get(&WrappedPeerId(peer)).await.supports_chunking();
+        let mut reqs = tokio::task::spawn_blocking(move || {
+            let req = AuthenticatedGossipRequest::new_with_body(req, &key);
+            if !chunking {
+                return Ok(vec![req]);
+            }
+
+            chunk_request(req, &key)
         })
         .await
-        .unwrap();
+        .unwrap()?;
+
+        if reqs.len() > 1 {
+            log_task!(Task::ChunkMessage, Outcome::Ok, peer = %peer, chunks = reqs.len(), "sending oversized request in chunks");
+        }
 
-        self.send_behavior(BehaviorJob::SendReq(peer, req_body, chan))
+        // Only the response to the last chunk answers the request
+        let last = reqs.pop().expect("a request has at least one chunk");
+        for req in reqs {
+            self.send_behavior(BehaviorJob::SendReq(peer, req, None))?;
+        }
+
+        self.send_behavior(BehaviorJob::SendReq(peer, last, chan))
     }
 
     /// Handle an outbound response
@@ -211,3 +344,69 @@ impl NetworkManagerExecutor {
         self.send_behavior(BehaviorJob::SendResp(chan, authenticate_resp))
     }
 }
+
+// -----------
+// | Helpers |
+// -----------
+
+/// The rejection to send in answer to a request stamped with the given
+/// versions, if its sender speaks an incompatible protocol version
+fn version_rejection(version: &GossipVersion) -> Option<AuthenticatedGossipResponse> {
+    (!version.is_compatible()).then(AuthenticatedGossipResponse::version_rejected)
+}
+
+/// Split an authenticated request into chunk requests if it is too large to be
+/// sent in a single message
+fn chunk_request(
+    req: AuthenticatedGossipRequest,
+    key: &HmacKey,
+) -> Result<Vec<AuthenticatedGossipRequest>, NetworkManagerError> {
+    let buf = serde_json::to_vec(&req).map_err(err_str!(NetworkManagerError::Serialization))?;
+    if !requires_chunking(&buf) {
+        return Ok(vec![req]);
+    }
+
+    let cluster_auth = req.inner.requires_cluster_auth();
+    let tracing_context = req.inner.tracing_context;
+    let chunks = split_payload(&buf, cluster_auth)
+        .into_iter()
+        .map(|chunk| {
+            let body = GossipRequestType::Chunk(chunk);
+            let inner = GossipRequest { tracing_context: tracing_context.clone(), body };
+            AuthenticatedGossipRequest::new_with_body(inner, key)
+        })
+        .collect();
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod test {
+    use gossip_api::request_response::{
+        GossipResponseType,
+        version::{GOSSIP_PROTOCOL_VERSION, GossipVersion},
+    };
+
+    use super::version_rejection;
+
+    /// Tests that requests from incompatible peers are answered with an
+    /// explicit rejection carrying the local versions
+    #[test]
+    fn test_version_rejection() {
+        assert!(version_rejection(&GossipVersion::local()).is_none());
+
+        let legacy = GossipVersion::default();
+        let future = GossipVersion {
+            version: GOSSIP_PROTOCOL_VERSION + 2,
+            min_version: GOSSIP_PROTOCOL_VERSION + 1,
+        };
+        for version in [legacy, future] {
+            let rejection = version_rejection(&version).unwrap();
+            assert!(rejection.sig.is_empty());
+            assert!(matches!(
+                rejection.inner.body,
+                GossipResponseType::VersionRejected(v) if v == GossipVersion::local()
+            ));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Times outbound requests against their responses to measure the round trip
+//! time to each peer
+
+use std::{collections::HashMap, time::Instant};
+
+use libp2p::request_response::RequestId;
+use types_gossip::{PeerLatencies, WrappedPeerId};
+use util::concurrency::{AsyncShared, new_async_shared};
+
+/// Tracks the send time of in-flight requests, and records the round trip time
+/// of each completed request in the shared latency table
+#[derive(Clone)]
+pub struct RequestTimer {
+    /// The peer and send time of each in-flight request
+    in_flight: AsyncShared<HashMap<RequestId, (WrappedPeerId, Instant)>>,
+    /// The latency table that round trip times are recorded in
+    latencies: PeerLatencies,
+}
+
+impl RequestTimer {
+    /// Constructor
+    pub fn new(latencies: PeerLatencies) -> Self {
+        Self { in_flight: new_async_shared(HashMap::new()), latencies }
+    }
+
+    /// Start timing a request sent to the given peer
+    pub async fn start(&self, request_id: RequestId, peer_id: WrappedPeerId) {
+        self.in_flight.write().await.insert(request_id, (peer_id, Instant::now()));
+    }
+
+    /// Stop timing a request on its response, recording the round trip time
+    pub async fn finish(&self, request_id: RequestId) {
+        if let Some((peer_id, sent_at)) = self.in_flight.write().await.remove(&request_id) {
+            self.latencies.record_sample(peer_id, sent_at.elapsed());
+        }
+    }
+
+    /// Stop timing a request that failed, without recording a sample
+    pub async fn cancel(&self, request_id: RequestId) {
+        self.in_flight.write().await.remove(&request_id);
+    }
+
+    /// Remove a peer's round trip time estimate, e.g. when the peer is expired
+    pub fn remove_peer(&self, peer_id: &WrappedPeerId) {
+        self.latencies.remove(peer_id);
+    }
+}
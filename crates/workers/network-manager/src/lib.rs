@@ -10,6 +10,8 @@
 mod composed_protocol;
 pub mod error;
 pub mod executor;
+pub mod latency;
 pub mod logging;
+pub mod versions;
 pub mod waiters;
 pub mod worker;
@@ -28,6 +28,13 @@ pub enum Task {
     SendResponseNotification,
     /// Handling a raft request routed through the network manager
     HandleRaftRequest,
+    /// Splitting an oversized outbound message into chunks
+    ChunkMessage,
+    /// Refusing traffic with a peer excluded by the access lists
+    PeerAccess,
+    /// Decoding an inbound request or response, refusing those from peers on
+    /// an incompatible protocol version
+    DecodeMessage,
 }
 
 impl LogTask for Task {
@@ -44,6 +51,9 @@ impl LogTask for Task {
             Task::IndexAddr => "index-addr",
             Task::SendResponseNotification => "send-response-notification",
             Task::HandleRaftRequest => "handle-raft-request",
+            Task::ChunkMessage => "chunk-message",
+            Task::PeerAccess => "peer-access",
+            Task::DecodeMessage => "decode-message",
         }
     }
 }
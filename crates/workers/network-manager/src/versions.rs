@@ -0,0 +1,78 @@
+//! Tracks the gossip protocol versions negotiated with connected peers
+
+use std::collections::HashMap;
+
+use gossip_api::request_response::version::GossipVersion;
+use types_gossip::WrappedPeerId;
+use util::concurrency::{AsyncShared, new_async_shared};
+
+/// Maps peers to the gossip protocol versions stamped on their messages
+///
+/// A peer's entry is dropped when its last connection closes, a peer may be
+/// upgraded before it reconnects
+#[derive(Clone)]
+pub struct PeerVersions {
+    /// The underlying map
+    map: AsyncShared<HashMap<WrappedPeerId, GossipVersion>>,
+}
+
+impl Default for PeerVersions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PeerVersions {
+    /// Constructor
+    pub fn new() -> Self {
+        Self { map: new_async_shared(HashMap::new()) }
+    }
+
+    /// Record the versions stamped on a message from the given peer
+    pub async fn record(&self, peer: WrappedPeerId, version: GossipVersion) {
+        self.map.write().await.insert(peer, version);
+    }
+
+    /// Forget the versions of a peer that has disconnected
+    pub async fn remove(&self, peer: &WrappedPeerId) {
+        self.map.write().await.remove(peer);
+    }
+
+    /// Get the versions of the given peer
+    ///
+    /// A peer not yet heard from is assumed to predate versioning
+    pub async fn get(&self, peer: &WrappedPeerId) -> GossipVersion {
+        self.map.read().await.get(peer).copied().unwrap_or_default()
+    }
+
+    /// Whether every peer heard from can reassemble chunked messages
+    pub async fn all_support_chunking(&self) -> bool {
+        self.map.read().await.values().all(GossipVersion::supports_chunking)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that chunking is only negotiated with peers that support it
+    #[tokio::test]
+    async fn test_chunking_negotiation() {
+        let versions = PeerVersions::new();
+        let legacy = WrappedPeerId::random();
+        let upgraded = WrappedPeerId::random();
+
+        // Peers not yet heard from are treated as legacy
+        assert!(!versions.get(&upgraded).await.supports_chunking());
+
+        versions.record(upgraded, GossipVersion::local()).await;
+        assert!(versions.get(&upgraded).await.supports_chunking());
+        assert!(versions.all_support_chunking().await);
+
+        versions.record(legacy, GossipVersion::default()).await;
+        assert!(!versions.all_support_chunking().await);
+
+        versions.remove(&legacy).await;
+        assert!(versions.all_support_chunking().await);
+    }
+}
@@ -15,7 +15,9 @@ use libp2p::{PeerId, Swarm};
 use state::State;
 use system_bus::SystemBus;
 use types_core::HmacKey;
-use types_gossip::{ClusterAsymmetricKeypair, ClusterId, PeerInfo, WrappedPeerId};
+use types_gossip::{
+    ClusterAsymmetricKeypair, ClusterId, PeerAccessList, PeerInfo, PeerLatencies, WrappedPeerId,
+};
 use types_runtime::{CancelChannel, Worker};
 use util::DefaultOption;
 
@@ -92,6 +94,12 @@ pub struct NetworkManagerConfig {
     pub system_bus: SystemBus,
     /// The global shared state of the local relayer
     pub global_state: State,
+    /// The table of round trip times to peers, measured by the network manager
+    pub peer_latencies: PeerLatencies,
+    /// The peers the relayer may talk to, managed by the operator
+    pub peer_access: PeerAccessList,
+    /// Whether to accept order book messages that carry no origin signature
+    pub allow_unsigned_order_book_messages: bool,
     /// The channel on which the coordinator can send a cancel signal to
     /// all network worker threads
     pub cancel_channel: CancelChannel,
@@ -266,9 +274,13 @@ impl Worker for NetworkManager {
             self.local_peer_id,
             self.config.allow_local,
             self.config.cluster_symmetric_key,
+            self.config.cluster_keypair.clone(),
             self.config.send_channel.take().unwrap(),
             self.config.gossip_work_queue.clone(),
             self.config.global_state.clone(),
+            self.config.peer_latencies.clone(),
+            self.config.peer_access.clone(),
+            self.config.allow_unsigned_order_book_messages,
             self.config.cancel_channel.clone(),
         );
 
@@ -0,0 +1,362 @@
+//! Strategies for aggregating exchange prices into a reference price
+//!
+//! The prices reported for a pair across exchanges are aggregated into a
+//! single reference price, against which the canonical price is checked for
+//! deviation and from which a canonical price is derived when the external
+//! price reporter is unavailable. The strategy is selected per pair.
+//!
+//! Alongside its price, an exchange may report the top of its book and its
+//! traded volume for the pair. Strategies that need them fall back to the
+//! exchange price where they are not reported.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use itertools::Itertools;
+use types_core::{Exchange, Price, PriceAggregationStrategy, Token, WeightedPriceSum};
+
+/// The percentage of prices discarded from either end by the trimmed mean
+const TRIM_PERCENT: usize = 20;
+
+/// The top of book and traded volume an exchange reported for a pair
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MarketData {
+    /// The exchange's best bid, if reported
+    pub best_bid: Option<Price>,
+    /// The exchange's best ask, if reported
+    pub best_ask: Option<Price>,
+    /// The exchange's trailing 24 hour traded volume for the pair, in units of
+    /// the quote token, if reported
+    pub volume: Option<f64>,
+}
+
+impl MarketData {
+    /// Convert the market data into units of another quote token, given the
+    /// price of the current quote token in the new one
+    ///
+    /// Data that cannot be converted is dropped
+    pub fn convert_quote(self, quote_price: Price) -> Self {
+        let convert = |price: Price| price.checked_mul(quote_price);
+        Self {
+            best_bid: self.best_bid.and_then(convert),
+            best_ask: self.best_ask.and_then(convert),
+            volume: self.volume.map(|volume| volume * quote_price.to_f64()),
+        }
+    }
+}
+
+/// An exchange's price for a pair, along with the market data it reported
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExchangePrice {
+    /// The exchange reporting the price
+    pub exchange: Exchange,
+    /// The exchange's price for the pair
+    pub price: Price,
+    /// The market data the exchange reported with the price
+    pub market: MarketData,
+}
+
+impl ExchangePrice {
+    /// An exchange price without market data
+    pub fn new(exchange: Exchange, price: Price) -> Self {
+        Self { exchange, price, market: MarketData::default() }
+    }
+
+    /// The exchange's best bid, or its price if it reported no book
+    pub fn best_bid(&self) -> Price {
+        self.market.best_bid.unwrap_or(self.price)
+    }
+
+    /// The exchange's best ask, or its price if it reported no book
+    pub fn best_ask(&self) -> Price {
+        self.market.best_ask.unwrap_or(self.price)
+    }
+
+    /// The weight of the exchange's price by its traded volume, in whole units
+    /// of the quote token, if the exchange reported its volume
+    pub fn volume_weight(&self) -> Option<u128> {
+        let volume = self.market.volume.filter(|volume| volume.is_finite() && *volume > 0.)?;
+        Some(volume.round() as u128)
+    }
+}
+
+/// Aggregates the prices reported for a pair across exchanges
+pub trait PriceAggregator: Debug + Send + Sync {
+    /// Aggregate the given exchange prices into a single price
+    ///
+    /// Returns `None` if no prices are given
+    fn aggregate(&self, prices: &[ExchangePrice]) -> Option<Price>;
+}
+
+/// Get the aggregator implementing the given strategy
+pub fn aggregator_for(strategy: PriceAggregationStrategy) -> &'static dyn PriceAggregator {
+    match strategy {
+        PriceAggregationStrategy::Median => &MedianAggregator,
+        PriceAggregationStrategy::WeightedMedian => &WeightedMedianAggregator,
+        PriceAggregationStrategy::TrimmedMean => &TrimmedMeanAggregator,
+        PriceAggregationStrategy::Midpoint => &MidpointAggregator,
+    }
+}
+
+/// The aggregation strategies configured for each pair
+#[derive(Clone, Debug, Default)]
+pub struct AggregationStrategies {
+    /// The strategy used for pairs without one of their own
+    pub default: PriceAggregationStrategy,
+    /// The strategies for individual pairs, keyed by the base token of the
+    /// USDC quoted pair
+    pub per_pair: HashMap<Token, PriceAggregationStrategy>,
+}
+
+impl AggregationStrategies {
+    /// Build the strategies from per-pair strategies keyed by base ticker
+    ///
+    /// Returns an error naming the first ticker that is not in the token
+    /// mapping
+    pub fn from_tickers(
+        default: PriceAggregationStrategy,
+        per_pair: &HashMap<String, PriceAggregationStrategy>,
+    ) -> Result<Self, String> {
+        let per_pair = per_pair
+            .iter()
+            .map(|(ticker, strategy)| {
+                let token = Token::maybe_from_ticker(ticker).ok_or_else(|| {
+                    format!("unknown ticker in price aggregation strategies: {ticker}")
+                })?;
+                Ok((token, *strategy))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { default, per_pair })
+    }
+
+    /// Get the strategy configured for the given base token
+    pub fn strategy_for(&self, base_token: &Token) -> PriceAggregationStrategy {
+        self.per_pair.get(base_token).copied().unwrap_or(self.default)
+    }
+}
+
+// ---------------
+// | Aggregators |
+// ---------------
+
+/// The median of the exchange prices
+#[derive(Debug)]
+pub struct MedianAggregator;
+
+impl PriceAggregator for MedianAggregator {
+    fn aggregate(&self, prices: &[ExchangePrice]) -> Option<Price> {
+        let sorted = sorted_prices(prices);
+        let mid = sorted.len() / 2;
+        match sorted.len() {
+            0 => None,
+            n if n % 2 == 0 => Some(sorted[mid - 1].midpoint(sorted[mid])),
+            _ => Some(sorted[mid]),
+        }
+    }
+}
+
+/// The median of the exchange prices, with each price weighted by the
+/// exchange's reported traded volume for the pair
+///
+/// The price is the lowest at which the cumulative weight reaches half of
+/// the total weight. Exchanges that did not report their volume carry no
+/// weight; if none did, this is the unweighted median
+#[derive(Debug)]
+pub struct WeightedMedianAggregator;
+
+impl PriceAggregator for WeightedMedianAggregator {
+    fn aggregate(&self, prices: &[ExchangePrice]) -> Option<Price> {
+        let weighted = prices
+            .iter()
+            .filter_map(|price| Some((price.price, price.volume_weight()?)))
+            .sorted_by_key(|(price, _)| *price)
+            .collect_vec();
+        if weighted.is_empty() {
+            return MedianAggregator.aggregate(prices);
+        }
+
+        // Compare against twice the cumulative weight to avoid halving the total
+        let total_weight = weighted.iter().map(|(_, weight)| weight).sum::<u128>();
+        let mut cumulative_weight = 0;
+        for (price, weight) in weighted.iter() {
+            cumulative_weight += weight;
+            if 2 * cumulative_weight >= total_weight {
+                return Some(*price);
+            }
+        }
+
+        weighted.last().map(|(price, _)| *price)
+    }
+}
+
+/// The mean of the exchange prices after discarding `TRIM_PERCENT` percent of
+/// the prices from either end
+#[derive(Debug)]
+pub struct TrimmedMeanAggregator;
+
+impl PriceAggregator for TrimmedMeanAggregator {
+    fn aggregate(&self, prices: &[ExchangePrice]) -> Option<Price> {
+        let sorted = sorted_prices(prices);
+        let trim = sorted.len() * TRIM_PERCENT / 100;
+        let kept = &sorted[trim..sorted.len() - trim];
+
+        let mut sum = WeightedPriceSum::default();
+        kept.iter().for_each(|price| sum.add(*price, 1 /* weight */));
+        sum.mean()
+    }
+}
+
+/// The midpoint of the best bid and best ask across exchanges
+///
+/// The best bid is the highest bid reported by any exchange and the best ask
+/// the lowest ask. An exchange that reported no book contributes its price as
+/// both its bid and ask
+#[derive(Debug)]
+pub struct MidpointAggregator;
+
+impl PriceAggregator for MidpointAggregator {
+    fn aggregate(&self, prices: &[ExchangePrice]) -> Option<Price> {
+        let best_bid = prices.iter().map(ExchangePrice::best_bid).max()?;
+        let best_ask = prices.iter().map(ExchangePrice::best_ask).min()?;
+        Some(best_bid.midpoint(best_ask))
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Sort the given exchange prices in ascending order
+fn sorted_prices(prices: &[ExchangePrice]) -> Vec<Price> {
+    prices.iter().map(|price| price.price).sorted().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use types_core::{
+        Chain, Exchange, Price, PriceAggregationStrategy, set_default_chain, write_token_remaps,
+    };
+
+    use super::{
+        AggregationStrategies, ExchangePrice, MarketData, MedianAggregator, MidpointAggregator,
+        PriceAggregator, TrimmedMeanAggregator, WeightedMedianAggregator,
+    };
+
+    /// Build a price from a float
+    fn price(val: f64) -> Price {
+        Price::from_f64_round_down(val).unwrap()
+    }
+
+    /// Build an exchange price with the given market data
+    fn exchange_price(exchange: Exchange, val: f64, market: MarketData) -> ExchangePrice {
+        ExchangePrice { exchange, price: price(val), market }
+    }
+
+    /// Build market data reporting only a traded volume
+    fn volume(volume: f64) -> MarketData {
+        MarketData { volume: Some(volume), ..Default::default() }
+    }
+
+    /// Build market data reporting only a book
+    fn book(best_bid: f64, best_ask: f64) -> MarketData {
+        MarketData {
+            best_bid: Some(price(best_bid)),
+            best_ask: Some(price(best_ask)),
+            volume: None,
+        }
+    }
+
+    /// Tests the median of an odd and an even number of prices
+    #[test]
+    fn test_median() {
+        let prices = [
+            ExchangePrice::new(Exchange::Binance, price(3.)),
+            ExchangePrice::new(Exchange::Okx, price(1.)),
+            ExchangePrice::new(Exchange::Kraken, price(2.)),
+        ];
+        assert_eq!(MedianAggregator.aggregate(&prices), Some(price(2.)));
+        assert_eq!(MedianAggregator.aggregate(&prices[..2]), Some(price(2.)));
+        assert_eq!(MedianAggregator.aggregate(&[]), None);
+    }
+
+    /// Tests that the weighted median follows the reported volume
+    #[test]
+    fn test_weighted_median() {
+        let prices = [
+            exchange_price(Exchange::Binance, 1., volume(10.)),
+            exchange_price(Exchange::Okx, 2., volume(10.)),
+            exchange_price(Exchange::Kraken, 3., volume(100.)),
+        ];
+        assert_eq!(WeightedMedianAggregator.aggregate(&prices), Some(price(3.)));
+    }
+
+    /// Tests that exchanges without a reported volume carry no weight, and
+    /// that the weighted median is the median if none report one
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_weighted_median__unreported_volume() {
+        let prices = [
+            exchange_price(Exchange::Binance, 1., MarketData::default()),
+            exchange_price(Exchange::Okx, 2., MarketData::default()),
+            exchange_price(Exchange::Kraken, 3., volume(1.)),
+        ];
+        assert_eq!(WeightedMedianAggregator.aggregate(&prices), Some(price(3.)));
+        assert_eq!(WeightedMedianAggregator.aggregate(&prices[..2]), Some(price(1.5)));
+    }
+
+    /// Tests that the trimmed mean discards the outliers
+    #[test]
+    fn test_trimmed_mean() {
+        let prices = [1., 10., 10., 10., 100.]
+            .into_iter()
+            .map(|val| ExchangePrice::new(Exchange::Binance, price(val)))
+            .collect::<Vec<_>>();
+        assert_eq!(TrimmedMeanAggregator.aggregate(&prices), Some(price(10.)));
+    }
+
+    /// Tests that the midpoint is taken between the best bid and best ask
+    /// across exchanges
+    #[test]
+    fn test_midpoint() {
+        let prices = [
+            exchange_price(Exchange::Binance, 100., book(99., 101.)),
+            exchange_price(Exchange::Okx, 100.5, book(100., 102.)),
+            // An exchange without a book quotes its price on both sides
+            exchange_price(Exchange::Kraken, 100.75, MarketData::default()),
+        ];
+
+        // Best bid 100.75 (Kraken), best ask 100.75 (Kraken)
+        assert_eq!(MidpointAggregator.aggregate(&prices), Some(price(100.75)));
+        // Best bid 100 (OKX), best ask 101 (Binance)
+        assert_eq!(MidpointAggregator.aggregate(&prices[..2]), Some(price(100.5)));
+    }
+
+    /// Tests converting market data into another quote
+    #[test]
+    fn test_convert_quote() {
+        let market = MarketData {
+            best_bid: Some(price(99.)),
+            best_ask: Some(price(101.)),
+            volume: Some(1_000.),
+        };
+        let converted = market.convert_quote(price(2.));
+        assert_eq!(converted.best_bid, Some(price(198.)));
+        assert_eq!(converted.best_ask, Some(price(202.)));
+        assert_eq!(converted.volume, Some(2_000.));
+    }
+
+    /// Tests that an unknown ticker is an error rather than a panic
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_from_tickers__unknown_ticker() {
+        write_token_remaps().entry(Chain::ArbitrumOne).or_default();
+        set_default_chain(Chain::ArbitrumOne);
+
+        let default = PriceAggregationStrategy::Median;
+        let per_pair =
+            HashMap::from([("NOT-A-TICKER".to_string(), PriceAggregationStrategy::Midpoint)]);
+        assert!(AggregationStrategies::from_tickers(default, &per_pair).is_err());
+        assert!(AggregationStrategies::from_tickers(default, &HashMap::new()).is_ok());
+    }
+}
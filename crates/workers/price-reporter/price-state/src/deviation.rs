@@ -0,0 +1,107 @@
+//! The price deviation circuit breaker
+//!
+//! A single compromised or glitching exchange feed can drag the reference
+//! price for a pair away from the market. Before exchange prices are
+//! aggregated, each is compared against their median, and sources deviating
+//! from it by more than the configured fraction are excluded.
+
+use types_core::Exchange;
+
+use crate::aggregation::{ExchangePrice, MedianAggregator, PriceAggregator};
+
+/// The number of sources below which the breaker does not exclude any
+///
+/// With fewer sources the median cannot tell the deviating source apart from
+/// the others
+const MIN_BREAKER_SOURCES: usize = 3;
+
+/// The exchange prices for a pair, split by the deviation circuit breaker
+#[derive(Debug, Default)]
+pub struct DeviationCheck {
+    /// The prices within the maximum deviation of the median
+    pub included: Vec<ExchangePrice>,
+    /// The sources deviating beyond the maximum, along with their deviation
+    /// from the median as a fraction
+    pub excluded: Vec<(Exchange, f64)>,
+}
+
+/// Split the given exchange prices into those within the maximum deviation of
+/// their median and those beyond it
+///
+/// A maximum deviation of zero disables the breaker
+pub fn check_source_deviation(prices: &[ExchangePrice], max_deviation: f64) -> DeviationCheck {
+    let median = MedianAggregator.aggregate(prices);
+    let Some(median) = median.filter(|_| max_deviation > 0. && prices.len() >= MIN_BREAKER_SOURCES)
+    else {
+        return DeviationCheck { included: prices.to_vec(), excluded: Vec::new() };
+    };
+
+    let mut check = DeviationCheck::default();
+    for price in prices.iter().copied() {
+        let deviation = price.price.relative_deviation(median);
+        if deviation > max_deviation {
+            check.excluded.push((price.exchange, deviation));
+        } else {
+            check.included.push(price);
+        }
+    }
+
+    check
+}
+
+#[cfg(test)]
+mod test {
+    use types_core::{Exchange, Price};
+
+    use super::check_source_deviation;
+    use crate::aggregation::ExchangePrice;
+
+    /// Build the exchange prices under test
+    fn prices(vals: &[(Exchange, f64)]) -> Vec<ExchangePrice> {
+        vals.iter()
+            .map(|&(exchange, val)| {
+                ExchangePrice::new(exchange, Price::from_f64_round_down(val).unwrap())
+            })
+            .collect()
+    }
+
+    /// Tests that a source deviating beyond the maximum from the median is
+    /// excluded, along with its deviation
+    #[test]
+    fn test_check_source_deviation() {
+        let prices =
+            prices(&[(Exchange::Binance, 100.), (Exchange::Okx, 101.), (Exchange::Kraken, 120.)]);
+
+        let check = check_source_deviation(&prices, 0.05);
+        let included = check.included.iter().map(|price| price.exchange).collect::<Vec<_>>();
+        assert_eq!(included, vec![Exchange::Binance, Exchange::Okx]);
+        assert_eq!(check.excluded.len(), 1);
+
+        let (exchange, deviation) = check.excluded[0];
+        assert_eq!(exchange, Exchange::Kraken);
+        assert!((deviation - 19. / 101.).abs() < 1e-6);
+    }
+
+    /// Tests that no source is excluded with fewer sources than the median
+    /// can tell apart
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_check_source_deviation__too_few_sources() {
+        let prices = prices(&[(Exchange::Binance, 100.), (Exchange::Okx, 150.)]);
+        let check = check_source_deviation(&prices, 0.05);
+        assert_eq!(check.included.len(), 2);
+        assert!(check.excluded.is_empty());
+    }
+
+    /// Tests that a maximum deviation of zero disables the breaker
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_check_source_deviation__disabled() {
+        let prices =
+            prices(&[(Exchange::Binance, 100.), (Exchange::Okx, 101.), (Exchange::Kraken, 120.)]);
+
+        let check = check_source_deviation(&prices, 0.);
+        assert_eq!(check.included.len(), 3);
+        assert!(check.excluded.is_empty());
+    }
+}
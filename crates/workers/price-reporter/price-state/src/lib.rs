@@ -4,10 +4,13 @@
 #![deny(clippy::missing_docs_in_private_items)]
 #![allow(incomplete_features)]
 
+pub mod aggregation;
+pub mod deviation;
 pub mod error;
 pub mod logging;
 mod state;
 pub mod util;
+pub mod vwap;
 pub use state::*;
 
 use types_core::{Exchange, Token};
@@ -1,29 +1,51 @@
 //! Concurrency primitives for the PriceReporter manager's shared streams
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, hash_map::Entry},
     sync::{
-        Arc,
-        atomic::{AtomicU64, Ordering},
+        Arc, RwLock, RwLockReadGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
     },
 };
 
-use atomic_float::AtomicF64;
 use itertools::Itertools;
+use portable_atomic::AtomicU128;
 use types_account::pair::Pair;
 use types_core::{
-    Exchange, Price, PriceReporterState, TimestampedPrice, Token, default_exchange_stable,
-    is_pair_named,
+    Exchange, ExchangeConnectionState, Price, PriceReport, PriceReporterState, TimestampedPrice,
+    Token, VwapWindow, default_exchange_stable, is_pair_named,
 };
+use util::get_current_time_millis;
 
 use crate::{
     StreamTuple,
+    aggregation::{
+        AggregationStrategies, ExchangePrice, MarketData, PriceAggregator, aggregator_for,
+    },
+    deviation::check_source_deviation,
     error::PriceStateError,
     util::{
         compute_price_reporter_state, eligible_for_stable_quote_conversion, get_listing_exchanges,
+        ts_too_stale,
     },
+    vwap::RollingVwap,
 };
 
+/// The maximum age of an exchange price sampled into a pair's VWAP
+///
+/// Older prices, e.g. from a disconnected exchange, would otherwise be
+/// sampled repeatedly
+const MAX_VWAP_SAMPLE_AGE_MS: u64 = 60_000; // 1 minute
+/// The minimum number of fresh exchange prices from which a canonical price
+/// is derived while the external price reporter is unreachable
+///
+/// A single exchange's price is not taken as the canonical price
+const MIN_FALLBACK_CANONICAL_SOURCES: usize = 2;
+/// The error message emitted when the VWAP lock is poisoned
+const ERR_VWAP_LOCK_POISONED: &str = "VWAP lock poisoned";
+/// The error message emitted when the stream states lock is poisoned
+const ERR_STATES_LOCK_POISONED: &str = "price stream states lock poisoned";
+
 // ---------------------------
 // | Individual Stream State |
 // ---------------------------
@@ -32,16 +54,46 @@ use crate::{
 /// Uses atomic primitives to allow for hardware synchronized update streaming
 #[derive(Debug, Default)]
 pub struct AtomicPriceStreamState {
-    /// The price of the pair on the exchange
-    price: AtomicF64,
+    /// The fixed-point representation of the price of the pair on the
+    /// exchange
+    price: AtomicU128,
     /// The time at which the last price was received from the exchange
     last_received: AtomicU64,
+    /// Whether the last price was polled from the exchange's REST ticker
+    /// rather than streamed
+    degraded: AtomicBool,
+    /// The time since which the stream has awaited its first price, used to
+    /// age a stream which has not yet reported
+    awaiting_since: AtomicU64,
+    /// One past the timestamp of the price the staleness watchdog found too
+    /// old, zero if the stream is not marked
+    ///
+    /// The mark is bound to the price it was made for, so a price received
+    /// while the watchdog marks the stream clears it
+    stale_for: AtomicU64,
+    /// The fixed-point representation of the exchange's best bid, zero if not
+    /// reported
+    best_bid: AtomicU128,
+    /// The fixed-point representation of the exchange's best ask, zero if not
+    /// reported
+    best_ask: AtomicU128,
+    /// The bits of the exchange's trailing 24 hour traded volume, zero if not
+    /// reported
+    volume: AtomicU64,
 }
 
 impl AtomicPriceStreamState {
+    /// Create a new state awaiting its first price
+    pub fn new() -> Self {
+        let state = Self::default();
+        state.awaiting_since.store(get_current_time_millis(), Ordering::Relaxed);
+        state
+    }
+
     /// Read the price and timestamp
     pub fn read_price(&self) -> (Price, u64) {
-        (self.price.load(Ordering::Relaxed), self.last_received.load(Ordering::Relaxed))
+        let price = Price::from_repr(self.price.load(Ordering::Relaxed));
+        (price, self.last_received.load(Ordering::Relaxed))
     }
 
     /// Update the state of the price stream
@@ -50,14 +102,96 @@ impl AtomicPriceStreamState {
         // for a race in between updating the timestamp and the price. This is
         // generally okay as the timestamp is only used for determining staleness
         // and given a race the timestamp will be very close to correct
-        self.price.store(price, Ordering::Relaxed);
+        self.price.store(price.repr(), Ordering::Relaxed);
         self.last_received.store(timestamp, Ordering::Relaxed);
+        self.degraded.store(false, Ordering::Relaxed);
+    }
+
+    /// Update the state of the price stream with a price polled in place of
+    /// the stream, marking the stream degraded
+    ///
+    /// Polled tickers carry no book, so any market data streamed earlier is
+    /// cleared rather than left to describe an old price
+    pub fn new_degraded_price(&self, price: Price, timestamp: u64) {
+        self.price.store(price.repr(), Ordering::Relaxed);
+        self.last_received.store(timestamp, Ordering::Relaxed);
+        self.degraded.store(true, Ordering::Relaxed);
+        self.new_market_data(MarketData::default());
+    }
+
+    /// Update the state of the price stream with a degraded price, unless the
+    /// stream already holds a price at least as recent
+    ///
+    /// Returns whether the price was saved
+    pub fn new_degraded_price_if_newer(&self, price: Price, timestamp: u64) -> bool {
+        if self.last_received.fetch_max(timestamp, Ordering::Relaxed) >= timestamp {
+            return false;
+        }
+
+        self.price.store(price.repr(), Ordering::Relaxed);
+        self.degraded.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Read the market data last reported with the price
+    pub fn read_market_data(&self) -> MarketData {
+        let read_price = |repr: &AtomicU128| {
+            Some(Price::from_repr(repr.load(Ordering::Relaxed))).filter(|p| !p.is_zero())
+        };
+        let volume = f64::from_bits(self.volume.load(Ordering::Relaxed));
+        MarketData {
+            best_bid: read_price(&self.best_bid),
+            best_ask: read_price(&self.best_ask),
+            volume: (volume > 0.).then_some(volume),
+        }
+    }
+
+    /// Update the market data reported with the stream's price
+    ///
+    /// As with the price, the fields are not updated transactionally
+    pub fn new_market_data(&self, market: MarketData) {
+        let repr = |price: Option<Price>| price.map(|p| p.repr()).unwrap_or_default();
+        self.best_bid.store(repr(market.best_bid), Ordering::Relaxed);
+        self.best_ask.store(repr(market.best_ask), Ordering::Relaxed);
+        self.volume.store(market.volume.unwrap_or_default().to_bits(), Ordering::Relaxed);
+    }
+
+    /// Whether the last price was polled in place of the stream
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Mark the stream stale until its next price if its last price is older
+    /// than `max_age_ms` as of `now`, returning the age of the price if the
+    /// stream was not already marked
+    ///
+    /// A stream which has not yet reported is aged from when it began awaiting
+    /// its first price
+    pub fn mark_stale_if_older(&self, max_age_ms: u64, now: u64) -> Option<u64> {
+        let ts = self.last_received.load(Ordering::Relaxed);
+        let since = if ts == 0 { self.awaiting_since.load(Ordering::Relaxed) } else { ts };
+        let age_ms = now.saturating_sub(since);
+        if age_ms <= max_age_ms {
+            return None;
+        }
+
+        let newly_marked = self.stale_for.swap(ts + 1, Ordering::Relaxed) != ts + 1;
+        newly_marked.then_some(age_ms)
+    }
+
+    /// Whether the stream has been marked stale since its last price
+    pub fn is_stale(&self) -> bool {
+        self.stale_for.load(Ordering::Relaxed) == self.last_received.load(Ordering::Relaxed) + 1
     }
 
     /// Clear the state of the price stream
     pub fn clear(&self) {
-        self.price.store(0.0, Ordering::Relaxed);
+        self.price.store(0, Ordering::Relaxed);
         self.last_received.store(0, Ordering::Relaxed);
+        self.degraded.store(false, Ordering::Relaxed);
+        self.awaiting_since.store(get_current_time_millis(), Ordering::Relaxed);
+        self.stale_for.store(0, Ordering::Relaxed);
+        self.new_market_data(MarketData::default());
     }
 }
 
@@ -78,9 +212,21 @@ pub struct PriceStreamStates(Arc<PriceStreamStatesInner>);
 pub struct PriceStreamStatesInner {
     /// The mapping between (exchange, base, quote) and the most recent
     /// state of a price stream
-    states: Arc<HashMap<StreamTuple, AtomicPriceStreamState>>,
+    ///
+    /// Streams are added when tokens are listed at runtime, but never removed
+    states: Arc<RwLock<HashMap<StreamTuple, AtomicPriceStreamState>>>,
     /// The set of disabled exchanges
     disabled_exchanges: HashSet<Exchange>,
+    /// The strategies by which exchange prices are aggregated for each pair
+    aggregation: AggregationStrategies,
+    /// The fraction by which an exchange price may deviate from the median of
+    /// a pair's exchange prices before it is excluded, zero if unlimited
+    max_source_deviation: f64,
+    /// The intermediate token through which each synthetic pair's price is
+    /// derived, keyed by the pair's base token
+    synthetic_routes: HashMap<Token, Token>,
+    /// The rolling VWAP samples of each (base, quote) pair
+    vwaps: Arc<RwLock<HashMap<(Token, Token), RollingVwap>>>,
 }
 
 impl PriceStreamStates {
@@ -88,24 +234,44 @@ impl PriceStreamStates {
     ///
     /// This inserts a default state for each (exchange, base, quote) pair
     /// which is supported by the given config
-    pub fn new(streams: Vec<StreamTuple>, disabled_exchanges: Vec<Exchange>) -> Self {
+    ///
+    /// A base token with a synthetic route is priced through the route's
+    /// intermediate token, as base / intermediate × intermediate / quote
+    pub fn new(
+        streams: Vec<StreamTuple>,
+        disabled_exchanges: Vec<Exchange>,
+        aggregation: AggregationStrategies,
+        max_source_deviation: f64,
+        synthetic_routes: HashMap<Token, Token>,
+    ) -> Self {
         let states = streams
             .into_iter()
-            .map(|(exchange, base, quote)| {
-                ((exchange, base, quote), AtomicPriceStreamState::default())
-            })
+            .map(|(exchange, base, quote)| ((exchange, base, quote), AtomicPriceStreamState::new()))
             .collect();
 
         let inner = PriceStreamStatesInner {
-            states: Arc::new(states),
+            states: Arc::new(RwLock::new(states)),
             disabled_exchanges: disabled_exchanges.into_iter().collect(),
+            aggregation,
+            max_source_deviation,
+            synthetic_routes,
+            vwaps: Arc::new(RwLock::new(HashMap::new())),
         };
         Self(Arc::new(inner))
     }
 
-    /// Get a reference to the inner states
-    fn states(&self) -> &HashMap<StreamTuple, AtomicPriceStreamState> {
-        &self.0.states
+    /// Get a read guard on the inner states
+    fn states(&self) -> RwLockReadGuard<'_, HashMap<StreamTuple, AtomicPriceStreamState>> {
+        self.0.states.read().expect(ERR_STATES_LOCK_POISONED)
+    }
+
+    /// Get the (base, quote) pairs with a canonical `Exchange::Renegade` stream
+    fn canonical_pairs(&self) -> Vec<(Token, Token)> {
+        self.states()
+            .keys()
+            .filter(|(exchange, ..)| *exchange == Exchange::Renegade)
+            .map(|(_, base, quote)| (base.clone(), quote.clone()))
+            .collect()
     }
 
     /// Returns whether a given exchange is disabled
@@ -113,6 +279,17 @@ impl PriceStreamStates {
         self.0.disabled_exchanges.contains(exchange)
     }
 
+    /// Get the intermediate token through which the given pair is derived, if
+    /// the pair is synthetic
+    fn synthetic_intermediate(&self, base_token: &Token, quote_token: &Token) -> Option<&Token> {
+        self.0.synthetic_routes.get(base_token).filter(|intermediate| *intermediate != quote_token)
+    }
+
+    /// Get the aggregator configured for the given base token
+    fn aggregator(&self, base_token: &Token) -> &'static dyn PriceAggregator {
+        aggregator_for(self.0.aggregation.strategy_for(base_token))
+    }
+
     // --- Getters --- //
 
     /// Peek at the Renegade price for the given base token
@@ -135,8 +312,8 @@ impl PriceStreamStates {
     ) -> Result<TimestampedPrice, PriceStateError> {
         let quote = Token::usdc();
         let tuple = (Exchange::Renegade, base.clone(), quote.clone());
-        let atomic_price = self
-            .states()
+        let states = self.states();
+        let atomic_price = states
             .get(&tuple)
             .ok_or_else(|| PriceStateError::pair_not_configured(base.clone(), quote.clone()))?;
 
@@ -144,12 +321,62 @@ impl PriceStreamStates {
         Ok(TimestampedPrice { price, timestamp })
     }
 
+    /// Peek at the VWAP for the given base token over the given trailing
+    /// window
+    ///
+    /// Uses the canonical quote (USDC), as with `peek_price`
+    pub fn peek_vwap(
+        &self,
+        base: &Token,
+        window: VwapWindow,
+    ) -> Result<TimestampedPrice, PriceStateError> {
+        let quote = Token::usdc();
+        let vwaps = self.0.vwaps.read().expect(ERR_VWAP_LOCK_POISONED);
+        vwaps
+            .get(&(base.clone(), quote.clone()))
+            .and_then(|pair_vwap| pair_vwap.vwap(window, get_current_time_millis()))
+            .ok_or_else(|| {
+                PriceStateError::no_price_data(format!("No {window} VWAP for {base} / {quote}"))
+            })
+    }
+
+    /// Get the decimal-corrected VWAP for a pair over the given trailing
+    /// window, in units of output token / input token
+    pub fn get_output_quoted_vwap(
+        &self,
+        pair: &Pair,
+        window: VwapWindow,
+    ) -> Result<TimestampedPrice, PriceStateError> {
+        let usdc_quoted_pair = pair.to_usdc_quoted().map_err(PriceStateError::no_price_data)?;
+        let (base, quote) = (usdc_quoted_pair.in_token(), usdc_quoted_pair.out_token());
+
+        let vwap = self.peek_vwap(&base, window)?;
+        let mut corrected_price = vwap
+            .get_decimal_corrected_price(&base, &quote)
+            .map_err(PriceStateError::no_price_data)?;
+
+        // As with the spot price, invert if the input token is the quote token
+        if pair.is_input_quote() {
+            corrected_price = corrected_price.invert().map_err(PriceStateError::no_price_data)?;
+        }
+        Ok(corrected_price)
+    }
+
     /// Get the decimal-corrected execution price for a pair, in units of
     /// output token / input token
     pub fn get_output_quoted_price(
         &self,
         pair: &Pair,
     ) -> Result<TimestampedPrice, PriceStateError> {
+        self.get_output_quoted_price_with_confidence(pair).map(|(price, _)| price)
+    }
+
+    /// Get the decimal-corrected execution price for a pair, in units of
+    /// output token / input token, along with the confidence in the price
+    pub fn get_output_quoted_price_with_confidence(
+        &self,
+        pair: &Pair,
+    ) -> Result<(TimestampedPrice, f64), PriceStateError> {
         // Convert the pair to a canonically quoted pair
         let usdc_quoted_pair = pair.to_usdc_quoted().map_err(PriceStateError::no_price_data)?;
         let (base, quote) = (usdc_quoted_pair.in_token(), usdc_quoted_pair.out_token());
@@ -160,6 +387,7 @@ impl PriceStreamStates {
             PriceStateError::no_price_data(format!("No price data for {base} / {quote}"))
         })?;
         let price: TimestampedPrice = state.into();
+        let confidence = state.confidence;
 
         // Correct the price for decimals
         let mut corrected_price = price
@@ -169,9 +397,9 @@ impl PriceStreamStates {
         // The decimal corrected price is in units of quote / base. If the input
         // token is the quote token, we need to invert the price.
         if pair.is_input_quote() {
-            corrected_price = corrected_price.invert();
+            corrected_price = corrected_price.invert().map_err(PriceStateError::no_price_data)?;
         }
-        Ok(corrected_price)
+        Ok((corrected_price, confidence))
     }
 
     /// Get the state of the price reporter for the given token pair
@@ -192,17 +420,251 @@ impl PriceStreamStates {
         let mut exchange_prices = Vec::new();
         let supported_exchanges = self.get_supported_exchanges(base_token, quote_token);
         for exchange in supported_exchanges {
-            if let Some((price, ts)) = self.get_latest_price(exchange, base_token, quote_token) {
-                exchange_prices.push((exchange, (price, ts)));
+            if let Some(price) = self.get_latest_exchange_price(exchange, base_token, quote_token) {
+                exchange_prices.push(price);
             }
         }
 
         // Compute the state of the price reporter
-        compute_price_reporter_state(base_token, quote_token, price, ts, &exchange_prices)
+        let aggregator = self.aggregator(base_token);
+        compute_price_reporter_state(
+            base_token,
+            quote_token,
+            price,
+            ts,
+            &exchange_prices,
+            aggregator,
+            self.0.max_source_deviation,
+        )
+    }
+
+    /// Get the state of the connection backing the given stream
+    pub fn get_connection_state(
+        &self,
+        exchange: Exchange,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> ExchangeConnectionState {
+        let stream_tuple = (exchange, base_token.clone(), quote_token.clone());
+        let states = self.states();
+        let Some(state) = states.get(&stream_tuple) else {
+            return ExchangeConnectionState::Unsupported;
+        };
+
+        let (price, local_timestamp) = state.read_price();
+        if local_timestamp == 0 {
+            return ExchangeConnectionState::NoDataReported;
+        }
+
+        let report = PriceReport {
+            base_token: base_token.clone(),
+            quote_token: quote_token.clone(),
+            price,
+            local_timestamp,
+            confidence: 0.,
+        };
+        if state.is_stale() {
+            ExchangeConnectionState::Stale(report)
+        } else if state.is_degraded() {
+            ExchangeConnectionState::Degraded(report)
+        } else {
+            ExchangeConnectionState::Nominal(report)
+        }
+    }
+
+    /// Aggregate the latest prices reported for the given pair across all
+    /// exchanges by the pair's strategy, along with the oldest timestamp among
+    /// them
+    ///
+    /// Sources excluded by the deviation circuit breaker are not aggregated.
+    /// Returns `None` if no exchange has reported a price for the pair
+    pub fn aggregate_exchange_price(
+        &self,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Option<(Price, u64)> {
+        let prices = self.latest_exchange_prices(base_token, quote_token);
+        let oldest_ts = prices.iter().map(|(_, ts)| *ts).min()?;
+        let prices = prices.into_iter().map(|(price, _)| price).collect_vec();
+
+        let check = check_source_deviation(&prices, self.0.max_source_deviation);
+        let price = self.aggregator(base_token).aggregate(&check.included)?;
+        Some((price, oldest_ts))
+    }
+
+    /// Derive the canonical price for a pair from the exchange prices
+    /// received since `since`, in place of the external price reporter's
+    /// stream, and save it as degraded
+    ///
+    /// The price aggregates at least `MIN_FALLBACK_CANONICAL_SOURCES` fresh
+    /// prices that pass the deviation breaker and is stamped with the oldest of
+    /// them. It never replaces a canonical price at least as recent, so a
+    /// price streamed by the reporter is kept. Returns the saved price
+    pub fn new_fallback_canonical_price(
+        &self,
+        base_token: &Token,
+        quote_token: &Token,
+        since: u64,
+    ) -> Option<Price> {
+        let prices = self
+            .latest_exchange_prices(base_token, quote_token)
+            .into_iter()
+            .filter(|(_, ts)| *ts >= since)
+            .collect_vec();
+        let oldest_ts = prices.iter().map(|(_, ts)| *ts).min()?;
+        let prices = prices.into_iter().map(|(price, _)| price).collect_vec();
+
+        let check = check_source_deviation(&prices, self.0.max_source_deviation);
+        if check.included.len() < MIN_FALLBACK_CANONICAL_SOURCES {
+            return None;
+        }
+        let price = self.aggregator(base_token).aggregate(&check.included)?;
+
+        let stream_tuple = (Exchange::Renegade, base_token.clone(), quote_token.clone());
+        let states = self.states();
+        let saved = states.get(&stream_tuple)?.new_degraded_price_if_newer(price, oldest_ts);
+        saved.then_some(price)
+    }
+
+    /// Derive the canonical price of a synthetic pair, along with the oldest
+    /// timestamp among its legs
+    ///
+    /// The price is the aggregate of the exchanges' base / intermediate prices
+    /// times the canonical intermediate / quote price. Each exchange's own
+    /// price for the pair is derived through its own intermediate / quote
+    /// market instead, so that the canonical price can be checked against
+    /// them. Returns `None` if the pair is not synthetic or a leg has no price
+    pub fn derive_synthetic_price(
+        &self,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Option<(Price, u64)> {
+        let intermediate = self.synthetic_intermediate(base_token, quote_token)?;
+        let (leg_price, leg_ts) = self.aggregate_exchange_price(base_token, intermediate)?;
+        let (cross_price, cross_ts) = self
+            .get_latest_price(Exchange::Renegade, intermediate, quote_token)
+            .filter(|(price, ts)| *ts != 0 && !price.is_zero())?;
+
+        let price = leg_price.checked_mul(cross_price)?;
+        Some((price, leg_ts.min(cross_ts)))
+    }
+
+    /// Get the exchange streams currently excluded by the deviation circuit
+    /// breaker, along with their deviation from the median of their pair
+    pub fn deviating_sources(&self) -> Vec<(StreamTuple, f64)> {
+        let mut deviating = Vec::new();
+        for (base, quote) in self.canonical_pairs() {
+            let prices = self
+                .latest_exchange_prices(&base, &quote)
+                .into_iter()
+                .map(|(price, _)| price)
+                .collect_vec();
+
+            let check = check_source_deviation(&prices, self.0.max_source_deviation);
+            for (exchange, deviation) in check.excluded {
+                deviating.push(((exchange, base.clone(), quote.clone()), deviation));
+            }
+        }
+
+        deviating
+    }
+
+    /// Get the age in milliseconds of the latest price on each stream
+    ///
+    /// Streams which have not yet received a price are skipped
+    pub fn stream_ages_ms(&self) -> Vec<(StreamTuple, u64)> {
+        let now = get_current_time_millis();
+        self.states()
+            .iter()
+            .filter_map(|(stream, state)| {
+                let (_, ts) = state.read_price();
+                (ts != 0).then(|| (stream.clone(), now.saturating_sub(ts)))
+            })
+            .collect()
     }
 
     // --- Setters --- //
 
+    /// Record a sample of each pair's latest exchange prices in the pair's
+    /// rolling VWAP
+    ///
+    /// Sources excluded by the deviation circuit breaker are not sampled
+    pub fn record_vwap_samples(&self) {
+        let now = get_current_time_millis();
+        let pairs = self.canonical_pairs();
+
+        let mut vwaps = self.0.vwaps.write().expect(ERR_VWAP_LOCK_POISONED);
+        for (base, quote) in pairs {
+            let prices = self
+                .latest_exchange_prices(&base, &quote)
+                .into_iter()
+                .filter(|(_, ts)| now.saturating_sub(*ts) <= MAX_VWAP_SAMPLE_AGE_MS)
+                .map(|(price, _)| price)
+                .collect_vec();
+
+            let check = check_source_deviation(&prices, self.0.max_source_deviation);
+            let pair_vwap = vwaps.entry((base, quote)).or_default();
+            pair_vwap.record(now, &check.included);
+        }
+    }
+
+    /// Add a default state for each of the given streams that is not already
+    /// tracked, returning the streams that were added
+    pub fn add_streams(&self, streams: Vec<StreamTuple>) -> Vec<StreamTuple> {
+        let mut states = self.0.states.write().expect(ERR_STATES_LOCK_POISONED);
+
+        let mut added = Vec::new();
+        for stream in streams {
+            if let Entry::Vacant(entry) = states.entry(stream.clone()) {
+                entry.insert(AtomicPriceStreamState::new());
+                added.push(stream);
+            }
+        }
+
+        added
+    }
+
+    /// Mark each stream stale whose last price is older than `max_age_ms`,
+    /// returning the newly marked streams along with the age of their prices
+    ///
+    /// Streams of every exchange are checked, including those which have not
+    /// yet reported
+    pub fn mark_stale_streams(&self, max_age_ms: u64) -> Vec<(StreamTuple, u64)> {
+        let now = get_current_time_millis();
+        self.states()
+            .iter()
+            .filter_map(|(stream, state)| {
+                let age_ms = state.mark_stale_if_older(max_age_ms, now)?;
+                Some((stream.clone(), age_ms))
+            })
+            .collect()
+    }
+
+    /// Whether the given stream has been marked stale since its last price
+    pub fn is_stale(&self, stream: &StreamTuple) -> bool {
+        self.states().get(stream).is_some_and(|state| state.is_stale())
+    }
+
+    /// Whether the price of the given pair is backed by stale data
+    ///
+    /// This is the case if any stream backing the canonical price is stale, or
+    /// if the pair is streamed from exchanges and every exchange's price is
+    /// stale
+    pub fn is_pair_stale(&self, base_token: &Token, quote_token: &Token) -> bool {
+        let canonical = self.exchange_price_staleness(Exchange::Renegade, base_token, quote_token);
+        if canonical == Some(true) {
+            return true;
+        }
+
+        let exchanges = self
+            .get_supported_exchanges(base_token, quote_token)
+            .into_iter()
+            .filter(|exchange| *exchange != Exchange::Renegade)
+            .filter_map(|exchange| self.exchange_price_staleness(exchange, base_token, quote_token))
+            .collect_vec();
+        !exchanges.is_empty() && exchanges.into_iter().all(|stale| stale)
+    }
+
     /// Clear all price states, returning the keys that were cleared
     pub fn clear_states(&self) -> Vec<(Exchange, Token, Token)> {
         // Iterate over the elements, clear the values and clone the keys
@@ -225,8 +687,8 @@ impl PriceStreamStates {
         timestamp: u64,
     ) -> Result<(), String> {
         let stream_tuple = (exchange, base, quote);
-        let price_state = self
-            .states()
+        let states = self.states();
+        let price_state = states
             .get(&stream_tuple)
             .ok_or(format!("Price stream state not found for {stream_tuple:?}"))?;
         price_state.new_price(price, timestamp);
@@ -234,23 +696,148 @@ impl PriceStreamStates {
         Ok(())
     }
 
+    /// Update the price state for the given (exchange, base, quote) with a
+    /// price polled in place of the stream, marking the stream degraded
+    pub fn new_degraded_price(
+        &self,
+        exchange: Exchange,
+        base: Token,
+        quote: Token,
+        price: Price,
+        timestamp: u64,
+    ) -> Result<(), String> {
+        let stream_tuple = (exchange, base, quote);
+        let states = self.states();
+        let price_state = states
+            .get(&stream_tuple)
+            .ok_or(format!("Price stream state not found for {stream_tuple:?}"))?;
+        price_state.new_degraded_price(price, timestamp);
+
+        Ok(())
+    }
+
+    /// Update the market data reported with the price for the given
+    /// (exchange, base, quote)
+    pub fn new_market_data(
+        &self,
+        exchange: Exchange,
+        base: Token,
+        quote: Token,
+        market: MarketData,
+    ) -> Result<(), String> {
+        let stream_tuple = (exchange, base, quote);
+        let states = self.states();
+        let price_state = states
+            .get(&stream_tuple)
+            .ok_or(format!("Price stream state not found for {stream_tuple:?}"))?;
+        price_state.new_market_data(market);
+
+        Ok(())
+    }
+
     // --- Helpers --- //
 
-    /// Get the latest price for the given exchange and token pair.
+    /// Get the latest valid price reported for the given pair on each
+    /// exchange, along with its timestamp
     ///
-    /// If the pair is eligible, we convert the price through the default stable
-    /// quote for the exchange.
+    /// Exchanges whose price is backed by a stale stream, or is older than the
+    /// maximum report age, are skipped. A dead feed's last price would
+    /// otherwise skew the median the deviation breaker compares against
+    fn latest_exchange_prices(
+        &self,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Vec<(ExchangePrice, u64)> {
+        self.get_supported_exchanges(base_token, quote_token)
+            .into_iter()
+            .filter(|exchange| *exchange != Exchange::Renegade)
+            .filter(|exchange| {
+                self.exchange_price_staleness(*exchange, base_token, quote_token) != Some(true)
+            })
+            .filter_map(|exchange| {
+                self.get_latest_exchange_price(exchange, base_token, quote_token)
+            })
+            .filter(|(price, ts)| !price.price.is_zero() && !ts_too_stale(*ts).0)
+            .collect_vec()
+    }
+
+    /// Get the latest price for the given exchange and token pair.
     fn get_latest_price(
         &self,
         exchange: Exchange,
         base_token: &Token,
         quote_token: &Token,
     ) -> Option<(Price, u64)> {
-        if eligible_for_stable_quote_conversion(base_token, quote_token, &exchange) {
+        let (price, ts) = self.get_latest_exchange_price(exchange, base_token, quote_token)?;
+        Some((price.price, ts))
+    }
+
+    /// Get the latest price for the given exchange and token pair, along with
+    /// the market data reported with it
+    ///
+    /// If the pair is synthetic, we derive the exchange's price through the
+    /// pair's intermediate token. If the pair is eligible, we convert the price
+    /// through the default stable quote for the exchange.
+    fn get_latest_exchange_price(
+        &self,
+        exchange: Exchange,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Option<(ExchangePrice, u64)> {
+        let intermediate = self.synthetic_intermediate(base_token, quote_token);
+        if let Some(intermediate) = intermediate.filter(|_| exchange != Exchange::Renegade) {
+            self.convert_through_intermediate(base_token, quote_token, intermediate, exchange)
+        } else if eligible_for_stable_quote_conversion(base_token, quote_token, &exchange) {
             self.convert_through_default_stable(base_token, quote_token, exchange)
         } else {
             let stream_tuple = (exchange, base_token.clone(), quote_token.clone());
-            self.states().get(&stream_tuple).map(|state| state.read_price())
+            let states = self.states();
+            let state = states.get(&stream_tuple)?;
+            let (price, ts) = state.read_price();
+            let market = state.read_market_data();
+            Some((ExchangePrice { exchange, price, market }, ts))
+        }
+    }
+
+    /// Whether any stream backing the given exchange's price for the pair has
+    /// been marked stale, or `None` if the exchange does not stream the pair
+    fn exchange_price_staleness(
+        &self,
+        exchange: Exchange,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Option<bool> {
+        let streams = self.backing_streams(exchange, base_token, quote_token);
+        let states = self.states();
+        let mut stale = false;
+        for stream in streams {
+            stale |= states.get(&stream)?.is_stale();
+        }
+
+        Some(stale)
+    }
+
+    /// Get the streams from which the given exchange's price for the pair is
+    /// read, following the same routes as `get_latest_exchange_price`
+    fn backing_streams(
+        &self,
+        exchange: Exchange,
+        base_token: &Token,
+        quote_token: &Token,
+    ) -> Vec<StreamTuple> {
+        let intermediate = self.synthetic_intermediate(base_token, quote_token);
+        if let Some(intermediate) = intermediate.filter(|_| exchange != Exchange::Renegade) {
+            let mut streams = vec![(exchange, base_token.clone(), intermediate.clone())];
+            streams.extend(self.backing_streams(exchange, intermediate, quote_token));
+            streams
+        } else if eligible_for_stable_quote_conversion(base_token, quote_token, &exchange) {
+            let default_stable = default_exchange_stable(&exchange);
+            vec![
+                (exchange, base_token.clone(), default_stable.clone()),
+                (exchange, quote_token.clone(), default_stable),
+            ]
+        } else {
+            vec![(exchange, base_token.clone(), quote_token.clone())]
         }
     }
 
@@ -275,24 +862,380 @@ impl PriceStreamStates {
         base_token: &Token,
         quote_token: &Token,
         exchange: Exchange,
-    ) -> Option<(Price, u64)> {
+    ) -> Option<(ExchangePrice, u64)> {
         let default_stable = default_exchange_stable(&exchange);
+        let states = self.states();
 
         // Get the base / default stable price
         let default_tuple = (exchange, base_token.clone(), default_stable.clone());
-        let (base_price, base_ts) = self.states().get(&default_tuple)?.read_price();
+        let base_state = states.get(&default_tuple)?;
+        let (base_price, base_ts) = base_state.read_price();
 
         // Get the quote / default stable price
         let conversion_tuple = (exchange, quote_token.clone(), default_stable.clone());
-        let (quote_price, quote_ts) = self.states().get(&conversion_tuple)?.read_price();
+        let (quote_price, quote_ts) = states.get(&conversion_tuple)?.read_price();
 
         // The converted price = (base / default stable) / (quote / default stable)
-        let price = base_price / quote_price;
+        let price = base_price.checked_div(quote_price)?;
+        let market = match quote_price.inverse() {
+            Some(default_price) => base_state.read_market_data().convert_quote(default_price),
+            None => MarketData::default(),
+        };
 
         // We take the minimum of the two timestamps, so we err on the side of safety
         // and call a price stale if one of the two price streams is stale
         let ts = base_ts.min(quote_ts);
 
-        Some((price, ts))
+        Some((ExchangePrice { exchange, price, market }, ts))
+    }
+
+    /// Derives the exchange's price for a synthetic pair from the exchange's
+    /// base / intermediate and intermediate / quote prices
+    ///
+    /// The intermediate / quote price is converted through the exchange's
+    /// default stable where needed, e.g. ETH/USDT on an exchange that quotes
+    /// in USDT
+    fn convert_through_intermediate(
+        &self,
+        base_token: &Token,
+        quote_token: &Token,
+        intermediate: &Token,
+        exchange: Exchange,
+    ) -> Option<(ExchangePrice, u64)> {
+        // Get the base / intermediate price on the exchange
+        let leg_tuple = (exchange, base_token.clone(), intermediate.clone());
+        let (leg_price, leg_market, leg_ts) = {
+            let states = self.states();
+            let leg_state = states.get(&leg_tuple)?;
+            let (price, ts) = leg_state.read_price();
+            (price, leg_state.read_market_data(), ts)
+        };
+
+        // Get the exchange's own intermediate / quote price, the intermediate is
+        // never itself synthetic
+        let (cross_price, cross_ts) = self.get_latest_price(exchange, intermediate, quote_token)?;
+
+        // As with the stable conversion, the derived price is only as fresh as
+        // the stalest of its legs
+        let price = leg_price.checked_mul(cross_price)?;
+        let market = leg_market.convert_quote(cross_price);
+        let ts = leg_ts.min(cross_ts);
+
+        Some((ExchangePrice { exchange, price, market }, ts))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use types_core::{
+        Chain, Exchange, ExchangeConnectionState, Price, PriceReporterState, Token, USD_TICKER,
+        USDC_TICKER, USDT_TICKER, set_default_chain, write_exchange_support, write_token_remaps,
+    };
+    use util::get_current_time_millis;
+
+    use super::{AtomicPriceStreamState, PriceStreamStates};
+    use crate::aggregation::AggregationStrategies;
+
+    /// The tickers of the tokens under test
+    const TICKERS: &[&str] = &[USDC_TICKER, USDT_TICKER, USD_TICKER, "WETH", "LDO"];
+
+    /// Setup the token remap and list every token on Binance and OKX
+    fn setup_tokens() {
+        let mut remaps = write_token_remaps();
+        let token_map = remaps.entry(Chain::ArbitrumOne).or_default();
+        for (i, &ticker) in TICKERS.iter().enumerate() {
+            token_map.insert(format!("{i:x}"), ticker.to_string());
+        }
+        drop(remaps);
+        set_default_chain(Chain::ArbitrumOne);
+
+        let mut support = write_exchange_support();
+        for &ticker in TICKERS {
+            let listing = HashMap::from([
+                (Exchange::Binance, ticker.to_string()),
+                (Exchange::Okx, ticker.to_string()),
+            ]);
+            support.insert(ticker.to_string(), listing);
+        }
+    }
+
+    /// Build price states for LDO routed through WETH, with Binance quoting in
+    /// USDT
+    fn synthetic_states() -> PriceStreamStates {
+        let (ldo, weth) = (Token::from_ticker("LDO"), Token::from_ticker("WETH"));
+        let (usdc, usdt) = (Token::usdc(), Token::usdt());
+        let streams = vec![
+            (Exchange::Binance, ldo.clone(), weth.clone()),
+            (Exchange::Binance, weth.clone(), usdt.clone()),
+            (Exchange::Binance, usdc.clone(), usdt),
+            (Exchange::Renegade, weth.clone(), usdc.clone()),
+            (Exchange::Renegade, ldo.clone(), usdc),
+        ];
+
+        let routes = HashMap::from([(ldo, weth)]);
+        PriceStreamStates::new(streams, vec![], AggregationStrategies::default(), 0., routes)
+    }
+
+    /// Record a price on a stream
+    fn set_price(states: &PriceStreamStates, stream: (Exchange, &str, &str), price: f64, ts: u64) {
+        let (exchange, base, quote) = stream;
+        let price = Price::from_f64_round_down(price).unwrap();
+        states
+            .new_price(exchange, Token::from_ticker(base), Token::from_ticker(quote), price, ts)
+            .unwrap();
+    }
+
+    /// Tests that an exchange's synthetic price is derived through the
+    /// exchange's own USDT quoted market, and the canonical price through the
+    /// canonical intermediate price, each stamped with their stalest leg
+    #[test]
+    fn test_synthetic_prices() {
+        setup_tokens();
+        let states = synthetic_states();
+        let (ldo, usdc) = (Token::from_ticker("LDO"), Token::usdc());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Binance, "LDO", "WETH"), 0.001, now - 10);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now);
+        set_price(&states, (Exchange::Binance, USDC_TICKER, USDT_TICKER), 1., now);
+        set_price(&states, (Exchange::Renegade, "WETH", USDC_TICKER), 2_100., now - 20);
+
+        let (price, ts) = states.get_latest_price(Exchange::Binance, &ldo, &usdc).unwrap();
+        assert!((price.to_f64() - 2.).abs() < 1e-9);
+        assert_eq!(ts, now - 10);
+
+        let (price, ts) = states.derive_synthetic_price(&ldo, &usdc).unwrap();
+        assert!((price.to_f64() - 2.1).abs() < 1e-9);
+        assert_eq!(ts, now - 20);
+
+        // The intermediate itself is not routed
+        assert!(states.derive_synthetic_price(&ldo, &Token::from_ticker("WETH")).is_none());
+    }
+
+    /// Tests that a synthetic pair's canonical price is checked for deviation
+    /// against the exchanges' independently derived prices
+    #[test]
+    fn test_synthetic_price_deviation() {
+        setup_tokens();
+        let states = synthetic_states();
+        let (ldo, usdc) = (Token::from_ticker("LDO"), Token::usdc());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Binance, "LDO", "WETH"), 0.001, now);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now);
+        set_price(&states, (Exchange::Binance, USDC_TICKER, USDT_TICKER), 1., now);
+
+        // A canonical intermediate price in line with the exchange
+        set_price(&states, (Exchange::Renegade, "WETH", USDC_TICKER), 2_000., now);
+        let (price, ts) = states.derive_synthetic_price(&ldo, &usdc).unwrap();
+        states.new_price(Exchange::Renegade, ldo.clone(), usdc.clone(), price, ts).unwrap();
+        assert!(matches!(states.get_state(&ldo, &usdc), PriceReporterState::Nominal(_)));
+
+        // A canonical intermediate price deviating from the exchange
+        set_price(&states, (Exchange::Renegade, "WETH", USDC_TICKER), 2_100., now);
+        let (price, ts) = states.derive_synthetic_price(&ldo, &usdc).unwrap();
+        states.new_price(Exchange::Renegade, ldo.clone(), usdc.clone(), price, ts).unwrap();
+        let state = states.get_state(&ldo, &usdc);
+        assert!(matches!(state, PriceReporterState::TooMuchDeviation(..)));
+    }
+
+    /// Build price states for WETH / USDT on Binance and OKX, with a
+    /// canonical stream
+    fn fallback_states() -> PriceStreamStates {
+        let (weth, usdt) = (Token::from_ticker("WETH"), Token::usdt());
+        let streams = vec![
+            (Exchange::Binance, weth.clone(), usdt.clone()),
+            (Exchange::Okx, weth.clone(), usdt.clone()),
+            (Exchange::Renegade, weth, usdt),
+        ];
+
+        let strategies = AggregationStrategies::default();
+        PriceStreamStates::new(streams, vec![], strategies, 0., HashMap::new())
+    }
+
+    /// Tests that a fallback canonical price is derived from the fresh
+    /// exchange prices and stamped with the oldest of them
+    #[test]
+    fn test_fallback_canonical_price() {
+        setup_tokens();
+        let states = fallback_states();
+        let (weth, usdt) = (Token::from_ticker("WETH"), Token::usdt());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Renegade, "WETH", USDT_TICKER), 1_900., now - 1_000);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now - 10);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_020., now - 5);
+
+        let price = states.new_fallback_canonical_price(&weth, &usdt, now - 100).unwrap();
+        assert!(price.to_f64() >= 2_000. && price.to_f64() <= 2_020.);
+
+        let conn = states.get_connection_state(Exchange::Renegade, &weth, &usdt);
+        let ExchangeConnectionState::Degraded(report) = conn else {
+            panic!("expected a degraded canonical stream, got {conn}");
+        };
+        assert_eq!(report.price, price);
+        assert_eq!(report.local_timestamp, now - 10);
+    }
+
+    /// Tests that no fallback canonical price is derived from a single fresh
+    /// exchange price
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_fallback_canonical_price__too_few_sources() {
+        setup_tokens();
+        let states = fallback_states();
+        let (weth, usdt) = (Token::from_ticker("WETH"), Token::usdt());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Renegade, "WETH", USDT_TICKER), 1_900., now - 1_000);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now - 10);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_020., now - 500);
+
+        assert!(states.new_fallback_canonical_price(&weth, &usdt, now - 100).is_none());
+        let conn = states.get_connection_state(Exchange::Renegade, &weth, &usdt);
+        let ExchangeConnectionState::Nominal(report) = conn else {
+            panic!("expected a nominal canonical stream, got {conn}");
+        };
+        assert_eq!(report.local_timestamp, now - 1_000);
+    }
+
+    /// Tests that a fallback canonical price never replaces a more recent
+    /// canonical price from the external price reporter
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_fallback_canonical_price__keeps_newer_canonical() {
+        setup_tokens();
+        let states = fallback_states();
+        let (weth, usdt) = (Token::from_ticker("WETH"), Token::usdt());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Renegade, "WETH", USDT_TICKER), 1_900., now);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now - 10);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_020., now - 5);
+
+        assert!(states.new_fallback_canonical_price(&weth, &usdt, now - 100).is_none());
+        let conn = states.get_connection_state(Exchange::Renegade, &weth, &usdt);
+        assert!(matches!(conn, ExchangeConnectionState::Nominal(_)));
+    }
+
+    /// Tests that a stream is marked stale once its price is older than the
+    /// threshold, only once per price, and that its next price clears the mark
+    #[test]
+    fn test_mark_stale_streams() {
+        setup_tokens();
+        let states = fallback_states();
+        let now = get_current_time_millis();
+        let binance = (Exchange::Binance, Token::from_ticker("WETH"), Token::usdt());
+
+        set_price(&states, (Exchange::Renegade, "WETH", USDT_TICKER), 2_000., now);
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now - 10_000);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_000., now);
+
+        let marked = states.mark_stale_streams(5_000);
+        assert_eq!(marked.len(), 1);
+        assert_eq!(marked[0].0, binance);
+        assert!(marked[0].1 >= 10_000);
+        assert!(states.is_stale(&binance));
+        assert!(states.mark_stale_streams(5_000).is_empty());
+
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now);
+        assert!(!states.is_stale(&binance));
+    }
+
+    /// Tests that a stale mark is bound to the price it was made for, so a
+    /// price received after the check is not considered stale
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_mark_stale__bound_to_price() {
+        let state = AtomicPriceStreamState::new();
+        let now = get_current_time_millis();
+        let price = Price::from_f64_round_down(1.).unwrap();
+
+        state.new_price(price, now - 10_000);
+        assert!(state.mark_stale_if_older(5_000, now).is_some());
+        state.new_price(price, now);
+        assert!(!state.is_stale());
+
+        // Re-checking the stream with the age of the old price does not mark
+        // the new one
+        assert!(state.mark_stale_if_older(5_000, now).is_none());
+        assert!(!state.is_stale());
+    }
+
+    /// Tests that a stream which never reports is marked stale once it has
+    /// awaited its first price past the threshold
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_mark_stale__never_reported() {
+        let state = AtomicPriceStreamState::new();
+        let now = get_current_time_millis();
+
+        assert!(state.mark_stale_if_older(5_000, now).is_none());
+        let age_ms = state.mark_stale_if_older(5_000, now + 10_000).unwrap();
+        assert!(age_ms >= 10_000);
+        assert!(state.is_stale());
+
+        state.new_price(Price::from_f64_round_down(1.).unwrap(), now);
+        assert!(!state.is_stale());
+    }
+
+    /// Tests that a pair is stale if its canonical stream is stale, or if every
+    /// exchange price backing it is stale
+    #[test]
+    fn test_is_pair_stale() {
+        setup_tokens();
+        let (weth, usdc, usdt) = (Token::from_ticker("WETH"), Token::usdc(), Token::usdt());
+        let streams = vec![
+            (Exchange::Binance, weth.clone(), usdt.clone()),
+            (Exchange::Binance, usdc.clone(), usdt.clone()),
+            (Exchange::Okx, weth.clone(), usdt.clone()),
+            (Exchange::Okx, usdc.clone(), usdt),
+            (Exchange::Renegade, weth.clone(), usdc.clone()),
+        ];
+        let strategies = AggregationStrategies::default();
+        let states = PriceStreamStates::new(streams, vec![], strategies, 0., HashMap::new());
+
+        let now = get_current_time_millis();
+        set_price(&states, (Exchange::Renegade, "WETH", USDC_TICKER), 2_000., now);
+        for exchange in [Exchange::Binance, Exchange::Okx] {
+            set_price(&states, (exchange, "WETH", USDT_TICKER), 2_000., now);
+            set_price(&states, (exchange, USDC_TICKER, USDT_TICKER), 1., now);
+        }
+
+        // A stale conversion leg makes only Binance's price stale
+        set_price(&states, (Exchange::Binance, USDC_TICKER, USDT_TICKER), 1., now - 10_000);
+        states.mark_stale_streams(5_000);
+        assert!(!states.is_pair_stale(&weth, &usdc));
+
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_000., now - 10_000);
+        states.mark_stale_streams(5_000);
+        assert!(states.is_pair_stale(&weth, &usdc));
+
+        // A stale canonical stream makes the pair stale on its own
+        set_price(&states, (Exchange::Binance, USDC_TICKER, USDT_TICKER), 1., now);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 2_000., now);
+        set_price(&states, (Exchange::Renegade, "WETH", USDC_TICKER), 2_000., now - 10_000);
+        states.mark_stale_streams(5_000);
+        assert!(states.is_pair_stale(&weth, &usdc));
+    }
+
+    /// Tests that an exchange price older than the maximum report age is left
+    /// out of aggregation, so a dead feed cannot skew the pair's median
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_aggregate_exchange_price__skips_too_stale() {
+        setup_tokens();
+        let states = fallback_states();
+        let (weth, usdt) = (Token::from_ticker("WETH"), Token::usdt());
+        let now = get_current_time_millis();
+
+        set_price(&states, (Exchange::Binance, "WETH", USDT_TICKER), 2_000., now - 10);
+        set_price(&states, (Exchange::Okx, "WETH", USDT_TICKER), 3_000., now - 60_000);
+
+        let (price, ts) = states.aggregate_exchange_price(&weth, &usdt).unwrap();
+        assert!((price.to_f64() - 2_000.).abs() < 1e-6);
+        assert_eq!(ts, now - 10);
     }
 }
@@ -1,12 +1,16 @@
 //! Utility functions for the price state module
 
 use itertools::Itertools;
-use statrs::statistics::{Data, Median};
 use types_core::{
     Exchange, Price, PriceReport, PriceReporterState, Token, USD_TICKER, default_exchange_stable,
 };
 use util::get_current_time_millis;
 
+use crate::{
+    aggregation::{ExchangePrice, PriceAggregator},
+    deviation::check_source_deviation,
+};
+
 /// If a pair has not reported an update within
 /// MAX_REPORT_AGE_MS (in milliseconds), we pause matches until we receive a
 /// more recent price. Note that this threshold cannot be too aggressive, as
@@ -19,6 +23,9 @@ const MAX_DEVIATION: f64 = 0.01;
 /// we have enough reports. This only applies to Named tokens, as Unnamed tokens
 /// simply use UniswapV3.
 const MIN_CONNECTIONS: usize = 1;
+/// The number of reporting exchanges at or above which the exchange count no
+/// longer limits the confidence in a price
+const FULL_CONFIDENCE_CONNECTIONS: usize = 3;
 
 // --------------------
 // | Exchange Support |
@@ -65,25 +72,31 @@ pub fn eligible_for_stable_quote_conversion(
 // ---------------------
 
 /// Computes the state of the price reporter for the given token pair,
-/// checking against the provided exchange prices.
+/// checking against the provided exchange prices as aggregated by the given
+/// aggregator.
+///
+/// Exchange prices deviating from their median by more than
+/// `max_source_deviation` are excluded from the aggregate
 pub fn compute_price_reporter_state(
     base_token: &Token,
     quote_token: &Token,
-    price: f64,
+    price: Price,
     local_timestamp: u64,
-    exchange_prices: &[(Exchange, (Price, u64))],
+    exchange_prices: &[(ExchangePrice, u64)],
+    aggregator: &dyn PriceAggregator,
+    max_source_deviation: f64,
 ) -> PriceReporterState {
-    // Fail closed on non-finite or non-positive served prices: NaN, +Inf, -Inf,
-    // 0, and negatives. The pre-existing check only caught literal 0.0, but a
-    // corrupted upstream feed could produce any of these. See incident
-    // 2026-05-08 (cbBTC).
-    if !price.is_finite() || price <= 0.0 {
+    // Fail closed on a zero served price. Non-finite and negative prices are
+    // rejected where prices are parsed, as a corrupted upstream feed could
+    // produce any of these. See incident 2026-05-08 (cbBTC).
+    if price.is_zero() {
         return PriceReporterState::NotEnoughDataReported(0);
     }
 
     let base_token = base_token.clone();
     let quote_token = quote_token.clone();
-    let price_report = PriceReport { base_token, quote_token, price, local_timestamp };
+    let mut price_report =
+        PriceReport { base_token, quote_token, price, local_timestamp, confidence: 0. };
 
     // Check that the most recent timestamp is not too old
     let (too_stale, time_diff) = ts_too_stale(local_timestamp);
@@ -93,24 +106,30 @@ pub fn compute_price_reporter_state(
 
     // Collect all non-zero, non-stale prices from other exchanges and ensure that
     // we have enough.
-    let non_zero_prices: Vec<Price> = exchange_prices
+    let non_zero_prices: Vec<ExchangePrice> = exchange_prices
         .iter()
-        .filter(|(_exchange, (price, ts))| {
-            *price != Price::default() && price.is_finite() && !ts_too_stale(*ts).0
-        })
-        .map(|(_, (price, _))| *price)
+        .filter(|(price, ts)| !price.price.is_zero() && !ts_too_stale(*ts).0)
+        .map(|(price, _)| *price)
         .collect();
 
-    // If we have enough data to create a median, check for deviation against it
+    // Exclude sources deviating too far from the others
+    let non_zero_prices = check_source_deviation(&non_zero_prices, max_source_deviation).included;
+
+    // If we have enough data to aggregate, check for deviation against the
+    // aggregate
     if non_zero_prices.len() < MIN_CONNECTIONS {
         return PriceReporterState::NotEnoughDataReported(non_zero_prices.len());
     }
 
-    // Compute the median price
-    let median_midpoint_price = Data::new(non_zero_prices.clone()).median();
+    // Aggregate the exchange prices into a reference price
+    let Some(reference_price) = aggregator.aggregate(&non_zero_prices) else {
+        return PriceReporterState::NotEnoughDataReported(non_zero_prices.len());
+    };
+    let prices = non_zero_prices.iter().map(|price| price.price).collect_vec();
+    price_report.confidence = compute_confidence(&prices, reference_price, time_diff);
 
     // Ensure that there is not too much deviation between the prices
-    let deviation = (price - median_midpoint_price).abs() / median_midpoint_price;
+    let deviation = price.relative_deviation(reference_price);
     if deviation > MAX_DEVIATION {
         return PriceReporterState::TooMuchDeviation(price_report, deviation);
     }
@@ -118,9 +137,93 @@ pub fn compute_price_reporter_state(
     PriceReporterState::Nominal(price_report)
 }
 
+/// Scores the confidence in a price between 0 and 1
+///
+/// The score is the product of three factors, each between 0 and 1:
+/// - coverage: the fraction of `FULL_CONFIDENCE_CONNECTIONS` exchanges
+///   reporting
+/// - agreement: falls linearly to zero as the spread between the highest and
+///   lowest exchange prices, relative to the reference price, reaches twice
+///   `MAX_DEVIATION`
+/// - freshness: falls linearly to zero as the age of the price reaches
+///   `MAX_REPORT_AGE_MS`
+fn compute_confidence(exchange_prices: &[Price], reference_price: Price, price_age_ms: u64) -> f64 {
+    let coverage = (exchange_prices.len() as f64 / FULL_CONFIDENCE_CONNECTIONS as f64).min(1.);
+
+    let (Some(min), Some(max)) = (exchange_prices.iter().min(), exchange_prices.iter().max())
+    else {
+        return 0.;
+    };
+    let spread = (max.to_f64() - min.to_f64()) / reference_price.to_f64();
+    let agreement = 1. - (spread / (2. * MAX_DEVIATION)).min(1.);
+
+    let freshness = 1. - (price_age_ms as f64 / MAX_REPORT_AGE_MS as f64).min(1.);
+    coverage * agreement * freshness
+}
+
 /// Returns whether or not the provided timestamp is too stale,
 /// and the time difference between the current time and the provided timestamp
-fn ts_too_stale(ts: u64) -> (bool, u64) {
-    let time_diff = get_current_time_millis() - ts;
+pub(crate) fn ts_too_stale(ts: u64) -> (bool, u64) {
+    let time_diff = get_current_time_millis().saturating_sub(ts);
     (time_diff > MAX_REPORT_AGE_MS, time_diff)
 }
+
+#[cfg(test)]
+mod test {
+    use types_core::Price;
+
+    use super::{MAX_DEVIATION, MAX_REPORT_AGE_MS, compute_confidence};
+
+    /// The tolerance used when comparing confidence scores
+    const TOLERANCE: f64 = 1e-9;
+
+    /// Build prices from a list of floats
+    fn prices(vals: &[f64]) -> Vec<Price> {
+        vals.iter().map(|v| Price::from_f64_round_down(*v).unwrap()).collect()
+    }
+
+    /// Tests that three agreeing, fresh exchange prices score full confidence
+    #[test]
+    fn test_full_confidence() {
+        let reference = Price::from_f64_round_down(100.).unwrap();
+        let confidence = compute_confidence(&prices(&[100., 100., 100.]), reference, 0);
+        assert!((confidence - 1.).abs() < TOLERANCE);
+
+        // Exchanges beyond the third do not raise the score further
+        let confidence = compute_confidence(&prices(&[100.; 5]), reference, 0);
+        assert!((confidence - 1.).abs() < TOLERANCE);
+    }
+
+    /// Tests that each factor scales the confidence
+    #[test]
+    fn test_confidence_factors() {
+        let reference = Price::from_f64_round_down(100.).unwrap();
+
+        // Coverage: a single exchange scores a third
+        let confidence = compute_confidence(&prices(&[100.]), reference, 0);
+        assert!((confidence - 1. / 3.).abs() < TOLERANCE);
+
+        // Agreement: a spread of the maximum deviation halves the score
+        let spread = 100. * MAX_DEVIATION;
+        let confidence = compute_confidence(&prices(&[100., 100., 100. + spread]), reference, 0);
+        assert!((confidence - 0.5).abs() < 1e-6);
+
+        // Freshness: a price half as old as the staleness cutoff halves the score
+        let confidence =
+            compute_confidence(&prices(&[100., 100., 100.]), reference, MAX_REPORT_AGE_MS / 2);
+        assert!((confidence - 0.5).abs() < TOLERANCE);
+    }
+
+    /// Tests that the score falls to zero on a wide spread, a stale price, or
+    /// no exchange prices
+    #[test]
+    fn test_zero_confidence() {
+        let reference = Price::from_f64_round_down(100.).unwrap();
+        let wide_spread = prices(&[90., 100., 110.]);
+        assert_eq!(compute_confidence(&wide_spread, reference, 0), 0.);
+
+        let agreeing = prices(&[100., 100., 100.]);
+        assert_eq!(compute_confidence(&agreeing, reference, MAX_REPORT_AGE_MS), 0.);
+        assert_eq!(compute_confidence(&[], reference, 0), 0.);
+    }
+}
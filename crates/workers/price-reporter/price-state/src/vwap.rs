@@ -0,0 +1,174 @@
+//! Rolling volume weighted average prices
+//!
+//! Exchange streams report midpoints and a trailing 24 hour volume rather
+//! than individual trades. Each sample therefore weights an exchange's price
+//! by the volume it traded since the previous sample, estimated from the
+//! change in its trailing volume. Samples are kept for the longest window,
+//! and averaged over the trailing window requested.
+
+use std::collections::{HashMap, VecDeque};
+
+use types_core::{Exchange, TimestampedPrice, VwapWindow, WeightedPriceSum};
+
+use crate::aggregation::ExchangePrice;
+
+/// The length of the trailing window over which exchanges report their
+/// traded volume, in milliseconds
+const REPORTED_VOLUME_WINDOW_MS: u64 = 24 * 60 * 60 * 1000; // 24 hours
+
+/// A volume weighted sample of a pair's exchange prices
+#[derive(Clone, Copy, Debug)]
+struct VwapSample {
+    /// The time at which the sample was taken, in milliseconds since the epoch
+    timestamp: u64,
+    /// The exchange prices, each weighted by the volume its exchange traded
+    /// since the previous sample
+    prices: WeightedPriceSum,
+}
+
+/// An exchange's trailing volume as of a sample
+#[derive(Clone, Copy, Debug)]
+struct ReportedVolume {
+    /// The time of the sample, in milliseconds since the epoch
+    timestamp: u64,
+    /// The exchange's trailing 24 hour volume, in units of the quote token
+    volume: f64,
+}
+
+/// The rolling samples from which a pair's VWAP is computed
+#[derive(Debug, Default)]
+pub struct RollingVwap {
+    /// The samples within the longest window, oldest first
+    samples: VecDeque<VwapSample>,
+    /// The trailing volume each exchange reported as of its last sample
+    last_volumes: HashMap<Exchange, ReportedVolume>,
+}
+
+impl RollingVwap {
+    /// Record a sample of the given exchange prices
+    ///
+    /// Prices from exchanges that did not report their volume are ignored, as
+    /// is an exchange's first reported volume, which only sets the baseline
+    /// for its next sample
+    pub fn record(&mut self, timestamp: u64, prices: &[ExchangePrice]) {
+        let mut sum = WeightedPriceSum::default();
+        for price in prices {
+            let Some(volume) = price.market.volume.filter(|v| v.is_finite() && *v >= 0.) else {
+                continue;
+            };
+
+            let current = ReportedVolume { timestamp, volume };
+            let Some(last) = self.last_volumes.insert(price.exchange, current) else { continue };
+            if let Some(weight) = traded_volume(last, current) {
+                sum.add(price.price, weight);
+            }
+        }
+
+        if sum.weight() > 0 {
+            self.samples.push_back(VwapSample { timestamp, prices: sum });
+        }
+        self.prune(timestamp);
+    }
+
+    /// Compute the VWAP over the given trailing window
+    ///
+    /// The timestamp of the result is that of the latest sample. Returns
+    /// `None` if no sample falls within the window
+    pub fn vwap(&self, window: VwapWindow, now: u64) -> Option<TimestampedPrice> {
+        let start = now.saturating_sub(window.duration_ms());
+        let in_window = self.samples.iter().rev().take_while(|sample| sample.timestamp >= start);
+
+        let mut latest = None;
+        let mut sum = WeightedPriceSum::default();
+        for sample in in_window {
+            latest.get_or_insert(sample.timestamp);
+            sum.merge(&sample.prices);
+        }
+
+        let timestamp = latest?;
+        Some(TimestampedPrice { price: sum.mean()?, timestamp })
+    }
+
+    /// Drop the samples older than the longest window
+    fn prune(&mut self, now: u64) {
+        let longest = VwapWindow::ALL.iter().map(VwapWindow::duration_ms).max().unwrap_or_default();
+        let start = now.saturating_sub(longest);
+        while self.samples.front().is_some_and(|sample| sample.timestamp < start) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Estimate the volume an exchange traded between two of its trailing volume
+/// reports, in whole units of the quote token
+///
+/// The trailing volume grows by the volume traded and shrinks by the volume
+/// which left its window. The latter is estimated by assuming the earlier
+/// report's volume was traded at a uniform rate. Returns `None` if no volume
+/// was traded
+fn traded_volume(last: ReportedVolume, current: ReportedVolume) -> Option<u128> {
+    let elapsed_ms =
+        current.timestamp.saturating_sub(last.timestamp).min(REPORTED_VOLUME_WINDOW_MS);
+    let expired = last.volume * (elapsed_ms as f64 / REPORTED_VOLUME_WINDOW_MS as f64);
+    let traded = (current.volume - last.volume + expired).round();
+    (traded >= 1.).then_some(traded as u128)
+}
+
+#[cfg(test)]
+mod test {
+    use types_core::{Exchange, Price, VwapWindow};
+
+    use super::{REPORTED_VOLUME_WINDOW_MS, ReportedVolume, RollingVwap, traded_volume};
+    use crate::aggregation::{ExchangePrice, MarketData};
+
+    /// Build an exchange price reporting the given trailing volume
+    fn exchange_price(exchange: Exchange, price: f64, volume: Option<f64>) -> ExchangePrice {
+        let price = Price::from_f64_round_down(price).unwrap();
+        ExchangePrice { exchange, price, market: MarketData { volume, ..Default::default() } }
+    }
+
+    /// Tests estimating the volume traded between two reports
+    #[test]
+    fn test_traded_volume() {
+        let report = |timestamp, volume| ReportedVolume { timestamp, volume };
+
+        // Growth with no time elapsed is all traded volume
+        assert_eq!(traded_volume(report(0, 1_000.), report(0, 1_500.)), Some(500));
+        // An unchanged trailing volume over a tenth of the window replaced the
+        // tenth that expired
+        let tenth = REPORTED_VOLUME_WINDOW_MS / 10;
+        assert_eq!(traded_volume(report(0, 1_000.), report(tenth, 1_000.)), Some(100));
+        // A shrinking volume with no time elapsed traded nothing
+        assert_eq!(traded_volume(report(0, 1_000.), report(0, 900.)), None);
+        // After a full window, the trailing volume was all traded since
+        let later = 2 * REPORTED_VOLUME_WINDOW_MS;
+        assert_eq!(traded_volume(report(0, 1_000.), report(later, 300.)), Some(300));
+    }
+
+    /// Tests that the VWAP weights each price by the volume traded since the
+    /// previous sample, rather than by the trailing volume
+    #[test]
+    fn test_rolling_vwap() {
+        let mut vwap = RollingVwap::default();
+
+        // The first sample only sets each exchange's baseline
+        let baseline = [
+            exchange_price(Exchange::Binance, 100., Some(1_000_000.)),
+            exchange_price(Exchange::Okx, 110., Some(1_000.)),
+        ];
+        vwap.record(0, &baseline);
+        assert!(vwap.vwap(VwapWindow::OneMinute, 0).is_none());
+
+        // Okx traded three times Binance's volume despite its lower trailing
+        // volume, and an exchange without a volume is ignored
+        let traded = [
+            exchange_price(Exchange::Binance, 100., Some(1_000_100.)),
+            exchange_price(Exchange::Okx, 110., Some(1_300.)),
+            exchange_price(Exchange::Kraken, 1_000., None),
+        ];
+        vwap.record(1, &traded);
+        let res = vwap.vwap(VwapWindow::OneMinute, 1).unwrap();
+        assert_eq!(res.timestamp, 1);
+        assert!((res.price.to_f64() - 107.5).abs() < 1e-6);
+    }
+}
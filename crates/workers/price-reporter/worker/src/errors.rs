@@ -1,4 +1,4 @@
-//! Defines all Error types, both for individual ExchangeConnections and the
+//! Defines all Error types, both for individual exchange connections and the
 //! PriceReporter itself.
 use std::error::Error;
 use std::fmt::{self, Display};
@@ -6,9 +6,9 @@ use std::fmt::{self, Display};
 use thiserror::Error;
 use types_core::{Exchange, Token};
 
-/// The core error type used by the ExchangeConnection. All thrown errors are
-/// handled by the PriceReporter, either for restarts or panics upon too many
-/// consecutive errors.
+/// The core error type used by exchange connections. All thrown errors are
+/// handled by the PriceReporter, which re-establishes the connection or
+/// falls back to polling exchange REST tickers.
 #[derive(Clone, Debug, Error)]
 pub enum ExchangeConnectionError {
     /// A websocket remote connection hangup.
@@ -23,33 +23,48 @@ pub enum ExchangeConnectionError {
     /// Could not parse a remote server message.
     #[error("could not parse remote server message: {0}")]
     InvalidMessage(String),
-    /// The maximum retry count was exceeded while trying to re-establish
-    /// an exchange connection
-    #[error(
-        "maximum retry count exceeded while trying to re-establish an exchange connection to {0}"
-    )]
-    MaxRetries(Exchange),
     /// The given pair is not supported by the exchange
     #[error("the given pair ({0}, {1}) is not supported by the exchange ({2})")]
     UnsupportedPair(Token, Token, Exchange),
+    /// Error reading an on-chain price feed
+    #[error("error reading an on-chain price feed: {0}")]
+    OnchainRead(String),
+    /// Error requesting an exchange's REST ticker
+    #[error("error requesting an exchange's REST ticker: {0}")]
+    RestRequest(String),
     /// Error sending on the `write` end of the websocket
     #[error("error sending on the `write` end of the websocket: {0}")]
     SendError(String),
     /// Error saving the state of a price stream
     #[error("error saving the state of a price stream: {0}")]
     SaveState(String),
-    /// Tried to initialize an ExchangeConnection that was already initialized
-    #[error("tried to initialize an ExchangeConnection that was already initialized: {0}")]
-    AlreadyInitialized(Exchange, Token, Token),
 }
 
 impl ExchangeConnectionError {
+    /// Create a cryptographic error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn crypto<T: ToString>(message: T) -> Self {
+        Self::Crypto(message.to_string())
+    }
+
     /// Create an invalid message error
     #[allow(clippy::needless_pass_by_value)]
     pub fn invalid_message<T: ToString>(message: T) -> Self {
         Self::InvalidMessage(message.to_string())
     }
 
+    /// Create an on-chain read error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn onchain_read<T: ToString>(message: T) -> Self {
+        Self::OnchainRead(message.to_string())
+    }
+
+    /// Create a REST request error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn rest_request<T: ToString>(message: T) -> Self {
+        Self::RestRequest(message.to_string())
+    }
+
     /// Create a save state error
     #[allow(clippy::needless_pass_by_value)]
     pub fn save_state<T: ToString>(message: T) -> Self {
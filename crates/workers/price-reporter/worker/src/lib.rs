@@ -1,6 +1,7 @@
 //! The price reporter module manages all external price feeds, including
-//! PriceReporter spin-up and tear-down, websocket connections to all exchanges
-//! (both centralized and decentralized), and computing PriceReports.
+//! PriceReporter spin-up and tear-down, the async connections to the external
+//! price reporter, exchange REST tickers and on-chain feeds, and computing
+//! PriceReports.
 
 #![deny(unsafe_code)]
 #![deny(missing_docs)]
@@ -8,7 +9,6 @@
 #![allow(incomplete_features)]
 
 pub mod errors;
-pub mod exchange;
 pub mod logging;
 pub mod manager;
 #[cfg(feature = "mocks")]
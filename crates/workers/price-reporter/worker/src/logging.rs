@@ -14,10 +14,16 @@ pub enum Task {
     ExchangeConnection,
     /// Forwarding price messages over an established price stream.
     PriceStream,
+    /// Polling on-chain price feeds.
+    OnchainFeed,
     /// Fetching a single price for a pair.
     FetchPrice,
     /// Liveness checks against price feeds.
     Healthcheck,
+    /// Polling exchange REST tickers while the price stream is down.
+    RestFallback,
+    /// Deriving prices for pairs with no direct market.
+    SyntheticPrice,
 }
 
 impl LogTask for Task {
@@ -26,8 +32,11 @@ impl LogTask for Task {
             Task::ReporterLifecycle => "reporter-lifecycle",
             Task::ExchangeConnection => "exchange-connection",
             Task::PriceStream => "price-stream",
+            Task::OnchainFeed => "onchain-feed",
             Task::FetchPrice => "fetch-price",
             Task::Healthcheck => "healthcheck",
+            Task::RestFallback => "rest-fallback",
+            Task::SyntheticPrice => "synthetic-price",
         }
     }
 }
@@ -0,0 +1,242 @@
+//! The Chainlink price feed poller
+//!
+//! Chainlink aggregators are read directly from the chain through the darkpool
+//! client, rather than streamed from the external price reporter. Every feed
+//! quotes its asset in USD, so the price of a pair is the ratio of the base
+//! and quote feeds; the token mapping's Chainlink "ticker" for a token is the
+//! address of its feed.
+
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use alloy_primitives::Address;
+use constants::in_bootstrap_mode;
+use darkpool_client::{DarkpoolClient, client::chainlink::ChainlinkRound};
+use price_state::PriceStreamStates;
+use types_core::{Exchange, Price, Token};
+use types_runtime::CancelChannel;
+use util::{
+    concurrency::runtime::sleep_forever_async, get_current_time_millis, log_task, logging::Outcome,
+};
+
+use crate::{
+    errors::{ExchangeConnectionError, PriceReporterError},
+    logging::Task,
+    manager::{
+        metrics::{FeedSource, record_feed_age, record_feed_message},
+        utils::get_all_stream_tuples,
+    },
+    worker::PriceReporterConfig,
+};
+
+/// The interval at which the Chainlink feeds are polled
+const CHAINLINK_POLL_INTERVAL_MS: u64 = 5_000; // 5 seconds
+/// The maximum age of a round that is reported as a price
+///
+/// Feeds only publish a new round on a deviation or a heartbeat, the longest of
+/// which is a day, so a round older than this means the feed has stopped
+const MAX_ROUND_AGE_S: u64 = 90_000; // 25 hours
+
+/// A pair priced from a pair of Chainlink USD feeds
+#[derive(Clone, Debug)]
+struct ChainlinkPair {
+    /// The base token of the pair
+    base: Token,
+    /// The quote token of the pair
+    quote: Token,
+    /// The address of the base token's USD feed
+    base_feed: Address,
+    /// The address of the quote token's USD feed
+    quote_feed: Address,
+}
+
+impl ChainlinkPair {
+    /// Look up the feeds for the given pair in the token mapping
+    fn new(base: Token, quote: Token) -> Result<Self, ExchangeConnectionError> {
+        let base_feed = feed_address(&base)?;
+        let quote_feed = feed_address(&quote)?;
+        Ok(Self { base, quote, base_feed, quote_feed })
+    }
+}
+
+impl Display for ChainlinkPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+/// Polls the Chainlink feeds for every configured pair, saving each price
+/// into the price stream states
+pub(crate) struct ChainlinkPoller {
+    /// The client used to read the feeds
+    client: DarkpoolClient,
+    /// The pairs to poll
+    pairs: Vec<ChainlinkPair>,
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl ChainlinkPoller {
+    /// Create a poller for the Chainlink streams in the config
+    ///
+    /// Returns `None` if Chainlink is not configured or no pair has a feed
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Option<Self> {
+        let client = config.darkpool_client.clone()?;
+        let pairs: Vec<_> = get_all_stream_tuples(config)
+            .into_iter()
+            .filter(|(exchange, ..)| *exchange == Exchange::Chainlink)
+            .filter_map(|(_, base, quote)| match ChainlinkPair::new(base.clone(), quote.clone()) {
+                Ok(pair) => Some(pair),
+                Err(e) => {
+                    log_task!(Task::OnchainFeed, Outcome::Failed, subject = %format!("{base}/{quote}"), error = %e, "skipping Chainlink pair");
+                    None
+                },
+            })
+            .collect();
+
+        if pairs.is_empty() {
+            return None;
+        }
+
+        Some(Self { client, pairs, price_stream_states, cancel_channel })
+    }
+
+    /// The polling loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut interval = tokio::time::interval(Duration::from_millis(CHAINLINK_POLL_INTERVAL_MS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.poll_pairs().await,
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "ChainlinkPoller cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Poll the feeds for every pair, logging failures
+    ///
+    /// A failed pair keeps its last price, which ages out of the price state
+    /// like any other stream that stops reporting
+    async fn poll_pairs(&self) {
+        for pair in &self.pairs {
+            if let Err(e) = self.poll_pair(pair).await {
+                log_task!(Task::OnchainFeed, Outcome::Failed, subject = %pair, error = %e, "error polling Chainlink feeds");
+            }
+        }
+    }
+
+    /// Poll the feeds for a single pair and save the price
+    async fn poll_pair(&self, pair: &ChainlinkPair) -> Result<(), ExchangeConnectionError> {
+        let base_round = self.read_feed(pair.base_feed).await?;
+        let quote_round = self.read_feed(pair.quote_feed).await?;
+        let (price, ts) = pair_price(&base_round, &quote_round).ok_or_else(|| {
+            ExchangeConnectionError::onchain_read(format!("cannot price {pair} from its feeds"))
+        })?;
+
+        record_feed_message(Exchange::Chainlink, FeedSource::Onchain);
+        // A round is stamped when the feed updates it, not when it is read, so
+        // its timestamp gives the age of the price rather than the latency
+        record_feed_age(Exchange::Chainlink, FeedSource::Onchain, ts);
+
+        // The price is stamped with the time of its rounds rather than the
+        // poll, so that a feed which stops updating ages out of the price state
+        self.price_stream_states
+            .new_price(Exchange::Chainlink, pair.base.clone(), pair.quote.clone(), price, ts)
+            .map_err(ExchangeConnectionError::save_state)
+    }
+
+    /// Read the latest round of a feed, rejecting a round that is too old
+    async fn read_feed(&self, feed: Address) -> Result<ChainlinkRound, ExchangeConnectionError> {
+        let round = self
+            .client
+            .get_chainlink_round(feed)
+            .await
+            .map_err(ExchangeConnectionError::onchain_read)?;
+
+        check_round_age(feed, &round, get_current_time_millis() / 1000)?;
+        Ok(round)
+    }
+}
+
+/// Price a pair from the rounds of its base and quote feeds
+///
+/// Returns the price and its timestamp in milliseconds since the epoch; the
+/// pair's price is as old as the older of its two rounds
+fn pair_price(base_round: &ChainlinkRound, quote_round: &ChainlinkRound) -> Option<(Price, u64)> {
+    let price = base_round.price.checked_div(quote_round.price)?;
+    let updated_at_s = base_round.updated_at.min(quote_round.updated_at);
+    Some((price, updated_at_s * 1000))
+}
+
+/// Reject a round that is too old to be reported as a price
+fn check_round_age(
+    feed: Address,
+    round: &ChainlinkRound,
+    now_s: u64,
+) -> Result<(), ExchangeConnectionError> {
+    let age_s = now_s.saturating_sub(round.updated_at);
+    if age_s > MAX_ROUND_AGE_S {
+        let msg = format!("feed {feed} last updated {age_s}s ago");
+        return Err(ExchangeConnectionError::onchain_read(msg));
+    }
+
+    Ok(())
+}
+
+/// Parse the address of a token's Chainlink feed from the token mapping
+fn feed_address(token: &Token) -> Result<Address, ExchangeConnectionError> {
+    let feed = token
+        .get_exchange_ticker(Exchange::Chainlink)
+        .ok_or_else(|| ExchangeConnectionError::onchain_read(format!("no feed for {token}")))?;
+    Address::from_str(&feed).map_err(ExchangeConnectionError::onchain_read)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::Address;
+    use darkpool_client::client::chainlink::ChainlinkRound;
+    use types_core::Price;
+
+    use super::{MAX_ROUND_AGE_S, check_round_age, pair_price};
+
+    /// Build a round with the given price and update time
+    fn round(price: f64, updated_at: u64) -> ChainlinkRound {
+        ChainlinkRound { price: Price::from_f64_round_down(price).unwrap(), updated_at }
+    }
+
+    /// Tests that a pair is priced as the ratio of its feeds, stamped with the
+    /// time of the older round in milliseconds
+    #[test]
+    fn test_pair_price() {
+        let (price, ts) = pair_price(&round(3_000., 1_000), &round(0.5, 900)).unwrap();
+        assert_eq!(price.to_f64(), 6_000.);
+        assert_eq!(ts, 900_000);
+
+        let (_, ts) = pair_price(&round(3_000., 1_000), &round(1., 1_100)).unwrap();
+        assert_eq!(ts, 1_000_000);
+    }
+
+    /// Tests that rounds older than the maximum age are rejected
+    #[test]
+    fn test_round_age() {
+        let now_s = 1_000_000;
+        let fresh = round(1., now_s - MAX_ROUND_AGE_S);
+        let stale = round(1., now_s - MAX_ROUND_AGE_S - 1);
+
+        assert!(check_round_age(Address::ZERO, &fresh, now_s).is_ok());
+        assert!(check_round_age(Address::ZERO, &stale, now_s).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! The price deviation circuit breaker's alerts
+//!
+//! Exchange prices deviating too far from the median of their pair are
+//! excluded from aggregation as they are read. This task tracks which streams
+//! are excluded, and publishes each exclusion and recovery to the system bus.
+//! The API server forwards them on the admin price deviations websocket topic,
+//! alerting operators to a compromised or glitching feed.
+
+use std::{collections::HashMap, time::Duration};
+
+use constants::in_bootstrap_mode;
+use price_state::{PriceStreamStates, StreamTuple};
+use system_bus::{PRICE_DEVIATION_TOPIC, SystemBus, SystemBusMessage};
+use types_runtime::CancelChannel;
+use util::{concurrency::runtime::sleep_forever_async, log_task, logging::Outcome};
+
+use crate::{errors::PriceReporterError, logging::Task, worker::PriceReporterConfig};
+
+/// The interval at which exchange prices are checked for deviation
+const CHECK_INTERVAL_MS: u64 = 1_000; // 1 second
+
+/// Publishes the streams excluded by the deviation circuit breaker
+pub(crate) struct DeviationBreaker {
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The system bus on which exclusions are published
+    system_bus: SystemBus,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl DeviationBreaker {
+    /// Create a new breaker, returns `None` if the breaker is disabled
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Option<Self> {
+        if config.max_source_deviation == 0. {
+            return None;
+        }
+
+        Some(Self { price_stream_states, system_bus: config.system_bus.clone(), cancel_channel })
+    }
+
+    /// The breaker loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_millis(CHECK_INTERVAL_MS));
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut excluded = HashMap::new();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.check_streams(&mut excluded),
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "DeviationBreaker cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Publish the streams which were excluded or included again since the
+    /// last check
+    fn check_streams(&self, excluded: &mut HashMap<StreamTuple, f64>) {
+        let deviating: HashMap<_, _> =
+            self.price_stream_states.deviating_sources().into_iter().collect();
+
+        for (stream, deviation) in deviating.iter() {
+            if !excluded.contains_key(stream) {
+                let (exchange, base, quote) = stream;
+                log_task!(Task::PriceStream, Outcome::Partial, subject = %format!("{exchange}-{base}-{quote}"), deviation = deviation, "excluding deviating exchange price");
+                self.publish(stream, Some(*deviation));
+            }
+        }
+
+        for stream in excluded.keys().filter(|stream| !deviating.contains_key(*stream)) {
+            let (exchange, base, quote) = stream;
+            log_task!(Task::PriceStream, Outcome::Ok, subject = %format!("{exchange}-{base}-{quote}"), "exchange price within deviation limit again");
+            self.publish(stream, None);
+        }
+
+        *excluded = deviating;
+    }
+
+    /// Publish an exclusion or recovery for a stream
+    fn publish(&self, stream: &StreamTuple, deviation: Option<f64>) {
+        let (exchange, base, quote) = stream.clone();
+        self.system_bus.publish(
+            PRICE_DEVIATION_TOPIC.to_string(),
+            SystemBusMessage::PriceSourceDeviationUpdate { exchange, base, quote, deviation },
+        );
+    }
+}
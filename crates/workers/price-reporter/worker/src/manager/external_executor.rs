@@ -13,7 +13,7 @@ use futures::{
     SinkExt, StreamExt,
     stream::{SplitSink, SplitStream},
 };
-use price_state::PriceStreamStates;
+use price_state::{PriceStreamStates, aggregation::MarketData};
 use serde::{Deserialize, Serialize};
 use tokio::{
     net::TcpStream,
@@ -32,14 +32,24 @@ use util::{
 use crate::{
     errors::{ExchangeConnectionError, PriceReporterError},
     logging::Task,
-    manager::utils::get_all_stream_tuples,
+    manager::{
+        metrics::{
+            FeedSource, record_feed_latency, record_feed_message, record_feed_parse_failure,
+        },
+        reconnect::{ReconnectHandle, ReconnectSupervisor},
+        rest_fallback::{RestFallbackPoller, RestFallbackSwitch},
+        utils::get_all_stream_tuples,
+    },
     worker::PriceReporterConfig,
 };
 
-/// The number of milliseconds to wait in between retrying connections
-const CONN_RETRY_DELAY_MS: u64 = 2_000; // 2 seconds
+/// The name under which the connection to the external price reporter is
+/// supervised
+const PRICE_REPORTER_CONNECTION: &str = "external-price-reporter";
 /// The error message emitted when the price reporter sends a close frame
 const PRICE_REPORTER_CONN_CLOSED_ERR: &str = "received close frame";
+/// The interval at which streams are added for tokens listed at runtime
+const TOKEN_LISTING_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 /// A type alias for the write end of the websocket connection
 type WsWriteStream = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
@@ -54,8 +64,36 @@ type WsReadStream = SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
 pub struct PriceMessage {
     /// The topic for which the price update is being sent
     pub topic: String,
-    /// The new price
-    pub price: Price,
+    /// The new price, converted to a fixed-point `Price` on receipt
+    pub price: f64,
+    /// The time at which the exchange reported the price, in milliseconds
+    /// since the epoch, if the price reporter forwards it
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    /// The exchange's best bid, if the price reporter forwards its book
+    #[serde(default)]
+    pub best_bid: Option<f64>,
+    /// The exchange's best ask, if the price reporter forwards its book
+    #[serde(default)]
+    pub best_ask: Option<f64>,
+    /// The exchange's trailing 24 hour traded volume for the pair, in units of
+    /// the quote token, if the price reporter forwards it
+    #[serde(default)]
+    pub volume: Option<f64>,
+}
+
+impl PriceMessage {
+    /// The market data forwarded with the price
+    ///
+    /// Values that are not valid prices or volumes are dropped
+    fn market_data(&self) -> MarketData {
+        let to_price = |val: Option<f64>| val.and_then(Price::from_f64_round_down);
+        MarketData {
+            best_bid: to_price(self.best_bid).filter(|price| !price.is_zero()),
+            best_ask: to_price(self.best_ask).filter(|price| !price.is_zero()),
+            volume: self.volume.filter(|volume| volume.is_finite() && *volume > 0.),
+        }
+    }
 }
 
 /// The actual executor that handles incoming jobs, to subscribe to
@@ -66,6 +104,8 @@ pub struct ExternalPriceReporterExecutor {
     price_stream_states: PriceStreamStates,
     /// The manager config
     config: PriceReporterConfig,
+    /// The supervisor through which connections are re-established
+    reconnect_supervisor: ReconnectSupervisor,
     /// The channel on which the coordinator may cancel execution
     cancel_channel: DefaultOption<CancelChannel>,
 }
@@ -76,10 +116,12 @@ impl ExternalPriceReporterExecutor {
         config: PriceReporterConfig,
         cancel_channel: CancelChannel,
         price_stream_states: PriceStreamStates,
+        reconnect_supervisor: ReconnectSupervisor,
     ) -> Self {
         Self {
             price_stream_states,
             config,
+            reconnect_supervisor,
             cancel_channel: DefaultOption::new(Some(cancel_channel)),
         }
     }
@@ -93,16 +135,39 @@ impl ExternalPriceReporterExecutor {
 
         let mut cancel_channel = self.cancel_channel.take().unwrap();
 
+        // Poll exchange REST tickers if the connection to the external price
+        // reporter keeps failing
+        let fallback_switch = RestFallbackSwitch::default();
+        if let Some(poller) = RestFallbackPoller::new(
+            &self.config,
+            fallback_switch.clone(),
+            cancel_channel.clone(),
+            self.price_stream_states.clone(),
+        ) {
+            tokio::spawn(poller.execution_loop());
+        }
+
         // Spawn WS handler loop, which forwards reads/writes over channels
-        let (msg_out_tx, mut msg_in_rx) = self.spawn_ws_handler_loop();
+        let (msg_out_tx, mut msg_in_rx) = self.spawn_ws_handler_loop(fallback_switch);
 
         // Begin streaming prices for all the pairs which are supported by the config
         self.initialize_price_streams(&msg_out_tx)?;
+        let mut listing_interval = tokio::time::interval(TOKEN_LISTING_REFRESH_INTERVAL);
         loop {
             tokio::select! {
+                // Stream prices for any tokens listed since the last refresh
+                _ = listing_interval.tick() => {
+                    self.subscribe_new_listings(&msg_out_tx)?;
+                }
                 // Process price update from external price reporter
+                // A malformed message is dropped rather than stopping the stream
                 Some(price_message) = msg_in_rx.recv() => {
-                    self.handle_price_update(&price_message).map_err(PriceReporterError::ExchangeConnection)?;
+                    match self.handle_price_update(&price_message) {
+                        Err(ExchangeConnectionError::InvalidMessage(e)) => {
+                            log_task!(Task::PriceStream, Outcome::Failed, topic = %price_message.topic, error = %e, "dropping invalid price message");
+                        },
+                        res => res.map_err(PriceReporterError::ExchangeConnection)?,
+                    }
                 }
                 // Await cancellation by the coordinator
                 _ = cancel_channel.changed() => {
@@ -121,6 +186,38 @@ impl ExternalPriceReporterExecutor {
         // Subscribe to all the streams
         let all_stream_tuples = get_all_stream_tuples(&self.config);
         for (exchange, base, quote) in all_stream_tuples {
+            if self.config.is_priced_locally(exchange, &base) {
+                continue;
+            }
+
+            subscribe_to_price_stream(exchange, &base, &quote, msg_out_tx)?;
+        }
+        Ok(())
+    }
+
+    /// Add and subscribe to the streams required by tokens listed at runtime,
+    /// e.g. through the admin API
+    ///
+    /// Streams added here are re-subscribed on reconnect along with the rest
+    fn subscribe_new_listings(
+        &self,
+        msg_out_tx: &UnboundedSender<WebsocketMessage>,
+    ) -> Result<(), PriceReporterError> {
+        let all_stream_tuples = get_all_stream_tuples(&self.config);
+        let new_streams = self.price_stream_states.add_streams(all_stream_tuples);
+        for (exchange, base, quote) in new_streams {
+            if self.config.is_priced_locally(exchange, &base) {
+                continue;
+            }
+
+            log_task!(
+                Task::PriceStream,
+                Outcome::Started,
+                exchange = %exchange,
+                base = %base,
+                quote = %quote,
+                "streaming prices for newly listed token"
+            );
             subscribe_to_price_stream(exchange, &base, &quote, msg_out_tx)?;
         }
         Ok(())
@@ -130,6 +227,7 @@ impl ExternalPriceReporterExecutor {
     /// with the external price reporter
     fn spawn_ws_handler_loop(
         &self,
+        fallback_switch: RestFallbackSwitch,
     ) -> (UnboundedSender<WebsocketMessage>, UnboundedReceiver<PriceMessage>) {
         let price_reporter_url = self.config.price_reporter_url.clone().unwrap();
 
@@ -137,10 +235,16 @@ impl ExternalPriceReporterExecutor {
         let (msg_in_tx, msg_in_rx) = unbounded_channel();
 
         let subscription_states = self.price_stream_states.clone();
+        let health = ConnectionHealth {
+            fallback_switch,
+            reconnect: self.reconnect_supervisor.register(PRICE_REPORTER_CONNECTION),
+        };
 
         tokio::spawn(ws_handler_loop(
             price_reporter_url,
+            self.config.clone(),
             subscription_states,
+            health,
             msg_out_rx,
             msg_out_tx.clone(),
             msg_in_tx,
@@ -154,20 +258,35 @@ impl ExternalPriceReporterExecutor {
         &self,
         price_message: &PriceMessage,
     ) -> Result<(), ExchangeConnectionError> {
-        let price = price_message.price;
+        let (exchange, base_token, quote_token) = parse_topic(&price_message.topic)
+            .inspect_err(|_| record_feed_parse_failure(None, FeedSource::Stream))?;
+        let price = Price::from_f64_round_down(price_message.price).ok_or_else(|| {
+            record_feed_parse_failure(Some(exchange), FeedSource::Stream);
+            ExchangeConnectionError::invalid_message(format!(
+                "invalid price: {}",
+                price_message.price
+            ))
+        })?;
+
+        record_feed_message(exchange, FeedSource::Stream);
+        if let Some(exchange_ts) = price_message.timestamp {
+            record_feed_latency(exchange, FeedSource::Stream, exchange_ts);
+        }
 
-        // Do not update if the price is default, simply let the price age
-        if price == Price::default() {
+        // Do not update if the price is zero, simply let the price age
+        if price.is_zero() {
             return Ok(());
         }
 
-        let (exchange, base_token, quote_token) = parse_topic(&price_message.topic)
-            .map_err(err_str!(ExchangeConnectionError::InvalidMessage))?;
         let ts = get_current_time_millis();
 
-        // Save the price update for the pair on the given exchange
+        // Save the price update and its market data for the pair on the given
+        // exchange
         self.price_stream_states
             .new_price(exchange, base_token.clone(), quote_token.clone(), price, ts)
+            .map_err(ExchangeConnectionError::save_state)?;
+        self.price_stream_states
+            .new_market_data(exchange, base_token, quote_token, price_message.market_data())
             .map_err(ExchangeConnectionError::save_state)
     }
 }
@@ -176,6 +295,34 @@ impl ExternalPriceReporterExecutor {
 // | Helpers |
 // -----------
 
+/// The trackers of the health of the connection to the external price
+/// reporter
+struct ConnectionHealth {
+    /// The switch which polls exchange REST tickers in place of the stream
+    /// after repeated failures
+    fallback_switch: RestFallbackSwitch,
+    /// The connection's handle on the reconnection supervisor
+    reconnect: ReconnectHandle,
+}
+
+impl ConnectionHealth {
+    /// Record a failure of the connection
+    fn record_failure(&self) {
+        self.fallback_switch.record_failure();
+    }
+
+    /// Record that the connection delivered a message
+    fn record_success(&mut self) {
+        self.fallback_switch.record_success();
+        self.reconnect.mark_connected();
+    }
+
+    /// Wait until the next reconnect attempt may be made
+    async fn wait_to_reconnect(&mut self) {
+        self.reconnect.wait_to_reconnect().await;
+    }
+}
+
 /// Build a websocket connection to the given endpoint
 async fn ws_connect(
     url: Url,
@@ -201,14 +348,21 @@ async fn ws_connect(
 /// The main loop for the websocket handler, responsible for forwarding
 /// messages between the external price reporter and the executor, and
 /// re-establishing connections indefinitely in case of failure
+///
+/// Connection failures are recorded on the fallback switch, which polls
+/// exchange REST tickers in place of the stream after repeated failures, and
+/// reconnects are paced by the reconnection supervisor
 async fn ws_handler_loop(
     price_reporter_url: Url,
+    config: PriceReporterConfig,
     subscription_states: PriceStreamStates,
+    mut health: ConnectionHealth,
     mut msg_out_rx: UnboundedReceiver<WebsocketMessage>,
     msg_out_tx: UnboundedSender<WebsocketMessage>,
     msg_in_tx: UnboundedSender<PriceMessage>,
 ) -> Result<(), PriceReporterError> {
-    let (mut ws_write, mut ws_read) = connect_with_retries(price_reporter_url.clone()).await;
+    let (mut ws_write, mut ws_read) =
+        connect_with_retries(price_reporter_url.clone(), &mut health).await;
 
     // Outer loop handles retrying the websocket connection to the external price
     // reporter in case of some failure
@@ -220,6 +374,7 @@ async fn ws_handler_loop(
                 // to the executor
                 Some(res) = ws_read.next() => {
                     if let Err(e) = handle_incoming_ws_message(res, &msg_in_tx) {
+                        health.record_failure();
                         match e {
                             ExchangeConnectionError::ConnectionHangup(_) => {
                                 log_task!(Task::ExchangeConnection, Outcome::Retrying, "Connection to external price reporter lost, reconnecting...");
@@ -232,11 +387,14 @@ async fn ws_handler_loop(
                         // Fail over into connection retry loop
                         break;
                     }
+
+                    health.record_success();
                 }
 
                 // Forward outgoing messages from the executor to the external price reporter
                 Some(message) = msg_out_rx.recv() => {
                     if let Err(e) = ws_write.send(Message::Text(serde_json::to_string(&message).unwrap())).await {
+                        health.record_failure();
                         log_task!(Task::PriceStream, Outcome::Retrying, error = %e, "error sending message to external price reporter, retrying");
                         break;
                     }
@@ -248,9 +406,15 @@ async fn ws_handler_loop(
         // As such, we will have to re-subscribe to all the price streams that
         // were previously subscribed to on the re-established connection, so we
         // enqueue the re-subscription jobs here
-        (ws_write, ws_read) =
-            connect_and_resubscribe(price_reporter_url.clone(), &msg_out_tx, &subscription_states)
-                .await?;
+        health.wait_to_reconnect().await;
+        (ws_write, ws_read) = connect_and_resubscribe(
+            price_reporter_url.clone(),
+            &config,
+            &msg_out_tx,
+            &subscription_states,
+            &mut health,
+        )
+        .await?;
     }
 }
 
@@ -292,7 +456,7 @@ fn subscribe_to_price_stream(
 ) -> Result<(), ExchangeConnectionError> {
     // Send subscription messages to WS connection
     let topic = format_topic(&exchange, base_token, quote_token);
-    let message = WebsocketMessage::Subscribe { topic };
+    let message = WebsocketMessage::Subscribe { topic, cursor: None };
     msg_out_tx.send(message).map_err(ExchangeConnectionError::send_error)?;
 
     Ok(())
@@ -302,24 +466,30 @@ fn subscribe_to_price_stream(
 /// the pairs that were previously subscribed to
 async fn connect_and_resubscribe(
     price_reporter_url: Url,
+    config: &PriceReporterConfig,
     msg_out_tx: &UnboundedSender<WebsocketMessage>,
     subscription_states: &PriceStreamStates,
+    health: &mut ConnectionHealth,
 ) -> Result<(WsWriteStream, WsReadStream), PriceReporterError> {
-    let (ws_write, ws_read) = connect_with_retries(price_reporter_url).await;
-    resubscribe_to_prior_streams(msg_out_tx, subscription_states)
+    let (ws_write, ws_read) = connect_with_retries(price_reporter_url, health).await;
+    resubscribe_to_prior_streams(config, msg_out_tx, subscription_states)
         .map_err(PriceReporterError::ExchangeConnection)?;
     Ok((ws_write, ws_read))
 }
 
 /// Attempt to reconnect to the external price reporter,
 /// retrying indefinitely until a successful connection is made
-async fn connect_with_retries(price_reporter_url: Url) -> (WsWriteStream, WsReadStream) {
+async fn connect_with_retries(
+    price_reporter_url: Url,
+    health: &mut ConnectionHealth,
+) -> (WsWriteStream, WsReadStream) {
     loop {
         match ws_connect(price_reporter_url.clone()).await {
             Ok((write, read)) => return (write, read),
             Err(e) => {
+                health.record_failure();
                 log_task!(Task::ExchangeConnection, Outcome::Retrying, error = %e, "error connecting to external price reporter, retrying");
-                tokio::time::sleep(Duration::from_millis(CONN_RETRY_DELAY_MS)).await;
+                health.wait_to_reconnect().await;
             },
         }
     }
@@ -329,6 +499,7 @@ async fn connect_with_retries(price_reporter_url: Url) -> (WsWriteStream, WsRead
 /// pairs currently indexed in the subscription states, clearing the mapping in
 /// the process.
 fn resubscribe_to_prior_streams(
+    config: &PriceReporterConfig,
     msg_out_tx: &UnboundedSender<WebsocketMessage>,
     subscription_states: &PriceStreamStates,
 ) -> Result<(), ExchangeConnectionError> {
@@ -337,6 +508,10 @@ fn resubscribe_to_prior_streams(
 
     // Re-send subscription jobs for all the pairs
     for (exchange, base_token, quote_token) in streams {
+        if config.is_priced_locally(exchange, &base_token) {
+            continue;
+        }
+
         subscribe_to_price_stream(exchange, &base_token, &quote_token, msg_out_tx)?;
     }
 
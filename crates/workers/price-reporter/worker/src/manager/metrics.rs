@@ -0,0 +1,84 @@
+//! Helpers for tracking the quality of the price reporter's feeds
+//!
+//! Feed metrics are tagged by exchange and by the source the prices arrive
+//! through, so that operators can compare feeds, e.g. an exchange's streamed
+//! prices against its polled ones
+
+use renegade_metrics::labels::{
+    CONNECTION_METRIC_TAG, EXCHANGE_METRIC_TAG, PRICE_FEED_AGE_METRIC, PRICE_FEED_LATENCY_METRIC,
+    PRICE_FEED_MESSAGES_METRIC, PRICE_FEED_PARSE_FAILURES_METRIC, PRICE_FEED_RECONNECTS_METRIC,
+    PRICE_SOURCE_METRIC_TAG,
+};
+use types_core::Exchange;
+use util::get_current_time_millis;
+
+/// The exchange tag given to a message whose exchange cannot be parsed
+const UNKNOWN_EXCHANGE: &str = "unknown";
+
+/// The source through which a feed's prices arrive
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FeedSource {
+    /// Streamed from the external price reporter
+    Stream,
+    /// Polled from an exchange's REST ticker
+    Rest,
+    /// Read from an on-chain feed
+    Onchain,
+}
+
+impl FeedSource {
+    /// The metric tag value of the source
+    fn as_str(&self) -> &'static str {
+        match self {
+            FeedSource::Stream => "stream",
+            FeedSource::Rest => "rest",
+            FeedSource::Onchain => "onchain",
+        }
+    }
+}
+
+/// Build the tags of a feed metric
+fn feed_labels(exchange: Option<Exchange>, source: FeedSource) -> [(String, String); 2] {
+    let exchange = exchange.map_or(UNKNOWN_EXCHANGE.to_string(), |e| e.to_string());
+    [
+        (EXCHANGE_METRIC_TAG.to_string(), exchange),
+        (PRICE_SOURCE_METRIC_TAG.to_string(), source.as_str().to_string()),
+    ]
+}
+
+/// Record a price received on a feed
+pub(crate) fn record_feed_message(exchange: Exchange, source: FeedSource) {
+    let labels = feed_labels(Some(exchange), source);
+    metrics::counter!(PRICE_FEED_MESSAGES_METRIC, &labels).increment(1);
+}
+
+/// Record a message on a feed that could not be parsed into a price
+pub(crate) fn record_feed_parse_failure(exchange: Option<Exchange>, source: FeedSource) {
+    let labels = feed_labels(exchange, source);
+    metrics::counter!(PRICE_FEED_PARSE_FAILURES_METRIC, &labels).increment(1);
+}
+
+/// Record the delay between the feed timestamping a price, in milliseconds
+/// since the epoch, and its receipt
+pub(crate) fn record_feed_latency(exchange: Exchange, source: FeedSource, feed_ts_ms: u64) {
+    let latency_ms = get_current_time_millis().saturating_sub(feed_ts_ms);
+    let labels = feed_labels(Some(exchange), source);
+    metrics::histogram!(PRICE_FEED_LATENCY_METRIC, &labels).record(latency_ms as f64);
+}
+
+/// Record the age of the price a feed reports, given the time in milliseconds
+/// since the epoch at which the feed last updated it
+///
+/// Used in place of the latency for feeds that update on their own schedule
+/// rather than on every change in price, e.g. Chainlink's heartbeat
+pub(crate) fn record_feed_age(exchange: Exchange, source: FeedSource, updated_at_ms: u64) {
+    let age_ms = get_current_time_millis().saturating_sub(updated_at_ms);
+    let labels = feed_labels(Some(exchange), source);
+    metrics::histogram!(PRICE_FEED_AGE_METRIC, &labels).record(age_ms as f64);
+}
+
+/// Record a reconnect attempt by a price reporter connection
+pub(crate) fn record_reconnect(connection: &str) {
+    let labels = [(CONNECTION_METRIC_TAG.to_string(), connection.to_string())];
+    metrics::counter!(PRICE_FEED_RECONNECTS_METRIC, &labels).increment(1);
+}
@@ -1,5 +1,15 @@
 //! Defines the PriceReporterExecutor, the handler that is responsible
 //! for executing individual PriceReporterJobs.
 
+pub(crate) mod auth;
+pub(crate) mod chainlink;
+pub(crate) mod deviation_breaker;
 pub mod external_executor;
+pub(crate) mod metrics;
+pub(crate) mod reconnect;
+pub(crate) mod rest_fallback;
+pub(crate) mod staleness_watchdog;
+pub(crate) mod synthetic;
+pub(crate) mod uniswap_twap;
 pub(crate) mod utils;
+pub(crate) mod vwap_sampler;
@@ -0,0 +1,254 @@
+//! The reconnection supervisor
+//!
+//! Every connection the price reporter maintains reconnects through a shared
+//! supervisor rather than on its own fixed schedule. Each connection waits a
+//! jittered exponential backoff before an attempt, and the supervisor caps
+//! the number of attempts made across all connections within a window, so
+//! that a widespread outage does not become a storm of retries. Changes in a
+//! connection's state are published to the system bus.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rand::Rng;
+use system_bus::{
+    PRICE_REPORTER_CONNECTION_TOPIC, PriceReporterConnectionState, SystemBus, SystemBusMessage,
+};
+use util::{get_current_time_millis, log_task, logging::Outcome};
+
+use crate::{logging::Task, manager::metrics::record_reconnect};
+
+/// The backoff before the first reconnect attempt, in milliseconds
+const INITIAL_BACKOFF_MS: u64 = 500;
+/// The maximum backoff before a reconnect attempt, in milliseconds
+const BACKOFF_CEILING_MS: u64 = 60_000; // 1 minute
+/// The maximum number of reconnect attempts across all connections within
+/// the window
+const MAX_RECONNECTS_PER_WINDOW: usize = 20;
+/// The window over which reconnect attempts are capped, in milliseconds
+const RECONNECT_WINDOW_MS: u64 = 60_000; // 1 minute
+
+/// The supervisor shared by all connections of the price reporter
+#[derive(Clone)]
+pub(crate) struct ReconnectSupervisor {
+    /// The times of the reconnect attempts made within the window
+    recent_attempts: Arc<Mutex<VecDeque<u64>>>,
+    /// The system bus on which state changes are published
+    system_bus: SystemBus,
+}
+
+impl ReconnectSupervisor {
+    /// Create a new supervisor
+    pub(crate) fn new(system_bus: SystemBus) -> Self {
+        Self { recent_attempts: Default::default(), system_bus }
+    }
+
+    /// Register a connection with the supervisor
+    pub(crate) fn register<T: ToString>(&self, connection: T) -> ReconnectHandle {
+        ReconnectHandle {
+            connection: connection.to_string(),
+            attempts: 0,
+            connected: false,
+            supervisor: self.clone(),
+        }
+    }
+
+    /// Reserve a reconnect attempt within the window
+    ///
+    /// Returns the time to wait before trying again if the window is full
+    fn reserve_attempt(&self) -> Option<Duration> {
+        let now = get_current_time_millis();
+        let mut recent = self.recent_attempts.lock().expect("reconnect attempts lock poisoned");
+        while recent.front().is_some_and(|ts| now.saturating_sub(*ts) >= RECONNECT_WINDOW_MS) {
+            recent.pop_front();
+        }
+
+        if recent.len() < MAX_RECONNECTS_PER_WINDOW {
+            recent.push_back(now);
+            return None;
+        }
+
+        let oldest = recent.front().copied().unwrap_or(now);
+        let wait_ms = (oldest + RECONNECT_WINDOW_MS).saturating_sub(now).max(1);
+        Some(Duration::from_millis(wait_ms))
+    }
+
+    /// Publish a change in a connection's state
+    fn publish(&self, connection: &str, state: PriceReporterConnectionState) {
+        let connection = connection.to_string();
+        self.system_bus.publish(
+            PRICE_REPORTER_CONNECTION_TOPIC.to_string(),
+            SystemBusMessage::PriceReporterConnectionUpdate { connection, state },
+        );
+    }
+}
+
+/// A connection's handle on the supervisor
+pub(crate) struct ReconnectHandle {
+    /// The name of the connection
+    connection: String,
+    /// The number of consecutive reconnect attempts
+    attempts: u32,
+    /// Whether the connection is known to be healthy
+    connected: bool,
+    /// The supervisor
+    supervisor: ReconnectSupervisor,
+}
+
+impl ReconnectHandle {
+    /// Wait until the next reconnect attempt may be made
+    pub(crate) async fn wait_to_reconnect(&mut self) {
+        self.connected = false;
+        self.attempts = self.attempts.saturating_add(1);
+        record_reconnect(&self.connection);
+        let backoff = backoff_delay(self.attempts);
+        let backoff_ms = backoff.as_millis() as u64;
+        log_task!(
+            Task::ExchangeConnection,
+            Outcome::Retrying,
+            subject = %self.connection,
+            attempt = self.attempts,
+            backoff_ms = backoff_ms,
+            "reconnecting after backoff"
+        );
+        self.supervisor.publish(
+            &self.connection,
+            PriceReporterConnectionState::Reconnecting { attempt: self.attempts, backoff_ms },
+        );
+        tokio::time::sleep(backoff).await;
+
+        while let Some(wait) = self.supervisor.reserve_attempt() {
+            let wait_ms = wait.as_millis() as u64;
+            log_task!(
+                Task::ExchangeConnection,
+                Outcome::Retrying,
+                subject = %self.connection,
+                wait_ms = wait_ms,
+                "too many recent reconnects, holding back attempt"
+            );
+            self.supervisor
+                .publish(&self.connection, PriceReporterConnectionState::Throttled { wait_ms });
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Record that the connection delivered a message, resetting its backoff
+    pub(crate) fn mark_connected(&mut self) {
+        if self.connected {
+            return;
+        }
+
+        self.connected = true;
+        self.attempts = 0;
+        self.supervisor.publish(&self.connection, PriceReporterConnectionState::Connected);
+    }
+}
+
+/// The jittered backoff before the given reconnect attempt
+///
+/// The backoff doubles with each attempt up to the ceiling, and is drawn
+/// uniformly from its upper half so that connections failing together do not
+/// retry together
+fn backoff_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(u64::BITS - 1);
+    let backoff_ms = INITIAL_BACKOFF_MS.saturating_mul(1u64 << exponent).min(BACKOFF_CEILING_MS);
+    let jittered_ms = rand::thread_rng().gen_range(backoff_ms / 2..=backoff_ms);
+    Duration::from_millis(jittered_ms)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use system_bus::{
+        PRICE_REPORTER_CONNECTION_TOPIC, PriceReporterConnectionState, SystemBus, SystemBusMessage,
+        TopicReader,
+    };
+
+    use super::{
+        BACKOFF_CEILING_MS, INITIAL_BACKOFF_MS, MAX_RECONNECTS_PER_WINDOW, RECONNECT_WINDOW_MS,
+        ReconnectSupervisor, backoff_delay,
+    };
+
+    /// Read the connection states published since the last read
+    async fn read_states(
+        reader: &mut TopicReader<SystemBusMessage>,
+    ) -> Vec<PriceReporterConnectionState> {
+        let mut states = Vec::new();
+        while reader.has_next() {
+            if let SystemBusMessage::PriceReporterConnectionUpdate { state, .. } =
+                reader.next_message().await
+            {
+                states.push(state);
+            }
+        }
+
+        states
+    }
+
+    /// Tests that the backoff doubles with each attempt up to the ceiling,
+    /// jittered within its upper half
+    #[test]
+    fn test_backoff_delay() {
+        let expected_ms =
+            [(1, INITIAL_BACKOFF_MS), (2, 2 * INITIAL_BACKOFF_MS), (3, 4 * INITIAL_BACKOFF_MS)];
+        for (attempt, backoff_ms) in expected_ms {
+            let delay = backoff_delay(attempt);
+            assert!(delay >= Duration::from_millis(backoff_ms / 2));
+            assert!(delay <= Duration::from_millis(backoff_ms));
+        }
+
+        // Later attempts are capped at the ceiling, without overflowing
+        for attempt in [20, u32::MAX] {
+            let delay = backoff_delay(attempt);
+            assert!(delay >= Duration::from_millis(BACKOFF_CEILING_MS / 2));
+            assert!(delay <= Duration::from_millis(BACKOFF_CEILING_MS));
+        }
+    }
+
+    /// Tests that reconnect attempts are capped within the window
+    #[test]
+    fn test_reserve_attempt() {
+        let supervisor = ReconnectSupervisor::new(SystemBus::new());
+        for _ in 0..MAX_RECONNECTS_PER_WINDOW {
+            assert!(supervisor.reserve_attempt().is_none());
+        }
+
+        let wait = supervisor.reserve_attempt().expect("attempt should be held back");
+        assert!(wait <= Duration::from_millis(RECONNECT_WINDOW_MS));
+    }
+
+    /// Tests that a connection publishes its reconnects once per attempt, and
+    /// its recovery once, resetting its backoff
+    #[tokio::test]
+    async fn test_reconnect_handle() {
+        let system_bus = SystemBus::new();
+        let mut reader = system_bus.subscribe(PRICE_REPORTER_CONNECTION_TOPIC.to_string());
+        let supervisor = ReconnectSupervisor::new(system_bus);
+        let mut handle = supervisor.register("test-connection");
+
+        handle.wait_to_reconnect().await;
+        let states = read_states(&mut reader).await;
+        assert!(matches!(
+            states.as_slice(),
+            [PriceReporterConnectionState::Reconnecting { attempt: 1, .. }]
+        ));
+
+        // Only the first message after a reconnect publishes the recovery
+        handle.mark_connected();
+        handle.mark_connected();
+        let states = read_states(&mut reader).await;
+        assert_eq!(states, vec![PriceReporterConnectionState::Connected]);
+
+        // The next reconnect starts from the first attempt again
+        handle.wait_to_reconnect().await;
+        let states = read_states(&mut reader).await;
+        assert!(matches!(
+            states.as_slice(),
+            [PriceReporterConnectionState::Reconnecting { attempt: 1, .. }]
+        ));
+    }
+}
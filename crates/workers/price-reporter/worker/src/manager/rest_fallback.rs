@@ -0,0 +1,448 @@
+//! The REST ticker fallback
+//!
+//! Prices are streamed from the external price reporter over a single
+//! websocket. If that connection repeatedly hangs up or cannot be
+//! re-established, every externally streamed pair would otherwise report no
+//! data until it recovers. Instead, after enough consecutive failures the
+//! relayer polls each exchange's REST ticker directly, marking the streams it
+//! fills this way as degraded, until the websocket delivers prices again.
+//!
+//! Exchanges with configured credentials are polled through their
+//! authenticated endpoints, which carry better rate limits.
+//!
+//! The canonical (Renegade) price for a pair is computed by the external
+//! price reporter. While the fallback is active, it is derived from the
+//! exchange prices refreshed by the latest poll, provided enough of them agree,
+//! and never replaces a more recent price from the reporter.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use constants::in_bootstrap_mode;
+use itertools::Itertools;
+use price_state::{PriceStreamStates, StreamTuple};
+use reqwest::Client;
+use serde_json::Value;
+use types_core::{Exchange, Price, Token};
+use types_runtime::CancelChannel;
+use util::{
+    concurrency::runtime::sleep_forever_async, get_current_time_millis, log_task, logging::Outcome,
+};
+
+use crate::{
+    errors::{ExchangeConnectionError, PriceReporterError},
+    logging::Task,
+    manager::{
+        auth::authenticate_get,
+        metrics::{
+            FeedSource, record_feed_latency, record_feed_message, record_feed_parse_failure,
+        },
+        utils::get_all_stream_tuples,
+    },
+    worker::{ExchangeCredentials, PriceReporterConfig},
+};
+
+/// The number of consecutive failures of the external price reporter
+/// connection after which the REST fallback is activated
+const REST_FALLBACK_FAILURE_THRESHOLD: u32 = 3;
+/// The timeout on a single REST ticker request
+const REST_TICKER_TIMEOUT_MS: u64 = 5_000; // 5 seconds
+
+// -------------------
+// | Fallback Switch |
+// -------------------
+
+/// Tracks consecutive failures of the external price reporter connection,
+/// activating the REST fallback once they reach the threshold
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RestFallbackSwitch {
+    /// The number of connection failures since a message was last received
+    consecutive_failures: Arc<AtomicU32>,
+    /// Whether the REST fallback is active
+    active: Arc<AtomicBool>,
+}
+
+impl RestFallbackSwitch {
+    /// Whether the REST fallback is active
+    pub(crate) fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Record a hangup or failed connection attempt
+    pub(crate) fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= REST_FALLBACK_FAILURE_THRESHOLD && !self.active.swap(true, Ordering::Relaxed)
+        {
+            log_task!(
+                Task::RestFallback,
+                Outcome::Started,
+                failures = failures,
+                "external price reporter connection failing, polling exchange REST tickers"
+            );
+        }
+    }
+
+    /// Record a message received from the external price reporter
+    pub(crate) fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        if self.active.swap(false, Ordering::Relaxed) {
+            log_task!(
+                Task::RestFallback,
+                Outcome::Ok,
+                "external price reporter connection restored, ending REST fallback"
+            );
+        }
+    }
+}
+
+// -------------------
+// | Fallback Poller |
+// -------------------
+
+/// Polls exchange REST tickers in place of the external price reporter while
+/// the fallback is active
+pub(crate) struct RestFallbackPoller {
+    /// The switch activating the fallback
+    switch: RestFallbackSwitch,
+    /// The interval at which tickers are polled
+    interval: Duration,
+    /// The exchange streams which have a REST ticker
+    streams: Vec<StreamTuple>,
+    /// The pairs for which a canonical price is streamed
+    canonical_pairs: Vec<(Token, Token)>,
+    /// The client used to query the tickers
+    http_client: Client,
+    /// The API credentials with which requests are authenticated, keyed by
+    /// exchange
+    credentials: HashMap<Exchange, ExchangeCredentials>,
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl RestFallbackPoller {
+    /// Create a poller for the streams streamed from the external price
+    /// reporter
+    ///
+    /// Returns `None` if the fallback is disabled
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        switch: RestFallbackSwitch,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Option<Self> {
+        let interval_ms = config.exchange_conn_config.rest_fallback_interval_ms;
+        if interval_ms == 0 {
+            return None;
+        }
+
+        let (canonical, streams): (Vec<_>, Vec<_>) = get_all_stream_tuples(config)
+            .into_iter()
+            .filter(|(exchange, base, _)| !config.is_priced_locally(*exchange, base))
+            .partition(|(exchange, ..)| *exchange == Exchange::Renegade);
+        let credentials = config.exchange_conn_config.credentials.clone();
+        let streams = streams
+            .into_iter()
+            .filter(|(exchange, base, quote)| {
+                let authenticated = credentials.contains_key(exchange);
+                ticker_url(*exchange, base, quote, authenticated).is_some()
+            })
+            .collect();
+        let canonical_pairs = canonical.into_iter().map(|(_, base, quote)| (base, quote)).collect();
+
+        let http_client = Client::builder()
+            .timeout(Duration::from_millis(REST_TICKER_TIMEOUT_MS))
+            .build()
+            .expect("building the REST ticker client cannot fail");
+
+        Some(Self {
+            switch,
+            interval: Duration::from_millis(interval_ms),
+            streams,
+            canonical_pairs,
+            http_client,
+            credentials,
+            price_stream_states,
+            cancel_channel,
+        })
+    }
+
+    /// The polling loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if self.switch.is_active() {
+                        self.poll_streams().await;
+                    }
+                },
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "RestFallbackPoller cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Poll the ticker of every stream, then derive the canonical prices from
+    /// the exchange prices refreshed by the poll
+    ///
+    /// A failed stream keeps its last price, which ages out of the price state
+    /// like any other stream that stops reporting
+    async fn poll_streams(&self) {
+        let poll_start = get_current_time_millis();
+        for (exchange, base, quote) in &self.streams {
+            if let Err(e) = self.poll_stream(*exchange, base, quote).await {
+                let subject = format!("{exchange}-{base}-{quote}");
+                log_task!(Task::RestFallback, Outcome::Failed, subject = %subject, error = %e, "error polling exchange REST ticker");
+            }
+        }
+
+        for (base, quote) in &self.canonical_pairs {
+            if self
+                .price_stream_states
+                .new_fallback_canonical_price(base, quote, poll_start)
+                .is_none()
+            {
+                let subject = format!("{base}-{quote}");
+                log_task!(Task::RestFallback, Outcome::Skipped, subject = %subject, "no fallback canonical price saved, too few fresh exchange prices or a newer canonical price");
+            }
+        }
+    }
+
+    /// Poll the ticker of a single stream and save the price
+    async fn poll_stream(
+        &self,
+        exchange: Exchange,
+        base: &Token,
+        quote: &Token,
+    ) -> Result<(), ExchangeConnectionError> {
+        let credentials = self.credentials.get(&exchange);
+        let url = ticker_url(exchange, base, quote, credentials.is_some()).ok_or_else(|| {
+            ExchangeConnectionError::UnsupportedPair(base.clone(), quote.clone(), exchange)
+        })?;
+        let request = authenticate_get(self.http_client.get(&url), exchange, &url, credentials)?;
+        let body: Value = request
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(ExchangeConnectionError::rest_request)?
+            .json()
+            .await
+            .map_err(|e| {
+                record_feed_parse_failure(Some(exchange), FeedSource::Rest);
+                ExchangeConnectionError::invalid_message(e)
+            })?;
+
+        let Some(price) = parse_ticker_price(exchange, &body) else {
+            record_feed_parse_failure(Some(exchange), FeedSource::Rest);
+            return Err(ExchangeConnectionError::invalid_message(body.to_string()));
+        };
+        record_feed_message(exchange, FeedSource::Rest);
+        if let Some(exchange_ts) = parse_ticker_timestamp(exchange, &body) {
+            record_feed_latency(exchange, FeedSource::Rest, exchange_ts);
+        }
+
+        if price.is_zero() {
+            return Err(ExchangeConnectionError::invalid_message(body.to_string()));
+        }
+
+        let ts = get_current_time_millis();
+        self.price_stream_states
+            .new_degraded_price(exchange, base.clone(), quote.clone(), price, ts)
+            .map_err(ExchangeConnectionError::save_state)
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Get the URL of the REST ticker for a pair on an exchange
+///
+/// If `authenticated` is set, the ticker is the one served to authenticated
+/// clients where the exchange has a separate one. Returns `None` if the
+/// exchange has no REST ticker or does not list both tokens
+fn ticker_url(
+    exchange: Exchange,
+    base: &Token,
+    quote: &Token,
+    authenticated: bool,
+) -> Option<String> {
+    if !base.is_named() || !quote.is_named() {
+        return None;
+    }
+
+    let base = base.get_exchange_ticker(exchange)?;
+    let quote = quote.get_exchange_ticker(exchange)?;
+    let url = match exchange {
+        Exchange::Binance => {
+            format!("https://api.binance.com/api/v3/ticker/price?symbol={base}{quote}")
+        },
+        Exchange::Bybit => {
+            format!("https://api.bybit.com/v5/market/tickers?category=spot&symbol={base}{quote}")
+        },
+        Exchange::Coinbase if authenticated => {
+            format!("https://api.coinbase.com/api/v3/brokerage/products/{base}-{quote}")
+        },
+        Exchange::Coinbase => {
+            format!("https://api.exchange.coinbase.com/products/{base}-{quote}/ticker")
+        },
+        Exchange::Kraken => format!("https://api.kraken.com/0/public/Ticker?pair={base}{quote}"),
+        Exchange::Kucoin => {
+            format!("https://api.kucoin.com/api/v1/market/orderbook/level1?symbol={base}-{quote}")
+        },
+        Exchange::Okx => format!("https://www.okx.com/api/v5/market/ticker?instId={base}-{quote}"),
+        Exchange::Chainlink | Exchange::UniswapV3 | Exchange::Renegade => return None,
+    };
+
+    Some(url)
+}
+
+/// Parse the last traded price from an exchange's REST ticker response
+///
+/// The authenticated Coinbase ticker reports the price under the same key as
+/// the public one
+fn parse_ticker_price(exchange: Exchange, body: &Value) -> Option<Price> {
+    let price = match exchange {
+        Exchange::Binance => &body["price"],
+        Exchange::Bybit => &body["result"]["list"][0]["lastPrice"],
+        Exchange::Coinbase => &body["price"],
+        // Kraken keys the result by its own name for the pair
+        Exchange::Kraken => {
+            let (_, ticker) = body["result"].as_object()?.iter().exactly_one().ok()?;
+            &ticker["c"][0]
+        },
+        Exchange::Kucoin => &body["data"]["price"],
+        Exchange::Okx => &body["data"][0]["last"],
+        Exchange::Chainlink | Exchange::UniswapV3 | Exchange::Renegade => return None,
+    };
+
+    match price {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_f64().and_then(Price::from_f64_round_down),
+    }
+}
+
+/// Parse the time at which the exchange generated a REST ticker response, in
+/// milliseconds since the epoch
+///
+/// Returns `None` for exchanges whose tickers carry no timestamp
+fn parse_ticker_timestamp(exchange: Exchange, body: &Value) -> Option<u64> {
+    let ts = match exchange {
+        Exchange::Bybit => &body["time"],
+        Exchange::Kucoin => &body["data"]["time"],
+        Exchange::Okx => &body["data"][0]["ts"],
+        _ => return None,
+    };
+
+    match ts {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_u64(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use types_core::{Exchange, Price};
+
+    use super::{
+        REST_FALLBACK_FAILURE_THRESHOLD, RestFallbackSwitch, parse_ticker_price,
+        parse_ticker_timestamp,
+    };
+
+    /// Tests that the fallback activates after the threshold of consecutive
+    /// failures, and ends on the next success
+    #[test]
+    fn test_fallback_switch() {
+        let switch = RestFallbackSwitch::default();
+        for _ in 1..REST_FALLBACK_FAILURE_THRESHOLD {
+            switch.record_failure();
+        }
+        assert!(!switch.is_active());
+
+        // A success resets the count of consecutive failures
+        switch.record_success();
+        switch.record_failure();
+        assert!(!switch.is_active());
+
+        for _ in 1..REST_FALLBACK_FAILURE_THRESHOLD {
+            switch.record_failure();
+        }
+        assert!(switch.is_active());
+
+        switch.record_success();
+        assert!(!switch.is_active());
+    }
+
+    /// Tests parsing the last traded price from the exchanges' tickers
+    #[test]
+    fn test_parse_ticker_price() {
+        let expected: Price = "2000.5".parse().unwrap();
+        let binance = json!({ "symbol": "ETHUSDT", "price": "2000.5" });
+        let kraken = json!({ "error": [], "result": { "XETHZUSD": { "c": ["2000.5", "0.1"] } } });
+        let okx = json!({ "code": "0", "data": [{ "instId": "ETH-USDT", "last": "2000.5" }] });
+
+        assert_eq!(parse_ticker_price(Exchange::Binance, &binance), Some(expected));
+        assert_eq!(parse_ticker_price(Exchange::Kraken, &kraken), Some(expected));
+        assert_eq!(parse_ticker_price(Exchange::Okx, &okx), Some(expected));
+    }
+
+    /// Tests that malformed tickers yield no price
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_parse_ticker_price__malformed() {
+        let empty_okx = json!({ "code": "0", "data": [] });
+        let kraken_two_pairs = json!({ "result": { "A": { "c": ["1"] }, "B": { "c": ["2"] } } });
+        let binance_error = json!({ "code": -1121, "msg": "Invalid symbol." });
+
+        assert_eq!(parse_ticker_price(Exchange::Okx, &empty_okx), None);
+        assert_eq!(parse_ticker_price(Exchange::Kraken, &kraken_two_pairs), None);
+        assert_eq!(parse_ticker_price(Exchange::Binance, &binance_error), None);
+        assert_eq!(parse_ticker_price(Exchange::Renegade, &binance_error), None);
+    }
+
+    /// Tests parsing the time at which the exchanges generated their tickers
+    #[test]
+    fn test_parse_ticker_timestamp() {
+        let ts = 1_700_000_000_123;
+        let bybit = json!({ "retCode": 0, "result": { "list": [] }, "time": ts });
+        let kucoin = json!({ "code": "200000", "data": { "price": "2000.5", "time": ts } });
+        let okx = json!({ "code": "0", "data": [{ "last": "2000.5", "ts": ts.to_string() }] });
+
+        assert_eq!(parse_ticker_timestamp(Exchange::Bybit, &bybit), Some(ts));
+        assert_eq!(parse_ticker_timestamp(Exchange::Kucoin, &kucoin), Some(ts));
+        assert_eq!(parse_ticker_timestamp(Exchange::Okx, &okx), Some(ts));
+    }
+
+    /// Tests that tickers without a timestamp, or with a malformed one, yield
+    /// no timestamp
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_parse_ticker_timestamp__missing() {
+        let binance = json!({ "symbol": "ETHUSDT", "price": "2000.5" });
+        let empty_okx = json!({ "code": "0", "data": [] });
+        let bad_okx = json!({ "code": "0", "data": [{ "ts": "yesterday" }] });
+        let negative_bybit = json!({ "time": -1 });
+
+        assert_eq!(parse_ticker_timestamp(Exchange::Binance, &binance), None);
+        assert_eq!(parse_ticker_timestamp(Exchange::Okx, &empty_okx), None);
+        assert_eq!(parse_ticker_timestamp(Exchange::Okx, &bad_okx), None);
+        assert_eq!(parse_ticker_timestamp(Exchange::Bybit, &negative_bybit), None);
+    }
+}
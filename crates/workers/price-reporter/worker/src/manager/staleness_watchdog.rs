@@ -0,0 +1,214 @@
+//! The price staleness watchdog
+//!
+//! Tracks the age of the last price on the streams of every exchange, marking
+//! a stream stale once its price is older than the configured threshold. A
+//! stream which never reports is aged from when it was added. Each transition
+//! is published to the system bus, on which the matching engine re-runs the
+//! orders it skipped on a stale pair once the pair recovers.
+
+use std::{collections::HashSet, time::Duration};
+
+use constants::in_bootstrap_mode;
+use price_state::{PriceStreamStates, StreamTuple};
+use system_bus::{PRICE_STALENESS_TOPIC, SystemBus, SystemBusMessage};
+use types_runtime::CancelChannel;
+use util::{concurrency::runtime::sleep_forever_async, log_task, logging::Outcome};
+
+use crate::{errors::PriceReporterError, logging::Task, worker::PriceReporterConfig};
+
+/// The minimum interval at which stream ages are checked
+const MIN_CHECK_INTERVAL_MS: u64 = 250;
+
+/// Marks streams stale once their last price exceeds the staleness threshold
+pub(crate) struct StalenessWatchdog {
+    /// The age in milliseconds past which a stream is stale
+    threshold_ms: u64,
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The system bus on which staleness transitions are published
+    system_bus: SystemBus,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl StalenessWatchdog {
+    /// Create a new watchdog
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Self {
+        Self {
+            threshold_ms: config.staleness_threshold_ms,
+            price_stream_states,
+            system_bus: config.system_bus.clone(),
+            cancel_channel,
+        }
+    }
+
+    /// The watchdog loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        // Check several times per threshold so that a stream is marked stale
+        // soon after it crosses the threshold
+        let check_interval_ms = (self.threshold_ms / 4).max(MIN_CHECK_INTERVAL_MS);
+        let mut interval = tokio::time::interval(Duration::from_millis(check_interval_ms));
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut stale_streams = HashSet::new();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.check_streams(&mut stale_streams),
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "StalenessWatchdog cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Publish the streams which recovered since the last check, then mark
+    /// the streams past the threshold stale and publish them
+    ///
+    /// Recoveries are published first so that a stream which recovers and goes
+    /// stale again between checks is published in order
+    fn check_streams(&self, stale_streams: &mut HashSet<StreamTuple>) {
+        // A stream recovers once a new price clears its stale mark
+        let recovered: Vec<_> = stale_streams
+            .iter()
+            .filter(|stream| !self.price_stream_states.is_stale(stream))
+            .cloned()
+            .collect();
+        for stream in recovered {
+            let (exchange, base, quote) = &stream;
+            log_task!(Task::PriceStream, Outcome::Ok, subject = %format!("{exchange}-{base}-{quote}"), "price stream recovered");
+            self.publish(&stream, false /* stale */);
+            stale_streams.remove(&stream);
+        }
+
+        // The mark is bound to the price found too old, so a price received
+        // during the check is never marked
+        for (stream, age_ms) in self.price_stream_states.mark_stale_streams(self.threshold_ms) {
+            let (exchange, base, quote) = &stream;
+            log_task!(Task::PriceStream, Outcome::Partial, subject = %format!("{exchange}-{base}-{quote}"), age_ms = age_ms, "price stream is stale");
+            self.publish(&stream, true /* stale */);
+            stale_streams.insert(stream);
+        }
+    }
+
+    /// Publish a staleness transition for a stream
+    fn publish(&self, stream: &StreamTuple, stale: bool) {
+        let (exchange, base, quote) = stream.clone();
+        self.system_bus.publish(
+            PRICE_STALENESS_TOPIC.to_string(),
+            SystemBusMessage::PriceStreamStalenessUpdate { exchange, base, quote, stale },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use price_state::{PriceStreamStates, StreamTuple, aggregation::AggregationStrategies};
+    use system_bus::{PRICE_STALENESS_TOPIC, SystemBus, SystemBusMessage, TopicReader};
+    use types_core::{Chain, Exchange, Price, Token};
+    use types_runtime::new_cancel_channel;
+    use util::get_current_time_millis;
+
+    use super::StalenessWatchdog;
+
+    /// The staleness threshold under test
+    const THRESHOLD_MS: u64 = 5_000;
+
+    /// Build a watchdog over a Binance and an OKX stream, returning it along
+    /// with the streams
+    fn watchdog() -> (StalenessWatchdog, StreamTuple, StreamTuple) {
+        let base = Token::new("0x1", Chain::ArbitrumOne);
+        let quote = Token::new("0x2", Chain::ArbitrumOne);
+        let binance = (Exchange::Binance, base.clone(), quote.clone());
+        let okx = (Exchange::Okx, base, quote);
+
+        let streams = vec![binance.clone(), okx.clone()];
+        let strategies = AggregationStrategies::default();
+        let price_stream_states =
+            PriceStreamStates::new(streams, vec![], strategies, 0., HashMap::new());
+        let (_cancel_sender, cancel_channel) = new_cancel_channel();
+        let watchdog = StalenessWatchdog {
+            threshold_ms: THRESHOLD_MS,
+            price_stream_states,
+            system_bus: SystemBus::new(),
+            cancel_channel,
+        };
+
+        (watchdog, binance, okx)
+    }
+
+    /// Record a price on a stream
+    fn set_price(watchdog: &StalenessWatchdog, stream: &StreamTuple, ts: u64) {
+        let (exchange, base, quote) = stream.clone();
+        let price = Price::from_f64_round_down(1.).unwrap();
+        watchdog.price_stream_states.new_price(exchange, base, quote, price, ts).unwrap();
+    }
+
+    /// Read the staleness transitions published since the last read
+    async fn read_updates(reader: &mut TopicReader<SystemBusMessage>) -> Vec<(Exchange, bool)> {
+        let mut updates = Vec::new();
+        while reader.has_next() {
+            if let SystemBusMessage::PriceStreamStalenessUpdate { exchange, stale, .. } =
+                reader.next_message().await
+            {
+                updates.push((exchange, stale));
+            }
+        }
+
+        updates
+    }
+
+    /// Tests that a stream is published stale once past the threshold, once
+    /// per price, and published recovered on its next price
+    #[tokio::test]
+    async fn test_check_streams() {
+        let (watchdog, binance, okx) = watchdog();
+        let mut reader = watchdog.system_bus.subscribe(PRICE_STALENESS_TOPIC.to_string());
+        let mut stale_streams = HashSet::new();
+        let now = get_current_time_millis();
+
+        set_price(&watchdog, &binance, now - 2 * THRESHOLD_MS);
+        set_price(&watchdog, &okx, now);
+        watchdog.check_streams(&mut stale_streams);
+        assert_eq!(read_updates(&mut reader).await, vec![(Exchange::Binance, true)]);
+
+        watchdog.check_streams(&mut stale_streams);
+        assert!(read_updates(&mut reader).await.is_empty());
+
+        set_price(&watchdog, &binance, get_current_time_millis());
+        watchdog.check_streams(&mut stale_streams);
+        assert_eq!(read_updates(&mut reader).await, vec![(Exchange::Binance, false)]);
+        assert!(stale_streams.is_empty());
+    }
+
+    /// Tests that a stream which recovers and goes stale again between checks
+    /// is published recovered before it is published stale
+    #[tokio::test]
+    #[allow(non_snake_case)]
+    async fn test_check_streams__stale_again() {
+        let (watchdog, binance, okx) = watchdog();
+        let mut reader = watchdog.system_bus.subscribe(PRICE_STALENESS_TOPIC.to_string());
+        let mut stale_streams = HashSet::new();
+        let now = get_current_time_millis();
+
+        set_price(&watchdog, &binance, now - 3 * THRESHOLD_MS);
+        set_price(&watchdog, &okx, now);
+        watchdog.check_streams(&mut stale_streams);
+        assert_eq!(read_updates(&mut reader).await, vec![(Exchange::Binance, true)]);
+
+        set_price(&watchdog, &binance, now - 2 * THRESHOLD_MS);
+        watchdog.check_streams(&mut stale_streams);
+        let updates = read_updates(&mut reader).await;
+        assert_eq!(updates, vec![(Exchange::Binance, false), (Exchange::Binance, true)]);
+    }
+}
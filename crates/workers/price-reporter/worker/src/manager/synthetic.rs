@@ -0,0 +1,102 @@
+//! The synthetic price poller
+//!
+//! A pair with no direct market is priced through an intermediate token, e.g.
+//! TOKEN/USDC as TOKEN/WETH × WETH/USDC. The price state derives each
+//! exchange's price for such a pair from the exchange's own legs. The external
+//! price reporter cannot stream a canonical price for the pair, so this poller
+//! derives one from the aggregate of the exchanges' TOKEN/WETH prices and the
+//! canonical WETH/USDC price, timestamped with the stalest leg it was derived
+//! from. The canonical price is then checked for deviation against the
+//! exchange prices like any other.
+
+use std::time::Duration;
+
+use constants::in_bootstrap_mode;
+use price_state::PriceStreamStates;
+use types_core::{Exchange, Token};
+use types_runtime::CancelChannel;
+use util::{concurrency::runtime::sleep_forever_async, log_task, logging::Outcome};
+
+use crate::{errors::PriceReporterError, logging::Task, worker::PriceReporterConfig};
+
+/// The interval at which synthetic prices are derived
+const SYNTHETIC_PRICE_INTERVAL_MS: u64 = 1_000; // 1 second
+
+/// Derives the canonical price of every synthetic pair
+pub(crate) struct SyntheticPricePoller {
+    /// The base tokens of the synthetic pairs, each quoted in USDC
+    bases: Vec<Token>,
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl SyntheticPricePoller {
+    /// Create a poller for the configured synthetic pairs
+    ///
+    /// Returns `None` if no synthetic route is configured
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Option<Self> {
+        if config.synthetic_routes.is_empty() {
+            return None;
+        }
+
+        let bases = config.synthetic_routes.keys().cloned().collect();
+        Some(Self { bases, price_stream_states, cancel_channel })
+    }
+
+    /// The polling loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(SYNTHETIC_PRICE_INTERVAL_MS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.derive_prices(),
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "SyntheticPricePoller cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Derive and save the canonical price of each synthetic pair
+    ///
+    /// A price is only saved if its legs have reported since the last one, so
+    /// that a pair whose legs stop reporting ages like any other stream
+    fn derive_prices(&self) {
+        let usdc = Token::usdc();
+        for base in &self.bases {
+            let Some((price, ts)) = self.price_stream_states.derive_synthetic_price(base, &usdc)
+            else {
+                continue;
+            };
+
+            let last_ts =
+                self.price_stream_states.peek_timestamped_price(base).map(|p| p.timestamp);
+            if last_ts.is_ok_and(|last_ts| ts <= last_ts) {
+                continue;
+            }
+
+            if let Err(e) = self.price_stream_states.new_price(
+                Exchange::Renegade,
+                base.clone(),
+                usdc.clone(),
+                price,
+                ts,
+            ) {
+                log_task!(Task::SyntheticPrice, Outcome::Failed, subject = %format!("{base}/{usdc}"), error = %e, "error saving synthetic price");
+            }
+        }
+    }
+}
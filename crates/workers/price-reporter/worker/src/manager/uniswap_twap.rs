@@ -0,0 +1,292 @@
+//! The Uniswap V3 TWAP poller
+//!
+//! For pairs configured with a Uniswap V3 pool, the relayer reads a
+//! time-weighted average price from the pool's `observe` oracle rather than
+//! streaming the pool's spot price from the external price reporter. A TWAP
+//! over a long enough window cannot be moved within a single block, so it
+//! resists the manipulation a spot price is exposed to.
+
+use std::{fmt::Display, time::Duration};
+
+use alloy_primitives::Address;
+use constants::in_bootstrap_mode;
+use darkpool_client::{DarkpoolClient, client::uniswap_v3::UniswapV3Twap};
+use price_state::PriceStreamStates;
+use types_core::{Exchange, Price, Token};
+use types_runtime::CancelChannel;
+use util::{
+    concurrency::runtime::sleep_forever_async, get_current_time_millis, log_task, logging::Outcome,
+};
+
+use crate::{
+    errors::{ExchangeConnectionError, PriceReporterError},
+    logging::Task,
+    manager::{
+        metrics::{FeedSource, record_feed_message, record_feed_parse_failure},
+        utils::get_all_stream_tuples,
+    },
+    worker::PriceReporterConfig,
+};
+
+/// The interval at which the TWAP pools are polled
+const UNISWAP_TWAP_POLL_INTERVAL_MS: u64 = 5_000; // 5 seconds
+
+/// A pair priced from a Uniswap V3 pool's TWAP
+#[derive(Clone, Debug)]
+struct TwapPair {
+    /// The base token of the pair
+    base: Token,
+    /// The quote token of the pair
+    quote: Token,
+    /// The address of the pool
+    pool: Address,
+    /// The length of the TWAP window, in seconds
+    window_secs: u32,
+    /// The power of ten converting a price in base units to a price in whole
+    /// tokens, i.e. `base_decimals - quote_decimals`
+    decimal_exponent: i32,
+}
+
+impl TwapPair {
+    /// Look up the decimals of the pair's tokens
+    fn new(
+        base: Token,
+        quote: Token,
+        pool: Address,
+        window_secs: u32,
+    ) -> Result<Self, ExchangeConnectionError> {
+        let base_decimals = token_decimals(&base)?;
+        let quote_decimals = token_decimals(&quote)?;
+        let decimal_exponent = i32::from(base_decimals) - i32::from(quote_decimals);
+        Ok(Self { base, quote, pool, window_secs, decimal_exponent })
+    }
+}
+
+impl Display for TwapPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+/// Polls the TWAP of every configured Uniswap V3 pool, saving each price into
+/// the price stream states
+pub(crate) struct UniswapTwapPoller {
+    /// The client used to read the pools
+    client: DarkpoolClient,
+    /// The pairs to poll
+    pairs: Vec<TwapPair>,
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl UniswapTwapPoller {
+    /// Create a poller for the Uniswap V3 streams that have a TWAP pool
+    /// configured
+    ///
+    /// Returns `None` if no darkpool client is configured or no streamed pair
+    /// has a pool
+    pub(crate) fn new(
+        config: &PriceReporterConfig,
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Option<Self> {
+        let client = config.darkpool_client.clone()?;
+        let pools = &config.exchange_conn_config.uniswap_twap_pools;
+        let pairs: Vec<_> = get_all_stream_tuples(config)
+            .into_iter()
+            .filter(|(exchange, ..)| *exchange == Exchange::UniswapV3)
+            .filter_map(|(_, base, quote)| {
+                let twap_config = pools.get(&base)?;
+                match TwapPair::new(base.clone(), quote.clone(), twap_config.pool, twap_config.window_secs) {
+                    Ok(pair) => Some(pair),
+                    Err(e) => {
+                        log_task!(Task::OnchainFeed, Outcome::Failed, subject = %format!("{base}/{quote}"), error = %e, "skipping Uniswap V3 TWAP pair");
+                        None
+                    },
+                }
+            })
+            .collect();
+
+        if pairs.is_empty() {
+            return None;
+        }
+
+        Some(Self { client, pairs, price_stream_states, cancel_channel })
+    }
+
+    /// The polling loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut cancel_channel = self.cancel_channel.clone();
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(UNISWAP_TWAP_POLL_INTERVAL_MS));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.poll_pairs().await,
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "UniswapTwapPoller cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Poll the TWAP for every pair, logging failures
+    ///
+    /// A failed pair keeps its last price, which ages out of the price state
+    /// like any other stream that stops reporting
+    async fn poll_pairs(&self) {
+        for pair in &self.pairs {
+            if let Err(e) = self.poll_pair(pair).await {
+                log_task!(Task::OnchainFeed, Outcome::Failed, subject = %pair, error = %e, "error polling Uniswap V3 TWAP");
+            }
+        }
+    }
+
+    /// Poll the TWAP for a single pair and save the price
+    async fn poll_pair(&self, pair: &TwapPair) -> Result<(), ExchangeConnectionError> {
+        let twap = self
+            .client
+            .get_uniswap_v3_twap(pair.pool, pair.window_secs)
+            .await
+            .map_err(ExchangeConnectionError::onchain_read)?;
+        let price = twap_price(&twap, pair)?;
+        record_feed_message(Exchange::UniswapV3, FeedSource::Onchain);
+
+        let ts = get_current_time_millis();
+        self.price_stream_states
+            .new_price(Exchange::UniswapV3, pair.base.clone(), pair.quote.clone(), price, ts)
+            .map_err(ExchangeConnectionError::save_state)
+    }
+}
+
+/// Convert a pool's TWAP to the price of the pair's base token in whole
+/// units of its quote token
+fn twap_price(twap: &UniswapV3Twap, pair: &TwapPair) -> Result<Price, ExchangeConnectionError> {
+    // The pool must be a pool between the pair's two tokens
+    let quote_addr = pair.quote.get_alloy_address();
+    let raw_price = twap
+        .price_of(pair.base.get_alloy_address())
+        .filter(|_| twap.token0 == quote_addr || twap.token1 == quote_addr)
+        .ok_or_else(|| {
+            let msg = format!("pool {} does not trade {pair}", pair.pool);
+            ExchangeConnectionError::onchain_read(msg)
+        })?;
+
+    // The tick price is a float, and is converted before correcting for
+    // decimals so that the correction is exact
+    Price::from_f64_round_down(raw_price)
+        .and_then(|price| price.scale_pow10(pair.decimal_exponent))
+        .ok_or_else(|| {
+            record_feed_parse_failure(Some(Exchange::UniswapV3), FeedSource::Onchain);
+            let msg = format!("invalid TWAP price {raw_price} for {pair}");
+            ExchangeConnectionError::onchain_read(msg)
+        })
+}
+
+/// Get a token's decimals from the token mapping
+fn token_decimals(token: &Token) -> Result<u8, ExchangeConnectionError> {
+    token
+        .get_decimals()
+        .ok_or_else(|| ExchangeConnectionError::onchain_read(format!("no decimals for {token}")))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use alloy_primitives::Address;
+    use darkpool_client::client::uniswap_v3::UniswapV3Twap;
+    use types_core::{Chain, Token, set_default_chain, write_token_remaps};
+
+    use super::{TwapPair, twap_price};
+    use crate::worker::{DEFAULT_UNISWAP_TWAP_WINDOW_SECS, UniswapTwapConfig};
+
+    /// The tick at which token0 is worth twice token1
+    fn double_tick() -> f64 {
+        2f64.ln() / 1.0001f64.ln()
+    }
+
+    /// Build a token at an address made of the given byte
+    fn token(byte: u8) -> Token {
+        Token::from_alloy_address_on_chain(&Address::repeat_byte(byte), Chain::ArbitrumOne)
+    }
+
+    /// Build a pair of the given tokens, with the base token having
+    /// `decimal_exponent` more decimals than the quote
+    fn pair(base: &Token, quote: &Token, decimal_exponent: i32) -> TwapPair {
+        TwapPair {
+            base: base.clone(),
+            quote: quote.clone(),
+            pool: Address::ZERO,
+            window_secs: 1_800,
+            decimal_exponent,
+        }
+    }
+
+    /// Tests that a TWAP is priced in the pair's direction and corrected for
+    /// the tokens' decimals
+    #[test]
+    fn test_twap_price() {
+        let (base, quote) = (token(1), token(2));
+        let twap = UniswapV3Twap {
+            token0: base.get_alloy_address(),
+            token1: quote.get_alloy_address(),
+            mean_tick: double_tick(),
+        };
+
+        let price = twap_price(&twap, &pair(&base, &quote, 0)).unwrap();
+        assert!((price.to_f64() - 2.).abs() < 1e-6);
+        let price = twap_price(&twap, &pair(&base, &quote, 2)).unwrap();
+        assert!((price.to_f64() - 200.).abs() < 1e-4);
+
+        // Quoted in the other direction, the price inverts
+        let price = twap_price(&twap, &pair(&quote, &base, 0)).unwrap();
+        assert!((price.to_f64() - 0.5).abs() < 1e-6);
+    }
+
+    /// Tests that a pool which does not trade both of the pair's tokens is
+    /// rejected
+    #[test]
+    fn test_twap_price_wrong_pool() {
+        let (base, quote, other) = (token(1), token(2), token(3));
+        let twap = UniswapV3Twap {
+            token0: base.get_alloy_address(),
+            token1: other.get_alloy_address(),
+            mean_tick: 0.,
+        };
+
+        assert!(twap_price(&twap, &pair(&base, &quote, 0)).is_err());
+        assert!(twap_price(&twap, &pair(&quote, &base, 0)).is_err());
+    }
+
+    /// Tests that each pool uses its configured window, or the default window
+    /// if it has none
+    #[test]
+    fn test_twap_windows() {
+        let mut remaps = write_token_remaps();
+        let token_map = remaps.entry(Chain::ArbitrumOne).or_default();
+        token_map.insert(format!("{:#x}", Address::repeat_byte(0xa1)), "WETH".to_string());
+        token_map.insert(format!("{:#x}", Address::repeat_byte(0xa2)), "ARB".to_string());
+        drop(remaps);
+        set_default_chain(Chain::ArbitrumOne);
+
+        let (weth_pool, arb_pool) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let pools = HashMap::from([("WETH".to_string(), weth_pool), ("ARB".to_string(), arb_pool)]);
+        let windows = HashMap::from([("ARB".to_string(), 600)]);
+        let configs = UniswapTwapConfig::from_tickers(&pools, &windows);
+
+        let weth = &configs[&Token::from_ticker("WETH")];
+        assert_eq!(weth.pool, weth_pool);
+        assert_eq!(weth.window_secs, DEFAULT_UNISWAP_TWAP_WINDOW_SECS);
+        let arb = &configs[&Token::from_ticker("ARB")];
+        assert_eq!(arb.pool, arb_pool);
+        assert_eq!(arb.window_secs, 600);
+    }
+}
@@ -76,6 +76,20 @@ pub fn required_streams_for_pair(
     requested_quote: &Token,
     config: &PriceReporterConfig,
 ) -> Vec<(Exchange, Token, Token)> {
+    // A synthetic pair streams its leg to the intermediate token on each
+    // exchange, along with the streams pricing the intermediate in the quote.
+    // Its canonical price is derived locally
+    if let Some(intermediate) = config.synthetic_intermediate(requested_base, requested_quote) {
+        let mut streams = get_supported_exchanges(requested_base, intermediate, config)
+            .into_iter()
+            .filter(|exchange| *exchange != Exchange::Renegade)
+            .map(|exchange| (exchange, requested_base.clone(), intermediate.clone()))
+            .collect_vec();
+        streams.extend(required_streams_for_pair(intermediate, requested_quote, config));
+        streams.push((Exchange::Renegade, requested_base.clone(), requested_quote.clone()));
+        return streams;
+    }
+
     let mut streams = Vec::new();
     let exchanges = get_supported_exchanges(requested_base, requested_quote, config);
 
@@ -0,0 +1,55 @@
+//! The VWAP sampler
+//!
+//! Samples each pair's exchange prices into the pair's rolling VWAP windows
+//! at a fixed interval, so that matches may be priced off a VWAP rather than
+//! the instantaneous price.
+
+use std::time::Duration;
+
+use constants::in_bootstrap_mode;
+use price_state::PriceStreamStates;
+use types_runtime::CancelChannel;
+use util::{concurrency::runtime::sleep_forever_async, log_task, logging::Outcome};
+
+use crate::{errors::PriceReporterError, logging::Task};
+
+/// The interval at which exchange prices are sampled
+const SAMPLE_INTERVAL_MS: u64 = 1_000; // 1 second
+
+/// Samples exchange prices into the rolling VWAP windows
+pub(crate) struct VwapSampler {
+    /// The latest states of all price streams
+    price_stream_states: PriceStreamStates,
+    /// The channel on which the coordinator may cancel execution
+    cancel_channel: CancelChannel,
+}
+
+impl VwapSampler {
+    /// Create a new sampler
+    pub(crate) fn new(
+        cancel_channel: CancelChannel,
+        price_stream_states: PriceStreamStates,
+    ) -> Self {
+        Self { price_stream_states, cancel_channel }
+    }
+
+    /// The sampler loop, runs until cancelled
+    pub(crate) async fn execution_loop(self) -> Result<(), PriceReporterError> {
+        // If the relayer is in bootstrap mode, sleep forever
+        if in_bootstrap_mode() {
+            sleep_forever_async().await;
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_millis(SAMPLE_INTERVAL_MS));
+        let mut cancel_channel = self.cancel_channel.clone();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.price_stream_states.record_vwap_samples(),
+                _ = cancel_channel.changed() => {
+                    log_task!(Task::ReporterLifecycle, Outcome::Ok, "VwapSampler cancelled, shutting down...");
+                    return Err(PriceReporterError::Cancelled("received cancel signal".to_string()));
+                }
+            }
+        }
+    }
+}
@@ -12,7 +12,7 @@ use crate::manager::utils::get_all_stream_tuples;
 use crate::worker::PriceReporterConfig;
 
 /// The conversion price to use for all quote conversion mocks
-const CONVERSION_PRICE: Price = 1.0;
+const CONVERSION_PRICE: Price = Price::ONE;
 /// Ticker names to use in setting up the mock token remap
 const MOCK_TICKER_NAMES: &[&str] = &[
     USDC_TICKER,
@@ -79,7 +79,13 @@ impl MockPriceReporter {
         let all_streams = get_all_stream_tuples(config);
         let disabled_exchanges =
             Exchange::all().into_iter().filter(|e| !config.exchange_configured(*e)).collect_vec();
-        PriceStreamStates::new(all_streams, disabled_exchanges)
+        PriceStreamStates::new(
+            all_streams,
+            disabled_exchanges,
+            config.aggregation_strategies.clone(),
+            config.max_source_deviation,
+            config.synthetic_routes.clone(),
+        )
     }
 
     /// Start the mock price reporter
@@ -1,30 +1,41 @@
 //! Defines the Worker logic for the PriceReporterManger, which simply
 //! dispatches jobs to the PriceReporterExecutor.
 
+use alloy_primitives::Address;
 use async_trait::async_trait;
+use darkpool_client::DarkpoolClient;
 use price_state::PriceStreamStates;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
 use std::thread::{self, JoinHandle};
 use system_bus::SystemBus;
 use tokio::runtime::Builder as TokioBuilder;
-use types_core::Exchange;
+use types_core::{Exchange, Token};
 use types_runtime::{CancelChannel, Worker};
 use url::Url;
 
+pub use price_state::aggregation::AggregationStrategies;
+
 use crate::manager::{
-    external_executor::ExternalPriceReporterExecutor, utils::get_all_stream_tuples,
+    chainlink::ChainlinkPoller, deviation_breaker::DeviationBreaker,
+    external_executor::ExternalPriceReporterExecutor, reconnect::ReconnectSupervisor,
+    staleness_watchdog::StalenessWatchdog, synthetic::SyntheticPricePoller,
+    uniswap_twap::UniswapTwapPoller, utils::get_all_stream_tuples, vwap_sampler::VwapSampler,
 };
 
 use super::errors::PriceReporterError;
 
 /// The number of threads backing the price reporter manager
 const PRICE_REPORTER_MANAGER_NUM_THREADS: usize = 2;
+/// The TWAP window used for a Uniswap V3 pool configured without one
+pub const DEFAULT_UNISWAP_TWAP_WINDOW_SECS: u32 = 1_800; // 30 minutes
 
 // ----------
 // | Config |
 // ----------
 
 /// The config passed from the coordinator to the PriceReporter
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct PriceReporterConfig {
     /// The global system bus
     pub system_bus: SystemBus,
@@ -32,6 +43,19 @@ pub struct PriceReporterConfig {
     pub exchange_conn_config: ExchangeConnectionsConfig,
     /// The URL of an external price reporter service
     pub price_reporter_url: Option<Url>,
+    /// The age in milliseconds past which a stream's last price is stale
+    pub staleness_threshold_ms: u64,
+    /// The strategies by which exchange prices are aggregated for each pair
+    pub aggregation_strategies: AggregationStrategies,
+    /// The fraction by which an exchange price may deviate from the median of
+    /// its pair's exchange prices before it is excluded, zero if unlimited
+    pub max_source_deviation: f64,
+    /// The intermediate token through which each pair with no direct market
+    /// is priced, keyed by the pair's base token
+    pub synthetic_routes: HashMap<Token, Token>,
+    /// The client used to read on-chain price feeds, Chainlink is not
+    /// configured without one
+    pub darkpool_client: Option<DarkpoolClient>,
     /// Whether or not the worker is disabled
     pub disabled: bool,
     /// Exchanges that are explicitly disabled for price reporting
@@ -44,24 +68,102 @@ pub struct PriceReporterConfig {
 /// The configuration options that may be used by exchange connections
 #[derive(Clone, Debug, Default)]
 pub struct ExchangeConnectionsConfig {
-    /// The coinbase API key that the price reporter may use
-    pub coinbase_key_name: Option<String>,
-    /// The coinbase API secret that the price reporter may use
-    pub coinbase_key_secret: Option<String>,
+    /// The API credentials the price reporter may use, keyed by exchange
+    pub credentials: HashMap<Exchange, ExchangeCredentials>,
     /// The ethereum RPC node websocket addresses for on-chain data
     pub eth_websocket_addr: Option<String>,
+    /// The Uniswap V3 pools from which the relayer reads TWAPs, keyed by the
+    /// base token of the USDC quoted pair each pool prices
+    pub uniswap_twap_pools: HashMap<Token, UniswapTwapConfig>,
+    /// The interval in milliseconds at which exchange REST tickers are polled
+    /// while the external price reporter connection is failing, zero if the
+    /// fallback is disabled
+    pub rest_fallback_interval_ms: u64,
+}
+
+/// The API credentials for an exchange
+#[derive(Clone)]
+pub struct ExchangeCredentials {
+    /// The API key, or the API key name for Coinbase
+    pub key: String,
+    /// The API secret, if the exchange signs requests with one
+    pub secret: Option<String>,
+}
+
+impl Debug for ExchangeCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExchangeCredentials")
+            .field("key", &self.key)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Build the exchange credentials from optional API keys and secrets
+///
+/// Exchanges without an API key are omitted
+pub fn exchange_credentials_from_keys(
+    keys: Vec<(Exchange, Option<String>, Option<String>)>,
+) -> HashMap<Exchange, ExchangeCredentials> {
+    keys.into_iter()
+        .filter_map(|(exchange, key, secret)| {
+            Some((exchange, ExchangeCredentials { key: key?, secret }))
+        })
+        .collect()
+}
+
+/// A Uniswap V3 pool from which a pair's price is read as a TWAP
+#[derive(Clone, Copy, Debug)]
+pub struct UniswapTwapConfig {
+    /// The address of the pool
+    pub pool: Address,
+    /// The length of the TWAP window, in seconds
+    pub window_secs: u32,
+}
+
+impl UniswapTwapConfig {
+    /// Build the TWAP configs from pool addresses and window lengths keyed by
+    /// base ticker
+    ///
+    /// Pools without a window use `DEFAULT_UNISWAP_TWAP_WINDOW_SECS`
+    pub fn from_tickers(
+        pools: &HashMap<String, Address>,
+        windows: &HashMap<String, u32>,
+    ) -> HashMap<Token, UniswapTwapConfig> {
+        pools
+            .iter()
+            .map(|(ticker, pool)| {
+                let window_secs =
+                    windows.get(ticker).copied().unwrap_or(DEFAULT_UNISWAP_TWAP_WINDOW_SECS);
+                (Token::from_ticker(ticker), UniswapTwapConfig { pool: *pool, window_secs })
+            })
+            .collect()
+    }
+}
+
+/// Build the synthetic routes from intermediate tickers keyed by base ticker
+pub fn synthetic_routes_from_tickers(routes: &HashMap<String, String>) -> HashMap<Token, Token> {
+    routes
+        .iter()
+        .map(|(base, intermediate)| (Token::from_ticker(base), Token::from_ticker(intermediate)))
+        .collect()
 }
 
 impl ExchangeConnectionsConfig {
     /// Whether or not the Coinbase connection is configured
     pub fn coinbase_configured(&self) -> bool {
-        self.coinbase_key_name.is_some() && self.coinbase_key_secret.is_some()
+        self.credentials.get(&Exchange::Coinbase).is_some_and(|creds| creds.secret.is_some())
     }
 
     /// Whether or not the Uniswap V3 connection is configured
     pub fn uniswap_v3_configured(&self) -> bool {
         self.eth_websocket_addr.is_some()
     }
+
+    /// Whether or not any Uniswap V3 TWAP pool is configured
+    pub fn uniswap_twap_configured(&self) -> bool {
+        !self.uniswap_twap_pools.is_empty()
+    }
 }
 
 impl PriceReporterConfig {
@@ -71,7 +173,13 @@ impl PriceReporterConfig {
         let disabled_exchanges =
             Exchange::all().into_iter().filter(|e| !self.exchange_configured(*e)).collect();
 
-        PriceStreamStates::new(streams, disabled_exchanges)
+        PriceStreamStates::new(
+            streams,
+            disabled_exchanges,
+            self.aggregation_strategies.clone(),
+            self.max_source_deviation,
+            self.synthetic_routes.clone(),
+        )
     }
 
     /// Returns true if the necessary configuration information is present
@@ -82,20 +190,52 @@ impl PriceReporterConfig {
     pub(crate) fn exchange_configured(&self, exchange: Exchange) -> bool {
         let disabled = self.disabled_exchanges.contains(&exchange);
 
-        let configured = if self.price_reporter_url.is_some() {
+        let configured = if exchange == Exchange::Chainlink {
+            // Chainlink feeds are read on-chain by the relayer itself, rather than
+            // streamed from the external price reporter
+            self.darkpool_client.is_some()
+        } else if self.price_reporter_url.is_some() {
             // If we are using the external price reporter, we assume all exchanges are
             // configured
             true
         } else {
             match exchange {
                 Exchange::Coinbase => self.exchange_conn_config.coinbase_configured(),
-                Exchange::UniswapV3 => self.exchange_conn_config.uniswap_v3_configured(),
+                Exchange::UniswapV3 => {
+                    let twap_configured = self.darkpool_client.is_some()
+                        && self.exchange_conn_config.uniswap_twap_configured();
+                    self.exchange_conn_config.uniswap_v3_configured() || twap_configured
+                },
                 _ => true,
             }
         };
 
         !disabled && configured
     }
+
+    /// Whether the relayer reads the given stream's prices on-chain itself,
+    /// rather than streaming them from the external price reporter
+    pub(crate) fn is_polled_onchain(&self, exchange: Exchange, base: &Token) -> bool {
+        match exchange {
+            Exchange::Chainlink => true,
+            Exchange::UniswapV3 => self.exchange_conn_config.uniswap_twap_pools.contains_key(base),
+            _ => false,
+        }
+    }
+
+    /// Whether the relayer prices the given stream itself, either on-chain or
+    /// by deriving a synthetic price, rather than streaming it from the
+    /// external price reporter
+    pub(crate) fn is_priced_locally(&self, exchange: Exchange, base: &Token) -> bool {
+        let synthetic = exchange == Exchange::Renegade && self.synthetic_routes.contains_key(base);
+        synthetic || self.is_polled_onchain(exchange, base)
+    }
+
+    /// Get the intermediate token through which the given pair is priced, if
+    /// the pair has no direct market
+    pub(crate) fn synthetic_intermediate(&self, base: &Token, quote: &Token) -> Option<&Token> {
+        self.synthetic_routes.get(base).filter(|intermediate| *intermediate != quote)
+    }
 }
 
 // ------------------
@@ -163,8 +303,47 @@ impl Worker for PriceReporter {
             .build()
             .unwrap();
 
+        // Poll the Chainlink feeds alongside the external price streams
         let streams = self.price_stream_states.clone();
-        let manager_executor = ExternalPriceReporterExecutor::new(config, cancel_channel, streams);
+        if let Some(poller) = ChainlinkPoller::new(&config, cancel_channel.clone(), streams.clone())
+        {
+            runtime.spawn(poller.execution_loop());
+        }
+        if let Some(poller) =
+            UniswapTwapPoller::new(&config, cancel_channel.clone(), streams.clone())
+        {
+            runtime.spawn(poller.execution_loop());
+        }
+
+        if let Some(poller) =
+            SyntheticPricePoller::new(&config, cancel_channel.clone(), streams.clone())
+        {
+            runtime.spawn(poller.execution_loop());
+        }
+
+        // Alert on exchange prices excluded for deviating from the others
+        if let Some(breaker) =
+            DeviationBreaker::new(&config, cancel_channel.clone(), streams.clone())
+        {
+            runtime.spawn(breaker.execution_loop());
+        }
+
+        // Sample exchange prices into each pair's rolling VWAP windows
+        let sampler = VwapSampler::new(cancel_channel.clone(), streams.clone());
+        runtime.spawn(sampler.execution_loop());
+
+        // Mark streams stale once their prices age past the threshold
+        let watchdog = StalenessWatchdog::new(&config, cancel_channel.clone(), streams.clone());
+        runtime.spawn(watchdog.execution_loop());
+
+        // All connections reconnect through a single shared supervisor
+        let reconnect_supervisor = ReconnectSupervisor::new(config.system_bus.clone());
+        let manager_executor = ExternalPriceReporterExecutor::new(
+            config,
+            cancel_channel,
+            streams,
+            reconnect_supervisor,
+        );
         let manager_executor_handle = thread::Builder::new()
             .name("price-reporter-manager-executor".to_string())
             .spawn(move || runtime.block_on(manager_executor.execution_loop()).err().unwrap())
@@ -1,7 +1,10 @@
 //! An implementation of the proof manager which uses an external prover service
 
+use std::time::Instant;
+
 use constants::in_bootstrap_mode;
 use job_types::proof_manager::{ProofJob, ProofManagerJob, ProofManagerReceiver};
+use renegade_metrics::record_proof_generation_latency;
 use tracing::instrument;
 use types_runtime::CancelChannel;
 use util::log_task;
@@ -57,11 +60,11 @@ impl ExternalProofManager {
             .enable_all()
             .build()
             .map_err(ProofManagerError::setup)?;
-        runtime.block_on(async { self.work_loop() })
+        runtime.block_on(self.work_loop())
     }
 
     /// The work loop of the external proof manager
-    fn work_loop(self) -> Result<(), ProofManagerError> {
+    async fn work_loop(mut self) -> Result<(), ProofManagerError> {
         loop {
             // Check the cancel channel before blocking on a job
             if self
@@ -77,11 +80,12 @@ impl ExternalProofManager {
                 return Err(ProofManagerError::Cancelled("received cancel signal".to_string()));
             }
 
-            // Block on a job
+            // Wait for a job
             let job = self
                 .job_queue
                 .recv()
-                .map_err(|err| ProofManagerError::RecvError(err.to_string()))?;
+                .await
+                .ok_or_else(|| ProofManagerError::RecvError("job queue closed".to_string()))?;
 
             // Handle the job
             let client = self.client.clone();
@@ -100,6 +104,7 @@ impl ExternalProofManager {
         traced_job: TracedMessage<ProofManagerJob>,
     ) -> Result<(), ProofManagerError> {
         let job = traced_job.consume();
+        let start = Instant::now();
         let response = match job.type_ {
             // Update proofs
             ProofJob::ValidBalanceCreate { witness, statement } => {
@@ -210,6 +215,7 @@ impl ExternalProofManager {
                 client.prove_valid_public_relayer_fee_payment(witness, statement).await
             },
         }?;
+        record_proof_generation_latency(start.elapsed());
 
         // Ignore send errors
         let _err = job.response_channel.send(response);
@@ -1,10 +1,10 @@
 //! A prover implementation which uses the native prover service
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use circuit_types::{
     PlonkLinkProof, ProofLinkingHint,
-    traits::{SingleProverCircuit, setup_preprocessed_keys},
+    traits::{BaseType, SingleProverCircuit, setup_preprocessed_keys},
 };
 use circuits_core::{
     singleprover_prove, singleprover_prove_with_hint,
@@ -104,7 +104,9 @@ use constants::in_bootstrap_mode;
 use job_types::proof_manager::{
     ProofJob, ProofManagerJob, ProofManagerReceiver, ProofManagerResponse,
 };
+use mpc_relation::constants::GATE_WIDTH;
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use renegade_metrics::{record_circuit_size, record_proof_generation_latency};
 use tracing::{info_span, instrument};
 use types_proofs::{
     IntentOnlySettlementProofBundle, PrivateSettlementProofBundle, ProofAndHintBundle, ProofBundle,
@@ -126,6 +128,11 @@ const WORKER_STACK_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 /// Error message when sending a proof response fails
 const ERR_SENDING_RESPONSE: &str = "error sending proof response, channel closed";
 
+/// The size of a compressed commitment (a G1 point) in a proof, in bytes
+const COMMITMENT_BYTES: usize = 32;
+/// The size of a polynomial evaluation (a scalar) in a proof, in bytes
+const EVALUATION_BYTES: usize = 32;
+
 /// A native prover, generates all proofs locally
 #[derive(Clone)]
 pub struct NativeProofManager {
@@ -167,7 +174,7 @@ impl NativeProofManager {
         });
 
         // Take the job queue and cancel channel for the coordinator loop
-        let job_queue = self.job_queue.take().expect("job queue not set");
+        let mut job_queue = self.job_queue.take().expect("job queue not set");
         let cancel_channel = self.cancel_channel.take().expect("cancel channel not set");
         loop {
             // Check the cancel channel before blocking on a job
@@ -185,8 +192,8 @@ impl NativeProofManager {
 
             // Dequeue the next job and hand it to the thread pool
             let job = job_queue
-                .recv()
-                .map_err(|err| ProofManagerError::JobQueueClosed(err.to_string()))?;
+                .blocking_recv()
+                .ok_or_else(|| ProofManagerError::JobQueueClosed("job queue closed".to_string()))?;
 
             // Clone the thread pool for the worker thread
             let self_clone = self.clone();
@@ -206,6 +213,7 @@ impl NativeProofManager {
         job: TracedMessage<ProofManagerJob>,
     ) -> Result<(), ProofManagerError> {
         let ProofManagerJob { type_, response_channel } = job.consume();
+        let start = Instant::now();
         let proof_response = match type_ {
             // Update proofs
             ProofJob::ValidBalanceCreate { witness, statement } => {
@@ -300,6 +308,7 @@ impl NativeProofManager {
                 self.prove_valid_public_relayer_fee_payment(witness, statement)
             },
         }?;
+        record_proof_generation_latency(start.elapsed());
 
         response_channel
             .send(proof_response)
@@ -335,52 +344,59 @@ impl NativeProofManager {
         setup_preprocessed_keys::<SizedValidPublicProtocolFeePayment>();
         setup_preprocessed_keys::<SizedValidPublicRelayerFeePayment>();
 
-        // Set up layouts for all of the circuits
+        // Set up layouts for all of the circuits, recording their sizes
         // Update proofs
-        ValidBalanceCreate::get_circuit_layout().map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidDeposit::get_circuit_layout().map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidOrderCancellationCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidWithdrawal::get_circuit_layout().map_err(err_str!(ProofManagerError::Setup))?;
+        Self::setup_circuit_layout::<ValidBalanceCreate>()?;
+        Self::setup_circuit_layout::<SizedValidDeposit>()?;
+        Self::setup_circuit_layout::<SizedValidOrderCancellationCircuit>()?;
+        Self::setup_circuit_layout::<SizedValidWithdrawal>()?;
         // Validity proofs
-        SizedIntentAndBalanceValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedIntentAndBalanceFirstFillValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedIntentOnlyValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        IntentOnlyFirstFillValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedNewOutputBalanceValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedOutputBalanceValidityCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
+        Self::setup_circuit_layout::<SizedIntentAndBalanceValidityCircuit>()?;
+        Self::setup_circuit_layout::<SizedIntentAndBalanceFirstFillValidityCircuit>()?;
+        Self::setup_circuit_layout::<SizedIntentOnlyValidityCircuit>()?;
+        Self::setup_circuit_layout::<IntentOnlyFirstFillValidityCircuit>()?;
+        Self::setup_circuit_layout::<SizedNewOutputBalanceValidityCircuit>()?;
+        Self::setup_circuit_layout::<SizedOutputBalanceValidityCircuit>()?;
         // Settlement proofs
-        IntentAndBalanceBoundedSettlementCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        IntentAndBalancePrivateSettlementCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        IntentAndBalancePublicSettlementCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        IntentOnlyBoundedSettlementCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        IntentOnlyPublicSettlementCircuit::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
+        Self::setup_circuit_layout::<IntentAndBalanceBoundedSettlementCircuit>()?;
+        Self::setup_circuit_layout::<IntentAndBalancePrivateSettlementCircuit>()?;
+        Self::setup_circuit_layout::<IntentAndBalancePublicSettlementCircuit>()?;
+        Self::setup_circuit_layout::<IntentOnlyBoundedSettlementCircuit>()?;
+        Self::setup_circuit_layout::<IntentOnlyPublicSettlementCircuit>()?;
         // Fee proofs
-        SizedValidNoteRedemption::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidPrivateProtocolFeePayment::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidPrivateRelayerFeePayment::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidPublicProtocolFeePayment::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
-        SizedValidPublicRelayerFeePayment::get_circuit_layout()
-            .map_err(err_str!(ProofManagerError::Setup))?;
+        Self::setup_circuit_layout::<SizedValidNoteRedemption>()?;
+        Self::setup_circuit_layout::<SizedValidPrivateProtocolFeePayment>()?;
+        Self::setup_circuit_layout::<SizedValidPrivateRelayerFeePayment>()?;
+        Self::setup_circuit_layout::<SizedValidPublicProtocolFeePayment>()?;
+        Self::setup_circuit_layout::<SizedValidPublicRelayerFeePayment>()?;
 
         Ok(())
     }
 
+    /// Initialize the circuit layout cache for the given circuit and record
+    /// the circuit's size
+    fn setup_circuit_layout<C: SingleProverCircuit>() -> Result<(), ProofManagerError> {
+        let layout = C::get_circuit_layout().map_err(err_str!(ProofManagerError::Setup))?;
+        let name = C::name();
+        let n_constraints = layout.n_gates;
+        let domain_size = layout.circuit_size();
+        let witness_size = C::Witness::NUM_SCALARS;
+        let proof_size = expected_proof_size();
+        record_circuit_size(&name, n_constraints, domain_size, witness_size, proof_size);
+
+        log_task!(
+            Task::PreprocessCircuits,
+            Outcome::Ok,
+            circuit = %name,
+            n_constraints = n_constraints,
+            domain_size = domain_size,
+            witness_size = witness_size,
+            proof_size = proof_size,
+            "preprocessed circuit"
+        );
+        Ok(())
+    }
+
     // Update proofs
     /// Create a proof of `VALID BALANCE CREATE`
     #[instrument(skip_all, err)]
@@ -787,3 +803,49 @@ impl NativeProofManager {
         ])
     }
 }
+
+// -----------
+// | Helpers |
+// -----------
+
+/// The expected size of a proof, in bytes
+///
+/// A Plonk proof has a fixed shape regardless of the circuit it proves, so
+/// this is the size of its commitments and evaluations, excluding
+/// serialization framing
+fn expected_proof_size() -> usize {
+    // Wire and quotient commitments, the permutation product commitment, and
+    // the two opening proofs
+    let n_commitments = 2 * (GATE_WIDTH + 1) + 3;
+    // Wire evaluations, permutation sigma evaluations, and the shifted
+    // permutation evaluation
+    let n_evaluations = (GATE_WIDTH + 1) + GATE_WIDTH + 1;
+
+    n_commitments * COMMITMENT_BYTES + n_evaluations * EVALUATION_BYTES
+}
+
+#[cfg(test)]
+mod test {
+    use ark_serialize::CanonicalSerialize;
+    use types_proofs::mocks::dummy_proof;
+
+    use super::expected_proof_size;
+
+    /// The number of variable length vectors in a serialized proof, each
+    /// prefixed with its length
+    const N_PROOF_VECS: usize = 4;
+    /// The size of a serialized vector's length prefix, in bytes
+    const VEC_LEN_BYTES: usize = 8;
+    /// The size of the serialized flag of the proof's optional lookup proof,
+    /// in bytes
+    const OPTION_FLAG_BYTES: usize = 1;
+
+    /// Tests that the expected proof size matches the size of a serialized
+    /// proof, excluding serialization framing
+    #[test]
+    fn test_expected_proof_size() {
+        let serialized_size = dummy_proof().compressed_size();
+        let framing = N_PROOF_VECS * VEC_LEN_BYTES + OPTION_FLAG_BYTES;
+        assert_eq!(expected_proof_size(), serialized_size - framing);
+    }
+}
@@ -13,6 +13,8 @@ pub enum Task {
     ManagerLifecycle,
     /// Handling a single proof generation job dispatched to the manager.
     HandleProofJob,
+    /// Preprocessing a circuit's keys and layout at startup.
+    PreprocessCircuits,
 }
 
 impl LogTask for Task {
@@ -20,6 +22,7 @@ impl LogTask for Task {
         match self {
             Task::ManagerLifecycle => "manager-lifecycle",
             Task::HandleProofJob => "handle-proof-job",
+            Task::PreprocessCircuits => "preprocess-circuits",
         }
     }
 }
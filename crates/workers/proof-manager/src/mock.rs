@@ -116,9 +116,9 @@ pub struct MockProofManager;
 #[allow(clippy::needless_pass_by_value)]
 impl MockProofManager {
     /// Start a mock proof manager
-    pub fn start(job_queue: ProofManagerReceiver, skip_constraints: bool) {
+    pub fn start(mut job_queue: ProofManagerReceiver, skip_constraints: bool) {
         Handle::current().spawn_blocking(move || {
-            if let Err(e) = Self::execution_loop(&job_queue, skip_constraints) {
+            if let Err(e) = Self::execution_loop(&mut job_queue, skip_constraints) {
                 log_task!(Task::ManagerLifecycle, Outcome::Failed, error = %e, "error in mock proof manager");
             }
         });
@@ -126,15 +126,15 @@ impl MockProofManager {
 
     /// The execution loop for the mock
     fn execution_loop(
-        job_queue: &ProofManagerReceiver,
+        job_queue: &mut ProofManagerReceiver,
         skip_constraints: bool,
     ) -> Result<(), ProofManagerError> {
         loop {
-            match job_queue.recv() {
-                Err(_) => {
+            match job_queue.blocking_recv() {
+                None => {
                     return Err(ProofManagerError::JobQueueClosed("job queue closed".to_string()));
                 },
-                Ok(job) => Self::handle_job(job, skip_constraints)?,
+                Some(job) => Self::handle_job(job, skip_constraints)?,
             }
         }
     }
@@ -15,7 +15,10 @@ use config::RelayerConfig;
 use mock_node::MockNodeController;
 use state::test_helpers::tmp_db_path;
 use test_helpers::{integration_test_main, types::TestVerbosity};
-use util::{on_chain::set_protocol_fee, telemetry::LevelFilter};
+use util::{
+    on_chain::set_protocol_fee,
+    telemetry::{LevelFilter, LogFormat},
+};
 
 // -------
 // | CLI |
@@ -87,7 +90,7 @@ fn setup_integration_tests(test_args: &IntegrationTestArgs) {
 
     // Configure logging
     if matches!(test_args.verbosity, TestVerbosity::Full) {
-        util::telemetry::setup_system_logger(LevelFilter::INFO);
+        util::telemetry::setup_system_logger(LevelFilter::INFO, LogFormat::Pretty);
     }
 }
 
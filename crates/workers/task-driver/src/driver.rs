@@ -3,12 +3,14 @@
 
 use std::{collections::HashMap, fmt::Debug, time::Duration};
 
+use darkpool_client::client::gas::GasTracker;
 use job_types::task_driver::{TaskDriverJob, TaskDriverReceiver, TaskNotificationSender};
 use state::State;
 use tokio::runtime::Builder as TokioRuntimeBuilder;
 use tracing::instrument;
 use types_core::AccountId;
-use types_tasks::{QueuedTask, TaskDescriptor, TaskIdentifier};
+use types_runtime::{InFlightTask, ShutdownProgress};
+use types_tasks::{ChainSubmission, QueuedTask, TaskDescriptor, TaskIdentifier};
 use util::log_task;
 use util::logging::Outcome;
 use util::{
@@ -52,6 +54,9 @@ const TASK_DRIVER_THREAD_NAME: &str = "renegade-task-driver";
 const TASK_DRIVER_N_RETRIES: usize = 5;
 /// The stack size to allocate for task driver threads
 const DRIVER_THREAD_STACK_SIZE: usize = 50_000_000; // 50MB
+/// The interval at which a held task checks whether the state has exited
+/// safe mode
+const SAFE_MODE_POLL_MS: u64 = 1_000; // 1 second
 
 /// Error message sent on a notification when a task is not found
 const TASK_NOT_FOUND_ERROR: &str = "task not found";
@@ -75,6 +80,9 @@ pub struct TaskExecutor {
     task_context: TaskContext,
     /// The map of task notifications to send
     task_notifications: TaskNotificationMap,
+    /// The relayer's shutdown progress, new tasks are held once the drain
+    /// reaches the task phase
+    shutdown: ShutdownProgress,
 }
 
 /// The config of the runtime arguments
@@ -124,6 +132,7 @@ impl TaskExecutor {
             runtime_config: config.runtime_config,
             task_context,
             task_notifications: new_shared(HashMap::new()),
+            shutdown: config.shutdown,
         }
     }
 
@@ -299,8 +308,17 @@ impl TaskExecutor {
         descriptor: T::Descriptor,
         affected_accounts: Vec<AccountId>,
     ) -> Result<(), TaskDriverError> {
-        // Collect the arguments then spawn
-        let ctx = self.task_context();
+        // Hold the task while the state is in safe mode, its state updates would
+        // be refused
+        self.await_safe_mode_exit(id).await;
+        // Count the task as in flight so that a shutdown drain waits for it
+        let _in_flight = self.begin_in_flight(id).await;
+
+        // Collect the arguments then spawn, tracking the gas spent by the task's
+        // on-chain submissions
+        let mut ctx = self.task_context();
+        let (darkpool_client, gas_tracker) = ctx.darkpool_client.with_gas_tracker();
+        ctx.darkpool_client = darkpool_client;
         let args = self.runtime_config;
 
         // Create and run the task
@@ -328,6 +346,10 @@ impl TaskExecutor {
         let mut task = task_res.unwrap();
         let res = Self::run_task_to_completion(&mut task, args).await;
 
+        // Record the gas spent by the run, whether or not it succeeded
+        let task_type = task.inner().name();
+        self.record_gas_costs(id, task_type, &affected_accounts, &gas_tracker).await;
+
         // A preempted (yielded) task has been requeued by a higher-priority
         // serial preemption (Stage 2 order-yield). Do NOT clean up / pop / clear
         // -- leave it queued so it re-runs from scratch when it next becomes the
@@ -399,6 +421,84 @@ impl TaskExecutor {
         if task.completed() { Ok(()) } else { Err(TaskDriverError::TaskFailed) }
     }
 
+    /// Wait for the state to exit safe mode, if it is in safe mode
+    async fn await_safe_mode_exit(&self, id: TaskIdentifier) {
+        if !self.state().is_safe_mode() {
+            return;
+        }
+
+        log_task!(
+            LogTask::TaskExecution,
+            Outcome::Partial,
+            subject = %id,
+            "state is in safe mode, holding task until the raft quorum is regained"
+        );
+        while self.state().is_safe_mode() {
+            tokio::time::sleep(Duration::from_millis(SAFE_MODE_POLL_MS)).await;
+        }
+    }
+
+    /// Count a task as in flight, or hold it indefinitely if the node is
+    /// draining for shutdown
+    ///
+    /// A held task is left in its queue, so it runs once the node restarts
+    async fn begin_in_flight(&self, id: TaskIdentifier) -> InFlightTask {
+        let in_flight = self.shutdown.track_task();
+        if self.shutdown.accepts_tasks() {
+            return in_flight;
+        }
+
+        drop(in_flight);
+        log_task!(
+            LogTask::TaskExecution,
+            Outcome::Skipped,
+            subject = %id,
+            "node is shutting down, holding task until restart"
+        );
+        std::future::pending().await
+    }
+
+    /// Record the gas costs of the submissions a task made in state
+    ///
+    /// Failures are logged rather than propagated, as the task's outcome does
+    /// not depend on its accounting
+    async fn record_gas_costs(
+        &self,
+        id: TaskIdentifier,
+        task_type: String,
+        affected_accounts: &[AccountId],
+        gas_tracker: &GasTracker,
+    ) {
+        let costs: Vec<ChainSubmission> = gas_tracker
+            .take()
+            .into_iter()
+            .map(|cost| ChainSubmission {
+                tx_hash: format!("{:#x}", cost.tx_hash),
+                gas_used: cost.gas_used,
+                effective_gas_price: cost.effective_gas_price,
+                fee_paid: cost.fee_paid(),
+            })
+            .collect();
+        if costs.is_empty() {
+            return;
+        }
+
+        let accounts = affected_accounts.to_vec();
+        let res = match self.state().record_task_gas_costs(id, task_type, accounts, costs).await {
+            Ok(waiter) => waiter.await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = res {
+            log_task!(
+                LogTask::TaskExecution,
+                Outcome::Failed,
+                subject = %id,
+                error = %e,
+                "error recording task gas costs"
+            );
+        }
+    }
+
     /// Run the success/failure hooks for a task
     async fn run_post_task_hooks<T: Task>(
         &self,
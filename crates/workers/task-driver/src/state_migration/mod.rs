@@ -46,7 +46,7 @@ mod test {
     use flate2::read::GzDecoder;
     use state::{State, test_helpers::mock_state_with_config};
     use tracing::warn;
-    use util::telemetry::{LevelFilter, setup_system_logger};
+    use util::telemetry::{LevelFilter, LogFormat, setup_system_logger};
 
     use super::run_state_migrations;
 
@@ -60,7 +60,7 @@ mod test {
     /// Test state migrations
     #[tokio::test]
     async fn test_state_migrations() {
-        setup_system_logger(LevelFilter::INFO);
+        setup_system_logger(LevelFilter::INFO, LogFormat::Pretty);
 
         // Create state from the snapshot
         let state = match create_state_from_snapshot().await {
@@ -78,6 +78,11 @@ pub enum CancelOrderTaskError {
     /// An error interacting with the darkpool client
     #[error("darkpool client error: {0}")]
     DarkpoolClient(String),
+    /// A submitted transaction was mined but not confirmed in time
+    ///
+    /// The transaction may still be confirmed, so the task must not resubmit it
+    #[error("transaction unconfirmed: {0}")]
+    TxUnconfirmed(String),
     /// The order was not found
     #[error("order not found: {0}")]
     OrderNotFound(OrderId),
@@ -111,7 +116,10 @@ impl TaskError for CancelOrderTaskError {
 
 impl From<DarkpoolClientError> for CancelOrderTaskError {
     fn from(e: DarkpoolClientError) -> Self {
-        CancelOrderTaskError::darkpool_client(e)
+        match e {
+            DarkpoolClientError::TxUnconfirmed(msg) => CancelOrderTaskError::TxUnconfirmed(msg),
+            e => CancelOrderTaskError::darkpool_client(e),
+        }
     }
 }
 
@@ -98,6 +98,10 @@ impl From<CreateBalanceTaskState> for TaskStateWrapper {
 pub enum CreateBalanceTaskError {
     /// Error interacting with darkpool client
     DarkpoolClient(String),
+    /// A submitted transaction was mined but not confirmed in time
+    ///
+    /// The transaction may still be confirmed, so the task must not resubmit it
+    TxUnconfirmed(String),
     /// Error generating a proof of `VALID BALANCE CREATE`
     ProofGeneration(String),
     /// Error updating validity proofs affected by this balance update
@@ -146,8 +150,11 @@ impl From<StateError> for CreateBalanceTaskError {
 }
 
 impl From<DarkpoolClientError> for CreateBalanceTaskError {
-    fn from(e: darkpool_client::errors::DarkpoolClientError) -> Self {
-        CreateBalanceTaskError::DarkpoolClient(e.to_string())
+    fn from(e: DarkpoolClientError) -> Self {
+        match e {
+            DarkpoolClientError::TxUnconfirmed(msg) => CreateBalanceTaskError::TxUnconfirmed(msg),
+            e => CreateBalanceTaskError::DarkpoolClient(e.to_string()),
+        }
     }
 }
 
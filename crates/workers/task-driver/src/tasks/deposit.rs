@@ -11,7 +11,7 @@ use circuit_types::{Amount, primitives::schnorr::SchnorrPublicKey};
 use circuits_core::zk_circuits::valid_deposit::{
     SizedValidDepositWitness, ValidDepositStatement, ValidDepositWitness,
 };
-use darkpool_client::DarkpoolClient;
+use darkpool_client::{DarkpoolClient, errors::DarkpoolClientError};
 use darkpool_types::deposit::Deposit;
 use job_types::proof_manager::ProofJob;
 use renegade_solidity_abi::v2::IDarkpoolV2::DepositAuth;
@@ -96,6 +96,10 @@ impl From<DepositTaskState> for TaskStateWrapper {
 pub enum DepositTaskError {
     /// Error interacting with darkpool client
     DarkpoolClient(String),
+    /// A submitted transaction was mined but not confirmed in time
+    ///
+    /// The transaction may still be confirmed, so the task must not resubmit it
+    TxUnconfirmed(String),
     /// Error generating a proof of `VALID DEPOSIT`
     ProofGeneration(String),
     /// Error updating validity proofs affected by this balance update
@@ -145,9 +149,12 @@ impl Display for DepositTaskError {
 
 impl Error for DepositTaskError {}
 
-impl From<darkpool_client::errors::DarkpoolClientError> for DepositTaskError {
-    fn from(e: darkpool_client::errors::DarkpoolClientError) -> Self {
-        DepositTaskError::DarkpoolClient(e.to_string())
+impl From<DarkpoolClientError> for DepositTaskError {
+    fn from(e: DarkpoolClientError) -> Self {
+        match e {
+            DarkpoolClientError::TxUnconfirmed(msg) => DepositTaskError::TxUnconfirmed(msg),
+            e => DepositTaskError::DarkpoolClient(e.to_string()),
+        }
     }
 }
 
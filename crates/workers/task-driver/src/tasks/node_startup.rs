@@ -31,6 +31,7 @@ use crate::{
     logging::Task as LogTask,
     state_migration::run_state_migrations,
     task_state::TaskStateWrapper,
+    tasks::validity_proofs::reverification::reverify_validity_proofs,
     traits::{Descriptor, Task, TaskContext, TaskError, TaskState},
 };
 
@@ -167,6 +168,8 @@ pub struct NodeStartupTask {
     pub proof_queue: ProofManagerQueue,
     /// A sender to the task driver queue
     pub task_queue: TaskDriverQueue,
+    /// The task context, used to reverify validity proofs after recovery
+    pub ctx: TaskContext,
     /// The state of the task
     pub task_state: NodeStartupTaskState,
 }
@@ -181,11 +184,12 @@ impl Task for NodeStartupTask {
         Ok(Self {
             gossip_warmup_ms: descriptor.gossip_warmup_ms,
             raft_seed: descriptor.raft_seed,
-            darkpool_client: ctx.darkpool_client,
-            network_sender: ctx.network_queue,
-            state: ctx.state,
-            proof_queue: ctx.proof_queue,
-            task_queue: ctx.task_queue,
+            darkpool_client: ctx.darkpool_client.clone(),
+            network_sender: ctx.network_queue.clone(),
+            state: ctx.state.clone(),
+            proof_queue: ctx.proof_queue.clone(),
+            task_queue: ctx.task_queue.clone(),
+            ctx,
             task_state: NodeStartupTaskState::Pending,
         })
     }
@@ -444,6 +448,10 @@ impl NodeStartupTask {
             self.refresh_account(account_id).await?;
         }
 
+        // Reverify validity proofs against the chain, as the snapshot's proofs
+        // may reference roots that have since left the contract's history.
+        // This runs in the background so as not to block startup
+        self.spawn_proof_reverification();
         Ok(())
     }
 
@@ -487,6 +495,31 @@ impl NodeStartupTask {
         Ok(())
     }
 
+    /// Spawn a background pass reverifying stored validity proofs
+    fn spawn_proof_reverification(&self) {
+        let ctx = self.ctx.clone();
+        tokio::spawn(async move {
+            log_task!(LogTask::NodeStartup, Outcome::Started, "reverifying validity proofs");
+            match reverify_validity_proofs(&ctx).await {
+                Ok(summary) => log_task!(
+                    LogTask::NodeStartup,
+                    Outcome::Ok,
+                    valid = summary.num_valid,
+                    reproved = summary.num_reproved,
+                    spent = summary.num_spent,
+                    failed = summary.num_failed,
+                    "reverified validity proofs"
+                ),
+                Err(e) => log_task!(
+                    LogTask::NodeStartup,
+                    Outcome::Failed,
+                    error = %e,
+                    "failed to reverify validity proofs"
+                ),
+            }
+        });
+    }
+
     /// Setup the external match fee overrides for all tokens
     async fn setup_external_match_fees(&self) -> Result<(), NodeStartupTaskError> {
         let tokens: Vec<Token> = get_all_tokens()
@@ -228,11 +228,7 @@ impl Task for SettleInternalMatchTask {
             },
             SettleInternalMatchTaskState::UpdatingValidityProofs => {
                 self.update_validity_proofs().await?;
-                record_match_volume(
-                    &self.match_result,
-                    false, // is_external_match
-                    &[self.account_id, self.other_account_id],
-                );
+                record_match_volume(&self.match_result, false /* is_external_match */);
                 // Settlement duration (T): how long this committed settle held the
                 // account queue. Deferred (Stage-3 FIFO) settles behind it wait on
                 // this, so a T above the quoter's fill timeout (3s) is why they
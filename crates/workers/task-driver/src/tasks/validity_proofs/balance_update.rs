@@ -78,7 +78,7 @@ async fn refresh_intent_and_balance_validity_for_orders(
 ///
 /// Returns a tuple containing the intent signature and the new output balance
 /// signature.
-async fn get_private_fill_auth(
+pub(crate) async fn get_private_fill_auth(
     order_id: OrderId,
     ctx: &TaskContext,
 ) -> Result<Option<(SchnorrSignature, SchnorrSignature)>, ValidityProofsError> {
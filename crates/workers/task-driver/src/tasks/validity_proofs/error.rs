@@ -14,6 +14,9 @@ pub enum ValidityProofsError {
     /// Proof generation failed
     #[error("proof generation failed: {0}")]
     ProofGeneration(String),
+    /// An error querying the darkpool contract
+    #[error("darkpool error: {0}")]
+    Darkpool(String),
 }
 
 impl ValidityProofsError {
@@ -33,6 +36,12 @@ impl ValidityProofsError {
     pub fn proof_generation<T: ToString>(msg: T) -> Self {
         Self::ProofGeneration(msg.to_string())
     }
+
+    /// Create a new darkpool error
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn darkpool<T: ToString>(msg: T) -> Self {
+        Self::Darkpool(msg.to_string())
+    }
 }
 
 impl From<StateError> for ValidityProofsError {
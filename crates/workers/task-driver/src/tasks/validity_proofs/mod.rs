@@ -5,3 +5,4 @@ pub mod error;
 pub mod intent_and_balance;
 pub mod intent_only;
 pub mod output_balance;
+pub mod reverification;
@@ -0,0 +1,361 @@
+//! Reverification of stored validity proofs against on-chain state
+//!
+//! A node recovering from a snapshot may hold validity proofs whose statements
+//! reference Merkle roots that have since left the contract's root history.
+//! These helpers check the roots and nullifiers of each stored statement
+//! against the chain in batches, and re-prove only the orders whose proofs are
+//! stale. Orders this node is responsible for matching are reverified first.
+
+use alloy::primitives::Address;
+use circuit_types::{Nullifier, merkle::MerkleRoot};
+use types_account::order::{Order, PrivacyRing};
+use types_core::AccountId;
+use util::log_task;
+use util::logging::Outcome;
+
+use crate::{
+    logging::Task as LogTask,
+    tasks::validity_proofs::{
+        balance_update::get_private_fill_auth, error::ValidityProofsError,
+        intent_and_balance::update_intent_and_balance_validity_proof,
+        intent_only::update_intent_only_validity_proof,
+        output_balance::update_output_balance_validity_proof,
+    },
+    traits::TaskContext,
+};
+
+/// The number of orders reverified per batch
+const REVERIFICATION_BATCH_SIZE: usize = 16;
+
+/// A summary of a reverification pass
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReverificationSummary {
+    /// The number of orders whose proofs were still valid on-chain
+    pub num_valid: usize,
+    /// The number of orders that were re-proven
+    pub num_reproved: usize,
+    /// The number of orders whose proofs reference a spent nullifier
+    ///
+    /// These are left to the account refresh, which reconciles the order
+    /// against its on-chain state
+    pub num_spent: usize,
+    /// The number of orders that could not be reverified
+    pub num_failed: usize,
+}
+
+/// The result of checking a statement's roots and nullifiers on-chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatementStatus {
+    /// All roots are in the contract's history and no nullifier is spent
+    Valid,
+    /// At least one root is no longer in the contract's history
+    StaleRoot,
+    /// At least one nullifier has been spent on-chain
+    Spent,
+}
+
+/// An order awaiting reverification
+struct PendingOrder {
+    /// The account owning the order
+    account_id: AccountId,
+    /// The order
+    order: Order,
+    /// Whether the local node is responsible for matching the order
+    locally_managed: bool,
+}
+
+/// A cache of root history lookups, shared across a reverification pass
+///
+/// Many orders are proven against the same few roots, so caching avoids
+/// repeating the same contract call for each of them
+#[derive(Default)]
+struct RootCache {
+    /// The roots checked so far and whether each is in the contract's history
+    roots: Vec<(MerkleRoot, bool)>,
+}
+
+impl RootCache {
+    /// Check whether a root is in the contract's history
+    async fn check_root(
+        &mut self,
+        root: MerkleRoot,
+        ctx: &TaskContext,
+    ) -> Result<bool, ValidityProofsError> {
+        if let Some((_, valid)) = self.roots.iter().find(|(r, _)| *r == root) {
+            return Ok(*valid);
+        }
+
+        let valid = ctx
+            .darkpool_client
+            .check_merkle_root(root)
+            .await
+            .map_err(ValidityProofsError::darkpool)?;
+        self.roots.push((root, valid));
+        Ok(valid)
+    }
+
+    /// Check a statement's roots and nullifiers against the chain
+    async fn check_statement(
+        &mut self,
+        roots: &[MerkleRoot],
+        nullifiers: &[Nullifier],
+        ctx: &TaskContext,
+    ) -> Result<StatementStatus, ValidityProofsError> {
+        for nullifier in nullifiers {
+            let spent = ctx
+                .darkpool_client
+                .is_nullifier_spent(*nullifier)
+                .await
+                .map_err(ValidityProofsError::darkpool)?;
+            if spent {
+                return Ok(StatementStatus::Spent);
+            }
+        }
+
+        for root in roots {
+            if !self.check_root(*root, ctx).await? {
+                return Ok(StatementStatus::StaleRoot);
+            }
+        }
+
+        Ok(StatementStatus::Valid)
+    }
+}
+
+/// Reverify the validity proofs of all orders in state against the chain,
+/// re-proving those that reference stale Merkle roots
+///
+/// Failures for individual orders are logged and counted rather than aborting
+/// the pass
+pub async fn reverify_validity_proofs(
+    ctx: &TaskContext,
+) -> Result<ReverificationSummary, ValidityProofsError> {
+    let pending = collect_pending_orders(ctx).await?;
+    let mut summary = ReverificationSummary::default();
+    let mut cache = RootCache::default();
+
+    for batch in pending.chunks(REVERIFICATION_BATCH_SIZE) {
+        for pending_order in batch {
+            let order_id = pending_order.order.id;
+            match reverify_order(pending_order, &mut cache, ctx).await {
+                Ok(StatementStatus::Valid) => summary.num_valid += 1,
+                Ok(StatementStatus::StaleRoot) => summary.num_reproved += 1,
+                Ok(StatementStatus::Spent) => summary.num_spent += 1,
+                Err(e) => {
+                    log_task!(LogTask::NodeStartup, Outcome::Failed, subject = %order_id, error = %e, "failed to reverify validity proof");
+                    summary.num_failed += 1;
+                },
+            }
+        }
+
+        log_task!(
+            LogTask::NodeStartup,
+            Outcome::Ok,
+            processed =
+                summary.num_valid + summary.num_reproved + summary.num_spent + summary.num_failed,
+            total = pending.len(),
+            "reverified batch of validity proofs"
+        );
+    }
+
+    Ok(summary)
+}
+
+/// Collect the orders to reverify, placing locally-managed orders first
+async fn collect_pending_orders(
+    ctx: &TaskContext,
+) -> Result<Vec<PendingOrder>, ValidityProofsError> {
+    let mut pending = Vec::new();
+    for account_id in ctx.state.get_all_account_ids().await? {
+        for order in ctx.state.get_account_orders(&account_id).await? {
+            // Ring 0 orders have no validity proofs
+            if order.ring == PrivacyRing::Ring0 {
+                continue;
+            }
+
+            let locally_managed = ctx.state.is_matching_owner(&order.id).await?;
+            pending.push(PendingOrder { account_id, order, locally_managed });
+        }
+    }
+
+    prioritize_locally_managed(&mut pending);
+    Ok(pending)
+}
+
+/// Order the pending orders so that locally-managed orders come first
+///
+/// The sort is stable, so orders otherwise keep their relative position
+fn prioritize_locally_managed(pending: &mut [PendingOrder]) {
+    pending.sort_by_key(|o| !o.locally_managed);
+}
+
+/// Reverify a single order's validity proofs, re-proving them if stale
+///
+/// Returns the status of the order's proofs before any re-proving
+async fn reverify_order(
+    pending: &PendingOrder,
+    cache: &mut RootCache,
+    ctx: &TaskContext,
+) -> Result<StatementStatus, ValidityProofsError> {
+    let PendingOrder { account_id, order, .. } = pending;
+    match order.ring {
+        PrivacyRing::Ring0 => Ok(StatementStatus::Valid),
+        PrivacyRing::Ring1 => reverify_intent_only(order, cache, ctx).await,
+        PrivacyRing::Ring2 | PrivacyRing::Ring3 => {
+            reverify_private_fill(*account_id, order, cache, ctx).await
+        },
+    }
+}
+
+/// Reverify the `INTENT ONLY VALIDITY` proof of a Ring 1 order
+async fn reverify_intent_only(
+    order: &Order,
+    cache: &mut RootCache,
+    ctx: &TaskContext,
+) -> Result<StatementStatus, ValidityProofsError> {
+    // First fill proofs do not reference a Merkle root
+    let Some(bundle) = ctx.state.get_intent_only_validity_proof(order.id).await? else {
+        return Ok(StatementStatus::Valid);
+    };
+
+    let statement = &bundle.statement;
+    let status = cache
+        .check_statement(&[statement.merkle_root], &[statement.old_intent_nullifier], ctx)
+        .await?;
+    if status == StatementStatus::StaleRoot {
+        refresh_intent_merkle_proof(order, ctx).await?;
+        update_intent_only_validity_proof(order.id, ctx).await?;
+    }
+
+    Ok(status)
+}
+
+/// Reverify the `INTENT AND BALANCE VALIDITY` and `OUTPUT BALANCE VALIDITY`
+/// proofs of a Ring 2/3 order
+async fn reverify_private_fill(
+    account_id: AccountId,
+    order: &Order,
+    cache: &mut RootCache,
+    ctx: &TaskContext,
+) -> Result<StatementStatus, ValidityProofsError> {
+    let Some((intent_sig, balance_sig)) = get_private_fill_auth(order.id, ctx).await? else {
+        return Ok(StatementStatus::Valid);
+    };
+
+    // Check the intent-and-balance proof; first fill proofs do not reference
+    // a Merkle root for the intent, only for the capitalizing balance
+    let mut intent_status = StatementStatus::Valid;
+    let in_mint = order.intent.inner.in_token;
+    if let Some(bundle) = ctx.state.get_intent_and_balance_validity_proof(order.id).await? {
+        let statement = &bundle.statement;
+        let roots = [statement.intent_merkle_root, statement.balance_merkle_root];
+        let nullifiers = [statement.old_intent_nullifier, statement.old_balance_nullifier];
+        intent_status = cache.check_statement(&roots, &nullifiers, ctx).await?;
+        if intent_status == StatementStatus::StaleRoot {
+            refresh_intent_merkle_proof(order, ctx).await?;
+            refresh_balance_merkle_proof(account_id, in_mint, ctx).await?;
+            update_intent_and_balance_validity_proof(account_id, order.id, intent_sig, ctx).await?;
+        }
+    }
+
+    if intent_status == StatementStatus::Spent {
+        return Ok(intent_status);
+    }
+
+    // Check the output balance proof
+    let mut output_status = StatementStatus::Valid;
+    let out_mint = order.intent.inner.out_token;
+    if let Some(bundle) = ctx.state.get_output_balance_validity_proof(account_id, out_mint).await? {
+        let statement = &bundle.statement;
+        output_status = cache
+            .check_statement(&[statement.merkle_root], &[statement.old_balance_nullifier], ctx)
+            .await?;
+        if output_status == StatementStatus::StaleRoot {
+            refresh_balance_merkle_proof(account_id, out_mint, ctx).await?;
+            update_output_balance_validity_proof(
+                account_id,
+                order.id,
+                balance_sig,
+                true, // force
+                ctx,
+            )
+            .await?;
+        }
+    }
+
+    Ok(combine_statuses(intent_status, output_status))
+}
+
+/// Combine the statuses of an order's proofs, with a spent nullifier taking
+/// precedence over a stale root
+fn combine_statuses(a: StatementStatus, b: StatementStatus) -> StatementStatus {
+    match (a, b) {
+        (StatementStatus::Spent, _) | (_, StatementStatus::Spent) => StatementStatus::Spent,
+        (StatementStatus::StaleRoot, _) | (_, StatementStatus::StaleRoot) => {
+            StatementStatus::StaleRoot
+        },
+        _ => StatementStatus::Valid,
+    }
+}
+
+// -----------
+// | Helpers |
+// -----------
+
+/// Fetch a fresh Merkle authentication path for an order's intent from the
+/// chain and store it
+async fn refresh_intent_merkle_proof(
+    order: &Order,
+    ctx: &TaskContext,
+) -> Result<(), ValidityProofsError> {
+    let commitment = order.intent.compute_commitment();
+    let path = ctx
+        .darkpool_client
+        .find_merkle_authentication_path(commitment)
+        .await
+        .map_err(ValidityProofsError::darkpool)?;
+
+    let waiter = ctx.state.add_intent_merkle_proof(order.id, path).await?;
+    waiter.await?;
+    Ok(())
+}
+
+/// Fetch a fresh Merkle authentication path for a darkpool balance from the
+/// chain and store it
+async fn refresh_balance_merkle_proof(
+    account_id: AccountId,
+    mint: Address,
+    ctx: &TaskContext,
+) -> Result<(), ValidityProofsError> {
+    let balance = ctx
+        .state
+        .get_account_darkpool_balance(&account_id, &mint)
+        .await?
+        .ok_or(ValidityProofsError::state(format!("darkpool balance not found for {mint}")))?;
+
+    let commitment = balance.state_wrapper.compute_commitment();
+    let path = ctx
+        .darkpool_client
+        .find_merkle_authentication_path(commitment)
+        .await
+        .map_err(ValidityProofsError::darkpool)?;
+
+    let waiter = ctx.state.add_balance_merkle_proof(account_id, mint, path).await?;
+    waiter.await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests combining proof statuses
+    #[test]
+    fn test_combine_statuses() {
+        use StatementStatus::*;
+        assert_eq!(combine_statuses(Valid, Valid), Valid);
+        assert_eq!(combine_statuses(Valid, StaleRoot), StaleRoot);
+        assert_eq!(combine_statuses(StaleRoot, Spent), Spent);
+        assert_eq!(combine_statuses(Spent, Valid), Spent);
+    }
+}
@@ -29,7 +29,7 @@ use crate::{
     traits::{Descriptor, Task, TaskContext, TaskError, TaskState},
     utils::enqueue_proof_job,
 };
-use darkpool_client::DarkpoolClient;
+use darkpool_client::{DarkpoolClient, errors::DarkpoolClientError};
 
 /// The task name for the withdraw task
 const WITHDRAW_TASK_NAME: &str = "withdraw";
@@ -94,6 +94,11 @@ pub enum WithdrawTaskError {
     /// An error interacting with the darkpool client
     #[error("darkpool client error: {0}")]
     DarkpoolClient(String),
+    /// A submitted transaction was mined but not confirmed in time
+    ///
+    /// The transaction may still be confirmed, so the task must not resubmit it
+    #[error("transaction unconfirmed: {0}")]
+    TxUnconfirmed(String),
     /// An error generating a proof of `VALID WITHDRAWAL`
     #[error("proof generation error: {0}")]
     ProofGeneration(String),
@@ -116,6 +121,15 @@ impl TaskError for WithdrawTaskError {
     }
 }
 
+impl From<DarkpoolClientError> for WithdrawTaskError {
+    fn from(e: DarkpoolClientError) -> Self {
+        match e {
+            DarkpoolClientError::TxUnconfirmed(msg) => WithdrawTaskError::TxUnconfirmed(msg),
+            e => WithdrawTaskError::DarkpoolClient(e.to_string()),
+        }
+    }
+}
+
 impl From<StateError> for WithdrawTaskError {
     fn from(e: StateError) -> Self {
         WithdrawTaskError::State(e.to_string())
@@ -292,17 +306,11 @@ impl WithdrawTask {
 
         let commitment = proof_bundle.statement.new_balance_commitment;
         let auth = self.build_withdrawal_auth();
-        let receipt = self
-            .darkpool_client()
-            .withdraw(auth, proof_bundle)
-            .await
-            .map_err(|e| WithdrawTaskError::DarkpoolClient(e.to_string()))?;
+        let receipt = self.darkpool_client().withdraw(auth, proof_bundle).await?;
 
         // Parse a Merkle opening for the new balance from the receipt
-        let opening = self
-            .darkpool_client()
-            .find_merkle_authentication_path_with_tx(commitment, &receipt)
-            .map_err(|e| WithdrawTaskError::DarkpoolClient(e.to_string()))?;
+        let opening =
+            self.darkpool_client().find_merkle_authentication_path_with_tx(commitment, &receipt)?;
 
         // Store the Merkle opening in state
         let waiter =
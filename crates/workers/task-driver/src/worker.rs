@@ -14,7 +14,7 @@ use job_types::{
 use state::State;
 use system_bus::SystemBus;
 use types_core::HmacKey;
-use types_runtime::Worker;
+use types_runtime::{ShutdownProgress, Worker};
 use url::Url;
 use util::DefaultOption;
 
@@ -55,6 +55,9 @@ pub struct TaskDriverConfig {
     pub indexer_url: Url,
     /// The HMAC key for authenticating requests to the indexer API
     pub indexer_hmac_key: HmacKey,
+    /// The relayer's shutdown progress, used to hold new tasks while the node
+    /// drains
+    pub shutdown: ShutdownProgress,
 }
 
 impl TaskDriverConfig {
@@ -86,6 +89,7 @@ impl TaskDriverConfig {
             state,
             indexer_url,
             indexer_hmac_key,
+            shutdown: ShutdownProgress::new(),
         }
     }
 }